@@ -1,3 +1,8 @@
+pub mod diagnostics;
+pub mod options;
 pub mod orchestrator;
+pub mod request;
 
+pub use options::DownloadOptions;
 pub use orchestrator::Orchestrator;
+pub use request::DownloadRequest;
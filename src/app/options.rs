@@ -0,0 +1,510 @@
+use crate::cli::{parse_page_spec, Cli};
+use crate::core::clip::ClipRange;
+use crate::core::danmaku::{DanmakuFormat, DanmakuLimit};
+use crate::core::muxer::{EmbedOptions, MergeOutputFormat, ReencodeOptions};
+use crate::error::{DownloaderError, Result};
+use crate::platform::bilibili::{page_from_url, ApiMode};
+use crate::types::{FormatSort, Page, VideoInfo};
+use crate::utils::file::{CollisionStrategy, OrganizeBy, SidecarPolicy};
+use crate::utils::http::IpVersion;
+use crate::utils::signal::InterruptPolicy;
+use crate::utils::throttle;
+use std::path::PathBuf;
+
+/// Everything the orchestrator needs for a download, independent of CLI parsing
+///
+/// Library users and tests build this directly:
+/// `DownloadOptions { url: Some(url), ..Default::default() }`
+#[derive(Debug, Clone)]
+pub struct DownloadOptions {
+    /// Video URL to download
+    pub url: Option<String>,
+    /// Quality priority (e.g. ["1080P", "720P"])
+    pub quality_priority: Vec<String>,
+    /// Codec priority (e.g. ["avc", "hevc"])
+    pub codec_priority: Vec<String>,
+    /// Stream sort expression, overrides the quality/codec priorities
+    pub format_sort: Option<FormatSort>,
+    /// Only select video streams with this frame rate
+    pub fps: Option<u32>,
+    /// Output file path or template
+    pub output: Option<String>,
+    /// File name template for each page of a multi-page video
+    pub page_template: Option<String>,
+    /// Added to page numbers in file names
+    pub page_number_offset: usize,
+    /// What to do when the output file already exists
+    pub on_collision: CollisionStrategy,
+    /// What to do with existing danmaku/subtitle sidecar files
+    pub overwrite_policy: SidecarPolicy,
+    /// Subdirectory inserted into the output path (per uploader or upload date)
+    pub organize_by: OrganizeBy,
+    /// Container of the muxed file (None keeps the output extension, `.mp4` by default)
+    pub merge_output_format: Option<MergeOutputFormat>,
+    pub cookie: Option<String>,
+    pub access_token: Option<String>,
+    /// Persist server-set cookies to this file
+    pub cookie_jar: Option<PathBuf>,
+    /// Config file path (credentials are read from the auth.toml next to it)
+    pub config_file: Option<PathBuf>,
+    /// Audio language for multi-language bangumi
+    pub audio_lang: Option<String>,
+    /// Page numbers to download (None means all)
+    pub pages: Option<Vec<usize>>,
+    pub this_episode_only: bool,
+    pub include_extras: bool,
+    /// Only keep batch videos published on or after this date (YYYY-MM-DD)
+    pub since: Option<String>,
+    /// Only keep batch videos published on or before this date (YYYY-MM-DD)
+    pub until: Option<String>,
+    /// Skip batch videos whose estimated size is below this many bytes
+    pub min_filesize: Option<u64>,
+    /// Skip batch videos whose estimated size is above this many bytes
+    pub max_filesize: Option<u64>,
+    /// Always ask before a batch download (large batches always ask unless `assume_yes`)
+    pub confirm: bool,
+    /// Never ask before a batch download
+    pub assume_yes: bool,
+    pub threads: usize,
+    /// Download threads for the video stream (`threads` when unset)
+    pub video_threads: Option<usize>,
+    /// Download threads for the audio stream (`threads` when unset)
+    pub audio_threads: Option<usize>,
+    /// Keep going with the remaining URLs of a batch file, or videos of a collection, when one fails
+    pub continue_on_error: bool,
+    /// Write buffer size in bytes when streaming downloads to disk
+    pub buffer_size: usize,
+    /// IP protocol used to connect to servers (`--force-ipv4` / `--force-ipv6`)
+    pub ip_version: IpVersion,
+    /// Pages of a multi-page video downloaded at the same time
+    pub concurrent_pages: usize,
+    /// Delay between API requests in batch-fetch loops
+    pub api_delay_ms: u64,
+    /// Reuse parsed video info cached on disk for this many seconds (None disables the cache)
+    pub cache_ttl: Option<u64>,
+    /// Re-check the login every this many videos of a batch (None disables the check)
+    pub cookie_check_interval: Option<usize>,
+    /// Stop the batch instead of only warning when the login is lost
+    pub abort_on_login_lost: bool,
+    pub skip_subtitle: bool,
+    /// Subtitle languages to download (None means all)
+    pub subtitle_langs: Option<Vec<String>>,
+    pub skip_cover: bool,
+    pub write_thumbnail: bool,
+    pub thumbnail_size: Option<String>,
+    pub thumbnail_format: Option<String>,
+    /// Write the video's tags next to the output file
+    pub write_tags: bool,
+    /// What gets embedded into the muxed file or written next to it
+    pub embed: EmbedOptions,
+    /// Generate a thumbnail grid image next to the muxed file
+    pub gen_contact_sheet: bool,
+    pub contact_sheet_cols: u32,
+    pub contact_sheet_rows: u32,
+    pub skip_mux: bool,
+    /// Join the downloaded pages of a multi-page video into one file
+    pub concat_pages: bool,
+    /// With `skip_mux`, remux each stream into its own playable file
+    pub remux_each: bool,
+    /// Only download and keep this time range of each page
+    pub clip: Option<ClipRange>,
+    /// Keep the temp directory after download (always kept when muxing fails)
+    pub keep_temp: bool,
+    /// Keep or delete unfinished downloads on Ctrl-C/SIGTERM
+    pub on_interrupt: InterruptPolicy,
+    /// Restart unfinished `.part` downloads from zero instead of resuming them
+    pub force_overwrite_partial: bool,
+    /// Re-encode settings for the muxer (None means stream copy)
+    pub reencode: Option<ReencodeOptions>,
+    pub verify: bool,
+    pub checksum: bool,
+    pub interactive: bool,
+    pub info_only: bool,
+    /// Show each page's available streams as a table (implies info only)
+    pub list_qualities_table: bool,
+    /// List streams (and subtitles) of every batch video instead of only the first
+    pub info_all: bool,
+    /// Show the subtitle languages of the selected pages (implies info only)
+    pub list_subtitles: bool,
+    /// List planned output paths with existing/duplicate markers (implies info only)
+    pub preview_paths: bool,
+    pub list_audio_languages: bool,
+    pub simulate_quality: bool,
+    /// Select streams and report what would be downloaded, without downloading (`--simulate`)
+    pub simulate: bool,
+    pub ffmpeg_path: Option<PathBuf>,
+    pub use_mp4box: bool,
+    /// Explicit API mode (None means Web with automatic International fallback)
+    pub api_mode: Option<ApiMode>,
+    /// Put overseas-friendly CDN hosts first in each stream's URL list
+    pub prefer_intl_cdn: bool,
+    pub download_danmaku: bool,
+    pub danmaku_format: DanmakuFormat,
+    /// Also merge the danmaku of all selected pages onto one concatenated timeline
+    pub danmaku_merge: bool,
+    /// Also keep the raw danmaku XML next to the output (`--keep-raw-danmaku`)
+    pub keep_raw_danmaku: bool,
+    /// Density caps applied when converting danmaku to ASS
+    pub danmaku_limit: DanmakuLimit,
+    /// Font name or file for ASS danmaku and embedded subtitles (`--font`)
+    pub font: Option<String>,
+    /// Write the video's comments next to the output file
+    pub download_comments: bool,
+    /// Include every reply of each comment
+    pub comment_replies: bool,
+    /// Maximum number of top-level comments (None means all)
+    pub max_comments: Option<usize>,
+    pub use_aria2c: bool,
+    pub aria2c_path: Option<String>,
+    pub aria2c_args: Option<String>,
+    /// aria2c split/connection overrides (None uses config or defaults)
+    pub aria2c_split: Option<u32>,
+    pub aria2c_max_connection_per_server: Option<u32>,
+    pub aria2c_min_split_size: Option<u64>,
+    /// aria2c JSON-RPC endpoint (implies aria2c mode)
+    pub aria2c_rpc: Option<String>,
+    pub aria2c_rpc_secret: Option<String>,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            url: None,
+            quality_priority: vec!["1080P".to_string(), "720P".to_string(), "480P".to_string()],
+            codec_priority: vec!["avc".to_string(), "hevc".to_string(), "av1".to_string()],
+            format_sort: None,
+            fps: None,
+            output: None,
+            page_template: None,
+            page_number_offset: 0,
+            on_collision: CollisionStrategy::default(),
+            overwrite_policy: SidecarPolicy::default(),
+            organize_by: OrganizeBy::default(),
+            merge_output_format: None,
+            cookie: None,
+            access_token: None,
+            cookie_jar: None,
+            config_file: None,
+            audio_lang: None,
+            pages: None,
+            this_episode_only: false,
+            include_extras: false,
+            since: None,
+            until: None,
+            min_filesize: None,
+            max_filesize: None,
+            confirm: false,
+            assume_yes: false,
+            threads: 4,
+            video_threads: None,
+            audio_threads: None,
+            continue_on_error: false,
+            buffer_size: crate::core::downloader::DEFAULT_BUFFER_SIZE,
+            ip_version: IpVersion::default(),
+            concurrent_pages: 1,
+            api_delay_ms: throttle::DEFAULT_DELAY_MS,
+            cache_ttl: None,
+            cookie_check_interval: None,
+            abort_on_login_lost: false,
+            skip_subtitle: false,
+            subtitle_langs: None,
+            skip_cover: false,
+            write_thumbnail: false,
+            thumbnail_size: None,
+            thumbnail_format: None,
+            write_tags: false,
+            embed: EmbedOptions::default(),
+            gen_contact_sheet: false,
+            contact_sheet_cols: 4,
+            contact_sheet_rows: 4,
+            skip_mux: false,
+            concat_pages: false,
+            clip: None,
+            remux_each: false,
+            keep_temp: false,
+            on_interrupt: InterruptPolicy::default(),
+            force_overwrite_partial: false,
+            reencode: None,
+            verify: false,
+            checksum: false,
+            interactive: false,
+            info_only: false,
+            list_qualities_table: false,
+            info_all: false,
+            list_subtitles: false,
+            preview_paths: false,
+            list_audio_languages: false,
+            simulate_quality: false,
+            simulate: false,
+            ffmpeg_path: None,
+            use_mp4box: false,
+            api_mode: None,
+            prefer_intl_cdn: false,
+            download_danmaku: false,
+            danmaku_format: DanmakuFormat::Ass,
+            danmaku_merge: false,
+            keep_raw_danmaku: false,
+            danmaku_limit: DanmakuLimit::default(),
+            font: None,
+            download_comments: false,
+            comment_replies: false,
+            max_comments: None,
+            use_aria2c: false,
+            aria2c_path: None,
+            aria2c_args: None,
+            aria2c_split: None,
+            aria2c_max_connection_per_server: None,
+            aria2c_min_split_size: None,
+            aria2c_rpc: None,
+            aria2c_rpc_secret: None,
+        }
+    }
+}
+
+impl DownloadOptions {
+    /// API mode used for requests
+    pub fn get_api_mode(&self) -> ApiMode {
+        self.api_mode.unwrap_or(ApiMode::Web)
+    }
+
+    /// Download threads used for the video stream
+    pub fn video_thread_count(&self) -> usize {
+        self.video_threads.unwrap_or(self.threads)
+    }
+
+    /// Download threads used for the audio stream
+    pub fn audio_thread_count(&self) -> usize {
+        self.audio_threads.unwrap_or(self.threads)
+    }
+
+    /// Copy of these options with per-URL overrides from a batch file applied
+    ///
+    /// Keys: quality, codec, output, pages, fps, audio-lang, page-template,
+    /// skip-subtitle, skip-cover, danmaku (`-` and `_` are interchangeable)
+    pub fn with_overrides(&self, overrides: &[(String, String)]) -> Result<Self> {
+        let mut options = self.clone();
+        let split = |value: &str| value.split(',').map(|s| s.trim().to_string()).collect();
+
+        for (key, value) in overrides {
+            let invalid = || {
+                DownloaderError::Config(format!("Invalid value for batch option {}: {}", key, value))
+            };
+            let flag = || match value.to_ascii_lowercase().as_str() {
+                "true" | "yes" | "1" => Ok(true),
+                "false" | "no" | "0" => Ok(false),
+                _ => Err(invalid()),
+            };
+
+            match key.replace('_', "-").to_ascii_lowercase().as_str() {
+                "quality" => {
+                    options.quality_priority = split(value);
+                    // 排序表达式优先于清晰度/编码优先级，单行指定时需要清除
+                    options.format_sort = None;
+                }
+                "codec" => {
+                    options.codec_priority = split(value);
+                    options.format_sort = None;
+                }
+                "output" => options.output = Some(value.clone()),
+                "pages" => {
+                    // 无法解析的分P选择不能当作全部分P
+                    options.pages = match parse_page_spec(value) {
+                        None if !value.trim().eq_ignore_ascii_case("all") => return Err(invalid()),
+                        pages => pages,
+                    }
+                }
+                "fps" => options.fps = Some(value.parse().map_err(|_| invalid())?),
+                "audio-lang" => options.audio_lang = Some(value.clone()),
+                "page-template" => options.page_template = Some(value.clone()),
+                "page-number-offset" => {
+                    options.page_number_offset = value.parse().map_err(|_| invalid())?
+                }
+                "skip-subtitle" => options.skip_subtitle = flag()?,
+                "skip-cover" => options.skip_cover = flag()?,
+                "embed-metadata" => {
+                    options.embed = if flag()? {
+                        EmbedOptions::all()
+                    } else {
+                        EmbedOptions::default()
+                    }
+                }
+                "danmaku" => options.download_danmaku = flag()?,
+                _ => {
+                    return Err(DownloaderError::Config(format!(
+                        "Unknown batch option: {}",
+                        key
+                    )))
+                }
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// Whether `video_info` only carries a placeholder P1, so the full detail has to be
+    /// fetched before calling [`select_pages`](Self::select_pages)
+    ///
+    /// 收藏夹列表不含分P标题和时长，即使只下载P1也要先获取详情，否则文件名和时长都不对
+    pub fn needs_full_pages(&self, video_info: &VideoInfo) -> bool {
+        video_info.pages_incomplete()
+    }
+
+    /// Pages of `video_info` to download, from `--this-episode-only`, `pages` or the URL's `?p=`
+    ///
+    /// Errors instead of returning an empty list, so a video without downloadable pages
+    /// never ends in a "successful" run that produced nothing
+    pub fn select_pages(&self, video_info: &VideoInfo) -> Result<Vec<Page>> {
+        if video_info.pages.is_empty() {
+            return Err(DownloaderError::NoPages(format!(
+                "{} ({}) has no pages",
+                video_info.title, video_info.id
+            )));
+        }
+
+        if self.this_episode_only {
+            // 仅下载ep链接对应的那一集
+            if let Some(ep_id) = video_info.target_ep_id() {
+                let page = video_info.page_for_ep(ep_id).ok_or_else(|| {
+                    DownloaderError::Parse(format!("Episode ep{} not found in season", ep_id))
+                })?;
+                return Ok(vec![page.clone()]);
+            }
+            tracing::warn!("--this-episode-only only applies to bangumi ep URLs, ignoring");
+        }
+
+        if let Some(ref page_numbers) = self.pages {
+            // Filter pages by user selection
+            let mut selected = Vec::new();
+            for &num in page_numbers {
+                if let Some(page) = video_info.pages.iter().find(|p| p.number == num) {
+                    selected.push(page.clone());
+                } else {
+                    tracing::warn!("Page {} not found, skipping", num);
+                }
+            }
+
+            if selected.is_empty() {
+                return Err(DownloaderError::NoPages(format!(
+                    "none of the selected pages exist in {} (it has {} page(s))",
+                    video_info.id,
+                    video_info.pages.len()
+                )));
+            }
+
+            Ok(selected)
+        } else if let Some(page) = self
+            .url
+            .as_deref()
+            .and_then(page_from_url)
+            .and_then(|number| video_info.pages.iter().find(|p| p.number == number))
+        {
+            // 链接带 ?p= 且未指定 --pages 时只下载该分P
+            Ok(vec![page.clone()])
+        } else {
+            // Download all pages
+            Ok(video_info.pages.clone())
+        }
+    }
+
+    /// Whether an estimated size is within `min_filesize`/`max_filesize`
+    pub fn filesize_in_range(&self, size: u64) -> bool {
+        self.min_filesize.is_none_or(|min| size >= min)
+            && self.max_filesize.is_none_or(|max| size <= max)
+    }
+}
+
+impl From<&Cli> for DownloadOptions {
+    fn from(cli: &Cli) -> Self {
+        Self {
+            url: cli.url.clone(),
+            quality_priority: cli.parse_quality_priority(),
+            codec_priority: cli.parse_codec_priority(),
+            format_sort: cli.format_sort.clone(),
+            fps: cli.fps,
+            output: cli.output.clone(),
+            page_template: cli.page_template.clone(),
+            page_number_offset: cli.page_number_offset,
+            on_collision: cli.get_collision_strategy(),
+            overwrite_policy: cli.get_sidecar_policy(),
+            organize_by: cli.get_organize_by(),
+            merge_output_format: cli.get_merge_output_format(),
+            cookie: cli.cookie.clone(),
+            access_token: cli.access_token.clone(),
+            cookie_jar: cli.cookie_jar.clone(),
+            config_file: cli.config_file.clone(),
+            audio_lang: cli.audio_lang.clone(),
+            pages: cli.parse_pages(),
+            this_episode_only: cli.this_episode_only,
+            include_extras: cli.include_extras,
+            since: cli.since.clone(),
+            until: cli.until.clone(),
+            min_filesize: cli.min_filesize,
+            max_filesize: cli.max_filesize,
+            confirm: cli.confirm,
+            assume_yes: cli.yes,
+            threads: cli.threads,
+            video_threads: cli.video_threads,
+            audio_threads: cli.audio_threads,
+            continue_on_error: cli.continue_on_error || cli.retry_failed.is_some(),
+            buffer_size: cli.buffer_size as usize,
+            ip_version: cli.get_ip_version(),
+            concurrent_pages: cli.concurrent_pages as usize,
+            api_delay_ms: cli.api_delay_ms,
+            cache_ttl: cli.cache_ttl.filter(|ttl| *ttl > 0),
+            cookie_check_interval: cli.cookie_check_interval.map(|n| n as usize),
+            abort_on_login_lost: cli.on_login_lost.eq_ignore_ascii_case("abort"),
+            skip_subtitle: cli.skip_subtitle,
+            subtitle_langs: cli.parse_subtitle_langs(),
+            skip_cover: cli.skip_cover,
+            write_thumbnail: cli.write_thumbnail,
+            thumbnail_size: cli.thumbnail_size.clone(),
+            thumbnail_format: cli.thumbnail_format.clone(),
+            write_tags: cli.write_tags,
+            embed: cli.get_embed_options(),
+            gen_contact_sheet: cli.gen_contact_sheet,
+            contact_sheet_cols: cli.contact_sheet_cols,
+            contact_sheet_rows: cli.contact_sheet_rows,
+            skip_mux: cli.skip_mux,
+            concat_pages: cli.concat_pages,
+            clip: cli.clip,
+            remux_each: cli.remux_each,
+            keep_temp: cli.keep_temp,
+            on_interrupt: cli.get_interrupt_policy(),
+            force_overwrite_partial: cli.force_overwrite_partial,
+            reencode: cli.get_reencode_options(),
+            verify: cli.verify,
+            checksum: cli.checksum,
+            interactive: cli.interactive,
+            info_only: cli.info_only,
+            // --info-only --info-all 输出批量中每个视频的完整流信息
+            list_qualities_table: cli.list_qualities_table || (cli.info_only && cli.info_all),
+            info_all: cli.info_all,
+            list_subtitles: cli.list_subtitles,
+            preview_paths: cli.preview_paths,
+            list_audio_languages: cli.list_audio_languages,
+            simulate_quality: cli.simulate_quality,
+            simulate: cli.simulate,
+            ffmpeg_path: cli.ffmpeg_path.clone(),
+            use_mp4box: cli.use_mp4box,
+            api_mode: cli.has_explicit_api_mode().then(|| cli.get_api_mode()),
+            prefer_intl_cdn: cli.prefer_intl_cdn || cli.use_intl_api,
+            download_danmaku: cli.download_danmaku,
+            danmaku_format: cli.get_danmaku_format(),
+            danmaku_merge: cli.danmaku_merge,
+            keep_raw_danmaku: cli.keep_raw_danmaku,
+            danmaku_limit: cli.get_danmaku_limit(),
+            font: cli.font.clone(),
+            download_comments: cli.download_comments,
+            comment_replies: cli.comment_replies,
+            max_comments: cli.max_comments,
+            use_aria2c: cli.use_aria2c,
+            aria2c_path: cli.aria2c_path.clone(),
+            aria2c_args: cli.aria2c_args.clone(),
+            aria2c_split: cli.aria2c_split,
+            aria2c_max_connection_per_server: cli.aria2c_max_connection_per_server,
+            aria2c_min_split_size: cli.aria2c_min_split_size,
+            aria2c_rpc: cli.aria2c_rpc.clone(),
+            aria2c_rpc_secret: cli.aria2c_rpc_secret.clone(),
+        }
+    }
+}
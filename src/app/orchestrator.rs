@@ -1,21 +1,49 @@
-use crate::cli::Cli;
+use crate::app::DownloadOptions;
+use crate::core::aria2_rpc::Aria2Rpc;
+use crate::core::clip::ClipRange;
 use crate::core::danmaku;
-use crate::core::downloader::Downloader;
-use crate::core::muxer::Muxer;
-use crate::core::progress::ProgressTracker;
+use crate::core::downloader::{self, Aria2cOptions, Downloader};
+use crate::core::muxer::{self, HdrType, Muxer};
+use crate::core::nfo;
+use crate::core::progress::{ProgressTracker, RunSummary};
 use crate::core::subtitle;
 use crate::error::{DownloaderError, Result};
-use crate::platform::bilibili::parser;
-use crate::platform::bilibili::selector::select_best_streams;
-use crate::platform::bilibili::BilibiliPlatform;
+use crate::platform::bilibili::cdn::{CdnSelector, DefaultCdn, OverseasCdn};
+use crate::platform::bilibili::{image, parser};
+use crate::platform::bilibili::{BilibiliPlatform, ParseOptions};
+use crate::platform::selector::{
+    audio_languages, describe_stream, estimated_size, stream_fps, stream_table, StreamSelector,
+};
 use crate::platform::Platform;
-use crate::types::{Auth, Page, Stream, StreamPreferences, StreamType, VideoInfo};
+use crate::types::{Auth, Page, Stream, StreamPreferences, StreamType, Subtitle, VideoInfo};
+use crate::utils::cache::{self, ParseCache};
+use crate::utils::checksum;
 use crate::utils::config::Config;
-use crate::utils::file;
+use crate::utils::cookie::CookieJar;
+use crate::utils::file::{self, BatchEntry, FailedDownload, SidecarPolicy};
+use crate::utils::font;
 use crate::utils::http::HttpClient;
-use dialoguer::Select;
+use crate::utils::i18n::{t, tf, Msg};
+use crate::utils::signal::InterruptPolicy;
+use crate::utils::throttle::{self, ApiThrottle};
+use dialoguer::{Confirm, Select};
+use futures::StreamExt;
+use indicatif::ProgressBar;
+use std::borrow::Cow;
+use std::fmt::Display;
+use std::io::IsTerminal;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+
+/// 由视频与分P计算输出路径的回调，见 [`Orchestrator::with_output_path_hook`]
+pub type OutputPathHook = dyn Fn(&VideoInfo, &Page) -> PathBuf + Send + Sync;
+
+/// 可通过配置 `[platforms] enabled` 启用的平台名称
+const KNOWN_PLATFORMS: &[&str] = &["bilibili"];
+
+/// 批量下载超过该数量的视频时，开始前需要确认
+const BATCH_CONFIRM_THRESHOLD: usize = 50;
 
 pub struct Orchestrator {
     platforms: Vec<Box<dyn Platform>>,
@@ -25,24 +53,54 @@ pub struct Orchestrator {
     config: Config,
     http_client: Arc<HttpClient>,
     override_auth: Option<Auth>,
+    /// 自定义输出路径（见 `with_output_path_hook`）
+    output_path_hook: Option<Arc<OutputPathHook>>,
+    /// 正在使用的临时目录，中断时按 `--on-interrupt` 保留或清理
+    temp_dirs: std::sync::Mutex<Vec<PathBuf>>,
+    /// 本次运行中失败的URL和分P（`--failures-file`）
+    failures: std::sync::Mutex<Vec<FailedDownload>>,
+    /// ASS 弹幕样式使用的字体名称
+    danmaku_font: String,
+    /// 嵌入字幕时附加到 MKV 的字体文件
+    subtitle_font: Option<PathBuf>,
 }
 
 impl Orchestrator {
-    pub fn new(config: Config, cli: &Cli) -> Result<Self> {
-        let http_client = Arc::new(HttpClient::new()?);
+    pub fn new(config: Config, options: &DownloadOptions) -> Result<Self> {
+        // 下载器与平台共享同一个HTTP客户端及Cookie存储
+        let cookie_jar = Arc::new(match options.cookie_jar {
+            Some(ref path) => CookieJar::load(path)?,
+            None => CookieJar::new(),
+        });
+        let api_throttle = Arc::new(ApiThrottle::new(
+            throttle::DEFAULT_MAX_CONCURRENT,
+            Duration::from_millis(options.api_delay_ms),
+        ));
+        let http_client =
+            Arc::new(
+                HttpClient::with_cookie_jar_and_ip_version(cookie_jar, options.ip_version)?
+                    .with_api_throttle(api_throttle),
+            );
         
         // Configure downloader with aria2c settings
-        let mut downloader = Downloader::new(http_client.clone(), cli.threads);
+        let mut downloader = Downloader::new(http_client.clone(), options.threads)
+            .with_buffer_size(options.buffer_size)
+            .with_force_overwrite_partial(options.force_overwrite_partial);
         
         // Determine download method from CLI or config
-        let use_aria2c = cli.use_aria2c 
+        let aria2c_rpc = options
+            .aria2c_rpc
+            .clone()
+            .or_else(|| config.aria2c.as_ref().and_then(|a| a.rpc_url.clone()));
+        let use_aria2c = options.use_aria2c
+            || aria2c_rpc.is_some()
             || config.aria2c.as_ref().map(|a| a.enabled).unwrap_or(false);
         
         if use_aria2c {
             downloader = downloader.with_method(crate::core::downloader::DownloadMethod::Aria2c);
             
             // Set aria2c path from CLI or config
-            if let Some(ref path) = cli.aria2c_path {
+            if let Some(ref path) = options.aria2c_path {
                 downloader = downloader.with_aria2c_path(path.clone());
             } else if let Some(ref aria2c_config) = config.aria2c {
                 if let Some(ref path) = aria2c_config.path {
@@ -51,7 +109,7 @@ impl Orchestrator {
             }
             
             // Set custom aria2c args from CLI or config
-            if let Some(ref args) = cli.aria2c_args {
+            if let Some(ref args) = options.aria2c_args {
                 downloader = downloader.with_aria2c_args(args.clone());
             } else if let Some(ref aria2c_config) = config.aria2c {
                 if let Some(ref args) = aria2c_config.args {
@@ -59,6 +117,38 @@ impl Orchestrator {
                 }
             }
             
+            // Split/connection options: CLI > config > defaults
+            let aria2c_config = config.aria2c.as_ref();
+            let defaults = Aria2cOptions::default();
+            let config_min_split_size = match aria2c_config.and_then(|a| a.min_split_size.as_deref()) {
+                Some(size) => Some(file::parse_size(size).ok_or_else(|| {
+                    DownloaderError::Config(format!("Invalid aria2c min_split_size: {}", size))
+                })?),
+                None => None,
+            };
+            downloader = downloader.with_aria2c_options(Aria2cOptions {
+                split: options
+                    .aria2c_split
+                    .or_else(|| aria2c_config.and_then(|a| a.split))
+                    .unwrap_or(defaults.split),
+                max_connection_per_server: options
+                    .aria2c_max_connection_per_server
+                    .or_else(|| aria2c_config.and_then(|a| a.max_connection_per_server))
+                    .unwrap_or(defaults.max_connection_per_server),
+                min_split_size: options
+                    .aria2c_min_split_size
+                    .or(config_min_split_size)
+                    .unwrap_or(defaults.min_split_size),
+            });
+
+            if let Some(ref endpoint) = aria2c_rpc {
+                let secret = options
+                    .aria2c_rpc_secret
+                    .clone()
+                    .or_else(|| config.aria2c.as_ref().and_then(|a| a.rpc_secret.clone()));
+                downloader = downloader.with_aria2c_rpc(Some(Arc::new(Aria2Rpc::new(endpoint, secret))));
+            }
+
             tracing::info!("aria2c download mode enabled");
         }
         
@@ -66,18 +156,53 @@ impl Orchestrator {
         
         let muxer =
             Arc::new(Muxer::new_with_options(
-                cli.ffmpeg_path.clone().or_else(|| {
+                options.ffmpeg_path.clone().or_else(|| {
                     config.paths.as_ref().and_then(|p| p.ffmpeg.clone())
                 }),
-                cli.use_mp4box,
-            )?);
+                options.use_mp4box,
+            )?
+            .with_reencode(options.reencode.clone()));
+        // 下载前确认 FFmpeg 包含重新编码所需的编码器，避免下载完成后才混流失败
+        if let Some(ref reencode) = options.reencode {
+            if !options.skip_mux {
+                muxer.check_reencode(reencode)?;
+            }
+        }
         let progress = Arc::new(ProgressTracker::new());
 
         // 根据CLI参数选择API模式
-        let api_mode = cli.get_api_mode();
-        let platforms: Vec<Box<dyn Platform>> = vec![
-            Box::new(BilibiliPlatform::with_api_mode(api_mode)?)
-        ];
+        let api_mode = options.get_api_mode();
+        let cdn_selector: Arc<dyn CdnSelector> = if options.prefer_intl_cdn {
+            Arc::new(OverseasCdn)
+        } else {
+            Arc::new(DefaultCdn)
+        };
+        let mut platforms: Vec<Box<dyn Platform>> = Vec::new();
+        if config.is_platform_enabled("bilibili") {
+            platforms.push(Box::new(
+                BilibiliPlatform::with_client(api_mode, http_client.clone())?
+                    // 未指定API模式时，区域限制自动回退到国际版API
+                    .with_intl_fallback(options.api_mode.is_none())
+                    .with_cdn_selector(cdn_selector)
+                    .with_parse_options(ParseOptions {
+                        include_extras: options.include_extras,
+                        fetch_tags: options.write_tags || options.embed.tags || options.embed.nfo,
+                        since: options.since.clone(),
+                        until: options.until.clone(),
+                    }),
+            ));
+        }
+
+        // 配置中出现未知的平台名称时给出警告
+        if let Some(enabled) = config.platforms.as_ref().and_then(|p| p.enabled.as_ref()) {
+            for name in enabled {
+                if !KNOWN_PLATFORMS.iter().any(|known| known.eq_ignore_ascii_case(name)) {
+                    tracing::warn!("Unknown platform '{}' in [platforms] enabled, ignoring", name);
+                }
+            }
+        }
+
+        let (danmaku_font, subtitle_font) = resolve_fonts(&config, options);
 
         Ok(Self {
             platforms,
@@ -87,14 +212,147 @@ impl Orchestrator {
             config,
             http_client,
             override_auth: None,
+            output_path_hook: None,
+            temp_dirs: std::sync::Mutex::new(Vec::new()),
+            failures: std::sync::Mutex::new(Vec::new()),
+            danmaku_font,
+            subtitle_font,
         })
     }
 
+    /// Totals and timing of everything downloaded so far (printed at the end of a run)
+    pub fn summary(&self) -> RunSummary {
+        self.progress.summary()
+    }
+
+    /// Write cookies set by the server during the run back to `--cookie-jar`
+    pub fn flush_cookies(&self) {
+        self.http_client.cookie_jar().flush();
+    }
+
+    /// URLs and pages that failed so far, in the order they failed (for `--failures-file`)
+    pub fn failures(&self) -> Vec<FailedDownload> {
+        self.failures.lock().unwrap().clone()
+    }
+
+    fn record_failure(&self, url: &str, pages: Option<Vec<usize>>, error: &DownloaderError) {
+        self.failures.lock().unwrap().push(FailedDownload {
+            url: url.to_string(),
+            pages,
+            error: error.to_string(),
+        });
+    }
+
+    fn failure_count(&self) -> usize {
+        self.failures.lock().unwrap().len()
+    }
+
+    /// 失败记录中视频的URL：批量链接中的视频使用视频自身的链接（按视频类型生成）
+    fn failure_url(&self, video_info: &VideoInfo, options: &DownloadOptions) -> String {
+        match options.url {
+            Some(ref url) if !self.is_batch_url(url) => url.clone(),
+            _ => video_info.url(),
+        }
+    }
+
+    /// Apply the `--on-interrupt` policy to the downloads that were still in progress
+    /// when a signal arrived
+    pub async fn handle_interrupt(&self, policy: InterruptPolicy) {
+        let temp_dirs = std::mem::take(&mut *self.temp_dirs.lock().unwrap());
+        for dir in temp_dirs {
+            match policy {
+                InterruptPolicy::Resume => println!("  ℹ️  {}", tf(Msg::PartialKept, &[&dir.display()])),
+                InterruptPolicy::Clean => match file::cleanup_temp_dir(&dir).await {
+                    Ok(()) => println!("  🗑 {}", tf(Msg::PartialCleaned, &[&dir.display()])),
+                    Err(e) => tracing::warn!("Failed to remove {}: {}", dir.display(), e),
+                },
+            }
+        }
+    }
+
+    /// Compute every page's output path with `hook` instead of the templates
+    ///
+    /// The returned path is used as-is: `output`, `page_template`, `organize_by`,
+    /// `page_number_offset` and `merge_output_format` no longer apply, only `on_collision`
+    /// does. Relative paths are relative to the working directory, and missing parent
+    /// directories are created. For example, to sort pages into one folder per uploader:
+    ///
+    /// ```no_run
+    /// # use rvd::app::{DownloadOptions, Orchestrator};
+    /// # use rvd::utils::config::Config;
+    /// # fn main() -> rvd::Result<()> {
+    /// let options = DownloadOptions::default();
+    /// let orchestrator = Orchestrator::new(Config::default(), &options)?
+    ///     .with_output_path_hook(|video, page| {
+    ///         format!("library/{}/{}-P{}.mp4", video.uploader_mid, video.id, page.number).into()
+    ///     });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_output_path_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&VideoInfo, &Page) -> PathBuf + Send + Sync + 'static,
+    {
+        self.output_path_hook = Some(Arc::new(hook));
+        self
+    }
+
     /// Set authentication override (used when login is performed before download)
     pub fn set_auth(&mut self, auth: Option<Auth>) {
         self.override_auth = auth;
     }
 
+    /// Download each batch file entry in order (`--batch-file`)
+    ///
+    /// Every entry uses `options` with its own overrides applied. Stops at the first failed
+    /// URL unless `continue_on_error` is set, in which case the remaining URLs are still
+    /// processed and a partial failure is reported at the end
+    pub async fn run_urls(
+        &self,
+        entries: &[BatchEntry],
+        options: DownloadOptions,
+    ) -> Result<Vec<PathBuf>> {
+        // 先检查所有行的选项，避免下载到一半才发现写错
+        let mut jobs = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let mut entry_options = options.with_overrides(&entry.overrides)?;
+            entry_options.url = Some(entry.url.clone());
+            jobs.push(entry_options);
+        }
+
+        let mut outputs = Vec::new();
+        let mut failed = 0;
+
+        for (idx, job) in jobs.into_iter().enumerate() {
+            let url = entries[idx].url.as_str();
+            if let Ok(platform) = self.select_platform(url) {
+                let auth = self.build_auth(&job, platform.name());
+                self.check_login_periodically(idx, &options, platform, auth.as_ref())
+                    .await?;
+            }
+            println!("\n🔗 [{}/{}] {}", idx + 1, entries.len(), url);
+
+            match self.run(job).await {
+                Ok(paths) => outputs.extend(paths),
+                Err(e) if options.continue_on_error => {
+                    println!("  ✗ {}", tf(Msg::Failed, &[&e]));
+                    tracing::warn!("Failed to download {}: {}", url, e);
+                    failed += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        if failed > 0 {
+            return Err(DownloaderError::PartialFailure {
+                failed,
+                total: entries.len(),
+            });
+        }
+
+        Ok(outputs)
+    }
+
     fn select_platform(&self, url: &str) -> Result<&dyn Platform> {
         for platform in &self.platforms {
             if platform.can_handle(url) {
@@ -108,13 +366,32 @@ impl Orchestrator {
     fn is_batch_url(&self, url: &str) -> bool {
         // Check if URL is a batch download type (favorites, space, medialist, series)
         url.contains("favlist") 
-            || (url.contains("space.bilibili.com") && !url.contains("/video/"))
+            || (url.contains("space.bilibili.com") && !url.contains("/video/BV"))
             || url.contains("medialist")
             || url.contains("seriesdetail")
     }
 
-    pub async fn run(&self, cli: Cli) -> Result<()> {
-        let url = cli.url.as_ref().ok_or_else(|| {
+    /// Download everything the URL points to, returning the output files
+    ///
+    /// Failed pages, batch videos or (when nothing more precise is known) the whole URL
+    /// are recorded, see [`failures`](Self::failures)
+    pub async fn run(&self, options: DownloadOptions) -> Result<Vec<PathBuf>> {
+        let recorded = self.failure_count();
+        let url = options.url.clone();
+        let pages = options.pages.clone();
+
+        let result = self.run_url(options).await;
+        if let (Err(e), Some(url)) = (&result, url) {
+            // 失败的分P或批量中的视频已单独记录时不再记录整个URL
+            if self.failure_count() == recorded {
+                self.record_failure(&url, pages, e);
+            }
+        }
+        result
+    }
+
+    async fn run_url(&self, options: DownloadOptions) -> Result<Vec<PathBuf>> {
+        let url = options.url.as_ref().ok_or_else(|| {
             DownloaderError::Parse("No URL provided for download".to_string())
         })?;
         
@@ -125,7 +402,7 @@ impl Orchestrator {
         tracing::info!("Using platform: {}", platform.name());
 
         // Build auth
-        let auth = self.build_auth(&cli);
+        let auth = self.build_auth(&options, platform.name());
 
         // Check if this is a batch download URL (for bilibili)
         let is_batch = self.is_batch_url(url);
@@ -133,493 +410,2188 @@ impl Orchestrator {
         if is_batch {
             // Handle batch download
             if let Some(bilibili) = platform.as_any().downcast_ref::<BilibiliPlatform>() {
-                let videos = bilibili.parse_video_batch(url, auth.as_ref()).await?;
+                let videos = bilibili
+                    .parse_video_batch(url, auth.as_ref())
+                    .await
+                    .map_err(|e| e.in_stage("parse"))?;
                 
                 if videos.is_empty() {
                     return Err(DownloaderError::Parse("No videos found in batch".to_string()));
                 }
                 
-                println!("\n📦 Batch download: {} video(s) found", videos.len());
+                println!("\n📦 {}", tf(Msg::BatchFound, &[&videos.len()]));
                 
-                if cli.info_only {
-                    for (idx, video) in videos.iter().enumerate() {
+                if options.preview_paths {
+                    self.preview_output_paths(&videos, &options, platform, auth.as_ref())
+                        .await?;
+                    return Ok(Vec::new());
+                }
+
+                if options.list_qualities_table || options.list_subtitles {
+                    // 逐个获取流信息较慢，默认只列出第一个视频
+                    let count = if options.info_all { videos.len() } else { 1 };
+                    for (idx, video) in videos.iter().take(count).enumerate() {
                         println!("\n[{}/{}]", idx + 1, videos.len());
                         self.display_video_info(video);
+                        if options.list_qualities_table {
+                            self.display_stream_tables(video, &options, platform, auth.as_ref())
+                                .await?;
+                        }
+                        if options.list_subtitles {
+                            self.display_subtitle_list(video, &options, platform, auth.as_ref())
+                                .await?;
+                        }
                     }
-                    return Ok(());
+                    if count < videos.len() {
+                        println!("{}", tf(Msg::MoreVideos, &[&(videos.len() - count)]));
+                    }
+                    return Ok(Vec::new());
+                }
+
+                if options.info_only {
+                    // 批量只列出标题，完整信息使用 --info-all
+                    for (idx, video) in videos.iter().enumerate() {
+                        println!("[{}/{}] {} ({})", idx + 1, videos.len(), video.title, video.id);
+                    }
+                    return Ok(Vec::new());
                 }
                 
                 // Build stream preferences
                 let preferences = StreamPreferences {
-                    quality_priority: cli.parse_quality_priority(),
-                    codec_priority: cli.parse_codec_priority(),
+                    quality_priority: options.quality_priority.clone(),
+                    codec_priority: options.codec_priority.clone(),
+                    audio_language: options.audio_lang.clone(),
+                    format_sort: options.format_sort.clone(),
+                    fps: options.fps,
                 };
                 
-                // Download each video in the batch
+                if !self
+                    .confirm_batch(&videos, &preferences, &options, platform, auth.as_ref())
+                    .await?
+                {
+                    println!("{}", t(Msg::Cancelled));
+                    return Ok(Vec::new());
+                }
+
+                // Download each video in the batch; with `continue_on_error` a failed video
+                // doesn't stop the rest
+                let mut outputs = Vec::new();
+                let mut failed = 0;
                 for (idx, video_info) in videos.iter().enumerate() {
-                    println!("\n[{}/{}] Processing: {}", idx + 1, videos.len(), video_info.title);
-                    
-                    // Determine which pages to download
-                    let pages_to_download = self.select_pages(video_info, &cli)?;
-                    
-                    // Download each page
-                    for page in pages_to_download {
-                        self.process_page(
-                            video_info,
-                            &page,
-                            &preferences,
-                            &cli,
-                            platform,
-                            auth.as_ref(),
-                        )
+                    self.check_login_periodically(idx, &options, platform, auth.as_ref())
                         .await?;
+                    println!(
+                        "\n[{}/{}] {}",
+                        idx + 1,
+                        videos.len(),
+                        tf(Msg::Processing, &[&video_info.title])
+                    );
+
+                    let recorded = self.failure_count();
+                    match self
+                        .download_video(video_info, &preferences, &options, platform, auth.as_ref())
+                        .await
+                    {
+                        Ok(paths) => outputs.extend(paths),
+                        Err(e) => {
+                            println!("  ✗ {}", tf(Msg::Failed, &[&e]));
+                            tracing::warn!("Failed to download {}: {}", video_info.id, e);
+                            if self.failure_count() == recorded {
+                                let url = self.failure_url(video_info, &options);
+                                self.record_failure(&url, options.pages.clone(), &e);
+                            }
+                            if !options.continue_on_error {
+                                self.progress.finish_all();
+                                return Err(e);
+                            }
+                            failed += 1;
+                        }
                     }
                 }
-                
+
                 self.progress.finish_all();
-                println!("\n✓ All {} video(s) downloaded successfully!", videos.len());
+
+                if failed > 0 {
+                    return Err(DownloaderError::PartialFailure {
+                        failed,
+                        total: videos.len(),
+                    });
+                }
+                println!("\n✓ {}", tf(Msg::BatchDone, &[&videos.len()]));
                 
-                return Ok(());
+                return Ok(outputs);
             }
         }
 
         // Single video download (original logic)
-        let video_info = platform.parse_video(url, auth.as_ref()).await?;
+        let video_info = self
+            .parse_video_cached(url, &options, platform, auth.as_ref())
+            .await?;
 
         // Display video info
         self.display_video_info(&video_info);
 
-        if cli.info_only {
-            return Ok(());
+        if options.preview_paths {
+            self.preview_output_paths(
+                std::slice::from_ref(&video_info),
+                &options,
+                platform,
+                auth.as_ref(),
+            )
+            .await?;
+            return Ok(Vec::new());
+        }
+
+        if options.list_qualities_table || options.list_subtitles {
+            if options.list_qualities_table {
+                self.display_stream_tables(&video_info, &options, platform, auth.as_ref())
+                    .await?;
+            }
+            if options.list_subtitles {
+                self.display_subtitle_list(&video_info, &options, platform, auth.as_ref())
+                    .await?;
+            }
+            return Ok(Vec::new());
+        }
+
+        if options.info_only {
+            return Ok(Vec::new());
         }
 
         // Determine which pages to download
-        let pages_to_download = self.select_pages(&video_info, &cli)?;
+        let pages_to_download = options.select_pages(&video_info)?;
 
         tracing::info!("Will download {} page(s)", pages_to_download.len());
 
         // Build stream preferences
         let preferences = StreamPreferences {
-            quality_priority: cli.parse_quality_priority(),
-            codec_priority: cli.parse_codec_priority(),
+            quality_priority: options.quality_priority.clone(),
+            codec_priority: options.codec_priority.clone(),
+            audio_language: options.audio_lang.clone(),
+            format_sort: options.format_sort.clone(),
+            fps: options.fps,
         };
 
         // Download each page
-        for page in pages_to_download {
-            self.process_page(
+        let outputs = self
+            .process_pages(
                 &video_info,
-                &page,
+                &pages_to_download,
                 &preferences,
-                &cli,
+                &options,
                 platform,
                 auth.as_ref(),
             )
             .await?;
-        }
 
         self.progress.finish_all();
-        println!("\n✓ All downloads completed successfully!");
+        println!("\n✓ {}", t(Msg::AllDone));
 
-        Ok(())
+        Ok(outputs)
     }
 
-    fn build_auth(&self, cli: &Cli) -> Option<Auth> {
-        // Priority: override_auth (from login) > CLI parameters > auth.toml > config.toml
-        
-        // If we have override auth from login, use it directly
-        if self.override_auth.is_some() {
-            return self.override_auth.clone();
+    /// 收藏夹中的多P视频只带有占位的P1，处理前获取完整详情
+    async fn complete_video_info<'a>(
+        &self,
+        video_info: &'a VideoInfo,
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Result<Cow<'a, VideoInfo>> {
+        if !options.needs_full_pages(video_info) {
+            return Ok(Cow::Borrowed(video_info));
         }
-        
-        // Try to load from auth.toml if config file is specified
-        let auth_from_file = if let Some(ref config_path) = cli.config_file {
-            use crate::auth::storage::CredentialStorage;
-            CredentialStorage::load_from_config(config_path)
-                .ok()
-                .flatten()
-                .map(|creds| CredentialStorage::to_auth(&creds))
-        } else {
-            None
-        };
-
-        // Build final auth with priority
-        let cookie = cli
-            .cookie
-            .clone()
-            .or_else(|| auth_from_file.as_ref().and_then(|a| a.cookie.clone()))
-            .or_else(|| self.config.auth.as_ref().and_then(|a| a.cookie.clone()));
 
-        let access_token = cli
-            .access_token
-            .clone()
-            .or_else(|| auth_from_file.as_ref().and_then(|a| a.access_token.clone()))
-            .or_else(|| self.config.auth.as_ref().and_then(|a| a.access_token.clone()));
+        tracing::debug!("Fetching all pages of {}", video_info.id);
+        let url = format!("https://www.bilibili.com/video/{}", video_info.id);
+        Ok(Cow::Owned(platform.parse_video(&url, auth).await.map_err(|e| e.in_stage("parse"))?))
+    }
 
-        if cookie.is_some() || access_token.is_some() {
-            Some(Auth {
-                cookie,
-                access_token,
-            })
-        } else {
-            None
+    /// `--preview-paths`：列出每个视频各分P的输出路径，标出已存在和重名的文件，不下载
+    ///
+    /// 未获取流信息，模板中的 `<quality>`、`<codec>` 原样显示
+    async fn preview_output_paths(
+        &self,
+        videos: &[VideoInfo],
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Result<()> {
+        let mut planned = Vec::new();
+        for video_info in videos {
+            let selected: Result<(Cow<VideoInfo>, Vec<Page>)> = async {
+                let full_info = self
+                    .complete_video_info(video_info, options, platform, auth)
+                    .await?;
+                let pages = options.select_pages(&full_info)?;
+                Ok((full_info, pages))
+            }
+            .await;
+            match selected {
+                Ok((full_info, pages)) => {
+                    for page in pages {
+                        let path =
+                            self.planned_output_path(&full_info, &page, "<quality>", "<codec>", options);
+                        planned.push((full_info.title.clone(), page.number, path));
+                    }
+                }
+                Err(e) => println!("  ✗ {} ({}): {}", video_info.title, video_info.id, e),
+            }
         }
-    }
 
-    fn display_video_info(&self, video_info: &VideoInfo) {
-        println!("\n📹 Video Information:");
-        println!("  Title: {}", video_info.title);
-        println!("  Uploader: {}", video_info.uploader);
-        println!("  Pages: {}", video_info.pages.len());
-        if !video_info.description.is_empty() {
-            // 安全地截断字符串，考虑 UTF-8 字符边界
-            let desc = if video_info.description.chars().count() > 100 {
-                let truncated: String = video_info.description.chars().take(100).collect();
-                format!("{}...", truncated)
+        let paths: Vec<PathBuf> = planned.iter().map(|(_, _, path)| path.clone()).collect();
+        let duplicates = file::duplicate_paths(&paths);
+        let mut existing = 0;
+
+        println!("\n📋 {}", t(Msg::PathPreview));
+        for (title, number, path) in &planned {
+            let mut notes = Vec::new();
+            if path.exists() {
+                existing += 1;
+                notes.push(t(Msg::PathExists));
+            }
+            if duplicates.contains(path) {
+                notes.push(t(Msg::PathDuplicate));
+            }
+            let notes = if notes.is_empty() {
+                String::new()
             } else {
-                video_info.description.clone()
+                format!("  ⚠ {}", notes.join(", "))
             };
-            println!("  Description: {}", desc);
+            println!("  {} P{} → {}{}", title, number, path.display(), notes);
         }
-        println!();
+        println!(
+            "\n{}",
+            tf(Msg::PathPreviewSummary, &[&planned.len(), &existing, &duplicates.len()])
+        );
+
+        Ok(())
     }
 
-    fn select_pages(&self, video_info: &VideoInfo, cli: &Cli) -> Result<Vec<Page>> {
-        if let Some(page_numbers) = cli.parse_pages() {
-            // Filter pages by user selection
-            let mut selected = Vec::new();
-            for num in page_numbers {
-                if let Some(page) = video_info.pages.iter().find(|p| p.number == num) {
-                    selected.push(page.clone());
-                } else {
-                    tracing::warn!("Page {} not found, skipping", num);
+    /// Download the selected pages of one video from a batch
+    async fn download_video(
+        &self,
+        video_info: &VideoInfo,
+        preferences: &StreamPreferences,
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Result<Vec<PathBuf>> {
+        let video_info = &*self
+            .complete_video_info(video_info, options, platform, auth)
+            .await?;
+
+        // Determine which pages to download
+        let pages_to_download = options.select_pages(video_info)?;
+
+        if options.min_filesize.is_some() || options.max_filesize.is_some() {
+            match platform
+                .estimate_size(video_info, &pages_to_download, preferences, auth)
+                .await
+                .ok()
+            {
+                Some(size) if !options.filesize_in_range(size) => {
+                    println!(
+                        "  ⏭ {}",
+                        tf(Msg::SkippedBySize, &[&file::format_size(size)])
+                    );
+                    tracing::info!(
+                        "Skipping {}: estimated size {} is outside --min-filesize/--max-filesize",
+                        video_info.id,
+                        file::format_size(size)
+                    );
+                    self.progress.record_skipped(pages_to_download.len());
+                    return Ok(Vec::new());
                 }
+                Some(_) => {}
+                None => tracing::warn!(
+                    "Could not estimate the size of {}, downloading anyway",
+                    video_info.id
+                ),
             }
+        }
 
-            if selected.is_empty() {
-                return Err(DownloaderError::Parse(
-                    "No valid pages selected".to_string(),
-                ));
-            }
+        self.process_pages(video_info, &pages_to_download, preferences, options, platform, auth)
+            .await
+    }
 
-            Ok(selected)
-        } else {
-            // Download all pages
-            Ok(video_info.pages.clone())
+    /// `--cookie-check-interval`：批量下载每处理N个视频重新检查一次登录状态
+    ///
+    /// 登录失效时按 `abort_on_login_lost` 警告或中止；检查请求本身失败时只记录警告
+    async fn check_login_periodically(
+        &self,
+        done: usize,
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Result<()> {
+        let Some(interval) = options.cookie_check_interval else {
+            return Ok(());
+        };
+        // 未提供凭证时无需检查
+        let (Some(auth), Some(bilibili)) =
+            (auth, platform.as_any().downcast_ref::<BilibiliPlatform>())
+        else {
+            return Ok(());
+        };
+        if done == 0 || !done.is_multiple_of(interval) {
+            return Ok(());
         }
-    }
 
-    fn interactive_select_streams(&self, streams: &[Stream]) -> Result<(Stream, Stream)> {
-        let video_streams: Vec<&Stream> = streams
-            .iter()
-            .filter(|s| s.stream_type == StreamType::Video)
-            .collect();
+        match bilibili.check_login(Some(auth)).await {
+            Ok(true) => tracing::debug!("Login still valid after {} video(s)", done),
+            Ok(false) if options.abort_on_login_lost => {
+                println!("  ✗ {}", tf(Msg::LoginLostAbort, &[&done]));
+                return Err(DownloaderError::AuthRequired);
+            }
+            Ok(false) => {
+                println!("  ⚠ {}", tf(Msg::LoginLostWarn, &[&done]));
+                tracing::warn!("Login lost after {} video(s)", done);
+            }
+            Err(e) => tracing::warn!("Failed to check the login status: {}", e),
+        }
+        Ok(())
+    }
 
-        let audio_streams: Vec<&Stream> = streams
-            .iter()
-            .filter(|s| s.stream_type == StreamType::Audio)
-            .collect();
+    /// 下载多个分P，`--concurrent-pages` 大于1时并发处理
+    ///
+    /// 输出文件按分P顺序返回；并发时等所有分P结束后返回第一个错误
+    async fn process_pages(
+        &self,
+        video_info: &VideoInfo,
+        pages: &[Page],
+        preferences: &StreamPreferences,
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Result<Vec<PathBuf>> {
+        let mut outputs = self
+            .download_pages(video_info, pages, preferences, options, platform, auth)
+            .await?;
 
-        if video_streams.is_empty() {
-            return Err(DownloaderError::DownloadFailed(
-                "No video streams available".to_string(),
-            ));
+        if options.concat_pages && pages.len() > 1 && !options.simulate && !options.skip_mux {
+            if let Some(joined) = self
+                .concat_outputs(video_info, pages, &outputs, options, platform)
+                .await
+            {
+                outputs = vec![joined];
+            }
         }
 
-        if audio_streams.is_empty() {
-            return Err(DownloaderError::DownloadFailed(
-                "No audio streams available".to_string(),
-            ));
+        // 评论属于整个视频，只在第一个输出文件旁写一份
+        if options.download_comments {
+            if let Some(first) = outputs.first() {
+                self.write_comments(video_info, first, options, platform, auth).await;
+            }
         }
 
-        // Select video stream
-        println!("\n🎬 Select video quality:");
-        let video_options: Vec<String> = video_streams
-            .iter()
-            .map(|s| format!("{} {} - {}kbps", s.quality, s.codec, s.bandwidth / 1000))
-            .collect();
+        if options.download_danmaku && options.danmaku_merge && pages.len() > 1 {
+            if let Some(first) = outputs.first() {
+                self.write_merged_danmaku(pages, first, options).await;
+            }
+        }
 
-        let video_selection = Select::new()
-            .with_prompt("Video quality")
-            .items(&video_options)
-            .default(0)
-            .interact()
-            .map_err(|e| DownloaderError::Parse(format!("Selection failed: {}", e)))?;
+        Ok(outputs)
+    }
 
-        let selected_video = video_streams[video_selection].clone();
+    async fn download_pages(
+        &self,
+        video_info: &VideoInfo,
+        pages: &[Page],
+        preferences: &StreamPreferences,
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Result<Vec<PathBuf>> {
+        let mut outputs = Vec::new();
 
-        // Select audio stream
-        println!("\n🔊 Select audio quality:");
-        let audio_options: Vec<String> = audio_streams
-            .iter()
-            .map(|s| format!("{} - {}kbps", s.codec, s.bandwidth / 1000))
-            .collect();
+        // 交互式选择需要逐个分P进行
+        if options.concurrent_pages <= 1 || pages.len() <= 1 || options.interactive {
+            for (idx, page) in pages.iter().enumerate() {
+                let paths = self
+                    .process_page(video_info, page, preferences, options, platform, auth)
+                    .await
+                    .inspect_err(|e| {
+                        self.progress.record_failed(1);
+                        // 之后的分P不再下载，一并记录以便重试
+                        let remaining = pages[idx..].iter().map(|p| p.number).collect();
+                        let url = self.failure_url(video_info, options);
+                        self.record_failure(&url, Some(remaining), e);
+                    })?;
+                outputs.extend(paths);
+            }
+            return Ok(outputs);
+        }
 
-        let audio_selection = Select::new()
-            .with_prompt("Audio quality")
-            .items(&audio_options)
-            .default(0)
-            .interact()
-            .map_err(|e| DownloaderError::Parse(format!("Selection failed: {}", e)))?;
+        tracing::info!(
+            "Downloading {} page(s), {} at a time",
+            pages.len(),
+            options.concurrent_pages
+        );
+        // 各分P在同一任务中并发推进，FFmpeg 调用都放在阻塞线程池中执行（见 run_muxer），
+        // 一个分P混流时其他分P的下载不会停顿
+        let results: Vec<Result<Vec<PathBuf>>> = futures::stream::iter(pages)
+            .map(|page| self.process_page(video_info, page, preferences, options, platform, auth))
+            .buffered(options.concurrent_pages)
+            .collect()
+            .await;
 
-        let selected_audio = audio_streams[audio_selection].clone();
+        let mut first_error = None;
+        for (page, result) in pages.iter().zip(results) {
+            match result {
+                Ok(paths) => outputs.extend(paths),
+                Err(e) => {
+                    println!("  ✗ {}", tf(Msg::PageFailed, &[&page.number as &dyn Display, &e]));
+                    self.progress.record_failed(1);
+                    let url = self.failure_url(video_info, options);
+                    self.record_failure(&url, Some(vec![page.number]), &e);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
 
-        Ok((selected_video, selected_audio))
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(outputs),
+        }
     }
 
-    async fn process_page(
+    /// Print which listed qualities the current auth can actually download (no download)
+    async fn report_quality_capability(
         &self,
         video_info: &VideoInfo,
         page: &Page,
-        preferences: &StreamPreferences,
-        cli: &Cli,
         platform: &dyn Platform,
         auth: Option<&Auth>,
     ) -> Result<()> {
-        println!("\n📥 Downloading: P{} - {}", page.number, page.title);
+        let bilibili = platform
+            .as_any()
+            .downcast_ref::<BilibiliPlatform>()
+            .ok_or_else(|| {
+                DownloaderError::UnsupportedPlatform(format!(
+                    "--simulate-quality is not supported for {}",
+                    platform.name()
+                ))
+            })?;
 
-        // Get chapters early (before downloading)
-        let chapters = match parser::fetch_chapters(
-            &self.http_client,
-            &video_info.aid.to_string(),
-            &page.cid,
-        )
-        .await
-        {
-            Ok(chapters) => {
-                if !chapters.is_empty() {
-                    tracing::debug!("Found {} chapter(s)", chapters.len());
-                }
-                chapters
-            }
-            Err(e) => {
-                tracing::debug!("Failed to fetch chapters: {}", e);
-                Vec::new()
-            }
+        let ep_id = if video_info.is_bangumi {
+            page.ep_id.as_deref().or(video_info.ep_id.as_deref())
+        } else {
+            None
         };
+        let probe = bilibili
+            .probe_qualities(&video_info.aid.to_string(), &page.cid, ep_id, auth)
+            .await?;
 
-        // Get streams (use aid for bilibili API)
-        let streams = if video_info.is_bangumi {
-            // 番剧需要使用特殊的API
-            if let Some(platform_bilibili) = platform.as_any().downcast_ref::<crate::platform::bilibili::BilibiliPlatform>() {
-                if let Some(ref ep_id) = page.ep_id {
-                    // 使用page的ep_id（每个episode有自己的ep_id）
-                    platform_bilibili
-                        .get_bangumi_streams(&video_info.aid.to_string(), &page.cid, ep_id, auth)
-                        .await?
-                } else if let Some(ref ep_id) = video_info.ep_id {
-                    // 如果page没有ep_id，使用video_info的ep_id
-                    platform_bilibili
-                        .get_bangumi_streams(&video_info.aid.to_string(), &page.cid, ep_id, auth)
-                        .await?
-                } else {
-                    // 如果都没有ep_id，尝试使用普通API
-                    platform
-                        .get_streams(&video_info.aid.to_string(), &page.cid, auth)
-                        .await?
-                }
-            } else {
-                platform
-                    .get_streams(&video_info.aid.to_string(), &page.cid, auth)
-                    .await?
+        let names = |ids: &[u32]| {
+            if ids.is_empty() {
+                return "-".to_string();
             }
-        } else {
-            platform
-                .get_streams(&video_info.aid.to_string(), &page.cid, auth)
-                .await?
+            ids.iter()
+                .map(|id| format!("{} ({})", parser::get_quality_name(*id), id))
+                .collect::<Vec<_>>()
+                .join(", ")
         };
 
-        if streams.is_empty() {
-            return Err(DownloaderError::DownloadFailed(
-                "No streams available".to_string(),
-            ));
+        println!("  {}", t(if auth.is_some() { Msg::QualityAuthYes } else { Msg::QualityAuthNo }));
+        println!("  {}", tf(Msg::QualityListed, &[&names(&probe.listed)]));
+        println!("  {}", tf(Msg::QualityDownloadable, &[&names(&probe.available)]));
+        println!("  {}", tf(Msg::QualityLocked, &[&names(&probe.locked())]));
+        let max = probe.max_available().map_or_else(|| "-".to_string(), |id| names(&[id]));
+        println!("  {}", tf(Msg::QualityMax, &[&max]));
+
+        Ok(())
+    }
+
+    /// 获取分P的音视频流（番剧使用对应ep的接口）
+    async fn get_page_streams(
+        &self,
+        video_info: &VideoInfo,
+        page: &Page,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Result<Vec<Stream>> {
+        let in_stage = |e: DownloaderError| e.in_stage(format!("play-url (P{})", page.number));
+
+        if let Some(bilibili) = platform.as_any().downcast_ref::<BilibiliPlatform>() {
+            return bilibili.get_page_streams(video_info, page, auth).await.map_err(in_stage);
+        }
+        if video_info.is_live_replay() {
+            return Err(DownloaderError::DownloadFailed(
+                "Live replays require the bilibili platform".to_string(),
+            ));
+        }
+
+        platform
+            .get_streams(&video_info.aid.to_string(), &page.cid, auth)
+            .await
+            .map_err(in_stage)
+    }
+
+    /// 显示每个分P可用的音视频流表格（`--list-qualities-table`）
+    async fn display_stream_tables(
+        &self,
+        video_info: &VideoInfo,
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Result<()> {
+        for page in options.select_pages(video_info)? {
+            println!("  P{} - {} ({}s)", page.number, page.title, page.duration);
+            match self.get_page_streams(video_info, &page, platform, auth).await {
+                Ok(streams) => {
+                    for row in stream_table(&streams, page.duration) {
+                        println!("    {}", row);
+                    }
+                }
+                Err(e) => println!("    {}", tf(Msg::StreamsFailed, &[&e])),
+            }
+            println!();
+        }
+
+        Ok(())
+    }
+
+    /// 分P的输出路径（冲突处理之前）：自定义命名回调 > 输出模板 > 默认路径
+    pub fn planned_output_path(
+        &self,
+        video_info: &VideoInfo,
+        page: &Page,
+        quality: &str,
+        codec: &str,
+        options: &DownloadOptions,
+    ) -> PathBuf {
+        // Output template: CLI > config (multi-page template for multi-page videos)
+        let config_template = if video_info.is_multi_page() {
+            self.config
+                .multi_output_template
+                .as_ref()
+                .or(self.config.output_template.as_ref())
+        } else {
+            self.config.output_template.as_ref()
+        }
+        .map(|template| {
+            if template.ends_with(".mp4") {
+                template.clone()
+            } else {
+                format!("{}.mp4", template)
+            }
+        });
+
+        let named_page = file::offset_page_number(page, options.page_number_offset);
+
+        // --organize-by 的子目录插在输出目录与文件名（多P视频为标题目录）之间
+        let subdirectory = options.organize_by.subdirectory(video_info);
+        let organize = |path: PathBuf| match subdirectory {
+            Some(ref subdirectory) => PathBuf::from(subdirectory).join(path),
+            None => path,
+        };
+
+        let output_path = if let Some(ref hook) = self.output_path_hook {
+            // 库调用方自定义的命名完全取代模板与默认路径
+            hook(video_info, page)
+        } else if let Some(output) = options.output.clone().or(config_template) {
+            let parsed = file::parse_template(
+                &output,
+                video_info,
+                Some(&named_page),
+                quality,
+                codec,
+            );
+            let path = PathBuf::from(&parsed);
+            
+            // If the path is a directory or doesn't have an extension, add a filename
+            if path.is_dir() || path.extension().is_none() {
+                let filename = if video_info.is_multi_page() {
+                    file::page_file_name(
+                        options.page_template.as_deref(),
+                        video_info,
+                        &named_page,
+                        quality,
+                        codec,
+                    )
+                } else {
+                    format!("{}.mp4", file::sanitize_filename(&video_info.title))
+                };
+                path.join(organize(PathBuf::from(filename)))
+            } else {
+                match (path.parent(), path.file_name()) {
+                    (Some(parent), Some(name)) => parent.join(organize(PathBuf::from(name))),
+                    _ => organize(path),
+                }
+            }
+        } else if video_info.is_multi_page() && options.page_template.is_some() {
+            // 与默认路径一致，多P视频放在以视频标题命名的目录下
+            organize(PathBuf::from(file::sanitize_filename(&video_info.title)).join(file::page_file_name(
+                options.page_template.as_deref(),
+                video_info,
+                &named_page,
+                quality,
+                codec,
+            )))
+        } else {
+            organize(file::get_default_output_path(video_info, Some(&named_page)))
+        };
+
+        // --merge-output-format 只决定最终容器，替换掉模板或默认的扩展名
+        match options.merge_output_format {
+            Some(format) if !options.skip_mux && self.output_path_hook.is_none() => {
+                output_path.with_extension(format.extension())
+            }
+            _ => output_path,
+        }
+    }
+
+    /// 混流后读取输出文件的时长（不解码），与预期时长相差较大时提示可能被截断
+    ///
+    /// `--verify` 会完整解码并在时长不符时报错，这里只是廉价的提示
+    async fn check_output_duration(&self, output: &std::path::Path, expected: u64) {
+        if expected == 0 {
+            return;
+        }
+        let path = output.to_path_buf();
+        let Some(actual) = self.run_muxer(move |muxer| muxer.probe_duration(&path)).await else {
+            tracing::debug!("Could not read duration of {:?}", output);
+            return;
+        };
+        if !muxer::duration_matches(actual, expected) {
+            let actual = format!("{:.1}", actual);
+            println!("  ⚠ {}", tf(Msg::DurationMismatch, &[&expected, &actual]));
+        }
+    }
+
+    /// 在阻塞线程池中调用FFmpeg，避免同时下载的其他分P（包括它们的网络传输）停顿
+    async fn run_muxer<T, F>(&self, f: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce(&Muxer) -> T + Send + 'static,
+    {
+        let muxer = self.muxer.clone();
+        tokio::task::spawn_blocking(move || f(&muxer))
+            .await
+            .unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+    }
+
+    /// 本次下载使用的下载器：带上认证信息及指定的线程数，与共享下载器相同时直接复用
+    fn session_downloader(&self, thread_count: usize, auth: Option<&Auth>) -> Arc<Downloader> {
+        if auth.is_none() && thread_count == self.downloader.thread_count {
+            return self.downloader.clone();
+        }
+
+        let mut new_downloader = Downloader::new(self.http_client.clone(), thread_count)
+            .with_method(self.downloader.method)
+            .with_buffer_size(self.downloader.buffer_size)
+            .with_force_overwrite_partial(self.downloader.force_overwrite_partial)
+            .with_aria2c_path(self.downloader.aria2c_path.clone())
+            .with_aria2c_options(self.downloader.aria2c_options)
+            .with_aria2c_rpc(self.downloader.aria2c_rpc.clone())
+            .with_range_support(self.downloader.range_support_cache())
+            .with_auth(auth.cloned());
+        if let Some(ref args) = self.downloader.aria2c_args {
+            new_downloader = new_downloader.with_aria2c_args(args.clone());
+        }
+        Arc::new(new_downloader)
+    }
+
+    /// 列出所选分P可用的字幕语言（`--list-subtitles`），供选择 `--subtitle-lang`
+    async fn display_subtitle_list(
+        &self,
+        video_info: &VideoInfo,
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Result<()> {
+        let aid = video_info.aid.to_string();
+        let mut found = false;
+        for page in options.select_pages(video_info)? {
+            println!("  P{} - {}", page.number, page.title);
+            match platform.get_subtitles(&aid, &page.cid, auth).await {
+                Ok(subtitles) if subtitles.is_empty() => println!("    {}", t(Msg::NoSubtitles)),
+                Ok(subtitles) => {
+                    found = true;
+                    for subtitle in subtitles {
+                        println!("    {:<12} {}", subtitle.language_code, subtitle.language);
+                    }
+                }
+                Err(e) => println!("    {}", tf(Msg::SubtitlesFailed, &[&e])),
+            }
+            println!();
+        }
+
+        if !found && auth.is_none() {
+            tracing::warn!(
+                "No subtitles found. CC/AI subtitles usually require login (--cookie or --login-qrcode)"
+            );
+        }
+
+        Ok(())
+    }
+
+    fn build_auth(&self, options: &DownloadOptions, platform: &str) -> Option<Auth> {
+        // Priority: override_auth (from login) > CLI parameters > auth.toml > config.toml
+        
+        // If we have override auth from login, use it directly
+        if self.override_auth.is_some() {
+            return self.override_auth.clone();
+        }
+
+        resolve_auth(&self.config, options, platform)
+    }
+
+    /// 大批量下载前显示摘要并询问是否继续
+    ///
+    /// 超过 [`BATCH_CONFIRM_THRESHOLD`] 个视频或指定 `--confirm` 时询问；
+    /// 指定 `--yes` 或非交互终端时直接继续
+    async fn confirm_batch(
+        &self,
+        videos: &[VideoInfo],
+        preferences: &StreamPreferences,
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Result<bool> {
+        if options.assume_yes || !(options.confirm || videos.len() > BATCH_CONFIRM_THRESHOLD) {
+            return Ok(true);
+        }
+        if !std::io::stdin().is_terminal() {
+            tracing::warn!(
+                "Not running in a terminal, starting batch download without confirmation"
+            );
+            return Ok(true);
+        }
+
+        let total_duration: u64 = videos.iter().map(|v| v.duration).sum();
+        let estimated_size = match self
+            .sample_bandwidth(&videos[0], preferences, platform, auth)
+            .await
+        {
+            Some(bandwidth) => tf(
+                Msg::SizeFromFirstVideo,
+                &[&file::format_size(bandwidth * total_duration / 8)],
+            ),
+            None => t(Msg::Unknown).to_string(),
+        };
+        let output_dir = match options.output {
+            Some(ref output) => output.clone(),
+            None => std::env::current_dir()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_else(|_| ".".to_string()),
+        };
+
+        let duration = format!(
+            "{}:{:02}:{:02}",
+            total_duration / 3600,
+            total_duration % 3600 / 60,
+            total_duration % 60
+        );
+        println!("\n📋 {}", t(Msg::BatchSummary));
+        println!("  {}", tf(Msg::SummaryVideos, &[&videos.len()]));
+        println!("  {}", tf(Msg::SummaryDuration, &[&duration]));
+        println!("  {}", tf(Msg::SummarySize, &[&estimated_size]));
+        println!("  {}", tf(Msg::SummaryOutput, &[&output_dir]));
+
+        Confirm::new()
+            .with_prompt(t(Msg::Proceed))
+            .default(false)
+            .interact()
+            .map_err(|e| DownloaderError::Parse(format!("Confirmation failed: {}", e)))
+    }
+
+    /// 第一个视频第一个分P按当前偏好选中的音视频流的平均码率（bit/s），用于估算批量下载大小
+    async fn sample_bandwidth(
+        &self,
+        video_info: &VideoInfo,
+        preferences: &StreamPreferences,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Option<u64> {
+        let page = video_info.pages.first()?;
+        if page.duration == 0 {
+            return None;
+        }
+        let size = platform
+            .estimate_size(video_info, std::slice::from_ref(page), preferences, auth)
+            .await
+            .ok()?;
+        Some(size * 8 / page.duration)
+    }
+
+    /// 解析单个视频，指定 `--cache-ttl` 时在有效期内复用同一登录身份缓存的结果
+    async fn parse_video_cached(
+        &self,
+        url: &str,
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Result<VideoInfo> {
+        let cache = options.cache_ttl.and_then(|ttl| {
+            ParseCache::default_dir().map(|dir| ParseCache::new(dir, Duration::from_secs(ttl)))
+        });
+
+        let identity = cache::auth_identity(auth);
+
+        if let Some(video_info) = cache.as_ref().and_then(|c| c.get(url, &identity)) {
+            println!("{}", t(Msg::UsingCachedInfo));
+            return Ok(video_info);
+        }
+
+        let video_info = platform.parse_video(url, auth).await.map_err(|e| e.in_stage("parse"))?;
+        if let Some(ref cache) = cache {
+            cache.put(url, &identity, &video_info);
+        }
+        Ok(video_info)
+    }
+
+    fn display_video_info(&self, video_info: &VideoInfo) {
+        println!("\n📹 {}", t(Msg::VideoInformation));
+        println!("  {}", tf(Msg::Title, &[&video_info.title]));
+        println!("  {}", tf(Msg::Uploader, &[&video_info.uploader]));
+        println!("  {}", tf(Msg::Pages, &[&video_info.page_count()]));
+        if let Some(extra) = video_info.bilibili_extra() {
+            if !extra.tname.is_empty() {
+                println!("  {}", tf(Msg::Category, &[&extra.tname]));
+            }
+            let stat = &extra.stat;
+            println!(
+                "  {}",
+                tf(Msg::Stats, &[&stat.view, &stat.like, &stat.coin, &stat.favorite])
+            );
+            if !extra.tags.is_empty() {
+                println!("  {}", tf(Msg::Tags, &[&extra.tags.join(", ")]));
+            }
+        }
+        if !video_info.description.is_empty() {
+            // 安全地截断字符串，考虑 UTF-8 字符边界
+            let desc = if video_info.description.chars().count() > 100 {
+                let truncated: String = video_info.description.chars().take(100).collect();
+                format!("{}...", truncated)
+            } else {
+                video_info.description.clone()
+            };
+            println!("  {}", tf(Msg::Description, &[&desc]));
+        }
+        println!();
+    }
+
+    fn interactive_select_streams(&self, streams: &[Stream]) -> Result<(Stream, Stream)> {
+        let video_streams: Vec<&Stream> = streams
+            .iter()
+            .filter(|s| s.stream_type == StreamType::Video)
+            .collect();
+
+        let audio_streams: Vec<&Stream> = streams
+            .iter()
+            .filter(|s| s.stream_type == StreamType::Audio)
+            .collect();
+
+        if video_streams.is_empty() {
+            return Err(DownloaderError::DownloadFailed(
+                "No video streams available".to_string(),
+            ));
+        }
+
+        if audio_streams.is_empty() {
+            return Err(DownloaderError::DownloadFailed(
+                "No audio streams available".to_string(),
+            ));
+        }
+
+        // Select video stream
+        println!("\n🎬 {}", t(Msg::SelectVideoQuality));
+        let video_options: Vec<String> = video_streams
+            .iter()
+            .map(|s| {
+                format!(
+                    "{} {} {:.0}fps - {}kbps",
+                    s.quality,
+                    s.codec,
+                    stream_fps(s),
+                    s.bandwidth / 1000
+                )
+            })
+            .collect();
+
+        let video_selection = Select::new()
+            .with_prompt(t(Msg::VideoQuality))
+            .items(&video_options)
+            .default(0)
+            .interact()
+            .map_err(|e| DownloaderError::Parse(format!("Selection failed: {}", e)))?;
+
+        let selected_video = video_streams[video_selection].clone();
+
+        // Select audio stream
+        println!("\n🔊 {}", t(Msg::SelectAudioQuality));
+        let audio_options: Vec<String> = audio_streams
+            .iter()
+            .map(|s| format!("{} - {}kbps", s.codec, s.bandwidth / 1000))
+            .collect();
+
+        let audio_selection = Select::new()
+            .with_prompt(t(Msg::AudioQuality))
+            .items(&audio_options)
+            .default(0)
+            .interact()
+            .map_err(|e| DownloaderError::Parse(format!("Selection failed: {}", e)))?;
+
+        let selected_audio = audio_streams[audio_selection].clone();
+
+        Ok((selected_video, selected_audio))
+    }
+
+    async fn process_page(
+        &self,
+        video_info: &VideoInfo,
+        page: &Page,
+        preferences: &StreamPreferences,
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Result<Vec<PathBuf>> {
+        println!("\n📥 {}", tf(Msg::Downloading, &[&page.number as &dyn Display, &page.title]));
+
+        // Get chapters early (before downloading)
+        let chapters = if !options.embed.chapters {
+            Vec::new()
+        } else {
+            match parser::fetch_chapters(&self.http_client, &video_info.aid.to_string(), &page.cid)
+                .await
+            {
+                Ok(chapters) => {
+                    if !chapters.is_empty() {
+                        tracing::debug!("Found {} chapter(s)", chapters.len());
+                    }
+                    chapters
+                }
+                Err(e) => {
+                    tracing::debug!("Failed to fetch chapters: {}", e);
+                    Vec::new()
+                }
+            }
+        };
+
+        if options.simulate_quality {
+            self.report_quality_capability(video_info, page, platform, auth)
+                .await?;
+            return Ok(Vec::new());
+        }
+
+        // Get streams (use aid for bilibili API)
+        let streams = self.get_page_streams(video_info, page, platform, auth).await?;
+
+        if streams.is_empty() {
+            return Err(DownloaderError::DownloadFailed(
+                "No streams available".to_string(),
+            ));
+        }
+
+        if options.list_audio_languages {
+            let languages = audio_languages(&streams);
+            println!("  {}", t(Msg::AudioLanguages));
+            println!("    original (原声)");
+            for language in languages {
+                println!("    {}", language);
+            }
+            return Ok(Vec::new());
+        }
+
+        // 优先级中列出的8K/杜比视界没有返回时提示，随后按优先级自动降级
+        if !options.interactive {
+            let missing = parser::missing_top_qualities(&preferences.quality_priority, &streams);
+            if !missing.is_empty() {
+                tracing::warn!(
+                    "Requested {} not returned by the play URL API (fnval={}); the account may lack access, falling back to the next quality",
+                    missing.join(", "),
+                    parser::fnval::ALL
+                );
+            }
+        }
+
+        // Select best streams (interactive or automatic)
+        let (video_stream, audio_stream) = if options.interactive {
+            self.interactive_select_streams(&streams)?
+        } else {
+            StreamSelector::new(preferences).select(&streams)?
+        };
+
+        // Determine output path
+        let output_path = self.planned_output_path(
+            video_info,
+            page,
+            &video_stream.quality,
+            &video_stream.codec,
+            options,
+        );
+
+        // `--skip-mux` 不生成合并文件，检查实际写入的各个流文件；模拟运行时不占位
+        let page_outputs = |path: &std::path::Path| {
+            if options.skip_mux {
+                skip_mux_outputs(path, &audio_stream, options).to_vec()
+            } else {
+                vec![path.to_path_buf()]
+            }
+        };
+        // 占位文件在本分P结束前保留，失败时自动清理
+        let claimed = match file::claim_output_path(
+            &output_path,
+            options.on_collision,
+            Some(&video_info.id),
+            &page_outputs,
+            !options.simulate,
+        )? {
+            Some(claimed) => claimed,
+            None => {
+                println!("  ⏭ {}", tf(Msg::SkippedExists, &[&output_path.display()]));
+                // 模拟运行时不写入任何文件
+                if options.overwrite_policy == SidecarPolicy::Overwrite && !options.simulate {
+                    self.refresh_sidecars(video_info, page, &output_path, options, platform, auth)
+                        .await;
+                }
+                self.progress.record_skipped(1);
+                return Ok(Vec::new());
+            }
+        };
+        let output_path = claimed.path.clone();
+
+        // `--audio-lang all`：其他语言的音轨只能嵌入 MKV，且不支持与片段下载同时使用
+        let mut extra_audio = StreamSelector::new(preferences).extra_audio(&streams, &audio_stream);
+        if !extra_audio.is_empty() {
+            let languages: Vec<&str> =
+                extra_audio.iter().filter_map(|s| s.language.as_deref()).collect();
+            if muxer::MergeOutputFormat::from_path(&output_path) != Some(muxer::MergeOutputFormat::Mkv)
+                || options.skip_mux
+            {
+                println!("  ⚠ {}", tf(Msg::ExtraAudioNeedsMkv, &[&languages.join(", ")]));
+                extra_audio.clear();
+            } else if options.clip.is_some() {
+                println!("  ⚠ {}", tf(Msg::ExtraAudioWithClip, &[&languages.join(", ")]));
+                extra_audio.clear();
+            }
+        }
+
+        if options.simulate {
+            let subtitles = if options.skip_subtitle {
+                Vec::new()
+            } else {
+                self.fetch_subtitles(video_info, page, options, platform, auth).await
+            };
+            report_simulated_page(
+                page,
+                (&video_stream, &audio_stream),
+                &subtitles,
+                &output_path,
+                &platform.get_cover(video_info),
+                options,
+            );
+            return Ok(Vec::new());
+        }
+
+        // Create temp directory
+        // 目录名固定，中断后重新运行时可以继续使用已下载的文件
+        let temp_dir = file::create_temp_dir(&format!("{}_{}", video_info.id, page.cid)).await?;
+        self.temp_dirs.lock().unwrap().push(temp_dir.clone());
+
+        // Create downloaders with auth for this download session
+        // 视频流较大，可以使用与音频不同的线程数（`--video-threads` / `--audio-threads`）
+        let video_downloader = self.session_downloader(options.video_thread_count(), auth);
+        let audio_downloader = self.session_downloader(options.audio_thread_count(), auth);
+
+        // 输出文件的时长（秒），`--clip` 时为片段时长
+        let output_duration = match options.clip {
+            Some(clip) => clip.duration().ceil() as u64,
+            None => page.duration,
+        };
+
+        // Download video
+        let video_path = temp_stream_path(&temp_dir, "video", &video_stream, options.clip);
+        // 并发下载分P时用分P编号区分进度条
+        let bar_id = |kind: &str| {
+            if options.concurrent_pages > 1 {
+                format!("P{} {}", page.number, kind)
+            } else {
+                kind.to_string()
+            }
+        };
+        let video_bar = bar_id("Video");
+        let video_pb = self.progress.create_bar(&video_bar, 0);
+        let video_start = download_stream(
+            &video_downloader,
+            &video_stream,
+            &video_path,
+            options.clip,
+            video_pb,
+        )
+        .await
+        .map_err(|e| e.in_stage(format!("video download (P{})", page.number)))?;
+        self.progress.finish(&video_bar, &format!("✓ {}", t(Msg::VideoDownloaded)));
+
+        // Download audio
+        // 音视频在同一文件中（如直播回放的FLV分段）时无需重复下载，混流时从同一文件取音轨
+        let (audio_path, audio_start) = if audio_stream.url == video_stream.url {
+            (video_path.clone(), video_start)
+        } else {
+            let audio_path = temp_stream_path(&temp_dir, "audio", &audio_stream, options.clip);
+            let audio_bar = bar_id("Audio");
+            let audio_pb = self.progress.create_bar(&audio_bar, 0);
+            let audio_start = download_stream(
+                &audio_downloader,
+                &audio_stream,
+                &audio_path,
+                options.clip,
+                audio_pb,
+            )
+            .await
+            .map_err(|e| e.in_stage(format!("audio download (P{})", page.number)))?;
+            self.progress.finish(&audio_bar, &format!("✓ {}", t(Msg::AudioDownloaded)));
+            (audio_path, audio_start)
+        };
+        let mut downloaded_bytes = tokio::fs::metadata(&video_path).await.map_or(0, |m| m.len());
+        if audio_path != video_path {
+            downloaded_bytes += tokio::fs::metadata(&audio_path).await.map_or(0, |m| m.len());
+        }
+
+        // `--audio-lang all` 时其他语言的音轨，混流时一并嵌入 MKV
+        let mut extra_audio_tracks = Vec::with_capacity(extra_audio.len());
+        for stream in &extra_audio {
+            let language = stream.language.clone().unwrap_or_default();
+            let path = temp_stream_path(
+                &temp_dir,
+                &format!("audio_{}", file::sanitize_filename(&language)),
+                stream,
+                None,
+            );
+            let bar = bar_id(&format!("Audio {}", language));
+            let pb = self.progress.create_bar(&bar, 0);
+            download_stream(&audio_downloader, stream, &path, None, pb)
+                .await
+                .map_err(|e| e.in_stage(format!("{} audio download (P{})", language, page.number)))?;
+            self.progress.finish(&bar, &format!("✓ {}", t(Msg::AudioDownloaded)));
+            downloaded_bytes += tokio::fs::metadata(&path).await.map_or(0, |m| m.len());
+            extra_audio_tracks.push(muxer::AudioTrack { path, language });
+        }
+
+        // Download subtitles
+        let mut subtitle_paths = Vec::new();
+        if !options.skip_subtitle {
+            let subtitles = self.fetch_subtitles(video_info, page, options, platform, auth).await;
+            for (i, subtitle) in subtitles.iter().enumerate() {
+                let subtitle_path = temp_dir.join(format!("subtitle_{}.srt", i));
+                if let Ok(()) = subtitle::download_and_convert_subtitle(
+                    &self.http_client,
+                    subtitle,
+                    &subtitle_path,
+                )
+                .await
+                {
+                    subtitle_paths.push((subtitle_path, subtitle.language_code.clone()));
+                    println!("  ✓ {}", tf(Msg::SubtitleDownloaded, &[&subtitle.language]));
+                }
+            }
+        }
+
+        // Download danmaku
+        let raw_danmaku_temp_path = (options.download_danmaku && options.keep_raw_danmaku)
+            .then(|| temp_dir.join(format!("danmaku.{}", danmaku::RAW_EXTENSION)));
+        let danmaku_temp_path = if options.download_danmaku {
+            let danmaku_format = options.danmaku_format;
+            let danmaku_path = temp_dir.join(format!("danmaku.{}", danmaku_format.extension()));
+            
+            match danmaku::download_danmaku(
+                &self.http_client,
+                &page.cid,
+                &danmaku_path,
+                danmaku_format,
+                &self.danmaku_font,
+                options.danmaku_limit,
+                raw_danmaku_temp_path.as_deref(),
+            )
+            .await
+            {
+                Ok(()) => {
+                    println!("  ✓ {}", t(Msg::DanmakuDownloaded));
+                    Some(danmaku_path)
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to download danmaku: {}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Download cover
+        let cover_path = if !options.skip_cover {
+            let cover_url = platform.get_cover(video_info);
+            let cover_path = temp_dir.join("cover.jpg");
+            if self.download_small_file(&cover_url, &cover_path).await.is_ok() {
+                println!("  ✓ {}", t(Msg::CoverDownloaded));
+                Some(cover_path)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
+        // Create output directory
+        if let Some(parent) = output_path.parent() {
+            tokio::fs::create_dir_all(file::long_path(parent)).await?;
+        }
+
+        // Mux or copy files
+        let final_outputs = if options.skip_mux && options.remux_each {
+            // 每个流单独封装为可播放的文件
+            let [video_out, audio_out] = skip_mux_outputs(&output_path, &audio_stream, options);
+            for (input, output) in [(&video_path, &video_out), (&audio_path, &audio_out)] {
+                let (input, output) = (input.clone(), output.clone());
+                let result = self.run_muxer(move |muxer| muxer.remux_stream(&input, &output)).await;
+                if let Err(e) = result {
+                    println!("  ✗ {}", tf(Msg::RemuxFailed, &[&e]));
+                    println!("  ℹ️  {}", tf(Msg::TempKept, &[&temp_dir.display()]));
+                    return Err(e);
+                }
+            }
+            println!("  ✓ {}", t(Msg::StreamsSavedSeparately));
+            vec![video_out, audio_out]
+        } else if options.skip_mux {
+            // Just copy the files
+            let [video_out, audio_out] = skip_mux_outputs(&output_path, &audio_stream, options);
+            tokio::fs::copy(&video_path, file::long_path(&video_out)).await?;
+            tokio::fs::copy(&audio_path, file::long_path(&audio_out)).await?;
+            println!("  ✓ {}", t(Msg::FilesSaved));
+            vec![video_out, audio_out]
+        } else {
+            // 检测HDR类型 (125: HDR 真彩, 126: 杜比视界)
+            let hdr = HdrType::from_quality_id(video_stream.quality_id);
+            
+            if hdr.is_hdr() {
+                tracing::info!("检测到HDR清晰度: {:?}", hdr);
+            }
+            
+            // Mux video and audio with chapters
+            println!("  🔄 {}", t(Msg::Muxing));
+            let mux_bar = bar_id("Mux");
+            let subtitles: Vec<PathBuf> = subtitle_paths.iter().map(|(path, _)| path.clone()).collect();
+            // 片段的章节时间对不上，裁剪时不写入章节
+            let chapters = if options.clip.is_some() { Vec::new() } else { chapters };
+            let trim = options.clip.map(|clip| muxer::TrimOptions {
+                video_seek: clip.start - video_start,
+                audio_seek: clip.start - audio_start,
+                start: clip.start,
+                duration: clip.duration(),
+            });
+            let mux_options = muxer::MuxOptions {
+                hdr,
+                video_codec: video_stream.codec.clone(),
+                reencode: options.reencode.clone(),
+                progress: Some(self.progress.create_time_bar(&mux_bar, output_duration)),
+                trim,
+                font: self.subtitle_font.clone(),
+                extra_audio: extra_audio_tracks,
+                ..options.embed.mux_options(
+                    &subtitles,
+                    &chapters,
+                    cover_path.as_deref(),
+                    muxer::metadata_tags(video_info, Some(page)),
+                )
+            };
+            let mux_result = self
+                .muxer
+                .mux_advanced(&video_path, &audio_path, &output_path, &mux_options)
+                .await;
+            self.progress.finish(&mux_bar, &format!("✓ {}", t(Msg::Muxed)));
+            if let Err(e) = mux_result {
+                println!("  ✗ {}", tf(Msg::MuxFailed, &[&e]));
+                println!("  ℹ️  {}", tf(Msg::TempKept, &[&temp_dir.display()]));
+                return Err(e);
+            }
+            println!("  ✓ {}", tf(Msg::MuxedTo, &[&output_path.display()]));
+
+            if options.verify {
+                println!("  🔍 {}", t(Msg::Verifying));
+                let path = output_path.clone();
+                let result = self
+                    .run_muxer(move |muxer| muxer.verify_output(&path, Some(output_duration)))
+                    .await;
+                if let Err(e) = result {
+                    println!("  ✗ {}", tf(Msg::VerifyFailed, &[&e]));
+                    println!("  ℹ️  {}", tf(Msg::TempKept, &[&temp_dir.display()]));
+                    return Err(e);
+                }
+                println!("  ✓ {}", t(Msg::Verified));
+            } else {
+                self.check_output_duration(&output_path, output_duration).await;
+            }
+
+            vec![output_path.clone()]
+        };
+
+        if options.checksum {
+            for output in &final_outputs {
+                self.write_checksum(output, options.overwrite_policy).await?;
+            }
+        }
+
+        if options.write_thumbnail {
+            self.write_thumbnails(&platform.get_cover(video_info), &output_path, options)
+                .await;
+        }
+
+        if options.write_tags {
+            self.write_tags(video_info, &output_path).await;
+        }
+
+        // 未嵌入的字幕保存在输出文件旁：`title.zh-CN.srt`
+        if !options.embed.subtitles {
+            for (subtitle_path, language_code) in &subtitle_paths {
+                let sidecar = output_path.with_extension(format!("{}.srt", language_code));
+                if !options.overwrite_policy.should_write(&sidecar) {
+                    println!("  ⏭ {}", tf(Msg::SidecarKept, &[&sidecar.display()]));
+                    continue;
+                }
+                match tokio::fs::copy(subtitle_path, file::long_path(&sidecar)).await {
+                    Ok(_) => println!("  ✓ {}", tf(Msg::SubtitleSaved, &[&sidecar.display()])),
+                    Err(e) => tracing::warn!("Failed to save subtitle: {}", e),
+                }
+            }
+        }
+
+        if options.embed.nfo {
+            let nfo_path = output_path.with_extension("nfo");
+            match tokio::fs::write(file::long_path(&nfo_path), nfo::build_nfo(video_info, Some(page))).await {
+                Ok(()) => println!("  ✓ {}", tf(Msg::NfoSaved, &[&nfo_path.display()])),
+                Err(e) => tracing::warn!("Failed to write nfo: {}", e),
+            }
+        }
+
+        if options.gen_contact_sheet && !options.skip_mux {
+            let sheet_path = output_path.with_extension("contact.jpg");
+            let (video, sheet) = (output_path.clone(), sheet_path.clone());
+            let (cols, rows) = (options.contact_sheet_cols, options.contact_sheet_rows);
+            let result = self
+                .run_muxer(move |muxer| {
+                    muxer.generate_contact_sheet(&video, &sheet, cols, rows, Some(output_duration))
+                })
+                .await;
+            match result {
+                Ok(()) => {
+                    println!("  ✓ {}", tf(Msg::ContactSheetSaved, &[&sheet_path.display()]))
+                }
+                Err(e) => tracing::warn!("Failed to generate contact sheet: {}", e),
+            }
+        }
+
+        // Copy danmaku file to output directory (same name as video, different extension)
+        let danmaku_files = [
+            danmaku_temp_path.map(|path| (path, options.danmaku_format.extension())),
+            raw_danmaku_temp_path.map(|path| (path, danmaku::RAW_EXTENSION)),
+        ];
+        for (danmaku_temp_path, danmaku_ext) in danmaku_files.into_iter().flatten() {
+            if danmaku_temp_path.exists() {
+                let danmaku_output_path = output_path.with_extension(danmaku_ext);
+
+                if options.overwrite_policy.should_write(&danmaku_output_path) {
+                    // 与字幕一样，弹幕保存失败不影响已完成的视频
+                    match tokio::fs::copy(&danmaku_temp_path, file::long_path(&danmaku_output_path)).await {
+                        Ok(_) => println!("  ✓ {}", tf(Msg::DanmakuSaved, &[&danmaku_output_path.display()])),
+                        Err(e) => tracing::warn!("Failed to save danmaku: {}", e),
+                    }
+                } else {
+                    println!("  ⏭ {}", tf(Msg::SidecarKept, &[&danmaku_output_path.display()]));
+                }
+            }
         }
 
-        // Select best streams (interactive or automatic)
-        let (video_stream, audio_stream) = if cli.interactive {
-            self.interactive_select_streams(&streams)?
+        // Cleanup temp directory
+        if options.keep_temp {
+            println!("  ℹ️  {}", tf(Msg::TempKept, &[&temp_dir.display()]));
         } else {
-            select_best_streams(&streams, preferences)?
-        };
+            file::cleanup_temp_dir(&temp_dir).await?;
+        }
+        self.temp_dirs.lock().unwrap().retain(|dir| *dir != temp_dir);
 
-        // Create temp directory
-        let temp_dir = file::create_temp_dir(&format!("{}_{}", video_info.id, page.cid)).await?;
+        self.progress.record_page(&video_info.id, downloaded_bytes);
+        Ok(final_outputs)
+    }
 
-        // Create a downloader with auth for this download session
-        let downloader_with_auth = if auth.is_some() {
-            // Clone the Arc to get the inner Downloader, then create a new one with auth
-            let mut new_downloader = Downloader::new(self.http_client.clone(), self.downloader.thread_count);
-            new_downloader = new_downloader
-                .with_method(self.downloader.method)
-                .with_aria2c_path(self.downloader.aria2c_path.clone())
-                .with_auth(auth.cloned());
-            if let Some(ref args) = self.downloader.aria2c_args {
-                new_downloader = new_downloader.with_aria2c_args(args.clone());
-            }
-            Arc::new(new_downloader)
-        } else {
-            self.downloader.clone()
+    /// 获取分P的字幕列表，按 `--subtitle-lang` 筛选；获取失败时返回空列表
+    async fn fetch_subtitles(
+        &self,
+        video_info: &VideoInfo,
+        page: &Page,
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) -> Vec<Subtitle> {
+        let Ok(mut subtitles) = platform
+            .get_subtitles(&video_info.aid.to_string(), &page.cid, auth)
+            .await
+        else {
+            return Vec::new();
         };
 
-        // Download video
-        let video_path = temp_dir.join("video.m4s");
-        let video_pb = self.progress.create_bar("Video", 0);
-        downloader_with_auth
-            .download(&video_stream.url, &video_path, Some(video_pb.clone()))
-            .await?;
-        self.progress.finish("Video", "✓ Video downloaded");
-
-        // Download audio
-        let audio_path = temp_dir.join("audio.m4s");
-        let audio_pb = self.progress.create_bar("Audio", 0);
-        downloader_with_auth
-            .download(&audio_stream.url, &audio_path, Some(audio_pb.clone()))
-            .await?;
-        self.progress.finish("Audio", "✓ Audio downloaded");
+        if subtitles.is_empty() && auth.is_none() {
+            tracing::warn!(
+                "No subtitles found. CC/AI subtitles usually require login (--cookie or --login-qrcode)"
+            );
+        }
 
-        // Download subtitles
-        let mut subtitle_paths = Vec::new();
-        if !cli.skip_subtitle {
-            if let Ok(subtitles) = platform
-                .get_subtitles(&video_info.aid.to_string(), &page.cid)
-                .await
-            {
-                for (i, subtitle) in subtitles.iter().enumerate() {
-                    let subtitle_path = temp_dir.join(format!("subtitle_{}.srt", i));
-                    if let Ok(()) = subtitle::download_and_convert_subtitle(
-                        &self.http_client,
-                        subtitle,
-                        &subtitle_path,
-                    )
-                    .await
-                    {
-                        subtitle_paths.push(subtitle_path);
-                        println!("  ✓ Subtitle downloaded: {}", subtitle.language);
-                    }
-                }
+        if let Some(ref langs) = options.subtitle_langs {
+            let available: Vec<String> =
+                subtitles.iter().map(|s| s.language_code.clone()).collect();
+            subtitles = subtitle::filter_by_language(subtitles, langs);
+            if subtitles.is_empty() && !available.is_empty() {
+                tracing::warn!(
+                    "No subtitles match {:?} (available: {})",
+                    langs,
+                    available.join(", ")
+                );
             }
         }
 
-        // Download danmaku
-        let danmaku_temp_path = if cli.download_danmaku {
-            let danmaku_format = cli.get_danmaku_format();
-            let danmaku_ext = match danmaku_format {
-                danmaku::DanmakuFormat::Xml => "xml",
-                danmaku::DanmakuFormat::Ass => "ass",
-            };
-            let danmaku_path = temp_dir.join(format!("danmaku.{}", danmaku_ext));
-            
+        subtitles
+    }
+
+    /// `--overwrite-policy overwrite`：视频已存在被跳过时，仍重新下载弹幕和未嵌入的字幕
+    async fn refresh_sidecars(
+        &self,
+        video_info: &VideoInfo,
+        page: &Page,
+        output_path: &std::path::Path,
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) {
+        if options.download_danmaku {
+            let danmaku_path = output_path.with_extension(options.danmaku_format.extension());
+            let raw_path = options
+                .keep_raw_danmaku
+                .then(|| file::long_path(&output_path.with_extension(danmaku::RAW_EXTENSION)));
             match danmaku::download_danmaku(
                 &self.http_client,
                 &page.cid,
-                &danmaku_path,
-                danmaku_format,
+                &file::long_path(&danmaku_path),
+                options.danmaku_format,
+                &self.danmaku_font,
+                options.danmaku_limit,
+                raw_path.as_deref(),
             )
             .await
             {
-                Ok(()) => {
-                    println!("  ✓ Danmaku downloaded");
-                    Some(danmaku_path)
+                Ok(()) => println!("  ✓ {}", tf(Msg::DanmakuSaved, &[&danmaku_path.display()])),
+                Err(e) => tracing::warn!("Failed to refresh danmaku: {}", e),
+            }
+        }
+
+        // 嵌入视频中的字幕无法单独刷新
+        if !options.skip_subtitle && !options.embed.subtitles {
+            for subtitle in self.fetch_subtitles(video_info, page, options, platform, auth).await {
+                let sidecar = output_path.with_extension(format!("{}.srt", subtitle.language_code));
+                match subtitle::download_and_convert_subtitle(
+                    &self.http_client,
+                    &subtitle,
+                    &file::long_path(&sidecar),
+                )
+                .await
+                {
+                    Ok(()) => println!("  ✓ {}", tf(Msg::SubtitleSaved, &[&sidecar.display()])),
+                    Err(e) => tracing::warn!("Failed to refresh subtitle: {}", e),
                 }
+            }
+        }
+    }
+
+    /// 将视频标签写入输出文件旁的 `.tags.txt`（每行一个标签）
+    async fn write_tags(&self, video_info: &VideoInfo, output_path: &std::path::Path) {
+        // 批量解析的视频没有预先获取标签，此时单独请求
+        let aid = video_info.aid.to_string();
+        let tags = match video_info.bilibili_extra() {
+            Some(extra) if !extra.tags.is_empty() => extra.tags.clone(),
+            _ => match parser::fetch_video_tags(&self.http_client, &aid).await {
+                Ok(tags) => tags,
                 Err(e) => {
-                    tracing::warn!("Failed to download danmaku: {}", e);
-                    None
+                    tracing::warn!("Failed to fetch video tags: {}", e);
+                    return;
                 }
+            },
+        };
+
+        if tags.is_empty() {
+            tracing::debug!("No tags for {}", video_info.id);
+            return;
+        }
+
+        let tags_path = output_path.with_extension("tags.txt");
+        let content = format!("{}\n", tags.join("\n"));
+        match tokio::fs::write(file::long_path(&tags_path), content).await {
+            Ok(()) => println!("  ✓ {}", tf(Msg::TagsSaved, &[&tags_path.display()])),
+            Err(e) => tracing::warn!("Failed to write tags: {}", e),
+        }
+    }
+
+    /// `--concat-pages`：将各分P的输出按顺序拼接为一个文件并删除分P文件
+    ///
+    /// 有分P被跳过、分辨率不一致或拼接失败时保留分P文件，返回 `None`
+    async fn concat_outputs(
+        &self,
+        video_info: &VideoInfo,
+        pages: &[Page],
+        outputs: &[PathBuf],
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+    ) -> Option<PathBuf> {
+        let first = outputs.first()?;
+        if outputs.len() != pages.len() {
+            println!("  ⚠ {}", t(Msg::ConcatIncomplete));
+            return None;
+        }
+
+        let resolutions: Vec<_> = outputs.iter().map(|p| self.muxer.read_resolution(p)).collect();
+        if resolutions.iter().any(|r| *r != resolutions[0]) {
+            println!("  ⚠ {}", t(Msg::ConcatResolutionMismatch));
+            return None;
+        }
+
+        let ext = first.extension().and_then(|e| e.to_str()).unwrap_or("mp4");
+        let joined = first.with_file_name(format!(
+            "{}.{}",
+            file::sanitize_filename(&video_info.title),
+            ext
+        ));
+        let joined = match file::resolve_output_collision(&joined, options.on_collision) {
+            Ok(Some(path)) => path,
+            Ok(None) => {
+                println!("  ⏭ {}", tf(Msg::SkippedExists, &[&joined.display()]));
+                return None;
+            }
+            Err(e) => {
+                println!("  ✗ {}", tf(Msg::ConcatFailed, &[&e]));
+                return None;
             }
-        } else {
-            None
         };
 
-        // Download cover
-        let _cover_path = if !cli.skip_cover {
-            let cover_url = platform.get_cover(video_info);
-            let cover_path = temp_dir.join("cover.jpg");
-            if self
-                .downloader
-                .download(&cover_url, &cover_path, None)
+        if let Err(e) = self.muxer.concat(outputs, &joined) {
+            println!("  ✗ {}", tf(Msg::ConcatFailed, &[&e]));
+            return None;
+        }
+        // 分P文件和它们的附属文件（校验、NFO、标签、弹幕、字幕等）一并删除，
+        // 之后为合并后的文件重新生成
+        for output in outputs {
+            if let Err(e) = std::fs::remove_file(file::long_path(output)) {
+                tracing::warn!("Failed to remove {}: {}", output.display(), e);
+            }
+            remove_sidecars(output, &joined);
+        }
+        println!(
+            "  ✓ {}",
+            tf(Msg::PagesConcatenated, &[&outputs.len(), &joined.display() as &dyn Display])
+        );
+
+        if options.verify {
+            let duration: u64 = pages
+                .iter()
+                .map(|page| match options.clip {
+                    Some(clip) => clip.duration().ceil() as u64,
+                    None => page.duration,
+                })
+                .sum();
+            println!("  🔍 {}", t(Msg::Verifying));
+            match self.muxer.verify_output(&joined, Some(duration)) {
+                Ok(()) => println!("  ✓ {}", t(Msg::Verified)),
+                Err(e) => println!("  ✗ {}", tf(Msg::VerifyFailed, &[&e])),
+            }
+        }
+        if options.checksum {
+            if let Err(e) = self.write_checksum(&joined, options.overwrite_policy).await {
+                tracing::warn!("Failed to write checksum for {}: {}", joined.display(), e);
+            }
+        }
+        if options.write_thumbnail {
+            self.write_thumbnails(&platform.get_cover(video_info), &joined, options)
+                .await;
+        }
+        if options.write_tags {
+            self.write_tags(video_info, &joined).await;
+        }
+        if options.embed.nfo {
+            let nfo_path = joined.with_extension("nfo");
+            match tokio::fs::write(file::long_path(&nfo_path), nfo::build_nfo(video_info, None)).await {
+                Ok(()) => println!("  ✓ {}", tf(Msg::NfoSaved, &[&nfo_path.display()])),
+                Err(e) => tracing::warn!("Failed to write nfo: {}", e),
+            }
+        }
+
+        Some(joined)
+    }
+
+    /// 将所选分P的弹幕按分P顺序合并为一个文件，写在第一个输出文件旁
+    async fn write_merged_danmaku(
+        &self,
+        pages: &[Page],
+        output_path: &std::path::Path,
+        options: &DownloadOptions,
+    ) {
+        // 时长未知时无法计算后续分P的偏移
+        if let Some(page) = pages.iter().find(|p| p.duration == 0) {
+            println!("  ⚠ {}", tf(Msg::DanmakuMergeSkipped, &[&page.number]));
+            return;
+        }
+
+        let mut parts = Vec::with_capacity(pages.len());
+        for page in pages {
+            let xml = danmaku::fetch_danmaku(&self.http_client, &page.cid)
                 .await
-                .is_ok()
-            {
-                println!("  ✓ Cover downloaded");
-                Some(cover_path)
-            } else {
-                None
+                .and_then(|bytes| danmaku::decode_danmaku(&bytes));
+            match xml {
+                Ok(xml) => parts.push((xml, page.duration)),
+                Err(e) => {
+                    tracing::warn!("Failed to fetch danmaku of P{} for merging: {}", page.number, e);
+                    return;
+                }
+            }
+        }
+
+        let format = options.danmaku_format;
+        let content = match danmaku::merge_danmaku(
+            &parts,
+            format,
+            &self.danmaku_font,
+            options.danmaku_limit,
+        ) {
+            Ok(Some(content)) => content,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Failed to merge danmaku: {}", e);
+                return;
             }
-        } else {
-            None
         };
 
-        // Determine output path
-        let output_path = if let Some(ref output) = cli.output {
-            let parsed = file::parse_template(
-                output,
-                video_info,
-                Some(page),
-                &video_stream.quality,
-                &video_stream.codec,
-            );
-            let path = PathBuf::from(&parsed);
-            
-            // If the path is a directory or doesn't have an extension, add a filename
-            if path.is_dir() || path.extension().is_none() {
-                let filename = if video_info.pages.len() > 1 {
-                    format!("P{:02}_{}.mp4", page.number, file::sanitize_filename(&page.title))
-                } else {
-                    format!("{}.mp4", file::sanitize_filename(&video_info.title))
-                };
-                path.join(filename)
-            } else {
-                path
+        let merged_path = output_path.with_extension(format!("merged.{}", format.extension()));
+        if !options.overwrite_policy.should_write(&merged_path) {
+            println!("  ⏭ {}", tf(Msg::SidecarKept, &[&merged_path.display()]));
+            return;
+        }
+        match tokio::fs::write(file::long_path(&merged_path), content).await {
+            Ok(()) => println!(
+                "  ✓ {}",
+                tf(
+                    Msg::DanmakuMerged,
+                    &[&pages.len(), &merged_path.display() as &dyn Display]
+                )
+            ),
+            Err(e) => tracing::warn!("Failed to write merged danmaku: {}", e),
+        }
+    }
+
+    /// 将视频评论写入输出文件旁的 `.comments.json`，获取失败时只记录警告
+    async fn write_comments(
+        &self,
+        video_info: &VideoInfo,
+        output_path: &std::path::Path,
+        options: &DownloadOptions,
+        platform: &dyn Platform,
+        auth: Option<&Auth>,
+    ) {
+        let Some(bilibili) = platform.as_any().downcast_ref::<BilibiliPlatform>() else {
+            tracing::warn!("--download-comments is not supported for {}", platform.name());
+            return;
+        };
+
+        let comment_options = parser::CommentOptions {
+            with_replies: options.comment_replies,
+            max_comments: options.max_comments,
+        };
+        let comments = match bilibili
+            .fetch_comments(&video_info.aid.to_string(), auth, comment_options)
+            .await
+        {
+            Ok(comments) => comments,
+            Err(e) => {
+                tracing::warn!("Failed to fetch comments: {}", e);
+                return;
             }
-        } else {
-            file::get_default_output_path(video_info, Some(page))
         };
 
-        // Create output directory
-        if let Some(parent) = output_path.parent() {
-            tokio::fs::create_dir_all(parent).await?;
+        let archive = serde_json::json!({
+            "bvid": video_info.id,
+            "aid": video_info.aid,
+            "title": video_info.title,
+            "count": comments.len(),
+            "comments": comments,
+        });
+        let comments_path = output_path.with_extension("comments.json");
+        let content = match serde_json::to_string_pretty(&archive) {
+            Ok(content) => content,
+            Err(e) => {
+                tracing::warn!("Failed to serialize comments: {}", e);
+                return;
+            }
+        };
+        match tokio::fs::write(file::long_path(&comments_path), content).await {
+            Ok(()) => println!(
+                "  ✓ {}",
+                tf(Msg::CommentsSaved, &[&comments.len() as &dyn Display, &comments_path.display()])
+            ),
+            Err(e) => tracing::warn!("Failed to write comments: {}", e),
         }
+    }
 
-        // Mux or copy files
-        if cli.skip_mux {
-            // Just copy the files
-            let video_out = output_path.with_extension("video.m4s");
-            let audio_out = output_path.with_extension("audio.m4s");
-            tokio::fs::copy(&video_path, &video_out).await?;
-            tokio::fs::copy(&audio_path, &audio_out).await?;
-            println!("  ✓ Files saved (muxing skipped)");
+    /// 下载封面等小文件：先完整读入内存再写入，不产生 `.part` 和续传信息
+    async fn download_small_file(&self, url: &str, path: &std::path::Path) -> Result<()> {
+        let data = self
+            .downloader
+            .download_to_memory(url, downloader::DEFAULT_MEMORY_LIMIT)
+            .await?;
+        tokio::fs::write(file::long_path(path), data).await?;
+        Ok(())
+    }
+
+    /// 将封面图片保存到输出文件旁，可按多个尺寸保存
+    async fn write_thumbnails(&self, cover_url: &str, output_path: &std::path::Path, options: &DownloadOptions) {
+        for (url, path) in thumbnail_targets(cover_url, output_path, options) {
+            match self.download_small_file(&url, &path).await {
+                Ok(()) => println!("  ✓ {}", tf(Msg::ThumbnailSaved, &[&path.display()])),
+                Err(e) => tracing::warn!("Failed to download thumbnail {}: {}", url, e),
+            }
+        }
+    }
+
+    /// 为输出文件生成SHA-256校验文件，如果已有校验文件则先进行比对
+    ///
+    /// 哈希不一致时给出警告；`--overwrite-policy keep` 下保留原有的校验文件
+    async fn write_checksum(&self, output: &std::path::Path, policy: SidecarPolicy) -> Result<()> {
+        let sidecar = checksum::sidecar_path(output);
+        let previous = checksum::read_sidecar(output).await.unwrap_or_else(|e| {
+            tracing::warn!("Ignoring existing checksum file: {}", e);
+            None
+        });
+
+        let keep = !policy.should_write(&sidecar);
+        let hash = if keep {
+            checksum::sha256_file(output).await?
         } else {
-            // 检测是否是杜比视界 (quality_id 126)
-            let is_dolby_vision = video_stream.quality_id == 126;
-            
-            if is_dolby_vision {
-                tracing::info!("检测到杜比视界清晰度");
+            checksum::write_sidecar(output).await?
+        };
+
+        match previous {
+            Some(ref expected) if *expected == hash => {
+                println!("  ✓ {}", tf(Msg::ChecksumMatches, &[&hash]));
+            }
+            Some(ref expected) => {
+                tracing::warn!(
+                    "Checksum differs from previous download of {} (was {}, now {})",
+                    output.display(),
+                    expected,
+                    hash
+                );
+                let msg = if keep { Msg::ChecksumKept } else { Msg::ChecksumChanged };
+                println!("  ⚠ {}", tf(msg, &[&hash]));
+            }
+            None if keep => {
+                tracing::debug!("Keeping existing checksum file {}", sidecar.display());
+            }
+            None => {
+                println!("  ✓ {}", tf(Msg::ChecksumWritten, &[&sidecar.display()]));
             }
-            
-            // Mux video and audio with chapters
-            println!("  🔄 Muxing...");
-            self.muxer
-                .mux_with_options(&video_path, &audio_path, &output_path, &subtitle_paths, &chapters, is_dolby_vision)
-                .await?;
-            println!("  ✓ Muxed to: {}", output_path.display());
         }
 
-        // Copy danmaku file to output directory (same name as video, different extension)
-        if let Some(danmaku_temp_path) = danmaku_temp_path {
-            if danmaku_temp_path.exists() {
-                let danmaku_ext = danmaku_temp_path
-                    .extension()
-                    .and_then(|e| e.to_str())
-                    .unwrap_or("xml");
-                let danmaku_output_path = output_path.with_extension(danmaku_ext);
-                
-                tokio::fs::copy(&danmaku_temp_path, &danmaku_output_path).await?;
-                println!("  ✓ Danmaku saved to: {}", danmaku_output_path.display());
+        Ok(())
+    }
+}
+
+/// 按 CLI 参数 > auth.toml > config.toml 的优先级确定 `platform` 使用的认证信息
+///
+/// auth.toml 保存的是 Bilibili 登录获得的凭据，只用于 Bilibili；
+/// config.toml 中按 `[auth.<platform>]` 选择，顶层的 `[auth]` 视为 Bilibili 的认证信息
+pub(crate) fn resolve_auth(
+    config: &Config,
+    options: &DownloadOptions,
+    platform: &str,
+) -> Option<Auth> {
+    // Try to load from auth.toml if config file is specified
+    let auth_from_file = if platform != "bilibili" {
+        None
+    } else if let Some(ref config_path) = options.config_file {
+        use crate::auth::storage::CredentialStorage;
+        CredentialStorage::load_from_config(config_path)
+            .ok()
+            .flatten()
+            .map(|creds| CredentialStorage::to_auth(&creds))
+    } else {
+        None
+    };
+
+    let config_auth = config.auth.as_ref().map(|a| a.for_platform(platform));
+
+    // Build final auth with priority
+    let cookie = options
+        .cookie
+        .clone()
+        .or_else(|| auth_from_file.as_ref().and_then(|a| a.cookie.clone()))
+        .or_else(|| {
+            config_auth.as_ref().and_then(|a| {
+                a.cookie_value()
+                    .map_err(|e| tracing::warn!("Ignoring cookies_file for {}: {}", platform, e))
+                    .ok()
+                    .flatten()
+            })
+        });
+
+    let access_token = options
+        .access_token
+        .clone()
+        .or_else(|| auth_from_file.as_ref().and_then(|a| a.access_token.clone()))
+        .or_else(|| config_auth.as_ref().and_then(|a| a.access_token.clone()));
+
+    if cookie.is_some() || access_token.is_some() {
+        Some(Auth {
+            cookie,
+            access_token,
+        })
+    } else {
+        None
+    }
+}
+
+/// 解析弹幕与字幕使用的字体：`--font` > 配置文件 `[danmaku]`/`[subtitle] font` > 系统中文字体
+///
+/// 只在需要字体时查找，找不到中文字体时给出一次警告。附加到 MKV 的字体文件只使用
+/// 明确指定的字体：系统字体体积很大，且不一定允许分发
+fn resolve_fonts(config: &Config, options: &DownloadOptions) -> (String, Option<PathBuf>) {
+    let mut missing = false;
+
+    let mut danmaku_font = font::default_family().to_string();
+    if options.download_danmaku && matches!(options.danmaku_format, danmaku::DanmakuFormat::Ass) {
+        match font::resolve_font(options.font.as_deref().or(config.danmaku_font())) {
+            Some(font) => danmaku_font = font.family,
+            None => missing = true,
+        }
+    }
+
+    // 是否附加由 build_mux_args 按实际输出文件的扩展名决定（只有 MKV 支持附件），
+    // 这里不能只看 --merge-output-format，`-o video.mkv` 同样输出 MKV
+    let mut subtitle_font = None;
+    if !options.skip_subtitle && options.embed.subtitles {
+        if let Some(spec) = options.font.as_deref().or(config.subtitle_font()) {
+            subtitle_font = font::resolve_font(Some(spec)).and_then(|font| font.path);
+        }
+    }
+
+    if missing {
+        println!("⚠ {}", t(Msg::NoCjkFont));
+    }
+    (danmaku_font, subtitle_font)
+}
+
+/// 流在临时目录中的文件名，包含清晰度与编码，避免继续下载时误用其他流的文件
+///
+/// 片段（`--clip`）只含部分内容，使用单独的文件名且不会被复用
+fn temp_stream_path(
+    temp_dir: &std::path::Path,
+    kind: &str,
+    stream: &Stream,
+    clip: Option<ClipRange>,
+) -> PathBuf {
+    match clip {
+        Some(_) => temp_dir.join(format!("{}.clip.m4s", kind)),
+        None => temp_dir.join(format!(
+            "{}.{}.{}.m4s",
+            kind,
+            stream.quality_id,
+            file::sanitize_filename(&stream.codec)
+        )),
+    }
+}
+
+/// `--simulate`：报告分P选中的流、预估大小和将写入的文件，不下载
+fn report_simulated_page(
+    page: &Page,
+    (video, audio): (&Stream, &Stream),
+    subtitles: &[Subtitle],
+    output_path: &std::path::Path,
+    cover_url: &str,
+    options: &DownloadOptions,
+) {
+    let duration = match options.clip {
+        Some(clip) => clip.duration().ceil() as u64,
+        None => page.duration,
+    };
+    println!("  🧪 {}", t(Msg::Simulated));
+    println!(
+        "    {}",
+        tf(
+            Msg::SimulatedStream,
+            &[&t(Msg::VideoStream), &describe_stream(video), &file::format_size(estimated_size(video, duration))]
+        )
+    );
+    // 音视频在同一文件中时只下载一次
+    if audio.url != video.url {
+        println!(
+            "    {}",
+            tf(
+                Msg::SimulatedStream,
+                &[&t(Msg::AudioStream), &describe_stream(audio), &file::format_size(estimated_size(audio, duration))]
+            )
+        );
+    }
+    if !subtitles.is_empty() {
+        let languages: Vec<&str> = subtitles.iter().map(|s| s.language_code.as_str()).collect();
+        println!("    {}", tf(Msg::SimulatedSubtitles, &[&languages.join(", ")]));
+    }
+
+    let outputs = if options.skip_mux {
+        skip_mux_outputs(output_path, audio, options).to_vec()
+    } else {
+        vec![output_path.to_path_buf()]
+    };
+    let mut paths = outputs.clone();
+    if options.checksum {
+        paths.extend(outputs.iter().map(|output| checksum::sidecar_path(output)));
+    }
+    if options.write_thumbnail {
+        paths.extend(
+            thumbnail_targets(cover_url, output_path, options)
+                .into_iter()
+                .map(|(_, path)| path),
+        );
+    }
+    if options.write_tags {
+        paths.push(output_path.with_extension("tags.txt"));
+    }
+    if !options.embed.subtitles {
+        paths.extend(
+            subtitles
+                .iter()
+                .map(|s| output_path.with_extension(format!("{}.srt", s.language_code))),
+        );
+    }
+    if options.embed.nfo {
+        paths.push(output_path.with_extension("nfo"));
+    }
+    if options.gen_contact_sheet && !options.skip_mux {
+        paths.push(output_path.with_extension("contact.jpg"));
+    }
+    if options.download_danmaku {
+        paths.push(output_path.with_extension(options.danmaku_format.extension()));
+        if options.keep_raw_danmaku {
+            paths.push(output_path.with_extension(danmaku::RAW_EXTENSION));
+        }
+    }
+    for path in paths {
+        println!("    {}", tf(Msg::SimulatedFile, &[&path.display()]));
+    }
+}
+
+/// 删除 `output` 旁的附属文件（同名、扩展名不同，如 `.sha256`、`.nfo`、`.ass`、`.zh-CN.srt`），
+/// `keep` 及其附属文件除外
+fn remove_sidecars(output: &std::path::Path, keep: &std::path::Path) {
+    let (Some(parent), Some(stem)) = (output.parent(), output.file_stem().and_then(|s| s.to_str()))
+    else {
+        return;
+    };
+    let prefix = format!("{}.", stem);
+    let keep_prefix = keep
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .map(|stem| format!("{}.", stem));
+    let dir = if parent.as_os_str().is_empty() {
+        std::path::Path::new(".")
+    } else {
+        parent
+    };
+    let Ok(entries) = std::fs::read_dir(file::long_path(dir)) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if !name.starts_with(&prefix)
+            || keep_prefix.as_deref().is_some_and(|keep| name.starts_with(keep))
+        {
+            continue;
+        }
+        let sidecar = parent.join(name);
+        match std::fs::remove_file(file::long_path(&sidecar)) {
+            Ok(()) => tracing::debug!("Removed sidecar {}", sidecar.display()),
+            Err(e) => tracing::warn!("Failed to remove {}: {}", sidecar.display(), e),
+        }
+    }
+}
+
+/// `--skip-mux` 时保存的视频和音频文件：原始的 `.video.m4s` / `.audio.m4s`，
+/// `--remux-each` 时为可播放的 `.video.mp4` 和按音频编码选择扩展名的音频文件
+fn skip_mux_outputs(
+    output_path: &std::path::Path,
+    audio: &Stream,
+    options: &DownloadOptions,
+) -> [PathBuf; 2] {
+    if options.remux_each {
+        [
+            output_path.with_extension("video.mp4"),
+            output_path.with_extension(muxer::audio_stream_extension(&audio.codec)),
+        ]
+    } else {
+        [
+            output_path.with_extension("video.m4s"),
+            output_path.with_extension("audio.m4s"),
+        ]
+    }
+}
+
+/// `--write-thumbnail` 要下载的封面（地址, 保存路径），`--thumbnail-size` 的每个尺寸各一张
+fn thumbnail_targets(
+    cover_url: &str,
+    output_path: &std::path::Path,
+    options: &DownloadOptions,
+) -> Vec<(String, PathBuf)> {
+    let format = options.thumbnail_format.as_deref().map(|f| f.to_lowercase());
+    let ext = match format.as_deref() {
+        Some(f) => f.to_string(),
+        None => std::path::Path::new(cover_url.split('@').next().unwrap_or(cover_url))
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("jpg")
+            .to_string(),
+    };
+
+    // (尺寸标签, 宽, 高)，未指定尺寸时保存原图
+    let mut variants: Vec<(Option<String>, Option<u32>, Option<u32>)> = Vec::new();
+    if let Some(ref sizes) = options.thumbnail_size {
+        for size in sizes.split(',').filter(|s| !s.trim().is_empty()) {
+            match image::parse_image_size(size) {
+                Some((width, height)) => {
+                    let label = match (width, height) {
+                        (Some(w), Some(h)) => format!("{}x{}", w, h),
+                        (Some(w), None) => format!("{}w", w),
+                        _ => continue,
+                    };
+                    variants.push((Some(label), width, height));
+                }
+                None => tracing::warn!("Invalid thumbnail size: {}", size),
             }
         }
+    }
+    if variants.is_empty() {
+        variants.push((None, None, None));
+    }
 
-        // Cleanup temp directory
-        file::cleanup_temp_dir(&temp_dir).await?;
+    variants
+        .into_iter()
+        .map(|(label, width, height)| {
+            let url = image::resize_image_url(cover_url, width, height, format.as_deref());
+            let path = file::thumbnail_path(output_path, label.as_deref(), &ext);
+            (url, path)
+        })
+        .collect()
+}
 
-        Ok(())
+/// 下载单个流，返回文件开头在原视频中的时间（秒）
+///
+/// 指定 `--clip` 且流带有DASH索引时只下载覆盖片段的部分；没有索引（如直播回放）时
+/// 下载完整的流，混流时再裁剪
+async fn download_stream(
+    downloader: &Downloader,
+    stream: &Stream,
+    output: &std::path::Path,
+    clip: Option<ClipRange>,
+    progress: Arc<ProgressBar>,
+) -> Result<f64> {
+    // 上次中断时已完整下载的流（未完成的只有 `.part`）直接使用
+    if clip.is_none() && output.exists() {
+        println!("  ✓ {}", tf(Msg::ReusingDownload, &[&output.display()]));
+        let size = tokio::fs::metadata(output).await.map_or(0, |m| m.len());
+        progress.set_length(size);
+        progress.set_position(size);
+        return Ok(0.0);
+    }
+
+    match (clip, stream.segment_base) {
+        (Some(clip), Some(segment_base)) => {
+            downloader
+                .download_clip(
+                    &stream.url,
+                    &stream.backup_urls,
+                    segment_base,
+                    clip,
+                    output,
+                    Some(progress),
+                )
+                .await
+        }
+        (clip, _) => {
+            if clip.is_some() {
+                tracing::warn!("Stream has no DASH index, downloading it in full before trimming");
+            }
+            downloader
+                .download_with_fallback(&stream.url, &stream.backup_urls, output, Some(progress))
+                .await?;
+            Ok(0.0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::DownloadRequest;
+
+    /// 只响应 `-version` 的假FFmpeg，用于在未安装FFmpeg时创建 Orchestrator
+    #[cfg(unix)]
+    fn fake_ffmpeg(dir: &std::path::Path) -> std::path::PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join("ffmpeg");
+        std::fs::write(&path, "#!/bin/sh\necho 'ffmpeg version 6.0'\n").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_request_threads_reach_stream_downloaders() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let options = DownloadRequest::new("https://www.bilibili.com/video/BV1xx411c7mD")
+            .threads(2)
+            .ffmpeg_path(fake_ffmpeg(temp_dir.path()))
+            .download_options();
+        let orchestrator = Orchestrator::new(Config::default(), &options).unwrap();
+
+        let video = orchestrator.session_downloader(options.video_thread_count(), None);
+        let audio = orchestrator.session_downloader(options.audio_thread_count(), None);
+        assert_eq!((video.thread_count, audio.thread_count), (2, 2));
+
+        // 单独指定的流线程数优先于 threads
+        let options = DownloadOptions {
+            video_threads: Some(8),
+            ..options
+        };
+        let video = orchestrator.session_downloader(options.video_thread_count(), None);
+        let audio = orchestrator.session_downloader(options.audio_thread_count(), None);
+        assert_eq!((video.thread_count, audio.thread_count), (8, 2));
+    }
+
+    #[test]
+    fn test_subtitle_font_resolved_without_merge_output_format() {
+        // `-o video.mkv` 不指定 --merge-output-format 时同样输出 MKV，需要附加字体
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let font_path = temp_dir.path().join("MyFont.ttf");
+        std::fs::write(&font_path, b"not a real font").unwrap();
+        let options = DownloadOptions {
+            output: Some("video.mkv".to_string()),
+            font: Some(font_path.to_string_lossy().into_owned()),
+            ..Default::default()
+        };
+
+        let (_, subtitle_font) = resolve_fonts(&Config::default(), &options);
+        assert_eq!(subtitle_font, Some(font_path));
+
+        // 不嵌入字幕时不需要字体
+        let options = DownloadOptions {
+            skip_subtitle: true,
+            ..options
+        };
+        assert_eq!(resolve_fonts(&Config::default(), &options).1, None);
     }
 }
@@ -0,0 +1,194 @@
+use crate::app::orchestrator::OutputPathHook;
+use crate::app::{DownloadOptions, Orchestrator};
+use crate::error::Result;
+use crate::types::{Auth, Page, VideoInfo};
+use crate::utils::config::Config;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// High-level download API for using rvd as a library
+///
+/// ```no_run
+/// # async fn example() -> rvd::Result<()> {
+/// let outputs = rvd::DownloadRequest::new("https://www.bilibili.com/video/BV1xx411c7mD")
+///     .quality("1080P,720P")
+///     .output("downloads/<videoTitle>")
+///     .build()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct DownloadRequest {
+    url: String,
+    quality: Option<String>,
+    codec: Option<String>,
+    output: Option<String>,
+    pages: Option<Vec<usize>>,
+    threads: Option<usize>,
+    auth: Option<Auth>,
+    ffmpeg_path: Option<PathBuf>,
+    skip_subtitle: bool,
+    skip_cover: bool,
+    skip_mux: bool,
+    output_path_hook: Option<Arc<OutputPathHook>>,
+    config: Config,
+}
+
+impl std::fmt::Debug for DownloadRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DownloadRequest")
+            .field("url", &self.url)
+            .field("quality", &self.quality)
+            .field("codec", &self.codec)
+            .field("output", &self.output)
+            .field("pages", &self.pages)
+            .field("threads", &self.threads)
+            .field("auth", &self.auth)
+            .field("ffmpeg_path", &self.ffmpeg_path)
+            .field("skip_subtitle", &self.skip_subtitle)
+            .field("skip_cover", &self.skip_cover)
+            .field("skip_mux", &self.skip_mux)
+            .field("output_path_hook", &self.output_path_hook.is_some())
+            .field("config", &self.config)
+            .finish()
+    }
+}
+
+impl DownloadRequest {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            quality: None,
+            codec: None,
+            output: None,
+            pages: None,
+            threads: None,
+            auth: None,
+            ffmpeg_path: None,
+            skip_subtitle: false,
+            skip_cover: false,
+            skip_mux: false,
+            output_path_hook: None,
+            config: Config::default(),
+        }
+    }
+
+    /// Quality priority (comma-separated, e.g. "1080P,720P")
+    pub fn quality(mut self, quality: impl Into<String>) -> Self {
+        self.quality = Some(quality.into());
+        self
+    }
+
+    /// Codec priority (comma-separated, e.g. "hevc,avc")
+    pub fn codec(mut self, codec: impl Into<String>) -> Self {
+        self.codec = Some(codec.into());
+        self
+    }
+
+    /// Output file path or template
+    pub fn output(mut self, output: impl Into<String>) -> Self {
+        self.output = Some(output.into());
+        self
+    }
+
+    /// Page numbers to download (all pages if not set)
+    pub fn pages(mut self, pages: impl Into<Vec<usize>>) -> Self {
+        self.pages = Some(pages.into());
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    pub fn auth(mut self, auth: Auth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    pub fn ffmpeg_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ffmpeg_path = Some(path.into());
+        self
+    }
+
+    pub fn skip_subtitle(mut self, skip: bool) -> Self {
+        self.skip_subtitle = skip;
+        self
+    }
+
+    pub fn skip_cover(mut self, skip: bool) -> Self {
+        self.skip_cover = skip;
+        self
+    }
+
+    /// Keep separate video and audio files instead of muxing
+    pub fn skip_mux(mut self, skip: bool) -> Self {
+        self.skip_mux = skip;
+        self
+    }
+
+    /// Compute each page's output path, replacing the output template and default naming
+    /// (see [`Orchestrator::with_output_path_hook`])
+    pub fn output_path_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&VideoInfo, &Page) -> PathBuf + Send + Sync + 'static,
+    {
+        self.output_path_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Settings not given on the request fall back to this config
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Download options this request resolves to, with unset settings taken from the config
+    pub(crate) fn download_options(&self) -> DownloadOptions {
+        let defaults = DownloadOptions::default();
+        let split = |list: String| list.split(',').map(|s| s.trim().to_string()).collect();
+
+        DownloadOptions {
+            url: Some(self.url.clone()),
+            quality_priority: self
+                .quality
+                .clone()
+                .map(split)
+                .or_else(|| self.config.default_quality.clone())
+                .unwrap_or(defaults.quality_priority),
+            codec_priority: self
+                .codec
+                .clone()
+                .map(split)
+                .or_else(|| self.config.default_codec.clone())
+                .unwrap_or(defaults.codec_priority),
+            output: self.output.clone(),
+            pages: self.pages.clone(),
+            threads: self
+                .threads
+                .or(self.config.thread_count)
+                .unwrap_or(defaults.threads),
+            ffmpeg_path: self.ffmpeg_path.clone(),
+            skip_subtitle: self.skip_subtitle,
+            skip_cover: self.skip_cover,
+            skip_mux: self.skip_mux,
+            ..defaults
+        }
+    }
+
+    /// Run the download, resolving to the output files
+    pub async fn build(self) -> Result<Vec<PathBuf>> {
+        let options = self.download_options();
+        let mut orchestrator = Orchestrator::new(self.config, &options)?;
+        if let Some(hook) = self.output_path_hook {
+            orchestrator = orchestrator.with_output_path_hook(move |video, page| hook(video, page));
+        }
+        if self.auth.is_some() {
+            orchestrator.set_auth(self.auth);
+        }
+
+        orchestrator.run(options).await
+    }
+}
@@ -11,15 +11,25 @@
 //! 协调完整的登录流程
 
 use crate::auth::qrcode::QRCodeDisplay;
-use crate::auth::types::{AuthError, Credentials, LoginStatus};
+use crate::auth::types::{AuthError, CaptchaResult, Credentials, LoginStatus};
 use crate::auth::AuthProvider;
-use crate::error::Result;
+use crate::error::{DownloaderError, Result};
+use crate::utils::i18n::{t, Msg};
+use indicatif::{ProgressBar, ProgressStyle};
 use std::path::Path;
 use std::time::Duration;
 
+/// 二维码过期后最多申请的次数（含第一次）
+pub const MAX_QRCODE_ATTEMPTS: usize = 3;
+
+/// 每个二维码的轮询时长（秒），与B站二维码有效期一致
+const QRCODE_POLL_SECONDS: usize = 180;
+
 /// 登录管理器
 pub struct LoginManager {
     provider: Box<dyn AuthProvider>,
+    /// 以纯ASCII字符显示二维码
+    ascii_qrcode: bool,
 }
 
 impl LoginManager {
@@ -29,7 +39,16 @@ impl LoginManager {
     ///
     /// * `provider` - 认证提供者
     pub fn new(provider: Box<dyn AuthProvider>) -> Self {
-        Self { provider }
+        Self {
+            provider,
+            ascii_qrcode: false,
+        }
+    }
+
+    /// 以纯ASCII字符显示二维码，适用于会破坏方块字符或颜色的终端
+    pub fn with_ascii_qrcode(mut self, ascii: bool) -> Self {
+        self.ascii_qrcode = ascii;
+        self
     }
 
     /// 执行二维码登录
@@ -37,7 +56,7 @@ impl LoginManager {
     /// 完整的登录流程：
     /// 1. 申请二维码
     /// 2. 显示二维码（终端 + 图片文件）
-    /// 3. 轮询登录状态
+    /// 3. 轮询登录状态（二维码过期时重新申请，最多 `MAX_QRCODE_ATTEMPTS` 次）
     /// 4. 清理资源
     /// 5. 返回凭证
     ///
@@ -45,6 +64,90 @@ impl LoginManager {
     ///
     /// 返回登录凭证或错误
     pub async fn perform_qr_login(&self) -> Result<Credentials> {
+        let qrcode_path = Path::new("qrcode.png");
+        let mut result = Err(AuthError::QRCodeExpired.into());
+
+        for attempt in 1..=MAX_QRCODE_ATTEMPTS {
+            if attempt > 1 {
+                tracing::warn!(
+                    "二维码已过期，重新生成 ({}/{})",
+                    attempt,
+                    MAX_QRCODE_ATTEMPTS
+                );
+            }
+
+            result = self.qr_login_once(qrcode_path).await;
+            match result {
+                Err(DownloaderError::Auth(AuthError::QRCodeExpired)) => continue,
+                _ => break,
+            }
+        }
+
+        // 清理资源
+        if qrcode_path.exists() {
+            if let Err(e) = std::fs::remove_file(qrcode_path) {
+                tracing::warn!("无法删除二维码图片: {}", e);
+            }
+        }
+
+        result
+    }
+
+    /// 执行短信验证码登录
+    ///
+    /// 登录流程：
+    /// 1. 申请人机验证，由用户在浏览器中完成极验验证
+    /// 2. 发送短信验证码
+    /// 3. 输入验证码登录
+    ///
+    /// # Arguments
+    ///
+    /// * `cid` - 国际区号（中国大陆为86）
+    /// * `tel` - 手机号
+    /// * `prompt` - 向用户询问输入的回调，参数为提示文字
+    ///
+    /// # Returns
+    ///
+    /// 返回登录凭证或错误
+    pub async fn perform_sms_login<F>(&self, cid: u32, tel: &str, mut prompt: F) -> Result<Credentials>
+    where
+        F: FnMut(&str) -> Result<String>,
+    {
+        // 步骤1: 人机验证
+        tracing::info!("申请人机验证...");
+        let captcha = self.provider.request_captcha().await?;
+
+        println!("\n{}", t(Msg::CaptchaRequired));
+        println!("  gt:        {}", captcha.gt);
+        println!("  challenge: {}", captcha.challenge);
+        println!("{}\n", t(Msg::CaptchaHint));
+
+        let validate = prompt("validate")?;
+        let seccode = prompt(t(Msg::SeccodePrompt))?;
+        let result = CaptchaResult {
+            challenge: captcha.challenge.clone(),
+            seccode: if seccode.is_empty() {
+                format!("{}|jordan", validate)
+            } else {
+                seccode
+            },
+            validate,
+        };
+
+        // 步骤2: 发送短信
+        let captcha_key = self.provider.send_sms_code(cid, tel, &captcha, &result).await?;
+        tracing::info!("验证码已发送到 +{} {}", cid, tel);
+
+        // 步骤3: 验证码登录
+        let code = prompt(t(Msg::SmsCodePrompt))?;
+        let credentials = self.provider.login_with_sms(cid, tel, &code, &captcha_key).await?;
+        tracing::info!("登录成功！");
+
+        Ok(credentials)
+    }
+
+    /// 申请并显示一个二维码，轮询直到登录结束或二维码过期
+    async fn qr_login_once(&self, qrcode_path: &Path) -> Result<Credentials> {
         // 步骤1: 申请二维码
         tracing::info!("获取登录地址...");
         let qr_data = self.provider.request_qrcode().await?;
@@ -53,13 +156,18 @@ impl LoginManager {
         tracing::info!("生成二维码...");
 
         // 尝试在终端显示
-        if let Err(e) = QRCodeDisplay::display_in_terminal(&qr_data.url) {
+        let displayed = if self.ascii_qrcode {
+            QRCodeDisplay::display_ascii(&qr_data.url)
+        } else {
+            QRCodeDisplay::display_in_terminal(&qr_data.url)
+        };
+        if let Err(e) = displayed {
             tracing::warn!("无法在终端显示二维码: {}", e);
             tracing::warn!("请打开图片文件扫描二维码");
         }
+        QRCodeDisplay::print_url(&qr_data.url);
 
         // 步骤3: 保存二维码图片
-        let qrcode_path = Path::new("qrcode.png");
         if let Err(e) = QRCodeDisplay::save_to_file(&qr_data.url, qrcode_path) {
             tracing::warn!("无法保存二维码图片: {}", e);
         } else {
@@ -68,19 +176,7 @@ impl LoginManager {
 
         // 步骤4: 轮询登录状态
         tracing::info!("等待扫码...");
-
-        let credentials = self
-            .poll_with_retry(&qr_data.key, 180)
-            .await?;
-
-        // 步骤5: 清理资源
-        if qrcode_path.exists() {
-            if let Err(e) = std::fs::remove_file(qrcode_path) {
-                tracing::warn!("无法删除二维码图片: {}", e);
-            }
-        }
-
-        Ok(credentials)
+        self.poll_with_retry(&qr_data.key, QRCODE_POLL_SECONDS).await
     }
 
     /// 轮询登录状态的内部实现
@@ -98,7 +194,24 @@ impl LoginManager {
     async fn poll_with_retry(&self, key: &str, max_attempts: usize) -> Result<Credentials> {
         let mut scanned_shown = false; // 标记是否已显示"已扫码"提示
 
+        // 显示剩余时间，非终端环境下自动隐藏
+        let countdown = ProgressBar::new_spinner();
+        countdown.set_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.green} {msg}")
+                .unwrap_or_else(|_| ProgressStyle::default_spinner()),
+        );
+        let _clear = ClearOnDrop(&countdown);
+
         for attempt in 1..=max_attempts {
+            let status = if scanned_shown { "已扫码，等待确认" } else { "等待扫码" };
+            countdown.set_message(format!(
+                "{}... 二维码剩余 {}s",
+                status,
+                max_attempts + 1 - attempt
+            ));
+            countdown.tick();
+
             // 等待1秒
             tokio::time::sleep(Duration::from_secs(1)).await;
 
@@ -148,6 +261,15 @@ impl LoginManager {
     }
 }
 
+/// 轮询结束时清除倒计时行
+struct ClearOnDrop<'a>(&'a ProgressBar);
+
+impl Drop for ClearOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.finish_and_clear();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
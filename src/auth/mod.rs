@@ -16,7 +16,7 @@ pub mod qrcode;
 pub mod storage;
 pub mod types;
 
-pub use types::{AuthError, Credentials, LoginStatus, QRCodeData};
+pub use types::{AuthError, CaptchaData, CaptchaResult, Credentials, LoginStatus, QRCodeData};
 
 use async_trait::async_trait;
 use crate::error::Result;
@@ -44,6 +44,51 @@ pub trait AuthProvider: Send + Sync {
     /// 返回当前的登录状态
     async fn poll_login_status(&self, key: &str) -> Result<LoginStatus>;
 
+    /// 申请人机验证（短信登录前需要完成）
+    async fn request_captcha(&self) -> Result<CaptchaData> {
+        Err(AuthError::UnsupportedMethod("SMS login".to_string()).into())
+    }
+
+    /// 发送短信验证码
+    ///
+    /// # Arguments
+    ///
+    /// * `cid` - 国际区号（中国大陆为86）
+    /// * `tel` - 手机号
+    /// * `captcha` - 申请到的人机验证参数
+    /// * `result` - 用户完成人机验证的结果
+    ///
+    /// # Returns
+    ///
+    /// 返回登录时需要的captcha_key
+    async fn send_sms_code(
+        &self,
+        _cid: u32,
+        _tel: &str,
+        _captcha: &CaptchaData,
+        _result: &CaptchaResult,
+    ) -> Result<String> {
+        Err(AuthError::UnsupportedMethod("SMS login".to_string()).into())
+    }
+
+    /// 使用短信验证码登录
+    ///
+    /// # Arguments
+    ///
+    /// * `cid` - 国际区号
+    /// * `tel` - 手机号
+    /// * `code` - 短信验证码
+    /// * `captcha_key` - 发送验证码时返回的key
+    async fn login_with_sms(
+        &self,
+        _cid: u32,
+        _tel: &str,
+        _code: &str,
+        _captcha_key: &str,
+    ) -> Result<Credentials> {
+        Err(AuthError::UnsupportedMethod("SMS login".to_string()).into())
+    }
+
     /// 刷新凭证（可选）
     ///
     /// # Arguments
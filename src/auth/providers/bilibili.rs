@@ -1,4 +1,6 @@
-use crate::auth::types::{AuthError, Credentials, LoginStatus, QRCodeData};
+use crate::auth::types::{
+    AuthError, CaptchaData, CaptchaResult, Credentials, LoginStatus, QRCodeData,
+};
 use crate::auth::AuthProvider;
 use crate::error::Result;
 use crate::platform::bilibili::ApiMode;
@@ -207,6 +209,46 @@ impl BilibiliAuthProvider {
         Ok(cookies)
     }
 
+    /// 短信登录仅支持Web端
+    fn ensure_web_mode(&self) -> Result<()> {
+        if matches!(self.api_mode, ApiMode::Web) {
+            Ok(())
+        } else {
+            Err(AuthError::UnsupportedMethod(format!(
+                "SMS login is only available in Web mode, not {:?}",
+                self.api_mode
+            ))
+            .into())
+        }
+    }
+
+    /// 以表单形式POST到passport接口，并检查根级别的code字段
+    ///
+    /// 返回HTTP响应头中提取的Cookie和解析后的JSON
+    async fn post_passport_form(&self, url: &str, form: &[(&str, &str)]) -> Result<(String, Value)> {
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(form)
+            .finish();
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            "Content-Type",
+            "application/x-www-form-urlencoded"
+                .parse()
+                .map_err(|e| AuthError::Network(format!("Invalid header: {}", e)))?,
+        );
+
+        let response = self.client.post(url, &body, Some(headers)).await?;
+        let cookies = self.extract_cookies_from_response(&response)?;
+
+        let json: Value = response.json().await.map_err(|e| {
+            AuthError::InvalidResponse(format!("Failed to parse JSON response: {}", e))
+        })?;
+        check_code(&json)?;
+
+        Ok((cookies, json))
+    }
+
     /// TV端申请二维码
     ///
     /// API: https://passport.snm0516.aisee.tv/x/passport-tv-login/qrcode/auth_code
@@ -453,6 +495,38 @@ impl BilibiliAuthProvider {
     }
 }
 
+/// 检查响应根级别的code字段，非0时返回登录失败
+fn check_code(json: &Value) -> Result<()> {
+    let code = json["code"]
+        .as_i64()
+        .ok_or_else(|| AuthError::InvalidResponse("Missing 'code' field".to_string()))?;
+
+    if code != 0 {
+        let message = json["message"].as_str().unwrap_or("Unknown error");
+        return Err(AuthError::LoginFailed(format!("code {}: {}", code, message)).into());
+    }
+
+    Ok(())
+}
+
+/// 解析人机验证申请接口的响应
+fn parse_captcha(json: &Value) -> Result<CaptchaData> {
+    check_code(json)?;
+
+    let field = |value: &Value, name: &str| {
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| AuthError::InvalidResponse(format!("Missing '{}' field", name)))
+    };
+
+    Ok(CaptchaData {
+        gt: field(&json["data"]["geetest"]["gt"], "data.geetest.gt")?,
+        challenge: field(&json["data"]["geetest"]["challenge"], "data.geetest.challenge")?,
+        token: field(&json["data"]["token"], "data.token")?,
+    })
+}
+
 #[async_trait]
 impl AuthProvider for BilibiliAuthProvider {
     async fn request_qrcode(&self) -> Result<QRCodeData> {
@@ -478,13 +552,123 @@ impl AuthProvider for BilibiliAuthProvider {
             .into()),
         }
     }
+
+    /// API: https://passport.bilibili.com/x/passport-login/captcha
+    async fn request_captcha(&self) -> Result<CaptchaData> {
+        self.ensure_web_mode()?;
+        tracing::debug!("Requesting login captcha");
+
+        let url = "https://passport.bilibili.com/x/passport-login/captcha?source=main_web";
+        let response = self.client.get(url, None).await?;
+        let json: Value = response.json().await.map_err(|e| {
+            AuthError::InvalidResponse(format!("Failed to parse JSON response: {}", e))
+        })?;
+
+        parse_captcha(&json)
+    }
+
+    /// API: https://passport.bilibili.com/x/passport-login/web/sms/send
+    async fn send_sms_code(
+        &self,
+        cid: u32,
+        tel: &str,
+        captcha: &CaptchaData,
+        result: &CaptchaResult,
+    ) -> Result<String> {
+        self.ensure_web_mode()?;
+        tracing::debug!("Sending SMS code");
+
+        let cid = cid.to_string();
+        let (_, json) = self
+            .post_passport_form(
+                "https://passport.bilibili.com/x/passport-login/web/sms/send",
+                &[
+                    ("cid", cid.as_str()),
+                    ("tel", tel),
+                    ("source", "main_web"),
+                    ("token", captcha.token.as_str()),
+                    ("challenge", result.challenge.as_str()),
+                    ("validate", result.validate.as_str()),
+                    ("seccode", result.seccode.as_str()),
+                ],
+            )
+            .await?;
+
+        json["data"]["captcha_key"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| {
+                AuthError::InvalidResponse("Missing 'data.captcha_key' field".to_string()).into()
+            })
+    }
+
+    /// API: https://passport.bilibili.com/x/passport-login/web/login/sms
+    async fn login_with_sms(
+        &self,
+        cid: u32,
+        tel: &str,
+        code: &str,
+        captcha_key: &str,
+    ) -> Result<Credentials> {
+        self.ensure_web_mode()?;
+        tracing::debug!("Logging in with SMS code");
+
+        let cid = cid.to_string();
+        let (cookies, _) = self
+            .post_passport_form(
+                "https://passport.bilibili.com/x/passport-login/web/login/sms",
+                &[
+                    ("cid", cid.as_str()),
+                    ("tel", tel),
+                    ("code", code),
+                    ("source", "main_web"),
+                    ("captcha_key", captcha_key),
+                ],
+            )
+            .await?;
+
+        // 与Web端二维码登录相同，凭证通过Set-Cookie下发
+        if cookies.is_empty() {
+            return Err(AuthError::InvalidResponse(
+                "Login successful but no cookies found".to_string(),
+            )
+            .into());
+        }
+
+        Ok(Credentials {
+            cookie: Some(cookies),
+            access_token: None,
+            refresh_token: None,
+            expires_at: None,
+            mid: None,
+        })
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_cookie_extraction() {
         // 这个测试需要mock HTTP响应，暂时跳过
         // 实际测试将在集成测试中进行
     }
+
+    #[test]
+    fn test_parse_captcha() {
+        let json: Value = serde_json::from_str(
+            r#"{"code":0,"message":"0","data":{"type":"geetest","token":"tok",
+                "geetest":{"challenge":"chal","gt":"gt123"}}}"#,
+        )
+        .unwrap();
+        let captcha = parse_captcha(&json).unwrap();
+        assert_eq!(captcha.gt, "gt123");
+        assert_eq!(captcha.challenge, "chal");
+        assert_eq!(captcha.token, "tok");
+
+        let json: Value =
+            serde_json::from_str(r#"{"code":-400,"message":"请求错误"}"#).unwrap();
+        assert!(parse_captcha(&json).is_err());
+    }
 }
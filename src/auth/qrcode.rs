@@ -12,6 +12,7 @@
 
 use crate::auth::types::AuthError;
 use crate::error::Result;
+use crate::utils::i18n::{t, Msg};
 use qrcode::QrCode;
 use std::path::Path;
 
@@ -41,6 +42,56 @@ impl QRCodeDisplay {
         Ok(())
     }
 
+    /// 在终端以纯ASCII字符显示二维码（适用于会破坏方块字符或颜色的终端）
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - 二维码URL
+    ///
+    /// # Returns
+    ///
+    /// 成功返回Ok(())，失败返回错误
+    pub fn display_ascii(url: &str) -> Result<()> {
+        let code = Self::generate_qrcode(url)?;
+
+        println!("\n{}", t(Msg::ScanQrCode));
+        println!();
+        print!("{}", Self::render_ascii(&code));
+        println!();
+
+        Ok(())
+    }
+
+    /// 打印二维码链接，终端无法扫描时可复制到二维码生成器
+    pub fn print_url(url: &str) {
+        println!("{}", t(Msg::QrCodeUrlHint));
+        println!("{}", url);
+        println!();
+    }
+
+    /// 将二维码渲染为ASCII文本
+    ///
+    /// 与PowerShell显示方式相同采用反色：浅色模块为 `##`，深色模块为空格，
+    /// 四周留出2个模块宽的静区，适配深色背景的终端
+    fn render_ascii(code: &QrCode) -> String {
+        const QUIET_ZONE: usize = 2;
+        let width = code.width() + QUIET_ZONE * 2;
+        let mut output = String::new();
+
+        for y in 0..width {
+            output.push_str("    ");
+            for x in 0..width {
+                let in_code = (QUIET_ZONE..QUIET_ZONE + code.width()).contains(&x)
+                    && (QUIET_ZONE..QUIET_ZONE + code.width()).contains(&y);
+                let dark = in_code && code[(x - QUIET_ZONE, y - QUIET_ZONE)] == qrcode::Color::Dark;
+                output.push_str(if dark { "  " } else { "##" });
+            }
+            output.push('\n');
+        }
+
+        output
+    }
+
     /// 保存二维码为PNG图片文件
     ///
     /// # Arguments
@@ -110,7 +161,7 @@ impl QRCodeDisplay {
     ///
     /// 成功返回Ok(())，失败返回错误
     fn display_unicode_blocks(code: &QrCode) -> Result<()> {
-        println!("\n请使用手机扫描以下二维码：");
+        println!("\n{}", t(Msg::ScanQrCode));
         println!();
 
         // 添加上边距
@@ -150,7 +201,7 @@ impl QRCodeDisplay {
     ///
     /// 成功返回Ok(())，失败返回错误
     fn display_ansi_colors(code: &QrCode) -> Result<()> {
-        println!("\n请使用手机扫描以下二维码：");
+        println!("\n{}", t(Msg::ScanQrCode));
         println!();
 
         // 添加上边距（白色）
@@ -213,6 +264,22 @@ mod tests {
         assert!(metadata.len() > 0);
     }
 
+    #[test]
+    fn test_render_ascii() {
+        let code = QRCodeDisplay::generate_qrcode("https://www.bilibili.com/").unwrap();
+        let ascii = QRCodeDisplay::render_ascii(&code);
+        let lines: Vec<&str> = ascii.lines().collect();
+
+        let width = code.width() + 4;
+        assert_eq!(lines.len(), width);
+        assert!(lines.iter().all(|line| line.len() == 4 + width * 2));
+        assert!(ascii.is_ascii());
+        // 静区全部为浅色
+        assert_eq!(lines[0].trim(), "##".repeat(width));
+        // 左上角定位图案的第一个模块为深色
+        assert_eq!(&lines[2][8..10], "  ");
+    }
+
     #[test]
     fn test_is_windows_powershell() {
         // 这个测试只验证函数可以调用，不验证具体结果
@@ -126,6 +126,7 @@ impl CredentialStorage {
             })?;
 
             if let Some(auth_config) = config.auth {
+                let auth_config = auth_config.for_platform("bilibili");
                 return Ok(Some(Self::auth_config_to_credentials(&auth_config)));
             }
         }
@@ -177,6 +178,7 @@ impl CredentialStorage {
             refresh_token: credentials.refresh_token.clone(),
             expires_at: credentials.expires_at,
             mid: credentials.mid,
+            ..Default::default()
         }
     }
 
@@ -7,7 +7,9 @@ use thiserror::Error;
 pub enum LoginMethod {
     /// 二维码登录
     QRCode,
-    // 未来扩展：Password, SMS
+    /// 短信验证码登录
+    Sms,
+    // 未来扩展：Password（需要RSA加密密码）
 }
 
 /// 登录凭证（平台无关）
@@ -32,6 +34,23 @@ pub struct QRCodeData {
     pub key: String, // qrcode_key 或 auth_code
 }
 
+/// 人机验证（极验geetest）参数
+#[derive(Debug, Clone)]
+pub struct CaptchaData {
+    pub gt: String,
+    pub challenge: String,
+    /// 登录API的验证token
+    pub token: String,
+}
+
+/// 用户完成人机验证后得到的结果
+#[derive(Debug, Clone)]
+pub struct CaptchaResult {
+    pub challenge: String,
+    pub validate: String,
+    pub seccode: String,
+}
+
 /// 登录状态
 #[derive(Debug, Clone)]
 pub enum LoginStatus {
@@ -74,4 +93,7 @@ pub enum AuthError {
 
     #[error("Invalid response from server: {0}")]
     InvalidResponse(String),
+
+    #[error("Login method not supported: {0}")]
+    UnsupportedMethod(String),
 }
@@ -6,12 +6,37 @@ use std::path::PathBuf;
 #[command(author = "RVD Contributors")]
 #[command(version = "0.2.7")]
 #[command(about = "A modular video downloader written in Rust", long_about = None)]
+#[command(group(
+    clap::ArgGroup::new("info_listing")
+        .args(["info_only", "list_qualities_table", "list_subtitles"])
+        .multiple(true)
+))]
 pub struct Cli {
     /// Video URL to download (supports bilibili BV/av/ep/ss)
-    /// Optional when using --login-qrcode or --login-tv
-    #[arg(required_unless_present_any = ["login_qrcode", "login_tv"])]
+    /// Optional when using --batch-file, --retry-failed, --login-qrcode, --login-tv, --login-sms, --remux or --test-connection
+    #[arg(required_unless_present_any = ["batch_file", "retry_failed", "login_qrcode", "login_tv", "login_sms", "remux", "test_connection"])]
     pub url: Option<String>,
 
+    /// Read URLs from a file ("-" for stdin), one per line; blank lines and lines
+    /// starting with "#" are ignored. All URLs share the same options
+    #[arg(short = 'a', long, value_name = "FILE", conflicts_with = "url")]
+    pub batch_file: Option<String>,
+
+    /// Keep downloading the remaining URLs of a batch file, or the remaining videos of a
+    /// collection, when one fails
+    #[arg(long)]
+    pub continue_on_error: bool,
+
+    /// Write the URLs and pages that failed to FILE (batch file format) for --retry-failed;
+    /// the file is removed when nothing failed
+    #[arg(long, value_name = "FILE")]
+    pub failures_file: Option<PathBuf>,
+
+    /// Retry exactly the URLs and pages listed in a file written by --failures-file, using
+    /// the current options. Keeps going when one of them fails again
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["url", "batch_file"])]
+    pub retry_failed: Option<PathBuf>,
+
     /// Quality priority (comma-separated, e.g., "1080P,720P,480P")
     #[arg(short = 'q', long)]
     pub quality: Option<String>,
@@ -20,38 +45,288 @@ pub struct Cli {
     #[arg(short = 'c', long)]
     pub codec: Option<String>,
 
+    /// Stream sort expression like yt-dlp's -S (e.g. "res,fps,codec:hevc,br"; "+size" prefers smaller).
+    /// Keys: res, fps, codec[:name], br, size, quality[:name]. Overrides --quality/--codec
+    #[arg(short = 'S', long, value_name = "EXPR")]
+    pub format_sort: Option<crate::types::FormatSort>,
+
+    /// Only download video streams with this frame rate (e.g. 60); ignored if none match
+    #[arg(long, value_name = "N")]
+    pub fps: Option<u32>,
+
     /// Output file path or template
     #[arg(short = 'o', long)]
     pub output: Option<String>,
 
+    /// File name template for each page of a multi-page video (e.g. "<pageNumberWithZero>-<pageTitle>");
+    /// replaces the default "P01_<pageTitle>" when --output is a directory or not given
+    #[arg(long, value_name = "TEMPLATE")]
+    pub page_template: Option<String>,
+
+    /// Add N to the page numbers used in file names (P01, <pageNumber>), so a series archived
+    /// over several runs keeps continuous numbering
+    #[arg(long, value_name = "N", default_value = "0")]
+    pub page_number_offset: usize,
+
+    /// What to do when an output file already exists (rename appends " (2)", rename-id appends
+    /// "_<bvid>")
+    #[arg(long, value_name = "STRATEGY", default_value = "overwrite", value_parser = ["rename", "rename-id", "overwrite", "skip", "error"])]
+    pub on_collision: String,
+
+    /// What to do with existing danmaku/subtitle sidecar files: follow the video (written only when the
+    /// video is), overwrite (refresh them even when the existing video is skipped) or keep them
+    #[arg(long, value_name = "POLICY", default_value = "follow", value_parser = ["follow", "overwrite", "keep"])]
+    pub overwrite_policy: String,
+
+    /// Put each download in a subdirectory named after the uploader or the upload date
+    /// (inserted between the output directory and the file name)
+    #[arg(long, value_name = "BY", default_value = "none", value_parser = ["uploader", "date", "none"])]
+    pub organize_by: String,
+
+    /// Container of the final muxed file (mp4, mkv or mov); replaces the output extension
+    #[arg(long, value_name = "FORMAT", value_parser = ["mp4", "mkv", "mov"])]
+    pub merge_output_format: Option<String>,
+
     /// Cookie string for authentication
     #[arg(long)]
     pub cookie: Option<String>,
 
+    /// Persist cookies set by the server (e.g. buvid3) to this file and reuse them across runs
+    #[arg(long, value_name = "FILE")]
+    pub cookie_jar: Option<PathBuf>,
+
     /// Access token for authentication
     #[arg(long)]
     pub access_token: Option<String>,
 
+    /// Audio language to download for multi-language bangumi (e.g. "ja", "zh-Hans", "original");
+    /// "all" keeps the original audio as the default track and, with MKV output, also embeds
+    /// every other language
+    #[arg(long, value_name = "CODE")]
+    pub audio_lang: Option<String>,
+
+    /// List available audio languages for each page (no download)
+    #[arg(long)]
+    pub list_audio_languages: bool,
+
+    /// Report which qualities the current auth and API mode can actually download (no download)
+    #[arg(long)]
+    pub simulate_quality: bool,
+
+    /// Parse and select streams with real API requests, then report the streams, estimated
+    /// sizes and output paths of each page instead of downloading and muxing
+    #[arg(long, visible_alias = "no-download")]
+    pub simulate: bool,
+
     /// Select specific pages (e.g., "1", "1,2,3", "1-5", "ALL")
     #[arg(short = 'p', long)]
     pub pages: Option<String>,
 
+    /// Download only the episode from the given ep URL (instead of the whole season)
+    #[arg(long, conflicts_with = "pages")]
+    pub this_episode_only: bool,
+
+    /// Include bangumi extras (PV, trailers, special sections) as additional pages
+    #[arg(long)]
+    pub include_extras: bool,
+
+    /// Only download videos published on or after this date (YYYY-MM-DD, Beijing time)
+    /// from UP space, favorites, series and watch-later lists
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    pub since: Option<String>,
+
+    /// Only download videos published on or before this date (YYYY-MM-DD, Beijing time)
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    pub until: Option<String>,
+
+    /// Skip batch videos whose estimated size (from the selected streams) is larger than this
+    /// (e.g. "2G", "500M")
+    #[arg(long, value_name = "SIZE", value_parser = parse_filesize)]
+    pub max_filesize: Option<u64>,
+
+    /// Skip batch videos whose estimated size is smaller than this (e.g. "10M")
+    #[arg(long, value_name = "SIZE", value_parser = parse_filesize)]
+    pub min_filesize: Option<u64>,
+
+    /// Always show a summary and ask for confirmation before a batch download
+    /// (asked automatically for batches of more than 50 videos)
+    #[arg(long, conflicts_with = "yes")]
+    pub confirm: bool,
+
+    /// Never ask for confirmation before batch downloads
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+
     /// Number of download threads
     #[arg(short = 't', long, default_value = "4")]
     pub threads: usize,
 
+    /// Number of download threads for the video stream (defaults to --threads)
+    #[arg(long, value_name = "N")]
+    pub video_threads: Option<usize>,
+
+    /// Number of download threads for the audio stream (defaults to --threads)
+    #[arg(long, value_name = "N")]
+    pub audio_threads: Option<usize>,
+
+    /// Write buffer size in bytes when saving downloads to disk (4096 to 67108864, default 1 MiB).
+    /// Larger values mean fewer disk writes on fast connections, at the cost of memory per download
+    #[arg(long, value_name = "BYTES", default_value = "1048576", value_parser = clap::value_parser!(u64).range(4096..=67108864))]
+    pub buffer_size: u64,
+
+    /// Only connect over IPv4 (on dual-stack networks where IPv6 is slow)
+    #[arg(long, conflicts_with = "force_ipv6")]
+    pub force_ipv4: bool,
+
+    /// Only connect over IPv6
+    #[arg(long)]
+    pub force_ipv6: bool,
+
+    /// Number of pages of a multi-page video to download at the same time
+    #[arg(long, value_name = "N", default_value = "1", value_parser = clap::value_parser!(u32).range(1..=16))]
+    pub concurrent_pages: u32,
+
+    /// Delay between API requests when fetching batches (favorites, space, series), in milliseconds
+    #[arg(long, value_name = "MS", default_value = "200")]
+    pub api_delay_ms: u64,
+
+    /// Cache parsed video info on disk for this many seconds, per URL and login
+    /// (off by default; 0 disables the cache)
+    #[arg(long, value_name = "SECONDS")]
+    pub cache_ttl: Option<u64>,
+
+    /// Re-check the login every N videos of a batch, so an expired cookie doesn't
+    /// silently drop the quality of the rest of a long job (off by default)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub cookie_check_interval: Option<u32>,
+
+    /// What to do when --cookie-check-interval finds the login lost
+    #[arg(long, value_name = "ACTION", default_value = "warn", value_parser = ["warn", "abort"], requires = "cookie_check_interval")]
+    pub on_login_lost: String,
+
     /// Skip subtitle download
     #[arg(long)]
     pub skip_subtitle: bool,
 
+    /// Only download subtitles in these languages (comma-separated codes, e.g. "zh-Hans,en"; "all" for every track)
+    #[arg(long, value_name = "CODES")]
+    pub subtitle_lang: Option<String>,
+
     /// Skip cover download
     #[arg(long)]
     pub skip_cover: bool,
 
+    /// Save the cover image next to the output file
+    #[arg(long)]
+    pub write_thumbnail: bool,
+
+    /// Cover sizes to save (comma-separated widths or WxH, e.g. "1920,480x270"); original size if omitted
+    #[arg(long, value_name = "SIZES", requires = "write_thumbnail")]
+    pub thumbnail_size: Option<String>,
+
+    /// Cover image format (jpg, png or webp)
+    #[arg(long, value_name = "FORMAT", requires = "write_thumbnail", value_parser = ["jpg", "png", "webp"])]
+    pub thumbnail_format: Option<String>,
+
+    /// Write the video's tags to "<output>.tags.txt" (one per line)
+    #[arg(long)]
+    pub write_tags: bool,
+
+    /// Embed everything: chapters, cover, subtitles, metadata tags, plus a .nfo next to the output
+    #[arg(long)]
+    pub embed_metadata: bool,
+
+    /// Don't embed chapters
+    #[arg(long)]
+    pub no_embed_chapters: bool,
+
+    /// Don't embed the cover (with --embed-metadata)
+    #[arg(long)]
+    pub no_embed_cover: bool,
+
+    /// Save subtitles as .srt files next to the output instead of embedding them
+    #[arg(long)]
+    pub no_embed_subs: bool,
+
+    /// Don't write title/uploader/date/description tags into the file (with --embed-metadata)
+    #[arg(long)]
+    pub no_embed_tags: bool,
+
+    /// Don't write the .nfo file (with --embed-metadata)
+    #[arg(long)]
+    pub no_embed_nfo: bool,
+
+    /// Generate a thumbnail grid ("<output>.contact.jpg") from the muxed file with ffmpeg
+    #[arg(long, conflicts_with = "skip_mux")]
+    pub gen_contact_sheet: bool,
+
+    /// Number of thumbnail columns in the contact sheet
+    #[arg(long, value_name = "N", default_value = "4", value_parser = clap::value_parser!(u32).range(1..=20))]
+    pub contact_sheet_cols: u32,
+
+    /// Number of thumbnail rows in the contact sheet
+    #[arg(long, value_name = "N", default_value = "4", value_parser = clap::value_parser!(u32).range(1..=20))]
+    pub contact_sheet_rows: u32,
+
     /// Skip muxing (keep separate video and audio files)
     #[arg(long)]
     pub skip_mux: bool,
 
+    /// Join the selected pages of a multi-page video into one output file (lossless, via
+    /// ffmpeg's concat demuxer); parts with different resolutions are kept as separate files
+    #[arg(long, conflicts_with = "skip_mux")]
+    pub concat_pages: bool,
+
+    /// With --skip-mux, copy each stream into its own playable file (.video.mp4 / .audio.m4a,
+    /// or .audio.flac for lossless audio) instead of keeping the raw fragmented .m4s
+    #[arg(long, alias = "keep-fragments", requires = "skip_mux")]
+    pub remux_each: bool,
+
+    /// Only download this time range, e.g. "1:30-2:45" or "3600-3900" (HH:MM:SS, MM:SS or seconds).
+    /// Fetches just the DASH segments covering it, then trims with ffmpeg (exact cuts need --reencode)
+    #[arg(long, value_name = "START-END", value_parser = parse_clip, conflicts_with = "skip_mux")]
+    pub clip: Option<crate::core::clip::ClipRange>,
+
+    /// Re-encode the video with this codec while muxing (e.g. "avc", "hevc", "libx264").
+    /// By default streams are copied, which preserves Dolby Vision/HDR metadata and timestamps
+    #[arg(long, value_name = "CODEC")]
+    pub reencode: Option<String>,
+
+    /// Re-encode the audio with this codec (e.g. "aac"); audio is copied if omitted
+    #[arg(long, value_name = "CODEC", requires = "reencode")]
+    pub reencode_audio: Option<String>,
+
+    /// CRF (constant quality) for re-encoding
+    #[arg(long, requires = "reencode")]
+    pub crf: Option<u32>,
+
+    /// Video bitrate for re-encoding (e.g. "4M"), overrides --crf
+    #[arg(long, value_name = "RATE", requires = "reencode")]
+    pub video_bitrate: Option<String>,
+
+    /// Keep the temp directory (raw .m4s, subtitles, cover) after download and print its path
+    #[arg(long)]
+    pub keep_temp: bool,
+
+    /// On Ctrl-C or SIGTERM, keep the unfinished download so running the same command again
+    /// resumes it (resume), or delete it so the next run starts fresh (clean)
+    #[arg(long, value_name = "POLICY", default_value = "resume", value_parser = ["resume", "clean"])]
+    pub on_interrupt: String,
+
+    /// Discard unfinished .part downloads and start them from zero instead of resuming
+    /// (a .part is also restarted automatically when its size or tail bytes don't check out)
+    #[arg(long)]
+    pub force_overwrite_partial: bool,
+
+    /// Verify the muxed output with ffmpeg after download (keeps temp files on failure)
+    #[arg(long)]
+    pub verify: bool,
+
+    /// Write a SHA-256 checksum of each output file to "<output>.sha256"
+    /// (an existing checksum file is compared against the new download first)
+    #[arg(long)]
+    pub checksum: bool,
+
     /// Interactive mode for quality selection
     #[arg(short = 'i', long)]
     pub interactive: bool,
@@ -60,14 +335,58 @@ pub struct Cli {
     #[arg(long)]
     pub config_file: Option<PathBuf>,
 
+    /// Named settings group from the config file ([profiles.<name>]); CLI flags still take priority
+    #[arg(long, alias = "config-profile", value_name = "NAME")]
+    pub profile: Option<String>,
+
     /// Enable verbose logging
     #[arg(short = 'v', long)]
     pub verbose: bool,
 
-    /// Show video info only (no download)
+    /// Language of the output messages (zh or en, defaults to the config `lang` or system locale)
+    #[arg(long, value_name = "LANG", value_parser = ["zh", "en"])]
+    pub lang: Option<String>,
+
+    /// Open the downloaded file with the system default player (or [paths] player / --player)
+    #[arg(long)]
+    pub play_after: bool,
+
+    /// Player command for --play-after (e.g. "mpv --fs"), overrides [paths] player
+    #[arg(long, value_name = "COMMAND", requires = "play_after")]
+    pub player: Option<String>,
+
+    /// With --play-after, open the first downloaded file of a batch
+    #[arg(long, requires = "play_after", conflicts_with = "open_last")]
+    pub open_first: bool,
+
+    /// With --play-after, open the last downloaded file of a batch (default)
+    #[arg(long, requires = "play_after")]
+    pub open_last: bool,
+
+    /// Show video info only (no download); batches list only the titles
     #[arg(long)]
     pub info_only: bool,
 
+    /// Show video info with a table of each page's streams (quality, codec, fps,
+    /// bitrate, estimated size) without downloading; batches list only the first video
+    #[arg(long)]
+    pub list_qualities_table: bool,
+
+    /// List every video of a batch in full instead of only the first. With --info-only,
+    /// shows each video's info and stream table (like --list-qualities-table)
+    #[arg(long, requires = "info_listing")]
+    pub info_all: bool,
+
+    /// List the subtitle languages available for the selected pages without downloading;
+    /// batches list only the first video unless --info-all is given
+    #[arg(long)]
+    pub list_subtitles: bool,
+
+    /// List the output path of every selected page (of every video in a batch), flagging
+    /// files that already exist or would be written twice, then exit without downloading
+    #[arg(long)]
+    pub preview_paths: bool,
+
     /// Path to ffmpeg binary
     #[arg(long)]
     pub ffmpeg_path: Option<PathBuf>,
@@ -84,14 +403,58 @@ pub struct Cli {
     #[arg(long)]
     pub use_intl_api: bool,
 
+    /// Prefer overseas-friendly CDN mirrors (akamai / *ov hosts) over domestic ones
+    /// (implied by --use-intl-api)
+    #[arg(long)]
+    pub prefer_intl_cdn: bool,
+
     /// Download danmaku (bullet comments)
     #[arg(long)]
     pub download_danmaku: bool,
 
+    /// Save the video's comments to a .comments.json file next to the output
+    #[arg(long)]
+    pub download_comments: bool,
+
+    /// Also fetch every reply of each comment (one extra request per page of replies)
+    #[arg(long, requires = "download_comments")]
+    pub comment_replies: bool,
+
+    /// Stop after this many top-level comments (the newest ones are kept)
+    #[arg(long, value_name = "N", requires = "download_comments")]
+    pub max_comments: Option<usize>,
+
     /// Danmaku format (xml or ass)
     #[arg(long, default_value = "ass")]
     pub danmaku_format: String,
 
+    /// Also keep the unconverted danmaku source (the raw XML from the API) as a `.raw.xml`
+    /// sidecar, for analysis without the lossy ASS conversion
+    #[arg(long, requires = "download_danmaku")]
+    pub keep_raw_danmaku: bool,
+
+    /// Font for ASS danmaku and embedded subtitles: a font name or a font file path
+    /// (default: [danmaku]/[subtitle] font from the config, then a system CJK font)
+    #[arg(long, value_name = "FONT")]
+    pub font: Option<String>,
+
+    /// For multi-page videos, also write one danmaku file with every selected page shifted
+    /// onto a single timeline (for players of the parts concatenated in page order)
+    #[arg(long, requires = "download_danmaku")]
+    pub danmaku_merge: bool,
+
+    /// Keep at most this many comments in ASS danmaku, sampled evenly over the timeline
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub danmaku_max: Option<u32>,
+
+    /// Keep at most this many comments per second in ASS danmaku (default: 20)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub danmaku_per_second: Option<u32>,
+
+    /// Convert every comment to ASS, ignoring the danmaku density caps
+    #[arg(long, conflicts_with_all = ["danmaku_max", "danmaku_per_second"])]
+    pub no_danmaku_limit: bool,
+
     /// Login using QR code (Web mode)
     #[arg(long, conflicts_with = "login_tv")]
     pub login_qrcode: bool,
@@ -100,6 +463,24 @@ pub struct Cli {
     #[arg(long, conflicts_with = "login_qrcode")]
     pub login_tv: bool,
 
+    /// Login with an SMS verification code (Web mode, prompts for the phone number,
+    /// captcha result and code); useful on headless servers
+    #[arg(long, conflicts_with_all = ["login_qrcode", "login_tv"])]
+    pub login_sms: bool,
+
+    /// Phone number for --login-sms (asked interactively if not given)
+    #[arg(long, value_name = "NUMBER", requires = "login_sms")]
+    pub phone: Option<String>,
+
+    /// Country calling code of the phone number for --login-sms
+    #[arg(long, value_name = "CODE", default_value = "86", requires = "login_sms")]
+    pub country_code: u32,
+
+    /// Draw the login QR code with plain ASCII characters,
+    /// for terminals that mangle block characters or colors
+    #[arg(long)]
+    pub login_qr_ascii: bool,
+
     /// Use aria2c for downloading (faster for large files)
     #[arg(long)]
     pub use_aria2c: bool,
@@ -112,12 +493,107 @@ pub struct Cli {
     #[arg(long)]
     pub aria2c_args: Option<String>,
 
+    /// Number of pieces aria2c splits each file into (default: 16)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub aria2c_split: Option<u32>,
+
+    /// Maximum aria2c connections per server, 1-16 (default: 16)
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..=16))]
+    pub aria2c_max_connection_per_server: Option<u32>,
+
+    /// Minimum size of each aria2c piece, 1M-1024M (default: 5M)
+    #[arg(long, value_name = "SIZE", value_parser = parse_split_size)]
+    pub aria2c_min_split_size: Option<u64>,
+
+    /// Send downloads to a running aria2c daemon over JSON-RPC (e.g. "http://localhost:6800/jsonrpc")
+    /// instead of starting aria2c for each file; implies --use-aria2c and falls back to the
+    /// aria2c command line when the daemon is unreachable
+    #[arg(long, value_name = "URL")]
+    pub aria2c_rpc: Option<String>,
+
+    /// Secret token of the aria2c RPC interface (--rpc-secret)
+    #[arg(long, value_name = "SECRET", requires = "aria2c_rpc")]
+    pub aria2c_rpc_secret: Option<String>,
+
     /// Use MP4Box for muxing (recommended for Dolby Vision with FFmpeg < 5.0)
     #[arg(long)]
     pub use_mp4box: bool,
+
+    /// Check connectivity to the Bilibili API/CDN hosts, proxy, login and region, then exit
+    #[arg(long, conflicts_with_all = ["url", "remux"])]
+    pub test_connection: bool,
+
+    /// Mux already-downloaded video and audio files without any network access
+    /// (e.g., "--remux title.video.m4s title.audio.m4s -o title.mp4"); honours --merge-output-format
+    #[arg(long, num_args = 2, value_names = ["VIDEO", "AUDIO"], conflicts_with = "url")]
+    pub remux: Option<Vec<PathBuf>>,
+
+    /// Subtitle file to embed when remuxing (can be repeated)
+    #[arg(long, requires = "remux")]
+    pub remux_subtitle: Vec<PathBuf>,
+
+    /// Chapters JSON file to embed when remuxing (array of {title, start, end})
+    #[arg(long, requires = "remux")]
+    pub remux_chapters: Option<PathBuf>,
+
+    /// Cover image to embed when remuxing
+    #[arg(long, requires = "remux")]
+    pub remux_cover: Option<PathBuf>,
 }
 
 impl Cli {
+    /// Fill settings not given on the command line from the config file
+    /// (config defaults, merged with the selected profile)
+    pub fn apply_config_defaults(
+        &mut self,
+        config: &crate::utils::config::Config,
+        matches: &clap::ArgMatches,
+    ) {
+        if self.quality.is_none() {
+            self.quality = config.default_quality.as_ref().map(|q| q.join(","));
+        }
+        if self.codec.is_none() {
+            self.codec = config.default_codec.as_ref().map(|c| c.join(","));
+        }
+        if matches.value_source("threads") != Some(clap::parser::ValueSource::CommandLine) {
+            if let Some(threads) = config.thread_count {
+                self.threads = threads;
+            }
+        }
+        if self.lang.is_none() {
+            self.lang = config.lang.clone();
+        }
+        if !self.embed_metadata {
+            self.embed_metadata = config.embed_metadata.unwrap_or(false);
+        }
+        if self.audio_lang.is_none() {
+            self.audio_lang = config.audio_lang.clone();
+        }
+        if !self.download_danmaku {
+            self.download_danmaku = config.download_danmaku.unwrap_or(false);
+        }
+        if matches.value_source("danmaku_format") != Some(clap::parser::ValueSource::CommandLine) {
+            if let Some(ref format) = config.danmaku_format {
+                self.danmaku_format = format.clone();
+            }
+        }
+    }
+
+    /// 嵌入选项：`--embed-metadata` 全部开启，`--no-embed-*` 逐项关闭
+    pub fn get_embed_options(&self) -> crate::core::muxer::EmbedOptions {
+        let mut embed = if self.embed_metadata {
+            crate::core::muxer::EmbedOptions::all()
+        } else {
+            crate::core::muxer::EmbedOptions::default()
+        };
+        embed.chapters &= !self.no_embed_chapters;
+        embed.cover &= !self.no_embed_cover;
+        embed.subtitles &= !self.no_embed_subs;
+        embed.tags &= !self.no_embed_tags;
+        embed.nfo &= !self.no_embed_nfo;
+        embed
+    }
+
     pub fn parse_quality_priority(&self) -> Vec<String> {
         if let Some(ref q) = self.quality {
             q.split(',').map(|s| s.trim().to_string()).collect()
@@ -134,45 +610,42 @@ impl Cli {
         }
     }
 
+    /// Subtitle languages to download (None means all)
+    pub fn parse_subtitle_langs(&self) -> Option<Vec<String>> {
+        let langs: Vec<String> = self
+            .subtitle_lang
+            .as_deref()?
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
+        if langs.is_empty() || langs.iter().any(|l| l.eq_ignore_ascii_case("all")) {
+            None
+        } else {
+            Some(langs)
+        }
+    }
+
     pub fn parse_pages(&self) -> Option<Vec<usize>> {
-        if let Some(ref pages_str) = self.pages {
-            if pages_str.to_uppercase() == "ALL" {
-                return None; // None means all pages
-            }
+        parse_page_spec(self.pages.as_deref()?)
+    }
 
-            let mut pages = Vec::new();
-
-            for part in pages_str.split(',') {
-                let part = part.trim();
-
-                if part.contains('-') {
-                    // Range: "1-5"
-                    let range: Vec<&str> = part.split('-').collect();
-                    if range.len() == 2 {
-                        if let (Ok(start), Ok(end)) =
-                            (range[0].parse::<usize>(), range[1].parse::<usize>())
-                        {
-                            for i in start..=end {
-                                pages.push(i);
-                            }
-                        }
-                    }
-                } else {
-                    // Single page: "1"
-                    if let Ok(page) = part.parse::<usize>() {
-                        pages.push(page);
-                    }
-                }
-            }
+    /// Re-encode options for the muxer (None means stream copy)
+    pub fn get_reencode_options(&self) -> Option<crate::core::muxer::ReencodeOptions> {
+        self.reencode
+            .as_ref()
+            .map(|codec| crate::core::muxer::ReencodeOptions {
+                video_codec: codec.clone(),
+                audio_codec: self.reencode_audio.clone(),
+                crf: self.crf,
+                video_bitrate: self.video_bitrate.clone(),
+            })
+    }
 
-            if pages.is_empty() {
-                None
-            } else {
-                Some(pages)
-            }
-        } else {
-            None
-        }
+    /// Whether the user explicitly chose an API mode
+    pub fn has_explicit_api_mode(&self) -> bool {
+        self.use_tv_api || self.use_app_api || self.use_intl_api
     }
 
     pub fn get_api_mode(&self) -> crate::platform::bilibili::ApiMode {
@@ -189,6 +662,23 @@ impl Cli {
         }
     }
 
+    /// ASS 弹幕的密度限制（`--danmaku-max`、`--danmaku-per-second`、`--no-danmaku-limit`）
+    pub fn get_danmaku_limit(&self) -> crate::core::danmaku::DanmakuLimit {
+        use crate::core::danmaku::DanmakuLimit;
+
+        if self.no_danmaku_limit {
+            return DanmakuLimit::unlimited();
+        }
+        let default = DanmakuLimit::default();
+        DanmakuLimit {
+            per_second: self
+                .danmaku_per_second
+                .map(|n| n as usize)
+                .or(default.per_second),
+            total: self.danmaku_max.map(|n| n as usize).or(default.total),
+        }
+    }
+
     pub fn get_danmaku_format(&self) -> crate::core::danmaku::DanmakuFormat {
         use crate::core::danmaku::DanmakuFormat;
         
@@ -199,9 +689,79 @@ impl Cli {
         }
     }
 
+    pub fn get_collision_strategy(&self) -> crate::utils::file::CollisionStrategy {
+        use crate::utils::file::CollisionStrategy;
+
+        match self.on_collision.to_lowercase().as_str() {
+            "rename" => CollisionStrategy::Rename,
+            "rename-id" => CollisionStrategy::RenameId,
+            "skip" => CollisionStrategy::Skip,
+            "error" => CollisionStrategy::Error,
+            _ => CollisionStrategy::Overwrite,
+        }
+    }
+
+    pub fn get_ip_version(&self) -> crate::utils::http::IpVersion {
+        use crate::utils::http::IpVersion;
+
+        if self.force_ipv4 {
+            IpVersion::V4
+        } else if self.force_ipv6 {
+            IpVersion::V6
+        } else {
+            IpVersion::Auto
+        }
+    }
+
+    pub fn get_interrupt_policy(&self) -> crate::utils::signal::InterruptPolicy {
+        use crate::utils::signal::InterruptPolicy;
+
+        match self.on_interrupt.to_lowercase().as_str() {
+            "clean" => InterruptPolicy::Clean,
+            _ => InterruptPolicy::Resume,
+        }
+    }
+
+    pub fn get_sidecar_policy(&self) -> crate::utils::file::SidecarPolicy {
+        use crate::utils::file::SidecarPolicy;
+
+        match self.overwrite_policy.to_lowercase().as_str() {
+            "overwrite" => SidecarPolicy::Overwrite,
+            "keep" => SidecarPolicy::Keep,
+            _ => SidecarPolicy::Follow,
+        }
+    }
+
+    pub fn get_organize_by(&self) -> crate::utils::file::OrganizeBy {
+        use crate::utils::file::OrganizeBy;
+
+        match self.organize_by.to_lowercase().as_str() {
+            "uploader" => OrganizeBy::Uploader,
+            "date" => OrganizeBy::Date,
+            _ => OrganizeBy::None,
+        }
+    }
+
+    pub fn get_merge_output_format(&self) -> Option<crate::core::muxer::MergeOutputFormat> {
+        use crate::core::muxer::MergeOutputFormat;
+
+        self.merge_output_format
+            .as_deref()
+            .map(|format| match format.to_lowercase().as_str() {
+                "mkv" => MergeOutputFormat::Mkv,
+                "mov" => MergeOutputFormat::Mov,
+                _ => MergeOutputFormat::Mp4,
+            })
+    }
+
     /// Check if login is requested
     pub fn needs_login(&self) -> bool {
-        self.login_qrcode || self.login_tv
+        self.login_qrcode || self.login_tv || self.login_sms
+    }
+
+    /// Check if remux mode is requested
+    pub fn is_remux(&self) -> bool {
+        self.remux.is_some()
     }
 
     /// Get the API mode for login (if login is requested)
@@ -210,7 +770,7 @@ impl Cli {
         
         if self.login_tv {
             Some(ApiMode::TV)
-        } else if self.login_qrcode {
+        } else if self.login_qrcode || self.login_sms {
             Some(ApiMode::Web)
         } else {
             None
@@ -218,3 +778,87 @@ impl Cli {
     }
 
 }
+
+/// 校验 `--since`/`--until` 的日期格式（`YYYY-MM-DD`）
+fn parse_date(value: &str) -> Result<String, String> {
+    let parts: Vec<&str> = value.split('-').collect();
+    let valid = match parts.as_slice() {
+        [year, month, day] => {
+            year.len() == 4
+                && month.len() == 2
+                && day.len() == 2
+                && parts.iter().all(|p| p.bytes().all(|b| b.is_ascii_digit()))
+                && (1..=12).contains(&month.parse::<u32>().unwrap_or(0))
+                && (1..=31).contains(&day.parse::<u32>().unwrap_or(0))
+        }
+        _ => false,
+    };
+
+    if valid {
+        Ok(value.to_string())
+    } else {
+        Err(format!("invalid date '{}', expected YYYY-MM-DD", value))
+    }
+}
+
+/// 解析 `--max-filesize`/`--min-filesize` 的大小（如 `2G`、`10M`）
+fn parse_filesize(value: &str) -> Result<u64, String> {
+    crate::utils::file::parse_size(value)
+        .ok_or_else(|| format!("invalid size '{}', expected e.g. 500K, 10M or 2G", value))
+}
+
+/// aria2c 只接受 1M-1024M 的最小分块大小
+fn parse_split_size(value: &str) -> Result<u64, String> {
+    let size = parse_filesize(value)?;
+    if !(1 << 20..=1 << 30).contains(&size) {
+        return Err(format!("split size '{}' must be between 1M and 1024M", value));
+    }
+    Ok(size)
+}
+
+fn parse_clip(value: &str) -> Result<crate::core::clip::ClipRange, String> {
+    crate::core::clip::ClipRange::parse(value).ok_or_else(|| {
+        format!(
+            "invalid clip '{}', expected START-END with END after START (e.g. 1:30-2:45)",
+            value
+        )
+    })
+}
+
+/// 解析分P选择，如 `1,3-5`；`ALL` 或没有有效分P时返回 None（表示全部）
+pub fn parse_page_spec(pages_str: &str) -> Option<Vec<usize>> {
+    if pages_str.to_uppercase() == "ALL" {
+        return None; // None means all pages
+    }
+
+    let mut pages = Vec::new();
+
+    for part in pages_str.split(',') {
+        let part = part.trim();
+
+        if part.contains('-') {
+            // Range: "1-5"
+            let range: Vec<&str> = part.split('-').collect();
+            if range.len() == 2 {
+                if let (Ok(start), Ok(end)) =
+                    (range[0].parse::<usize>(), range[1].parse::<usize>())
+                {
+                    for i in start..=end {
+                        pages.push(i);
+                    }
+                }
+            }
+        } else {
+            // Single page: "1"
+            if let Ok(page) = part.parse::<usize>() {
+                pages.push(page);
+            }
+        }
+    }
+
+    if pages.is_empty() {
+        None
+    } else {
+        Some(pages)
+    }
+}
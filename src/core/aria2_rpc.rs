@@ -0,0 +1,318 @@
+// aria2 JSON-RPC 客户端：把下载交给常驻的 aria2c 进程（`aria2c --enable-rpc`）
+//
+// 相比每次下载启动一个 aria2c 进程，批量下载时没有进程启动开销，也可以多个
+// 程序共用同一个 aria2 实例。文件由 aria2 进程写入，因此它必须能访问输出目录
+
+use crate::error::{DownloaderError, Result};
+use indicatif::ProgressBar;
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// 查询下载状态的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// 健康检查（`aria2.getVersion`）和取消下载的超时
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+/// 单个下载的默认最长时间，超过后取消任务
+const DEFAULT_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(6 * 60 * 60);
+/// 连续查询状态失败多少次后放弃（aria2 繁忙或网络抖动时单次查询可能失败）
+const MAX_POLL_ERRORS: usize = 5;
+
+/// `aria2.tellStatus` 返回的下载状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aria2Status {
+    /// active、waiting、paused、error、complete 或 removed
+    pub status: String,
+    pub total_length: u64,
+    pub completed_length: u64,
+    pub error_message: Option<String>,
+}
+
+pub struct Aria2Rpc {
+    endpoint: String,
+    secret: Option<String>,
+    client: reqwest::Client,
+    /// 健康检查结果，只检查一次
+    healthy: Mutex<Option<bool>>,
+    /// 单个下载的最长时间
+    download_timeout: Duration,
+}
+
+/// 下载未完成就结束（出错、超时、Ctrl-C 取消）时从 aria2 中移除任务，
+/// 避免常驻的 aria2 继续在后台下载
+struct RemoveOnDrop {
+    rpc: Option<Aria2Rpc>,
+    gid: String,
+}
+
+impl RemoveOnDrop {
+    /// 下载已完成，不需要移除
+    fn disarm(&mut self) {
+        self.rpc = None;
+    }
+}
+
+impl Drop for RemoveOnDrop {
+    fn drop(&mut self) {
+        let Some(rpc) = self.rpc.take() else {
+            return;
+        };
+        let gid = std::mem::take(&mut self.gid);
+        let remove = async move {
+            // 已经出错停止的任务不能 forceRemove，忽略错误
+            if let Err(e) = rpc
+                .call("aria2.forceRemove", vec![json!(gid)], Some(HEALTH_CHECK_TIMEOUT))
+                .await
+            {
+                tracing::debug!("aria2 RPC forceRemove {} failed: {}", gid, e);
+            }
+        };
+        // 多线程运行时中同步等待，确保程序随后退出前任务已被移除
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+                tokio::task::block_in_place(|| handle.block_on(remove))
+            }
+            Ok(handle) => {
+                handle.spawn(remove);
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+impl Aria2Rpc {
+    /// `endpoint` 如 `http://localhost:6800/jsonrpc`，`secret` 对应 aria2 的 `--rpc-secret`
+    pub fn new(endpoint: &str, secret: Option<String>) -> Self {
+        Self {
+            endpoint: endpoint.to_string(),
+            secret: secret.filter(|s| !s.is_empty()),
+            client: reqwest::Client::new(),
+            healthy: Mutex::new(None),
+            download_timeout: DEFAULT_DOWNLOAD_TIMEOUT,
+        }
+    }
+
+    /// 单个下载的最长时间（默认6小时），超过后取消任务并返回错误
+    pub fn with_download_timeout(mut self, timeout: Duration) -> Self {
+        self.download_timeout = timeout;
+        self
+    }
+
+    /// 共用连接和密钥的副本，用于在 `Drop` 中取消任务
+    fn detached(&self) -> Self {
+        Self {
+            endpoint: self.endpoint.clone(),
+            secret: self.secret.clone(),
+            client: self.client.clone(),
+            healthy: Mutex::new(None),
+            download_timeout: self.download_timeout,
+        }
+    }
+
+    /// 调用 RPC 方法，设置了密钥时自动添加 `token:` 参数
+    async fn call(&self, method: &str, params: Vec<Value>, timeout: Option<Duration>) -> Result<Value> {
+        let mut all_params = Vec::with_capacity(params.len() + 1);
+        if let Some(ref secret) = self.secret {
+            all_params.push(json!(format!("token:{}", secret)));
+        }
+        all_params.extend(params);
+
+        let body = json!({
+            "jsonrpc": "2.0",
+            "id": "rvd",
+            "method": method,
+            "params": all_params,
+        });
+        let mut request = self.client.post(&self.endpoint).json(&body);
+        if let Some(timeout) = timeout {
+            request = request.timeout(timeout);
+        }
+        // aria2 出错时也返回JSON（HTTP 400），不检查状态码
+        let response: Value = request.send().await?.json().await?;
+
+        if let Some(error) = response.get("error") {
+            return Err(DownloaderError::DownloadFailed(format!(
+                "aria2 RPC {} failed: {}",
+                method,
+                error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error")
+            )));
+        }
+        response.get("result").cloned().ok_or_else(|| {
+            DownloaderError::Parse(format!("aria2 RPC {} returned no result", method))
+        })
+    }
+
+    /// aria2 的版本号，用于检查 RPC 是否可用
+    pub async fn version(&self) -> Result<String> {
+        let result = self
+            .call("aria2.getVersion", Vec::new(), Some(HEALTH_CHECK_TIMEOUT))
+            .await?;
+        Ok(result
+            .get("version")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string())
+    }
+
+    /// RPC 是否可用（只在第一次调用时检查，结果会缓存）
+    pub async fn is_available(&self) -> bool {
+        if let Some(healthy) = *self.healthy.lock().unwrap() {
+            return healthy;
+        }
+
+        let healthy = match self.version().await {
+            Ok(version) => {
+                tracing::info!("Using aria2 {} via RPC at {}", version, self.endpoint);
+                true
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "aria2 RPC at {} is unreachable ({}), falling back to the aria2c command line",
+                    self.endpoint,
+                    e
+                );
+                false
+            }
+        };
+        *self.healthy.lock().unwrap() = Some(healthy);
+        healthy
+    }
+
+    /// 提交下载（`aria2.addUri`），返回任务的 GID
+    pub async fn add_uri(&self, url: &str, output: &Path, options: Vec<(String, String)>) -> Result<String> {
+        let invalid = || DownloaderError::DownloadFailed("Invalid output path".to_string());
+        let dir = output.parent().and_then(Path::to_str).ok_or_else(invalid)?;
+        let out = output.file_name().and_then(|n| n.to_str()).ok_or_else(invalid)?;
+
+        let mut aria2_options = json!({
+            "dir": dir,
+            "out": out,
+            "allow-overwrite": "true",
+            "auto-file-renaming": "false",
+        });
+        let mut headers = Vec::new();
+        for (key, value) in options {
+            // header 可以出现多次，其他选项后出现的覆盖先出现的
+            if key == "header" {
+                headers.push(Value::String(value));
+            } else {
+                aria2_options[key] = Value::String(value);
+            }
+        }
+        if !headers.is_empty() {
+            aria2_options["header"] = Value::Array(headers);
+        }
+
+        let result = self
+            .call("aria2.addUri", vec![json!([url]), aria2_options], None)
+            .await?;
+        result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| DownloaderError::Parse("aria2.addUri returned no GID".to_string()))
+    }
+
+    /// 查询下载状态（`aria2.tellStatus`）
+    pub async fn tell_status(&self, gid: &str) -> Result<Aria2Status> {
+        let result = self
+            .call(
+                "aria2.tellStatus",
+                vec![json!(gid), json!(["status", "totalLength", "completedLength", "errorMessage"])],
+                None,
+            )
+            .await?;
+
+        // 数值以字符串返回
+        let number = |key: &str| {
+            result
+                .get(key)
+                .and_then(Value::as_str)
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0)
+        };
+        Ok(Aria2Status {
+            status: result
+                .get("status")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+            total_length: number("totalLength"),
+            completed_length: number("completedLength"),
+            error_message: result
+                .get("errorMessage")
+                .and_then(Value::as_str)
+                .filter(|m| !m.is_empty())
+                .map(str::to_string),
+        })
+    }
+
+    /// 提交下载并轮询直到完成，期间更新进度条
+    ///
+    /// 偶发的查询失败会重试；超过最长时间或返回错误（包括被取消）时从 aria2 中移除任务
+    pub async fn download(
+        &self,
+        url: &str,
+        output: &Path,
+        options: Vec<(String, String)>,
+        progress: Option<Arc<ProgressBar>>,
+    ) -> Result<()> {
+        let gid = self.add_uri(url, output, options).await?;
+        tracing::debug!("aria2 RPC download {} started: {}", gid, url);
+        let mut guard = RemoveOnDrop {
+            rpc: Some(self.detached()),
+            gid: gid.clone(),
+        };
+
+        let deadline = tokio::time::Instant::now() + self.download_timeout;
+        let mut poll_errors = 0;
+        loop {
+            if tokio::time::Instant::now() >= deadline {
+                return Err(DownloaderError::DownloadFailed(format!(
+                    "aria2 download {} did not finish within {}s",
+                    gid,
+                    self.download_timeout.as_secs()
+                )));
+            }
+
+            let status = match self.tell_status(&gid).await {
+                Ok(status) => {
+                    poll_errors = 0;
+                    status
+                }
+                Err(e) if poll_errors + 1 < MAX_POLL_ERRORS => {
+                    poll_errors += 1;
+                    tracing::debug!("aria2 RPC tellStatus {} failed ({}), retrying", gid, e);
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            if let Some(ref pb) = progress {
+                if status.total_length > 0 {
+                    pb.set_length(status.total_length);
+                }
+                pb.set_position(status.completed_length);
+            }
+
+            match status.status.as_str() {
+                "complete" => {
+                    guard.disarm();
+                    return Ok(());
+                }
+                "error" | "removed" => {
+                    return Err(DownloaderError::DownloadFailed(format!(
+                        "aria2 download {} {}: {}",
+                        gid,
+                        status.status,
+                        status.error_message.as_deref().unwrap_or("no error message")
+                    )))
+                }
+                _ => tokio::time::sleep(POLL_INTERVAL).await,
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+// 片段下载（`--clip`）：按 DASH 索引（sidx）只下载覆盖指定时间范围的字节
+//
+// B站的 DASH 流是分片 MP4：初始化段（ftyp + moov）之后是索引 sidx，再之后是
+// 若干 moof + mdat 片段。下载初始化段和所需片段拼接后即可由 FFmpeg 读取，
+// 片段开头不一定正好是所需时间，混流时再用 `-ss`/`-t` 精确裁剪
+
+use crate::error::{DownloaderError, Result};
+
+/// 要下载的时间范围（秒）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClipRange {
+    pub start: f64,
+    pub end: f64,
+}
+
+impl ClipRange {
+    /// 解析 `开始-结束`，时间可以是秒数、`MM:SS` 或 `HH:MM:SS`（秒可带小数），
+    /// 如 `90-150`、`1:30-2:30`、`1:02:03-1:05:00`
+    pub fn parse(value: &str) -> Option<Self> {
+        let (start, end) = value.split_once('-')?;
+        let start = parse_time(start)?;
+        let end = parse_time(end)?;
+        (end > start).then_some(Self { start, end })
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.end - self.start
+    }
+}
+
+/// 解析 `HH:MM:SS.xx`、`MM:SS` 或秒数
+fn parse_time(value: &str) -> Option<f64> {
+    let mut seconds = 0.0;
+    for part in value.trim().split(':') {
+        let number: f64 = part.parse().ok()?;
+        if !number.is_finite() || number < 0.0 {
+            return None;
+        }
+        seconds = seconds * 60.0 + number;
+    }
+    Some(seconds)
+}
+
+/// 解析 `0-1000` 形式的字节范围（含首尾）
+pub fn parse_byte_range(value: &str) -> Option<(u64, u64)> {
+    let (start, end) = value.split_once('-')?;
+    let start = start.trim().parse().ok()?;
+    let end = end.trim().parse().ok()?;
+    (end >= start).then_some((start, end))
+}
+
+/// sidx 中的一个片段
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SidxSegment {
+    /// 片段在文件中的起始字节
+    pub offset: u64,
+    pub size: u64,
+    /// 片段的开始时间与时长（秒）
+    pub start: f64,
+    pub duration: f64,
+}
+
+fn read_u32(data: &[u8], pos: usize) -> Option<u64> {
+    data.get(pos..pos + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as u64)
+}
+
+fn read_u64(data: &[u8], pos: usize) -> Option<u64> {
+    data.get(pos..pos + 8).map(|b| {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(b);
+        u64::from_be_bytes(bytes)
+    })
+}
+
+/// 解析 sidx 盒子（ISO/IEC 14496-12 8.16.3），`box_offset` 为盒子在文件中的起始字节
+pub fn parse_sidx(data: &[u8], box_offset: u64) -> Result<Vec<SidxSegment>> {
+    let invalid = |reason: &str| DownloaderError::Parse(format!("Invalid sidx box: {}", reason));
+
+    let box_size = read_u32(data, 0).ok_or_else(|| invalid("truncated header"))?;
+    if data.get(4..8) != Some(b"sidx".as_slice()) {
+        return Err(invalid("missing sidx header"));
+    }
+    let version = *data.get(8).ok_or_else(|| invalid("truncated"))?;
+    let timescale = read_u32(data, 16).ok_or_else(|| invalid("truncated"))? as f64;
+    if timescale == 0.0 {
+        return Err(invalid("zero timescale"));
+    }
+
+    let (earliest, first_offset, mut pos) = if version == 0 {
+        (read_u32(data, 20), read_u32(data, 24), 28)
+    } else {
+        (read_u64(data, 20), read_u64(data, 28), 36)
+    };
+    let (earliest, first_offset) = earliest
+        .zip(first_offset)
+        .ok_or_else(|| invalid("truncated"))?;
+
+    // reserved(16) + reference_count(16)
+    let count = data
+        .get(pos + 2..pos + 4)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| invalid("truncated"))?;
+    pos += 4;
+
+    // 片段偏移以 sidx 盒子结束处为基准
+    let mut offset = box_offset + box_size + first_offset;
+    let mut time = earliest;
+    let mut segments = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let reference = read_u32(data, pos).ok_or_else(|| invalid("truncated reference"))?;
+        let duration = read_u32(data, pos + 4).ok_or_else(|| invalid("truncated reference"))?;
+        pos += 12;
+
+        // 最高位为1表示引用的是下一级 sidx，B站的流只有一级
+        if reference & 0x8000_0000 != 0 {
+            return Err(invalid("hierarchical index is not supported"));
+        }
+        let size = reference & 0x7fff_ffff;
+        segments.push(SidxSegment {
+            offset,
+            size,
+            start: time as f64 / timescale,
+            duration: duration as f64 / timescale,
+        });
+        offset += size;
+        time += duration;
+    }
+
+    Ok(segments)
+}
+
+/// 覆盖时间范围的连续片段：返回 (起始字节, 结束字节（含）, 第一个片段的开始时间)
+pub fn byte_range_for_clip(segments: &[SidxSegment], clip: ClipRange) -> Option<(u64, u64, f64)> {
+    let covering: Vec<&SidxSegment> = segments
+        .iter()
+        .filter(|s| s.start + s.duration > clip.start && s.start < clip.end)
+        .collect();
+    let first = covering.first()?;
+    let last = covering.last()?;
+    Some((first.offset, last.offset + last.size - 1, first.start))
+}
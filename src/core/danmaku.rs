@@ -10,13 +10,101 @@ pub enum DanmakuFormat {
     Ass,
 }
 
+impl DanmakuFormat {
+    /// 弹幕文件的扩展名
+    pub fn extension(self) -> &'static str {
+        match self {
+            DanmakuFormat::Xml => "xml",
+            DanmakuFormat::Ass => "ass",
+        }
+    }
+}
+
+/// 保留的原始弹幕（未经转换的 XML）使用的扩展名
+pub const RAW_EXTENSION: &str = "raw.xml";
+
+/// 默认每秒最多保留的弹幕条数
+pub const DEFAULT_PER_SECOND: usize = 20;
+
+/// ASS 弹幕的密度限制
+///
+/// 热门视频的弹幕可达数十万条，全部转换既慢又会铺满画面。超出限制的弹幕按时间
+/// 均匀抽样丢弃，相同输入总是得到相同结果。XML 格式不受影响
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DanmakuLimit {
+    /// 同一秒内最多保留的条数（`--danmaku-per-second`）
+    pub per_second: Option<usize>,
+    /// 总共最多保留的条数（`--danmaku-max`）
+    pub total: Option<usize>,
+}
+
+impl DanmakuLimit {
+    /// 不做任何限制（`--no-danmaku-limit`）
+    pub fn unlimited() -> Self {
+        Self {
+            per_second: None,
+            total: None,
+        }
+    }
+}
+
+impl Default for DanmakuLimit {
+    fn default() -> Self {
+        Self {
+            per_second: Some(DEFAULT_PER_SECOND),
+            total: None,
+        }
+    }
+}
+
 /// 下载弹幕
+///
+/// 指定 `raw_output` 时同时写入解压后未经转换的原始 XML（`--keep-raw-danmaku`）
 pub async fn download_danmaku(
-    _client: &Arc<HttpClient>,
+    client: &Arc<HttpClient>,
     cid: &str,
     output: &Path,
     format: DanmakuFormat,
+    font: &str,
+    limit: DanmakuLimit,
+    raw_output: Option<&Path>,
 ) -> Result<()> {
+    let bytes = fetch_danmaku(client, cid).await?;
+    if !save_danmaku(&bytes, output, format, font, limit, raw_output).await? {
+        tracing::info!("No danmaku available for cid: {}", cid);
+    }
+
+    Ok(())
+}
+
+/// 将弹幕接口返回的原始数据转换后写入 `output`，没有弹幕时不写入任何文件并返回 `false`
+///
+/// 指定 `raw_output` 时同时写入解压后未经转换的原始 XML
+pub async fn save_danmaku(
+    bytes: &[u8],
+    output: &Path,
+    format: DanmakuFormat,
+    font: &str,
+    limit: DanmakuLimit,
+    raw_output: Option<&Path>,
+) -> Result<bool> {
+    let Some(content) = convert_danmaku(bytes, format, font, limit)? else {
+        return Ok(false);
+    };
+    if let Some(raw_output) = raw_output {
+        tokio::fs::write(crate::utils::file::long_path(raw_output), decode_danmaku(bytes)?).await?;
+        tracing::info!("Raw danmaku saved to: {:?}", raw_output);
+    }
+    tokio::fs::write(crate::utils::file::long_path(output), content).await?;
+    tracing::info!("Danmaku saved to: {:?}", output);
+
+    Ok(true)
+}
+
+/// 获取弹幕接口返回的原始数据（可能经过deflate/gzip压缩）
+///
+/// 使用单独的客户端，但与 `client` 使用相同的IP协议（`--force-ipv4` / `--force-ipv6`）
+pub async fn fetch_danmaku(client: &HttpClient, cid: &str) -> Result<Vec<u8>> {
     // 下载 XML 格式弹幕
     let api = format!("https://comment.bilibili.com/{}.xml", cid);
     tracing::debug!("Fetching danmaku from: {}", api);
@@ -29,8 +117,9 @@ pub async fn download_danmaku(
         .no_gzip()
         .no_deflate()
         .no_brotli()
+        .local_address(client.ip_version().local_address())
         .build()
-        .map_err(crate::error::DownloaderError::Network)?;
+        .map_err(crate::error::DownloaderError::from)?;
     
     let response = raw_client.get(&api).send().await?;
     
@@ -42,8 +131,25 @@ pub async fn download_danmaku(
     
     tracing::debug!("Response status: {}", response.status());
     
-    let bytes = response.bytes().await?;
-    
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// 将弹幕接口返回的原始数据（可能经过deflate/gzip压缩）转换为指定格式
+///
+/// 纯内存操作，没有弹幕时返回 `None`。`font` 为 ASS 样式使用的字体名称，`limit` 为
+/// ASS 弹幕的密度限制
+pub fn convert_danmaku(
+    bytes: &[u8],
+    format: DanmakuFormat,
+    font: &str,
+    limit: DanmakuLimit,
+) -> Result<Option<String>> {
+    let xml_content = decode_danmaku(bytes)?;
+    render_danmaku(&xml_content, format, font, limit)
+}
+
+/// 解压弹幕接口返回的原始数据，得到 XML 文本
+pub fn decode_danmaku(bytes: &[u8]) -> Result<String> {
     // Try to decode as UTF-8 directly first, or decompress if needed
     let xml_content = match String::from_utf8(bytes.to_vec()) {
         Ok(text) => text,
@@ -52,7 +158,7 @@ pub async fn download_danmaku(
             use flate2::read::DeflateDecoder;
             use std::io::Read;
             
-            let mut decoder = DeflateDecoder::new(&bytes[..]);
+            let mut decoder = DeflateDecoder::new(bytes);
             let mut decompressed = String::new();
             match decoder.read_to_string(&mut decompressed) {
                 Ok(_) => {
@@ -62,7 +168,7 @@ pub async fn download_danmaku(
                 Err(_) => {
                     // Try gzip as fallback
                     use flate2::read::GzDecoder;
-                    let mut decoder = GzDecoder::new(&bytes[..]);
+                    let mut decoder = GzDecoder::new(bytes);
                     let mut decompressed = String::new();
                     decoder.read_to_string(&mut decompressed)
                         .map_err(|e| crate::error::DownloaderError::DownloadFailed(
@@ -75,27 +181,64 @@ pub async fn download_danmaku(
         }
     };
 
+    Ok(xml_content)
+}
+
+/// 将 XML 弹幕转换为指定格式，没有弹幕时返回 `None`
+fn render_danmaku(
+    xml_content: &str,
+    format: DanmakuFormat,
+    font: &str,
+    limit: DanmakuLimit,
+) -> Result<Option<String>> {
     if xml_content.is_empty() || !xml_content.contains("<d ") {
-        tracing::info!("No danmaku available for cid: {}", cid);
-        return Ok(());
+        return Ok(None);
     }
 
-    match format {
-        DanmakuFormat::Xml => {
-            // 格式化并保存 XML
-            let formatted_xml = format_xml(&xml_content)?;
-            tokio::fs::write(output, formatted_xml).await?;
-            tracing::info!("Danmaku saved to: {:?}", output);
-        }
-        DanmakuFormat::Ass => {
-            // 转换为 ASS 格式
-            let ass_content = convert_xml_to_ass(&xml_content)?;
-            tokio::fs::write(output, ass_content).await?;
-            tracing::info!("Danmaku converted to ASS and saved to: {:?}", output);
+    let content = match format {
+        // 格式化 XML
+        DanmakuFormat::Xml => format_xml(xml_content)?,
+        // 转换为 ASS 格式
+        DanmakuFormat::Ass => convert_xml_to_ass(xml_content, font, limit)?,
+    };
+
+    Ok(Some(content))
+}
+
+/// 合并多P弹幕，与按分P顺序拼接后的视频时间轴对齐
+///
+/// `parts` 按分P顺序给出每个分P的 XML 弹幕及其时长（秒）。第 N 个分P的弹幕时间
+/// 加上前 N-1 个分P的总时长；合并后没有弹幕时返回 `None`
+pub fn merge_danmaku(
+    parts: &[(String, u64)],
+    format: DanmakuFormat,
+    font: &str,
+    limit: DanmakuLimit,
+) -> Result<Option<String>> {
+    let mut merged = String::from(r#"<?xml version="1.0" encoding="UTF-8"?><i>"#);
+    let mut offset = 0u64;
+    for (xml, duration) in parts {
+        for element in shift_danmaku(xml, offset as f64) {
+            merged.push_str(&element);
         }
+        offset += duration;
     }
+    merged.push_str("</i>");
 
-    Ok(())
+    render_danmaku(&merged, format, font, limit)
+}
+
+/// 将每条弹幕的出现时间后移 `offset` 秒，返回调整后的 `<d>` 元素
+fn shift_danmaku(xml: &str, offset: f64) -> Vec<String> {
+    let re = regex::Regex::new(r#"<d p="([^",]+)(,[^"]*)?">([^<]*)</d>"#).unwrap();
+
+    re.captures_iter(xml)
+        .map(|cap| {
+            let time = cap[1].parse::<f64>().unwrap_or(0.0) + offset;
+            let rest = cap.get(2).map_or("", |m| m.as_str());
+            format!(r#"<d p="{:.5}{}">{}</d>"#, time, rest, &cap[3])
+        })
+        .collect()
 }
 
 /// 格式化 XML 弹幕
@@ -166,9 +309,71 @@ fn parse_danmaku_xml(xml: &str) -> Result<Vec<DanmakuItem>> {
     Ok(items)
 }
 
-/// 转换 XML 弹幕为 ASS 格式
-fn convert_xml_to_ass(xml: &str) -> Result<String> {
-    let items = parse_danmaku_xml(xml)?;
+/// 按密度限制抽样弹幕，返回按出现时间排序的结果
+///
+/// 先在每一秒内均匀保留至多 `per_second` 条，再在全体中均匀保留至多 `total` 条；
+/// 同一时间的弹幕保持原有顺序，因此结果是确定的
+fn limit_danmaku(mut items: Vec<DanmakuItem>, limit: DanmakuLimit) -> Vec<DanmakuItem> {
+    items.sort_by(|a, b| a.time.total_cmp(&b.time));
+    let original = items.len();
+
+    if let Some(per_second) = limit.per_second {
+        let mut kept = Vec::with_capacity(items.len());
+        let mut bucket = Vec::new();
+        let mut second = None;
+        for item in items {
+            let current = item.time.max(0.0) as u64;
+            if second != Some(current) {
+                kept.extend(sample_evenly(std::mem::take(&mut bucket), per_second));
+                second = Some(current);
+            }
+            bucket.push(item);
+        }
+        kept.extend(sample_evenly(bucket, per_second));
+        items = kept;
+    }
+    if let Some(total) = limit.total {
+        items = sample_evenly(items, total);
+    }
+
+    if items.len() < original {
+        tracing::info!(
+            "Danmaku density limit kept {} of {} comments",
+            items.len(),
+            original
+        );
+    }
+    items
+}
+
+/// 从 `items` 中等间隔地保留至多 `max` 条，保持原有顺序
+fn sample_evenly<T>(items: Vec<T>, max: usize) -> Vec<T> {
+    let len = items.len();
+    if len <= max {
+        return items;
+    }
+    // 第 k 条保留的是下标 k * len / max，首条总会被保留
+    let mut next = 0;
+    let mut kept = 0;
+    items
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| {
+            if kept < max && *index == next {
+                kept += 1;
+                next = kept * len / max;
+                true
+            } else {
+                false
+            }
+        })
+        .map(|(_, item)| item)
+        .collect()
+}
+
+/// 转换 XML 弹幕为 ASS 格式，`font` 需要包含中文字形，否则播放器会显示为方框
+fn convert_xml_to_ass(xml: &str, font: &str, limit: DanmakuLimit) -> Result<String> {
+    let items = limit_danmaku(parse_danmaku_xml(xml)?, limit);
 
     let mut ass = String::new();
 
@@ -184,7 +389,11 @@ fn convert_xml_to_ass(xml: &str) -> Result<String> {
     // 样式定义
     ass.push_str("[V4+ Styles]\n");
     ass.push_str("Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n");
-    ass.push_str("Style: Default,Arial,36,&H00FFFFFF,&H00FFFFFF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,0,2,20,20,20,0\n");
+    // 字体名称中的逗号会破坏样式字段
+    ass.push_str(&format!(
+        "Style: Default,{},36,&H00FFFFFF,&H00FFFFFF,&H00000000,&H00000000,0,0,0,0,100,100,0,0,1,2,0,2,20,20,20,0\n",
+        font.replace(',', " ")
+    ));
     ass.push('\n');
 
     // 事件
@@ -1,14 +1,54 @@
+use crate::core::aria2_rpc::Aria2Rpc;
+use crate::core::clip::{self, ClipRange};
 use crate::error::{DownloaderError, Result};
-use crate::types::Auth;
-use crate::utils::http::HttpClient;
+use crate::types::{Auth, SegmentBase};
+use crate::utils::file;
+use crate::utils::http::{self, HttpClient, IpVersion};
 use futures::StreamExt;
 use indicatif::ProgressBar;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncWriteExt, BufWriter};
 use tokio::process::Command;
 
+/// 写入文件的缓冲区默认大小（1 MiB）
+pub const DEFAULT_BUFFER_SIZE: usize = 1024 * 1024;
+/// 缓冲区大小下限（4 KiB），再小会导致大量系统调用
+pub const MIN_BUFFER_SIZE: usize = 4 * 1024;
+/// 缓冲区大小上限（64 MiB），每个并发下载任务各占用一份
+pub const MAX_BUFFER_SIZE: usize = 64 * 1024 * 1024;
+/// `download_to_memory` 默认允许的最大大小（16 MiB），用于字幕、弹幕、封面等小文件
+pub const DEFAULT_MEMORY_LIMIT: usize = 16 * 1024 * 1024;
+
+/// 续传前比对的 `.part` 末尾字节数
+const TAIL_CHECK_LEN: u64 = 4096;
+
+/// `.part` 的续传信息，开始下载时写入，续传前用于校验已下载的部分
+#[derive(Debug, Serialize, Deserialize)]
+struct PartMeta {
+    /// 完整文件的大小（字节）
+    size: u64,
+}
+
+/// 读取 `.part` 的续传信息，不存在或无法解析时返回 None
+async fn read_part_meta(part: &Path) -> Option<PartMeta> {
+    let content = tokio::fs::read_to_string(file::part_meta_path(part)).await.ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 是否为服务器返回的4xx错误（换一个CDN节点通常可以恢复）
+pub fn is_client_error(err: &DownloaderError) -> bool {
+    matches!(err, DownloaderError::Network(e) if e.status().is_some_and(|s| s.is_client_error()))
+}
+
+/// 是否为请求超时（CDN节点响应过慢，换一个节点通常可以恢复）
+pub fn is_timeout(err: &DownloaderError) -> bool {
+    matches!(err, DownloaderError::Timeout { .. })
+}
+
 /// Download method to use
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DownloadMethod {
@@ -18,14 +58,69 @@ pub enum DownloadMethod {
     Aria2c,
 }
 
+/// aria2c 的分段与连接设置（默认值与 BBDown 一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Aria2cOptions {
+    /// 每个文件分割的块数（`--split`）
+    pub split: u32,
+    /// 每个服务器的最大连接数（`--max-connection-per-server`，1-16）
+    pub max_connection_per_server: u32,
+    /// 最小分块大小，字节（`--min-split-size`，1M-1024M）
+    pub min_split_size: u64,
+}
+
+impl Default for Aria2cOptions {
+    fn default() -> Self {
+        Self {
+            split: 16,
+            max_connection_per_server: 16,
+            min_split_size: 5 * 1024 * 1024,
+        }
+    }
+}
+
+impl Aria2cOptions {
+    /// 对应的 aria2 选项（名称, 值），命令行模式和 RPC 模式共用
+    pub fn to_options(self) -> Vec<(String, String)> {
+        vec![
+            (
+                "max-connection-per-server".to_string(),
+                self.max_connection_per_server.to_string(),
+            ),
+            ("split".to_string(), self.split.to_string()),
+            ("min-split-size".to_string(), self.min_split_size.to_string()),
+        ]
+    }
+}
+
 pub struct Downloader {
     client: Arc<HttpClient>,
     pub(crate) thread_count: usize,
     chunk_size: usize,
+    pub(crate) buffer_size: usize,
     pub(crate) method: DownloadMethod,
     pub(crate) aria2c_path: String,
     pub(crate) aria2c_args: Option<String>,
+    pub(crate) aria2c_options: Aria2cOptions,
+    /// 常驻 aria2c 的 RPC 接口，设置后 aria2c 模式优先使用，不可用时回退到命令行
+    pub(crate) aria2c_rpc: Option<Arc<Aria2Rpc>>,
     auth: Option<Auth>,
+    /// 总是丢弃已有的 `.part` 从头下载，不续传
+    pub(crate) force_overwrite_partial: bool,
+    /// 各主机是否支持分段请求（见 `supports_range`），同一次运行的下载器共享
+    range_support: Arc<Mutex<HashMap<String, bool>>>,
+}
+
+/// 分段支持缓存的键：主机名加端口，无法解析时使用整个地址
+fn range_cache_key(url: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(parsed) => match (parsed.host_str(), parsed.port_or_known_default()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            _ => url.to_string(),
+        },
+        Err(_) => url.to_string(),
+    }
 }
 
 impl Downloader {
@@ -34,10 +129,15 @@ impl Downloader {
             client,
             thread_count,
             chunk_size: 10 * 1024 * 1024, // 10MB chunks
+            buffer_size: DEFAULT_BUFFER_SIZE,
             method: DownloadMethod::Builtin,
             aria2c_path: "aria2c".to_string(),
             aria2c_args: None,
+            aria2c_options: Aria2cOptions::default(),
+            aria2c_rpc: None,
             auth: None,
+            force_overwrite_partial: false,
+            range_support: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -47,6 +147,19 @@ impl Downloader {
         self
     }
 
+    /// Set the size of each range request used for large files
+    pub fn with_chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size.max(1);
+        self
+    }
+
+    /// Set the write buffer size used when streaming to disk
+    /// (clamped to `MIN_BUFFER_SIZE..=MAX_BUFFER_SIZE`)
+    pub fn with_buffer_size(mut self, size: usize) -> Self {
+        self.buffer_size = size.clamp(MIN_BUFFER_SIZE, MAX_BUFFER_SIZE);
+        self
+    }
+
     /// Set aria2c binary path
     pub fn with_aria2c_path(mut self, path: String) -> Self {
         self.aria2c_path = path;
@@ -59,12 +172,41 @@ impl Downloader {
         self
     }
 
+    /// Set aria2c split and connection options
+    pub fn with_aria2c_options(mut self, options: Aria2cOptions) -> Self {
+        self.aria2c_options = options;
+        self
+    }
+
+    /// Use a running aria2c daemon over JSON-RPC in aria2c mode
+    pub fn with_aria2c_rpc(mut self, rpc: Option<Arc<Aria2Rpc>>) -> Self {
+        self.aria2c_rpc = rpc;
+        self
+    }
+
     /// Set authentication info for aria2c
     pub fn with_auth(mut self, auth: Option<Auth>) -> Self {
         self.auth = auth;
         self
     }
 
+    /// Always restart from zero instead of resuming an existing `.part`
+    pub fn with_force_overwrite_partial(mut self, force: bool) -> Self {
+        self.force_overwrite_partial = force;
+        self
+    }
+
+    /// Share the per-host range support cache with other downloaders
+    pub fn with_range_support(mut self, cache: Arc<Mutex<HashMap<String, bool>>>) -> Self {
+        self.range_support = cache;
+        self
+    }
+
+    /// The per-host range support cache, for passing to [`Downloader::with_range_support`]
+    pub fn range_support_cache(&self) -> Arc<Mutex<HashMap<String, bool>>> {
+        self.range_support.clone()
+    }
+
     /// Check if aria2c is available
     pub async fn check_aria2c(&self) -> Result<bool> {
         match Command::new(&self.aria2c_path)
@@ -84,14 +226,35 @@ impl Downloader {
         progress: Option<Arc<ProgressBar>>,
     ) -> Result<()> {
         tracing::info!("Downloading: {} -> {:?}", url, output);
+        let output = &file::long_path(output);
 
         // Create parent directory if it doesn't exist
         if let Some(parent) = output.parent() {
             tokio::fs::create_dir_all(parent).await?;
         }
 
+        // 先写入 `.part`，完成后再改名：存在最终文件即表示下载完整，
+        // 中断后留下的 `.part` 下次可以续传
+        let part = file::part_path(output);
+        self.download_part(url, &part, progress).await?;
+        tokio::fs::rename(&part, output).await?;
+        let _ = tokio::fs::remove_file(file::part_meta_path(&part)).await;
+        Ok(())
+    }
+
+    async fn download_part(
+        &self,
+        url: &str,
+        output: &Path,
+        progress: Option<Arc<ProgressBar>>,
+    ) -> Result<()> {
         // Use aria2c if specified
         if self.method == DownloadMethod::Aria2c {
+            if let Some(ref rpc) = self.aria2c_rpc {
+                if rpc.is_available().await {
+                    return self.download_with_aria2c_rpc(rpc, url, output, progress).await;
+                }
+            }
             return self.download_with_aria2c(url, output, progress).await;
         }
 
@@ -108,8 +271,23 @@ impl Downloader {
             pb.set_length(file_size);
         }
 
-        // Check if server supports range requests
-        if self.supports_range(url).await && file_size > self.chunk_size as u64 {
+        // 上次中断留下的 `.part` 校验通过后从末尾继续下载
+        let downloaded = self.resumable_length(url, output, file_size).await;
+        if downloaded > 0 {
+            return self.download_resume(url, output, downloaded, progress).await;
+        }
+        // 分段下载时 `.part` 在合并前不存在，已完成的分段按续传信息保留
+        let chunk_dir = file::chunk_dir_path(output);
+        let same_file = read_part_meta(output).await.is_some_and(|meta| meta.size == file_size);
+        if self.force_overwrite_partial || !same_file {
+            let _ = tokio::fs::remove_dir_all(&chunk_dir).await;
+        }
+        let meta = serde_json::to_string(&PartMeta { size: file_size })
+            .map_err(|e| DownloaderError::Parse(e.to_string()))?;
+        tokio::fs::write(file::part_meta_path(output), meta).await?;
+
+        // 只有大文件才分段下载，主机不支持 Range 时回退到单连接
+        if file_size > self.chunk_size as u64 && self.supports_range(url).await {
             self.download_chunked(url, output, file_size, progress)
                 .await
         } else {
@@ -117,22 +295,190 @@ impl Downloader {
         }
     }
 
-    async fn download_simple(&self, url: &str, output: &Path) -> Result<()> {
-        let mut headers = reqwest::header::HeaderMap::new();
+    /// 下载文件，主地址返回4xx（CDN节点403/404等）或超时时依次改用备用地址
+    pub async fn download_with_fallback(
+        &self,
+        url: &str,
+        backup_urls: &[String],
+        output: &Path,
+        progress: Option<Arc<ProgressBar>>,
+    ) -> Result<()> {
+        let mut result = self.download(url, output, progress.clone()).await;
+
+        for (i, backup_url) in backup_urls.iter().enumerate() {
+            match result {
+                Err(ref e) if is_client_error(e) || is_timeout(e) => {
+                    tracing::warn!(
+                        "CDN request failed ({}), retrying with backup URL {}/{}",
+                        e,
+                        i + 1,
+                        backup_urls.len()
+                    );
+                    if let Some(ref pb) = progress {
+                        pb.set_position(0);
+                    }
+                    result = self.download(backup_url, output, progress.clone()).await;
+                }
+                _ => break,
+            }
+        }
 
-        // Add required headers for Bilibili video downloads
-        if url.contains("bilivideo.com") {
-            if let Ok(value) = "https://www.bilibili.com".parse() {
-                headers.insert("Referer", value);
+        result
+    }
+
+    /// 只下载流中覆盖 `clip` 的片段，返回所下载第一个片段在原视频中的开始时间（秒）
+    ///
+    /// 输出为初始化段加所需片段拼接成的分片MP4；主地址返回4xx或超时时依次改用备用地址
+    pub async fn download_clip(
+        &self,
+        url: &str,
+        backup_urls: &[String],
+        segment_base: SegmentBase,
+        clip: ClipRange,
+        output: &Path,
+        progress: Option<Arc<ProgressBar>>,
+    ) -> Result<f64> {
+        let mut result = self
+            .download_clip_from(url, segment_base, clip, output, progress.clone())
+            .await;
+
+        for (i, backup_url) in backup_urls.iter().enumerate() {
+            match result {
+                Err(ref e) if is_client_error(e) || is_timeout(e) => {
+                    tracing::warn!(
+                        "CDN request failed ({}), retrying with backup URL {}/{}",
+                        e,
+                        i + 1,
+                        backup_urls.len()
+                    );
+                    result = self
+                        .download_clip_from(backup_url, segment_base, clip, output, progress.clone())
+                        .await;
+                }
+                _ => break,
             }
-            if let Ok(value) =
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36".parse()
-            {
-                headers.insert("User-Agent", value);
+        }
+
+        result
+    }
+
+    async fn download_clip_from(
+        &self,
+        url: &str,
+        segment_base: SegmentBase,
+        clip: ClipRange,
+        output: &Path,
+        progress: Option<Arc<ProgressBar>>,
+    ) -> Result<f64> {
+        tracing::info!("Downloading clip {:.1}s-{:.1}s: {} -> {:?}", clip.start, clip.end, url, output);
+        let output = &file::long_path(output);
+
+        // 初始化段与索引通常相邻，一次请求取回
+        let (init_start, init_end) = segment_base.initialization;
+        let (index_start, index_end) = segment_base.index_range;
+        let header_start = init_start.min(index_start);
+        let header = self
+            .range_request(url, header_start, init_end.max(index_end))
+            .await?
+            .bytes()
+            .await?;
+        let slice = |start: u64, end: u64| {
+            header
+                .get((start - header_start) as usize..=(end - header_start) as usize)
+                .ok_or_else(|| {
+                    DownloaderError::DownloadFailed("Truncated DASH header response".to_string())
+                })
+        };
+        let init = slice(init_start, init_end)?;
+        let segments = clip::parse_sidx(slice(index_start, index_end)?, index_start)?;
+
+        let (start, end, first_start) =
+            clip::byte_range_for_clip(&segments, clip).ok_or_else(|| {
+                DownloaderError::DownloadFailed(format!(
+                    "Clip {:.1}s-{:.1}s is outside the stream",
+                    clip.start, clip.end
+                ))
+            })?;
+        tracing::debug!(
+            "Clip needs bytes {}-{} of {} segment(s), starting at {:.3}s",
+            start,
+            end,
+            segments.len(),
+            first_start
+        );
+
+        if let Some(ref pb) = progress {
+            pb.set_length(init.len() as u64 + end - start + 1);
+            pb.set_position(init.len() as u64);
+        }
+
+        let mut file = BufWriter::with_capacity(self.buffer_size, File::create(output).await?);
+        file.write_all(init).await?;
+        let mut stream = self.range_request(url, start, end).await?.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(DownloaderError::from)?;
+            file.write_all(&chunk).await?;
+            if let Some(ref pb) = progress {
+                pb.inc(chunk.len() as u64);
             }
         }
+        file.flush().await?;
+
+        Ok(first_start)
+    }
+
+    /// 请求字节范围（含首尾），服务器忽略 `Range` 时返回错误
+    async fn range_request(&self, url: &str, start: u64, end: u64) -> Result<reqwest::Response> {
+        let request = self
+            .client
+            .cdn_request(reqwest::Method::GET, url)
+            .header("Range", format!("bytes={}-{}", start, end));
 
-        let response = self.client.get(url, Some(headers)).await?;
+        let response = request.send().await?.error_for_status()?;
+        if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            return Err(DownloaderError::DownloadFailed(format!(
+                "Server ignored the Range request (status {}), cannot download a clip",
+                response.status().as_u16()
+            )));
+        }
+        Ok(response)
+    }
+
+    /// 将小文件（字幕、弹幕、封面等）直接下载到内存
+    ///
+    /// 超过 `max_size` 字节时返回错误，避免误用于音视频流；
+    /// 一般使用 [`DEFAULT_MEMORY_LIMIT`]
+    pub async fn download_to_memory(&self, url: &str, max_size: usize) -> Result<Vec<u8>> {
+        tracing::debug!("Downloading to memory: {}", url);
+
+        let too_large = || {
+            DownloaderError::DownloadFailed(format!(
+                "Resource exceeds the in-memory download limit of {} bytes: {}",
+                max_size, url
+            ))
+        };
+
+        let response = self.client.get(url, Some(http::cdn_headers(url))).await?.error_for_status()?;
+        if response.content_length().is_some_and(|len| len > max_size as u64) {
+            return Err(too_large());
+        }
+
+        // 服务器可能不返回Content-Length，边下载边检查大小
+        let mut data = Vec::new();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(DownloaderError::from)?;
+            if data.len() + chunk.len() > max_size {
+                return Err(too_large());
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        Ok(data)
+    }
+
+    async fn download_simple(&self, url: &str, output: &Path) -> Result<()> {
+        let response = self.client.get(url, Some(http::cdn_headers(url))).await?;
         let bytes = response.bytes().await?;
         tokio::fs::write(output, bytes).await?;
         Ok(())
@@ -144,27 +490,13 @@ impl Downloader {
         output: &Path,
         progress: Option<Arc<ProgressBar>>,
     ) -> Result<()> {
-        let mut headers = reqwest::header::HeaderMap::new();
-
-        // Add required headers for Bilibili video downloads
-        if url.contains("bilivideo.com") {
-            if let Ok(value) = "https://www.bilibili.com".parse() {
-                headers.insert("Referer", value);
-            }
-            if let Ok(value) =
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36".parse()
-            {
-                headers.insert("User-Agent", value);
-            }
-        }
-
-        let response = self.client.get(url, Some(headers)).await?;
-        let mut file = File::create(output).await?;
+        let response = self.client.get(url, Some(http::cdn_headers(url))).await?;
+        let mut file = BufWriter::with_capacity(self.buffer_size, File::create(output).await?);
         let mut stream = response.bytes_stream();
         let mut downloaded = 0u64;
 
         while let Some(chunk) = stream.next().await {
-            let chunk = chunk.map_err(DownloaderError::Network)?;
+            let chunk = chunk.map_err(DownloaderError::from)?;
             file.write_all(&chunk).await?;
             downloaded += chunk.len() as u64;
 
@@ -177,52 +509,163 @@ impl Downloader {
         Ok(())
     }
 
-    async fn get_file_size(&self, url: &str) -> Result<u64> {
-        let mut request = self.client.client.head(url);
+    /// 可以续传的 `.part` 长度，返回0表示从头下载
+    ///
+    /// 已下载的部分必须与续传信息中记录的大小一致、短于完整文件，且末尾字节与服务器
+    /// 上对应位置的内容相同，否则认为 `.part` 已损坏
+    async fn resumable_length(&self, url: &str, part: &Path, size: u64) -> u64 {
+        let len = tokio::fs::metadata(part).await.map_or(0, |m| m.len());
+        if len == 0 {
+            return 0;
+        }
+        if self.force_overwrite_partial {
+            tracing::info!("Discarding {:?} (--force-overwrite-partial)", part);
+            return 0;
+        }
 
-        // Add required headers for Bilibili video downloads
-        if url.contains("bilivideo.com") {
-            request = request.header("Referer", "https://www.bilibili.com");
-            request = request.header(
-                "User-Agent",
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
-            );
+        match read_part_meta(part).await {
+            Some(meta) if meta.size == size && len < size => {}
+            Some(meta) => {
+                tracing::warn!(
+                    "{:?} is inconsistent ({} of {} bytes, remote file is {} bytes), restarting",
+                    part,
+                    len,
+                    meta.size,
+                    size
+                );
+                return 0;
+            }
+            None => {
+                tracing::warn!("No resume info for {:?}, restarting", part);
+                return 0;
+            }
         }
 
-        let response = request.send().await?;
+        if !self.supports_range(url).await {
+            return 0;
+        }
+        match self.tail_matches(url, part, len).await {
+            Ok(true) => len,
+            Ok(false) => {
+                tracing::warn!("{:?} does not match the remote file, restarting", part);
+                0
+            }
+            Err(e) => {
+                tracing::warn!("Could not verify {:?} ({}), restarting", part, e);
+                0
+            }
+        }
+    }
 
-        if let Some(content_length) = response.headers().get("content-length") {
-            let size = content_length
-                .to_str()
-                .map_err(|_| DownloaderError::Parse("Invalid content-length".to_string()))?
-                .parse::<u64>()
-                .map_err(|_| DownloaderError::Parse("Invalid content-length".to_string()))?;
-            Ok(size)
-        } else {
-            Err(DownloaderError::DownloadFailed(
-                "No content-length header".to_string(),
-            ))
+    /// `.part` 末尾的字节与服务器上同一位置的内容是否相同
+    async fn tail_matches(&self, url: &str, part: &Path, len: u64) -> Result<bool> {
+        use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+        let start = len.saturating_sub(TAIL_CHECK_LEN);
+        let mut local = Vec::with_capacity((len - start) as usize);
+        let mut file = File::open(part).await?;
+        file.seek(std::io::SeekFrom::Start(start)).await?;
+        file.read_to_end(&mut local).await?;
+
+        let request = self
+            .client
+            .cdn_request(reqwest::Method::GET, url)
+            .header("Range", format!("bytes={}-{}", start, len - 1));
+
+        let response = request.send().await?.error_for_status()?;
+        if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            return Ok(false);
         }
+        Ok(response.bytes().await?.as_ref() == local.as_slice())
     }
 
-    async fn supports_range(&self, url: &str) -> bool {
-        let mut request = self.client.client.head(url);
+    /// 从 `offset` 字节处继续下载，追加到已有文件末尾
+    async fn download_resume(
+        &self,
+        url: &str,
+        output: &Path,
+        offset: u64,
+        progress: Option<Arc<ProgressBar>>,
+    ) -> Result<()> {
+        tracing::info!("Resuming {:?} from byte {}", output, offset);
+        let request = self
+            .client
+            .cdn_request(reqwest::Method::GET, url)
+            .header("Range", format!("bytes={}-", offset));
+
+        let response = request.send().await?.error_for_status()?;
+        if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            // 服务器返回完整内容时从头写入
+            tracing::warn!("Server ignored the Range request, restarting {:?}", output);
+            return self.download_streaming(url, output, progress).await;
+        }
 
-        // Add required headers for Bilibili video downloads
-        if url.contains("bilivideo.com") {
-            request = request.header("Referer", "https://www.bilibili.com");
-            request = request.header(
-                "User-Agent",
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
-            );
+        let file = tokio::fs::OpenOptions::new().append(true).open(output).await?;
+        let mut file = BufWriter::with_capacity(self.buffer_size, file);
+        let mut stream = response.bytes_stream();
+        let mut downloaded = offset;
+        if let Some(ref pb) = progress {
+            pb.set_position(downloaded);
         }
 
-        if let Ok(response) = request.send().await {
-            if let Some(accept_ranges) = response.headers().get("accept-ranges") {
-                return accept_ranges.to_str().unwrap_or("") == "bytes";
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(DownloaderError::from)?;
+            file.write_all(&chunk).await?;
+            downloaded += chunk.len() as u64;
+
+            if let Some(ref pb) = progress {
+                pb.set_position(downloaded);
             }
         }
-        false
+
+        file.flush().await?;
+        Ok(())
+    }
+
+    async fn get_file_size(&self, url: &str) -> Result<u64> {
+        self.client.content_length(url).await
+    }
+
+    /// 主机是否真正支持分段请求（`Range`），结果按主机缓存
+    ///
+    /// 只看 `Accept-Ranges` 不可靠：部分PCDN节点声明支持却忽略 `Range` 返回完整内容，
+    /// 分段下载会把整个文件写进每个分段。这里发送 `bytes=0-0` 的小请求，
+    /// 只有返回 `206` 时才认为支持，否则回退到单连接下载。只缓存 `206` 和 `200` 这样明确的回答，
+    /// 错误状态（如限流的 `429`、临时的 `5xx`）下次再探测
+    pub async fn supports_range(&self, url: &str) -> bool {
+        let host = range_cache_key(url);
+        if let Some(&supported) = self.range_support.lock().unwrap().get(&host) {
+            return supported;
+        }
+
+        let request = self.client.cdn_request(reqwest::Method::GET, url).header("Range", "bytes=0-0");
+
+        // 请求失败时不缓存，下次再探测
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                tracing::debug!("Range probe for {} failed: {}", host, e);
+                return false;
+            }
+        };
+        let status = response.status();
+        let supported = status == reqwest::StatusCode::PARTIAL_CONTENT;
+        tracing::debug!(
+            "Range support for {}: {} (probe status {}, Accept-Ranges: {})",
+            host,
+            if supported { "yes" } else { "no" },
+            status.as_u16(),
+            response
+                .headers()
+                .get("accept-ranges")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("-")
+        );
+
+        if supported || status == reqwest::StatusCode::OK {
+            self.range_support.lock().unwrap().insert(host, supported);
+        }
+        supported
     }
 
     async fn download_chunked(
@@ -235,30 +678,42 @@ impl Downloader {
         let chunk_count = ((total_size as f64) / (self.chunk_size as f64)).ceil() as usize;
         let mut tasks = Vec::new();
 
-        // Create temp directory for chunks
-        let temp_dir = output.parent().unwrap().join(".rvd_temp");
+        // 分段保存在 `.part` 旁的目录中，中断后已完成的分段不再重新下载
+        let temp_dir = file::chunk_dir_path(output);
         tokio::fs::create_dir_all(&temp_dir).await?;
 
-        let mut chunk_paths = Vec::new();
+        let mut chunk_paths = Vec::with_capacity(chunk_count);
 
         for i in 0..chunk_count {
             let start = i * self.chunk_size;
             let end = std::cmp::min(start + self.chunk_size - 1, total_size as usize - 1);
             let chunk_path = temp_dir.join(format!("chunk_{}", i));
+            chunk_paths.push(chunk_path.clone());
+            let chunk_len = (end - start + 1) as u64;
+            if tokio::fs::metadata(&chunk_path).await.is_ok_and(|m| m.len() == chunk_len) {
+                tracing::debug!("Reusing downloaded chunk {:?}", chunk_path);
+                if let Some(ref pb) = progress {
+                    pb.inc(chunk_len);
+                }
+                continue;
+            }
             let url = url.to_string();
             let client = self.client.clone();
             let progress = progress.clone();
 
             let task = tokio::spawn(async move {
+                // 完整下载后才改为分段文件名，存在的分段文件即为完整的分段
+                let partial = file::part_path(&chunk_path);
                 client
-                    .download_file(&url, &chunk_path, Some((start as u64, end as u64)))
+                    .download_file(&url, &partial, Some((start as u64, end as u64)))
                     .await?;
+                tokio::fs::rename(&partial, &chunk_path).await?;
 
                 if let Some(ref pb) = progress {
-                    pb.inc((end - start + 1) as u64);
+                    pb.inc(chunk_len);
                 }
 
-                Ok::<_, DownloaderError>(chunk_path)
+                Ok::<_, DownloaderError>(())
             });
 
             tasks.push(task);
@@ -267,10 +722,9 @@ impl Downloader {
             if tasks.len() >= self.thread_count {
                 let completed = futures::future::join_all(tasks.drain(..)).await;
                 for result in completed {
-                    let path = result.map_err(|e| {
+                    result.map_err(|e| {
                         DownloaderError::DownloadFailed(format!("Task failed: {}", e))
                     })??;
-                    chunk_paths.push(path);
                 }
             }
         }
@@ -278,9 +732,7 @@ impl Downloader {
         // Wait for remaining tasks
         let completed = futures::future::join_all(tasks).await;
         for result in completed {
-            let path = result
-                .map_err(|e| DownloaderError::DownloadFailed(format!("Task failed: {}", e)))??;
-            chunk_paths.push(path);
+            result.map_err(|e| DownloaderError::DownloadFailed(format!("Task failed: {}", e)))??;
         }
 
         // Merge chunks
@@ -293,7 +745,8 @@ impl Downloader {
     }
 
     async fn merge_chunks(&self, chunks: &[std::path::PathBuf], output: &Path) -> Result<()> {
-        let mut output_file = File::create(output).await?;
+        let mut output_file =
+            BufWriter::with_capacity(self.buffer_size, File::create(output).await?);
 
         for chunk in chunks {
             let mut chunk_file = File::open(chunk).await?;
@@ -336,27 +789,16 @@ impl Downloader {
             "--download-result=hide".to_string(),
             "--allow-overwrite=true".to_string(),
             "--console-log-level=warn".to_string(),
-            // Connection options (matching BBDown defaults)
-            "-x16".to_string(), // max connections per server
-            "-s16".to_string(), // split into 16 parts
             "-j16".to_string(), // max concurrent downloads
-            "-k5M".to_string(), // min split size 5MB
         ];
+        // Connection options, custom args below can still override them
+        for (key, value) in self.aria2c_connection_options() {
+            args.push(format!("--{}={}", key, value));
+        }
 
         // Add headers for Bilibili
-        if url.contains("bilivideo.com") {
-            // Only add Referer for non-TV/APP API URLs
-            if !url.contains("platform=android_tv_yst") && !url.contains("platform=android") {
-                args.push("--header=Referer: https://www.bilibili.com".to_string());
-            }
-            args.push("--header=User-Agent: Mozilla/5.0".to_string());
-
-            // Add cookie if available
-            if let Some(ref auth) = self.auth {
-                if let Some(ref cookie) = auth.cookie {
-                    args.push(format!("--header=Cookie: {}", cookie));
-                }
-            }
+        for header in self.aria2c_headers(url) {
+            args.push(format!("--header={}", header));
         }
 
         // Add custom args if provided
@@ -411,4 +853,82 @@ impl Downloader {
         tracing::info!("aria2c download completed successfully");
         Ok(())
     }
+
+    /// aria2c 的连接选项：分段设置，以及 `--force-ipv4` 时的 `disable-ipv6`
+    ///
+    /// aria2 只能禁用IPv6，`--force-ipv6` 时无法限制它只用IPv6，只给出警告
+    fn aria2c_connection_options(&self) -> Vec<(String, String)> {
+        let mut options = self.aria2c_options.to_options();
+        match self.client.ip_version() {
+            IpVersion::Auto => {}
+            IpVersion::V4 => options.push(("disable-ipv6".to_string(), "true".to_string())),
+            IpVersion::V6 => {
+                tracing::warn!(
+                    "aria2c cannot be restricted to IPv6, --force-ipv6 does not apply to it"
+                )
+            }
+        }
+        options
+    }
+
+    /// aria2c 请求需要附加的HTTP头（`Name: value`）
+    fn aria2c_headers(&self, url: &str) -> Vec<String> {
+        let mut headers = Vec::new();
+        if url.contains("bilivideo.com") {
+            // Only add Referer for non-TV/APP API URLs
+            if !url.contains("platform=android_tv_yst") && !url.contains("platform=android") {
+                headers.push("Referer: https://www.bilibili.com".to_string());
+            }
+            headers.push("User-Agent: Mozilla/5.0".to_string());
+
+            // Add cookie if available
+            if let Some(ref auth) = self.auth {
+                if let Some(ref cookie) = auth.cookie {
+                    headers.push(format!("Cookie: {}", cookie));
+                }
+            }
+        }
+        headers
+    }
+
+    /// 通过 aria2 RPC 下载
+    ///
+    /// 自定义参数中 `--key=value` 形式的选项作为 aria2 选项传入，其他形式（如 `-x8`）被忽略
+    async fn download_with_aria2c_rpc(
+        &self,
+        rpc: &Aria2Rpc,
+        url: &str,
+        output: &Path,
+        progress: Option<Arc<ProgressBar>>,
+    ) -> Result<()> {
+        // aria2 进程的工作目录可能不同，使用绝对路径
+        let output = if output.is_absolute() {
+            output.to_path_buf()
+        } else {
+            std::env::current_dir()?.join(output)
+        };
+
+        let mut options = self.aria2c_connection_options();
+        options.extend(
+            self.aria2c_headers(url)
+                .into_iter()
+                .map(|header| ("header".to_string(), header)),
+        );
+        if let Some(ref custom_args) = self.aria2c_args {
+            for arg in custom_args.split_whitespace() {
+                match arg.strip_prefix("--").and_then(|a| a.split_once('=')) {
+                    Some((key, value)) => options.push((key.to_string(), value.to_string())),
+                    None => tracing::debug!("Ignoring aria2c argument in RPC mode: {}", arg),
+                }
+            }
+        }
+
+        rpc.download(url, &output, options, progress.clone()).await?;
+        if let Some(ref pb) = progress {
+            pb.finish();
+        }
+
+        tracing::info!("aria2 RPC download completed successfully");
+        Ok(())
+    }
 }
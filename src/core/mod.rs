@@ -1,5 +1,8 @@
+pub mod aria2_rpc;
+pub mod clip;
 pub mod danmaku;
 pub mod downloader;
 pub mod muxer;
+pub mod nfo;
 pub mod progress;
 pub mod subtitle;
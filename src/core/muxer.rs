@@ -1,11 +1,272 @@
 use crate::error::{DownloaderError, Result};
+use indicatif::ProgressBar;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::ffi::OsString;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Command, Stdio};
+use std::sync::{Arc, OnceLock};
+
+/// 混流参数
+#[derive(Debug, Clone, Default)]
+pub struct MuxOptions {
+    /// 需要嵌入的字幕文件
+    pub subtitles: Vec<PathBuf>,
+    /// 需要写入的章节信息
+    pub chapters: Vec<crate::types::Chapter>,
+    /// 需要作为封面嵌入的图片
+    pub cover: Option<PathBuf>,
+    /// 视频流的HDR类型
+    pub hdr: HdrType,
+    /// 视频流的编码（如 HEVC、AV1），用于选择写入HDR色彩信息的比特流过滤器
+    pub video_codec: String,
+    /// 重新编码选项，None 表示直接复制流（`-c copy`）
+    pub reencode: Option<ReencodeOptions>,
+    /// 混流进度条，长度为视频时长（毫秒），按ffmpeg的 `-progress` 输出更新
+    pub progress: Option<Arc<ProgressBar>>,
+    /// 写入容器的元数据标签（`-metadata key=value`），如标题、作者、发布日期
+    pub metadata: Vec<(String, String)>,
+    /// 只保留其中一段（`--clip`），None 表示完整输出
+    pub trim: Option<TrimOptions>,
+    /// 嵌入字幕时作为附件写入 MKV 的字体文件，供支持内嵌字体的播放器显示中文
+    pub font: Option<PathBuf>,
+    /// 主音轨之外额外嵌入的音轨（`--audio-lang all` 且输出 MKV 时的其他语言）
+    pub extra_audio: Vec<AudioTrack>,
+}
+
+/// 额外嵌入的音轨及其语言代码（写入 `language` 元数据）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AudioTrack {
+    pub path: PathBuf,
+    pub language: String,
+}
+
+/// 混流时裁剪的时间范围
+///
+/// 片段下载的音视频文件不从0秒开始，因此每个输入分别指定裁剪开始的位置
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrimOptions {
+    /// 视频文件中裁剪开始的位置（秒，相对文件开头）
+    pub video_seek: f64,
+    /// 音频文件中裁剪开始的位置（秒，相对文件开头）
+    pub audio_seek: f64,
+    /// 片段在原视频中的开始时间（秒），用于字幕等完整的输入
+    pub start: f64,
+    pub duration: f64,
+}
+
+/// 输入文件前的裁剪参数：`-ss <seek> -t <duration>`
+fn trim_input_args(args: &mut Vec<OsString>, seek: f64, duration: f64) {
+    args.push("-ss".into());
+    args.push(format!("{:.3}", seek.max(0.0)).into());
+    args.push("-t".into());
+    args.push(format!("{:.3}", duration).into());
+}
+
+/// 嵌入到输出文件（或写在旁边）的元数据，由 `--embed-metadata` 与各 `--no-embed-*` 决定
+///
+/// 默认保持原有行为：嵌入章节和字幕，不嵌入封面和元数据标签，不写 `.nfo`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmbedOptions {
+    pub chapters: bool,
+    pub cover: bool,
+    pub subtitles: bool,
+    /// 标题、UP主、发布日期、简介等容器标签
+    pub tags: bool,
+    /// 在输出文件旁写入 Kodi/Jellyfin 使用的 `.nfo`
+    pub nfo: bool,
+}
+
+impl Default for EmbedOptions {
+    fn default() -> Self {
+        Self {
+            chapters: true,
+            cover: false,
+            subtitles: true,
+            tags: false,
+            nfo: false,
+        }
+    }
+}
+
+impl EmbedOptions {
+    /// 全部开启（`--embed-metadata`）
+    pub fn all() -> Self {
+        Self {
+            chapters: true,
+            cover: true,
+            subtitles: true,
+            tags: true,
+            nfo: true,
+        }
+    }
+
+    /// 按嵌入选项生成混流参数，未开启的项不会传给ffmpeg
+    pub fn mux_options(
+        &self,
+        subtitles: &[PathBuf],
+        chapters: &[crate::types::Chapter],
+        cover: Option<&Path>,
+        metadata: Vec<(String, String)>,
+    ) -> MuxOptions {
+        MuxOptions {
+            subtitles: if self.subtitles { subtitles.to_vec() } else { Vec::new() },
+            chapters: if self.chapters { chapters.to_vec() } else { Vec::new() },
+            cover: cover.filter(|_| self.cover).map(Path::to_path_buf),
+            metadata: if self.tags { metadata } else { Vec::new() },
+            ..Default::default()
+        }
+    }
+}
+
+/// 视频信息对应的容器元数据标签
+pub fn metadata_tags(video_info: &crate::types::VideoInfo, page: Option<&crate::types::Page>) -> Vec<(String, String)> {
+    let title = match page {
+        Some(page) if video_info.pages.len() > 1 => format!("{} - {}", video_info.title, page.title),
+        _ => video_info.title.clone(),
+    };
+    let mut tags = vec![
+        ("title".to_string(), title),
+        ("artist".to_string(), video_info.uploader.clone()),
+        ("date".to_string(), video_info.upload_date.clone()),
+        ("comment".to_string(), video_info.description.clone()),
+    ];
+    if let Some(extra) = video_info.bilibili_extra() {
+        tags.push(("genre".to_string(), extra.tname.clone()));
+        tags.push(("keywords".to_string(), extra.tags.join(",")));
+    }
+    tags.retain(|(_, value)| !value.trim().is_empty());
+    tags
+}
+
+/// 混流输出的容器格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeOutputFormat {
+    #[default]
+    Mp4,
+    Mkv,
+    Mov,
+}
+
+impl MergeOutputFormat {
+    /// 根据输出文件扩展名判断容器格式
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "mp4" | "m4v" => Some(Self::Mp4),
+            "mkv" => Some(Self::Mkv),
+            "mov" => Some(Self::Mov),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Mp4 => "mp4",
+            Self::Mkv => "mkv",
+            Self::Mov => "mov",
+        }
+    }
+
+    /// 嵌入字幕使用的编码，MP4/MOV 只支持 mov_text
+    pub fn subtitle_codec(&self) -> &'static str {
+        match self {
+            Self::Mkv => "srt",
+            Self::Mp4 | Self::Mov => "mov_text",
+        }
+    }
+}
+
+/// 视频流的动态范围类型，决定混流时需要保留的色彩元数据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HdrType {
+    #[default]
+    Sdr,
+    /// HDR10 (PQ / SMPTE ST 2084)
+    Hdr10,
+    /// 杜比视界（RPU元数据）
+    DolbyVision,
+}
+
+impl HdrType {
+    /// 根据Bilibili清晰度ID判断（125: HDR 真彩，126: 杜比视界）
+    pub fn from_quality_id(quality_id: u32) -> Self {
+        match quality_id {
+            125 => HdrType::Hdr10,
+            126 => HdrType::DolbyVision,
+            _ => HdrType::Sdr,
+        }
+    }
+
+    pub fn is_hdr(&self) -> bool {
+        *self != HdrType::Sdr
+    }
+}
+
+/// 重新编码选项
+///
+/// 默认情况下混流只复制音视频流，杜比视界/HDR元数据及原始时间戳都会保留；
+/// 重新编码会丢失这些元数据，仅用于设备兼容等特殊需求（如 HEVC 转 AVC）。
+#[derive(Debug, Clone, Default)]
+pub struct ReencodeOptions {
+    /// 视频编码器（支持 avc/hevc/av1 等别名，或直接指定 FFmpeg 编码器名称）
+    pub video_codec: String,
+    /// 音频编码器，None 表示复制音频流
+    pub audio_codec: Option<String>,
+    /// 恒定质量参数 CRF
+    pub crf: Option<u32>,
+    /// 视频码率（如 "4M"），与 CRF 同时指定时以码率为准
+    pub video_bitrate: Option<String>,
+}
+
+/// FFmpeg 编译时包含的编码器和滤镜（`ffmpeg -encoders` / `ffmpeg -filters`）
+///
+/// 精简版 FFmpeg 常常缺少 libx264、libass 等组件，运行前检查可以给出明确的提示，
+/// 而不是难以理解的 FFmpeg 报错
+#[derive(Debug, Clone, Default)]
+pub struct FfmpegCapabilities {
+    pub encoders: HashSet<String>,
+    pub filters: HashSet<String>,
+}
+
+impl FfmpegCapabilities {
+    /// 检查所需的编码器和滤镜，返回缺少的第一个组件对应的错误
+    pub fn require(&self, encoders: &[&str], filters: &[&str]) -> Result<()> {
+        let missing = |kind: &str, name: &str| DownloaderError::FFmpegMissingComponent {
+            kind: kind.to_string(),
+            name: name.to_string(),
+        };
+        if let Some(name) = encoders.iter().find(|e| !self.encoders.contains(**e)) {
+            return Err(missing("encoder", name));
+        }
+        if let Some(name) = filters.iter().find(|f| !self.filters.contains(**f)) {
+            return Err(missing("filter", name));
+        }
+        Ok(())
+    }
+
+    /// 检查重新编码所需的编码器，`copy`（直接复制流）不需要编码器
+    pub fn require_reencode(&self, reencode: &ReencodeOptions) -> Result<()> {
+        let encoders: Vec<String> = std::iter::once(reencode.video_codec.as_str())
+            .chain(reencode.audio_codec.as_deref())
+            .filter(|codec| !codec.eq_ignore_ascii_case("copy"))
+            .map(ffmpeg_encoder)
+            .collect();
+        let encoders: Vec<&str> = encoders.iter().map(String::as_str).collect();
+        self.require(&encoders, &[])
+    }
+}
+
+/// 联系表使用的FFmpeg滤镜
+const CONTACT_SHEET_FILTERS: &[&str] = &["fps", "scale", "tile"];
 
 pub struct Muxer {
     ffmpeg_path: PathBuf,
     ffmpeg_version: Option<(u32, u32)>, // (major, minor)
     use_mp4box: bool,
+    reencode: Option<ReencodeOptions>,
+    /// 首次需要时探测，None 表示探测失败（不阻止运行）
+    capabilities: OnceLock<Option<FfmpegCapabilities>>,
 }
 
 impl Muxer {
@@ -26,6 +287,8 @@ impl Muxer {
             ffmpeg_path: path,
             ffmpeg_version: None,
             use_mp4box,
+            reencode: None,
+            capabilities: OnceLock::new(),
         };
 
         // Check if ffmpeg is available and get version
@@ -34,6 +297,12 @@ impl Muxer {
         Ok(muxer)
     }
 
+    /// 设置默认的重新编码选项（None 为直接复制流）
+    pub fn with_reencode(mut self, reencode: Option<ReencodeOptions>) -> Self {
+        self.reencode = reencode;
+        self
+    }
+
     pub fn check_ffmpeg(&mut self) -> Result<String> {
         let output = Command::new(&self.ffmpeg_path)
             .arg("-version")
@@ -59,6 +328,42 @@ impl Muxer {
         Ok(first_line.to_string())
     }
 
+    /// FFmpeg 支持的编码器和滤镜，首次调用时运行 `ffmpeg -encoders` / `-filters` 探测并缓存
+    ///
+    /// 探测失败时返回 None，此时跳过组件检查，由 FFmpeg 自行报错
+    pub fn capabilities(&self) -> Option<&FfmpegCapabilities> {
+        self.capabilities
+            .get_or_init(|| {
+                let list = |flag: &str| {
+                    let output = Command::new(&self.ffmpeg_path)
+                        .args(["-hide_banner", flag])
+                        .output()
+                        .ok()
+                        .filter(|output| output.status.success())?;
+                    Some(parse_ffmpeg_components(&String::from_utf8_lossy(&output.stdout)))
+                };
+                let capabilities = FfmpegCapabilities {
+                    encoders: list("-encoders")?,
+                    filters: list("-filters")?,
+                };
+                tracing::debug!(
+                    "FFmpeg capabilities: {} encoders, {} filters",
+                    capabilities.encoders.len(),
+                    capabilities.filters.len()
+                );
+                Some(capabilities)
+            })
+            .as_ref()
+    }
+
+    /// 检查重新编码所需的编码器，在下载前调用可以尽早发现精简版 FFmpeg 缺少的组件
+    pub fn check_reencode(&self, reencode: &ReencodeOptions) -> Result<()> {
+        let Some(capabilities) = self.capabilities() else {
+            return Ok(());
+        };
+        capabilities.require_reencode(reencode)
+    }
+
     fn parse_ffmpeg_version(&self, version_output: &str) -> Option<(u32, u32)> {
         // 查找 "ffmpeg version X.Y" 或 "libavutil X.Y"
         for line in version_output.lines() {
@@ -125,7 +430,8 @@ impl Muxer {
         subtitles: &[PathBuf],
         chapters: &[crate::types::Chapter],
     ) -> Result<()> {
-        self.mux_with_options(video, audio, output, subtitles, chapters, false).await
+        self.mux_with_options(video, audio, output, subtitles, chapters, HdrType::Sdr)
+            .await
     }
 
     pub async fn mux_with_options(
@@ -135,9 +441,39 @@ impl Muxer {
         output: &Path,
         subtitles: &[PathBuf],
         chapters: &[crate::types::Chapter],
-        is_dolby_vision: bool,
+        hdr: HdrType,
+    ) -> Result<()> {
+        let options = MuxOptions {
+            subtitles: subtitles.to_vec(),
+            chapters: chapters.to_vec(),
+            cover: None,
+            hdr,
+            video_codec: String::new(),
+            reencode: self.reencode.clone(),
+            progress: None,
+            metadata: Vec::new(),
+            trim: None,
+            font: None,
+            extra_audio: Vec::new(),
+        };
+        self.mux_advanced(video, audio, output, &options).await
+    }
+
+    /// 使用完整的混流参数进行混流
+    pub async fn mux_advanced(
+        &self,
+        video: &Path,
+        audio: &Path,
+        output: &Path,
+        options: &MuxOptions,
     ) -> Result<()> {
         tracing::info!("Muxing video and audio to {:?}", output);
+        if let Some(ref reencode) = options.reencode {
+            self.check_reencode(reencode)?;
+        }
+        let output = &crate::utils::file::long_path(output);
+
+        let is_dolby_vision = options.hdr == HdrType::DolbyVision;
 
         // 检查是否需要使用mp4box处理杜比视界
         let should_use_mp4box = self.use_mp4box || (is_dolby_vision && !self.supports_dolby_vision());
@@ -151,49 +487,31 @@ impl Muxer {
             // TODO: 实现mp4box混流逻辑
         }
 
-        let mut cmd = Command::new(&self.ffmpeg_path);
-        cmd.arg("-i").arg(video);
-        cmd.arg("-i").arg(audio);
-
-        // Add subtitles
-        for subtitle in subtitles {
-            cmd.arg("-i").arg(subtitle);
-        }
-
         // 如果有章节信息，创建章节文件
-        let chapter_file = if !chapters.is_empty() {
+        let chapter_file = if !options.chapters.is_empty() {
             let chapter_path = output.with_extension("chapters.txt");
-            self.create_chapter_file(&chapter_path, chapters)?;
+            self.create_chapter_file(&chapter_path, &options.chapters)?;
             Some(chapter_path)
         } else {
             None
         };
 
-        // Copy codecs (no re-encoding)
-        cmd.arg("-c:v").arg("copy");
-        cmd.arg("-c:a").arg("copy");
-
-        if !subtitles.is_empty() {
-            cmd.arg("-c:s").arg("mov_text");
-        }
-
-        // 添加章节元数据
-        if let Some(ref chapter_path) = chapter_file {
-            cmd.arg("-i").arg(chapter_path);
-            cmd.arg("-map_metadata").arg(format!("{}", 2 + subtitles.len()));
-        }
-
-        // Overwrite output file
-        cmd.arg("-y");
+        let args = build_mux_args(video, audio, output, options, chapter_file.as_deref());
 
-        // Output file
-        cmd.arg(output);
+        let mut cmd = Command::new(&self.ffmpeg_path);
+        cmd.args(&args);
 
         tracing::debug!("FFmpeg command: {:?}", cmd);
 
-        let output_result = cmd
-            .output()
-            .map_err(|e| DownloaderError::MuxFailed(format!("Failed to execute ffmpeg: {}", e)))?;
+        // ffmpeg 运行期间会阻塞线程，放到阻塞线程池中，避免同时下载的其他分P停顿
+        let progress = options.progress.clone();
+        let output_result = tokio::task::spawn_blocking(move || match progress {
+            Some(ref progress) => run_with_progress(&mut cmd, progress),
+            None => cmd.output(),
+        })
+        .await
+        .map_err(|e| DownloaderError::MuxFailed(format!("FFmpeg task failed: {}", e)))?
+        .map_err(|e| DownloaderError::MuxFailed(format!("Failed to execute ffmpeg: {}", e)))?;
 
         // 清理章节文件
         if let Some(chapter_path) = chapter_file {
@@ -212,6 +530,201 @@ impl Muxer {
         Ok(())
     }
 
+    /// 校验混流后的输出文件
+    ///
+    /// 使用FFmpeg完整解码一遍输出文件（`-v error -f null -`），确认容器可以正常播放；
+    /// 如果提供了预期时长（秒），还会检查实际时长与预期时长是否大致一致。
+    pub fn verify_output(&self, output: &Path, expected_duration: Option<u64>) -> Result<()> {
+        tracing::info!("Verifying output file: {:?}", output);
+
+        let decode_result = Command::new(&self.ffmpeg_path)
+            .arg("-v")
+            .arg("error")
+            .arg("-i")
+            .arg(output)
+            .arg("-f")
+            .arg("null")
+            .arg("-")
+            .output()
+            .map_err(|e| {
+                DownloaderError::VerifyFailed(format!("Failed to execute ffmpeg: {}", e))
+            })?;
+
+        let stderr = String::from_utf8_lossy(&decode_result.stderr);
+        if !decode_result.status.success() || !stderr.trim().is_empty() {
+            let detail = stderr.lines().take(5).collect::<Vec<_>>().join("; ");
+            return Err(DownloaderError::VerifyFailed(format!(
+                "{} is not a valid media file: {}",
+                output.display(),
+                detail
+            )));
+        }
+
+        if let Some(expected) = expected_duration.filter(|d| *d > 0) {
+            let actual = self.probe_duration(output).ok_or_else(|| {
+                DownloaderError::VerifyFailed(format!(
+                    "Could not determine duration of {}",
+                    output.display()
+                ))
+            })?;
+
+            if !duration_matches(actual, expected) {
+                return Err(DownloaderError::VerifyFailed(format!(
+                    "Duration mismatch: expected ~{}s, got {:.1}s",
+                    expected, actual
+                )));
+            }
+        }
+
+        tracing::info!("Output verified successfully");
+        Ok(())
+    }
+
+    /// 读取媒体文件时长（秒）
+    ///
+    /// 优先使用与ffmpeg同目录的ffprobe只读取容器信息，不解码；没有ffprobe时解析 `ffmpeg -i` 的输出
+    pub fn probe_duration(&self, path: &Path) -> Option<f64> {
+        let probed = Command::new(ffprobe_path(&self.ffmpeg_path))
+            .arg("-v")
+            .arg("error")
+            .arg("-show_entries")
+            .arg("format=duration")
+            .arg("-of")
+            .arg("default=noprint_wrappers=1:nokey=1")
+            .arg(path)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| parse_ffprobe_duration(&String::from_utf8_lossy(&output.stdout)));
+
+        probed.or_else(|| self.read_duration(path))
+    }
+
+    /// 读取媒体文件时长（秒），通过解析 `ffmpeg -i` 输出的 `Duration:` 行
+    fn read_duration(&self, path: &Path) -> Option<f64> {
+        let output = Command::new(&self.ffmpeg_path)
+            .arg("-hide_banner")
+            .arg("-i")
+            .arg(path)
+            .output()
+            .ok()?;
+
+        // 没有指定输出文件时ffmpeg会返回非0，但Duration信息已输出到stderr
+        parse_ffmpeg_duration(&String::from_utf8_lossy(&output.stderr))
+    }
+
+    /// 读取媒体文件第一个视频流的分辨率（宽, 高）
+    pub fn read_resolution(&self, path: &Path) -> Option<(u32, u32)> {
+        let output = Command::new(&self.ffmpeg_path)
+            .arg("-hide_banner")
+            .arg("-i")
+            .arg(path)
+            .output()
+            .ok()?;
+
+        parse_ffmpeg_resolution(&String::from_utf8_lossy(&output.stderr))
+    }
+
+    /// 使用concat分离器将多个编码参数相同的文件按顺序无损拼接为一个文件
+    pub fn concat(&self, inputs: &[PathBuf], output: &Path) -> Result<()> {
+        tracing::info!("Concatenating {} file(s) into {:?}", inputs.len(), output);
+
+        // 列表中的相对路径按列表文件所在目录解析，统一使用绝对路径
+        let inputs: Vec<PathBuf> = inputs
+            .iter()
+            .map(|p| std::fs::canonicalize(p).unwrap_or_else(|_| p.clone()))
+            .collect();
+
+        // 列表文件与输出放在同一目录，结束后删除
+        let list_path = output.with_extension("concat.txt");
+        std::fs::write(&list_path, build_concat_list(&inputs))?;
+
+        let output = &crate::utils::file::long_path(output);
+        let result = Command::new(&self.ffmpeg_path)
+            .args(build_concat_args(&list_path, output))
+            .output();
+        let _ = std::fs::remove_file(&list_path);
+        let result =
+            result.map_err(|e| DownloaderError::MuxFailed(format!("Failed to execute ffmpeg: {}", e)))?;
+
+        if !result.status.success() {
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            return Err(DownloaderError::MuxFailed(format!(
+                "FFmpeg failed to concatenate: {}",
+                stderr
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// 将单个DASH分片流无损复制为独立可播放的文件（容器由输出扩展名决定）
+    pub fn remux_stream(&self, input: &Path, output: &Path) -> Result<()> {
+        tracing::info!("Remuxing {:?} to {:?}", input, output);
+        let output = &crate::utils::file::long_path(output);
+
+        let result = Command::new(&self.ffmpeg_path)
+            .args(build_remux_args(input, output))
+            .output()
+            .map_err(|e| DownloaderError::MuxFailed(format!("Failed to execute ffmpeg: {}", e)))?;
+
+        if !result.status.success() {
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            return Err(DownloaderError::MuxFailed(format!(
+                "FFmpeg failed to remux {}: {}",
+                input.display(),
+                stderr
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// 生成联系表（缩略图网格）
+    ///
+    /// 从视频中均匀抽取 `cols × rows` 帧，用FFmpeg的 `tile` 滤镜拼成一张jpg。
+    /// 未提供时长（秒）时从文件中读取。
+    pub fn generate_contact_sheet(
+        &self,
+        input: &Path,
+        output: &Path,
+        cols: u32,
+        rows: u32,
+        duration: Option<u64>,
+    ) -> Result<()> {
+        tracing::info!("Generating contact sheet: {:?}", output);
+        if let Some(capabilities) = self.capabilities() {
+            capabilities.require(&[], CONTACT_SHEET_FILTERS)?;
+        }
+
+        let duration = duration
+            .filter(|d| *d > 0)
+            .map(|d| d as f64)
+            .or_else(|| self.read_duration(input))
+            .ok_or_else(|| {
+                DownloaderError::MuxFailed(format!(
+                    "Could not determine duration of {}",
+                    input.display()
+                ))
+            })?;
+
+        let args = build_contact_sheet_args(input, output, cols, rows, duration);
+        let result = Command::new(&self.ffmpeg_path)
+            .args(&args)
+            .output()
+            .map_err(|e| DownloaderError::MuxFailed(format!("Failed to execute ffmpeg: {}", e)))?;
+
+        if !result.status.success() {
+            let stderr = String::from_utf8_lossy(&result.stderr);
+            return Err(DownloaderError::MuxFailed(format!(
+                "FFmpeg failed to generate contact sheet: {}",
+                stderr
+            )));
+        }
+
+        Ok(())
+    }
+
     fn create_chapter_file(&self, path: &Path, chapters: &[crate::types::Chapter]) -> Result<()> {
         let mut content = String::from(";FFMETADATA1\n");
 
@@ -234,3 +747,440 @@ impl Muxer {
         self.mux(video, audio, output, &Vec::new()).await
     }
 }
+
+/// 运行ffmpeg并读取 `-progress pipe:1` 输出更新进度条，返回与 `Command::output` 相同的结果
+fn run_with_progress(
+    cmd: &mut Command,
+    progress: &ProgressBar,
+) -> std::io::Result<std::process::Output> {
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // 另开线程读取stderr，避免管道写满导致ffmpeg阻塞
+    let mut stderr_pipe = child.stderr.take();
+    let stderr_reader = std::thread::spawn(move || {
+        let mut stderr = Vec::new();
+        if let Some(ref mut pipe) = stderr_pipe {
+            let _ = pipe.read_to_end(&mut stderr);
+        }
+        stderr
+    });
+
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines() {
+            if let Some(ms) = parse_progress_line(&line?) {
+                progress.set_position(ms.min(progress.length().unwrap_or(u64::MAX)));
+            }
+        }
+    }
+
+    let status = child.wait()?;
+    let stderr = stderr_reader.join().unwrap_or_default();
+    Ok(std::process::Output {
+        status,
+        stdout: Vec::new(),
+        stderr,
+    })
+}
+
+/// 解析ffmpeg `-progress` 输出中的已处理时长（毫秒）
+///
+/// `out_time_ms` 与 `out_time_us` 的单位实际上都是微秒；开始时可能输出 `N/A`
+pub fn parse_progress_line(line: &str) -> Option<u64> {
+    let (key, value) = line.trim().split_once('=')?;
+    match key {
+        "out_time_us" | "out_time_ms" => value.parse::<i64>().ok().map(|us| us.max(0) as u64 / 1000),
+        _ => None,
+    }
+}
+
+/// 构建FFmpeg混流参数
+///
+/// 所有输入（视频、音频、字幕、封面、章节元数据）都放在输出参数之前，
+/// 并使用显式的 `-map`，确保每个字幕轨都会被写入，且封面不会被误选为主视频流。
+pub fn build_mux_args(
+    video: &Path,
+    audio: &Path,
+    output: &Path,
+    options: &MuxOptions,
+    chapter_file: Option<&Path>,
+) -> Vec<OsString> {
+    let mut args: Vec<OsString> = Vec::new();
+    if let Some(ref trim) = options.trim {
+        trim_input_args(&mut args, trim.video_seek, trim.duration);
+    }
+    args.push("-i".into());
+    args.push(video.into());
+    if let Some(ref trim) = options.trim {
+        trim_input_args(&mut args, trim.audio_seek, trim.duration);
+    }
+    args.push("-i".into());
+    args.push(audio.into());
+    // 额外音轨紧跟主音轨，输入序号为 2..
+    for track in &options.extra_audio {
+        args.push("-i".into());
+        args.push(track.path.as_os_str().into());
+    }
+
+    // 输出机器可读的进度到stdout
+    if options.progress.is_some() {
+        args.extend(["-progress".into(), "pipe:1".into(), "-nostats".into()]);
+    }
+
+    // Add subtitles
+    for subtitle in &options.subtitles {
+        if let Some(ref trim) = options.trim {
+            trim_input_args(&mut args, trim.start, trim.duration);
+        }
+        args.push("-i".into());
+        args.push(subtitle.into());
+    }
+
+    let subtitle_input = 2 + options.extra_audio.len();
+    let mut next_input = subtitle_input + options.subtitles.len();
+
+    let cover_input = options.cover.as_ref().map(|cover| {
+        args.push("-i".into());
+        args.push(cover.into());
+        let index = next_input;
+        next_input += 1;
+        index
+    });
+
+    let chapter_input = chapter_file.map(|chapter_path| {
+        args.push("-i".into());
+        args.push(chapter_path.into());
+        next_input
+    });
+
+    // 显式映射输入流
+    args.push("-map".into());
+    args.push("0:v:0".into());
+    args.push("-map".into());
+    args.push("1:a:0".into());
+    for i in 0..options.extra_audio.len() {
+        args.push("-map".into());
+        args.push(format!("{}:a:0", 2 + i).into());
+    }
+    for i in 0..options.subtitles.len() {
+        args.push("-map".into());
+        args.push(format!("{}:0", subtitle_input + i).into());
+    }
+    if let Some(index) = cover_input {
+        args.push("-map".into());
+        args.push(format!("{}:0", index).into());
+    }
+
+    match options.reencode {
+        Some(ref reencode) => {
+            // 只重新编码主视频流，封面保持原样
+            args.push("-c:v:0".into());
+            args.push(ffmpeg_encoder(&reencode.video_codec).into());
+            if let Some(ref bitrate) = reencode.video_bitrate {
+                args.push("-b:v:0".into());
+                args.push(bitrate.into());
+            } else if let Some(crf) = reencode.crf {
+                args.push("-crf".into());
+                args.push(crf.to_string().into());
+            }
+            if cover_input.is_some() {
+                args.push("-c:v:1".into());
+                args.push("copy".into());
+            }
+            args.push("-c:a".into());
+            match reencode.audio_codec {
+                Some(ref codec) => args.push(ffmpeg_encoder(codec).into()),
+                None => args.push("copy".into()),
+            }
+        }
+        None => {
+            // Copy codecs (no re-encoding)
+            args.push("-c:v".into());
+            args.push("copy".into());
+            args.push("-c:a".into());
+            args.push("copy".into());
+        }
+    }
+
+    // 额外音轨标明语言，主音轨（原声）为默认音轨
+    for (i, track) in options.extra_audio.iter().enumerate() {
+        args.push(format!("-metadata:s:a:{}", i + 1).into());
+        args.push(format!("language={}", track.language).into());
+        args.push(format!("-disposition:a:{}", i + 1).into());
+        args.push("0".into());
+    }
+
+    // 保留HDR色彩元数据，避免播放器将输出识别为SDR
+    match options.hdr {
+        HdrType::Sdr => {}
+        HdrType::Hdr10 => match options.reencode {
+            // 重新编码时由编码器写入色彩信息
+            Some(_) => {
+                args.push("-color_primaries:v:0".into());
+                args.push("bt2020".into());
+                args.push("-color_trc:v:0".into());
+                args.push("smpte2084".into());
+                args.push("-colorspace:v:0".into());
+                args.push("bt2020nc".into());
+            }
+            // 复制流时 `-color_*` 不会改写码流中的VUI，需要通过比特流过滤器写入
+            None => {
+                if let Some(filter) = hdr10_metadata_filter(&options.video_codec) {
+                    args.push("-bsf:v:0".into());
+                    args.push(filter.into());
+                }
+            }
+        },
+        HdrType::DolbyVision => {
+            // 写入杜比视界配置记录（dvcC/dvvC）并保留RPU
+            args.push("-strict".into());
+            args.push("unofficial".into());
+        }
+    }
+
+    if !options.subtitles.is_empty() {
+        let format = MergeOutputFormat::from_path(output).unwrap_or_default();
+        args.push("-c:s".into());
+        args.push(format.subtitle_codec().into());
+
+        // 只有 MKV 支持附件
+        if let (MergeOutputFormat::Mkv, Some(font)) = (format, &options.font) {
+            args.push("-attach".into());
+            args.push(font.into());
+            args.push("-metadata:s:t:0".into());
+            args.push(format!("mimetype={}", crate::utils::font::mime_type(font)).into());
+        }
+    }
+
+    if cover_input.is_some() {
+        args.push("-disposition:v:1".into());
+        args.push("attached_pic".into());
+    }
+
+    // 添加章节元数据
+    if let Some(index) = chapter_input {
+        args.push("-map_metadata".into());
+        args.push(index.to_string().into());
+        args.push("-map_chapters".into());
+        args.push(index.to_string().into());
+    }
+
+    // 在章节元数据之后写入，同名标签以这里为准
+    for (key, value) in &options.metadata {
+        args.push("-metadata".into());
+        args.push(format!("{}={}", key, value).into());
+    }
+
+    // Overwrite output file
+    args.push("-y".into());
+
+    // Output file
+    args.push(output.into());
+
+    args
+}
+
+/// 复制HDR10视频流时写入 BT.2020 / PQ 色彩信息的比特流过滤器，不支持的编码返回 None
+///
+/// 色彩参数取 H.273 中的编号：色域 9（BT.2020）、传输特性 16（PQ）、矩阵 9（BT.2020 非恒定亮度）
+pub fn hdr10_metadata_filter(codec: &str) -> Option<&'static str> {
+    if codec.eq_ignore_ascii_case("hevc") {
+        Some("hevc_metadata=colour_primaries=9:transfer_characteristics=16:matrix_coefficients=9")
+    } else if codec.eq_ignore_ascii_case("av1") {
+        Some("av1_metadata=color_primaries=9:transfer_characteristics=16:matrix_coefficients=9")
+    } else {
+        None
+    }
+}
+
+/// 构建单流重新封装的FFmpeg参数（直接复制流，不重新编码）
+pub fn build_remux_args(input: &Path, output: &Path) -> Vec<OsString> {
+    vec![
+        "-i".into(),
+        input.into(),
+        "-map".into(),
+        "0".into(),
+        "-c".into(),
+        "copy".into(),
+        "-y".into(),
+        output.into(),
+    ]
+}
+
+/// 构建concat分离器的列表文件内容，每行一个 `file '<路径>'`
+///
+/// 路径中的单引号按concat语法转义为 `'\''`
+pub fn build_concat_list(inputs: &[PathBuf]) -> String {
+    inputs
+        .iter()
+        .map(|path| {
+            let path = path.to_string_lossy().replace('\'', r"'\''");
+            format!("file '{}'\n", path)
+        })
+        .collect()
+}
+
+/// 构建按列表文件拼接的FFmpeg参数（直接复制流，不重新编码）
+///
+/// `-safe 0` 允许列表中使用绝对路径及任意文件名
+pub fn build_concat_args(list: &Path, output: &Path) -> Vec<OsString> {
+    vec![
+        "-f".into(),
+        "concat".into(),
+        "-safe".into(),
+        "0".into(),
+        "-i".into(),
+        list.into(),
+        "-map".into(),
+        "0".into(),
+        "-c".into(),
+        "copy".into(),
+        "-y".into(),
+        output.into(),
+    ]
+}
+
+/// 联系表中每张缩略图的宽度（像素），高度按比例缩放
+pub const CONTACT_SHEET_TILE_WIDTH: u32 = 320;
+
+/// 构建生成联系表的FFmpeg参数
+///
+/// `fps` 滤镜按 `帧数/时长` 的速率均匀抽帧，`tile` 滤镜把抽出的帧拼成网格，只输出一帧。
+pub fn build_contact_sheet_args(
+    input: &Path,
+    output: &Path,
+    cols: u32,
+    rows: u32,
+    duration: f64,
+) -> Vec<OsString> {
+    let frames = cols * rows;
+    let duration = (duration.ceil() as u64).max(1);
+    let filter = format!(
+        "fps={}/{},scale={}:-2,tile={}x{}",
+        frames, duration, CONTACT_SHEET_TILE_WIDTH, cols, rows
+    );
+
+    vec![
+        "-v".into(),
+        "error".into(),
+        "-i".into(),
+        input.into(),
+        "-vf".into(),
+        filter.into(),
+        "-frames:v".into(),
+        "1".into(),
+        "-q:v".into(),
+        "3".into(),
+        "-y".into(),
+        output.into(),
+    ]
+}
+
+/// 解析 `ffmpeg -encoders` / `ffmpeg -filters` 的输出，返回组件名称
+///
+/// 每行为 `<标志> <名称> <说明>`，标志只由 `.`、`|` 和大写字母组成；
+/// 图例（`V..... = Video`）和分隔线不会被当作组件
+pub fn parse_ffmpeg_components(output: &str) -> HashSet<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let flags = fields.next()?;
+            let name = fields.next()?;
+            let is_flags = flags
+                .chars()
+                .all(|c| c == '.' || c == '|' || c.is_ascii_uppercase());
+            (is_flags && name != "=").then(|| name.to_string())
+        })
+        .collect()
+}
+
+/// 单独封装音频流（`--remux-each`）时使用的扩展名
+///
+/// MP4 容器不接受直接复制的 FLAC（Hi-Res 无损）音频，改用 `.flac`；AAC、E-AC-3 使用 `.m4a`
+pub fn audio_stream_extension(codec: &str) -> &'static str {
+    if codec.to_lowercase().starts_with("flac") {
+        "audio.flac"
+    } else {
+        "audio.m4a"
+    }
+}
+
+/// 将常用编码名称映射为FFmpeg编码器，其他名称原样传给FFmpeg
+pub fn ffmpeg_encoder(codec: &str) -> String {
+    match codec.to_lowercase().as_str() {
+        "avc" | "h264" | "x264" => "libx264".to_string(),
+        "hevc" | "h265" | "x265" => "libx265".to_string(),
+        "av1" => "libaom-av1".to_string(),
+        "aac" | "m4a" => "aac".to_string(),
+        "opus" => "libopus".to_string(),
+        _ => codec.to_string(),
+    }
+}
+
+/// 从FFmpeg输出中解析 `Duration: HH:MM:SS.xx`，返回秒数
+pub fn parse_ffmpeg_duration(output: &str) -> Option<f64> {
+    let line = output.lines().find(|l| l.trim_start().starts_with("Duration:"))?;
+    let value = line.trim_start().trim_start_matches("Duration:").trim();
+    let timestamp = value.split(',').next()?.trim();
+
+    let parts: Vec<&str> = timestamp.split(':').collect();
+    if parts.len() != 3 {
+        return None;
+    }
+
+    let hours = parts[0].parse::<f64>().ok()?;
+    let minutes = parts[1].parse::<f64>().ok()?;
+    let seconds = parts[2].parse::<f64>().ok()?;
+
+    Some(hours * 3600.0 + minutes * 60.0 + seconds)
+}
+
+/// ffmpeg所在目录中的ffprobe（如 `/opt/ffmpeg/bin/ffmpeg.exe` → `/opt/ffmpeg/bin/ffprobe.exe`）
+///
+/// 文件名不是ffmpeg时（如自定义的包装脚本）使用PATH中的ffprobe
+pub fn ffprobe_path(ffmpeg_path: &Path) -> PathBuf {
+    let is_ffmpeg = ffmpeg_path
+        .file_stem()
+        .is_some_and(|stem| stem.eq_ignore_ascii_case("ffmpeg"));
+    if !is_ffmpeg {
+        return PathBuf::from("ffprobe");
+    }
+
+    let mut probe = ffmpeg_path.with_file_name("ffprobe");
+    if let Some(extension) = ffmpeg_path.extension() {
+        probe.set_extension(extension);
+    }
+    probe
+}
+
+/// 解析 `ffprobe -show_entries format=duration -of default=noprint_wrappers=1:nokey=1` 的输出
+pub fn parse_ffprobe_duration(output: &str) -> Option<f64> {
+    output
+        .lines()
+        .find_map(|line| line.trim().parse::<f64>().ok())
+        .filter(|duration| duration.is_finite() && *duration > 0.0)
+}
+
+/// 从 `ffmpeg -i` 的输出中解析第一个视频流的分辨率，如 `Stream #0:0: Video: h264 ..., 1920x1080 [SAR 1:1 DAR 16:9], ...`
+pub fn parse_ffmpeg_resolution(output: &str) -> Option<(u32, u32)> {
+    let re = regex::Regex::new(r"\b(\d{2,5})x(\d{2,5})\b").unwrap();
+    let line = output
+        .lines()
+        .find(|l| l.trim_start().starts_with("Stream #") && l.contains("Video:"))?;
+    let cap = re.captures(line)?;
+    Some((cap[1].parse().ok()?, cap[2].parse().ok()?))
+}
+
+/// 判断实际时长与预期时长是否大致一致
+///
+/// 允许的误差为 3 秒或预期时长的 2%（取较大者），足以容忍音视频流长度的细微差异，
+/// 同时能发现CDN返回被截断的文件
+pub fn duration_matches(actual: f64, expected: u64) -> bool {
+    let expected = expected as f64;
+    let tolerance = (expected * 0.02).max(3.0);
+    (actual - expected).abs() <= tolerance
+}
@@ -0,0 +1,61 @@
+// Kodi/Jellyfin 的 `.nfo` 元数据文件，与输出视频同名放在同一目录
+use crate::types::{Page, VideoInfo};
+
+/// 生成 `<movie>` 格式的 nfo 内容
+///
+/// 多P视频的标题带上分P标题，时长按该分P计算
+pub fn build_nfo(video_info: &VideoInfo, page: Option<&Page>) -> String {
+    let (title, duration) = match page {
+        Some(page) if video_info.pages.len() > 1 => (
+            format!("{} - {}", video_info.title, page.title),
+            page.duration,
+        ),
+        Some(page) => (video_info.title.clone(), page.duration),
+        None => (video_info.title.clone(), video_info.duration),
+    };
+
+    let mut lines = vec![
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#.to_string(),
+        "<movie>".to_string(),
+    ];
+    let mut push = |tag: &str, value: &str| {
+        if !value.trim().is_empty() {
+            lines.push(format!("  <{tag}>{}</{tag}>", escape_xml(value)));
+        }
+    };
+
+    push("title", &title);
+    push("plot", &video_info.description);
+    push("studio", &video_info.uploader);
+    push("premiered", &video_info.upload_date);
+    push("year", video_info.upload_date.get(..4).unwrap_or_default());
+    if duration > 0 {
+        // Kodi 的 runtime 以分钟为单位
+        push("runtime", &duration.div_ceil(60).to_string());
+    }
+    push("thumb", &video_info.cover_url);
+    if let Some(extra) = video_info.bilibili_extra() {
+        push("genre", &extra.tname);
+        for tag in &extra.tags {
+            push("tag", tag);
+        }
+    }
+    lines.push(format!(
+        r#"  <uniqueid type="bilibili" default="true">{}</uniqueid>"#,
+        escape_xml(&video_info.id)
+    ));
+    lines.push("</movie>".to_string());
+
+    let mut content = lines.join("\n");
+    content.push('\n');
+    content
+}
+
+/// 转义XML文本中的特殊字符
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -1,10 +1,75 @@
+use crate::utils::file::format_size;
+use crate::utils::i18n::{t, tf, Msg};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 pub struct ProgressTracker {
     bars: Arc<Mutex<HashMap<String, ProgressBar>>>,
     multi_progress: MultiProgress,
+    stats: Mutex<RunStats>,
+    started: Instant,
+}
+
+/// 运行期间累计的下载统计
+#[derive(Debug, Default)]
+struct RunStats {
+    videos: HashSet<String>,
+    pages: usize,
+    bytes: u64,
+    skipped: usize,
+    failed: usize,
+}
+
+/// 运行结束时的摘要：下载数量、总大小、用时及跳过/失败的分P数
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunSummary {
+    pub videos: usize,
+    pub pages: usize,
+    /// 下载的音视频流总字节数
+    pub bytes: u64,
+    pub elapsed: Duration,
+    pub skipped: usize,
+    pub failed: usize,
+}
+
+impl RunSummary {
+    /// 是否没有处理任何分P（如只查看信息），此时不输出摘要
+    pub fn is_empty(&self) -> bool {
+        self.pages == 0 && self.skipped == 0 && self.failed == 0
+    }
+
+    /// 平均下载速度（字节/秒），按整个运行的用时计算
+    pub fn average_speed(&self) -> u64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            (self.bytes as f64 / secs) as u64
+        } else {
+            0
+        }
+    }
+
+    /// 供终端输出的摘要行
+    pub fn lines(&self) -> Vec<String> {
+        let secs = self.elapsed.as_secs();
+        let elapsed = format!("{}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60);
+
+        let mut lines = vec![
+            format!("📊 {}", t(Msg::RunSummary)),
+            format!("  {}", tf(Msg::RunDownloaded, &[&self.videos, &self.pages])),
+            format!("  {}", tf(Msg::RunTotalSize, &[&format_size(self.bytes)])),
+            format!("  {}", tf(Msg::RunElapsed, &[&elapsed])),
+            format!("  {}", tf(Msg::RunAverageSpeed, &[&format_size(self.average_speed())])),
+        ];
+        if self.skipped > 0 {
+            lines.push(format!("  ⏭ {}", tf(Msg::RunSkipped, &[&self.skipped])));
+        }
+        if self.failed > 0 {
+            lines.push(format!("  ✗ {}", tf(Msg::RunFailed, &[&self.failed])));
+        }
+        lines
+    }
 }
 
 impl ProgressTracker {
@@ -12,6 +77,39 @@ impl ProgressTracker {
         Self {
             bars: Arc::new(Mutex::new(HashMap::new())),
             multi_progress: MultiProgress::new(),
+            stats: Mutex::new(RunStats::default()),
+            started: Instant::now(),
+        }
+    }
+
+    /// 记录一个下载完成的分P及其下载的字节数
+    pub fn record_page(&self, video_id: &str, bytes: u64) {
+        let mut stats = self.stats.lock().unwrap();
+        stats.videos.insert(video_id.to_string());
+        stats.pages += 1;
+        stats.bytes += bytes;
+    }
+
+    /// 记录跳过的分P数
+    pub fn record_skipped(&self, pages: usize) {
+        self.stats.lock().unwrap().skipped += pages;
+    }
+
+    /// 记录下载失败的分P数
+    pub fn record_failed(&self, pages: usize) {
+        self.stats.lock().unwrap().failed += pages;
+    }
+
+    /// 到目前为止的统计
+    pub fn summary(&self) -> RunSummary {
+        let stats = self.stats.lock().unwrap();
+        RunSummary {
+            videos: stats.videos.len(),
+            pages: stats.pages,
+            bytes: stats.bytes,
+            elapsed: self.started.elapsed(),
+            skipped: stats.skipped,
+            failed: stats.failed,
         }
     }
 
@@ -31,6 +129,23 @@ impl ProgressTracker {
         pb_arc
     }
 
+    /// 按时长显示进度的进度条（用于混流），`duration` 为秒
+    pub fn create_time_bar(&self, id: &str, duration: u64) -> Arc<ProgressBar> {
+        let pb = self.multi_progress.add(ProgressBar::new(duration * 1000));
+        pb.set_style(
+            ProgressStyle::default_bar()
+                .template("{msg}\n{spinner:.green} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {percent}% ({eta})")
+                .expect("Failed to set progress style")
+                .progress_chars("#>-"),
+        );
+        pb.set_message(id.to_string());
+
+        let pb_arc = Arc::new(pb.clone());
+        self.bars.lock().unwrap().insert(id.to_string(), pb);
+
+        pb_arc
+    }
+
     #[allow(dead_code)]
     pub fn update(&self, id: &str, current: u64) {
         if let Some(pb) = self.bars.lock().unwrap().get(id) {
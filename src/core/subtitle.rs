@@ -17,6 +17,18 @@ struct SubtitleItem {
     content: String,
 }
 
+/// 按语言代码筛选字幕（不区分大小写）
+pub fn filter_by_language(subtitles: Vec<Subtitle>, languages: &[String]) -> Vec<Subtitle> {
+    subtitles
+        .into_iter()
+        .filter(|s| {
+            languages
+                .iter()
+                .any(|lang| lang.eq_ignore_ascii_case(&s.language_code))
+        })
+        .collect()
+}
+
 pub async fn download_and_convert_subtitle(
     client: &Arc<HttpClient>,
     subtitle: &Subtitle,
@@ -30,22 +42,25 @@ pub async fn download_and_convert_subtitle(
 
     // Download subtitle JSON
     let response = client.get(&subtitle.url, None).await?;
-    let json_text = response.text().await?;
-
-    // Parse JSON
-    let bili_subtitle: BilibiliSubtitle = serde_json::from_str(&json_text)
-        .map_err(|e| DownloaderError::Parse(format!("Failed to parse subtitle: {}", e)))?;
+    let json = response.bytes().await?;
 
-    // Convert to SRT format
-    let srt_content = convert_to_srt(&bili_subtitle);
+    let srt_content = convert_subtitle(&json)?;
 
     // Write to file
-    tokio::fs::write(output, srt_content).await?;
+    tokio::fs::write(crate::utils::file::long_path(output), srt_content).await?;
 
     tracing::info!("Subtitle saved: {:?}", output);
     Ok(())
 }
 
+/// 将B站JSON字幕转换为SRT文本（纯内存操作，便于作为库使用）
+pub fn convert_subtitle(json: &[u8]) -> Result<String> {
+    let bili_subtitle: BilibiliSubtitle = serde_json::from_slice(json)
+        .map_err(|e| DownloaderError::Parse(format!("Failed to parse subtitle: {}", e)))?;
+
+    Ok(convert_to_srt(&bili_subtitle))
+}
+
 fn convert_to_srt(subtitle: &BilibiliSubtitle) -> String {
     let mut srt = String::new();
 
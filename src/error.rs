@@ -4,7 +4,10 @@ use thiserror::Error;
 #[allow(dead_code)] // Some variants are reserved for future use
 pub enum DownloaderError {
     #[error("Network error: {0}")]
-    Network(#[from] reqwest::Error),
+    Network(reqwest::Error),
+
+    #[error("Timed out during {stage}: {url} (check the network, or retry later)")]
+    Timeout { stage: String, url: String },
 
     #[error("Platform not supported: {0}")]
     UnsupportedPlatform(String),
@@ -12,6 +15,9 @@ pub enum DownloaderError {
     #[error("Video not found: {0}")]
     VideoNotFound(String),
 
+    #[error("No downloadable pages: {0}")]
+    NoPages(String),
+
     #[error("Authentication required")]
     AuthRequired,
 
@@ -24,6 +30,9 @@ pub enum DownloaderError {
     #[error("Mux failed: {0}")]
     MuxFailed(String),
 
+    #[error("Verification failed: {0}")]
+    VerifyFailed(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 
@@ -36,18 +45,121 @@ pub enum DownloaderError {
     #[error("FFmpeg not found or not executable")]
     FFmpegNotFound,
 
+    #[error("FFmpeg lacks the {name} {kind} (install a full FFmpeg build that includes it)")]
+    FFmpegMissingComponent { kind: String, name: String },
+
     #[error("Invalid URL: {0}")]
     InvalidUrl(String),
 
     #[error("API error: {0}")]
     Api(String),
 
+    #[error("Request blocked by risk control: {0} (wait a while and retry, or provide --cookie)")]
+    RiskControl(String),
+
+    #[error("Access denied: {0} (the content may require login or a VIP account)")]
+    AccessDenied(String),
+
+    #[error("Content unavailable: {0} (it may have been removed or made private)")]
+    ContentRemoved(String),
+
+    #[error("Region locked: {0} (try --use-intl-api or a proxy)")]
+    RegionLocked(String),
+
+    #[error("Members only: {0} (charge the uploader with your account, then log in or pass its cookie)")]
+    MembersOnly(String),
+
+    #[error("DRM protected: {0} (encrypted streams cannot be downloaded or played back)")]
+    DrmProtected(String),
+
     #[error("Authentication error: {0}")]
     Auth(crate::auth::AuthError),
+
+    #[error("{failed} of {total} video(s) failed to download")]
+    PartialFailure { failed: usize, total: usize },
+}
+
+/// 进程退出码，供脚本区分错误类型
+pub mod exit_code {
+    /// 成功
+    pub const SUCCESS: i32 = 0;
+    /// 其他错误
+    pub const GENERAL: i32 = 1;
+    /// 参数或配置错误（与 clap 的参数错误退出码一致）
+    pub const USAGE: i32 = 2;
+    /// 网络错误或风控拦截，通常可以稍后重试
+    pub const NETWORK: i32 = 3;
+    /// 需要登录或权限不足
+    pub const AUTH: i32 = 4;
+    /// 视频不存在、已被删除或没有可下载的分P
+    pub const NOT_FOUND: i32 = 5;
+    /// 不支持的平台、无效链接或DRM加密内容
+    pub const UNSUPPORTED: i32 = 6;
+    /// 批量下载中部分视频失败
+    pub const PARTIAL_FAILURE: i32 = 7;
+    /// FFmpeg 不可用、缺少所需组件或混流/校验失败
+    pub const MUX: i32 = 8;
+    /// 内容有区域限制，可换用国际版API或代理重试
+    pub const REGION_LOCKED: i32 = 9;
+}
+
+impl DownloaderError {
+    /// 该错误对应的进程退出码（见 [`exit_code`]）
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            DownloaderError::Network(_)
+            | DownloaderError::Timeout { .. }
+            | DownloaderError::RiskControl(_) => exit_code::NETWORK,
+            DownloaderError::AuthRequired
+            | DownloaderError::AccessDenied(_)
+            | DownloaderError::MembersOnly(_)
+            | DownloaderError::Auth(_) => exit_code::AUTH,
+            DownloaderError::VideoNotFound(_)
+            | DownloaderError::NoPages(_)
+            | DownloaderError::ContentRemoved(_) => exit_code::NOT_FOUND,
+            DownloaderError::UnsupportedPlatform(_)
+            | DownloaderError::InvalidUrl(_)
+            | DownloaderError::DrmProtected(_) => exit_code::UNSUPPORTED,
+            DownloaderError::PartialFailure { .. } => exit_code::PARTIAL_FAILURE,
+            DownloaderError::MuxFailed(_)
+            | DownloaderError::VerifyFailed(_)
+            | DownloaderError::FFmpegNotFound
+            | DownloaderError::FFmpegMissingComponent { .. } => exit_code::MUX,
+            DownloaderError::Config(_) => exit_code::USAGE,
+            DownloaderError::RegionLocked(_) => exit_code::REGION_LOCKED,
+            _ => exit_code::GENERAL,
+        }
+    }
+
+    /// 为超时错误标明所处的阶段（如 `play-url`、`audio download (P3)`），其他错误原样返回
+    pub fn in_stage(self, stage: impl Into<String>) -> Self {
+        match self {
+            DownloaderError::Timeout { url, .. } => DownloaderError::Timeout {
+                stage: stage.into(),
+                url,
+            },
+            other => other,
+        }
+    }
 }
 
 pub type Result<T> = std::result::Result<T, DownloaderError>;
 
+/// 超时的请求转换为带有请求地址的 [`DownloaderError::Timeout`]，阶段由调用方通过
+/// [`DownloaderError::in_stage`] 补充
+impl From<reqwest::Error> for DownloaderError {
+    fn from(err: reqwest::Error) -> Self {
+        if err.is_timeout() {
+            DownloaderError::Timeout {
+                stage: "request".to_string(),
+                url: err.url().map_or_else(|| "unknown URL".to_string(), |url| url.to_string()),
+            }
+        } else {
+            DownloaderError::Network(err)
+        }
+    }
+}
+
 impl From<crate::auth::AuthError> for DownloaderError {
     fn from(err: crate::auth::AuthError) -> Self {
         DownloaderError::Auth(err)
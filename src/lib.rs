@@ -7,8 +7,11 @@ pub mod cli;
 pub mod core;
 pub mod error;
 pub mod platform;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod types;
 pub mod utils;
 
 // Re-export commonly used types
+pub use app::DownloadRequest;
 pub use error::{DownloaderError, Result};
@@ -1,41 +1,42 @@
-mod app;
-mod auth;
-mod cli;
-mod core;
-mod error;
-mod platform;
-mod types;
-mod utils;
-
-use app::Orchestrator;
-use clap::Parser;
+use rvd::{app, auth, cli, core, error, types, utils};
+use app::{DownloadOptions, Orchestrator};
+use clap::{CommandFactory, FromArgMatches};
 use cli::Cli;
 use error::DownloaderError;
+use std::fmt::Display;
+use std::path::Path;
 use tracing_subscriber::EnvFilter;
 use utils::config::Config;
+use utils::i18n::{t, tf, Lang, Msg};
 
 #[tokio::main]
 async fn main() {
-    // Initialize console with UTF-8 support on Windows
-    let _console_guard = utils::console::ConsoleGuard::new();
-
-    let exit_code = match run().await {
-        Ok(()) => 0,
-        Err(e) => {
-            eprintln!("\n❌ Error: {}", e);
-            1
+    let exit_code = {
+        // Initialize console with UTF-8 support on Windows
+        // (restored when the guard drops, before exiting)
+        let _console_guard = utils::console::ConsoleGuard::new();
+
+        match run().await {
+            Ok(code) => code,
+            Err(e) => {
+                eprintln!("\n❌ {}", tf(Msg::Error, &[&e]));
+                e.exit_code()
+            }
         }
     };
 
     std::process::exit(exit_code);
 }
 
-async fn run() -> Result<(), DownloaderError> {
-    // Parse CLI arguments
-    let cli = Cli::parse();
+/// 运行命令，返回进程退出码；不在这里直接退出，保证清理和收尾逻辑都能执行
+async fn run() -> Result<i32, DownloaderError> {
+    // Parse CLI arguments (keep the matches to know which flags were given explicitly)
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
     // Initialize logging
     init_logging(cli.verbose);
+    apply_lang(cli.lang.as_deref());
 
     // Handle login if requested and get credentials
     let login_auth = if cli.needs_login() {
@@ -45,32 +46,197 @@ async fn run() -> Result<(), DownloaderError> {
     };
 
     // If login was performed without a URL, just exit successfully
-    if cli.needs_login() && cli.url.is_none() {
-        return Ok(());
+    if cli.needs_login()
+        && cli.url.is_none()
+        && cli.batch_file.is_none()
+        && cli.retry_failed.is_none()
+        && !cli.is_remux()
+    {
+        return Ok(error::exit_code::SUCCESS);
     }
 
     // Load configuration
-    let config = if let Some(ref config_path) = cli.config_file {
+    let mut config = if let Some(ref config_path) = cli.config_file {
         Config::load(config_path)?
     } else {
         Config::load_default()?
     };
 
+    // Priority: CLI flags > selected profile > config defaults
+    if let Some(ref profile) = cli.profile {
+        config.apply_profile(profile)?;
+    }
+    cli.apply_config_defaults(&config, &matches);
+    apply_lang(cli.lang.as_deref());
+
+    // Remux mode: mux existing files and exit without touching the network
+    if cli.is_remux() {
+        return handle_remux(&cli, &config)
+            .await
+            .map(|()| error::exit_code::SUCCESS);
+    }
+
+    // Connection diagnostics: print the report and exit
+    if cli.test_connection {
+        let options = DownloadOptions::from(&cli);
+        let report = app::diagnostics::run(&config, &options, login_auth).await?;
+        for line in report.lines() {
+            println!("{}", line);
+        }
+        // 有主机无法连接时以网络错误的退出码结束，便于脚本判断
+        return Ok(if report.all_reachable() {
+            error::exit_code::SUCCESS
+        } else {
+            error::exit_code::NETWORK
+        });
+    }
+
+    // Player for --play-after: CLI > config
+    let player = cli
+        .player
+        .clone()
+        .or_else(|| config.paths.as_ref().and_then(|p| p.player.clone()));
+
     // Create orchestrator with login auth if available
-    let mut orchestrator = Orchestrator::new(config, &cli)?;
+    let options = DownloadOptions::from(&cli);
+    let mut orchestrator = Orchestrator::new(config, &options)?;
     
     // If we have login auth, it takes priority
     if let Some(auth) = login_auth {
         orchestrator.set_auth(Some(auth));
     }
 
-    // Run the download
-    orchestrator.run(cli).await?;
+    // Run the download (Ctrl-C/SIGTERM stop it and apply --on-interrupt)
+    let interrupt_policy = options.on_interrupt;
+    let entries = match (&cli.batch_file, &cli.retry_failed) {
+        (Some(batch_file), _) => Some(read_batch_file(batch_file)?),
+        (None, Some(failures_file)) => Some(read_failures_file(failures_file)?),
+        (None, None) => None,
+    };
+    let download = async {
+        match entries {
+            Some(ref entries) => orchestrator.run_urls(entries, options).await,
+            None => orchestrator.run(options).await,
+        }
+    };
+    let result = tokio::select! {
+        result = download => Ok(result),
+        signal = utils::signal::wait_for_signal() => Err(signal),
+    };
+    let (result, interrupted) = match result {
+        Ok(result) => (result, None),
+        Err(signal) => {
+            println!("\n⚠ {}", t(Msg::Interrupted));
+            orchestrator.handle_interrupt(interrupt_policy).await;
+            (Ok(Vec::new()), Some(signal))
+        }
+    };
 
-    Ok(())
+    orchestrator.flush_cookies();
+
+    if let Some(ref path) = cli.failures_file {
+        write_failures_file(path, &orchestrator.failures());
+    }
+
+    // 部分失败时也输出摘要
+    let summary = orchestrator.summary();
+    if !summary.is_empty() {
+        println!();
+        for line in summary.lines() {
+            println!("{}", line);
+        }
+    }
+    // 中断时仍写入失败记录和摘要，再以信号对应的退出码结束
+    if let Some(signal) = interrupted {
+        return Ok(signal.exit_code());
+    }
+    let outputs = result?;
+
+    if cli.play_after {
+        let target = if cli.open_first {
+            outputs.first()
+        } else {
+            outputs.last()
+        };
+        if let Some(path) = target {
+            println!("▶ {}", tf(Msg::Opening, &[&path.display()]));
+            if let Err(e) = utils::player::open_file(path, player.as_deref()) {
+                tracing::warn!("Failed to open {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    Ok(error::exit_code::SUCCESS)
+}
+
+/// 设置输出语言（`--lang` 或配置文件 `lang`），未指定时保持系统语言
+fn apply_lang(lang: Option<&str>) {
+    let Some(value) = lang else {
+        return;
+    };
+    match Lang::parse(value) {
+        Some(lang) => utils::i18n::set_lang(lang),
+        None => tracing::warn!("Unknown language '{}', expected zh or en", value),
+    }
 }
 
-async fn handle_login(cli: &Cli) -> Result<crate::types::Auth, DownloaderError> {
+/// 读取 `--retry-failed` 指定的失败记录文件
+fn read_failures_file(path: &Path) -> Result<Vec<utils::file::BatchEntry>, DownloaderError> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        DownloaderError::Config(format!("Failed to read failures file {}: {}", path.display(), e))
+    })?;
+
+    let entries = utils::file::parse_failures_file(&content)?;
+    if entries.is_empty() {
+        return Err(DownloaderError::Config(format!(
+            "Nothing to retry in {}",
+            path.display()
+        )));
+    }
+    println!("🔁 {}", tf(Msg::RetryingFailures, &[&entries.len()]));
+    Ok(entries)
+}
+
+/// 写入 `--failures-file`：没有失败时删除上次留下的文件，避免重复重试
+fn write_failures_file(path: &Path, failures: &[utils::file::FailedDownload]) {
+    if failures.is_empty() {
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(path) {
+                tracing::warn!("Failed to remove {}: {}", path.display(), e);
+            }
+        }
+        return;
+    }
+
+    match std::fs::write(path, utils::file::format_failures_file(failures)) {
+        Ok(()) => println!(
+            "📝 {}",
+            tf(Msg::FailuresWritten, &[&failures.len() as &dyn Display, &path.display()])
+        ),
+        Err(e) => tracing::warn!("Failed to write failures file {}: {}", path.display(), e),
+    }
+}
+
+/// 读取 `--batch-file` 中的URL列表（`-` 表示标准输入）
+fn read_batch_file(path: &str) -> Result<Vec<utils::file::BatchEntry>, DownloaderError> {
+    let content = if path == "-" {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        std::fs::read_to_string(path).map_err(|e| {
+            DownloaderError::Config(format!("Failed to read batch file {}: {}", path, e))
+        })?
+    };
+
+    let entries = utils::file::parse_batch_file(&content)?;
+    if entries.is_empty() {
+        return Err(DownloaderError::Config(format!("No URLs found in batch file {}", path)));
+    }
+    tracing::info!("Loaded {} URL(s) from {}", entries.len(), path);
+
+    Ok(entries)
+}
+
+async fn handle_login(cli: &Cli) -> Result<types::Auth, DownloaderError> {
     use auth::login::LoginManager;
     use auth::providers::BilibiliAuthProvider;
     use auth::storage::CredentialStorage;
@@ -90,26 +256,130 @@ async fn handle_login(cli: &Cli) -> Result<crate::types::Auth, DownloaderError>
     let provider = Box::new(BilibiliAuthProvider::new(http_client, api_mode));
 
     // Create login manager
-    let manager = LoginManager::new(provider);
+    let manager = LoginManager::new(provider).with_ascii_qrcode(cli.login_qr_ascii);
 
     // Perform login
-    let credentials = manager.perform_qr_login().await?;
+    let credentials = if cli.login_sms {
+        let prompt = |label: &str| -> Result<String, DownloaderError> {
+            dialoguer::Input::<String>::new()
+                .with_prompt(label)
+                .allow_empty(true)
+                .interact_text()
+                .map(|value| value.trim().to_string())
+                .map_err(|e| DownloaderError::Parse(format!("Input failed: {}", e)))
+        };
+        let phone = match cli.phone {
+            Some(ref phone) => phone.clone(),
+            None => prompt(t(Msg::PhonePrompt))?,
+        };
+        manager.perform_sms_login(cli.country_code, &phone, prompt).await?
+    } else {
+        manager.perform_qr_login().await?
+    };
 
     // Save or display credentials
     if let Some(ref config_path) = cli.config_file {
         CredentialStorage::save_to_config(&credentials, config_path)?;
         tracing::info!("✓ 凭证已保存到配置文件");
-        println!("\n✓ 登录成功！凭证已保存到配置文件。");
+        println!("\n✓ {}", t(Msg::LoginSuccessSaved));
     } else {
         tracing::info!("ℹ️  凭证仅在本次会话中有效（未指定配置文件）");
-        println!("\n✓ 登录成功！");
-        println!("ℹ️  提示：使用 --config-file 参数可以保存凭证以供后续使用。");
+        println!("\n✓ {}", t(Msg::LoginSuccess));
+        println!("ℹ️  {}", t(Msg::LoginSaveHint));
     }
 
     // Convert credentials to Auth and return
     Ok(CredentialStorage::to_auth(&credentials))
 }
 
+async fn handle_remux(cli: &Cli, config: &Config) -> Result<(), DownloaderError> {
+    use core::muxer::{HdrType, MuxOptions, Muxer};
+    use std::path::PathBuf;
+
+    let inputs = cli.remux.as_deref().unwrap_or_default();
+    let (video, audio) = match inputs {
+        [video, audio] => (video, audio),
+        _ => {
+            return Err(DownloaderError::Config(
+                "--remux requires a video file and an audio file".to_string(),
+            ))
+        }
+    };
+
+    for input in [video, audio] {
+        if !input.exists() {
+            return Err(DownloaderError::Config(format!(
+                "Remux input not found: {}",
+                input.display()
+            )));
+        }
+    }
+
+    let chapters = if let Some(ref chapters_path) = cli.remux_chapters {
+        let content = std::fs::read_to_string(chapters_path)?;
+        serde_json::from_str::<Vec<types::Chapter>>(&content)
+            .map_err(|e| DownloaderError::Parse(format!("Failed to parse chapters file: {}", e)))?
+    } else {
+        Vec::new()
+    };
+
+    let output = cli
+        .output
+        .as_ref()
+        .map(PathBuf::from)
+        .unwrap_or_else(|| utils::file::remux_output_path(video));
+    // 与下载流程一致，--merge-output-format 决定最终容器
+    let output = match cli.get_merge_output_format() {
+        Some(format) => output.with_extension(format.extension()),
+        None => output,
+    };
+
+    if let Some(parent) = output.parent() {
+        if !parent.as_os_str().is_empty() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+    }
+
+    let muxer = Muxer::new_with_options(
+        cli.ffmpeg_path
+            .clone()
+            .or_else(|| config.paths.as_ref().and_then(|p| p.ffmpeg.clone())),
+        cli.use_mp4box,
+    )?;
+
+    let reencode = cli.get_reencode_options();
+    // 混流前确认 FFmpeg 包含重新编码所需的编码器
+    if let Some(ref reencode) = reencode {
+        muxer.check_reencode(reencode)?;
+    }
+
+    let options = MuxOptions {
+        subtitles: cli.remux_subtitle.clone(),
+        chapters,
+        cover: cli.remux_cover.clone(),
+        hdr: HdrType::Sdr,
+        video_codec: String::new(),
+        reencode,
+        progress: None,
+        metadata: Vec::new(),
+        trim: None,
+        // 只附加明确指定的字体
+        font: cli
+            .font
+            .as_deref()
+            .or(config.subtitle_font())
+            .and_then(|spec| utils::font::resolve_font(Some(spec)))
+            .and_then(|font| font.path),
+        extra_audio: Vec::new(),
+    };
+
+    println!("🔄 {}", tf(Msg::Remuxing, &[&video.display(), &audio.display()]));
+    muxer.mux_advanced(video, audio, &output, &options).await?;
+    println!("✓ {}", tf(Msg::MuxedTo, &[&output.display()]));
+
+    Ok(())
+}
+
 fn init_logging(verbose: bool) {
     let filter = if verbose {
         EnvFilter::new("rvd=debug,info")
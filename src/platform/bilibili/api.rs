@@ -19,6 +19,9 @@ pub struct BangumiApiResponse<T> {
 #[derive(Debug, Deserialize)]
 pub struct BangumiPlayUrlResult {
     pub video_info: PlayUrlData,
+    /// 部分付费番剧使用DRM加密
+    #[serde(default)]
+    pub is_drm: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -33,6 +36,41 @@ pub struct VideoInfoData {
     pub pubdate: u64,
     pub owner: Owner,
     pub pages: Vec<PageData>,
+    #[serde(default)]
+    pub tid: u32,
+    #[serde(default)]
+    pub tname: String,
+    #[serde(default)]
+    pub copyright: u32,
+    #[serde(default)]
+    pub stat: VideoStat,
+    /// 充电专属视频，只有给UP主充电的用户可以观看
+    #[serde(default)]
+    pub is_upower_exclusive: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct VideoStat {
+    #[serde(default)]
+    pub view: u64,
+    #[serde(default)]
+    pub danmaku: u64,
+    #[serde(default)]
+    pub reply: u64,
+    #[serde(default)]
+    pub favorite: u64,
+    #[serde(default)]
+    pub coin: u64,
+    #[serde(default)]
+    pub share: u64,
+    #[serde(default)]
+    pub like: u64,
+}
+
+// 视频标签
+#[derive(Debug, Deserialize)]
+pub struct TagData {
+    pub tag_name: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -52,8 +90,38 @@ pub struct PageData {
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 pub struct PlayUrlData {
+    /// 当前请求实际返回的清晰度（durl格式时使用）
+    #[serde(default)]
+    pub quality: u32,
+    /// 视频列出的全部清晰度（不代表当前账号都能获取）
+    #[serde(default)]
+    pub accept_quality: Vec<u32>,
     pub dash: Option<DashData>,
     pub durl: Option<Vec<DurlData>>,
+    /// 多语言音轨（原声以外的配音），请求时需带上 support_multi_audio=true
+    #[serde(default)]
+    pub language: Option<LanguageData>,
+    /// 流是否经过DRM加密（加密的流下载后无法播放）
+    #[serde(default)]
+    pub is_drm: bool,
+    /// DRM技术类型，0 表示未加密
+    #[serde(default)]
+    pub drm_tech_type: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LanguageData {
+    #[serde(default)]
+    pub items: Vec<LanguageItem>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LanguageItem {
+    pub lang: String,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub audio: Vec<DashStream>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -91,6 +159,28 @@ pub struct DashStream {
     pub height: u32,
     #[serde(default)]
     pub frame_rate: String,
+    /// 分段信息（Web接口为 segment_base，部分接口为 SegmentBase）
+    #[serde(default, alias = "SegmentBase")]
+    pub segment_base: Option<DashSegmentBase>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DashSegmentBase {
+    /// 初始化段的字节范围，如 `0-927`
+    #[serde(alias = "Initialization")]
+    pub initialization: String,
+    /// sidx 的字节范围，如 `928-1415`
+    #[serde(alias = "indexRange")]
+    pub index_range: String,
+}
+
+impl DashSegmentBase {
+    pub fn to_segment_base(&self) -> Option<crate::types::SegmentBase> {
+        Some(crate::types::SegmentBase {
+            initialization: crate::core::clip::parse_byte_range(&self.initialization)?,
+            index_range: crate::core::clip::parse_byte_range(&self.index_range)?,
+        })
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -332,6 +422,8 @@ pub struct MediaListItem {
 #[derive(Debug, Deserialize)]
 pub struct SeriesListData {
     pub meta: SeriesMeta,
+    /// 翻页超出末尾时可能缺失
+    #[serde(default)]
     pub archives: Vec<SeriesArchive>,
     pub page: SeriesPage,
 }
@@ -350,6 +442,8 @@ pub struct SeriesArchive {
     pub aid: u64,
     pub bvid: String,
     pub title: String,
+    #[serde(default)]
+    pub pubdate: i64,
 }
 
 #[allow(dead_code)]
@@ -385,3 +479,120 @@ pub struct ClipInfo {
     pub start: f64,
     pub end: f64,
 }
+
+// 评论相关数据结构
+#[derive(Debug, Deserialize)]
+pub struct ReplyMainData {
+    #[serde(default)]
+    pub cursor: ReplyCursor,
+    #[serde(default)]
+    pub replies: Option<Vec<ReplyItem>>,
+    /// 置顶评论，只在第一页返回
+    #[serde(default)]
+    pub top_replies: Option<Vec<ReplyItem>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReplyCursor {
+    #[serde(default)]
+    pub is_end: bool,
+    #[serde(default)]
+    pub pagination_reply: Option<ReplyPagination>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplyPagination {
+    #[serde(default)]
+    pub next_offset: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplyItem {
+    pub rpid: u64,
+    #[serde(default)]
+    pub mid: u64,
+    #[serde(default)]
+    pub ctime: i64,
+    #[serde(default)]
+    pub like: u64,
+    #[serde(default)]
+    pub rcount: u64,
+    #[serde(default)]
+    pub member: ReplyMember,
+    #[serde(default)]
+    pub content: ReplyContent,
+    /// 楼中楼预览（最多几条）
+    #[serde(default)]
+    pub replies: Option<Vec<ReplyItem>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReplyMember {
+    #[serde(default)]
+    pub uname: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReplyContent {
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplyListData {
+    #[serde(default)]
+    pub page: ReplyPage,
+    #[serde(default)]
+    pub replies: Option<Vec<ReplyItem>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ReplyPage {
+    /// 回复总数
+    #[serde(default)]
+    pub count: u32,
+}
+
+// 直播回放（录像）信息
+#[derive(Debug, Deserialize)]
+pub struct LiveRecordInfoData {
+    pub live_record_info: LiveRecordInfo,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LiveRecordInfo {
+    pub rid: String,
+    #[serde(default)]
+    pub uid: u64,
+    #[serde(default)]
+    pub uname: String,
+    pub title: String,
+    #[serde(default)]
+    pub cover: String,
+    #[serde(default)]
+    pub area_name: String,
+    #[serde(default)]
+    pub start_timestamp: u64,
+    /// 回放总时长（毫秒）
+    #[serde(default)]
+    pub length: u64,
+}
+
+// 直播回放的分段播放地址
+#[derive(Debug, Deserialize)]
+pub struct LiveRecordUrlData {
+    #[serde(default)]
+    pub list: Vec<LiveRecordSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LiveRecordSegment {
+    pub url: String,
+    #[serde(default)]
+    pub backup_url: Option<String>,
+    #[serde(default)]
+    pub size: u64,
+    /// 分段时长（毫秒）
+    #[serde(default)]
+    pub length: u64,
+}
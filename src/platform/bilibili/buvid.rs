@@ -0,0 +1,220 @@
+// buvid3/buvid4 设备指纹Cookie
+// 部分接口（空间、WBI签名接口等）在缺少buvid时会间歇性返回 -352/-412 风控错误
+
+use crate::error::{DownloaderError, Result};
+use crate::types::Auth;
+use crate::utils::cookie::parse_cookie_pairs;
+use crate::utils::http::HttpClient;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SPI_API: &str = "https://api.bilibili.com/x/frontend/finger/spi";
+
+/// 磁盘缓存的有效期，过期后重新通过spi接口获取
+const CACHE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// buvid管理器：首次使用时确保请求中携带buvid3/buvid4
+pub struct BuvidManager {
+    client: Arc<HttpClient>,
+    cache_path: Option<PathBuf>,
+    ensured: bool,
+}
+
+/// 磁盘缓存内容
+#[derive(Debug, Serialize, Deserialize)]
+struct BuvidCache {
+    /// `buvid3=...; buvid4=...`
+    cookie: String,
+    /// 获取时间（Unix秒）
+    cached_at: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpiResponse {
+    code: i32,
+    #[serde(default)]
+    message: String,
+    data: Option<SpiData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpiData {
+    b_3: String,
+    b_4: String,
+}
+
+impl BuvidManager {
+    /// 创建buvid管理器，缓存文件位于 `~/.config/rvd/buvid`
+    pub fn new(client: Arc<HttpClient>) -> Self {
+        let cache_path = dirs::home_dir().map(|home| home.join(".config").join("rvd").join("buvid"));
+        Self {
+            client,
+            cache_path,
+            ensured: false,
+        }
+    }
+
+    /// 确保后续请求携带buvid Cookie
+    ///
+    /// 优先使用Auth或Cookie存储中已有的buvid3，其次是未过期的磁盘缓存，最后通过spi接口获取。
+    /// 获取失败不会中断下载，只记录警告。
+    pub async fn ensure(&mut self, auth: Option<&Auth>) {
+        if self.ensured {
+            return;
+        }
+        self.ensured = true;
+
+        let jar = self.client.cookie_jar();
+        let auth_has_buvid = auth
+            .and_then(|a| a.cookie.as_deref())
+            .map(|cookie| parse_cookie_pairs(cookie).iter().any(|(name, _)| name == "buvid3"))
+            .unwrap_or(false);
+        if auth_has_buvid || jar.get("buvid3").is_some() {
+            return;
+        }
+
+        if let Some(cached) = self.load_cache() {
+            tracing::debug!("Using cached buvid cookies");
+            jar.seed(&cached);
+            return;
+        }
+
+        match self.fetch().await {
+            Ok((buvid3, buvid4)) => {
+                tracing::debug!("Acquired buvid3: {}", buvid3);
+                jar.insert("buvid3", &buvid3);
+                jar.insert("buvid4", &buvid4);
+                self.save_cache(&format!("buvid3={}; buvid4={}", buvid3, buvid4));
+            }
+            Err(e) => {
+                tracing::warn!("Failed to acquire buvid cookies: {}", e);
+            }
+        }
+    }
+
+    async fn fetch(&self) -> Result<(String, String)> {
+        // 只请求一次，避免网络不可用时重试拖慢解析
+        let response = self.client.client.get(SPI_API).send().await?;
+        let json_text = response.text().await?;
+
+        tracing::debug!("SPI response: {}", json_text);
+
+        parse_spi_response(&json_text)
+    }
+
+    /// 读取未过期的缓存；旧版本的纯文本缓存没有时间戳，视为过期
+    fn load_cache(&self) -> Option<String> {
+        let content = std::fs::read_to_string(self.cache_path.as_ref()?).ok()?;
+        let cache: BuvidCache = match serde_json::from_str(&content) {
+            Ok(cache) => cache,
+            Err(e) => {
+                tracing::debug!("Ignoring unreadable buvid cache: {}", e);
+                return None;
+            }
+        };
+
+        let age = now_secs().saturating_sub(cache.cached_at);
+        if age >= CACHE_TTL.as_secs() {
+            tracing::debug!("Buvid cache expired ({}s old)", age);
+            return None;
+        }
+        let has_buvid3 = parse_cookie_pairs(&cache.cookie)
+            .iter()
+            .any(|(name, value)| name == "buvid3" && !value.is_empty());
+        has_buvid3.then_some(cache.cookie)
+    }
+
+    fn save_cache(&self, cookie: &str) {
+        let Some(ref path) = self.cache_path else {
+            return;
+        };
+        let cache = BuvidCache {
+            cookie: cookie.to_string(),
+            cached_at: now_secs(),
+        };
+        let result = serde_json::to_string(&cache)
+            .map_err(std::io::Error::other)
+            .and_then(|json| {
+                path.parent()
+                    .map_or(Ok(()), std::fs::create_dir_all)
+                    .and_then(|_| std::fs::write(path, json))
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to cache buvid cookies: {}", e);
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// 解析spi接口响应，返回 (buvid3, buvid4)
+fn parse_spi_response(json_text: &str) -> Result<(String, String)> {
+    let response: SpiResponse = serde_json::from_str(json_text)
+        .map_err(|e| DownloaderError::Parse(format!("Failed to parse spi response: {}", e)))?;
+
+    if response.code != 0 {
+        return Err(DownloaderError::Api(format!(
+            "SPI API error (code: {}): {}",
+            response.code, response.message
+        )));
+    }
+
+    let data = response
+        .data
+        .ok_or_else(|| DownloaderError::Api("No spi data".to_string()))?;
+
+    Ok((data.b_3, data.b_4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_spi_response() {
+        let json = r#"{"code":0,"message":"ok","data":{"b_3":"AAA-BBB-CCCinfoc","b_4":"DDD-EEE-FFF=="}}"#;
+        let (buvid3, buvid4) = parse_spi_response(json).unwrap();
+        assert_eq!(buvid3, "AAA-BBB-CCCinfoc");
+        assert_eq!(buvid4, "DDD-EEE-FFF==");
+    }
+
+    #[test]
+    fn test_parse_spi_response_error() {
+        let json = r#"{"code":-412,"message":"请求被拦截","data":null}"#;
+        assert!(parse_spi_response(json).is_err());
+    }
+
+    #[test]
+    fn test_cache_expires_after_ttl() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let manager = BuvidManager {
+            client: Arc::new(HttpClient::new().unwrap()),
+            cache_path: Some(temp_dir.path().join("buvid")),
+            ensured: false,
+        };
+
+        manager.save_cache("buvid3=AAA; buvid4=BBB");
+        assert_eq!(manager.load_cache().as_deref(), Some("buvid3=AAA; buvid4=BBB"));
+
+        let expired = BuvidCache {
+            cookie: "buvid3=AAA; buvid4=BBB".to_string(),
+            cached_at: now_secs() - CACHE_TTL.as_secs(),
+        };
+        std::fs::write(
+            manager.cache_path.as_ref().unwrap(),
+            serde_json::to_string(&expired).unwrap(),
+        )
+        .unwrap();
+        assert!(manager.load_cache().is_none());
+
+        // 旧版本的纯文本缓存没有时间戳
+        std::fs::write(manager.cache_path.as_ref().unwrap(), "buvid3=AAA; buvid4=BBB").unwrap();
+        assert!(manager.load_cache().is_none());
+    }
+}
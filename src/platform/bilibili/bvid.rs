@@ -0,0 +1,80 @@
+// AV号与BV号互相转换
+// 算法参考 bilibili-API-collect 文档（2023年起的新版算法，支持 2^51 以内的aid）
+
+use crate::error::{DownloaderError, Result};
+
+const XOR_CODE: u64 = 23442827791579;
+const MASK_CODE: u64 = 2251799813685247;
+const MAX_AID: u64 = 1 << 51;
+const BASE: u64 = 58;
+const ALPHABET: &[u8] = b"FcwAPNKTMug3GV5Lj7EJnHpWsx4tb8haYeviqBz6rkCy12mUSDQX9RdoZf";
+
+/// AV号转BV号，如 `170001` -> `BV17x411w7KC`
+pub fn av_to_bv(aid: u64) -> String {
+    let mut bytes = *b"BV1000000000";
+    let mut index = bytes.len() - 1;
+    let mut tmp = (MAX_AID | aid) ^ XOR_CODE;
+    while tmp > 0 {
+        bytes[index] = ALPHABET[(tmp % BASE) as usize];
+        tmp /= BASE;
+        index -= 1;
+    }
+    bytes.swap(3, 9);
+    bytes.swap(4, 7);
+
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+/// BV号转AV号，如 `BV17x411w7KC` -> `170001`（不区分 `BV`/`bv` 前缀大小写）
+pub fn bv_to_av(bvid: &str) -> Result<u64> {
+    let invalid = || DownloaderError::InvalidUrl(format!("Invalid BV id: {}", bvid));
+
+    let mut bytes: Vec<u8> = bvid.as_bytes().to_vec();
+    if bytes.len() != 12 || !bytes[..2].eq_ignore_ascii_case(b"BV") {
+        return Err(invalid());
+    }
+    bytes.swap(3, 9);
+    bytes.swap(4, 7);
+
+    let mut tmp: u64 = 0;
+    for byte in &bytes[3..] {
+        let digit = ALPHABET.iter().position(|c| c == byte).ok_or_else(invalid)?;
+        tmp = tmp * BASE + digit as u64;
+    }
+
+    Ok((tmp & MASK_CODE) ^ XOR_CODE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VECTORS: &[(u64, &str)] = &[
+        (1, "BV1xx411c7mQ"),
+        (2, "BV1xx411c7mD"),
+        (170001, "BV17x411w7KC"),
+        (111298867365120, "BV1L9Uoa9EUx"),
+    ];
+
+    #[test]
+    fn test_av_to_bv() {
+        for (aid, bvid) in VECTORS {
+            assert_eq!(av_to_bv(*aid), *bvid);
+        }
+    }
+
+    #[test]
+    fn test_bv_to_av() {
+        for (aid, bvid) in VECTORS {
+            assert_eq!(bv_to_av(bvid).unwrap(), *aid);
+        }
+        assert_eq!(bv_to_av("bv17x411w7KC").unwrap(), 170001);
+    }
+
+    #[test]
+    fn test_bv_to_av_invalid() {
+        assert!(bv_to_av("BV17x411w7K").is_err());
+        assert!(bv_to_av("AV17x411w7KC").is_err());
+        assert!(bv_to_av("BV17x411w7K0").is_err());
+    }
+}
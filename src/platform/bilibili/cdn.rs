@@ -0,0 +1,57 @@
+// CDN 主机选择：按策略调整每个流的主地址与备用地址的顺序
+//
+// 接口返回的地址以国内节点为主，海外用户下载很慢；海外友好的镜像（akamai、
+// `*ov` 节点）通常也在备用地址中，只需把它们排到前面
+
+use crate::types::Stream;
+
+/// CDN 主机选择策略，库调用方可实现自己的策略
+pub trait CdnSelector: Send + Sync {
+    /// 地址的优先级，越大越优先；相同优先级保持接口返回的顺序
+    fn score(&self, url: &str) -> i32;
+}
+
+/// 保持接口返回的顺序（默认）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultCdn;
+
+impl CdnSelector for DefaultCdn {
+    fn score(&self, _url: &str) -> i32 {
+        0
+    }
+}
+
+/// 海外友好的节点主机特征
+const OVERSEAS_HOST_MARKERS: &[&str] = &["akamaized.net", "ov.bilivideo.com"];
+
+/// 优先使用海外友好的节点（akamai 镜像、`upos-sz-mirroraliov` 等 `ov` 节点），
+/// 国内 PCDN 节点（`mcdn.bilivideo.cn`、IP 地址）排在最后
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OverseasCdn;
+
+impl CdnSelector for OverseasCdn {
+    fn score(&self, url: &str) -> i32 {
+        let Some(host) = url::Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string)) else {
+            return 0;
+        };
+
+        if OVERSEAS_HOST_MARKERS.iter().any(|marker| host.ends_with(marker)) {
+            1
+        } else if host.ends_with("mcdn.bilivideo.cn") || host.parse::<std::net::IpAddr>().is_ok() {
+            -1
+        } else {
+            0
+        }
+    }
+}
+
+/// 按策略重排流的地址：得分最高的作为主地址，其余依次作为备用地址
+pub fn reorder_urls(selector: &dyn CdnSelector, stream: &mut Stream) {
+    let mut urls = Vec::with_capacity(stream.backup_urls.len() + 1);
+    urls.push(std::mem::take(&mut stream.url));
+    urls.append(&mut stream.backup_urls);
+    urls.sort_by_key(|url| std::cmp::Reverse(selector.score(url)));
+
+    stream.url = urls.remove(0);
+    stream.backup_urls = urls;
+}
@@ -0,0 +1,39 @@
+// B站图片地址处理
+// hdslb 图床通过 `@` 后缀进行缩放和格式转换，例如 `xxx.jpg@1920w_1080h.png`
+
+/// 重写图片地址的 `@` 处理后缀
+///
+/// 宽高都为 None 且未指定格式时返回原图地址（去掉已有的处理后缀）。
+pub fn resize_image_url(
+    url: &str,
+    width: Option<u32>,
+    height: Option<u32>,
+    format: Option<&str>,
+) -> String {
+    // 去掉已有的处理后缀
+    let base = url.split('@').next().unwrap_or(url);
+
+    let mut params = Vec::new();
+    if let Some(w) = width {
+        params.push(format!("{}w", w));
+    }
+    if let Some(h) = height {
+        params.push(format!("{}h", h));
+    }
+
+    match (params.is_empty(), format) {
+        (true, None) => base.to_string(),
+        (true, Some(fmt)) => format!("{}@.{}", base, fmt),
+        (false, None) => format!("{}@{}", base, params.join("_")),
+        (false, Some(fmt)) => format!("{}@{}.{}", base, params.join("_"), fmt),
+    }
+}
+
+/// 解析尺寸字符串：`1920` 或 `1920x1080`
+pub fn parse_image_size(size: &str) -> Option<(Option<u32>, Option<u32>)> {
+    let size = size.trim().to_lowercase();
+    match size.split_once('x') {
+        Some((w, h)) => Some((Some(w.trim().parse().ok()?), Some(h.trim().parse().ok()?))),
+        None => Some((Some(size.parse().ok()?), None)),
+    }
+}
@@ -0,0 +1,209 @@
+// 直播回放（录像，`live.bilibili.com/record/R...`）的信息与播放地址
+//
+// 回放按分段保存为音视频合一的 FLV，每个分段作为一个分P下载；
+// 正在进行的直播（直播间链接）不支持下载
+
+use super::api::{ApiResponse, LiveRecordInfo, LiveRecordInfoData, LiveRecordSegment, LiveRecordUrlData};
+use super::parser::{api_error, format_timestamp};
+use crate::error::{DownloaderError, Result};
+use crate::types::{BilibiliExtra, ExtraData, Page, Stream, StreamType, VideoInfo};
+use crate::utils::http::HttpClient;
+use std::sync::Arc;
+
+/// 回放流的清晰度名称（接口只提供原画）
+const REPLAY_QUALITY: &str = "直播回放";
+
+/// 获取直播回放信息，每个分段对应一个分P
+pub async fn fetch_live_replay_info(client: &Arc<HttpClient>, rid: &str) -> Result<VideoInfo> {
+    let api = format!(
+        "https://api.live.bilibili.com/xlive/web-room/v1/record/getInfoByLiveRecord?rid={}",
+        rid
+    );
+    let response = client.get(&api, None).await?;
+    let json_text = response.text().await?;
+    tracing::debug!("Live replay info response: {}", json_text);
+    let info = parse_record_info(&json_text)?;
+
+    let segments = fetch_segments(client, rid).await?;
+    Ok(build_video_info(info, &segments))
+}
+
+/// 获取回放中第 `segment` 段（从1开始）的音视频流
+///
+/// FLV 分段同时包含音视频，返回的视频流和音频流指向同一地址，下载时只需下载一次
+pub async fn fetch_live_replay_streams(
+    client: &Arc<HttpClient>,
+    rid: &str,
+    segment: usize,
+) -> Result<Vec<Stream>> {
+    let segments = fetch_segments(client, rid).await?;
+    let total = segments.len();
+    let segment = segment
+        .checked_sub(1)
+        .and_then(|index| segments.into_iter().nth(index))
+        .ok_or_else(|| {
+            DownloaderError::DownloadFailed(format!(
+                "Live replay {} has no segment {} ({} in total)",
+                rid, segment, total
+            ))
+        })?;
+
+    Ok(segment_streams(segment))
+}
+
+async fn fetch_segments(client: &Arc<HttpClient>, rid: &str) -> Result<Vec<LiveRecordSegment>> {
+    let api = format!(
+        "https://api.live.bilibili.com/xlive/web-room/v1/record/getLiveRecordUrl?rid={}&platform=html5",
+        rid
+    );
+    let response = client.get(&api, None).await?;
+    let json_text = response.text().await?;
+    tracing::debug!("Live replay url response: {}", json_text);
+
+    parse_record_segments(&json_text)
+}
+
+fn parse_record_info(json_text: &str) -> Result<LiveRecordInfo> {
+    let api_response: ApiResponse<LiveRecordInfoData> = serde_json::from_str(json_text)
+        .map_err(|e| DownloaderError::Parse(format!("Failed to parse live replay info: {}", e)))?;
+
+    if api_response.code != 0 {
+        return Err(api_error(api_response.code, &api_response.message));
+    }
+    api_response
+        .data
+        .map(|data| data.live_record_info)
+        .ok_or_else(|| DownloaderError::Parse("No live replay info data".to_string()))
+}
+
+fn parse_record_segments(json_text: &str) -> Result<Vec<LiveRecordSegment>> {
+    let api_response: ApiResponse<LiveRecordUrlData> = serde_json::from_str(json_text)
+        .map_err(|e| DownloaderError::Parse(format!("Failed to parse live replay urls: {}", e)))?;
+
+    if api_response.code != 0 {
+        return Err(api_error(api_response.code, &api_response.message));
+    }
+    let segments = api_response.data.map(|data| data.list).unwrap_or_default();
+    if segments.is_empty() {
+        return Err(DownloaderError::DownloadFailed(
+            "Live replay has no playable segments (it may have expired)".to_string(),
+        ));
+    }
+    Ok(segments)
+}
+
+fn build_video_info(info: LiveRecordInfo, segments: &[LiveRecordSegment]) -> VideoInfo {
+    let pages = segments
+        .iter()
+        .enumerate()
+        .map(|(i, segment)| Page {
+            number: i + 1,
+            title: if segments.len() > 1 {
+                format!("{} ({}/{})", info.title, i + 1, segments.len())
+            } else {
+                info.title.clone()
+            },
+            // 不使用纯数字，避免与普通视频的cid混淆
+            cid: format!("{}-{}", info.rid, i + 1),
+            duration: segment.length / 1000,
+            ep_id: None,
+        })
+        .collect();
+
+    VideoInfo {
+        id: info.rid,
+        aid: 0,
+        title: info.title,
+        description: String::new(),
+        duration: info.length / 1000,
+        uploader: info.uname,
+        uploader_mid: info.uid.to_string(),
+        upload_date: format_timestamp(info.start_timestamp),
+        cover_url: info.cover,
+        pages,
+        is_bangumi: false,
+        ep_id: None,
+        extra_data: Some(ExtraData::Bilibili(BilibiliExtra {
+            tname: info.area_name,
+            live_replay: true,
+            ..Default::default()
+        })),
+    }
+}
+
+fn segment_streams(segment: LiveRecordSegment) -> Vec<Stream> {
+    let bandwidth = match segment.length {
+        0 => 0,
+        length => segment.size * 8 * 1000 / length,
+    };
+    let backup_urls: Vec<String> = segment.backup_url.into_iter().filter(|url| !url.is_empty()).collect();
+
+    [(StreamType::Video, "flv"), (StreamType::Audio, "aac")]
+        .into_iter()
+        .map(|(stream_type, codec)| Stream {
+            stream_type,
+            quality: REPLAY_QUALITY.to_string(),
+            quality_id: 0,
+            codec: codec.to_string(),
+            url: segment.url.clone(),
+            backup_urls: backup_urls.clone(),
+            size: segment.size,
+            bandwidth,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INFO: &str = r#"{"code":0,"message":"0","data":{"live_record_info":{
+        "rid":"R1sx411c7Xn","room_id":21452505,"uid":12345,"title":"周末直播",
+        "cover":"https://i0.hdslb.com/cover.jpg","area_name":"单机游戏",
+        "start_timestamp":1700000000,"end_timestamp":1700007200,"length":7200000}}}"#;
+
+    const URLS: &str = r#"{"code":0,"message":"0","data":{"list":[
+        {"url":"https://example.com/1.flv","backup_url":"","size":1000000,"length":3600000},
+        {"url":"https://example.com/2.flv","backup_url":"https://backup.example.com/2.flv","size":2000000,"length":3600000}
+    ],"size":3000000,"length":7200000}}"#;
+
+    #[test]
+    fn test_build_video_info() {
+        let info = parse_record_info(INFO).unwrap();
+        let segments = parse_record_segments(URLS).unwrap();
+        let video = build_video_info(info, &segments);
+
+        assert_eq!(video.id, "R1sx411c7Xn");
+        assert_eq!(video.uploader_mid, "12345");
+        assert_eq!(video.upload_date, "2023-11-15");
+        assert_eq!(video.duration, 7200);
+        assert!(video.is_live_replay());
+        assert_eq!(video.pages.len(), 2);
+        assert_eq!(video.pages[1].title, "周末直播 (2/2)");
+        assert_eq!(video.pages[1].cid, "R1sx411c7Xn-2");
+        assert_eq!(video.pages[1].duration, 3600);
+    }
+
+    #[test]
+    fn test_segment_streams_share_url() {
+        let segment = parse_record_segments(URLS).unwrap().remove(1);
+        let streams = segment_streams(segment);
+
+        assert_eq!(streams.len(), 2);
+        assert_eq!(streams[0].stream_type, StreamType::Video);
+        assert_eq!(streams[1].stream_type, StreamType::Audio);
+        assert_eq!(streams[0].url, streams[1].url);
+        assert_eq!(streams[0].backup_urls, vec!["https://backup.example.com/2.flv"]);
+        assert_eq!(streams[0].bandwidth, 4444);
+    }
+
+    #[test]
+    fn test_expired_replay() {
+        let empty = r#"{"code":0,"message":"0","data":{"list":[]}}"#;
+        assert!(parse_record_segments(empty).is_err());
+    }
+}
@@ -1,14 +1,23 @@
 mod api;
+pub mod buvid;
+pub mod cdn;
+pub mod bvid;
+pub mod image;
+pub mod live;
 pub mod parser;
-pub mod selector;
+// 选流逻辑与平台无关，保留原路径供现有调用方使用
+pub use crate::platform::selector;
 pub mod wbi;
 
 use crate::error::{DownloaderError, Result};
 use crate::platform::r#trait::Platform;
-use crate::types::{Auth, Stream, Subtitle, VideoInfo};
+use crate::platform::selector::{estimated_size, StreamSelector};
+use crate::types::{Auth, Page, Stream, StreamPreferences, Subtitle, VideoInfo};
 use crate::utils::http::HttpClient;
 use async_trait::async_trait;
+use futures::stream::{self, StreamExt, TryStreamExt};
 use regex::Regex;
+use std::pin::Pin;
 use std::sync::Arc;
 
 /// Result of parsing a video URL - can be either a single video or a batch of videos
@@ -18,10 +27,53 @@ pub enum ParseResult {
     Batch(Vec<VideoInfo>),
 }
 
+/// 逐个产出视频信息的异步流，出错后结束
+pub type VideoStream<'a> = Pin<Box<dyn futures::Stream<Item = Result<VideoInfo>> + Send + 'a>>;
+
+/// `stream_video_batch` 的翻页进度
+enum BatchCursor {
+    Start,
+    Page(parser::BatchSource, u32),
+    Done,
+}
+
 pub struct BilibiliPlatform {
     client: Arc<HttpClient>,
     api_mode: ApiMode,
+    intl_fallback: bool,
+    parse_options: ParseOptions,
+    cdn_selector: Arc<dyn cdn::CdnSelector>,
     wbi_manager: tokio::sync::Mutex<wbi::WbiManager>,
+    buvid_manager: tokio::sync::Mutex<buvid::BuvidManager>,
+}
+
+/// 视频信息解析选项
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// 番剧是否包含PV、预告及特别篇等花絮（作为额外分P）
+    pub include_extras: bool,
+    /// 解析单个视频时额外获取视频标签（多一次API请求）
+    pub fetch_tags: bool,
+    /// 批量下载只保留该日期（`YYYY-MM-DD`，含当天）及之后发布的视频
+    pub since: Option<String>,
+    /// 批量下载只保留该日期（`YYYY-MM-DD`，含当天）及之前发布的视频
+    pub until: Option<String>,
+}
+
+impl ParseOptions {
+    /// 发布日期是否在 `since`/`until` 范围内；日期未知时保留
+    pub fn in_date_range(&self, date: &str) -> bool {
+        let date = date.get(..10).unwrap_or(date);
+        if date.is_empty() {
+            return true;
+        }
+        self.since.as_deref().is_none_or(|since| date >= since)
+            && self.until.as_deref().is_none_or(|until| date <= until)
+    }
+
+    fn has_date_range(&self) -> bool {
+        self.since.is_some() || self.until.is_some()
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -32,7 +84,7 @@ pub enum ApiMode {
     International,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum VideoType {
     Bvid(String),
     Aid(String),
@@ -43,6 +95,8 @@ pub enum VideoType {
     SpaceVideo(String),
     MediaList(String),
     SeriesList(String),
+    /// 直播回放，值为回放的 rid（如 `R1sx411c7Xn`）
+    LiveReplay(String),
 }
 
 impl BilibiliPlatform {
@@ -53,16 +107,65 @@ impl BilibiliPlatform {
     }
 
     pub fn with_api_mode(api_mode: ApiMode) -> Result<Self> {
-        let client = Arc::new(HttpClient::new()?);
+        Self::with_client(api_mode, Arc::new(HttpClient::new()?))
+    }
+
+    /// 使用指定的HTTP客户端创建实例（用于共享Cookie存储等）
+    pub fn with_client(api_mode: ApiMode, client: Arc<HttpClient>) -> Result<Self> {
         let wbi_manager = wbi::WbiManager::new(client.clone());
+        let buvid_manager = buvid::BuvidManager::new(client.clone());
         Ok(Self {
             client,
             api_mode,
+            intl_fallback: false,
+            parse_options: ParseOptions::default(),
+            cdn_selector: Arc::new(cdn::DefaultCdn),
             wbi_manager: tokio::sync::Mutex::new(wbi_manager),
+            buvid_manager: tokio::sync::Mutex::new(buvid_manager),
         })
     }
 
+    /// 遇到区域限制时自动改用国际版API重试（仅在Web模式下生效）
+    pub fn with_intl_fallback(mut self, enabled: bool) -> Self {
+        self.intl_fallback = enabled;
+        self
+    }
+
+    /// 设置CDN主机选择策略（如海外用户使用 [`cdn::OverseasCdn`]）
+    pub fn with_cdn_selector(mut self, selector: Arc<dyn cdn::CdnSelector>) -> Self {
+        self.cdn_selector = selector;
+        self
+    }
+
+    /// 设置解析选项
+    pub fn with_parse_options(mut self, options: ParseOptions) -> Self {
+        self.parse_options = options;
+        self
+    }
+
     fn parse_url(&self, url: &str) -> Result<VideoType> {
+        let normalized = normalize_url(url);
+        if normalized != url {
+            tracing::debug!("Normalized URL: {} -> {}", url, normalized);
+        }
+        let url = normalized.as_str();
+
+        // 直播回放: https://live.bilibili.com/record/R1sx411c7Xn
+        // rid 中可能含有 `ss`、`ep` 加数字，需要在番剧之前匹配
+        let record_regex = Regex::new(r"live\.bilibili\.com/record/(R[0-9A-Za-z]+)").unwrap();
+        if let Some(caps) = record_regex.captures(url) {
+            return Ok(VideoType::LiveReplay(caps[1].to_string()));
+        }
+
+        // 直播间: https://live.bilibili.com/21452505，正在进行的直播不支持下载
+        let live_room_regex = Regex::new(r"live\.bilibili\.com/(?:h5/)?(\d+)").unwrap();
+        if let Some(caps) = live_room_regex.captures(url) {
+            return Err(DownloaderError::InvalidUrl(format!(
+                "Live room {} cannot be downloaded; only live replays (https://live.bilibili.com/record/R...) are supported",
+                &caps[1]
+            )));
+        }
+
         // BV号: BV1xx411c7mD 或 https://www.bilibili.com/video/BV1xx411c7mD
         let bv_regex = Regex::new(r"(BV[a-zA-Z0-9]+)").unwrap();
         if let Some(caps) = bv_regex.captures(url) {
@@ -94,19 +197,14 @@ impl BilibiliPlatform {
         }
 
         // 收藏夹: favId:mid 或 https://space.bilibili.com/{mid}/favlist?fid={favId}
-        let fav_regex = Regex::new(r"space\.bilibili\.com/(\d+)/favlist\?fid=(\d+)").unwrap();
+        let fav_regex =
+            Regex::new(r"space\.bilibili\.com/(\d+)/favlist\?(?:[^#]*&)?fid=(\d+)").unwrap();
         if let Some(caps) = fav_regex.captures(url) {
             let mid = caps[1].to_string();
             let fav_id = caps[2].to_string();
             return Ok(VideoType::FavoriteList(format!("{}:{}", fav_id, mid)));
         }
 
-        // UP主空间: mid123456 或 https://space.bilibili.com/123456
-        let space_regex = Regex::new(r"space\.bilibili\.com/(\d+)").unwrap();
-        if let Some(caps) = space_regex.captures(url) {
-            return Ok(VideoType::SpaceVideo(caps[1].to_string()));
-        }
-
         // 合集: https://www.bilibili.com/medialist/play/ml123456
         let media_regex = Regex::new(r"medialist/play/ml(\d+)").unwrap();
         if let Some(caps) = media_regex.captures(url) {
@@ -115,13 +213,22 @@ impl BilibiliPlatform {
 
         // 系列: https://space.bilibili.com/{mid}/channel/seriesdetail?sid={sid}
         let series_regex =
-            Regex::new(r"space\.bilibili\.com/(\d+)/channel/seriesdetail\?sid=(\d+)").unwrap();
+            Regex::new(r"space\.bilibili\.com/(\d+)/channel/seriesdetail\?(?:[^#]*&)?sid=(\d+)")
+                .unwrap();
         if let Some(caps) = series_regex.captures(url) {
             let mid = caps[1].to_string();
             let sid = caps[2].to_string();
             return Ok(VideoType::SeriesList(format!("{}:{}", mid, sid)));
         }
 
+        // UP主空间: https://space.bilibili.com/123456，以及投稿页 /video、/upload/video，
+        // 允许结尾斜杠和查询参数（如 ?tid=36）；收藏夹、系列等其他子页面不在此匹配
+        let space_regex =
+            Regex::new(r"space\.bilibili\.com/(\d+)(?:/upload)?(?:/video)?/?(?:[?#].*)?$").unwrap();
+        if let Some(caps) = space_regex.captures(url) {
+            return Ok(VideoType::SpaceVideo(caps[1].to_string()));
+        }
+
         Err(DownloaderError::InvalidUrl(format!(
             "Cannot parse bilibili URL: {}",
             url
@@ -129,6 +236,88 @@ impl BilibiliPlatform {
     }
 }
 
+/// 分享链接中常见的跟踪参数，解析前去除（`utm_*` 另行处理）
+const TRACKING_PARAMS: &[&str] = &[
+    "spm_id_from",
+    "from_spmid",
+    "from_outer_spmid",
+    "vd_source",
+    "share_source",
+    "share_medium",
+    "share_plat",
+    "share_session_id",
+    "share_tag",
+    "share_from",
+    "share_times",
+    "unique_k",
+    "bbid",
+    "ts",
+    "timestamp",
+    "seid",
+    "from",
+    "from_source",
+    "buvid",
+    "is_story_h5",
+    "plat_id",
+    "up_id",
+    "launch_id",
+    "trackid",
+    "msource",
+    "bsource",
+    "-Arouter",
+];
+
+/// 规范化复制来的链接：去掉首尾空白和标点、跟踪参数以及 `#reply...` 评论锚点
+///
+/// 分P参数 `p`、时间点 `t` 及收藏夹 `fid` 等有意义的参数保持不变；
+/// 不是 http(s) 链接时（如裸 BV 号）只去掉首尾的多余字符
+pub fn normalize_url(url: &str) -> String {
+    const JUNK: &[char] = &[
+        '"', '\'', '<', '>', '(', ')', '[', ']', '{', '}', ',', '.', ';', '!', '?', '，', '。',
+        '；', '！', '？', '（', '）', '【', '】', '《', '》', '「', '」', '“', '”', '‘', '’',
+    ];
+    let trimmed = url
+        .trim()
+        .trim_start_matches(JUNK)
+        .trim_end_matches(JUNK);
+
+    let Ok(mut parsed) = url::Url::parse(trimmed) else {
+        return trimmed.to_string();
+    };
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return trimmed.to_string();
+    }
+
+    let kept: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(key, _)| !TRACKING_PARAMS.contains(&key.as_ref()) && !key.starts_with("utm_"))
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+    if kept.is_empty() {
+        parsed.set_query(None);
+    } else {
+        parsed.query_pairs_mut().clear().extend_pairs(kept);
+    }
+
+    if parsed.fragment().is_some_and(|f| f.is_empty() || f.starts_with("reply")) {
+        parsed.set_fragment(None);
+    }
+
+    parsed.to_string()
+}
+
+/// 链接中的分P参数（`?p=3`），没有或无效时返回 None
+pub fn page_from_url(url: &str) -> Option<usize> {
+    let parsed = url::Url::parse(&normalize_url(url)).ok()?;
+    let page = parsed
+        .query_pairs()
+        .find(|(key, _)| key == "p")?
+        .1
+        .parse()
+        .ok()?;
+    (page > 0).then_some(page)
+}
+
 #[async_trait]
 impl Platform for BilibiliPlatform {
     fn as_any(&self) -> &dyn std::any::Any {
@@ -151,8 +340,16 @@ impl Platform for BilibiliPlatform {
 
     async fn parse_video(&self, url: &str, auth: Option<&Auth>) -> Result<VideoInfo> {
         let video_type = self.parse_url(url)?;
+        self.buvid_manager.lock().await.ensure(auth).await;
         let mut wbi = self.wbi_manager.lock().await;
-        let result = parser::parse_video_info(&self.client, video_type, auth, Some(&mut *wbi)).await?;
+        let result = parser::parse_video_info(
+            &self.client,
+            video_type,
+            auth,
+            Some(&mut *wbi),
+            &self.parse_options,
+        )
+        .await?;
         
         match result {
             ParseResult::Single(video) => Ok(*video),
@@ -175,11 +372,38 @@ impl Platform for BilibiliPlatform {
         cid: &str,
         auth: Option<&Auth>,
     ) -> Result<Vec<Stream>> {
-        parser::get_play_url_with_mode(&self.client, video_id, cid, auth, self.api_mode).await
+        self.get_streams_with_fallback(video_id, cid, auth, None).await
+    }
+
+    async fn get_subtitles(
+        &self,
+        video_id: &str,
+        cid: &str,
+        auth: Option<&Auth>,
+    ) -> Result<Vec<Subtitle>> {
+        parser::get_subtitles(&self.client, video_id, cid, auth).await
     }
 
-    async fn get_subtitles(&self, video_id: &str, cid: &str) -> Result<Vec<Subtitle>> {
-        parser::get_subtitles(&self.client, video_id, cid).await
+    /// 按选中流的实际大小（HEAD 请求的 `Content-Length`）估算，请求失败时按码率 × 时长估算
+    async fn estimate_size(
+        &self,
+        video_info: &VideoInfo,
+        pages: &[Page],
+        preferences: &StreamPreferences,
+        auth: Option<&Auth>,
+    ) -> Result<u64> {
+        let selector = StreamSelector::new(preferences);
+        let mut total = 0;
+        for page in pages {
+            let streams = self.get_page_streams(video_info, page, auth).await?;
+            let (video, audio) = selector.select(&streams)?;
+            total += self.stream_size(&video, page.duration).await;
+            // 音视频在同一文件中时只计算一次
+            if audio.url != video.url {
+                total += self.stream_size(&audio, page.duration).await;
+            }
+        }
+        Ok(total)
     }
 
     fn get_cover(&self, video_info: &VideoInfo) -> String {
@@ -198,14 +422,83 @@ impl BilibiliPlatform {
     /// This method handles batch URLs (favorites, space videos, playlists, etc.)
     /// and returns all videos in the batch. For single video URLs, returns a Vec with one element.
     pub async fn parse_video_batch(&self, url: &str, auth: Option<&Auth>) -> Result<Vec<VideoInfo>> {
-        let video_type = self.parse_url(url)?;
+        self.stream_video_batch(url, auth).try_collect().await
+    }
+
+    /// Stream the videos of a URL page by page instead of collecting the whole batch
+    ///
+    /// Each API page is yielded as soon as it is fetched, so callers can start on the
+    /// first videos right away and stop early by dropping the stream (no further pages
+    /// are requested). Single video URLs yield one item. The stream ends after an error.
+    ///
+    /// ```no_run
+    /// # async fn example(platform: &rvd::platform::bilibili::BilibiliPlatform) -> rvd::Result<()> {
+    /// use futures::TryStreamExt;
+    ///
+    /// let mut videos = platform.stream_video_batch("https://space.bilibili.com/123456", None);
+    /// while let Some(video) = videos.try_next().await? {
+    ///     println!("{}", video.title);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stream_video_batch<'a>(&'a self, url: &'a str, auth: Option<&'a Auth>) -> VideoStream<'a> {
+        let pages = stream::try_unfold(BatchCursor::Start, move |cursor| async move {
+            let (source, page) = match cursor {
+                BatchCursor::Done => return Result::Ok(None),
+                BatchCursor::Page(source, page) => (source, page),
+                BatchCursor::Start => {
+                    let video_type = self.parse_url(url)?;
+                    self.buvid_manager.lock().await.ensure(auth).await;
+                    match parser::BatchSource::resolve(&self.client, &video_type, auth).await? {
+                        Some(source) => (source, 1),
+                        None => {
+                            let video = self.parse_video(url, auth).await?;
+                            return Ok(Some((vec![video], BatchCursor::Done)));
+                        }
+                    }
+                }
+            };
+
+            let mut wbi = self.wbi_manager.lock().await;
+            let batch = parser::fetch_batch_page(
+                &self.client,
+                &source,
+                page,
+                auth,
+                Some(&mut *wbi),
+                &self.parse_options,
+            )
+            .await?;
+
+            let next = if batch.has_more {
+                BatchCursor::Page(source, page + 1)
+            } else {
+                BatchCursor::Done
+            };
+            Ok(Some((batch.videos, next)))
+        });
+
+        pages
+            .map_ok(|videos| stream::iter(videos.into_iter().map(Ok)))
+            .try_flatten()
+            .boxed()
+    }
+
+    /// Fetch the comments of a video (`aid`), oldest first, signed with this platform's WBI key
+    pub async fn fetch_comments(
+        &self,
+        aid: &str,
+        auth: Option<&Auth>,
+        options: parser::CommentOptions,
+    ) -> Result<Vec<parser::Comment>> {
         let mut wbi = self.wbi_manager.lock().await;
-        let result = parser::parse_video_info(&self.client, video_type, auth, Some(&mut *wbi)).await?;
-        
-        match result {
-            ParseResult::Single(video) => Ok(vec![*video]),
-            ParseResult::Batch(videos) => Ok(videos),
-        }
+        parser::fetch_comments(&self.client, aid, auth, &mut wbi, options).await
+    }
+
+    /// Whether `auth` is still logged in, checked via the nav endpoint
+    pub async fn check_login(&self, auth: Option<&Auth>) -> Result<bool> {
+        parser::check_login(&self.client, auth).await
     }
 
     /// Get streams for bangumi/pgc content with ep_id
@@ -216,6 +509,236 @@ impl BilibiliPlatform {
         ep_id: &str,
         auth: Option<&Auth>,
     ) -> Result<Vec<Stream>> {
-        parser::get_play_url_with_mode_and_ep(&self.client, video_id, cid, auth, self.api_mode, Some(ep_id)).await
+        self.get_streams_with_fallback(video_id, cid, auth, Some(ep_id)).await
+    }
+
+    /// 获取分P的音视频流：直播回放按分段获取，番剧使用分P（或链接）对应ep的接口
+    pub async fn get_page_streams(
+        &self,
+        video_info: &VideoInfo,
+        page: &Page,
+        auth: Option<&Auth>,
+    ) -> Result<Vec<Stream>> {
+        if video_info.is_live_replay() {
+            return self.get_live_replay_streams(&video_info.id, page.number).await;
+        }
+
+        let aid = video_info.aid.to_string();
+        // 优先使用page的ep_id（每个episode有自己的ep_id）
+        let ep_id = page.ep_id.as_deref().or(video_info.ep_id.as_deref());
+        match ep_id {
+            Some(ep_id) if video_info.is_bangumi => {
+                self.get_bangumi_streams(&aid, &page.cid, ep_id, auth).await
+            }
+            _ => self.get_streams_with_fallback(&aid, &page.cid, auth, None).await,
+        }
+    }
+
+    /// 流的大小：先用 HEAD 请求获取，失败时按码率 × 时长估算
+    async fn stream_size(&self, stream: &Stream, duration: u64) -> u64 {
+        if stream.size > 0 {
+            return stream.size;
+        }
+        match self.client.content_length(&stream.url).await {
+            Ok(size) => size,
+            Err(e) => {
+                tracing::debug!("HEAD request for stream size failed ({}), estimating from bitrate", e);
+                estimated_size(stream, duration)
+            }
+        }
+    }
+
+    /// Report which listed qualities the current auth and API mode can actually fetch
+    ///
+    /// 与下载一样，区域限制时按需回退到国际版API
+    pub async fn probe_qualities(
+        &self,
+        video_id: &str,
+        cid: &str,
+        ep_id: Option<&str>,
+        auth: Option<&Auth>,
+    ) -> Result<parser::QualityProbe> {
+        match parser::probe_qualities(&self.client, video_id, cid, auth, self.api_mode, ep_id).await {
+            Err(DownloaderError::RegionLocked(reason)) if self.retries_region_lock() => {
+                tracing::warn!(
+                    "Region locked with Web API ({}), probing with International API",
+                    reason
+                );
+                parser::probe_qualities(&self.client, video_id, cid, auth, ApiMode::International, ep_id)
+                    .await
+            }
+            other => other,
+        }
+    }
+
+    /// 区域限制时是否改用国际版API重试
+    fn retries_region_lock(&self) -> bool {
+        self.intl_fallback && matches!(self.api_mode, ApiMode::Web)
+    }
+
+    /// 获取直播回放第 `segment` 段（从1开始）的音视频流，并按CDN策略重排地址
+    pub async fn get_live_replay_streams(&self, rid: &str, segment: usize) -> Result<Vec<Stream>> {
+        let mut streams = live::fetch_live_replay_streams(&self.client, rid, segment).await?;
+        for stream in &mut streams {
+            cdn::reorder_urls(self.cdn_selector.as_ref(), stream);
+        }
+        Ok(streams)
+    }
+
+    /// 获取播放地址，并按CDN策略重排各流的主地址与备用地址
+    async fn get_streams_with_fallback(
+        &self,
+        video_id: &str,
+        cid: &str,
+        auth: Option<&Auth>,
+        ep_id: Option<&str>,
+    ) -> Result<Vec<Stream>> {
+        let mut streams = self.fetch_streams(video_id, cid, auth, ep_id).await?;
+        for stream in &mut streams {
+            cdn::reorder_urls(self.cdn_selector.as_ref(), stream);
+        }
+        Ok(streams)
+    }
+
+    /// 获取播放地址，区域限制时按需回退到国际版API
+    async fn fetch_streams(
+        &self,
+        video_id: &str,
+        cid: &str,
+        auth: Option<&Auth>,
+        ep_id: Option<&str>,
+    ) -> Result<Vec<Stream>> {
+        let result = parser::get_play_url_with_mode_and_ep(
+            &self.client,
+            video_id,
+            cid,
+            auth,
+            self.api_mode,
+            ep_id,
+        )
+        .await;
+
+        match result {
+            Err(DownloaderError::RegionLocked(reason)) if self.retries_region_lock() => {
+                tracing::warn!(
+                    "Region locked with Web API ({}), retrying with International API",
+                    reason
+                );
+                parser::get_play_url_with_mode_and_ep(
+                    &self.client,
+                    video_id,
+                    cid,
+                    auth,
+                    ApiMode::International,
+                    ep_id,
+                )
+                .await
+            }
+            other => other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(url: &str) -> VideoType {
+        BilibiliPlatform::new().unwrap().parse_url(url).unwrap()
+    }
+
+    #[test]
+    fn test_parse_space_url_variants() {
+        let space = VideoType::SpaceVideo("123456".to_string());
+        for url in [
+            "https://space.bilibili.com/123456",
+            "https://space.bilibili.com/123456/",
+            "https://space.bilibili.com/123456/video",
+            "https://space.bilibili.com/123456/upload/video",
+            "https://space.bilibili.com/123456/upload/video/",
+            "https://space.bilibili.com/123456/upload/video?tid=36",
+            "https://space.bilibili.com/123456/video?tid=0&pn=2",
+            "https://space.bilibili.com/123456?spm_id_from=333.1007.0.0",
+        ] {
+            assert_eq!(parse(url), space, "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_parse_space_sub_pages_not_swallowed() {
+        assert_eq!(
+            parse("https://space.bilibili.com/123456/favlist?fid=789"),
+            VideoType::FavoriteList("789:123456".to_string())
+        );
+        assert_eq!(
+            parse("https://space.bilibili.com/123456/favlist?spm_id_from=333.1387&fid=789&ftype=create"),
+            VideoType::FavoriteList("789:123456".to_string())
+        );
+        assert_eq!(
+            parse("https://space.bilibili.com/123456/channel/seriesdetail?sid=789"),
+            VideoType::SeriesList("123456:789".to_string())
+        );
+        assert_eq!(
+            parse("https://space.bilibili.com/123456/channel/seriesdetail?sid=789&ctype=0"),
+            VideoType::SeriesList("123456:789".to_string())
+        );
+        assert!(BilibiliPlatform::new()
+            .unwrap()
+            .parse_url("https://space.bilibili.com/123456/dynamic")
+            .is_err());
+    }
+
+    #[test]
+    fn test_parse_decorated_urls() {
+        assert_eq!(
+            parse("https://www.bilibili.com/video/BV1xx411c7mD/?spm_id_from=333.1007.tianma.1-1-1.click&vd_source=a1b2c3d4e5f6"),
+            VideoType::Bvid("BV1xx411c7mD".to_string())
+        );
+        assert_eq!(
+            parse("https://www.bilibili.com/bangumi/play/ep123456?from_spmid=666.25.episode.0&from_outer_spmid=333.337#reply98765"),
+            VideoType::Episode("123456".to_string())
+        );
+        assert_eq!(
+            parse("【视频标题】 https://www.bilibili.com/video/av170001?share_source=copy_web&vd_source=e5f6。"),
+            VideoType::Aid("170001".to_string())
+        );
+        assert_eq!(
+            parse("<https://space.bilibili.com/123456/favlist?fid=789&ftype=create&spm_id_from=333.1387>"),
+            VideoType::FavoriteList("789:123456".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_live_urls() {
+        assert_eq!(
+            parse("https://live.bilibili.com/record/R1sx411c7Xn?broadcast_type=0"),
+            VideoType::LiveReplay("R1sx411c7Xn".to_string())
+        );
+        // rid 中的 `ss4` 不能被识别为番剧
+        assert_eq!(
+            parse("https://live.bilibili.com/record/R1ss411c7Xn"),
+            VideoType::LiveReplay("R1ss411c7Xn".to_string())
+        );
+
+        let platform = BilibiliPlatform::new().unwrap();
+        for url in ["https://live.bilibili.com/21452505", "https://live.bilibili.com/h5/21452505"] {
+            assert!(matches!(platform.parse_url(url), Err(DownloaderError::InvalidUrl(_))));
+        }
+    }
+
+    #[test]
+    fn test_parse_options_in_date_range() {
+        let options = ParseOptions {
+            since: Some("2024-01-01".to_string()),
+            until: Some("2024-06-30".to_string()),
+            ..Default::default()
+        };
+        assert!(options.in_date_range("2024-01-01"));
+        assert!(options.in_date_range("2024-06-30"));
+        assert!(!options.in_date_range("2023-12-31"));
+        assert!(!options.in_date_range("2024-07-01"));
+        // 日期未知时保留
+        assert!(options.in_date_range(""));
+        assert!(ParseOptions::default().in_date_range("2000-01-01"));
     }
 }
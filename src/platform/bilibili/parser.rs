@@ -1,10 +1,12 @@
 use super::api::*;
 use super::api::{BangumiInfoData, CheeseInfoData};
 use super::VideoType;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use crate::error::{DownloaderError, Result};
-use crate::types::{Auth, Page, Stream, StreamType, Subtitle, VideoInfo};
-use crate::utils::http::HttpClient;
+use crate::types::{
+    Auth, BilibiliExtra, BilibiliStat, ExtraData, Page, Stream, StreamType, Subtitle, VideoInfo,
+};
+use crate::utils::http::{HttpClient, HttpTransport};
 use std::sync::Arc;
 
 const QUALITY_MAP: &[(&str, u32)] = &[
@@ -21,57 +23,99 @@ const QUALITY_MAP: &[(&str, u32)] = &[
     ("360P 流畅", 16),
 ];
 
+/// 将Bilibili API的错误码映射为具体的错误类型，便于给出可操作的提示
+pub fn api_error(code: i32, message: &str) -> DownloaderError {
+    let message = format!("{} (code: {})", message, code);
+    match code {
+        -101 => DownloaderError::AuthRequired,
+        -352 | -412 => DownloaderError::RiskControl(message),
+        -403 => DownloaderError::AccessDenied(message),
+        -404 => DownloaderError::VideoNotFound(message),
+        // 稿件不可见 / 审核中 / 仅UP主自己可见
+        62002 | 62004 | 62012 => DownloaderError::ContentRemoved(message),
+        -10403 => DownloaderError::RegionLocked(message),
+        _ => DownloaderError::Api(message),
+    }
+}
+
 pub async fn parse_video_info(
     client: &Arc<HttpClient>,
     video_type: VideoType,
     auth: Option<&Auth>,
     wbi_manager: Option<&mut super::wbi::WbiManager>,
+    options: &super::ParseOptions,
 ) -> Result<super::ParseResult> {
     match video_type {
         VideoType::Bvid(bvid) => {
-            let video = fetch_video_info_by_bvid(client, &bvid, auth).await?;
+            let mut video = fetch_video_info_by_bvid(client, &bvid, auth).await?;
+            check_members_only(&video, auth)?;
+            if options.fetch_tags {
+                attach_tags(client, &mut video).await;
+            }
             Ok(super::ParseResult::Single(Box::new(video)))
         }
         VideoType::Aid(aid) => {
-            let video = fetch_video_info_by_aid(client, &aid, auth).await?;
+            let mut video = fetch_video_info_by_aid(client, &aid, auth).await?;
+            check_members_only(&video, auth)?;
+            if options.fetch_tags {
+                attach_tags(client, &mut video).await;
+            }
             Ok(super::ParseResult::Single(Box::new(video)))
         }
         VideoType::Episode(ep) => {
-            let video = fetch_bangumi_info_by_ep(client, &ep, auth).await?;
+            let video = fetch_bangumi_info_by_ep(client, &ep, auth, options.include_extras).await?;
             Ok(super::ParseResult::Single(Box::new(video)))
         }
         VideoType::Season(ss) => {
-            let video = fetch_bangumi_info_by_ss(client, &ss, auth).await?;
+            let video = fetch_bangumi_info_by_ss(client, &ss, auth, options.include_extras).await?;
             Ok(super::ParseResult::Single(Box::new(video)))
         }
         VideoType::Cheese(ep) => {
             let video = fetch_cheese_info(client, &ep, auth).await?;
             Ok(super::ParseResult::Single(Box::new(video)))
         }
-        VideoType::FavoriteList(fav_info) => {
-            let videos = fetch_favorite_list(client, &fav_info, auth).await?;
-            Ok(super::ParseResult::Batch(videos))
-        }
-        VideoType::SpaceVideo(mid) => {
-            let wbi = wbi_manager.ok_or_else(|| {
-                DownloaderError::Api("WBI manager required for space video".to_string())
-            })?;
-            let videos = fetch_space_videos(client, &mid, auth, wbi).await?;
-            Ok(super::ParseResult::Batch(videos))
-        }
-        VideoType::MediaList(media_id) => {
-            let videos = fetch_media_list(client, &media_id, auth).await?;
-            Ok(super::ParseResult::Batch(videos))
+        VideoType::LiveReplay(rid) => {
+            let video = super::live::fetch_live_replay_info(client, &rid).await?;
+            Ok(super::ParseResult::Single(Box::new(video)))
         }
-        VideoType::SeriesList(series_info) => {
-            let videos = fetch_series_list(client, &series_info, auth).await?;
+        VideoType::FavoriteList(_)
+        | VideoType::SpaceVideo(_)
+        | VideoType::MediaList(_)
+        | VideoType::SeriesList(_) => {
+            let videos = fetch_all(client, video_type, auth, wbi_manager, options).await?;
             Ok(super::ParseResult::Batch(videos))
         }
     }
 }
 
-async fn fetch_video_info_by_bvid(
-    client: &Arc<HttpClient>,
+/// 按发布时间倒序的列表中，单个条目相对日期范围的位置
+#[derive(Debug, PartialEq)]
+enum DateCheck {
+    Keep,
+    /// 晚于 `until`，跳过
+    Skip,
+    /// 早于 `since`，之后的条目更早，可以停止翻页
+    Stop,
+}
+
+fn check_pubdate(options: &super::ParseOptions, timestamp: i64) -> DateCheck {
+    if !options.has_date_range() {
+        return DateCheck::Keep;
+    }
+
+    let date = format_timestamp(timestamp.max(0) as u64);
+    if options.since.as_deref().is_some_and(|since| date.as_str() < since) {
+        DateCheck::Stop
+    } else if options.until.as_deref().is_some_and(|until| date.as_str() > until) {
+        DateCheck::Skip
+    } else {
+        DateCheck::Keep
+    }
+}
+
+/// 获取普通视频（BV号）的信息
+pub async fn fetch_video_info_by_bvid(
+    client: &dyn HttpTransport,
     bvid: &str,
     auth: Option<&Auth>,
 ) -> Result<VideoInfo> {
@@ -79,8 +123,7 @@ async fn fetch_video_info_by_bvid(
         "https://api.bilibili.com/x/web-interface/view?bvid={}",
         bvid
     );
-    let response = client.get_with_auth(&api, auth).await?;
-    let json_text = response.text().await?;
+    let json_text = client.get_text(&api, auth).await?;
 
     tracing::debug!("Video info response: {}", json_text);
 
@@ -88,10 +131,7 @@ async fn fetch_video_info_by_bvid(
         .map_err(|e| DownloaderError::Parse(format!("Failed to parse video info: {}", e)))?;
 
     if api_response.code != 0 {
-        return Err(DownloaderError::Api(format!(
-            "API error: {}",
-            api_response.message
-        )));
+        return Err(api_error(api_response.code, &api_response.message));
     }
 
     let data = api_response
@@ -101,14 +141,14 @@ async fn fetch_video_info_by_bvid(
     convert_to_video_info(data)
 }
 
-async fn fetch_video_info_by_aid(
-    client: &Arc<HttpClient>,
+/// 获取普通视频（av号）的信息
+pub async fn fetch_video_info_by_aid(
+    client: &dyn HttpTransport,
     aid: &str,
     auth: Option<&Auth>,
 ) -> Result<VideoInfo> {
     let api = format!("https://api.bilibili.com/x/web-interface/view?aid={}", aid);
-    let response = client.get_with_auth(&api, auth).await?;
-    let json_text = response.text().await?;
+    let json_text = client.get_text(&api, auth).await?;
 
     tracing::debug!("Video info response: {}", json_text);
 
@@ -116,10 +156,7 @@ async fn fetch_video_info_by_aid(
         .map_err(|e| DownloaderError::Parse(format!("Failed to parse video info: {}", e)))?;
 
     if api_response.code != 0 {
-        return Err(DownloaderError::Api(format!(
-            "API error: {}",
-            api_response.message
-        )));
+        return Err(api_error(api_response.code, &api_response.message));
     }
 
     let data = api_response
@@ -129,6 +166,322 @@ async fn fetch_video_info_by_aid(
     convert_to_video_info(data)
 }
 
+/// 导航接口中的登录状态
+#[derive(Debug, Deserialize)]
+struct NavLoginData {
+    #[serde(rename = "isLogin", default)]
+    is_login: bool,
+}
+
+/// 通过导航接口检查当前凭证是否仍处于登录状态
+pub async fn check_login(client: &Arc<HttpClient>, auth: Option<&Auth>) -> Result<bool> {
+    let api = "https://api.bilibili.com/x/web-interface/nav";
+    let response = client.get_with_auth(api, auth).await?;
+    let json_text = response.text().await?;
+
+    parse_login_status(&json_text)
+}
+
+fn parse_login_status(json_text: &str) -> Result<bool> {
+    let api_response: ApiResponse<NavLoginData> = serde_json::from_str(json_text)
+        .map_err(|e| DownloaderError::Parse(format!("Failed to parse nav response: {}", e)))?;
+
+    match api_response.code {
+        0 => Ok(api_response.data.is_some_and(|d| d.is_login)),
+        // 账号未登录
+        -101 => Ok(false),
+        code => Err(api_error(code, &api_response.message)),
+    }
+}
+
+/// 按出口IP判断的所在地区（`x/web-interface/zone` 接口）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct IpZone {
+    #[serde(default)]
+    pub addr: String,
+    #[serde(default)]
+    pub country: String,
+    #[serde(default)]
+    pub province: String,
+    #[serde(default)]
+    pub isp: String,
+}
+
+impl IpZone {
+    /// 是否在中国大陆以外（仅限大陆播放的内容可能受区域限制）
+    pub fn outside_mainland(&self) -> bool {
+        self.country != "中国" || matches!(self.province.as_str(), "香港" | "澳门" | "台湾")
+    }
+}
+
+/// 查询出口IP对应的地区
+pub async fn fetch_ip_zone(client: &Arc<HttpClient>) -> Result<IpZone> {
+    let api = "https://api.bilibili.com/x/web-interface/zone";
+    let response = client.get(api, None).await?;
+    let json_text = response.text().await?;
+
+    parse_ip_zone(&json_text)
+}
+
+fn parse_ip_zone(json_text: &str) -> Result<IpZone> {
+    let api_response: ApiResponse<IpZone> = serde_json::from_str(json_text)
+        .map_err(|e| DownloaderError::Parse(format!("Failed to parse zone response: {}", e)))?;
+
+    if api_response.code != 0 {
+        return Err(api_error(api_response.code, &api_response.message));
+    }
+    api_response
+        .data
+        .ok_or_else(|| DownloaderError::Parse("No data in zone response".to_string()))
+}
+
+/// 获取视频标签
+pub async fn fetch_video_tags(client: &Arc<HttpClient>, aid: &str) -> Result<Vec<String>> {
+    let api = format!("https://api.bilibili.com/x/tag/archive/tags?aid={}", aid);
+    let response = client.get(&api, None).await?;
+    let json_text = response.text().await?;
+
+    tracing::debug!("Video tags response: {}", json_text);
+
+    parse_tags_response(&json_text)
+}
+
+fn parse_tags_response(json_text: &str) -> Result<Vec<String>> {
+    let api_response: ApiResponse<Vec<TagData>> = serde_json::from_str(json_text)
+        .map_err(|e| DownloaderError::Parse(format!("Failed to parse video tags: {}", e)))?;
+
+    if api_response.code != 0 {
+        return Err(api_error(api_response.code, &api_response.message));
+    }
+
+    Ok(api_response
+        .data
+        .unwrap_or_default()
+        .into_iter()
+        .map(|tag| tag.tag_name)
+        .filter(|name| !name.is_empty())
+        .collect())
+}
+
+/// 将视频标签写入附加元数据，获取失败时只记录警告
+async fn attach_tags(client: &Arc<HttpClient>, video: &mut VideoInfo) {
+    match fetch_video_tags(client, &video.aid.to_string()).await {
+        Ok(tags) => {
+            if let Some(ExtraData::Bilibili(ref mut extra)) = video.extra_data {
+                extra.tags = tags;
+            }
+        }
+        Err(e) => tracing::warn!("Failed to fetch video tags: {}", e),
+    }
+}
+
+/// 一条评论（`--download-comments`），楼中楼回复在 `replies` 中
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    pub rpid: u64,
+    /// 评论者的 mid
+    pub mid: u64,
+    pub user: String,
+    pub message: String,
+    pub likes: u64,
+    /// 发布时间（Unix 时间戳）
+    pub ctime: i64,
+    /// 回复总数（未获取回复时也会给出）
+    pub reply_count: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replies: Vec<Comment>,
+}
+
+impl From<ReplyItem> for Comment {
+    fn from(item: ReplyItem) -> Self {
+        Self {
+            rpid: item.rpid,
+            mid: item.mid,
+            user: item.member.uname,
+            message: item.content.message,
+            likes: item.like,
+            ctime: item.ctime,
+            reply_count: item.rcount,
+            replies: item
+                .replies
+                .unwrap_or_default()
+                .into_iter()
+                .map(Comment::from)
+                .collect(),
+        }
+    }
+}
+
+/// 评论获取选项
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommentOptions {
+    /// 同时获取每条评论的全部回复（每条有回复的评论多若干次请求）
+    pub with_replies: bool,
+    /// 最多获取的评论条数（不含回复），None 表示全部
+    pub max_comments: Option<usize>,
+}
+
+/// 评论列表单页
+#[derive(Debug, Default)]
+struct CommentPage {
+    comments: Vec<Comment>,
+    /// 下一页的游标，没有下一页时为 None
+    next_offset: Option<String>,
+}
+
+/// 按时间从新到旧逐页获取视频评论（`x/v2/reply/wbi/main` 的 `mode=2`，需要WBI签名），
+/// 置顶评论在最前
+///
+/// 相邻请求之间按API节流间隔等待；评论区关闭时返回空列表
+pub async fn fetch_comments(
+    client: &Arc<HttpClient>,
+    aid: &str,
+    auth: Option<&Auth>,
+    wbi_manager: &mut super::wbi::WbiManager,
+    options: CommentOptions,
+) -> Result<Vec<Comment>> {
+    let mut comments: Vec<Comment> = Vec::new();
+    let mut offset = String::new();
+
+    loop {
+        let pagination = super::wbi::encode_param(&serde_json::json!({ "offset": offset }).to_string());
+        let base_params = format!(
+            "mode=2&oid={}&pagination_str={}&plat=1&type=1&web_location=1315875",
+            aid, pagination
+        );
+        let signed_params = wbi_manager.sign_url(&base_params).await?;
+        let api = format!("https://api.bilibili.com/x/v2/reply/wbi/main?{}", signed_params);
+
+        let response = client.get_with_auth(&api, auth).await?;
+        let json_text = response.text().await?;
+        let page = parse_comment_page(&json_text)?;
+
+        // 置顶评论可能在后续页再次出现
+        for comment in page.comments {
+            if !comments.iter().any(|c| c.rpid == comment.rpid) {
+                comments.push(comment);
+            }
+        }
+        tracing::debug!("Fetched {} comment(s) of av{}", comments.len(), aid);
+
+        if let Some(max) = options.max_comments {
+            if comments.len() >= max {
+                comments.truncate(max);
+                break;
+            }
+        }
+        match page.next_offset {
+            Some(next) => offset = next,
+            None => break,
+        }
+        client.api_throttle().pause().await;
+    }
+
+    for comment in &mut comments {
+        if !options.with_replies {
+            // 只保留完整的回复列表，预览的几条不写入
+            comment.replies.clear();
+            continue;
+        }
+        if comment.reply_count > comment.replies.len() as u64 {
+            client.api_throttle().pause().await;
+            comment.replies = fetch_comment_replies(client, aid, comment.rpid, auth).await?;
+        }
+    }
+
+    Ok(comments)
+}
+
+/// 获取一条评论下的全部回复（`x/v2/reply/reply`）
+async fn fetch_comment_replies(
+    client: &Arc<HttpClient>,
+    aid: &str,
+    root: u64,
+    auth: Option<&Auth>,
+) -> Result<Vec<Comment>> {
+    let page_size = 20;
+    let mut replies = Vec::new();
+    let mut page = 1;
+
+    loop {
+        let api = format!(
+            "https://api.bilibili.com/x/v2/reply/reply?oid={}&type=1&root={}&pn={}&ps={}",
+            aid, root, page, page_size
+        );
+        let response = client.get_with_auth(&api, auth).await?;
+        let json_text = response.text().await?;
+        let (batch, total) = parse_reply_page(&json_text)?;
+
+        let fetched = batch.len();
+        replies.extend(batch);
+        if fetched == 0 || replies.len() >= total as usize {
+            break;
+        }
+        page += 1;
+        client.api_throttle().pause().await;
+    }
+
+    Ok(replies)
+}
+
+fn parse_comment_page(json_text: &str) -> Result<CommentPage> {
+    let api_response: ApiResponse<ReplyMainData> = serde_json::from_str(json_text)
+        .map_err(|e| DownloaderError::Parse(format!("Failed to parse comments: {}", e)))?;
+
+    match api_response.code {
+        0 => {}
+        // 评论区已关闭
+        12002 => return Ok(CommentPage::default()),
+        code => return Err(api_error(code, &api_response.message)),
+    }
+
+    let data = api_response
+        .data
+        .ok_or_else(|| DownloaderError::Parse("No comment data".to_string()))?;
+
+    let next_offset = if data.cursor.is_end {
+        None
+    } else {
+        data.cursor
+            .pagination_reply
+            .and_then(|p| p.next_offset)
+            .filter(|offset| !offset.is_empty())
+    };
+    let comments = data
+        .top_replies
+        .unwrap_or_default()
+        .into_iter()
+        .chain(data.replies.unwrap_or_default())
+        .map(Comment::from)
+        .collect();
+
+    Ok(CommentPage {
+        comments,
+        next_offset,
+    })
+}
+
+/// 解析回复列表单页，返回本页回复与回复总数
+fn parse_reply_page(json_text: &str) -> Result<(Vec<Comment>, u32)> {
+    let api_response: ApiResponse<ReplyListData> = serde_json::from_str(json_text)
+        .map_err(|e| DownloaderError::Parse(format!("Failed to parse comment replies: {}", e)))?;
+
+    if api_response.code != 0 {
+        return Err(api_error(api_response.code, &api_response.message));
+    }
+    let data = api_response
+        .data
+        .ok_or_else(|| DownloaderError::Parse("No comment reply data".to_string()))?;
+
+    let replies = data
+        .replies
+        .unwrap_or_default()
+        .into_iter()
+        .map(Comment::from)
+        .collect();
+    Ok((replies, data.page.count))
+}
+
 fn convert_to_video_info(data: VideoInfoData) -> Result<VideoInfo> {
     let duration = data.pages.first().map(|p| p.duration).unwrap_or(0);
 
@@ -157,9 +510,66 @@ fn convert_to_video_info(data: VideoInfoData) -> Result<VideoInfo> {
         pages,
         is_bangumi: false,
         ep_id: None,
+        extra_data: Some(ExtraData::Bilibili(BilibiliExtra {
+            tid: data.tid,
+            tname: data.tname,
+            copyright: data.copyright,
+            stat: BilibiliStat {
+                view: data.stat.view,
+                danmaku: data.stat.danmaku,
+                reply: data.stat.reply,
+                favorite: data.stat.favorite,
+                coin: data.stat.coin,
+                share: data.stat.share,
+                like: data.stat.like,
+            },
+            tags: Vec::new(),
+            members_only: data.is_upower_exclusive,
+            live_replay: false,
+            page_count: 0,
+        })),
     })
 }
 
+/// 充电专属视频未登录时无法获取播放地址，提前给出明确的错误
+///
+/// 已登录时是否充电只能从播放地址接口得知（见 [`members_only_error`]）
+fn check_members_only(video: &VideoInfo, auth: Option<&Auth>) -> Result<()> {
+    if !video.bilibili_extra().is_some_and(|extra| extra.members_only) {
+        return Ok(());
+    }
+
+    let logged_in = auth.is_some_and(|a| a.cookie.is_some() || a.access_token.is_some());
+    if !logged_in {
+        return Err(DownloaderError::MembersOnly(format!(
+            "{} is exclusive to members who charged the uploader, and no account is logged in",
+            video.id
+        )));
+    }
+
+    tracing::info!(
+        "{} is exclusive to members who charged the uploader, checking the current account",
+        video.id
+    );
+    Ok(())
+}
+
+/// 播放地址接口对充电专属视频的拒绝（87007/87008），说明当前账号未充电
+fn members_only_error(code: i32, message: &str, auth: Option<&Auth>) -> Option<DownloaderError> {
+    if !matches!(code, 87007 | 87008) {
+        return None;
+    }
+    let account = if auth.is_some() {
+        "the current account is not a member"
+    } else {
+        "no account is logged in"
+    };
+    Some(DownloaderError::MembersOnly(format!(
+        "{} (code: {}), {}",
+        message, code, account
+    )))
+}
+
 #[allow(dead_code)]
 pub async fn get_play_url(
     client: &Arc<HttpClient>,
@@ -188,6 +598,124 @@ pub async fn get_play_url_with_mode_and_ep(
     api_mode: super::ApiMode,
     ep_id: Option<&str>,
 ) -> Result<Vec<Stream>> {
+    let data = fetch_play_url_data(client, video_id, cid, auth, api_mode, ep_id).await?;
+    streams_from_play_url_data(data)
+}
+
+/// 当前认证与API模式下清晰度的实际可用情况
+#[derive(Debug, Clone, Default)]
+pub struct QualityProbe {
+    /// 视频列出的清晰度（quality_id，从高到低）
+    pub listed: Vec<u32>,
+    /// 实际返回了流地址的清晰度（quality_id，从高到低）
+    pub available: Vec<u32>,
+}
+
+impl QualityProbe {
+    /// 实际能下载到的最高清晰度
+    pub fn max_available(&self) -> Option<u32> {
+        self.available.first().copied()
+    }
+
+    /// 列出但当前无法获取的清晰度（通常需要登录或大会员）
+    pub fn locked(&self) -> Vec<u32> {
+        self.listed
+            .iter()
+            .filter(|id| !self.available.contains(id))
+            .copied()
+            .collect()
+    }
+}
+
+/// 请求播放地址，对比列出的清晰度和实际返回的清晰度
+pub async fn probe_qualities(
+    client: &Arc<HttpClient>,
+    video_id: &str,
+    cid: &str,
+    auth: Option<&Auth>,
+    api_mode: super::ApiMode,
+    ep_id: Option<&str>,
+) -> Result<QualityProbe> {
+    let data = fetch_play_url_data(client, video_id, cid, auth, api_mode, ep_id).await?;
+    Ok(quality_probe_from_data(&data))
+}
+
+fn quality_probe_from_data(data: &PlayUrlData) -> QualityProbe {
+    let mut listed = data.accept_quality.clone();
+    let mut available: Vec<u32> = match data.dash {
+        Some(ref dash) => dash.video.iter().map(|v| v.id).collect(),
+        None if data.durl.is_some() && data.quality != 0 => vec![data.quality],
+        None => Vec::new(),
+    };
+
+    for ids in [&mut listed, &mut available] {
+        ids.sort_unstable_by(|a, b| b.cmp(a));
+        ids.dedup();
+    }
+
+    QualityProbe { listed, available }
+}
+
+/// 播放地址请求的 `fnval` 位掩码中各位的含义
+///
+/// 缺少对应的位时接口不会返回该类流，即使账号有权限。Hi-Res 无损音频
+/// （`dash.flac`）没有单独的位，随 DASH 一起返回
+pub mod fnval {
+    /// DASH 格式（音视频分离），其余各位都依赖此位
+    pub const DASH: u32 = 16;
+    /// HDR 真彩（清晰度 125）
+    pub const HDR: u32 = 64;
+    /// 4K 超清（清晰度 120），需同时带 `fourk=1`
+    pub const FOUR_K: u32 = 128;
+    /// 杜比全景声音轨
+    pub const DOLBY_AUDIO: u32 = 256;
+    /// 杜比视界（清晰度 126）
+    pub const DOLBY_VISION: u32 = 512;
+    /// 8K 超高清（清晰度 127）
+    pub const EIGHT_K: u32 = 1024;
+    /// AV1 编码
+    pub const AV1: u32 = 2048;
+
+    /// 所有请求使用的组合值（4048），已包含 HDR、4K、杜比全景声、杜比视界、
+    /// 8K 和 AV1 各位。顶级画质没有返回时是账号权限或稿件本身的限制，
+    /// 不是 `fnval` 缺位
+    pub const ALL: u32 = DASH | HDR | FOUR_K | DOLBY_AUDIO | DOLBY_VISION | EIGHT_K | AV1;
+}
+
+/// 清晰度优先级中列出、但返回的流中没有的顶级画质（8K、杜比视界）
+///
+/// 用于在自动降级前给出提示，返回 `QUALITY_MAP` 中的清晰度名称
+pub fn missing_top_qualities(quality_priority: &[String], streams: &[Stream]) -> Vec<&'static str> {
+    const TOP_QUALITIES: &[(u32, &[&str])] = &[
+        (127, &["8k"]),
+        (126, &["杜比视界", "dolby", "dv"]),
+    ];
+
+    TOP_QUALITIES
+        .iter()
+        .filter(|(_, aliases)| {
+            quality_priority.iter().any(|quality| {
+                let quality = quality.to_lowercase();
+                aliases.iter().any(|alias| quality.contains(alias))
+            })
+        })
+        .filter(|(id, _)| {
+            !streams
+                .iter()
+                .any(|s| s.stream_type == StreamType::Video && s.quality_id == *id)
+        })
+        .map(|(id, _)| get_quality_name(*id))
+        .collect()
+}
+
+async fn fetch_play_url_data(
+    client: &Arc<HttpClient>,
+    video_id: &str,
+    cid: &str,
+    auth: Option<&Auth>,
+    api_mode: super::ApiMode,
+    ep_id: Option<&str>,
+) -> Result<PlayUrlData> {
     let is_bangumi = ep_id.is_some();
     
     let api = match api_mode {
@@ -196,13 +724,13 @@ pub async fn get_play_url_with_mode_and_ep(
                 // 番剧使用不同的API端点
                 let ep_param = ep_id.unwrap();
                 format!(
-                    "https://api.bilibili.com/pgc/player/web/v2/playurl?support_multi_audio=true&avid={}&cid={}&ep_id={}&fnval=4048&fnver=0&fourk=1&qn=127",
-                    video_id, cid, ep_param
+                    "https://api.bilibili.com/pgc/player/web/v2/playurl?support_multi_audio=true&avid={}&cid={}&ep_id={}&fnval={}&fnver=0&fourk=1&qn=127",
+                    video_id, cid, ep_param, fnval::ALL
                 )
             } else {
                 format!(
-                    "https://api.bilibili.com/x/player/wbi/playurl?avid={}&cid={}&qn=127&fnval=4048&fnver=0&fourk=1",
-                    video_id, cid
+                    "https://api.bilibili.com/x/player/wbi/playurl?avid={}&cid={}&qn=127&fnval={}&fnver=0&fourk=1",
+                    video_id, cid, fnval::ALL
                 )
             }
         }
@@ -210,31 +738,35 @@ pub async fn get_play_url_with_mode_and_ep(
             if is_bangumi {
                 let ep_param = ep_id.unwrap();
                 format!(
-                    "https://api.snm0516.aisee.tv/pgc/player/api/playurltv?avid={}&cid={}&ep_id={}&qn=127&fnval=4048&fnver=0&fourk=1",
-                    video_id, cid, ep_param
+                    "https://api.snm0516.aisee.tv/pgc/player/api/playurltv?avid={}&cid={}&ep_id={}&qn=127&fnval={}&fnver=0&fourk=1",
+                    video_id, cid, ep_param, fnval::ALL
                 )
             } else {
                 format!(
-                    "https://api.snm0516.aisee.tv/x/tv/playurl?avid={}&cid={}&qn=127&fnval=4048&fnver=0&fourk=1",
-                    video_id, cid
+                    "https://api.snm0516.aisee.tv/x/tv/playurl?avid={}&cid={}&qn=127&fnval={}&fnver=0&fourk=1",
+                    video_id, cid, fnval::ALL
                 )
             }
         }
         super::ApiMode::App => {
             // APP API 需要特殊的签名，这里使用简化版本
             format!(
-                "https://app.bilibili.com/x/v2/playurl?avid={}&cid={}&qn=127&fnval=4048&fnver=0&fourk=1",
-                video_id, cid
+                "https://app.bilibili.com/x/v2/playurl?avid={}&cid={}&qn=127&fnval={}&fnver=0&fourk=1",
+                video_id, cid, fnval::ALL
             )
         }
         super::ApiMode::International => {
             format!(
-                "https://app.global.bilibili.com/intl/gateway/v2/ogv/playurl?avid={}&cid={}&qn=127&fnval=4048&fnver=0&fourk=1",
-                video_id, cid
+                "https://app.global.bilibili.com/intl/gateway/v2/ogv/playurl?avid={}&cid={}&qn=127&fnval={}&fnver=0&fourk=1",
+                video_id, cid, fnval::ALL
             )
         }
     };
 
+    // TV登录获得的access_token在各模式下都能解锁更高清晰度，作为access_key附加到请求中
+    let api = append_access_key(api, auth);
+    tracing::debug!("Requesting play URL ({:?} mode) with credential: {}", api_mode, credential_label(auth));
+
     let response = client.get_with_auth(&api, auth).await?;
     let json_text = response.text().await?;
 
@@ -246,25 +778,24 @@ pub async fn get_play_url_with_mode_and_ep(
             .map_err(|e| DownloaderError::Parse(format!("Failed to parse bangumi play URL: {}", e)))?;
 
         if api_response.code != 0 {
-            return Err(DownloaderError::Api(format!(
-                "API error: {}",
-                api_response.message
-            )));
+            return Err(api_error(api_response.code, &api_response.message));
         }
 
-        api_response
+        let result = api_response
             .result
-            .ok_or_else(|| DownloaderError::DownloadFailed("No play URL data".to_string()))?
-            .video_info
+            .ok_or_else(|| DownloaderError::DownloadFailed("No play URL data".to_string()))?;
+        let mut data = result.video_info;
+        data.is_drm |= result.is_drm;
+        data
     } else {
         let api_response: ApiResponse<PlayUrlData> = serde_json::from_str(&json_text)
             .map_err(|e| DownloaderError::Parse(format!("Failed to parse play URL: {}", e)))?;
 
         if api_response.code != 0 {
-            return Err(DownloaderError::Api(format!(
-                "API error: {}",
-                api_response.message
-            )));
+            if let Some(err) = members_only_error(api_response.code, &api_response.message, auth) {
+                return Err(err);
+            }
+            return Err(api_error(api_response.code, &api_response.message));
         }
 
         api_response
@@ -272,6 +803,47 @@ pub async fn get_play_url_with_mode_and_ep(
             .ok_or_else(|| DownloaderError::DownloadFailed("No play URL data".to_string()))?
     };
 
+    Ok(data)
+}
+
+/// 认证信息中有access_token时，将其作为 `access_key` 参数附加到请求地址
+pub fn append_access_key(url: String, auth: Option<&Auth>) -> String {
+    match auth.and_then(|a| a.access_token.as_deref()) {
+        Some(token) if !token.is_empty() => {
+            let token: String = url::form_urlencoded::byte_serialize(token.as_bytes()).collect();
+            let separator = if url.contains('?') { '&' } else { '?' };
+            format!("{}{}access_key={}", url, separator, token)
+        }
+        _ => url,
+    }
+}
+
+/// 日志中显示请求使用的凭据类型（不输出凭据内容）
+fn credential_label(auth: Option<&Auth>) -> &'static str {
+    let has_cookie = auth.is_some_and(|a| a.cookie.is_some());
+    let has_token = auth.is_some_and(|a| a.access_token.is_some());
+    match (has_cookie, has_token) {
+        (true, true) => "cookie + access_key",
+        (false, true) => "access_key",
+        (true, false) => "cookie",
+        (false, false) => "none",
+    }
+}
+
+/// 播放地址是否为DRM加密流
+fn is_drm_protected(data: &PlayUrlData) -> bool {
+    data.is_drm || data.drm_tech_type != 0
+}
+
+fn streams_from_play_url_data(data: PlayUrlData) -> Result<Vec<Stream>> {
+    // 加密的流下载后无法播放，直接失败而不是下载无用的文件
+    if is_drm_protected(&data) {
+        return Err(DownloaderError::DrmProtected(format!(
+            "play URL is DRM-encrypted (drm_tech_type={})",
+            data.drm_tech_type
+        )));
+    }
+
     let mut streams = Vec::new();
 
     if let Some(dash) = data.dash {
@@ -286,8 +858,13 @@ pub async fn get_play_url_with_mode_and_ep(
                 quality_id: video.id,
                 codec: codec.to_string(),
                 url: video.base_url.clone(),
+                backup_urls: video.backup_url.clone().unwrap_or_default(),
                 size: 0, // Size not provided in API
                 bandwidth: video.bandwidth,
+                language: None,
+                height: video.height,
+                fps: video.frame_rate.parse().unwrap_or(0.0),
+                segment_base: video.segment_base.as_ref().and_then(DashSegmentBase::to_segment_base),
             });
         }
 
@@ -310,8 +887,13 @@ pub async fn get_play_url_with_mode_and_ep(
                 quality_id: audio.id,
                 codec: codec.to_string(),
                 url: audio.base_url.clone(),
+                backup_urls: audio.backup_url.clone().unwrap_or_default(),
                 size: 0,
                 bandwidth: audio.bandwidth,
+                language: None,
+                height: 0,
+                fps: 0.0,
+                segment_base: audio.segment_base.as_ref().and_then(DashSegmentBase::to_segment_base),
             });
         }
 
@@ -334,8 +916,13 @@ pub async fn get_play_url_with_mode_and_ep(
                         quality_id: audio.id,
                         codec: codec.to_string(),
                         url: audio.base_url.clone(),
+                        backup_urls: audio.backup_url.clone().unwrap_or_default(),
                         size: 0,
                         bandwidth: audio.bandwidth,
+                        language: None,
+                        height: 0,
+                        fps: 0.0,
+                        segment_base: audio.segment_base.as_ref().and_then(DashSegmentBase::to_segment_base),
                     });
                 }
             }
@@ -359,8 +946,46 @@ pub async fn get_play_url_with_mode_and_ep(
                     quality_id: flac_audio.id,
                     codec: codec.to_string(),
                     url: flac_audio.base_url.clone(),
+                    backup_urls: flac_audio.backup_url.clone().unwrap_or_default(),
                     size: 0,
                     bandwidth: flac_audio.bandwidth,
+                    language: None,
+                    height: 0,
+                    fps: 0.0,
+                    segment_base: flac_audio.segment_base.as_ref().and_then(DashSegmentBase::to_segment_base),
+                });
+            }
+        }
+    }
+
+    // 处理多语言音轨（配音），原声仍在上面的普通音频流中
+    if let Some(language) = data.language {
+        for item in language.items {
+            let label = if item.title.is_empty() {
+                item.lang.clone()
+            } else {
+                item.title.clone()
+            };
+
+            for audio in item.audio {
+                let codec = match audio.codecs.as_deref() {
+                    Some("mp4a.40.2") | Some("mp4a.40.5") | None => "M4A".to_string(),
+                    Some(codecs) => codecs.to_string(),
+                };
+
+                streams.push(Stream {
+                    stream_type: StreamType::Audio,
+                    quality: format!("{}kbps ({})", audio.bandwidth / 1000, label),
+                    quality_id: audio.id,
+                    codec,
+                    url: audio.base_url.clone(),
+                    backup_urls: audio.backup_url.clone().unwrap_or_default(),
+                    size: 0,
+                    bandwidth: audio.bandwidth,
+                    language: Some(item.lang.clone()),
+                    height: 0,
+                    fps: 0.0,
+                    segment_base: audio.segment_base.as_ref().and_then(DashSegmentBase::to_segment_base),
                 });
             }
         }
@@ -372,20 +997,22 @@ pub async fn get_play_url_with_mode_and_ep(
         ));
     }
 
-    Ok(streams)
+    Ok(crate::platform::selector::normalize_streams(streams))
 }
 
 pub async fn get_subtitles(
     client: &Arc<HttpClient>,
     video_id: &str,
     cid: &str,
+    auth: Option<&Auth>,
 ) -> Result<Vec<Subtitle>> {
     let api = format!(
         "https://api.bilibili.com/x/player/wbi/v2?aid={}&cid={}",
         video_id, cid
     );
 
-    let response = client.get(&api, None).await?;
+    // CC/AI字幕通常只返回给已登录用户，需要携带认证信息
+    let response = client.get_with_auth(&api, auth).await?;
     let json_text = response.text().await?;
 
     tracing::debug!("Subtitle response: {}", json_text);
@@ -421,7 +1048,8 @@ pub async fn get_subtitles(
     Ok(subtitles)
 }
 
-fn get_quality_name(quality_id: u32) -> &'static str {
+/// 清晰度ID对应的名称
+pub fn get_quality_name(quality_id: u32) -> &'static str {
     for (name, id) in QUALITY_MAP {
         if *id == quality_id {
             return name;
@@ -439,11 +1067,25 @@ fn get_codec_name(codec_id: u32) -> &'static str {
     }
 }
 
-fn format_timestamp(timestamp: u64) -> String {
-    use std::time::{Duration, UNIX_EPOCH};
-    let datetime = UNIX_EPOCH + Duration::from_secs(timestamp);
-    // Simple formatting - in production you'd use chrono
-    format!("{:?}", datetime)
+/// 将Unix时间戳格式化为北京时间（UTC+8）的日期 `YYYY-MM-DD`
+pub(super) fn format_timestamp(timestamp: u64) -> String {
+    let days = ((timestamp + 8 * 3600) / 86400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// 1970-01-01 起的天数转换为公历日期（Howard Hinnant 的 civil_from_days 算法）
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
 }
 
 // 番剧信息获取 - 通过 ep_id
@@ -451,6 +1093,7 @@ async fn fetch_bangumi_info_by_ep(
     client: &Arc<HttpClient>,
     ep_id: &str,
     auth: Option<&Auth>,
+    include_extras: bool,
 ) -> Result<VideoInfo> {
     let api = format!("https://api.bilibili.com/pgc/view/web/season?ep_id={}", ep_id);
     let response = client.get_with_auth(&api, auth).await?;
@@ -462,17 +1105,14 @@ async fn fetch_bangumi_info_by_ep(
         .map_err(|e| DownloaderError::Parse(format!("Failed to parse bangumi info: {}", e)))?;
 
     if api_response.code != 0 {
-        return Err(DownloaderError::Api(format!(
-            "API error: {}",
-            api_response.message
-        )));
+        return Err(api_error(api_response.code, &api_response.message));
     }
 
     let data = api_response
         .result
         .ok_or_else(|| DownloaderError::VideoNotFound(format!("ep{}", ep_id)))?;
 
-    convert_bangumi_to_video_info(data, ep_id)
+    convert_bangumi_to_video_info(data, ep_id, include_extras)
 }
 
 // 番剧信息获取 - 通过 season_id
@@ -480,6 +1120,7 @@ async fn fetch_bangumi_info_by_ss(
     client: &Arc<HttpClient>,
     season_id: &str,
     auth: Option<&Auth>,
+    include_extras: bool,
 ) -> Result<VideoInfo> {
     let api = format!("https://api.bilibili.com/pgc/view/web/season?season_id={}", season_id);
     let response = client.get_with_auth(&api, auth).await?;
@@ -491,45 +1132,74 @@ async fn fetch_bangumi_info_by_ss(
         .map_err(|e| DownloaderError::Parse(format!("Failed to parse bangumi info: {}", e)))?;
 
     if api_response.code != 0 {
-        return Err(DownloaderError::Api(format!(
-            "API error: {}",
-            api_response.message
-        )));
+        return Err(api_error(api_response.code, &api_response.message));
     }
 
     let data = api_response
         .result
         .ok_or_else(|| DownloaderError::VideoNotFound(format!("ss{}", season_id)))?;
 
-    convert_bangumi_to_video_info(data, "")
+    convert_bangumi_to_video_info(data, "", include_extras)
 }
 
-fn convert_bangumi_to_video_info(data: BangumiInfoData, target_ep_id: &str) -> Result<VideoInfo> {
-    let mut episodes = data.episodes;
+fn convert_bangumi_to_video_info(
+    data: BangumiInfoData,
+    target_ep_id: &str,
+    include_extras: bool,
+) -> Result<VideoInfo> {
     let mut title = data.title.clone();
 
-    // 如果主episodes为空或不包含目标ep，检查section
-    if !target_ep_id.is_empty() && !episodes.iter().any(|ep| ep.id.to_string() == target_ep_id) {
-        for section in &data.section {
-            if section.episodes.iter().any(|ep| ep.id.to_string() == target_ep_id) {
-                title = format!("{}[{}]", title, section.title);
-                episodes = section.episodes.clone();
-                break;
+    // (所属section标题, episode)，正片的section标题为None
+    let mut episodes: Vec<(Option<String>, BangumiEpisode)> = Vec::new();
+
+    if include_extras {
+        // 包含花絮：正片（含预告）在前，各section按顺序追加
+        episodes.extend(data.episodes.into_iter().map(|ep| (None, ep)));
+        for section in data.section {
+            let section_title = section.title;
+            episodes.extend(
+                section
+                    .episodes
+                    .into_iter()
+                    .map(|ep| (Some(section_title.clone()), ep)),
+            );
+        }
+    } else {
+        let mut main_episodes = data.episodes;
+
+        // 如果主episodes为空或不包含目标ep，检查section
+        if !target_ep_id.is_empty()
+            && !main_episodes.iter().any(|ep| ep.id.to_string() == target_ep_id)
+        {
+            for section in &data.section {
+                if section.episodes.iter().any(|ep| ep.id.to_string() == target_ep_id) {
+                    title = format!("{}[{}]", title, section.title);
+                    main_episodes = section.episodes.clone();
+                    break;
+                }
             }
         }
+
+        // 跳过预告
+        episodes.extend(
+            main_episodes
+                .into_iter()
+                .filter(|ep| ep.badge != "预告")
+                .map(|ep| (None, ep)),
+        );
     }
 
     let mut pages = Vec::new();
-    let mut index = 1;
     let mut ep_id_for_first_page = None;
 
-    for episode in episodes {
-        // 跳过预告
-        if episode.badge == "预告" {
-            continue;
+    for (index, (section_title, episode)) in (1..).zip(episodes) {
+        let mut ep_title = format!("{} {}", episode.title, episode.long_title).trim().to_string();
+        // 花絮在标题前标注所属section，便于与正片区分
+        if let Some(section_title) = section_title.filter(|t| !t.is_empty()) {
+            ep_title = format!("[{}] {}", section_title, ep_title);
+        } else if episode.badge == "预告" {
+            ep_title = format!("[{}] {}", episode.badge, ep_title);
         }
-
-        let ep_title = format!("{} {}", episode.title, episode.long_title).trim().to_string();
         let current_ep_id = episode.id.to_string();
 
         // 保存第一个episode的ep_id
@@ -544,8 +1214,6 @@ fn convert_bangumi_to_video_info(data: BangumiInfoData, target_ep_id: &str) -> R
             duration: 0, // Duration not provided in bangumi API
             ep_id: Some(current_ep_id),
         });
-
-        index += 1;
     }
 
     let pub_time = if !data.publish.pub_time.is_empty() {
@@ -569,6 +1237,7 @@ fn convert_bangumi_to_video_info(data: BangumiInfoData, target_ep_id: &str) -> R
         pages,
         is_bangumi: true,
         ep_id: ep_id_for_first_page,
+        extra_data: None,
     })
 }
 
@@ -588,10 +1257,7 @@ async fn fetch_cheese_info(
         .map_err(|e| DownloaderError::Parse(format!("Failed to parse cheese info: {}", e)))?;
 
     if api_response.code != 0 {
-        return Err(DownloaderError::Api(format!(
-            "API error: {}",
-            api_response.message
-        )));
+        return Err(api_error(api_response.code, &api_response.message));
     }
 
     let data = api_response
@@ -633,63 +1299,259 @@ fn convert_cheese_to_video_info(data: CheeseInfoData) -> Result<VideoInfo> {
         pages,
         is_bangumi: true, // 课程也算番剧类型
         ep_id: None,
+        extra_data: None,
     })
 }
 
-// 收藏夹信息获取
-pub async fn fetch_favorite_list(
-    client: &Arc<HttpClient>,
-    fav_info: &str,
-    auth: Option<&Auth>,
-) -> Result<Vec<VideoInfo>> {
-    let parts: Vec<&str> = fav_info.split(':').collect();
-    let (fav_id, mid) = if parts.len() == 2 {
-        (parts[0], parts[1])
-    } else {
-        return Err(DownloaderError::Parse(
-            "Invalid favorite list format, expected favId:mid".to_string(),
-        ));
-    };
+/// 批量下载的分页来源（收藏夹、UP主空间、合集、系列）
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchSource {
+    Favorite { fav_id: String },
+    Space { mid: String },
+    MediaList { media_id: String },
+    Series { mid: String, sid: String },
+}
 
-    // 如果 fav_id 为空，查找默认收藏夹
-    let fav_id = if fav_id.is_empty() {
-        let api = format!(
-            "https://api.bilibili.com/x/v3/fav/folder/created/list-all?up_mid={}",
-            mid
-        );
-        let response = client.get_with_auth(&api, auth).await?;
-        let json_text = response.text().await?;
+/// 批量来源的一页视频
+#[derive(Debug, Default)]
+pub struct BatchPage {
+    pub videos: Vec<VideoInfo>,
+    /// 是否还有下一页
+    pub has_more: bool,
+}
+
+impl BatchSource {
+    /// 批量类型的URL对应的分页来源，单个视频返回 `None`
+    ///
+    /// 收藏夹未指定ID时查找用户的默认收藏夹（多一次API请求）
+    pub async fn resolve(
+        client: &dyn HttpTransport,
+        video_type: &VideoType,
+        auth: Option<&Auth>,
+    ) -> Result<Option<Self>> {
+        let source = match video_type {
+            VideoType::FavoriteList(fav_info) => {
+                let (fav_id, mid) = fav_info.split_once(':').ok_or_else(|| {
+                    DownloaderError::Parse(
+                        "Invalid favorite list format, expected favId:mid".to_string(),
+                    )
+                })?;
+                let fav_id = if fav_id.is_empty() {
+                    fetch_default_favorite_id(client, mid, auth).await?
+                } else {
+                    fav_id.to_string()
+                };
+                BatchSource::Favorite { fav_id }
+            }
+            VideoType::SpaceVideo(mid) => BatchSource::Space { mid: mid.clone() },
+            VideoType::MediaList(media_id) => BatchSource::MediaList {
+                media_id: media_id.clone(),
+            },
+            VideoType::SeriesList(series_info) => {
+                let (mid, sid) = series_info.split_once(':').ok_or_else(|| {
+                    DownloaderError::Parse("Invalid series format, expected mid:sid".to_string())
+                })?;
+                BatchSource::Series {
+                    mid: mid.to_string(),
+                    sid: sid.to_string(),
+                }
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(source))
+    }
+}
 
-        #[derive(Deserialize)]
-        struct FavListData {
-            list: Vec<FavItem>,
+/// 获取批量来源的一页视频（页码从1开始），已按 `--since`/`--until` 过滤
+///
+/// UP主空间需要WBI签名；列表按发布时间倒序的来源遇到早于 `since` 的视频时不再有下一页
+pub async fn fetch_batch_page(
+    client: &dyn HttpTransport,
+    source: &BatchSource,
+    page: u32,
+    auth: Option<&Auth>,
+    wbi_manager: Option<&mut super::wbi::WbiManager>,
+    options: &super::ParseOptions,
+) -> Result<BatchPage> {
+    if page > 1 {
+        client.pause().await;
+    }
+
+    let mut batch = match source {
+        BatchSource::Favorite { fav_id } => fetch_favorite_page(client, fav_id, page, auth).await?,
+        BatchSource::Space { mid } => {
+            let wbi = wbi_manager.ok_or_else(|| {
+                DownloaderError::Api("WBI manager required for space video".to_string())
+            })?;
+            fetch_space_page(client, mid, page, auth, wbi, options).await?
         }
-        #[derive(Deserialize)]
-        struct FavItem {
-            id: u64,
+        BatchSource::MediaList { media_id } => fetch_media_list_page(client, media_id, auth).await?,
+        BatchSource::Series { mid, sid } => {
+            fetch_series_page(client, mid, sid, page, auth, options).await?
         }
+    };
 
-        let response: ApiResponse<FavListData> = serde_json::from_str(&json_text)
-            .map_err(|e| DownloaderError::Parse(format!("Failed to parse fav list: {}", e)))?;
+    if options.has_date_range() {
+        let total = batch.videos.len();
+        batch.videos.retain(|v| options.in_date_range(&v.upload_date));
+        tracing::debug!(
+            "Page {}: {} of {} video(s) match the date range",
+            page,
+            batch.videos.len(),
+            total
+        );
+    }
 
-        response
-            .data
-            .and_then(|d| d.list.first().map(|item| item.id.to_string()))
-            .ok_or_else(|| DownloaderError::Parse("No favorite list found".to_string()))?
-    } else {
-        fav_id.to_string()
-    };
+    Ok(batch)
+}
 
-    let page_size = 20;
+/// 逐页获取批量来源的全部视频
+pub async fn fetch_batch(
+    client: &dyn HttpTransport,
+    source: &BatchSource,
+    auth: Option<&Auth>,
+    mut wbi_manager: Option<&mut super::wbi::WbiManager>,
+    options: &super::ParseOptions,
+) -> Result<Vec<VideoInfo>> {
     let mut all_videos = Vec::new();
+    let mut page = 1;
+    loop {
+        let batch = fetch_batch_page(
+            client,
+            source,
+            page,
+            auth,
+            wbi_manager.as_deref_mut(),
+            options,
+        )
+        .await?;
+        all_videos.extend(batch.videos);
+        if !batch.has_more {
+            break;
+        }
+        page += 1;
+    }
+    Ok(all_videos)
+}
+
+/// 页数（向上取整）
+fn page_count(total: u32, page_size: u32) -> u32 {
+    total.div_ceil(page_size)
+}
+
+/// 响应缺少 `data` 或列表：第1页时是错误，之后的页（如翻页期间视频被删除导致超出末尾）视为列表结束
+fn end_of_list(page: u32, message: &str) -> Result<BatchPage> {
+    if page > 1 {
+        tracing::debug!("{} on page {}, treating as end of list", message, page);
+        return Ok(BatchPage::default());
+    }
+    Err(DownloaderError::Parse(message.to_string()))
+}
+
+/// 批量类型URL的全部视频
+async fn fetch_all(
+    client: &dyn HttpTransport,
+    video_type: VideoType,
+    auth: Option<&Auth>,
+    wbi_manager: Option<&mut super::wbi::WbiManager>,
+    options: &super::ParseOptions,
+) -> Result<Vec<VideoInfo>> {
+    let source = BatchSource::resolve(client, &video_type, auth)
+        .await?
+        .ok_or_else(|| DownloaderError::Parse("Not a batch URL".to_string()))?;
+    fetch_batch(client, &source, auth, wbi_manager, options).await
+}
+
+/// 收藏夹的全部视频（`fav_info` 为 `favId:mid`，favId 为空时使用默认收藏夹）
+#[deprecated(note = "use `BatchSource::resolve` with `fetch_batch` or `fetch_batch_page`")]
+pub async fn fetch_favorite_list(
+    client: &Arc<HttpClient>,
+    fav_info: &str,
+    auth: Option<&Auth>,
+) -> Result<Vec<VideoInfo>> {
+    let video_type = VideoType::FavoriteList(fav_info.to_string());
+    fetch_all(client, video_type, auth, None, &super::ParseOptions::default()).await
+}
+
+/// UP主空间的全部视频
+#[deprecated(note = "use `BatchSource::resolve` with `fetch_batch` or `fetch_batch_page`")]
+pub async fn fetch_space_videos(
+    client: &Arc<HttpClient>,
+    mid: &str,
+    auth: Option<&Auth>,
+    wbi_manager: &mut super::wbi::WbiManager,
+    options: &super::ParseOptions,
+) -> Result<Vec<VideoInfo>> {
+    let video_type = VideoType::SpaceVideo(mid.to_string());
+    fetch_all(client, video_type, auth, Some(wbi_manager), options).await
+}
+
+/// 合集的全部视频
+#[deprecated(note = "use `BatchSource::resolve` with `fetch_batch` or `fetch_batch_page`")]
+pub async fn fetch_media_list(
+    client: &Arc<HttpClient>,
+    media_id: &str,
+    auth: Option<&Auth>,
+) -> Result<Vec<VideoInfo>> {
+    let video_type = VideoType::MediaList(media_id.to_string());
+    fetch_all(client, video_type, auth, None, &super::ParseOptions::default()).await
+}
 
-    // 获取第一页以确定总数
+/// 系列的全部视频（`series_info` 为 `mid:sid`）
+#[deprecated(note = "use `BatchSource::resolve` with `fetch_batch` or `fetch_batch_page`")]
+pub async fn fetch_series_list(
+    client: &Arc<HttpClient>,
+    series_info: &str,
+    auth: Option<&Auth>,
+    options: &super::ParseOptions,
+) -> Result<Vec<VideoInfo>> {
+    let video_type = VideoType::SeriesList(series_info.to_string());
+    fetch_all(client, video_type, auth, None, options).await
+}
+
+// 查找用户的默认收藏夹
+async fn fetch_default_favorite_id(
+    client: &dyn HttpTransport,
+    mid: &str,
+    auth: Option<&Auth>,
+) -> Result<String> {
     let api = format!(
-        "https://api.bilibili.com/x/v3/fav/resource/list?media_id={}&pn=1&ps={}&order=mtime&type=2&tid=0&platform=web",
-        fav_id, page_size
+        "https://api.bilibili.com/x/v3/fav/folder/created/list-all?up_mid={}",
+        mid
     );
-    let response = client.get_with_auth(&api, auth).await?;
-    let json_text = response.text().await?;
+    let json_text = client.get_text(&api, auth).await?;
+
+    #[derive(Deserialize)]
+    struct FavListData {
+        list: Vec<FavItem>,
+    }
+    #[derive(Deserialize)]
+    struct FavItem {
+        id: u64,
+    }
+
+    let response: ApiResponse<FavListData> = serde_json::from_str(&json_text)
+        .map_err(|e| DownloaderError::Parse(format!("Failed to parse fav list: {}", e)))?;
+
+    response
+        .data
+        .and_then(|d| d.list.first().map(|item| item.id.to_string()))
+        .ok_or_else(|| DownloaderError::Parse("No favorite list found".to_string()))
+}
+
+// 收藏夹单页获取
+async fn fetch_favorite_page(
+    client: &dyn HttpTransport,
+    fav_id: &str,
+    page: u32,
+    auth: Option<&Auth>,
+) -> Result<BatchPage> {
+    let page_size = 20;
+    let api = format!(
+        "https://api.bilibili.com/x/v3/fav/resource/list?media_id={}&pn={}&ps={}&order=mtime&type=2&tid=0&platform=web",
+        fav_id, page, page_size
+    );
+    let json_text = client.get_text(&api, auth).await?;
 
     tracing::debug!("Favorite list response: {}", json_text);
 
@@ -697,143 +1559,81 @@ pub async fn fetch_favorite_list(
         .map_err(|e| DownloaderError::Parse(format!("Failed to parse favorite list: {}", e)))?;
 
     if api_response.code != 0 {
-        return Err(DownloaderError::Api(format!(
-            "API error: {}",
-            api_response.message
-        )));
+        return Err(api_error(api_response.code, &api_response.message));
     }
 
-    let data = api_response
-        .data
-        .ok_or_else(|| DownloaderError::Parse("No favorite list data".to_string()))?;
-
-    let total_count = data.info.media_count;
-    let total_pages = (total_count as f64 / page_size as f64).ceil() as u32;
-
-    // 处理第一页的媒体
-    if let Some(medias) = data.medias {
-        for media in medias {
-            // 只处理未失效的视频
-            if media.attr != 0 {
-                continue;
-            }
+    let Some(data) = api_response.data else {
+        return end_of_list(page, "No favorite list data");
+    };
 
-            if media.page > 1 {
-                // 多P视频，需要获取详细信息
-                let video_info = fetch_video_info_by_aid(client, &media.id.to_string(), auth).await?;
-                all_videos.push(video_info);
-            } else {
-                // 单P视频
-                let title = media.title.clone();
-                let video_info = VideoInfo {
-                    id: format!("av{}", media.id),
-                    aid: media.id,
-                    title: title.clone(),
-                    description: media.intro,
-                    duration: media.duration,
-                    uploader: media.upper.name,
-                    uploader_mid: media.upper.mid.to_string(),
-                    upload_date: format_timestamp(media.pubtime as u64),
-                    cover_url: media.cover,
-                    pages: vec![Page {
-                        number: 1,
-                        title,
-                        cid: media.ugc.as_ref().map(|u| u.first_cid.to_string()).unwrap_or_default(),
-                        duration: media.duration,
-                        ep_id: None,
-                    }],
-                    is_bangumi: false,
-                    ep_id: None,
-                };
-                all_videos.push(video_info);
-            }
+    let mut videos = Vec::new();
+    for media in data.medias.unwrap_or_default() {
+        // 只处理未失效的视频
+        if media.attr != 0 {
+            continue;
         }
-    }
-
-    // 获取剩余页面
-    for page in 2..=total_pages {
-        let api = format!(
-            "https://api.bilibili.com/x/v3/fav/resource/list?media_id={}&pn={}&ps={}&order=mtime&type=2&tid=0&platform=web",
-            fav_id, page, page_size
-        );
-        let response = client.get_with_auth(&api, auth).await?;
-        let json_text = response.text().await?;
-
-        let api_response: ApiResponse<FavoriteListData> = serde_json::from_str(&json_text)
-            .map_err(|e| DownloaderError::Parse(format!("Failed to parse favorite list: {}", e)))?;
-
-        if let Some(data) = api_response.data {
-            if let Some(medias) = data.medias {
-                for media in medias {
-                    if media.attr != 0 {
-                        continue;
-                    }
 
-                    if media.page > 1 {
-                        let video_info = fetch_video_info_by_aid(client, &media.id.to_string(), auth).await?;
-                        all_videos.push(video_info);
-                    } else {
-                        let title = media.title.clone();
-                        let video_info = VideoInfo {
-                            id: format!("av{}", media.id),
-                            aid: media.id,
-                            title: title.clone(),
-                            description: media.intro,
-                            duration: media.duration,
-                            uploader: media.upper.name,
-                            uploader_mid: media.upper.mid.to_string(),
-                            upload_date: format_timestamp(media.pubtime as u64),
-                            cover_url: media.cover,
-                            pages: vec![Page {
-                                number: 1,
-                                title,
-                                cid: media.ugc.as_ref().map(|u| u.first_cid.to_string()).unwrap_or_default(),
-                                duration: media.duration,
-                                ep_id: None,
-                            }],
-                            is_bangumi: false,
-                            ep_id: None,
-                        };
-                        all_videos.push(video_info);
-                    }
-                }
-            }
+        let first_cid = media.ugc.as_ref().map(|u| u.first_cid);
+        if media.page > 1 && first_cid.is_none() {
+            // 多P视频且缺少P1的cid，需要获取详细信息
+            client.pause().await;
+            let video_info = fetch_video_info_by_aid(client, &media.id.to_string(), auth).await?;
+            videos.push(video_info);
+            continue;
         }
+
+        // 多P视频只先构建占位的P1（没有分P标题和时长），下载前再获取详情
+        let title = media.title.clone();
+        let page_duration = if media.page > 1 { 0 } else { media.duration };
+        videos.push(VideoInfo {
+            id: format!("av{}", media.id),
+            aid: media.id,
+            title: title.clone(),
+            description: media.intro,
+            duration: media.duration,
+            uploader: media.upper.name,
+            uploader_mid: media.upper.mid.to_string(),
+            upload_date: format_timestamp(media.pubtime as u64),
+            cover_url: media.cover,
+            pages: vec![Page {
+                number: 1,
+                title,
+                cid: first_cid.map(|cid| cid.to_string()).unwrap_or_default(),
+                duration: page_duration,
+                ep_id: None,
+            }],
+            is_bangumi: false,
+            ep_id: None,
+            extra_data: (media.page > 1).then(|| {
+                ExtraData::Bilibili(BilibiliExtra {
+                    page_count: media.page as usize,
+                    ..Default::default()
+                })
+            }),
+        });
     }
 
-    Ok(all_videos)
+    Ok(BatchPage {
+        videos,
+        has_more: page < page_count(data.info.media_count, page_size),
+    })
 }
 
-// UP主空间视频获取（需要WBI签名）
-pub async fn fetch_space_videos(
-    client: &Arc<HttpClient>,
+// UP主空间视频单页获取（需要WBI签名）
+async fn fetch_space_page(
+    client: &dyn HttpTransport,
     mid: &str,
+    page: u32,
     auth: Option<&Auth>,
     wbi_manager: &mut super::wbi::WbiManager,
-) -> Result<Vec<VideoInfo>> {
-    // 获取用户信息
-    let user_info_api = format!("https://api.live.bilibili.com/live_user/v1/Master/info?uid={}", mid);
-    let response = client.get(&user_info_api, None).await?;
-    let json_text = response.text().await?;
-
-    let user_response: ApiResponse<UserInfoData> = serde_json::from_str(&json_text)
-        .map_err(|e| DownloaderError::Parse(format!("Failed to parse user info: {}", e)))?;
-
-    let _user_name = user_response
-        .data
-        .map(|d| d.info.uname)
-        .unwrap_or_else(|| format!("User_{}", mid));
-
+    options: &super::ParseOptions,
+) -> Result<BatchPage> {
     let page_size = 50;
-    let mut all_videos = Vec::new();
-
-    // 获取第一页 - 使用WBI签名
-    let base_params = format!("mid={}&order=pubdate&pn=1&ps={}&tid=0", mid, page_size);
+    let base_params = format!("mid={}&order=pubdate&pn={}&ps={}&tid=0", mid, page, page_size);
     let signed_params = wbi_manager.sign_url(&base_params).await?;
     let api = format!("https://api.bilibili.com/x/space/wbi/arc/search?{}", signed_params);
-    
-    let response = client.get_with_auth(&api, auth).await?;
-    let json_text = response.text().await?;
+
+    let json_text = client.get_text(&api, auth).await?;
 
     tracing::debug!("Space video response: {}", json_text);
 
@@ -841,71 +1641,54 @@ pub async fn fetch_space_videos(
         .map_err(|e| DownloaderError::Parse(format!("Failed to parse space videos: {}", e)))?;
 
     if api_response.code != 0 {
-        return Err(DownloaderError::Api(format!(
-            "API error: {}",
-            api_response.message
-        )));
+        return Err(api_error(api_response.code, &api_response.message));
     }
 
-    let data = api_response
-        .data
-        .ok_or_else(|| DownloaderError::Parse("No space video data".to_string()))?;
-
-    let page_info = data.page.ok_or_else(|| {
-        DownloaderError::Parse("No page info in response (may need authentication or WBI signature)".to_string())
-    })?;
-
-    let total_count = page_info.count;
-    let total_pages = (total_count as f64 / page_size as f64).ceil() as u32;
-
-    // 处理第一页的视频
-    if let Some(list) = data.list {
-        for item in list.vlist {
-            // 获取详细视频信息（包括分P信息）
-            let video_info = fetch_video_info_by_aid(client, &item.aid.to_string(), auth).await?;
-            all_videos.push(video_info);
-        }
-    } else {
-        return Err(DownloaderError::Parse("No video list in response".to_string()));
-    }
-
-    // 获取剩余页面
-    for page in 2..=total_pages {
-        let base_params = format!("mid={}&order=pubdate&pn={}&ps={}&tid=0", mid, page, page_size);
-        let signed_params = wbi_manager.sign_url(&base_params).await?;
-        let api = format!("https://api.bilibili.com/x/space/wbi/arc/search?{}", signed_params);
-        
-        let response = client.get_with_auth(&api, auth).await?;
-        let json_text = response.text().await?;
-
-        let api_response: ApiResponse<SpaceVideoData> = serde_json::from_str(&json_text)
-            .map_err(|e| DownloaderError::Parse(format!("Failed to parse space videos: {}", e)))?;
+    let Some(data) = api_response.data else {
+        return end_of_list(page, "No space video data");
+    };
+    let Some(page_info) = data.page else {
+        return end_of_list(page, "No page info in response (may need authentication or WBI signature)");
+    };
+    let Some(list) = data.list else {
+        return end_of_list(page, "No video list in response");
+    };
 
-        if let Some(data) = api_response.data {
-            if let Some(list) = data.list {
-                for item in list.vlist {
-                    let video_info = fetch_video_info_by_aid(client, &item.aid.to_string(), auth).await?;
-                    all_videos.push(video_info);
-                }
+    // 列表按发布时间倒序，遇到早于 --since 的视频即可停止翻页
+    let mut reached_since = false;
+    let mut videos = Vec::new();
+    for item in list.vlist {
+        match check_pubdate(options, item.created) {
+            DateCheck::Keep => {}
+            DateCheck::Skip => continue,
+            DateCheck::Stop => {
+                reached_since = true;
+                break;
             }
         }
+        // 获取详细视频信息（包括分P信息）
+        client.pause().await;
+        let video_info = fetch_video_info_by_aid(client, &item.aid.to_string(), auth).await?;
+        videos.push(video_info);
     }
 
-    Ok(all_videos)
+    Ok(BatchPage {
+        videos,
+        has_more: !reached_since && page < page_count(page_info.count, page_size),
+    })
 }
 
-// 合集视频获取
-pub async fn fetch_media_list(
-    client: &Arc<HttpClient>,
+// 合集视频获取（接口只返回一页）
+async fn fetch_media_list_page(
+    client: &dyn HttpTransport,
     media_id: &str,
     auth: Option<&Auth>,
-) -> Result<Vec<VideoInfo>> {
+) -> Result<BatchPage> {
     let api = format!(
         "https://api.bilibili.com/x/v2/medialist/resource/list?media_id={}&pn=1&ps=20&type=1",
         media_id
     );
-    let response = client.get_with_auth(&api, auth).await?;
-    let json_text = response.text().await?;
+    let json_text = client.get_text(&api, auth).await?;
 
     tracing::debug!("Media list response: {}", json_text);
 
@@ -913,51 +1696,41 @@ pub async fn fetch_media_list(
         .map_err(|e| DownloaderError::Parse(format!("Failed to parse media list: {}", e)))?;
 
     if api_response.code != 0 {
-        return Err(DownloaderError::Api(format!(
-            "API error: {}",
-            api_response.message
-        )));
+        return Err(api_error(api_response.code, &api_response.message));
     }
 
     let data = api_response
         .data
         .ok_or_else(|| DownloaderError::Parse("No media list data".to_string()))?;
 
-    let mut all_videos = Vec::new();
-
+    let mut videos = Vec::new();
     for item in data.medias {
+        client.pause().await;
         let video_info = fetch_video_info_by_bvid(client, &item.bv_id, auth).await?;
-        all_videos.push(video_info);
+        videos.push(video_info);
     }
 
-    Ok(all_videos)
+    Ok(BatchPage {
+        videos,
+        has_more: false,
+    })
 }
 
-// 系列视频获取
-pub async fn fetch_series_list(
-    client: &Arc<HttpClient>,
-    series_info: &str,
+// 系列视频单页获取
+async fn fetch_series_page(
+    client: &dyn HttpTransport,
+    mid: &str,
+    sid: &str,
+    page: u32,
     auth: Option<&Auth>,
-) -> Result<Vec<VideoInfo>> {
-    let parts: Vec<&str> = series_info.split(':').collect();
-    let (mid, sid) = if parts.len() == 2 {
-        (parts[0], parts[1])
-    } else {
-        return Err(DownloaderError::Parse(
-            "Invalid series format, expected mid:sid".to_string(),
-        ));
-    };
-
+    options: &super::ParseOptions,
+) -> Result<BatchPage> {
     let page_size = 30;
-    let mut all_videos = Vec::new();
-
-    // 获取第一页
     let api = format!(
-        "https://api.bilibili.com/x/series/archives?mid={}&series_id={}&pn=1&ps={}",
-        mid, sid, page_size
+        "https://api.bilibili.com/x/series/archives?mid={}&series_id={}&sort=desc&pn={}&ps={}",
+        mid, sid, page, page_size
     );
-    let response = client.get_with_auth(&api, auth).await?;
-    let json_text = response.text().await?;
+    let json_text = client.get_text(&api, auth).await?;
 
     tracing::debug!("Series list response: {}", json_text);
 
@@ -965,46 +1738,34 @@ pub async fn fetch_series_list(
         .map_err(|e| DownloaderError::Parse(format!("Failed to parse series list: {}", e)))?;
 
     if api_response.code != 0 {
-        return Err(DownloaderError::Api(format!(
-            "API error: {}",
-            api_response.message
-        )));
+        return Err(api_error(api_response.code, &api_response.message));
     }
 
-    let data = api_response
-        .data
-        .ok_or_else(|| DownloaderError::Parse("No series list data".to_string()))?;
-
-    let total_count = data.meta.total;
-    let total_pages = (total_count as f64 / page_size as f64).ceil() as u32;
+    let Some(data) = api_response.data else {
+        return end_of_list(page, "No series list data");
+    };
 
-    // 处理第一页的视频
+    // 列表按发布时间倒序，遇到早于 --since 的视频即可停止翻页
+    let mut reached_since = false;
+    let mut videos = Vec::new();
     for item in data.archives {
-        let video_info = fetch_video_info_by_bvid(client, &item.bvid, auth).await?;
-        all_videos.push(video_info);
-    }
-
-    // 获取剩余页面
-    for page in 2..=total_pages {
-        let api = format!(
-            "https://api.bilibili.com/x/series/archives?mid={}&series_id={}&pn={}&ps={}",
-            mid, sid, page, page_size
-        );
-        let response = client.get_with_auth(&api, auth).await?;
-        let json_text = response.text().await?;
-
-        let api_response: ApiResponse<SeriesListData> = serde_json::from_str(&json_text)
-            .map_err(|e| DownloaderError::Parse(format!("Failed to parse series list: {}", e)))?;
-
-        if let Some(data) = api_response.data {
-            for item in data.archives {
-                let video_info = fetch_video_info_by_bvid(client, &item.bvid, auth).await?;
-                all_videos.push(video_info);
+        match check_pubdate(options, item.pubdate) {
+            DateCheck::Keep => {}
+            DateCheck::Skip => continue,
+            DateCheck::Stop => {
+                reached_since = true;
+                break;
             }
         }
+        client.pause().await;
+        let video_info = fetch_video_info_by_bvid(client, &item.bvid, auth).await?;
+        videos.push(video_info);
     }
 
-    Ok(all_videos)
+    Ok(BatchPage {
+        videos,
+        has_more: !reached_since && page < page_count(data.meta.total, page_size),
+    })
 }
 
 // 获取章节信息
@@ -1094,3 +1855,298 @@ pub async fn fetch_chapters(
 
     Ok(chapters)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_bangumi() -> BangumiInfoData {
+        serde_json::from_value(serde_json::json!({
+            "cover": "",
+            "title": "测试番剧",
+            "evaluate": "",
+            "publish": { "pub_time": "2024-01-01 00:00:00" },
+            "episodes": [
+                { "aid": 1, "cid": 11, "id": 101, "title": "1", "long_title": "第一话", "badge": "", "pub_time": 0 },
+                { "aid": 2, "cid": 12, "id": 102, "title": "PV", "long_title": "先导", "badge": "预告", "pub_time": 0 }
+            ],
+            "section": [
+                {
+                    "title": "特别篇",
+                    "episodes": [
+                        { "aid": 3, "cid": 13, "id": 103, "title": "SP", "long_title": "", "badge": "", "pub_time": 0 }
+                    ]
+                }
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_bangumi_extras_skipped_by_default() {
+        let info = convert_bangumi_to_video_info(sample_bangumi(), "", false).unwrap();
+        assert_eq!(info.pages.len(), 1);
+        assert_eq!(info.pages[0].title, "1 第一话");
+    }
+
+    #[test]
+    fn test_bangumi_extras_included() {
+        let info = convert_bangumi_to_video_info(sample_bangumi(), "", true).unwrap();
+        let titles: Vec<&str> = info.pages.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, vec!["1 第一话", "[预告] PV 先导", "[特别篇] SP"]);
+        assert_eq!(info.pages[2].number, 3);
+        assert_eq!(info.pages[2].ep_id.as_deref(), Some("103"));
+    }
+
+    #[test]
+    fn test_quality_probe_locked() {
+        let data: PlayUrlData = serde_json::from_value(serde_json::json!({
+            "accept_quality": [80, 116, 64, 32, 16],
+            "dash": {
+                "video": [
+                    { "id": 64, "base_url": "a", "bandwidth": 1, "codecid": 7 },
+                    { "id": 64, "base_url": "b", "bandwidth": 1, "codecid": 12 },
+                    { "id": 32, "base_url": "c", "bandwidth": 1, "codecid": 7 }
+                ],
+                "audio": []
+            }
+        }))
+        .unwrap();
+
+        let probe = quality_probe_from_data(&data);
+        assert_eq!(probe.listed, vec![116, 80, 64, 32, 16]);
+        assert_eq!(probe.available, vec![64, 32]);
+        assert_eq!(probe.max_available(), Some(64));
+        assert_eq!(probe.locked(), vec![116, 80, 16]);
+    }
+
+    #[test]
+    fn test_fnval_all_requests_every_feature() {
+        assert_eq!(fnval::ALL, 4048);
+        for bit in [fnval::HDR, fnval::FOUR_K, fnval::DOLBY_VISION, fnval::EIGHT_K, fnval::AV1] {
+            assert_ne!(fnval::ALL & bit, 0);
+        }
+    }
+
+    #[test]
+    fn test_missing_top_qualities() {
+        let video = |id: u32| Stream {
+            stream_type: StreamType::Video,
+            quality: get_quality_name(id).to_string(),
+            quality_id: id,
+            codec: "HEVC".to_string(),
+            url: String::new(),
+            backup_urls: Vec::new(),
+            size: 0,
+            bandwidth: 0,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+        };
+        let priority = |qualities: &[&str]| -> Vec<String> {
+            qualities.iter().map(|q| q.to_string()).collect()
+        };
+
+        let streams = vec![video(120), video(80)];
+        assert_eq!(
+            missing_top_qualities(&priority(&["8K", "Dolby Vision", "4K"]), &streams),
+            vec!["8K 超高清", "杜比视界"]
+        );
+        assert!(missing_top_qualities(&priority(&["4K 超清", "1080P"]), &streams).is_empty());
+
+        let streams = vec![video(127), video(126)];
+        assert!(missing_top_qualities(&priority(&["8K 超高清", "杜比视界"]), &streams).is_empty());
+    }
+
+    #[test]
+    fn test_drm_protected_play_url_rejected() {
+        let data: PlayUrlData = serde_json::from_value(serde_json::json!({
+            "is_drm": true,
+            "drm_tech_type": 2,
+            "dash": {
+                "video": [{ "id": 80, "base_url": "a", "bandwidth": 1, "codecid": 7 }],
+                "audio": [{ "id": 30280, "base_url": "b", "bandwidth": 1 }]
+            }
+        }))
+        .unwrap();
+        assert!(matches!(
+            streams_from_play_url_data(data),
+            Err(DownloaderError::DrmProtected(_))
+        ));
+
+        let data: PlayUrlData = serde_json::from_value(serde_json::json!({
+            "dash": {
+                "video": [{ "id": 80, "base_url": "a", "bandwidth": 1, "codecid": 7 }],
+                "audio": [{ "id": 30280, "base_url": "b", "bandwidth": 1 }]
+            }
+        }))
+        .unwrap();
+        assert!(!is_drm_protected(&data));
+        assert_eq!(streams_from_play_url_data(data).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_dash_segment_base() {
+        let data: PlayUrlData = serde_json::from_value(serde_json::json!({
+            "dash": {
+                "video": [{ "id": 80, "base_url": "a", "bandwidth": 1, "codecid": 7,
+                    "segment_base": { "initialization": "0-927", "index_range": "928-1415" } }],
+                // 部分接口使用 SegmentBase 的写法
+                "audio": [{ "id": 30280, "base_url": "b", "bandwidth": 1,
+                    "SegmentBase": { "Initialization": "0-817", "indexRange": "818-1305" } }]
+            }
+        }))
+        .unwrap();
+        let streams = streams_from_play_url_data(data).unwrap();
+
+        let video = streams[0].segment_base.unwrap();
+        assert_eq!(video.initialization, (0, 927));
+        assert_eq!(video.index_range, (928, 1415));
+        assert_eq!(streams[1].segment_base.unwrap().index_range, (818, 1305));
+    }
+
+    #[test]
+    fn test_parse_login_status() {
+        let logged_in = r#"{"code":0,"message":"0","data":{"isLogin":true,"uname":"up"}}"#;
+        assert!(parse_login_status(logged_in).unwrap());
+
+        let logged_out = r#"{"code":-101,"message":"账号未登录","data":{"isLogin":false}}"#;
+        assert!(!parse_login_status(logged_out).unwrap());
+
+        let blocked = r#"{"code":-412,"message":"请求被拦截","data":null}"#;
+        assert!(matches!(
+            parse_login_status(blocked),
+            Err(DownloaderError::RiskControl(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_comment_page() {
+        let first = r#"{"code":0,"message":"0","data":{
+            "cursor":{"is_end":false,"pagination_reply":{"next_offset":"{\"type\":1,\"direction\":1,\"data\":{\"cursor\":2}}"}},
+            "top_replies":[{"rpid":1,"mid":10,"ctime":1700000000,"like":99,"rcount":0,"member":{"uname":"UP主"},"content":{"message":"置顶"}}],
+            "replies":[{"rpid":2,"mid":20,"ctime":1700000100,"like":5,"rcount":4,"member":{"uname":"观众"},"content":{"message":"第一"},
+                "replies":[{"rpid":3,"mid":30,"ctime":1700000200,"like":1,"rcount":0,"member":{"uname":"路人"},"content":{"message":"回复"}}]}]
+        }}"#;
+        let page = parse_comment_page(first).unwrap();
+        assert_eq!(page.comments.len(), 2);
+        assert_eq!(page.comments[0].message, "置顶");
+        assert_eq!(page.comments[1].user, "观众");
+        assert_eq!(page.comments[1].reply_count, 4);
+        assert_eq!(page.comments[1].replies[0].rpid, 3);
+        assert_eq!(
+            page.next_offset.as_deref(),
+            Some(r#"{"type":1,"direction":1,"data":{"cursor":2}}"#)
+        );
+
+        let last = r#"{"code":0,"message":"0","data":{"cursor":{"is_end":true,"pagination_reply":{"next_offset":"x"}},"replies":[]}}"#;
+        let page = parse_comment_page(last).unwrap();
+        assert!(page.comments.is_empty());
+        assert!(page.next_offset.is_none());
+
+        // 评论区关闭
+        let closed = r#"{"code":12002,"message":"评论区已关闭","data":null}"#;
+        assert!(parse_comment_page(closed).unwrap().comments.is_empty());
+        assert!(parse_comment_page(r#"{"code":-412,"message":"请求被拦截","data":null}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_reply_page() {
+        let json = r#"{"code":0,"message":"0","data":{"page":{"num":1,"size":20,"count":2},
+            "replies":[{"rpid":5,"mid":1,"ctime":0,"like":0,"rcount":0,"member":{"uname":"a"},"content":{"message":"x"}},
+                       {"rpid":6,"mid":2,"ctime":0,"like":0,"rcount":0,"member":{"uname":"b"},"content":{"message":"y"}}]}}"#;
+        let (replies, total) = parse_reply_page(json).unwrap();
+        assert_eq!(total, 2);
+        assert_eq!(replies.iter().map(|r| r.rpid).collect::<Vec<_>>(), vec![5, 6]);
+    }
+
+    #[test]
+    fn test_parse_ip_zone() {
+        let mainland = r#"{"code":0,"message":"0","data":{"addr":"1.2.3.4","country":"中国","province":"上海","city":"上海","isp":"电信"}}"#;
+        let zone = parse_ip_zone(mainland).unwrap();
+        assert_eq!(zone.isp, "电信");
+        assert!(!zone.outside_mainland());
+
+        let hong_kong = r#"{"code":0,"message":"0","data":{"addr":"1.2.3.4","country":"中国","province":"香港"}}"#;
+        assert!(parse_ip_zone(hong_kong).unwrap().outside_mainland());
+
+        let overseas = r#"{"code":0,"message":"0","data":{"addr":"1.2.3.4","country":"日本","province":"东京都"}}"#;
+        assert!(parse_ip_zone(overseas).unwrap().outside_mainland());
+
+        assert!(parse_ip_zone(r#"{"code":-412,"message":"请求被拦截","data":null}"#).is_err());
+    }
+
+    #[test]
+    fn test_members_only_detection() {
+        let data: VideoInfoData = serde_json::from_value(serde_json::json!({
+            "aid": 1, "bvid": "BV1xx411c7mQ", "cid": 2, "title": "t", "desc": "", "pic": "",
+            "pubdate": 0, "owner": { "mid": 3, "name": "up" }, "pages": [],
+            "is_upower_exclusive": true
+        }))
+        .unwrap();
+        let video = convert_to_video_info(data).unwrap();
+        assert!(video.bilibili_extra().unwrap().members_only);
+
+        assert!(matches!(
+            check_members_only(&video, None),
+            Err(DownloaderError::MembersOnly(_))
+        ));
+        let auth = Auth {
+            cookie: Some("SESSDATA=x".to_string()),
+            access_token: None,
+        };
+        assert!(check_members_only(&video, Some(&auth)).is_ok());
+
+        let err = members_only_error(87008, "充电专属视频", Some(&auth)).unwrap();
+        assert!(err.to_string().contains("not a member"));
+        assert!(members_only_error(-404, "啥都木有", None).is_none());
+    }
+
+    #[test]
+    fn test_parse_tags_response() {
+        let json = r#"{"code":0,"message":"0","data":[
+            {"tag_id":1,"tag_name":"单机游戏"},
+            {"tag_id":2,"tag_name":""},
+            {"tag_id":3,"tag_name":"实况"}
+        ]}"#;
+        assert_eq!(parse_tags_response(json).unwrap(), vec!["单机游戏", "实况"]);
+
+        let json = r#"{"code":0,"message":"0","data":null}"#;
+        assert!(parse_tags_response(json).unwrap().is_empty());
+
+        let json = r#"{"code":-404,"message":"啥都木有","data":null}"#;
+        assert!(matches!(
+            parse_tags_response(json),
+            Err(DownloaderError::VideoNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        // 2024-01-01 00:00:00 UTC
+        assert_eq!(format_timestamp(1704067200), "2024-01-01");
+        // 2023-12-31 16:00:00 UTC 为北京时间 2024-01-01 00:00:00
+        assert_eq!(format_timestamp(1704038400), "2024-01-01");
+        assert_eq!(format_timestamp(1704038399), "2023-12-31");
+        assert_eq!(format_timestamp(951782400), "2000-02-29");
+        assert_eq!(format_timestamp(0), "1970-01-01");
+    }
+
+    #[test]
+    fn test_check_pubdate() {
+        let options = super::super::ParseOptions {
+            since: Some("2024-01-01".to_string()),
+            until: Some("2024-06-30".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(check_pubdate(&options, 1704067200), DateCheck::Keep);
+        assert_eq!(check_pubdate(&options, 1704038399), DateCheck::Stop);
+        // 2024-07-01 12:00:00 UTC
+        assert_eq!(check_pubdate(&options, 1719835200), DateCheck::Skip);
+        assert_eq!(
+            check_pubdate(&super::super::ParseOptions::default(), 0),
+            DateCheck::Keep
+        );
+    }
+}
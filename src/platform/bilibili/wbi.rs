@@ -104,6 +104,21 @@ impl WbiManager {
     }
 }
 
+/// 编码参与签名的参数值：去掉 `!'()*` 后按 encodeURIComponent 规则编码（十六进制大写）
+///
+/// 调用方需按参数名排序拼接（`wts` 由 [`WbiManager::sign_url`] 追加在最后）
+pub fn encode_param(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes().filter(|b| !b"!'()*".contains(b)) {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
 /// 从URL中提取文件名（去掉路径和扩展名）
 fn extract_filename(url: &str) -> String {
     // 找到最后一个 '/' 后的内容
@@ -155,6 +170,14 @@ fn get_mixin_key(orig: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_encode_param() {
+        assert_eq!(encode_param("abc-_.~123"), "abc-_.~123");
+        assert_eq!(encode_param(r#"{"offset":""}"#), "%7B%22offset%22%3A%22%22%7D");
+        assert_eq!(encode_param("a b(c)!*'"), "a%20bc");
+        assert_eq!(encode_param("评论"), "%E8%AF%84%E8%AE%BA");
+    }
+
     #[test]
     fn test_extract_filename() {
         let url = "https://i0.hdslb.com/bfs/wbi/7cd084941338484aae1ad9425b84077c.png";
@@ -1,4 +1,5 @@
 pub mod bilibili;
+pub mod selector;
 pub mod r#trait;
 
 pub use r#trait::Platform;
@@ -0,0 +1,427 @@
+use crate::error::{DownloaderError, Result};
+use crate::types::{FormatSort, SortField, Stream, StreamPreferences, StreamType};
+use std::cmp::Ordering;
+
+/// 与平台无关的音视频流选择器
+///
+/// 视频流先按 `--fps` 筛选，再按排序表达式逐键打分比较（未指定 `--format-sort` 时
+/// 由清晰度/编码优先级转换为 `quality, codec, fps, br`）；所有键都相同时保留靠前的流
+/// （平台按清晰度从高到低返回）。音频流先按语言筛选（默认原声），再取码率最高的一条
+#[derive(Debug, Clone)]
+pub struct StreamSelector {
+    format_sort: FormatSort,
+    fps: Option<u32>,
+    audio_language: Option<String>,
+}
+
+impl Default for StreamSelector {
+    fn default() -> Self {
+        Self::new(&StreamPreferences::default())
+    }
+}
+
+impl StreamSelector {
+    pub fn new(preferences: &StreamPreferences) -> Self {
+        Self {
+            format_sort: preferences.effective_format_sort(),
+            fps: preferences.fps,
+            audio_language: preferences.audio_language.clone(),
+        }
+    }
+
+    /// 实际使用的排序表达式
+    pub fn format_sort(&self) -> &FormatSort {
+        &self.format_sort
+    }
+
+    /// 选出最佳的视频流和音频流
+    pub fn select(&self, streams: &[Stream]) -> Result<(Stream, Stream)> {
+        let video_streams: Vec<&Stream> = streams
+            .iter()
+            .filter(|s| s.stream_type == StreamType::Video)
+            .collect();
+
+        let audio_streams: Vec<&Stream> = streams
+            .iter()
+            .filter(|s| s.stream_type == StreamType::Audio)
+            .collect();
+
+        if video_streams.is_empty() {
+            return Err(DownloaderError::DownloadFailed(
+                "No video streams available".to_string(),
+            ));
+        }
+
+        if audio_streams.is_empty() {
+            return Err(DownloaderError::DownloadFailed(
+                "No audio streams available".to_string(),
+            ));
+        }
+
+        let best_video = self.best_video(video_streams)?;
+        let best_audio = self.best_audio(audio_streams)?;
+
+        Ok((best_video.clone(), best_audio.clone()))
+    }
+
+    /// 视频流在各排序键上的得分，按排序表达式的顺序排列
+    ///
+    /// 得分越大越好（`+` 前缀的键已取反），两条流的得分按字典序比较
+    pub fn score(&self, stream: &Stream) -> Vec<i64> {
+        self.format_sort
+            .keys
+            .iter()
+            .map(|key| {
+                let value = sort_value(stream, &key.field);
+                if key.ascending {
+                    -value
+                } else {
+                    value
+                }
+            })
+            .collect()
+    }
+
+    /// 比较两条视频流，Greater 表示 a 更优
+    pub fn compare(&self, a: &Stream, b: &Stream) -> Ordering {
+        compare_streams(a, b, &self.format_sort)
+    }
+
+    /// 将视频流按从优到劣排序（不做帧率筛选），得分相同时保持原有顺序
+    pub fn rank<'a>(&self, streams: &'a [Stream]) -> Vec<&'a Stream> {
+        let mut ranked: Vec<&Stream> = streams
+            .iter()
+            .filter(|s| s.stream_type == StreamType::Video)
+            .collect();
+        ranked.sort_by(|a, b| self.compare(b, a));
+        ranked
+    }
+
+    fn best_video<'a>(&self, video_streams: Vec<&'a Stream>) -> Result<&'a Stream> {
+        let video_streams = filter_fps(video_streams, self.fps);
+
+        // 相同排名时保留靠前的流（API按清晰度从高到低返回）
+        let mut best: Option<&Stream> = None;
+        for stream in video_streams {
+            if best.is_none_or(|b| self.compare(stream, b) == Ordering::Greater) {
+                best = Some(stream);
+            }
+        }
+        let best = best
+            .ok_or_else(|| DownloaderError::DownloadFailed("No video stream found".to_string()))?;
+
+        tracing::info!(
+            "Selected video: {} {} ({}kbps)",
+            best.quality,
+            best.codec,
+            best.bandwidth / 1000
+        );
+
+        Ok(best)
+    }
+
+    fn best_audio<'a>(&self, audio_streams: Vec<&'a Stream>) -> Result<&'a Stream> {
+        let audio_streams = filter_audio_language(audio_streams, self.audio_language.as_deref());
+
+        // Select highest bandwidth audio
+        let best = audio_streams
+            .into_iter()
+            .max_by_key(|s| s.bandwidth)
+            .ok_or_else(|| DownloaderError::DownloadFailed("No audio stream found".to_string()))?;
+
+        tracing::info!(
+            "Selected audio: {} ({}kbps)",
+            best.codec,
+            best.bandwidth / 1000
+        );
+
+        Ok(best)
+    }
+
+    /// `--audio-lang all` 时主音轨之外需要一并嵌入的音轨：其他每种语言中码率最高的一条
+    ///
+    /// 未指定 `all` 时返回空列表
+    pub fn extra_audio(&self, streams: &[Stream], primary: &Stream) -> Vec<Stream> {
+        if !self
+            .audio_language
+            .as_deref()
+            .is_some_and(|language| language.eq_ignore_ascii_case("all"))
+        {
+            return Vec::new();
+        }
+
+        let audio_streams: Vec<&Stream> = streams
+            .iter()
+            .filter(|s| s.stream_type == StreamType::Audio)
+            .collect();
+        audio_languages(audio_streams.iter().copied())
+            .into_iter()
+            .filter(|language| primary.language.as_ref() != Some(language))
+            .filter_map(|language| {
+                audio_streams
+                    .iter()
+                    .filter(|s| s.language.as_ref() == Some(&language))
+                    .max_by_key(|s| s.bandwidth)
+                    .map(|s| (*s).clone())
+            })
+            .collect()
+    }
+}
+
+/// 按偏好选出最佳的视频流和音频流，等同于 `StreamSelector::new(preferences).select(streams)`
+pub fn select_best_streams(
+    streams: &[Stream],
+    preferences: &StreamPreferences,
+) -> Result<(Stream, Stream)> {
+    StreamSelector::new(preferences).select(streams)
+}
+
+/// 按排序表达式比较两个视频流，Greater 表示 a 更优
+pub fn compare_streams(a: &Stream, b: &Stream, format_sort: &FormatSort) -> Ordering {
+    for key in &format_sort.keys {
+        let ordering = sort_value(a, &key.field).cmp(&sort_value(b, &key.field));
+        let ordering = if key.ascending {
+            ordering.reverse()
+        } else {
+            ordering
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+fn sort_value(stream: &Stream, field: &SortField) -> i64 {
+    match field {
+        SortField::Res => {
+            if stream.height > 0 {
+                stream.height as i64
+            } else {
+                height_for_quality(stream.quality_id) as i64
+            }
+        }
+        SortField::Fps => (stream_fps(stream) * 1000.0) as i64,
+        SortField::Codec(preferred) => {
+            let codec = stream.codec.to_lowercase();
+            match preferred
+                .iter()
+                .position(|p| codec.contains(&p.to_lowercase()))
+            {
+                // 列出的编码排在默认顺序之前
+                Some(index) => 100 - index as i64,
+                None if codec.contains("av1") => 3,
+                None if codec.contains("hevc") => 2,
+                None if codec.contains("avc") => 1,
+                None => 0,
+            }
+        }
+        SortField::Br => stream.bandwidth as i64,
+        SortField::Size => stream.size as i64,
+        SortField::Quality(preferred) => preferred
+            .iter()
+            .position(|p| stream.quality.contains(p.as_str()))
+            .map_or(0, |index| 100 - index as i64),
+    }
+}
+
+/// 视频帧率，未提供时按清晰度ID估算（116/74 为 60 帧）
+pub fn stream_fps(stream: &Stream) -> f64 {
+    if stream.fps > 0.0 {
+        stream.fps
+    } else if matches!(stream.quality_id, 116 | 74) {
+        60.0
+    } else {
+        30.0
+    }
+}
+
+/// 非交互输出中对单个流的简短描述：清晰度、编码、（视频）帧率和码率
+pub fn describe_stream(stream: &Stream) -> String {
+    match stream.stream_type {
+        StreamType::Video => format!(
+            "{} {} {:.0}fps {}kbps",
+            stream.quality,
+            stream.codec,
+            stream_fps(stream),
+            stream.bandwidth / 1000
+        ),
+        StreamType::Audio => {
+            format!("{} {} {}kbps", stream.quality, stream.codec, stream.bandwidth / 1000)
+        }
+    }
+}
+
+/// 整理解析出的流：去重并按固定顺序排列，保证选流、交互菜单和流列表结果稳定
+///
+/// 同一 (类型, 清晰度ID, 编码, 音轨语言) 只保留码率最高的一条（DASH 与备用数组
+/// 常有重复）；视频按清晰度、帧率从高到低，音频按码率从高到低，视频在前
+pub fn normalize_streams(streams: Vec<Stream>) -> Vec<Stream> {
+    let mut unique: Vec<Stream> = Vec::with_capacity(streams.len());
+    for stream in streams {
+        let existing = unique.iter_mut().find(|s| {
+            s.stream_type == stream.stream_type
+                && s.quality_id == stream.quality_id
+                && s.codec == stream.codec
+                && s.language == stream.language
+        });
+        match existing {
+            Some(existing) if stream.bandwidth > existing.bandwidth => *existing = stream,
+            Some(_) => {}
+            None => unique.push(stream),
+        }
+    }
+
+    let (mut video, mut audio): (Vec<Stream>, Vec<Stream>) = unique
+        .into_iter()
+        .partition(|s| s.stream_type == StreamType::Video);
+    video.sort_by(|a, b| {
+        b.quality_id
+            .cmp(&a.quality_id)
+            .then_with(|| stream_fps(b).total_cmp(&stream_fps(a)))
+    });
+    audio.sort_by_key(|s| std::cmp::Reverse(s.bandwidth));
+
+    video.extend(audio);
+    video
+}
+
+/// 按帧率筛选视频流（四舍五入比较）；没有匹配时保留全部视频流
+fn filter_fps(video_streams: Vec<&Stream>, fps: Option<u32>) -> Vec<&Stream> {
+    let Some(fps) = fps else {
+        return video_streams;
+    };
+
+    let matched: Vec<&Stream> = video_streams
+        .iter()
+        .copied()
+        .filter(|s| stream_fps(s).round() as u32 == fps)
+        .collect();
+
+    if matched.is_empty() {
+        tracing::warn!("No {}fps video stream available, ignoring --fps", fps);
+        return video_streams;
+    }
+
+    matched
+}
+
+/// 未提供分辨率信息时按清晰度ID估算高度
+fn height_for_quality(quality_id: u32) -> u32 {
+    match quality_id {
+        127 => 4320,
+        120 | 125 | 126 => 2160,
+        80 | 112 | 116 => 1080,
+        64 | 74 => 720,
+        32 => 480,
+        16 => 360,
+        _ => 0,
+    }
+}
+
+/// 流的预估大小（字节）：优先使用接口返回的大小，否则按 码率 × 时长 估算
+pub fn estimated_size(stream: &Stream, duration: u64) -> u64 {
+    if stream.size > 0 {
+        stream.size
+    } else {
+        stream.bandwidth * duration / 8
+    }
+}
+
+/// 生成流信息表格（含表头），视频流按清晰度从高到低，音频流按码率从高到低
+///
+/// 清晰度名称含中文，放在最后一列以免影响对齐
+pub fn stream_table(streams: &[Stream], duration: u64) -> Vec<String> {
+    let mut video: Vec<&Stream> = streams
+        .iter()
+        .filter(|s| s.stream_type == StreamType::Video)
+        .collect();
+    video.sort_by(|a, b| {
+        b.quality_id
+            .cmp(&a.quality_id)
+            .then_with(|| b.bandwidth.cmp(&a.bandwidth))
+    });
+    let mut audio: Vec<&Stream> = streams
+        .iter()
+        .filter(|s| s.stream_type == StreamType::Audio)
+        .collect();
+    audio.sort_by_key(|s| std::cmp::Reverse(s.bandwidth));
+
+    let mut rows = vec![format!(
+        "{:<6} {:<16} {:>5} {:>10} {:>10}  {}",
+        "TYPE", "CODEC", "FPS", "BITRATE", "EST. SIZE", "QUALITY"
+    )];
+    for stream in video.into_iter().chain(audio) {
+        let (kind, fps) = match stream.stream_type {
+            StreamType::Video => ("video", format!("{:.0}", stream_fps(stream))),
+            StreamType::Audio => ("audio", "-".to_string()),
+        };
+        let size = estimated_size(stream, duration);
+        let quality = match stream.language {
+            Some(ref language) => format!("{} [{}]", stream.quality, language),
+            None => stream.quality.clone(),
+        };
+        rows.push(format!(
+            "{:<6} {:<16} {:>5} {:>10} {:>10}  {}",
+            kind,
+            stream.codec,
+            fps,
+            format!("{}kbps", stream.bandwidth / 1000),
+            crate::utils::file::format_size(size),
+            quality
+        ));
+    }
+
+    rows
+}
+
+/// 列出多语言音轨的语言代码（不含原声），按出现顺序去重
+pub fn audio_languages<'a>(streams: impl IntoIterator<Item = &'a Stream>) -> Vec<String> {
+    let mut languages: Vec<String> = Vec::new();
+    for stream in streams {
+        if let Some(ref language) = stream.language {
+            if !languages.contains(language) {
+                languages.push(language.clone());
+            }
+        }
+    }
+    languages
+}
+
+/// 按语言筛选音频流，"original" 表示原声，"all" 同未指定；没有匹配时保留全部音频流
+fn filter_audio_language<'a>(audio_streams: Vec<&'a Stream>, language: Option<&str>) -> Vec<&'a Stream> {
+    // `all` 时主音轨仍为原声，其他语言见 `StreamSelector::extra_audio`
+    let Some(language) = language.filter(|l| !l.eq_ignore_ascii_case("all")) else {
+        // 未指定语言时默认使用原声（如果存在）
+        let original: Vec<&Stream> = audio_streams
+            .iter()
+            .copied()
+            .filter(|s| s.language.is_none())
+            .collect();
+        return if original.is_empty() { audio_streams } else { original };
+    };
+
+    let matched: Vec<&Stream> = audio_streams
+        .iter()
+        .copied()
+        .filter(|s| match s.language {
+            Some(ref lang) => lang.eq_ignore_ascii_case(language),
+            None => language.eq_ignore_ascii_case("original"),
+        })
+        .collect();
+
+    if matched.is_empty() {
+        let available = audio_languages(audio_streams.iter().copied());
+        tracing::warn!(
+            "Audio language '{}' not available (available: original{}), using default audio",
+            language,
+            available
+                .iter()
+                .map(|l| format!(", {}", l))
+                .collect::<String>()
+        );
+        return filter_audio_language(audio_streams, None);
+    }
+
+    matched
+}
@@ -1,5 +1,6 @@
 use crate::error::Result;
-use crate::types::{Auth, Stream, Subtitle, VideoInfo};
+use crate::platform::selector::{estimated_size, StreamSelector};
+use crate::types::{Auth, Page, Stream, StreamPreferences, Subtitle, VideoInfo};
 use async_trait::async_trait;
 
 /// Platform trait defines the interface that all video platform implementations must follow.
@@ -50,10 +51,47 @@ pub trait Platform: Send + Sync {
     /// # Arguments
     /// * `video_id` - The video identifier
     /// * `cid` - The content identifier
+    /// * `auth` - Optional authentication information (some subtitles are only
+    ///   returned to logged-in users)
     ///
     /// # Returns
     /// A vector of available subtitles
-    async fn get_subtitles(&self, video_id: &str, cid: &str) -> Result<Vec<Subtitle>>;
+    async fn get_subtitles(
+        &self,
+        video_id: &str,
+        cid: &str,
+        auth: Option<&Auth>,
+    ) -> Result<Vec<Subtitle>>;
+
+    /// Estimate the total download size of the given pages.
+    ///
+    /// # Arguments
+    /// * `video_info` - The video information
+    /// * `pages` - The pages that would be downloaded
+    /// * `preferences` - Preferences used to select each page's video and audio stream
+    /// * `auth` - Optional authentication information
+    ///
+    /// # Returns
+    /// The estimated size in bytes. The default fetches each page's streams and sums the
+    /// selected streams' reported size, or bandwidth × page duration when no size is known
+    async fn estimate_size(
+        &self,
+        video_info: &VideoInfo,
+        pages: &[Page],
+        preferences: &StreamPreferences,
+        auth: Option<&Auth>,
+    ) -> Result<u64> {
+        let selector = StreamSelector::new(preferences);
+        let mut total = 0;
+        for page in pages {
+            let streams = self
+                .get_streams(&video_info.aid.to_string(), &page.cid, auth)
+                .await?;
+            let (video, audio) = selector.select(&streams)?;
+            total += estimated_size(&video, page.duration) + estimated_size(&audio, page.duration);
+        }
+        Ok(total)
+    }
 
     /// Get the cover image URL for a video.
     ///
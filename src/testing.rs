@@ -0,0 +1,63 @@
+// 测试辅助：无需网络即可驱动解析器的模拟传输层
+//
+// 仅在启用 `testing` 特性时编译。目前只有视频信息和批量来源（收藏夹、空间、合集等）的
+// 解析经过 `HttpTransport`；播放地址、字幕和弹幕仍直接使用 `HttpClient`，需要用 mockito 等
+// 本地服务器测试
+
+use crate::error::{DownloaderError, Result};
+use crate::types::Auth;
+use crate::utils::http::HttpTransport;
+use async_trait::async_trait;
+use std::sync::Mutex;
+
+/// 按URL片段返回预置JSON的 [`HttpTransport`] 实现
+///
+/// 路由按添加顺序匹配，第一个被URL包含的片段生效；未匹配的请求返回错误
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    routes: Vec<(String, String)>,
+    requests: Mutex<Vec<String>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// URL包含 `pattern` 时返回 `body`
+    pub fn with_response(mut self, pattern: &str, body: &str) -> Self {
+        self.routes.push((pattern.to_string(), body.to_string()));
+        self
+    }
+
+    /// 返回 `{"code": code, "message": message}` 形式的API错误
+    pub fn with_api_error(self, pattern: &str, code: i32, message: &str) -> Self {
+        let body = serde_json::json!({ "code": code, "message": message }).to_string();
+        self.with_response(pattern, &body)
+    }
+
+    /// 已发出的请求URL（按请求顺序）
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn respond(&self, url: &str) -> Result<String> {
+        self.requests.lock().unwrap().push(url.to_string());
+        self.routes
+            .iter()
+            .find(|(pattern, _)| url.contains(pattern.as_str()))
+            .map(|(_, body)| body.clone())
+            .ok_or_else(|| DownloaderError::Api(format!("No mock response for {}", url)))
+    }
+}
+
+#[async_trait]
+impl HttpTransport for MockTransport {
+    async fn get_text(&self, url: &str, _auth: Option<&Auth>) -> Result<String> {
+        self.respond(url)
+    }
+
+    async fn post_text(&self, url: &str, _body: &str) -> Result<String> {
+        self.respond(url)
+    }
+}
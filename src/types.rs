@@ -16,6 +16,113 @@ pub struct VideoInfo {
     pub is_bangumi: bool, // 是否是番剧/课程
     #[serde(default)]
     pub ep_id: Option<String>, // 番剧的ep_id（如果是番剧）
+    #[serde(default)]
+    pub extra_data: Option<ExtraData>, // 平台特有的附加元数据
+}
+
+/// 平台特有的附加元数据
+///
+/// 通用的 `VideoInfo` 只保留各平台共有的字段，其余信息按平台放在这里，
+/// 通过 `VideoInfo::bilibili_extra()` 等方法以强类型方式访问。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "platform", rename_all = "lowercase")]
+pub enum ExtraData {
+    Bilibili(BilibiliExtra),
+}
+
+/// Bilibili 视频的附加元数据
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BilibiliExtra {
+    pub tid: u32,          // 分区ID
+    pub tname: String,     // 分区名称
+    pub copyright: u32,    // 1: 自制, 2: 转载
+    pub stat: BilibiliStat,
+    #[serde(default)]
+    pub tags: Vec<String>, // 视频标签（仅在需要时获取）
+    #[serde(default)]
+    pub members_only: bool, // 充电专属视频
+    #[serde(default)]
+    pub live_replay: bool, // 直播回放（id 为回放的 rid）
+    #[serde(default)]
+    pub page_count: usize, // 分P总数（仅收藏夹中未获取详情的多P视频，此时 pages 只有P1）
+}
+
+/// Bilibili 视频的统计数据
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BilibiliStat {
+    pub view: u64,
+    pub danmaku: u64,
+    pub reply: u64,
+    pub favorite: u64,
+    pub coin: u64,
+    pub share: u64,
+    pub like: u64,
+}
+
+impl VideoInfo {
+    /// 通过ep链接解析番剧时，URL中指定的ep_id（ss链接返回None）
+    pub fn target_ep_id(&self) -> Option<&str> {
+        if !self.is_bangumi {
+            return None;
+        }
+        self.id
+            .strip_prefix("bangumi_")
+            .filter(|ep_id| !ep_id.is_empty())
+    }
+
+    /// 获取Bilibili特有的附加元数据
+    pub fn bilibili_extra(&self) -> Option<&BilibiliExtra> {
+        match self.extra_data.as_ref()? {
+            ExtraData::Bilibili(extra) => Some(extra),
+        }
+    }
+
+    /// 是否是直播回放
+    pub fn is_live_replay(&self) -> bool {
+        self.bilibili_extra().is_some_and(|extra| extra.live_replay)
+    }
+
+    /// 分P总数，包括尚未获取详情的分P
+    pub fn page_count(&self) -> usize {
+        self.bilibili_extra()
+            .map_or(0, |extra| extra.page_count)
+            .max(self.pages.len())
+    }
+
+    /// 是否为多P视频（按分P总数判断，只带P1的收藏夹多P视频也算）
+    pub fn is_multi_page(&self) -> bool {
+        self.page_count() > 1
+    }
+
+    /// 是否只包含部分分P（收藏夹中的多P视频在需要时才获取完整详情）
+    pub fn pages_incomplete(&self) -> bool {
+        self.page_count() > self.pages.len()
+    }
+
+    /// 查找ep_id对应的分P
+    pub fn page_for_ep(&self, ep_id: &str) -> Option<&Page> {
+        self.pages
+            .iter()
+            .find(|page| page.ep_id.as_deref() == Some(ep_id))
+    }
+
+    /// 能重新解析出该视频的链接：直播回放为回放页，番剧为ep页（ss链接解析的番剧
+    /// 使用第一集），其他为视频页
+    pub fn url(&self) -> String {
+        if self.is_live_replay() {
+            return format!("https://live.bilibili.com/record/{}", self.id);
+        }
+        if self.is_bangumi {
+            let ep_id = self
+                .target_ep_id()
+                .or(self.ep_id.as_deref())
+                .or_else(|| self.pages.iter().find_map(|page| page.ep_id.as_deref()));
+            if let Some(ep_id) = ep_id {
+                return format!("https://www.bilibili.com/bangumi/play/ep{}", ep_id);
+            }
+        }
+        format!("https://www.bilibili.com/video/{}", self.id)
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,8 +142,27 @@ pub struct Stream {
     pub quality_id: u32,
     pub codec: String,
     pub url: String,
+    #[serde(default)]
+    pub backup_urls: Vec<String>, // 其他CDN节点的备用地址，主地址失败时使用
     pub size: u64,
     pub bandwidth: u64,
+    #[serde(default)]
+    pub language: Option<String>, // 音轨语言代码（仅多语言音轨，None为原声）
+    #[serde(default)]
+    pub height: u32, // 视频高度（未知时为0）
+    #[serde(default)]
+    pub fps: f64, // 视频帧率（未知时为0）
+    #[serde(default)]
+    pub segment_base: Option<SegmentBase>, // DASH 分段信息（仅DASH流，用于 `--clip`）
+}
+
+/// DASH 流的分段信息，均为文件中的字节范围（含首尾）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentBase {
+    /// 初始化段（ftyp + moov）
+    pub initialization: (u64, u64),
+    /// 片段索引（sidx）
+    pub index_range: (u64, u64),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -71,6 +197,9 @@ impl From<&crate::utils::config::AuthConfig> for Auth {
 pub struct StreamPreferences {
     pub quality_priority: Vec<String>,
     pub codec_priority: Vec<String>,
+    pub audio_language: Option<String>, // 音轨语言代码，"original"表示原声
+    pub format_sort: Option<FormatSort>, // 设置后代替清晰度/编码优先级排序视频流
+    pub fps: Option<u32>, // 只选择该帧率的视频流
 }
 
 impl Default for StreamPreferences {
@@ -78,7 +207,111 @@ impl Default for StreamPreferences {
         Self {
             quality_priority: vec!["1080P".to_string(), "720P".to_string(), "480P".to_string()],
             codec_priority: vec!["avc".to_string(), "hevc".to_string(), "av1".to_string()],
+            audio_language: None,
+            format_sort: None,
+            fps: None,
+        }
+    }
+}
+
+impl StreamPreferences {
+    /// 实际用于排序的表达式：`--format-sort` 优先，否则由清晰度/编码优先级转换而来
+    pub fn effective_format_sort(&self) -> FormatSort {
+        self.format_sort.clone().unwrap_or_else(|| {
+            FormatSort::from_priorities(&self.quality_priority, &self.codec_priority)
+        })
+    }
+}
+
+/// 视频流排序表达式（类似 yt-dlp 的 `-S`），如 `res,fps,codec:hevc,br`
+///
+/// 按顺序比较各个键，默认值越大越好，键名前加 `+` 表示越小越好（如 `+size`）。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FormatSort {
+    pub keys: Vec<SortKey>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub ascending: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SortField {
+    /// 分辨率（高度）
+    Res,
+    /// 帧率
+    Fps,
+    /// 编码，列出的编码按顺序优先，其余按 av1 > hevc > avc
+    Codec(Vec<String>),
+    /// 码率
+    Br,
+    /// 文件大小
+    Size,
+    /// 清晰度名称，列出的清晰度按顺序优先
+    Quality(Vec<String>),
+}
+
+impl FormatSort {
+    /// 将 `--quality`/`--codec` 优先级列表转换为排序表达式
+    pub fn from_priorities(quality_priority: &[String], codec_priority: &[String]) -> Self {
+        let key = |field| SortKey {
+            field,
+            ascending: false,
+        };
+        Self {
+            keys: vec![
+                key(SortField::Quality(quality_priority.to_vec())),
+                key(SortField::Codec(codec_priority.to_vec())),
+                key(SortField::Fps),
+                key(SortField::Br),
+            ],
+        }
+    }
+}
+
+impl std::str::FromStr for FormatSort {
+    type Err = String;
+
+    fn from_str(expr: &str) -> Result<Self, Self::Err> {
+        let mut keys = Vec::new();
+        for token in expr.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let (ascending, token) = match token.strip_prefix('+') {
+                Some(rest) => (true, rest),
+                None => (false, token),
+            };
+            let (name, value) = match token.split_once(':') {
+                Some((name, value)) => (name, Some(value)),
+                None => (token, None),
+            };
+            let values = || {
+                value
+                    .map(|v| v.split('/').map(|s| s.trim().to_string()).collect())
+                    .unwrap_or_default()
+            };
+
+            let field = match name.to_lowercase().as_str() {
+                "res" => SortField::Res,
+                "fps" => SortField::Fps,
+                "codec" => SortField::Codec(values()),
+                "br" => SortField::Br,
+                "size" => SortField::Size,
+                "quality" => SortField::Quality(values()),
+                _ => {
+                    return Err(format!(
+                        "unknown sort key '{}' (available: res, fps, codec, br, size, quality)",
+                        name
+                    ))
+                }
+            };
+            keys.push(SortKey { field, ascending });
+        }
+
+        if keys.is_empty() {
+            return Err("format sort expression is empty".to_string());
         }
+        Ok(Self { keys })
     }
 }
 
@@ -0,0 +1,113 @@
+//! 解析结果的磁盘缓存
+//!
+//! 以URL和登录身份为键把解析得到的 `VideoInfo` 保存为JSON，在有效期内重复运行
+//! （如 `--info-only` 后调整清晰度参数再下载）时直接复用，减少API请求。
+//! 登录前后可见的分P和内容不同，因此不同身份的结果分开缓存。
+//! 播放地址有时效，不在缓存范围内。
+
+use crate::types::{Auth, VideoInfo};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    url: String,
+    /// 解析时使用的登录身份（见 [`auth_identity`]）
+    identity: String,
+    /// 写入时间（Unix秒）
+    cached_at: u64,
+    video: VideoInfo,
+}
+
+/// 登录身份在缓存键中的表示：未登录为 `anonymous`，否则为认证信息的哈希（不保存凭据本身）
+pub fn auth_identity(auth: Option<&Auth>) -> String {
+    let credentials = auth.map(|auth| {
+        (
+            auth.cookie.as_deref().unwrap_or_default(),
+            auth.access_token.as_deref().unwrap_or_default(),
+        )
+    });
+    match credentials {
+        Some((cookie, token)) if !cookie.is_empty() || !token.is_empty() => {
+            let hash = Sha256::digest(format!("{}\n{}", cookie, token).as_bytes());
+            format!("{:x}", hash)[..16].to_string()
+        }
+        _ => "anonymous".to_string(),
+    }
+}
+
+/// 以URL和登录身份为键、带有效期的解析结果缓存
+#[derive(Debug, Clone)]
+pub struct ParseCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl ParseCache {
+    pub fn new(dir: impl Into<PathBuf>, ttl: Duration) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl,
+        }
+    }
+
+    /// 默认缓存目录：`<系统缓存目录>/rvd/parse`（Linux 下为 `~/.cache/rvd/parse`）
+    pub fn default_dir() -> Option<PathBuf> {
+        dirs::cache_dir().map(|dir| dir.join("rvd").join("parse"))
+    }
+
+    /// 读取未过期的缓存；缓存不存在、已过期或无法解析时返回 `None`
+    pub fn get(&self, url: &str, identity: &str) -> Option<VideoInfo> {
+        let content = std::fs::read_to_string(self.entry_path(url, identity)).ok()?;
+        let entry: CacheEntry = match serde_json::from_str(&content) {
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::debug!("Ignoring unreadable parse cache for {}: {}", url, e);
+                return None;
+            }
+        };
+        // 防止哈希冲突时误用其他URL或其他身份的结果
+        if entry.url != url || entry.identity != identity {
+            return None;
+        }
+
+        let age = now_secs().saturating_sub(entry.cached_at);
+        if age >= self.ttl.as_secs() {
+            tracing::debug!("Parse cache for {} expired ({}s old)", url, age);
+            return None;
+        }
+        Some(entry.video)
+    }
+
+    /// 写入缓存，失败只记录警告
+    pub fn put(&self, url: &str, identity: &str, video: &VideoInfo) {
+        let entry = CacheEntry {
+            url: url.to_string(),
+            identity: identity.to_string(),
+            cached_at: now_secs(),
+            video: video.clone(),
+        };
+        let result = serde_json::to_string(&entry)
+            .map_err(std::io::Error::other)
+            .and_then(|json| {
+                std::fs::create_dir_all(&self.dir)?;
+                std::fs::write(self.entry_path(url, identity), json)
+            });
+        if let Err(e) = result {
+            tracing::warn!("Failed to write parse cache: {}", e);
+        }
+    }
+
+    fn entry_path(&self, url: &str, identity: &str) -> PathBuf {
+        let hash = format!("{:x}", Sha256::digest(format!("{}\n{}", identity, url).as_bytes()));
+        self.dir.join(format!("{}.json", hash))
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
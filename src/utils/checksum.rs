@@ -0,0 +1,74 @@
+//! SHA-256 校验和工具
+//!
+//! 以流式方式计算文件哈希（不会将整个文件读入内存），
+//! 并以 `sha256sum` 兼容的格式读写 `<output>.sha256` 校验文件。
+
+use crate::error::{DownloaderError, Result};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncReadExt;
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// 流式计算文件的SHA-256（小写十六进制）
+pub async fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; READ_BUFFER_SIZE];
+
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// 获取校验文件路径：`video.mp4` -> `video.mp4.sha256`
+pub fn sidecar_path(output: &Path) -> PathBuf {
+    let mut name = output.as_os_str().to_owned();
+    name.push(".sha256");
+    PathBuf::from(name)
+}
+
+/// 格式化为 `sha256sum` 兼容的一行：`<hash>  <filename>`
+pub fn format_sidecar(hash: &str, output: &Path) -> String {
+    let filename = output
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    format!("{}  {}\n", hash, filename)
+}
+
+/// 从校验文件内容中解析哈希值
+pub fn parse_sidecar(content: &str) -> Option<String> {
+    let hash = content.split_whitespace().next()?.to_lowercase();
+    if hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(hash)
+    } else {
+        None
+    }
+}
+
+/// 读取已存在的校验文件，返回记录的哈希值
+pub async fn read_sidecar(output: &Path) -> Result<Option<String>> {
+    let path = sidecar_path(output);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = tokio::fs::read_to_string(&path).await?;
+    parse_sidecar(&content).map(Some).ok_or_else(|| {
+        DownloaderError::Parse(format!("Invalid checksum file: {}", path.display()))
+    })
+}
+
+/// 计算输出文件的SHA-256并写入校验文件，返回哈希值
+pub async fn write_sidecar(output: &Path) -> Result<String> {
+    let hash = sha256_file(output).await?;
+    tokio::fs::write(crate::utils::file::long_path(&sidecar_path(output)), format_sidecar(&hash, output)).await?;
+    Ok(hash)
+}
@@ -1,5 +1,6 @@
 use crate::error::{DownloaderError, Result};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -21,9 +22,60 @@ pub struct Config {
     pub paths: Option<PathsConfig>,
     #[serde(default)]
     pub aria2c: Option<Aria2cConfig>,
+    #[serde(default)]
+    pub platforms: Option<PlatformsConfig>,
+    /// `[danmaku]`：弹幕相关设置
+    #[serde(default)]
+    pub danmaku: Option<DanmakuConfig>,
+    /// `[subtitle]`：字幕相关设置
+    #[serde(default)]
+    pub subtitle: Option<SubtitleConfig>,
+    /// 输出消息的语言（`zh` 或 `en`），未设置时跟随系统语言
+    #[serde(default)]
+    pub lang: Option<String>,
+    /// 默认开启 `--embed-metadata`
+    #[serde(default)]
+    pub embed_metadata: Option<bool>,
+    /// 默认的 `--audio-lang`
+    #[serde(default)]
+    pub audio_lang: Option<String>,
+    /// 默认开启 `--download-danmaku`
+    #[serde(default)]
+    pub download_danmaku: Option<bool>,
+    /// 默认的 `--danmaku-format`（`xml` 或 `ass`）
+    #[serde(default)]
+    pub danmaku_format: Option<String>,
+    /// 命名的配置组，通过 `--profile <name>` 选择
+    #[serde(default)]
+    pub profiles: HashMap<String, ProfileConfig>,
 }
 
-#[derive(Debug, Clone, Deserialize, serde::Serialize)]
+/// 配置组：覆盖顶层的默认设置，但优先级低于命令行参数
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ProfileConfig {
+    #[serde(default)]
+    pub default_quality: Option<Vec<String>>,
+    #[serde(default)]
+    pub default_codec: Option<Vec<String>>,
+    #[serde(default)]
+    pub thread_count: Option<usize>,
+    #[serde(default)]
+    pub output_template: Option<String>,
+    #[serde(default)]
+    pub multi_output_template: Option<String>,
+    #[serde(default)]
+    pub embed_metadata: Option<bool>,
+    #[serde(default)]
+    pub audio_lang: Option<String>,
+    #[serde(default)]
+    pub download_danmaku: Option<bool>,
+    #[serde(default)]
+    pub danmaku_format: Option<String>,
+}
+
+/// `[auth]`：顶层字段为 Bilibili 的认证信息（兼容旧配置），
+/// `[auth.<platform>]`（如 `[auth.bilibili]`、`[auth.youtube]`）为各平台单独的认证信息
+#[derive(Debug, Clone, Default, Deserialize, serde::Serialize)]
 pub struct AuthConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cookie: Option<String>,
@@ -35,11 +87,80 @@ pub struct AuthConfig {
     pub expires_at: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mid: Option<u64>,
+    /// 从文件读取 Cookie（Netscape 格式的 cookies.txt，或 `a=b; c=d` 格式的 Cookie 字符串）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cookies_file: Option<PathBuf>,
+    /// 按平台名称区分的认证信息，`[auth]` 中其他非表格的键被忽略
+    #[serde(
+        flatten,
+        deserialize_with = "deserialize_auth_platforms",
+        skip_serializing_if = "HashMap::is_empty"
+    )]
+    pub platforms: HashMap<String, AuthConfig>,
+}
+
+/// 只把 `[auth]` 下的表格当作平台认证信息，未知的标量键（如拼写错误或旧版本的字段）直接忽略
+fn deserialize_auth_platforms<'de, D>(
+    deserializer: D,
+) -> std::result::Result<HashMap<String, AuthConfig>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    let values = HashMap::<String, toml::Value>::deserialize(deserializer)?;
+    values
+        .into_iter()
+        .filter(|(_, value)| value.is_table())
+        .map(|(platform, value)| {
+            AuthConfig::deserialize(value)
+                .map(|auth| (platform, auth))
+                .map_err(D::Error::custom)
+        })
+        .collect()
+}
+
+impl AuthConfig {
+    /// 指定平台使用的认证信息：`[auth.<platform>]` 中的字段优先，
+    /// 顶层字段只作为 Bilibili 的认证信息
+    pub fn for_platform(&self, platform: &str) -> AuthConfig {
+        let section = self.platforms.get(platform).cloned().unwrap_or_default();
+        if platform != "bilibili" {
+            return AuthConfig {
+                platforms: HashMap::new(),
+                ..section
+            };
+        }
+
+        AuthConfig {
+            cookie: section.cookie.or_else(|| self.cookie.clone()),
+            access_token: section.access_token.or_else(|| self.access_token.clone()),
+            refresh_token: section.refresh_token.or_else(|| self.refresh_token.clone()),
+            expires_at: section.expires_at.or(self.expires_at),
+            mid: section.mid.or(self.mid),
+            cookies_file: section.cookies_file.or_else(|| self.cookies_file.clone()),
+            platforms: HashMap::new(),
+        }
+    }
+
+    /// Cookie 字符串：`cookie` 优先，其次读取 `cookies_file`
+    pub fn cookie_value(&self) -> Result<Option<String>> {
+        if let Some(ref cookie) = self.cookie {
+            return Ok(Some(cookie.clone()));
+        }
+        match self.cookies_file {
+            Some(ref path) => crate::utils::cookie::read_cookies_file(path).map(Some),
+            None => Ok(None),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct PathsConfig {
     pub ffmpeg: Option<PathBuf>,
+    /// `--play-after` 使用的播放器命令（可带参数），未设置时使用系统默认程序
+    #[serde(default)]
+    pub player: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -50,6 +171,41 @@ pub struct Aria2cConfig {
     pub path: Option<String>,
     #[serde(default)]
     pub args: Option<String>,
+    /// 常驻 aria2c 的 JSON-RPC 地址，如 `http://localhost:6800/jsonrpc`
+    #[serde(default)]
+    pub rpc_url: Option<String>,
+    #[serde(default)]
+    pub rpc_secret: Option<String>,
+    /// 每个文件分割的块数
+    #[serde(default)]
+    pub split: Option<u32>,
+    /// 每个服务器的最大连接数
+    #[serde(default)]
+    pub max_connection_per_server: Option<u32>,
+    /// 最小分块大小，如 `5M`
+    #[serde(default)]
+    pub min_split_size: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct DanmakuConfig {
+    /// ASS 弹幕使用的字体，字体名称或字体文件路径，与 `--font` 格式相同
+    #[serde(default)]
+    pub font: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct SubtitleConfig {
+    /// 嵌入 MKV 字幕时附加的字体，字体名称或字体文件路径，与 `--font` 格式相同
+    #[serde(default)]
+    pub font: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PlatformsConfig {
+    /// 启用的平台名称列表（未设置时启用全部平台）
+    #[serde(default)]
+    pub enabled: Option<Vec<String>>,
 }
 
 impl Config {
@@ -68,6 +224,72 @@ impl Config {
         Ok(config)
     }
 
+    /// `[danmaku] font`
+    pub fn danmaku_font(&self) -> Option<&str> {
+        self.danmaku.as_ref().and_then(|d| d.font.as_deref())
+    }
+
+    /// `[subtitle] font`
+    pub fn subtitle_font(&self) -> Option<&str> {
+        self.subtitle.as_ref().and_then(|s| s.font.as_deref())
+    }
+
+    /// 将命名配置组合并到顶层设置上
+    pub fn apply_profile(&mut self, name: &str) -> Result<()> {
+        let profile = self.profiles.get(name).cloned().ok_or_else(|| {
+            let mut available: Vec<&str> = self.profiles.keys().map(|k| k.as_str()).collect();
+            available.sort();
+            DownloaderError::Config(format!(
+                "Profile '{}' not found (available: {})",
+                name,
+                if available.is_empty() {
+                    "none".to_string()
+                } else {
+                    available.join(", ")
+                }
+            ))
+        })?;
+
+        if profile.default_quality.is_some() {
+            self.default_quality = profile.default_quality;
+        }
+        if profile.default_codec.is_some() {
+            self.default_codec = profile.default_codec;
+        }
+        if profile.thread_count.is_some() {
+            self.thread_count = profile.thread_count;
+        }
+        if profile.output_template.is_some() {
+            self.output_template = profile.output_template;
+        }
+        if profile.multi_output_template.is_some() {
+            self.multi_output_template = profile.multi_output_template;
+        }
+        if profile.embed_metadata.is_some() {
+            self.embed_metadata = profile.embed_metadata;
+        }
+        if profile.audio_lang.is_some() {
+            self.audio_lang = profile.audio_lang;
+        }
+        if profile.download_danmaku.is_some() {
+            self.download_danmaku = profile.download_danmaku;
+        }
+        if profile.danmaku_format.is_some() {
+            self.danmaku_format = profile.danmaku_format;
+        }
+
+        tracing::info!("Using config profile: {}", name);
+        Ok(())
+    }
+
+    /// 判断平台是否启用（未配置 `[platforms] enabled` 时全部启用）
+    pub fn is_platform_enabled(&self, name: &str) -> bool {
+        match self.platforms.as_ref().and_then(|p| p.enabled.as_ref()) {
+            Some(enabled) => enabled.iter().any(|n| n.eq_ignore_ascii_case(name)),
+            None => true,
+        }
+    }
+
     pub fn load_default() -> Result<Self> {
         // Try to load from current directory
         let current_dir_config = PathBuf::from("rvd.toml");
@@ -0,0 +1,361 @@
+//! 持久化 Cookie 存储
+//!
+//! 作为 reqwest 的 cookie store 使用：服务端通过 `Set-Cookie` 下发的 Cookie
+//! （如 `buvid3`）按域名和名称记录下来，并在后续访问匹配的 Bilibili 域名时自动携带。
+//! 可选地绑定一个文件，以 Netscape cookies.txt 格式持久化（也能读取
+//! `name=value; name2=value2` 格式的旧文件）。服务端下发的 Cookie 先记在内存中，
+//! 由 [`CookieJar::flush`] 在运行结束时统一写回，不在请求过程中同步写盘。
+//!
+//! `--cookie` 传入的登录凭据不进入存储，由 [`CookieJar::cookie_header_with`]
+//! 按请求合并到 `Cookie` 请求头中，不带认证信息的请求不会携带它们。
+
+use crate::error::{DownloaderError, Result};
+use reqwest::cookie::CookieStore;
+use reqwest::header::HeaderValue;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use url::Url;
+
+/// 需要携带 Cookie 的域名后缀
+const COOKIE_DOMAINS: &[&str] = &["bilibili.com", "biliapi.net", "biliapi.com"];
+
+/// 没有域名信息的 Cookie（[`CookieJar::seed`]、[`CookieJar::insert`]、旧格式文件）归属的域名
+const DEFAULT_DOMAIN: &str = "bilibili.com";
+
+#[derive(Debug, Clone)]
+struct StoredCookie {
+    value: String,
+    /// 是否写入绑定的文件
+    persist: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct CookieJar {
+    /// 以 (域名, 名称) 为键，不同域名下发的同名 Cookie 互不覆盖
+    cookies: RwLock<BTreeMap<(String, String), StoredCookie>>,
+    path: Option<PathBuf>,
+    /// 有尚未写回文件的变更
+    dirty: AtomicBool,
+}
+
+impl CookieJar {
+    /// 创建仅保存在内存中的 Cookie 存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从文件加载 Cookie 存储，之后的变更会自动写回该文件（文件不存在时视为空）
+    pub fn load(path: &Path) -> Result<Self> {
+        let jar = Self {
+            cookies: RwLock::new(BTreeMap::new()),
+            path: Some(path.to_path_buf()),
+            dirty: AtomicBool::new(false),
+        };
+
+        if path.exists() {
+            let content = std::fs::read_to_string(path)?;
+            let mut cookies = jar.cookies.write().unwrap();
+            for (domain, name, value) in parse_jar_file(&content) {
+                cookies.insert((domain, name), StoredCookie { value, persist: true });
+            }
+        }
+
+        Ok(jar)
+    }
+
+    /// 写入 Cookie 字符串（如 `buvid3=xxx; buvid4=yyy`），同名 Cookie 会被覆盖
+    ///
+    /// 这些 Cookie 只在本次运行中使用，不写入绑定的文件（如从磁盘缓存恢复的buvid）
+    pub fn seed(&self, cookie: &str) {
+        let mut cookies = self.cookies.write().unwrap();
+        for (name, value) in parse_cookie_pairs(cookie) {
+            cookies.insert(
+                (DEFAULT_DOMAIN.to_string(), name),
+                StoredCookie { value, persist: false },
+            );
+        }
+    }
+
+    /// 按名称查找 Cookie，多个域名下都有时优先使用默认域名下的值
+    pub fn get(&self, name: &str) -> Option<String> {
+        let cookies = self.cookies.read().unwrap();
+        cookies
+            .get(&(DEFAULT_DOMAIN.to_string(), name.to_string()))
+            .or_else(|| {
+                cookies
+                    .iter()
+                    .find(|((_, cookie_name), _)| cookie_name == name)
+                    .map(|(_, cookie)| cookie)
+            })
+            .map(|cookie| cookie.value.clone())
+    }
+
+    /// 写入默认域名下的 Cookie 并保存到绑定的文件
+    pub fn insert(&self, name: &str, value: &str) {
+        self.cookies.write().unwrap().insert(
+            (DEFAULT_DOMAIN.to_string(), name.to_string()),
+            StoredCookie {
+                value: value.to_string(),
+                persist: true,
+            },
+        );
+        self.persist();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cookies.read().unwrap().is_empty()
+    }
+
+    /// 以 `name=value; name2=value2` 格式输出全部 Cookie
+    pub fn to_cookie_string(&self) -> String {
+        self.cookies
+            .read()
+            .unwrap()
+            .iter()
+            .map(|((_, name), cookie)| format!("{}={}", name, cookie.value))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// 发送到 `url` 的 `Cookie` 请求头：存储中匹配的 Cookie 加上 `extra`
+    /// （如 `--cookie` 传入的凭据，同名时覆盖存储中的值）
+    ///
+    /// 只合并到本次请求中，`extra` 不会写入存储；非Bilibili域名返回 `None`
+    pub fn cookie_header_with(&self, url: &Url, extra: &str) -> Option<HeaderValue> {
+        if !is_cookie_domain(url) {
+            return None;
+        }
+        let extra = parse_cookie_pairs(extra);
+        let stored = self.cookie_header_for(&url.host_str()?.to_ascii_lowercase());
+        let mut pairs: Vec<String> = parse_cookie_pairs(&stored)
+            .into_iter()
+            .filter(|(name, _)| !extra.iter().any(|(extra_name, _)| extra_name == name))
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        pairs.extend(extra.iter().map(|(name, value)| format!("{}={}", name, value)));
+        if pairs.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&pairs.join("; ")).ok()
+    }
+
+    /// 发送到 `host` 的 Cookie：域名匹配的全部 Cookie，同名时使用最具体（最长）的域名下的值
+    fn cookie_header_for(&self, host: &str) -> String {
+        let cookies = self.cookies.read().unwrap();
+        let mut matched: BTreeMap<&str, (&str, &str)> = BTreeMap::new();
+        for ((domain, name), cookie) in cookies.iter() {
+            if !domain_matches(host, domain) {
+                continue;
+            }
+            match matched.get(name.as_str()) {
+                Some((existing, _)) if existing.len() >= domain.len() => {}
+                _ => {
+                    matched.insert(name, (domain, &cookie.value));
+                }
+            }
+        }
+        matched
+            .iter()
+            .map(|(name, (_, value))| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+
+    /// 写回绑定的文件（未绑定文件时不做任何操作），只写入需要持久化的 Cookie
+    pub fn save(&self) -> Result<()> {
+        if let Some(ref path) = self.path {
+            if let Some(parent) = path.parent() {
+                if !parent.as_os_str().is_empty() {
+                    std::fs::create_dir_all(parent)?;
+                }
+            }
+            let mut content = String::from("# Netscape HTTP Cookie File\n");
+            for ((domain, name), cookie) in self.cookies.read().unwrap().iter() {
+                if cookie.persist {
+                    content.push_str(&format!(
+                        ".{}\tTRUE\t/\tFALSE\t0\t{}\t{}\n",
+                        domain, name, cookie.value
+                    ));
+                }
+            }
+            std::fs::write(path, content)?;
+        }
+        Ok(())
+    }
+
+    /// 有未写回的变更时写回绑定的文件，在运行结束时调用（存储被释放时也会调用）
+    pub fn flush(&self) {
+        if self.dirty.swap(false, Ordering::AcqRel) {
+            self.persist();
+        }
+    }
+
+    fn persist(&self) {
+        if let Err(e) = self.save() {
+            tracing::warn!("Failed to save cookie jar: {}", e);
+        }
+    }
+}
+
+impl Drop for CookieJar {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        if !is_cookie_domain(url) {
+            return;
+        }
+        let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+
+        let mut changed = false;
+        {
+            let mut cookies = self.cookies.write().unwrap();
+            for header in cookie_headers {
+                let Ok(header) = header.to_str() else {
+                    continue;
+                };
+                // 取 `name=value` 和 Domain 属性，忽略 Path/Expires 等其他属性
+                let Some((name, value)) = parse_cookie_pairs(header).into_iter().next() else {
+                    continue;
+                };
+                let expired = value.is_empty()
+                    || header
+                        .split(';')
+                        .any(|attr| attr.trim().eq_ignore_ascii_case("max-age=0"));
+                // 没有 Domain 属性或 Domain 不包含当前主机时归属当前主机
+                let domain = header
+                    .split(';')
+                    .skip(1)
+                    .filter_map(|attr| attr.split_once('='))
+                    .find(|(key, _)| key.trim().eq_ignore_ascii_case("domain"))
+                    .map(|(_, value)| normalize_domain(value))
+                    .filter(|domain| domain_matches(&host, domain))
+                    .unwrap_or_else(|| host.clone());
+                let key = (domain, name);
+
+                if expired {
+                    changed |= cookies.remove(&key).is_some();
+                } else if cookies.get(&key).map(|cookie| &cookie.value) != Some(&value) {
+                    tracing::debug!("Cookie set by {}: {}", host, key.1);
+                    // 服务端更新 `seed` 写入的 Cookie 时，新值同样不写入文件
+                    let persist = cookies.get(&key).is_none_or(|cookie| cookie.persist);
+                    cookies.insert(key, StoredCookie { value, persist });
+                    changed = true;
+                }
+            }
+        }
+
+        // 只标记变更，由 `flush` 在运行结束时写回，避免在请求过程中同步写盘
+        if changed && self.path.is_some() {
+            self.dirty.store(true, Ordering::Release);
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        if !is_cookie_domain(url) || self.is_empty() {
+            return None;
+        }
+        let header = self.cookie_header_for(&url.host_str()?.to_ascii_lowercase());
+        if header.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&header).ok()
+    }
+}
+
+/// 读取 Cookie 文件，返回 `a=b; c=d` 格式的 Cookie 字符串
+///
+/// 支持浏览器扩展导出的 Netscape 格式 cookies.txt，以及直接保存 Cookie 字符串的文本文件
+pub fn read_cookies_file(path: &Path) -> Result<String> {
+    let content = std::fs::read_to_string(path).map_err(|e| {
+        DownloaderError::Config(format!("Failed to read cookies file {}: {}", path.display(), e))
+    })?;
+    Ok(parse_cookies_file(&content))
+}
+
+/// 解析 Cookie 文件内容，见 [`read_cookies_file`]
+pub fn parse_cookies_file(content: &str) -> String {
+    // Netscape 格式：每行7个以Tab分隔的字段，最后两个为名称和值；`#HttpOnly_` 前缀的行也是Cookie
+    let netscape: Vec<String> = content
+        .lines()
+        .map(|line| line.strip_prefix("#HttpOnly_").unwrap_or(line))
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            (fields.len() == 7).then(|| format!("{}={}", fields[5], fields[6].trim()))
+        })
+        .collect();
+
+    if netscape.is_empty() {
+        content.trim().to_string()
+    } else {
+        netscape.join("; ")
+    }
+}
+
+/// 解析绑定文件的内容，返回 (域名, 名称, 值)：Netscape 格式保留每行的域名，
+/// 旧的 `a=b; c=d` 格式归属默认域名
+fn parse_jar_file(content: &str) -> Vec<(String, String, String)> {
+    let netscape: Vec<(String, String, String)> = content
+        .lines()
+        .map(|line| line.strip_prefix("#HttpOnly_").unwrap_or(line))
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').collect();
+            (fields.len() == 7).then(|| {
+                (
+                    normalize_domain(fields[0]),
+                    fields[5].to_string(),
+                    fields[6].trim().to_string(),
+                )
+            })
+        })
+        .collect();
+    if !netscape.is_empty() {
+        return netscape;
+    }
+
+    parse_cookie_pairs(content.trim())
+        .into_iter()
+        .map(|(name, value)| (DEFAULT_DOMAIN.to_string(), name, value))
+        .collect()
+}
+
+/// 解析 `a=b; c=d` 格式的 Cookie 字符串
+pub fn parse_cookie_pairs(cookie: &str) -> Vec<(String, String)> {
+    cookie
+        .split(';')
+        .filter_map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            let name = name.trim();
+            if name.is_empty() {
+                return None;
+            }
+            Some((name.to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+fn is_cookie_domain(url: &Url) -> bool {
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    COOKIE_DOMAINS
+        .iter()
+        .any(|domain| domain_matches(host, domain))
+}
+
+/// `host` 是否属于 `domain`（相同或为其子域名）
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+/// 统一域名格式：去掉前导的点并转为小写
+fn normalize_domain(domain: &str) -> String {
+    domain.trim().trim_start_matches('.').to_ascii_lowercase()
+}
@@ -1,6 +1,6 @@
-use crate::error::Result;
+use crate::error::{DownloaderError, Result};
 use crate::types::{Page, VideoInfo};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 pub fn sanitize_filename(name: &str) -> String {
@@ -58,6 +58,134 @@ pub fn parse_template(
     result
 }
 
+/// 多P视频中单个分P的文件名（含 `.mp4` 扩展名）
+///
+/// 提供分P模板（`--page-template`）时按模板生成，否则使用默认的 `P01_分P标题`
+pub fn page_file_name(
+    template: Option<&str>,
+    video_info: &VideoInfo,
+    page: &Page,
+    quality: &str,
+    codec: &str,
+) -> String {
+    let name = match template {
+        Some(template) => parse_template(template, video_info, Some(page), quality, codec),
+        None => default_page_stem(page),
+    };
+
+    if name.ends_with(".mp4") {
+        name
+    } else {
+        format!("{}.mp4", name)
+    }
+}
+
+/// 默认的分P文件名（不含扩展名）：`P01_分P标题`，分P标题为空时只用编号 `P01`
+fn default_page_stem(page: &Page) -> String {
+    if page.title.trim().is_empty() {
+        format!("P{:02}", page.number)
+    } else {
+        format!("P{:02}_{}", page.number, sanitize_filename(&page.title))
+    }
+}
+
+/// 生成文件名时使用的分P：编号加上 `--page-number-offset`，使分多次归档的系列编号连续
+///
+/// 只影响文件名（`P{:02}`、`<pageNumber>`、`<pageNumberWithZero>`），不影响选择分P
+pub fn offset_page_number(page: &Page, offset: usize) -> Page {
+    Page {
+        number: page.number + offset,
+        ..page.clone()
+    }
+}
+
+/// Windows 传统路径长度上限（MAX_PATH，含结尾的空字符）
+pub const MAX_PATH: usize = 260;
+
+/// 将 Windows 绝对路径转为扩展长度形式：`C:\a` -> `\\?\C:\a`，`\\server\share` -> `\\?\UNC\server\share`
+///
+/// 扩展长度路径不做任何解析，因此统一分隔符为 `\` 并去掉 `.`/`..` 组成部分；
+/// 已带前缀的路径和相对路径原样返回。不依赖当前平台，便于测试
+pub fn extended_length_path(path: &str) -> String {
+    if path.starts_with(r"\\?\") {
+        return path.to_string();
+    }
+
+    let normalized = path.replace('/', "\\");
+    // 盘符或 UNC 的服务器名、共享名不能被 `..` 越过
+    let (prefix, rest, root_parts) = if let Some(unc) = normalized.strip_prefix(r"\\") {
+        (r"\\?\UNC\", unc, 2)
+    } else if normalized.as_bytes().get(1) == Some(&b':') {
+        (r"\\?\", normalized.as_str(), 1)
+    } else {
+        return path.to_string();
+    };
+
+    let mut parts: Vec<&str> = Vec::new();
+    for (i, part) in rest.split('\\').enumerate() {
+        match part {
+            "" | "." if i > 0 => {}
+            ".." => {
+                if parts.len() > root_parts {
+                    parts.pop();
+                }
+            }
+            _ => parts.push(part),
+        }
+    }
+
+    format!("{}{}", prefix, parts.join("\\"))
+}
+
+/// 写入文件前调用：Windows 上路径超过 MAX_PATH 时改用扩展长度形式，其他平台原样返回
+///
+/// 中文标题加上较深的输出目录很容易超过 260 个字符，导致创建文件失败
+pub fn long_path(path: &Path) -> PathBuf {
+    if !cfg!(windows) {
+        return path.to_path_buf();
+    }
+
+    let absolute = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        match std::env::current_dir() {
+            Ok(dir) => dir.join(path),
+            Err(_) => return path.to_path_buf(),
+        }
+    };
+
+    let display = absolute.to_string_lossy();
+    // MAX_PATH 按 UTF-16 编码单元计算
+    if display.encode_utf16().count() < MAX_PATH {
+        return path.to_path_buf();
+    }
+    PathBuf::from(extended_length_path(&display))
+}
+
+/// 在文件名（含扩展名）后追加后缀：`video.m4s` + `.part` -> `video.m4s.part`
+fn append_to_file_name(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// 下载中的临时文件路径：`video.m4s` -> `video.m4s.part`
+///
+/// 下载完成后才改为最终文件名，中断后留下的 `.part` 可以续传
+pub fn part_path(output: &Path) -> PathBuf {
+    append_to_file_name(output, ".part")
+}
+
+/// `.part` 的续传信息文件：`video.m4s.part` -> `video.m4s.part.json`
+pub fn part_meta_path(part: &Path) -> PathBuf {
+    append_to_file_name(part, ".json")
+}
+
+/// 分段下载时存放各分段的目录：`video.m4s.part` -> `video.m4s.part.chunks`
+pub fn chunk_dir_path(part: &Path) -> PathBuf {
+    append_to_file_name(part, ".chunks")
+}
+
 pub async fn create_temp_dir(video_id: &str) -> Result<PathBuf> {
     let temp_dir = std::env::temp_dir().join("rvd").join(video_id);
     tokio::fs::create_dir_all(&temp_dir).await?;
@@ -73,7 +201,7 @@ pub async fn cleanup_temp_dir(dir: &Path) -> Result<()> {
 
 #[allow(dead_code)]
 pub async fn merge_files(chunks: &[PathBuf], output: &Path) -> Result<()> {
-    let mut output_file = tokio::fs::File::create(output).await?;
+    let mut output_file = tokio::fs::File::create(long_path(output)).await?;
 
     for chunk in chunks {
         let mut chunk_file = tokio::fs::File::open(chunk).await?;
@@ -83,24 +211,366 @@ pub async fn merge_files(chunks: &[PathBuf], output: &Path) -> Result<()> {
     Ok(())
 }
 
+/// 封面图片保存路径：`video.mp4` -> `video.jpg`，指定尺寸时为 `video.1920w.jpg`
+pub fn thumbnail_path(output: &Path, size_label: Option<&str>, ext: &str) -> PathBuf {
+    match size_label {
+        Some(label) => output.with_extension(format!("{}.{}", label, ext)),
+        None => output.with_extension(ext),
+    }
+}
+
+/// 根据 `--skip-mux` 产生的视频文件名推导混流输出路径
+///
+/// `title.video.m4s` -> `title.mp4`，其他文件名直接替换扩展名为 `.mp4`
+pub fn remux_output_path(video: &Path) -> PathBuf {
+    let stem = video
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "video".to_string());
+    let stem = stem.strip_suffix(".video").unwrap_or(&stem).to_string();
+    video.with_file_name(format!("{}.mp4", stem))
+}
+
+/// 未指定 `--output` 时的输出路径
+///
+/// 单P视频为 `标题.mp4`；多P视频放在以标题命名的目录下，每个分P为 `P01_分P标题.mp4`
+/// （分P标题为空时为 `P01.mp4`），避免各分P互相覆盖
 pub fn get_default_output_path(video_info: &VideoInfo, page: Option<&Page>) -> PathBuf {
-    if video_info.pages.len() > 1 {
+    if video_info.is_multi_page() {
         // Multi-page video
-        if let Some(p) = page {
-            PathBuf::from(format!(
-                "{}/P{:02}_{}.mp4",
-                sanitize_filename(&video_info.title),
-                p.number,
-                sanitize_filename(&p.title)
-            ))
-        } else {
-            PathBuf::from(format!(
-                "{}/video.mp4",
-                sanitize_filename(&video_info.title)
-            ))
+        let directory = PathBuf::from(sanitize_filename(&video_info.title));
+        match page {
+            Some(p) => directory.join(format!("{}.mp4", default_page_stem(p))),
+            None => directory.join("video.mp4"),
         }
     } else {
         // Single video
         PathBuf::from(format!("{}.mp4", sanitize_filename(&video_info.title)))
     }
 }
+
+/// 将字节数格式化为易读的大小，如 `1.5 GB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// 输出文件已存在时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CollisionStrategy {
+    /// 追加序号，如 `title (2).mp4`
+    Rename,
+    /// 追加视频ID，如 `title_BV1xx411c7mD.mp4`，仍冲突时再追加序号
+    RenameId,
+    /// 覆盖已有文件
+    #[default]
+    Overwrite,
+    /// 跳过该分P
+    Skip,
+    /// 报错终止
+    Error,
+}
+
+/// 出现不止一次的路径（按首次出现的顺序），如清理后同名的标题
+pub fn duplicate_paths(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    for path in paths {
+        if !seen.insert(path) && !duplicates.contains(path) {
+            duplicates.push(path.clone());
+        }
+    }
+    duplicates
+}
+
+/// 按冲突策略确定最终输出路径，返回 `None` 表示跳过
+///
+/// 只检查 `path` 本身，不占用路径；分P的输出使用 [`claim_output_path`]
+pub fn resolve_output_collision(path: &Path, strategy: CollisionStrategy) -> Result<Option<PathBuf>> {
+    let claimed = claim_output_path(path, strategy, None, &|p: &Path| vec![p.to_path_buf()], false)?;
+    Ok(claimed.map(|claimed| claimed.path.clone()))
+}
+
+/// 已确定的输出路径，`reserve` 时在第一个输出文件处留有占位文件
+///
+/// 占位文件在写入内容前被丢弃（如下载失败）时自动删除
+#[derive(Debug)]
+pub struct ClaimedOutput {
+    pub path: PathBuf,
+    placeholder: Option<PathBuf>,
+}
+
+impl Drop for ClaimedOutput {
+    fn drop(&mut self) {
+        if let Some(ref placeholder) = self.placeholder {
+            if std::fs::metadata(placeholder).is_ok_and(|m| m.len() == 0) {
+                let _ = std::fs::remove_file(placeholder);
+            }
+        }
+    }
+}
+
+/// 按冲突策略确定分P的输出路径，返回 `None` 表示跳过
+///
+/// `outputs` 给出某个输出路径实际会写入的文件（如 `--skip-mux` 的 `.video.m4s` 与
+/// `.audio.m4s`），其中任意一个已存在即视为冲突。`RenameId` 先追加 `_<id>`，仍冲突时再追加序号。
+/// `reserve` 时用 `create_new` 原子地创建第一个输出文件作为占位，并发下载的分P不会选中同一个名称；
+/// `Overwrite` 不检查也不占位
+pub fn claim_output_path(
+    path: &Path,
+    strategy: CollisionStrategy,
+    id: Option<&str>,
+    outputs: &dyn Fn(&Path) -> Vec<PathBuf>,
+    reserve: bool,
+) -> Result<Option<ClaimedOutput>> {
+    let unclaimed = |path: PathBuf| ClaimedOutput { path, placeholder: None };
+    if strategy == CollisionStrategy::Overwrite {
+        return Ok(Some(unclaimed(path.to_path_buf())));
+    }
+
+    // 所有输出都不存在且（需要占位时）成功创建占位文件
+    let try_claim = |candidate: &Path| -> Result<Option<ClaimedOutput>> {
+        let files = outputs(candidate);
+        if files.iter().any(|file| file.exists()) {
+            return Ok(None);
+        }
+        let Some(first) = files.first().filter(|_| reserve) else {
+            return Ok(Some(unclaimed(candidate.to_path_buf())));
+        };
+        if let Some(parent) = first.parent() {
+            std::fs::create_dir_all(long_path(parent))?;
+        }
+        match std::fs::OpenOptions::new().write(true).create_new(true).open(long_path(first)) {
+            Ok(_) => Ok(Some(ClaimedOutput {
+                path: candidate.to_path_buf(),
+                placeholder: Some(long_path(first)),
+            })),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    };
+
+    if let Some(claimed) = try_claim(path)? {
+        return Ok(Some(claimed));
+    }
+    match strategy {
+        CollisionStrategy::Overwrite => unreachable!(),
+        CollisionStrategy::Skip => Ok(None),
+        CollisionStrategy::Error => Err(DownloaderError::DownloadFailed(format!(
+            "Output file already exists: {}",
+            path.display()
+        ))),
+        CollisionStrategy::Rename | CollisionStrategy::RenameId => {
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let ext = path
+                .extension()
+                .map(|e| format!(".{}", e.to_string_lossy()))
+                .unwrap_or_default();
+            let stem = match (strategy, id) {
+                (CollisionStrategy::RenameId, Some(id)) => {
+                    let with_id = format!("{}_{}", stem, id);
+                    if let Some(claimed) = try_claim(&path.with_file_name(format!("{}{}", with_id, ext)))? {
+                        return Ok(Some(claimed));
+                    }
+                    with_id
+                }
+                _ => stem,
+            };
+            for n in 2.. {
+                if let Some(claimed) = try_claim(&path.with_file_name(format!("{} ({}){}", stem, n, ext)))? {
+                    return Ok(Some(claimed));
+                }
+            }
+            unreachable!()
+        }
+    }
+}
+
+/// 弹幕、字幕等附属文件的覆盖策略（`--overwrite-policy`），与视频文件的冲突策略相互独立
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SidecarPolicy {
+    /// 与视频一致：视频被写入时才写入附属文件
+    #[default]
+    Follow,
+    /// 总是刷新：视频因已存在被跳过时也重新下载附属文件
+    Overwrite,
+    /// 保留已存在的附属文件
+    Keep,
+}
+
+impl SidecarPolicy {
+    /// 是否写入该附属文件：`Keep` 策略下已存在的文件不再写入
+    pub fn should_write(self, path: &Path) -> bool {
+        !(self == SidecarPolicy::Keep && path.exists())
+    }
+}
+
+/// 按视频信息自动分子目录（`--organize-by`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrganizeBy {
+    /// 不分目录
+    #[default]
+    None,
+    /// 按UP主名称
+    Uploader,
+    /// 按发布日期（`YYYY-MM-DD`）
+    Date,
+}
+
+impl OrganizeBy {
+    /// 该视频对应的子目录名（已清理非法字符），`None` 策略返回 None
+    pub fn subdirectory(self, video_info: &VideoInfo) -> Option<String> {
+        let name = match self {
+            OrganizeBy::None => return None,
+            OrganizeBy::Uploader => video_info.uploader.as_str(),
+            OrganizeBy::Date => video_info.upload_date.get(..10).unwrap_or(&video_info.upload_date),
+        };
+        if name.trim().is_empty() {
+            Some("unknown".to_string())
+        } else {
+            Some(sanitize_filename(name))
+        }
+    }
+}
+
+/// 解析易读的大小，如 `2G`、`10M`、`1.5GiB`、`500KB`、`1024`（按1024进制，与 [`format_size`] 一致）
+pub fn parse_size(value: &str) -> Option<u64> {
+    let value = value.trim();
+    let upper = value.to_ascii_uppercase();
+    let number = upper.trim_end_matches("IB").trim_end_matches('B');
+    let (digits, multiplier) = match number.chars().last()? {
+        'K' => (&number[..number.len() - 1], 1u64 << 10),
+        'M' => (&number[..number.len() - 1], 1 << 20),
+        'G' => (&number[..number.len() - 1], 1 << 30),
+        'T' => (&number[..number.len() - 1], 1 << 40),
+        _ => (number, 1),
+    };
+
+    let size: f64 = digits.trim().parse().ok()?;
+    if !size.is_finite() || size < 0.0 {
+        return None;
+    }
+    Some((size * multiplier as f64) as u64)
+}
+
+/// 批量文件中的一行：URL及该URL单独使用的选项
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchEntry {
+    pub url: String,
+    /// `key=value` 形式的选项覆盖，按出现顺序
+    pub overrides: Vec<(String, String)>,
+}
+
+/// 解析批量文件：每行一个URL，忽略空行和以 `#` 开头的注释行
+///
+/// URL后可用 `|` 追加以空白分隔的 `key=value` 选项，
+/// 如 `https://... | quality=720P pages=1-3`
+pub fn parse_batch_file(content: &str) -> Result<Vec<BatchEntry>> {
+    let mut entries = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (url, options) = line.split_once('|').unwrap_or((line, ""));
+        let mut overrides = Vec::new();
+        for option in options.split_whitespace() {
+            let (key, value) = option.split_once('=').ok_or_else(|| {
+                DownloaderError::Config(format!(
+                    "Line {}: invalid option '{}', expected key=value",
+                    index + 1,
+                    option
+                ))
+            })?;
+            overrides.push((key.to_string(), value.to_string()));
+        }
+
+        entries.push(BatchEntry {
+            url: url.trim().to_string(),
+            overrides,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// 下载失败的URL或分P，写入 `--failures-file` 供 `--retry-failed` 重试
+#[derive(Debug, Clone, PartialEq)]
+pub struct FailedDownload {
+    pub url: String,
+    /// 失败（或因前一个分P失败而未下载）的分P编号，None 表示整个URL失败（如解析失败）
+    pub pages: Option<Vec<usize>>,
+    pub error: String,
+}
+
+/// 生成失败记录文件：批量文件格式，同一URL的记录合并为一行
+///
+/// 失败的分P写为 `pages=` 选项，错误信息写在URL上方的注释中
+pub fn format_failures_file(failures: &[FailedDownload]) -> String {
+    // 合并后的记录，error 为以换行分隔的所有错误信息
+    let mut merged: Vec<FailedDownload> = Vec::new();
+    for failure in failures {
+        let Some(entry) = merged.iter_mut().find(|entry| entry.url == failure.url) else {
+            merged.push(failure.clone());
+            continue;
+        };
+        match (entry.pages.as_mut(), &failure.pages) {
+            (Some(pages), Some(failed)) => pages.extend(failed),
+            // 整个URL失败时重试全部分P
+            _ => entry.pages = None,
+        }
+        if !entry.error.lines().any(|error| error == failure.error) {
+            entry.error = format!("{}\n{}", entry.error, failure.error);
+        }
+    }
+
+    let mut content = String::from("# rvd failures file, retry with: rvd --retry-failed <this file>\n");
+    for FailedDownload { url, pages, error } in merged {
+        for error in error.lines() {
+            content.push_str(&format!("# {}\n", error));
+        }
+        match pages {
+            Some(mut pages) if !pages.is_empty() => {
+                pages.sort_unstable();
+                pages.dedup();
+                let pages: Vec<String> = pages.iter().map(|p| p.to_string()).collect();
+                content.push_str(&format!("{} | pages={}\n", url, pages.join(",")));
+            }
+            _ => content.push_str(&format!("{}\n", url)),
+        }
+    }
+    content
+}
+
+/// 解析 `--retry-failed` 读取的失败记录文件
+///
+/// 与批量文件格式相同，但只接受 `pages` 选项，其余选项沿用本次的命令行参数
+pub fn parse_failures_file(content: &str) -> Result<Vec<BatchEntry>> {
+    let entries = parse_batch_file(content)?;
+    for entry in &entries {
+        if let Some((key, _)) = entry.overrides.iter().find(|(key, _)| key != "pages") {
+            return Err(DownloaderError::Config(format!(
+                "Unexpected option '{}' for {} in failures file, only pages= is allowed",
+                key, entry.url
+            )));
+        }
+    }
+    Ok(entries)
+}
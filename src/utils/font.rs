@@ -0,0 +1,173 @@
+// 弹幕 ASS 与嵌入字幕使用的字体：`--font` 或配置文件指定，未指定时查找系统自带的中文字体
+
+use std::path::{Path, PathBuf};
+
+/// 解析后的字体
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font {
+    /// 字体名称，写入 ASS 样式的 Fontname
+    pub family: String,
+    /// 字体文件，按名称指定且未找到对应文件时为 `None`
+    pub path: Option<PathBuf>,
+}
+
+/// 各平台常见的中文字体文件及其字体名称，按优先级排列
+#[cfg(target_os = "windows")]
+const CJK_FONT_CANDIDATES: &[(&str, &str)] = &[
+    (r"C:\Windows\Fonts\msyh.ttc", "Microsoft YaHei"),
+    (r"C:\Windows\Fonts\simhei.ttf", "SimHei"),
+    (r"C:\Windows\Fonts\simsun.ttc", "SimSun"),
+];
+
+#[cfg(target_os = "macos")]
+const CJK_FONT_CANDIDATES: &[(&str, &str)] = &[
+    ("/System/Library/Fonts/PingFang.ttc", "PingFang SC"),
+    ("/System/Library/Fonts/STHeiti Medium.ttc", "Heiti SC"),
+    ("/Library/Fonts/Arial Unicode.ttf", "Arial Unicode MS"),
+];
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const CJK_FONT_CANDIDATES: &[(&str, &str)] = &[
+    ("/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc", "Noto Sans CJK SC"),
+    ("/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc", "Noto Sans CJK SC"),
+    ("/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc", "Noto Sans CJK SC"),
+    ("/usr/share/fonts/truetype/wqy/wqy-microhei.ttc", "WenQuanYi Micro Hei"),
+    ("/usr/share/fonts/wenquanyi/wqy-microhei/wqy-microhei.ttc", "WenQuanYi Micro Hei"),
+    ("/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf", "Droid Sans Fallback"),
+];
+
+/// 找不到任何中文字体时写入 ASS 的字体名称，交给播放器按名称匹配或回退
+pub fn default_family() -> &'static str {
+    CJK_FONT_CANDIDATES[0].1
+}
+
+/// 按 `spec`（字体名称或字体文件路径）解析字体，未指定时查找系统中文字体
+///
+/// 返回 `None` 表示未指定字体且系统中没有找到已知的中文字体
+pub fn resolve_font(spec: Option<&str>) -> Option<Font> {
+    match spec.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(spec) => resolve_spec(spec),
+        None => find_cjk_font(CJK_FONT_CANDIDATES),
+    }
+}
+
+fn resolve_spec(spec: &str) -> Option<Font> {
+    let path = Path::new(spec);
+    if path.is_file() {
+        let family = std::fs::read(path)
+            .ok()
+            .and_then(|data| font_family_name(&data))
+            .or_else(|| path.file_stem().map(|s| s.to_string_lossy().into_owned()))?;
+        return Some(Font { family, path: Some(path.to_path_buf()) });
+    }
+    if is_font_file_name(spec) {
+        // 看起来是字体文件但不存在，回退到系统中文字体
+        tracing::warn!("Font file not found: {}", spec);
+        return find_cjk_font(CJK_FONT_CANDIDATES);
+    }
+
+    // 按名称指定：已知的中文字体补上文件路径，其余只写入名称
+    let path = CJK_FONT_CANDIDATES
+        .iter()
+        .find(|(path, family)| family.eq_ignore_ascii_case(spec) && Path::new(path).is_file())
+        .map(|(path, _)| PathBuf::from(path));
+    Some(Font { family: spec.to_string(), path })
+}
+
+/// 返回候选列表中第一个存在的字体
+pub fn find_cjk_font(candidates: &[(&str, &str)]) -> Option<Font> {
+    candidates
+        .iter()
+        .find(|(path, _)| Path::new(path).is_file())
+        .map(|(path, family)| Font {
+            family: family.to_string(),
+            path: Some(PathBuf::from(path)),
+        })
+}
+
+/// 是否为字体文件扩展名（ttf/otf/ttc/otc）
+fn is_font_file_name(spec: &str) -> bool {
+    Path::new(spec)
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| matches!(e.to_lowercase().as_str(), "ttf" | "otf" | "ttc" | "otc"))
+        .unwrap_or(false)
+}
+
+/// 作为附件写入 MKV 时字体文件的 MIME 类型
+pub fn mime_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|e| e.to_str()).map(str::to_lowercase).as_deref() {
+        Some("otf") | Some("otc") => "application/vnd.ms-opentype",
+        _ => "application/x-truetype-font",
+    }
+}
+
+/// 从 TrueType/OpenType 字体（或字体集合中的第一个字体）的 `name` 表读取字体名称
+///
+/// 优先使用英文的首选字体族名（nameID 16），其次为字体族名（nameID 1）
+pub fn font_family_name(data: &[u8]) -> Option<String> {
+    // 字体集合（TTC）：使用第一个字体
+    let font_offset = if data.get(0..4)? == b"ttcf" {
+        read_u32(data, 12)? as usize
+    } else {
+        0
+    };
+
+    let num_tables = read_u16(data, font_offset + 4)? as usize;
+    let name_table = (0..num_tables)
+        .map(|i| font_offset + 12 + i * 16)
+        .find(|&record| data.get(record..record + 4) == Some(b"name"))
+        .and_then(|record| read_u32(data, record + 8))? as usize;
+
+    let count = read_u16(data, name_table + 2)? as usize;
+    let strings = name_table + read_u16(data, name_table + 4)? as usize;
+
+    let mut best: Option<(u32, String)> = None;
+    for i in 0..count {
+        let record = name_table + 6 + i * 12;
+        let platform_id = read_u16(data, record)?;
+        let language_id = read_u16(data, record + 4)?;
+        let name_id = read_u16(data, record + 6)?;
+        if name_id != 1 && name_id != 16 {
+            continue;
+        }
+        let length = read_u16(data, record + 8)? as usize;
+        let offset = strings + read_u16(data, record + 10)? as usize;
+        let Some(bytes) = data.get(offset..offset + length) else {
+            continue;
+        };
+        let name = match platform_id {
+            // Unicode 与 Windows 平台：UTF-16BE
+            0 | 3 => {
+                let units: Vec<u16> = bytes
+                    .chunks_exact(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                    .collect();
+                String::from_utf16_lossy(&units)
+            }
+            // Macintosh 平台：按 ASCII 读取
+            1 => bytes.iter().map(|&b| b as char).collect(),
+            _ => continue,
+        };
+        if name.trim().is_empty() {
+            continue;
+        }
+
+        let english = platform_id == 1 || language_id == 0x0409;
+        let score = u32::from(name_id == 16) * 2 + u32::from(english);
+        if best.as_ref().is_none_or(|(best_score, _)| score > *best_score) {
+            best = Some((score, name.trim().to_string()));
+        }
+    }
+
+    best.map(|(_, name)| name)
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2).map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
@@ -1,36 +1,123 @@
 use crate::error::{DownloaderError, Result};
 use crate::types::Auth;
-use reqwest::{header::HeaderMap, Client, Response};
+use crate::utils::cookie::CookieJar;
+use crate::utils::throttle::ApiThrottle;
+use async_trait::async_trait;
+use reqwest::header::{HeaderMap, HeaderValue, COOKIE, REFERER, USER_AGENT};
+use reqwest::{Client, RequestBuilder, Response};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::fs::File;
 use tokio::io::AsyncWriteExt;
 
+/// 解析API请求所用的传输层抽象，测试中可替换为返回固定JSON的实现
+/// （见 `rvd::testing::MockTransport`）
+#[async_trait]
+pub trait HttpTransport: Send + Sync {
+    /// 带认证信息的GET请求，返回响应正文
+    async fn get_text(&self, url: &str, auth: Option<&Auth>) -> Result<String>;
+
+    /// POST请求，返回响应正文
+    async fn post_text(&self, url: &str, body: &str) -> Result<String>;
+
+    /// 连续API请求之间的节流等待（翻页、逐个获取视频详情）
+    async fn pause(&self) {}
+}
+
+/// 连接服务器时使用的IP协议（`--force-ipv4` / `--force-ipv6`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IpVersion {
+    /// 双栈网络由系统和 Happy Eyeballs 自动选择
+    #[default]
+    Auto,
+    /// 只使用IPv4
+    V4,
+    /// 只使用IPv6
+    V6,
+}
+
+impl IpVersion {
+    /// 绑定的本地地址：只绑定一种协议的地址时，连接也只会尝试该协议的服务器地址
+    pub fn local_address(self) -> Option<IpAddr> {
+        match self {
+            IpVersion::Auto => None,
+            IpVersion::V4 => Some(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            IpVersion::V6 => Some(IpAddr::V6(Ipv6Addr::UNSPECIFIED)),
+        }
+    }
+}
+
+/// 视频CDN（`bilivideo.com`）要求的请求头，其他地址不附加
+pub fn cdn_headers(url: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if url.contains("bilivideo.com") {
+        headers.insert(REFERER, HeaderValue::from_static("https://www.bilibili.com"));
+        headers.insert(
+            USER_AGENT,
+            HeaderValue::from_static("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36"),
+        );
+    }
+    headers
+}
+
 pub struct HttpClient {
     pub client: Client,
+    cookie_jar: Arc<CookieJar>,
+    api_throttle: Arc<ApiThrottle>,
     retry_count: usize,
     #[allow(dead_code)]
     timeout: Duration,
+    ip_version: IpVersion,
 }
 
 impl HttpClient {
     pub fn new() -> Result<Self> {
+        Self::with_cookie_jar(Arc::new(CookieJar::new()))
+    }
+
+    /// 使用指定的Cookie存储创建客户端（多个客户端可共享同一个存储）
+    pub fn with_cookie_jar(cookie_jar: Arc<CookieJar>) -> Result<Self> {
+        Self::with_cookie_jar_and_ip_version(cookie_jar, IpVersion::Auto)
+    }
+
+    /// 使用指定的Cookie存储创建客户端，并限定连接使用的IP协议
+    pub fn with_cookie_jar_and_ip_version(
+        cookie_jar: Arc<CookieJar>,
+        ip_version: IpVersion,
+    ) -> Result<Self> {
         let client = Client::builder()
             .timeout(Duration::from_secs(60))
             .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
+            .cookie_provider(cookie_jar.clone())
+            .local_address(ip_version.local_address())
             .build()?;
 
         Ok(Self {
             client,
+            cookie_jar,
+            api_throttle: Arc::new(ApiThrottle::default()),
             retry_count: 3,
             timeout: Duration::from_secs(60),
+            ip_version,
         })
     }
 
+    /// 连接使用的IP协议，其他自行建立连接的客户端（弹幕、aria2c）据此保持一致
+    pub fn ip_version(&self) -> IpVersion {
+        self.ip_version
+    }
+
     pub async fn get(&self, url: &str, headers: Option<HeaderMap>) -> Result<Response> {
         self.request_with_retry(url, headers, None).await
     }
 
+    /// 不经重试的视频CDN请求，附加 [`cdn_headers`]，由调用方设置 `Range` 等请求头
+    pub fn cdn_request(&self, method: reqwest::Method, url: &str) -> RequestBuilder {
+        self.client.request(method, url).headers(cdn_headers(url))
+    }
+
     #[allow(dead_code)]
     pub async fn post(
         &self,
@@ -81,7 +168,10 @@ impl HttpClient {
                     }
                 }
                 Err(e) => {
-                    last_error = Some(DownloaderError::Network(e));
+                    if e.is_timeout() {
+                        tracing::debug!("Request timed out (attempt {}): {}", attempt + 1, url);
+                    }
+                    last_error = Some(e.into());
                 }
             }
         }
@@ -96,29 +186,14 @@ impl HttpClient {
         output: &Path,
         range: Option<(u64, u64)>,
     ) -> Result<()> {
-        let mut request = self.client.get(url);
-
-        // Add required headers for Bilibili video downloads
-        if url.contains("bilivideo.com") {
-            request = request.header("Referer", "https://www.bilibili.com");
-            request = request.header(
-                "User-Agent",
-                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36",
-            );
-        }
+        let mut request = self.cdn_request(reqwest::Method::GET, url);
 
         if let Some((start, end)) = range {
             request = request.header("Range", format!("bytes={}-{}", start, end));
         }
 
-        let response = request.send().await?;
-
-        if !response.status().is_success() {
-            return Err(DownloaderError::DownloadFailed(format!(
-                "HTTP {}",
-                response.status()
-            )));
-        }
+        // 保留状态码，便于下载器在4xx时改用备用CDN地址
+        let response = request.send().await?.error_for_status()?;
 
         let mut file = File::create(output).await?;
         let bytes = response.bytes().await?;
@@ -128,10 +203,56 @@ impl HttpClient {
         Ok(())
     }
 
-    pub fn add_auth(&self, headers: &mut HeaderMap, auth: &Auth) {
+    /// 用 HEAD 请求获取文件大小（`Content-Length`），不下载内容
+    pub async fn content_length(&self, url: &str) -> Result<u64> {
+        let request = self.cdn_request(reqwest::Method::HEAD, url);
+
+        // 错误页（如403）的长度不是文件大小，交给调用方按码率估算
+        let response = request.send().await?.error_for_status()?;
+
+        if let Some(content_length) = response.headers().get("content-length") {
+            let size = content_length
+                .to_str()
+                .map_err(|_| DownloaderError::Parse("Invalid content-length".to_string()))?
+                .parse::<u64>()
+                .map_err(|_| DownloaderError::Parse("Invalid content-length".to_string()))?;
+            Ok(size)
+        } else {
+            Err(DownloaderError::DownloadFailed(
+                "No content-length header".to_string(),
+            ))
+        }
+    }
+
+    /// 设置请求失败时的最大尝试次数（1 表示不重试）
+    pub fn with_retry_count(mut self, retry_count: usize) -> Self {
+        self.retry_count = retry_count.max(1);
+        self
+    }
+
+    /// 替换API请求节流设置（多个客户端可共享同一个节流器）
+    pub fn with_api_throttle(mut self, api_throttle: Arc<ApiThrottle>) -> Self {
+        self.api_throttle = api_throttle;
+        self
+    }
+
+    pub fn cookie_jar(&self) -> &Arc<CookieJar> {
+        &self.cookie_jar
+    }
+
+    pub fn api_throttle(&self) -> &Arc<ApiThrottle> {
+        &self.api_throttle
+    }
+
+    pub fn add_auth(&self, headers: &mut HeaderMap, url: &str, auth: &Auth) {
+        // Cookie只设置在本次请求的请求头中，不写入cookie store，凭据变更或
+        // 不带认证的请求都不会沿用旧的Cookie；服务端下发的Cookie（如buvid3）一并合并
         if let Some(ref cookie) = auth.cookie {
-            if let Ok(value) = cookie.parse() {
-                headers.insert("Cookie", value);
+            let value = url::Url::parse(url)
+                .ok()
+                .and_then(|url| self.cookie_jar.cookie_header_with(&url, cookie));
+            if let Some(value) = value {
+                headers.insert(COOKIE, value);
             }
         }
 
@@ -146,7 +267,7 @@ impl HttpClient {
         let mut headers = HeaderMap::new();
 
         if let Some(auth) = auth {
-            self.add_auth(&mut headers, auth);
+            self.add_auth(&mut headers, url, auth);
         }
 
         // Add referer for bilibili API
@@ -156,6 +277,8 @@ impl HttpClient {
             }
         }
 
+        // 限制同时进行的API请求数（下载文件走 get/download_file，不受限制）
+        let _permit = self.api_throttle.acquire().await;
         self.get(url, Some(headers)).await
     }
 }
@@ -165,3 +288,34 @@ impl Default for HttpClient {
         Self::new().expect("Failed to create HTTP client")
     }
 }
+
+#[async_trait]
+impl HttpTransport for HttpClient {
+    async fn get_text(&self, url: &str, auth: Option<&Auth>) -> Result<String> {
+        Ok(self.get_with_auth(url, auth).await?.text().await?)
+    }
+
+    async fn post_text(&self, url: &str, body: &str) -> Result<String> {
+        Ok(self.post(url, body, None).await?.text().await?)
+    }
+
+    async fn pause(&self) {
+        self.api_throttle.pause().await;
+    }
+}
+
+// 便于直接传入共享的 `Arc<HttpClient>`
+#[async_trait]
+impl<T: HttpTransport + ?Sized> HttpTransport for Arc<T> {
+    async fn get_text(&self, url: &str, auth: Option<&Auth>) -> Result<String> {
+        (**self).get_text(url, auth).await
+    }
+
+    async fn post_text(&self, url: &str, body: &str) -> Result<String> {
+        (**self).post_text(url, body).await
+    }
+
+    async fn pause(&self) {
+        (**self).pause().await;
+    }
+}
@@ -0,0 +1,467 @@
+// 用户可见消息的语言选择（`--lang` / 配置文件 `lang`，默认跟随系统语言）
+//
+// 所有面向用户的输出都通过 `t`/`tf` 从消息表中取文本，保证中英文内容一致；
+// 日志（tracing）面向开发者，保持英文。
+
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 输出语言
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    Zh,
+    En,
+}
+
+impl Lang {
+    /// 解析 `zh`/`en`（也接受 `zh-CN`、`en_US` 等形式）
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.to_ascii_lowercase();
+        if value.starts_with("zh") {
+            Some(Lang::Zh)
+        } else if value.starts_with("en") {
+            Some(Lang::En)
+        } else {
+            None
+        }
+    }
+
+    /// 根据 locale 字符串（如 `zh_CN.UTF-8`）选择语言，非中文环境使用英文
+    pub fn from_locale(locale: &str) -> Self {
+        if locale.to_ascii_lowercase().starts_with("zh") {
+            Lang::Zh
+        } else {
+            Lang::En
+        }
+    }
+
+    /// 从 `LC_ALL`、`LC_MESSAGES`、`LANG` 环境变量检测系统语言，均未设置时使用中文
+    pub fn detect() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|key| std::env::var(key).ok())
+            .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+            .map(|locale| Self::from_locale(&locale))
+            .unwrap_or(Lang::Zh)
+    }
+}
+
+// 0 表示尚未设置，首次使用时检测系统语言
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// 设置输出语言（命令行参数与配置文件加载后可再次调用覆盖）
+pub fn set_lang(lang: Lang) {
+    let value = match lang {
+        Lang::Zh => 1,
+        Lang::En => 2,
+    };
+    CURRENT.store(value, Ordering::Relaxed);
+}
+
+/// 当前输出语言
+pub fn lang() -> Lang {
+    match CURRENT.load(Ordering::Relaxed) {
+        1 => Lang::Zh,
+        2 => Lang::En,
+        _ => {
+            let lang = Lang::detect();
+            set_lang(lang);
+            lang
+        }
+    }
+}
+
+/// 用户可见的消息，带参数的消息使用 `{}` 占位（见 [`tf`]）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Msg {
+    Error,
+    Failed,
+    Cancelled,
+    BatchFound,
+    MoreVideos,
+    Processing,
+    BatchDone,
+    AllDone,
+    SkippedBySize,
+    SkippedExists,
+    LoginLostAbort,
+    LoginLostWarn,
+    PageFailed,
+    StreamsFailed,
+    BatchSummary,
+    SummaryVideos,
+    SummaryDuration,
+    SummarySize,
+    SummaryOutput,
+    RunSummary,
+    RunDownloaded,
+    RunTotalSize,
+    RunElapsed,
+    RunAverageSpeed,
+    RunSkipped,
+    RunFailed,
+    SizeFromFirstVideo,
+    Unknown,
+    Proceed,
+    VideoInformation,
+    Title,
+    Uploader,
+    Pages,
+    Category,
+    Stats,
+    Tags,
+    Description,
+    SelectVideoQuality,
+    SelectAudioQuality,
+    VideoQuality,
+    AudioQuality,
+    QualityAuthYes,
+    QualityAuthNo,
+    QualityListed,
+    QualityDownloadable,
+    QualityLocked,
+    QualityMax,
+    Downloading,
+    AudioLanguages,
+    ExtraAudioNeedsMkv,
+    ExtraAudioWithClip,
+    NoSubtitles,
+    RetryingFailures,
+    FailuresWritten,
+    PathPreview,
+    PathExists,
+    PathDuplicate,
+    PathPreviewSummary,
+    Simulated,
+    SimulatedStream,
+    VideoStream,
+    AudioStream,
+    SimulatedFile,
+    SimulatedSubtitles,
+    SubtitlesFailed,
+    SubtitleDownloaded,
+    DanmakuDownloaded,
+    CoverDownloaded,
+    VideoDownloaded,
+    AudioDownloaded,
+    RemuxFailed,
+    TempKept,
+    StreamsSavedSeparately,
+    FilesSaved,
+    Muxing,
+    MuxFailed,
+    MuxedTo,
+    Muxed,
+    Verifying,
+    VerifyFailed,
+    Verified,
+    DurationMismatch,
+    ContactSheetSaved,
+    DanmakuSaved,
+    DanmakuMerged,
+    DanmakuMergeSkipped,
+    NoCjkFont,
+    PagesConcatenated,
+    ConcatIncomplete,
+    ConcatResolutionMismatch,
+    ConcatFailed,
+    TagsSaved,
+    SubtitleSaved,
+    NfoSaved,
+    SidecarKept,
+    Interrupted,
+    PartialKept,
+    PartialCleaned,
+    ReusingDownload,
+    UsingCachedInfo,
+    CommentsSaved,
+    ThumbnailSaved,
+    ChecksumMatches,
+    ChecksumChanged,
+    ChecksumKept,
+    ChecksumWritten,
+    Opening,
+    Remuxing,
+    LoginSuccess,
+    LoginSuccessSaved,
+    LoginSaveHint,
+    ScanQrCode,
+    QrCodeUrlHint,
+    CaptchaRequired,
+    CaptchaHint,
+    PhonePrompt,
+    SeccodePrompt,
+    SmsCodePrompt,
+    ConnectionReport,
+    ProxyInUse,
+    NoProxy,
+    HostReachable,
+    HostUnreachable,
+    NoCredentials,
+    CredentialsValid,
+    CredentialsInvalid,
+    LoginCheckFailed,
+    Region,
+    RegionLockLikely,
+    RegionUnknown,
+}
+
+impl Msg {
+    /// 指定语言的消息文本
+    pub fn text(self, lang: Lang) -> &'static str {
+        let (zh, en) = match self {
+            Msg::Error => ("错误：{}", "Error: {}"),
+            Msg::Failed => ("失败：{}", "Failed: {}"),
+            Msg::Cancelled => ("已取消", "Cancelled"),
+            Msg::BatchFound => ("批量下载：找到 {} 个视频", "Batch download: {} video(s) found"),
+            Msg::MoreVideos => (
+                "... 还有 {} 个视频，使用 --info-all 全部列出",
+                "... {} more video(s), use --info-all to list them all",
+            ),
+            Msg::Processing => ("正在处理：{}", "Processing: {}"),
+            Msg::BatchDone => ("全部 {} 个视频下载完成！", "All {} video(s) downloaded successfully!"),
+            Msg::AllDone => ("全部下载完成！", "All downloads completed successfully!"),
+            Msg::SkippedBySize => ("已跳过：预估大小 {}", "Skipped: estimated size {}"),
+            Msg::SkippedExists => ("已跳过（输出文件已存在）：{}", "Skipped (output exists): {}"),
+            Msg::LoginLostAbort => (
+                "处理 {} 个视频后登录已失效，中止下载",
+                "Login lost after {} video(s), aborting",
+            ),
+            Msg::LoginLostWarn => (
+                "处理 {} 个视频后登录已失效，剩余视频可能只能下载较低清晰度",
+                "Login lost after {} video(s), the remaining videos may only get lower qualities",
+            ),
+            Msg::PageFailed => ("P{} 失败：{}", "P{} failed: {}"),
+            Msg::StreamsFailed => ("获取流信息失败：{}", "Failed to get streams: {}"),
+            Msg::BatchSummary => ("批量下载摘要：", "Batch summary:"),
+            Msg::SummaryVideos => ("视频数：{}", "Videos: {}"),
+            Msg::SummaryDuration => ("总时长：{}", "Total duration: {}"),
+            Msg::SummarySize => ("预估大小：{}", "Estimated size: {}"),
+            Msg::SummaryOutput => ("输出位置：{}", "Output: {}"),
+            Msg::RunSummary => ("下载摘要：", "Download summary:"),
+            Msg::RunDownloaded => ("已下载：{} 个视频，{} 个分P", "Downloaded: {} video(s), {} page(s)"),
+            Msg::RunTotalSize => ("总大小：{}", "Total size: {}"),
+            Msg::RunElapsed => ("用时：{}", "Time: {}"),
+            Msg::RunAverageSpeed => ("平均速度：{}/s", "Average speed: {}/s"),
+            Msg::RunSkipped => ("已跳过：{} 个分P", "Skipped: {} page(s)"),
+            Msg::RunFailed => ("失败：{} 个分P", "Failed: {} page(s)"),
+            Msg::SizeFromFirstVideo => (
+                "约 {}（按第一个视频的流估算）",
+                "~{} (based on the first video's streams)",
+            ),
+            Msg::Unknown => ("未知", "unknown"),
+            Msg::Proceed => ("是否继续？", "Proceed?"),
+            Msg::VideoInformation => ("视频信息：", "Video Information:"),
+            Msg::Title => ("标题：{}", "Title: {}"),
+            Msg::Uploader => ("UP主：{}", "Uploader: {}"),
+            Msg::Pages => ("分P数：{}", "Pages: {}"),
+            Msg::Category => ("分区：{}", "Category: {}"),
+            Msg::Stats => (
+                "数据：{} 播放，{} 点赞，{} 投币，{} 收藏",
+                "Stats: {} views, {} likes, {} coins, {} favorites",
+            ),
+            Msg::Tags => ("标签：{}", "Tags: {}"),
+            Msg::Description => ("简介：{}", "Description: {}"),
+            Msg::SelectVideoQuality => ("选择视频清晰度：", "Select video quality:"),
+            Msg::SelectAudioQuality => ("选择音频质量：", "Select audio quality:"),
+            Msg::VideoQuality => ("视频清晰度", "Video quality"),
+            Msg::AudioQuality => ("音频质量", "Audio quality"),
+            Msg::QualityAuthYes => ("认证：已登录", "Auth: yes"),
+            Msg::QualityAuthNo => ("认证：未登录", "Auth: no"),
+            Msg::QualityListed => ("列出的清晰度：{}", "Listed:       {}"),
+            Msg::QualityDownloadable => ("可下载：{}", "Downloadable: {}"),
+            Msg::QualityLocked => ("需要更高权限：{}", "Locked:       {}"),
+            Msg::QualityMax => ("最高清晰度：{}", "Max quality:  {}"),
+            Msg::Downloading => ("正在下载：P{} - {}", "Downloading: P{} - {}"),
+            Msg::AudioLanguages => ("音轨语言：", "Audio languages:"),
+            Msg::ExtraAudioNeedsMkv => (
+                "其他语言的音轨（{}）只能嵌入 MKV，请使用 --merge-output-format mkv；本次只下载原声",
+                "Other audio languages ({}) can only be embedded in MKV (use --merge-output-format mkv); downloading the original audio only",
+            ),
+            Msg::ExtraAudioWithClip => (
+                "--clip 不支持嵌入其他语言的音轨（{}），本次只下载原声",
+                "--clip can't embed other audio languages ({}); downloading the original audio only",
+            ),
+            Msg::NoSubtitles => ("无可用字幕", "No subtitles available"),
+            Msg::RetryingFailures => ("重试上次失败的 {} 项", "Retrying {} failed item(s)"),
+            Msg::FailuresWritten => (
+                "{} 项失败记录已写入：{}（使用 --retry-failed 重试）",
+                "{} failure(s) written to {} (retry with --retry-failed)",
+            ),
+            Msg::PathPreview => ("输出路径预览：", "Output path preview:"),
+            Msg::PathExists => ("已存在", "exists"),
+            Msg::PathDuplicate => ("重名", "duplicate"),
+            Msg::PathPreviewSummary => (
+                "共 {} 个文件，{} 个已存在，{} 个路径重名",
+                "{} file(s), {} already exist, {} duplicated path(s)",
+            ),
+            Msg::Simulated => ("模拟运行（--simulate），未下载：", "Simulated (--simulate), nothing downloaded:"),
+            Msg::SimulatedStream => ("{}：{}（约 {}）", "{}: {} (~{})"),
+            Msg::VideoStream => ("视频", "Video"),
+            Msg::AudioStream => ("音频", "Audio"),
+            Msg::SimulatedFile => ("将写入：{}", "Would write: {}"),
+            Msg::SimulatedSubtitles => ("字幕：{}", "Subtitles: {}"),
+            Msg::SubtitlesFailed => ("获取字幕列表失败：{}", "Failed to get subtitles: {}"),
+            Msg::SubtitleDownloaded => ("字幕已下载：{}", "Subtitle downloaded: {}"),
+            Msg::DanmakuDownloaded => ("弹幕已下载", "Danmaku downloaded"),
+            Msg::CoverDownloaded => ("封面已下载", "Cover downloaded"),
+            Msg::VideoDownloaded => ("视频已下载", "Video downloaded"),
+            Msg::AudioDownloaded => ("音频已下载", "Audio downloaded"),
+            Msg::RemuxFailed => ("转封装失败：{}", "Remuxing failed: {}"),
+            Msg::TempKept => ("临时文件保留在：{}", "Temp files kept at: {}"),
+            Msg::StreamsSavedSeparately => (
+                "音视频流已分别保存（跳过混流）",
+                "Streams saved as separate files (muxing skipped)",
+            ),
+            Msg::FilesSaved => ("文件已保存（跳过混流）", "Files saved (muxing skipped)"),
+            Msg::Muxing => ("正在混流...", "Muxing..."),
+            Msg::MuxFailed => ("混流失败：{}", "Muxing failed: {}"),
+            Msg::MuxedTo => ("已混流到：{}", "Muxed to: {}"),
+            Msg::Muxed => ("混流完成", "Muxed"),
+            Msg::Verifying => ("正在校验...", "Verifying..."),
+            Msg::VerifyFailed => ("校验失败：{}", "Verification failed: {}"),
+            Msg::Verified => ("输出文件校验通过", "Output verified"),
+            Msg::DurationMismatch => (
+                "输出时长与预期不符（预期约 {}s，实际 {}s），下载可能被截断",
+                "Output duration differs from expected (~{}s expected, got {}s), the download may be truncated",
+            ),
+            Msg::ContactSheetSaved => ("缩略图拼图已保存到：{}", "Contact sheet saved to: {}"),
+            Msg::DanmakuSaved => ("弹幕已保存到：{}", "Danmaku saved to: {}"),
+            Msg::DanmakuMerged => (
+                "{} 个分P的弹幕已合并到：{}",
+                "Danmaku of {} page(s) merged into: {}",
+            ),
+            Msg::DanmakuMergeSkipped => (
+                "P{} 时长未知，无法合并弹幕",
+                "Skipped merging danmaku: the duration of P{} is unknown",
+            ),
+            Msg::NoCjkFont => (
+                "未找到中文字体，弹幕和字幕中的中文可能显示为方框，可用 --font 指定字体",
+                "No CJK font found; Chinese text in danmaku and subtitles may render as boxes (use --font to pick one)",
+            ),
+            Msg::PagesConcatenated => ("{} 个分P已拼接为：{}", "{} page(s) concatenated into: {}"),
+            Msg::ConcatIncomplete => (
+                "部分分P未下载（已存在或被跳过），保留分P文件，不拼接",
+                "Some pages were not downloaded (existing or skipped); keeping separate files",
+            ),
+            Msg::ConcatResolutionMismatch => (
+                "各分P分辨率不一致，无法无损拼接，保留分P文件",
+                "Pages have different resolutions and can't be joined losslessly; keeping separate files",
+            ),
+            Msg::ConcatFailed => ("拼接失败，保留分P文件：{}", "Concatenation failed, keeping separate files: {}"),
+            Msg::TagsSaved => ("标签已保存到：{}", "Tags saved to: {}"),
+            Msg::SubtitleSaved => ("字幕已保存到：{}", "Subtitle saved to: {}"),
+            Msg::NfoSaved => ("NFO已保存到：{}", "NFO saved to: {}"),
+            Msg::SidecarKept => ("已保留已有文件：{}", "Kept existing file: {}"),
+            Msg::Interrupted => ("收到中断信号，已停止下载", "Interrupted, download stopped"),
+            Msg::PartialKept => (
+                "已保留未完成的下载，重新运行相同的命令即可继续：{}",
+                "Kept the unfinished download, run the same command again to resume: {}",
+            ),
+            Msg::PartialCleaned => ("已删除未完成的下载：{}", "Removed the unfinished download: {}"),
+            Msg::ReusingDownload => ("使用上次已下载的文件：{}", "Using previously downloaded file: {}"),
+            Msg::UsingCachedInfo => (
+                "使用缓存的视频信息（不带 --cache-ttl 运行可重新获取）",
+                "Using cached video info (run without --cache-ttl to refetch)",
+            ),
+            Msg::CommentsSaved => ("{} 条评论已保存到：{}", "{} comment(s) saved to: {}"),
+            Msg::ThumbnailSaved => ("封面已保存到：{}", "Thumbnail saved to: {}"),
+            Msg::ChecksumMatches => (
+                "校验和与上次下载一致：{}",
+                "Checksum matches previous download: {}",
+            ),
+            Msg::ChecksumChanged => (
+                "校验和与上次下载不同，已更新：{}",
+                "Checksum changed since previous download, updated: {}",
+            ),
+            Msg::ChecksumKept => (
+                "校验和与上次下载不同，已保留原校验文件：{}",
+                "Checksum differs from previous download, keeping existing checksum file: {}",
+            ),
+            Msg::ChecksumWritten => ("校验和已写入：{}", "Checksum written: {}"),
+            Msg::Opening => ("正在打开 {}", "Opening {}"),
+            Msg::Remuxing => ("正在混流 {} + {}", "Remuxing {} + {}"),
+            Msg::LoginSuccess => ("登录成功！", "Login successful!"),
+            Msg::LoginSuccessSaved => (
+                "登录成功！凭证已保存到配置文件。",
+                "Login successful! Credentials saved to the config file.",
+            ),
+            Msg::LoginSaveHint => (
+                "提示：使用 --config-file 参数可以保存凭证以供后续使用。",
+                "Tip: use --config-file to save the credentials for later runs.",
+            ),
+            Msg::ScanQrCode => (
+                "请使用手机扫描以下二维码：",
+                "Scan the QR code below with the Bilibili app:",
+            ),
+            Msg::QrCodeUrlHint => (
+                "无法扫描时，请将以下链接复制到二维码生成器：",
+                "If the code can't be scanned, paste this link into a QR code generator:",
+            ),
+            Msg::CaptchaRequired => (
+                "发送短信前需要完成极验（geetest）人机验证：",
+                "A geetest captcha must be solved before the SMS code is sent:",
+            ),
+            Msg::CaptchaHint => (
+                "请使用以上参数在极验验证工具中完成验证，然后输入得到的结果。",
+                "Solve it with the parameters above in a geetest tool, then enter the result.",
+            ),
+            Msg::PhonePrompt => ("手机号", "Phone number"),
+            Msg::SeccodePrompt => (
+                "seccode（留空使用 validate|jordan）",
+                "seccode (leave empty to use validate|jordan)",
+            ),
+            Msg::SmsCodePrompt => ("短信验证码", "SMS code"),
+            Msg::ConnectionReport => ("连接诊断：", "Connection report:"),
+            Msg::ProxyInUse => ("代理：{}", "Proxy: {}"),
+            Msg::NoProxy => ("代理：未使用", "Proxy: none"),
+            Msg::HostReachable => ("{}：{} ms", "{}: {} ms"),
+            Msg::HostUnreachable => ("{}：无法连接（{}）", "{}: unreachable ({})"),
+            Msg::NoCredentials => ("登录：未提供凭证", "Login: no credentials"),
+            Msg::CredentialsValid => ("登录：凭证有效", "Login: credentials valid"),
+            Msg::CredentialsInvalid => (
+                "登录：凭证已失效，请重新登录",
+                "Login: credentials expired, please log in again",
+            ),
+            Msg::LoginCheckFailed => ("登录：检查失败（{}）", "Login: check failed ({})"),
+            Msg::Region => ("地区：{}（IP {}）", "Region: {} (IP {})"),
+            Msg::RegionLockLikely => (
+                "当前不在中国大陆，仅限大陆播放的内容可能受区域限制（可尝试 --use-intl-api）",
+                "Outside mainland China: mainland-only content may be region-locked (try --use-intl-api)",
+            ),
+            Msg::RegionUnknown => ("地区：查询失败", "Region: lookup failed"),
+        };
+
+        match lang {
+            Lang::Zh => zh,
+            Lang::En => en,
+        }
+    }
+}
+
+/// 当前语言的消息文本
+pub fn t(msg: Msg) -> &'static str {
+    msg.text(lang())
+}
+
+/// 当前语言的消息文本，依次用 `args` 替换 `{}` 占位
+pub fn tf(msg: Msg, args: &[&dyn Display]) -> String {
+    fill(t(msg), args)
+}
+
+/// 依次用 `args` 替换模板中的 `{}`，多余的占位保持原样
+pub fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template;
+    while let Some(pos) = rest.find("{}") {
+        result.push_str(&rest[..pos]);
+        match args.next() {
+            Some(arg) => result.push_str(&arg.to_string()),
+            None => result.push_str("{}"),
+        }
+        rest = &rest[pos + 2..];
+    }
+    result.push_str(rest);
+    result
+}
@@ -1,4 +1,12 @@
+pub mod cache;
+pub mod checksum;
 pub mod config;
 pub mod console;
+pub mod cookie;
 pub mod file;
+pub mod font;
 pub mod http;
+pub mod i18n;
+pub mod player;
+pub mod signal;
+pub mod throttle;
@@ -0,0 +1,42 @@
+use crate::error::{DownloaderError, Result};
+use std::ffi::OsString;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// 打开文件使用的命令：指定播放器时使用播放器命令（可带参数），否则使用系统默认程序
+///
+/// 返回 (程序, 参数)，文件路径作为最后一个参数
+pub fn build_open_command(path: &Path, player: Option<&str>) -> (String, Vec<OsString>) {
+    if let Some(player) = player.map(str::trim).filter(|p| !p.is_empty()) {
+        let mut parts = player.split_whitespace();
+        let program = parts.next().unwrap_or(player).to_string();
+        let mut args: Vec<OsString> = parts.map(OsString::from).collect();
+        args.push(path.into());
+        return (program, args);
+    }
+
+    if cfg!(target_os = "windows") {
+        // start 的第一个带引号参数是窗口标题，需要传入空标题
+        let args = vec!["/C".into(), "start".into(), "".into(), path.into()];
+        ("cmd".to_string(), args)
+    } else if cfg!(target_os = "macos") {
+        ("open".to_string(), vec![path.into()])
+    } else {
+        ("xdg-open".to_string(), vec![path.into()])
+    }
+}
+
+/// 使用播放器或系统默认程序打开文件，不等待播放器退出
+pub fn open_file(path: &Path, player: Option<&str>) -> Result<()> {
+    let (program, args) = build_open_command(path, player);
+    tracing::debug!("Opening {:?} with {} {:?}", path, program, args);
+
+    Command::new(&program)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| DownloaderError::Config(format!("Failed to run {}: {}", program, e)))
+}
@@ -0,0 +1,63 @@
+// 中断信号处理：Ctrl-C（SIGINT）与 systemd 停止服务时发送的 SIGTERM
+
+/// 收到中断信号时如何处理未完成的下载（`--on-interrupt`）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InterruptPolicy {
+    /// 保留临时目录中已下载的流和 `.part` 文件，重新运行相同命令时继续
+    #[default]
+    Resume,
+    /// 删除未完成的临时文件，下次重新开始
+    Clean,
+}
+
+/// 收到的中断信号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// Ctrl-C（SIGINT）
+    Interrupt,
+    /// SIGTERM（如 `systemctl stop`）
+    Terminate,
+}
+
+impl Signal {
+    /// 按惯例使用 128 + 信号编号作为退出码
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Signal::Interrupt => 130,
+            Signal::Terminate => 143,
+        }
+    }
+}
+
+/// 等待 Ctrl-C 或 SIGTERM（仅 Unix），返回先收到的信号
+///
+/// 无法注册信号处理时对应的分支永不完成，不影响正常下载
+pub async fn wait_for_signal() -> Signal {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::debug!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut stream) => {
+                stream.recv().await;
+            }
+            Err(e) => {
+                tracing::debug!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => Signal::Interrupt,
+        _ = terminate => Signal::Terminate,
+    }
+}
@@ -0,0 +1,50 @@
+//! API 请求节流
+//!
+//! 批量解析（收藏夹、UP主空间、系列等）会连续发起大量元数据请求，
+//! 容易触发 -352/-412 风控。这里限制同时进行的 API 请求数，并在批量循环中
+//! 的相邻请求之间加入可配置的间隔。视频文件下载不受影响。
+
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+/// 默认的最大并发 API 请求数
+pub const DEFAULT_MAX_CONCURRENT: usize = 4;
+/// 默认的批量请求间隔（毫秒）
+pub const DEFAULT_DELAY_MS: u64 = 200;
+
+#[derive(Debug)]
+pub struct ApiThrottle {
+    permits: Semaphore,
+    delay: Duration,
+}
+
+impl ApiThrottle {
+    pub fn new(max_concurrent: usize, delay: Duration) -> Self {
+        Self {
+            permits: Semaphore::new(max_concurrent.max(1)),
+            delay,
+        }
+    }
+
+    /// 获取一个并发许可，许可释放前占用一个 API 请求名额
+    pub async fn acquire(&self) -> SemaphorePermit<'_> {
+        // 信号量不会被关闭，acquire 只会在关闭时失败
+        self.permits.acquire().await.expect("API semaphore closed")
+    }
+
+    /// 批量循环中两次请求之间的等待
+    pub async fn pause(&self) {
+        if !self.delay.is_zero() {
+            tokio::time::sleep(self.delay).await;
+        }
+    }
+}
+
+impl Default for ApiThrottle {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_MAX_CONCURRENT,
+            Duration::from_millis(DEFAULT_DELAY_MS),
+        )
+    }
+}
@@ -0,0 +1,104 @@
+// 下载选项测试：分P选择
+use rvd::app::DownloadOptions;
+use rvd::error::{exit_code, DownloaderError};
+use rvd::types::{BilibiliExtra, ExtraData, Page, VideoInfo};
+
+fn video_with_pages(count: usize) -> VideoInfo {
+    VideoInfo {
+        id: "BV1xx411c7mD".to_string(),
+        aid: 170001,
+        title: "测试视频".to_string(),
+        description: String::new(),
+        duration: 0,
+        uploader: String::new(),
+        uploader_mid: String::new(),
+        upload_date: String::new(),
+        cover_url: String::new(),
+        pages: (1..=count)
+            .map(|number| Page {
+                number,
+                title: format!("P{}", number),
+                cid: (1000 + number).to_string(),
+                duration: 60,
+                ep_id: None,
+            })
+            .collect(),
+        is_bangumi: false,
+        ep_id: None,
+        extra_data: None,
+    }
+}
+
+fn numbers(pages: &[Page]) -> Vec<usize> {
+    pages.iter().map(|p| p.number).collect()
+}
+
+#[test]
+fn test_select_all_pages_by_default() {
+    let pages = DownloadOptions::default()
+        .select_pages(&video_with_pages(3))
+        .unwrap();
+    assert_eq!(numbers(&pages), vec![1, 2, 3]);
+}
+
+#[test]
+fn test_select_pages_from_option_and_url() {
+    let options = DownloadOptions {
+        pages: Some(vec![3, 1, 9]),
+        ..Default::default()
+    };
+    // 不存在的分P被跳过
+    assert_eq!(numbers(&options.select_pages(&video_with_pages(3)).unwrap()), vec![3, 1]);
+
+    let options = DownloadOptions {
+        url: Some("https://www.bilibili.com/video/BV1xx411c7mD?p=2".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(numbers(&options.select_pages(&video_with_pages(3)).unwrap()), vec![2]);
+}
+
+#[test]
+fn test_video_without_pages_is_an_error() {
+    let err = DownloadOptions::default()
+        .select_pages(&video_with_pages(0))
+        .unwrap_err();
+    assert!(matches!(err, DownloaderError::NoPages(_)), "{:?}", err);
+    assert!(err.to_string().contains("BV1xx411c7mD"));
+    assert_eq!(err.exit_code(), exit_code::NOT_FOUND);
+}
+
+#[test]
+fn test_selection_without_matching_pages_is_an_error() {
+    let options = DownloadOptions {
+        pages: Some(vec![5, 6]),
+        ..Default::default()
+    };
+    let err = options.select_pages(&video_with_pages(3)).unwrap_err();
+    assert!(matches!(err, DownloaderError::NoPages(_)), "{:?}", err);
+}
+
+#[test]
+fn test_needs_full_pages_for_placeholder_first_page() {
+    // 收藏夹中的多P视频只带有占位的P1（没有分P标题和时长）
+    let mut video = video_with_pages(1);
+    video.extra_data = Some(ExtraData::Bilibili(BilibiliExtra {
+        page_count: 3,
+        ..Default::default()
+    }));
+
+    let first_only = DownloadOptions {
+        pages: Some(vec![1]),
+        ..Default::default()
+    };
+    assert!(first_only.needs_full_pages(&video));
+
+    let second = DownloadOptions {
+        pages: Some(vec![1, 2]),
+        ..Default::default()
+    };
+    assert!(second.needs_full_pages(&video));
+    assert!(DownloadOptions::default().needs_full_pages(&video));
+
+    // 已有全部分P时不需要再获取
+    assert!(!DownloadOptions::default().needs_full_pages(&video_with_pages(3)));
+}
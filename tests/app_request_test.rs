@@ -0,0 +1,86 @@
+// 库接口 DownloadRequest 测试
+use rvd::{DownloadRequest, DownloaderError};
+
+#[tokio::test]
+async fn test_download_request_unsupported_url() {
+    // 不支持的平台在发起任何网络请求前就应返回错误（未安装FFmpeg时会更早失败）
+    let result = DownloadRequest::new("https://example.com/video/123")
+        .quality("1080P")
+        .output("out/<videoTitle>")
+        .skip_cover(true)
+        .build()
+        .await;
+
+    assert!(matches!(
+        result,
+        Err(DownloaderError::UnsupportedPlatform(_)) | Err(DownloaderError::FFmpegNotFound)
+    ));
+}
+
+#[tokio::test]
+async fn test_download_request_rejects_flag_as_url() {
+    let result = DownloadRequest::new("--not-a-url").build().await;
+    assert!(result.is_err());
+}
+
+/// 只响应 `-version` 的假FFmpeg，用于在未安装FFmpeg时创建 Orchestrator
+#[cfg(unix)]
+fn fake_ffmpeg(dir: &std::path::Path) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+    let path = dir.join("ffmpeg");
+    std::fs::write(&path, "#!/bin/sh\necho 'ffmpeg version 6.0'\n").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
+
+#[cfg(unix)]
+#[test]
+fn test_output_path_hook_replaces_template() {
+    use rvd::app::{DownloadOptions, Orchestrator};
+    use rvd::core::muxer::MergeOutputFormat;
+    use rvd::types::{Page, VideoInfo};
+    use rvd::utils::config::Config;
+    use std::path::PathBuf;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let options = DownloadOptions {
+        ffmpeg_path: Some(fake_ffmpeg(temp_dir.path())),
+        output: Some("downloads/<videoTitle>".to_string()),
+        merge_output_format: Some(MergeOutputFormat::Mkv),
+        ..DownloadOptions::default()
+    };
+    let orchestrator = Orchestrator::new(Config::default(), &options)
+        .unwrap()
+        .with_output_path_hook(|video, page| {
+            format!("library/{}/{}-P{}.mp4", video.uploader_mid, video.id, page.number).into()
+        });
+
+    let page = Page {
+        number: 2,
+        title: "P2".to_string(),
+        cid: "654321".to_string(),
+        duration: 60,
+        ep_id: None,
+    };
+    let video = VideoInfo {
+        id: "BV1xx411c7mD".to_string(),
+        aid: 170001,
+        title: "测试视频".to_string(),
+        description: String::new(),
+        duration: 60,
+        uploader: "测试UP主".to_string(),
+        uploader_mid: "12345".to_string(),
+        upload_date: "2024-01-01".to_string(),
+        cover_url: String::new(),
+        pages: vec![page.clone()],
+        is_bangumi: false,
+        ep_id: None,
+        extra_data: None,
+    };
+
+    // 回调给出的路径原样使用，不受输出模板和 --merge-output-format 影响
+    assert_eq!(
+        orchestrator.planned_output_path(&video, &page, "1080P", "avc", &options),
+        PathBuf::from("library/12345/BV1xx411c7mD-P2.mp4")
+    );
+}
@@ -8,12 +8,12 @@
  */
 
 use async_trait::async_trait;
-use rvd::auth::login::LoginManager;
-use rvd::auth::types::{Credentials, LoginStatus, QRCodeData};
+use rvd::auth::login::{LoginManager, MAX_QRCODE_ATTEMPTS};
+use rvd::auth::types::{CaptchaData, CaptchaResult, Credentials, LoginStatus, QRCodeData};
 use rvd::auth::AuthProvider;
 use rvd::error::Result;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 // Mock provider for testing
 struct MockAuthProvider {
@@ -165,6 +165,156 @@ async fn test_login_manager_with_scanned_status() {
     assert!(result.is_ok(), "Login should succeed even with Scanned status");
 }
 
+// Mock provider whose first QR codes expire before being scanned
+struct ExpiringQRCodeProvider {
+    qrcode_count: Arc<AtomicUsize>,
+    expired_qrcodes: usize,
+}
+
+#[async_trait]
+impl AuthProvider for ExpiringQRCodeProvider {
+    async fn request_qrcode(&self) -> Result<QRCodeData> {
+        let count = self.qrcode_count.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(QRCodeData {
+            url: "https://www.bilibili.com/test".to_string(),
+            key: format!("test_key_{}", count),
+        })
+    }
+
+    async fn poll_login_status(&self, key: &str) -> Result<LoginStatus> {
+        let count = self.qrcode_count.load(Ordering::SeqCst);
+        assert_eq!(key, format!("test_key_{}", count), "Should poll the latest QR code");
+
+        if count <= self.expired_qrcodes {
+            Ok(LoginStatus::Expired)
+        } else {
+            Ok(LoginStatus::Success(Credentials {
+                cookie: Some("test_cookie".to_string()),
+                access_token: None,
+                refresh_token: None,
+                expires_at: None,
+                mid: None,
+            }))
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_login_manager_regenerates_expired_qrcode() {
+    let qrcode_count = Arc::new(AtomicUsize::new(0));
+    let provider = Box::new(ExpiringQRCodeProvider {
+        qrcode_count: qrcode_count.clone(),
+        expired_qrcodes: 1,
+    });
+    let manager = LoginManager::new(provider);
+
+    let result = manager.perform_qr_login().await;
+    assert!(result.is_ok(), "Login should succeed with a fresh QR code");
+    assert_eq!(qrcode_count.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_login_manager_gives_up_after_max_qrcodes() {
+    let qrcode_count = Arc::new(AtomicUsize::new(0));
+    let provider = Box::new(ExpiringQRCodeProvider {
+        qrcode_count: qrcode_count.clone(),
+        expired_qrcodes: usize::MAX,
+    });
+    let manager = LoginManager::new(provider);
+
+    let result = manager.perform_qr_login().await;
+    assert!(result.is_err(), "Login should fail once every QR code expired");
+    assert_eq!(qrcode_count.load(Ordering::SeqCst), MAX_QRCODE_ATTEMPTS);
+}
+
+// Mock provider that records the SMS login flow
+struct SmsProvider {
+    sent_to: Arc<Mutex<Option<(u32, String, String)>>>,
+}
+
+#[async_trait]
+impl AuthProvider for SmsProvider {
+    async fn request_qrcode(&self) -> Result<QRCodeData> {
+        unreachable!("SMS login should not request a QR code")
+    }
+
+    async fn poll_login_status(&self, _key: &str) -> Result<LoginStatus> {
+        unreachable!("SMS login should not poll")
+    }
+
+    async fn request_captcha(&self) -> Result<CaptchaData> {
+        Ok(CaptchaData {
+            gt: "gt".to_string(),
+            challenge: "challenge".to_string(),
+            token: "token".to_string(),
+        })
+    }
+
+    async fn send_sms_code(
+        &self,
+        cid: u32,
+        tel: &str,
+        captcha: &CaptchaData,
+        result: &CaptchaResult,
+    ) -> Result<String> {
+        assert_eq!(captcha.token, "token");
+        assert_eq!(result.challenge, "challenge");
+        *self.sent_to.lock().unwrap() = Some((cid, tel.to_string(), result.seccode.clone()));
+        Ok("captcha_key".to_string())
+    }
+
+    async fn login_with_sms(
+        &self,
+        _cid: u32,
+        _tel: &str,
+        code: &str,
+        captcha_key: &str,
+    ) -> Result<Credentials> {
+        assert_eq!(captcha_key, "captcha_key");
+        if code != "123456" {
+            return Err(rvd::auth::AuthError::LoginFailed("wrong code".to_string()).into());
+        }
+        Ok(Credentials {
+            cookie: Some("SESSDATA=abc".to_string()),
+            access_token: None,
+            refresh_token: None,
+            expires_at: None,
+            mid: None,
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_login_manager_sms_login() {
+    let sent_to = Arc::new(Mutex::new(None));
+    let manager = LoginManager::new(Box::new(SmsProvider {
+        sent_to: sent_to.clone(),
+    }));
+
+    // 依次回答 validate、seccode（留空）、短信验证码
+    let mut answers = vec!["123456", "", "validate_value"];
+    let result = manager
+        .perform_sms_login(86, "13800000000", |_| Ok(answers.pop().unwrap().to_string()))
+        .await;
+
+    let credentials = result.expect("SMS login should succeed");
+    assert_eq!(credentials.cookie, Some("SESSDATA=abc".to_string()));
+    assert_eq!(
+        *sent_to.lock().unwrap(),
+        Some((86, "13800000000".to_string(), "validate_value|jordan".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn test_login_manager_sms_not_supported() {
+    // 未实现短信登录的提供者返回不支持
+    let manager = LoginManager::new(Box::new(MockAuthProvider::new_success(0)));
+    let result = manager
+        .perform_sms_login(86, "13800000000", |_| Ok(String::new()))
+        .await;
+    assert!(result.is_err());
+}
+
 // Mock provider that simulates network errors
 struct NetworkErrorProvider {
     error_count: Arc<AtomicUsize>,
@@ -31,6 +31,101 @@ fn test_parse_quality_priority_default() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
 
     let quality = cli.parse_quality_priority();
@@ -67,6 +162,101 @@ fn test_parse_quality_priority_custom() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
 
     let quality = cli.parse_quality_priority();
@@ -103,6 +293,101 @@ fn test_parse_codec_priority_default() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
 
     let codec = cli.parse_codec_priority();
@@ -139,6 +424,101 @@ fn test_parse_codec_priority_custom() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
 
     let codec = cli.parse_codec_priority();
@@ -175,6 +555,101 @@ fn test_parse_pages_none() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
 
     let pages = cli.parse_pages();
@@ -211,6 +686,101 @@ fn test_parse_pages_all() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
 
     let pages = cli.parse_pages();
@@ -247,6 +817,101 @@ fn test_parse_pages_single() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
 
     let pages = cli.parse_pages();
@@ -283,6 +948,101 @@ fn test_parse_pages_multiple() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
 
     let pages = cli.parse_pages();
@@ -319,6 +1079,101 @@ fn test_parse_pages_range() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
 
     let pages = cli.parse_pages();
@@ -355,6 +1210,101 @@ fn test_parse_pages_mixed() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
 
     let pages = cli.parse_pages();
@@ -392,6 +1342,101 @@ fn test_cli_api_mode_tv() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
     
     assert!(cli.use_tv_api);
@@ -429,6 +1474,101 @@ fn test_cli_api_mode_app() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
     
     assert!(cli.use_app_api);
@@ -466,6 +1606,101 @@ fn test_cli_api_mode_international() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
     
     assert!(cli.use_intl_api);
@@ -504,6 +1739,101 @@ fn test_cli_danmaku_download() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
     
     assert!(cli.download_danmaku);
@@ -539,6 +1869,101 @@ fn test_cli_danmaku_format_xml() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
     
     assert_eq!(cli.danmaku_format, "xml");
@@ -576,6 +2001,101 @@ fn test_cli_danmaku_format_ass() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
     
     assert_eq!(cli.danmaku_format, "ass");
@@ -614,7 +2134,846 @@ fn test_cli_interactive_flag() {
         aria2c_path: None,
         aria2c_args: None,
         use_mp4box: false,
+        remux: None,
+        remux_subtitle: Vec::new(),
+        remux_chapters: None,
+        remux_cover: None,
+        verify: false,
+        checksum: false,
+        include_extras: false,
+        this_episode_only: false,
+        cookie_jar: None,
+        audio_lang: None,
+        list_audio_languages: false,
+        subtitle_lang: None,
+        reencode: None,
+        reencode_audio: None,
+        crf: None,
+        video_bitrate: None,
+        write_thumbnail: false,
+        thumbnail_size: None,
+        thumbnail_format: None,
+        profile: None,
+        simulate_quality: false,
+        simulate: false,
+        on_collision: "overwrite".to_string(),
+        api_delay_ms: 200,
+        format_sort: None,
+        fps: None,
+        keep_temp: false,
+        gen_contact_sheet: false,
+        contact_sheet_cols: 4,
+        contact_sheet_rows: 4,
+        merge_output_format: None,
+        remux_each: false,
+        page_template: None,
+        confirm: false,
+        yes: false,
+        concurrent_pages: 1,
+        write_tags: false,
+        since: None,
+        until: None,
+        buffer_size: 1048576,
+        login_qr_ascii: false,
+        login_sms: false,
+        phone: None,
+        country_code: 86,
+        list_qualities_table: false,
+        info_all: false,
+        max_filesize: None,
+        min_filesize: None,
+        batch_file: None,
+        continue_on_error: false,
+        play_after: false,
+        player: None,
+        open_first: false,
+        open_last: false,
+        cookie_check_interval: None,
+        on_login_lost: "warn".to_string(),
+        lang: None,
+        organize_by: "none".to_string(),
+        prefer_intl_cdn: false,
+        test_connection: false,
+        download_comments: false,
+        comment_replies: false,
+        max_comments: None,
+        embed_metadata: false,
+        no_embed_chapters: false,
+        no_embed_cover: false,
+        no_embed_subs: false,
+        no_embed_tags: false,
+        no_embed_nfo: false,
+        clip: None,
+        aria2c_rpc: None,
+        aria2c_rpc_secret: None,
+        aria2c_split: None,
+        aria2c_max_connection_per_server: None,
+        aria2c_min_split_size: None,
+        overwrite_policy: "follow".to_string(),
+        page_number_offset: 0,
+        on_interrupt: "resume".to_string(),
+        cache_ttl: None,
+        danmaku_merge: false,
+        danmaku_max: None,
+        danmaku_per_second: None,
+        no_danmaku_limit: false,
+        concat_pages: false,
+        force_overwrite_partial: false,
+        list_subtitles: false,
+        video_threads: None,
+        audio_threads: None,
+        force_ipv4: false,
+        force_ipv6: false,
+        preview_paths: false,
+        failures_file: None,
+        retry_failed: None,
+        font: None,
+        keep_raw_danmaku: false,
     };
     
     assert!(cli.interactive);
 }
+
+#[test]
+fn test_parse_subtitle_langs() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD"]).unwrap();
+    assert_eq!(cli.parse_subtitle_langs(), None);
+
+    let cli =
+        Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--subtitle-lang", "zh-Hans, en"]).unwrap();
+    assert_eq!(
+        cli.parse_subtitle_langs(),
+        Some(vec!["zh-Hans".to_string(), "en".to_string()])
+    );
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--subtitle-lang", "all"]).unwrap();
+    assert_eq!(cli.parse_subtitle_langs(), None);
+}
+
+#[test]
+fn test_concurrent_pages_range() {
+    use clap::Parser;
+
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--concurrent-pages", "0"]).is_err());
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--concurrent-pages", "17"]).is_err());
+}
+
+#[test]
+fn test_buffer_size_range() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD"]).unwrap();
+    assert_eq!(cli.buffer_size, 1048576);
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--buffer-size", "65536"]).unwrap();
+    assert_eq!(cli.buffer_size, 65536);
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--buffer-size", "1024"]).is_err());
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--buffer-size", "134217728"]).is_err());
+}
+
+#[test]
+fn test_batch_file() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from(["rvd", "--batch-file", "urls.txt", "--continue-on-error"]).unwrap();
+    assert_eq!(cli.url, None);
+    assert_eq!(cli.batch_file, Some("urls.txt".to_string()));
+    assert!(cli.continue_on_error);
+
+    let cli = Cli::try_parse_from(["rvd", "-a", "-"]).unwrap();
+    assert_eq!(cli.batch_file, Some("-".to_string()));
+
+    // URL和批量文件不能同时使用
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "-a", "urls.txt"]).is_err());
+}
+
+#[test]
+fn test_batch_entry_overrides() {
+    use rvd::app::DownloadOptions;
+
+    let global = DownloadOptions {
+        format_sort: Some("res".parse().unwrap()),
+        ..Default::default()
+    };
+    let overrides = |pairs: &[(&str, &str)]| -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    };
+
+    let options = global
+        .with_overrides(&overrides(&[
+            ("quality", "4K,1080P"),
+            ("pages", "1-3"),
+            ("skip_subtitle", "true"),
+            ("audio-lang", "ja"),
+        ]))
+        .unwrap();
+    assert_eq!(options.quality_priority, vec!["4K".to_string(), "1080P".to_string()]);
+    assert!(options.format_sort.is_none());
+    assert_eq!(options.pages, Some(vec![1, 2, 3]));
+    assert!(options.skip_subtitle);
+    assert_eq!(options.audio_lang, Some("ja".to_string()));
+
+    // 未覆盖的选项保持不变
+    assert!(global.with_overrides(&[]).unwrap().format_sort.is_some());
+
+    assert!(global.with_overrides(&overrides(&[("fps", "sixty")])).is_err());
+    assert!(global.with_overrides(&overrides(&[("skip-cover", "maybe")])).is_err());
+    assert!(global.with_overrides(&overrides(&[("unknown", "1")])).is_err());
+
+    // 写错的分P选择不能退化为下载全部分P
+    assert!(global.with_overrides(&overrides(&[("pages", "1-3x")])).is_err());
+    assert!(global.with_overrides(&overrides(&[("pages", "")])).is_err());
+    let options = global.with_overrides(&overrides(&[("pages", "ALL")])).unwrap();
+    assert_eq!(options.pages, None);
+}
+
+#[test]
+fn test_filesize_limits() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    let cli = Cli::try_parse_from([
+        "rvd",
+        "https://space.bilibili.com/12345",
+        "--min-filesize",
+        "10M",
+        "--max-filesize",
+        "2G",
+    ])
+    .unwrap();
+    let options = DownloadOptions::from(&cli);
+    assert_eq!(options.min_filesize, Some(10 * 1024 * 1024));
+    assert_eq!(options.max_filesize, Some(2 * 1024 * 1024 * 1024));
+    assert!(options.filesize_in_range(100 * 1024 * 1024));
+    assert!(!options.filesize_in_range(1024 * 1024));
+    assert!(!options.filesize_in_range(3 * 1024 * 1024 * 1024));
+    assert!(DownloadOptions::default().filesize_in_range(0));
+
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--max-filesize", "big"]).is_err());
+}
+
+#[test]
+fn test_since_until_dates() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from([
+        "rvd",
+        "https://space.bilibili.com/12345",
+        "--since",
+        "2024-01-01",
+        "--until",
+        "2024-06-30",
+    ])
+    .unwrap();
+    assert_eq!(cli.since, Some("2024-01-01".to_string()));
+    assert_eq!(cli.until, Some("2024-06-30".to_string()));
+
+    for date in ["2024-1-01", "2024-13-01", "2024/01/01", "yesterday"] {
+        assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--since", date]).is_err());
+    }
+}
+
+#[test]
+fn test_download_options_from_cli() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD"]).unwrap();
+    let options = DownloadOptions::from(&cli);
+    let defaults = DownloadOptions::default();
+    assert_eq!(options.url.as_deref(), Some("BV1xx411c7mD"));
+    assert_eq!(options.quality_priority, defaults.quality_priority);
+    assert_eq!(options.codec_priority, defaults.codec_priority);
+    assert_eq!(options.threads, defaults.threads);
+    assert!(!options.keep_temp);
+    assert_eq!(options.concurrent_pages, 1);
+    // 未指定API模式时允许自动回退
+    assert!(options.api_mode.is_none());
+
+    let cli = Cli::try_parse_from([
+        "rvd",
+        "BV1xx411c7mD",
+        "-q",
+        "4K,1080P",
+        "-p",
+        "1-3",
+        "--use-tv-api",
+        "--reencode",
+        "avc",
+        "--keep-temp",
+        "--concurrent-pages",
+        "3",
+    ])
+    .unwrap();
+    let options = DownloadOptions::from(&cli);
+    assert_eq!(options.quality_priority, vec!["4K".to_string(), "1080P".to_string()]);
+    assert_eq!(options.pages, Some(vec![1, 2, 3]));
+    assert!(matches!(
+        options.api_mode,
+        Some(rvd::platform::bilibili::ApiMode::TV)
+    ));
+    assert_eq!(options.reencode.map(|r| r.video_codec), Some("avc".to_string()));
+    assert!(options.keep_temp);
+    assert_eq!(options.concurrent_pages, 3);
+}
+
+#[test]
+fn test_play_after() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--play-after", "--player", "mpv --fs"])
+        .unwrap();
+    assert!(cli.play_after);
+    assert_eq!(cli.player, Some("mpv --fs".to_string()));
+
+    let cli = Cli::try_parse_from(["rvd", "-a", "urls.txt", "--play-after", "--open-first"]).unwrap();
+    assert!(cli.open_first);
+
+    // 需要 --play-after，且 --open-first/--open-last 互斥
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--player", "mpv"]).is_err());
+    assert!(Cli::try_parse_from([
+        "rvd",
+        "BV1xx411c7mD",
+        "--play-after",
+        "--open-first",
+        "--open-last"
+    ])
+    .is_err());
+}
+
+#[test]
+fn test_cookie_check_interval() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    let cli = Cli::try_parse_from(["rvd", "-a", "urls.txt", "--cookie-check-interval", "20"]).unwrap();
+    let options = DownloadOptions::from(&cli);
+    assert_eq!(options.cookie_check_interval, Some(20));
+    assert!(!options.abort_on_login_lost);
+
+    let cli = Cli::try_parse_from([
+        "rvd",
+        "-a",
+        "urls.txt",
+        "--cookie-check-interval",
+        "5",
+        "--on-login-lost",
+        "abort",
+    ])
+    .unwrap();
+    assert!(DownloadOptions::from(&cli).abort_on_login_lost);
+
+    // 默认关闭，间隔至少为1
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD"]).unwrap();
+    assert_eq!(DownloadOptions::from(&cli).cookie_check_interval, None);
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--cookie-check-interval", "0"]).is_err());
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--on-login-lost", "abort"]).is_err());
+}
+
+#[test]
+fn test_lang() {
+    use clap::{CommandFactory, FromArgMatches};
+
+    let matches = Cli::command().get_matches_from(["rvd", "BV1xx411c7mD", "--lang", "en"]);
+    let cli = Cli::from_arg_matches(&matches).unwrap();
+    assert_eq!(cli.lang, Some("en".to_string()));
+    assert!(Cli::command().try_get_matches_from(["rvd", "BV1xx411c7mD", "--lang", "fr"]).is_err());
+
+    // 命令行未指定时使用配置文件中的语言
+    let config: rvd::utils::config::Config = toml::from_str("lang = \"en\"").unwrap();
+    let matches = Cli::command().get_matches_from(["rvd", "BV1xx411c7mD"]);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap();
+    cli.apply_config_defaults(&config, &matches);
+    assert_eq!(cli.lang, Some("en".to_string()));
+}
+
+#[test]
+fn test_organize_by() {
+    use clap::Parser;
+    use rvd::utils::file::OrganizeBy;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD"]).unwrap();
+    assert_eq!(cli.get_organize_by(), OrganizeBy::None);
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--organize-by", "uploader"]).unwrap();
+    assert_eq!(cli.get_organize_by(), OrganizeBy::Uploader);
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--organize-by", "date"]).unwrap();
+    assert_eq!(cli.get_organize_by(), OrganizeBy::Date);
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--organize-by", "tag"]).is_err());
+}
+
+#[test]
+fn test_prefer_intl_cdn() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD"]).unwrap();
+    assert!(!DownloadOptions::from(&cli).prefer_intl_cdn);
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--prefer-intl-cdn"]).unwrap();
+    assert!(DownloadOptions::from(&cli).prefer_intl_cdn);
+    // 国际版API隐含海外CDN优先
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--use-intl-api"]).unwrap();
+    assert!(DownloadOptions::from(&cli).prefer_intl_cdn);
+}
+
+#[test]
+fn test_test_connection() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from(["rvd", "--test-connection"]).unwrap();
+    assert!(cli.test_connection);
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--test-connection"]).is_err());
+}
+
+#[test]
+fn test_download_comments() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    let cli = Cli::try_parse_from([
+        "rvd",
+        "BV1xx411c7mD",
+        "--download-comments",
+        "--comment-replies",
+        "--max-comments",
+        "500",
+    ])
+    .unwrap();
+    let options = DownloadOptions::from(&cli);
+    assert!(options.download_comments);
+    assert!(options.comment_replies);
+    assert_eq!(options.max_comments, Some(500));
+
+    // 回复与数量限制需要 --download-comments
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--comment-replies"]).is_err());
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--max-comments", "10"]).is_err());
+}
+
+#[test]
+fn test_embed_metadata() {
+    use clap::Parser;
+    use rvd::core::muxer::EmbedOptions;
+
+    let embed = |args: &[&str]| {
+        let mut argv = vec!["rvd", "BV1xx411c7mD"];
+        argv.extend_from_slice(args);
+        Cli::try_parse_from(argv).unwrap().get_embed_options()
+    };
+
+    // 默认保持原有行为
+    assert_eq!(embed(&[]), EmbedOptions::default());
+    assert_eq!(embed(&["--embed-metadata"]), EmbedOptions::all());
+    assert_eq!(
+        embed(&["--embed-metadata", "--no-embed-cover", "--no-embed-nfo"]),
+        EmbedOptions {
+            cover: false,
+            nfo: false,
+            ..EmbedOptions::all()
+        }
+    );
+    // 章节和字幕不需要 --embed-metadata 也可以关闭
+    let options = embed(&["--no-embed-chapters", "--no-embed-subs"]);
+    assert!(!options.chapters);
+    assert!(!options.subtitles);
+}
+
+#[test]
+fn test_clip() {
+    use clap::Parser;
+    use rvd::core::clip::ClipRange;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--clip", "1:00:00-1:02:30"]).unwrap();
+    assert_eq!(cli.clip, Some(ClipRange { start: 3600.0, end: 3750.0 }));
+
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--clip", "2:00-1:00"]).is_err());
+    // 片段需要混流时裁剪
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--clip", "0-60", "--skip-mux"]).is_err());
+}
+
+#[test]
+fn test_aria2c_rpc() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from([
+        "rvd",
+        "BV1xx411c7mD",
+        "--aria2c-rpc",
+        "http://localhost:6800/jsonrpc",
+        "--aria2c-rpc-secret",
+        "token",
+    ])
+    .unwrap();
+    assert_eq!(cli.aria2c_rpc.as_deref(), Some("http://localhost:6800/jsonrpc"));
+    assert_eq!(cli.aria2c_rpc_secret.as_deref(), Some("token"));
+
+    // 密钥需要与RPC地址一起使用
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--aria2c-rpc-secret", "token"]).is_err());
+}
+
+#[test]
+fn test_aria2c_split_options() {
+    use clap::Parser;
+
+    let cli = Cli::try_parse_from([
+        "rvd",
+        "BV1xx411c7mD",
+        "--aria2c-split",
+        "8",
+        "--aria2c-max-connection-per-server",
+        "4",
+        "--aria2c-min-split-size",
+        "2M",
+    ])
+    .unwrap();
+    assert_eq!(cli.aria2c_split, Some(8));
+    assert_eq!(cli.aria2c_max_connection_per_server, Some(4));
+    assert_eq!(cli.aria2c_min_split_size, Some(2 * 1024 * 1024));
+
+    // aria2c 的取值范围
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--aria2c-max-connection-per-server", "32"]).is_err());
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--aria2c-min-split-size", "512K"]).is_err());
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--aria2c-split", "0"]).is_err());
+}
+
+#[test]
+fn test_overwrite_policy() {
+    use clap::Parser;
+    use rvd::utils::file::SidecarPolicy;
+
+    let policy = |args: &[&str]| {
+        let mut argv = vec!["rvd", "BV1xx411c7mD"];
+        argv.extend_from_slice(args);
+        Cli::try_parse_from(argv).unwrap().get_sidecar_policy()
+    };
+    assert_eq!(policy(&[]), SidecarPolicy::Follow);
+    assert_eq!(policy(&["--overwrite-policy", "overwrite"]), SidecarPolicy::Overwrite);
+    assert_eq!(policy(&["--overwrite-policy", "keep"]), SidecarPolicy::Keep);
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--overwrite-policy", "never"]).is_err());
+}
+
+#[test]
+fn test_config_quality_codec_defaults() {
+    use clap::{CommandFactory, FromArgMatches};
+
+    let config: rvd::utils::config::Config =
+        toml::from_str("default_quality = [\"720P\"]\ndefault_codec = [\"av1\", \"hevc\"]").unwrap();
+
+    // 命令行未指定时使用配置中的清晰度和编码
+    let matches = Cli::command().get_matches_from(["rvd", "BV1xx411c7mD"]);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap();
+    cli.apply_config_defaults(&config, &matches);
+    assert_eq!(cli.parse_quality_priority(), vec!["720P"]);
+    assert_eq!(cli.parse_codec_priority(), vec!["av1", "hevc"]);
+
+    // 命令行参数优先
+    let matches = Cli::command().get_matches_from(["rvd", "BV1xx411c7mD", "--quality", "1080P"]);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap();
+    cli.apply_config_defaults(&config, &matches);
+    assert_eq!(cli.parse_quality_priority(), vec!["1080P"]);
+    assert_eq!(cli.parse_codec_priority(), vec!["av1", "hevc"]);
+
+    // 都未设置时使用内置默认值
+    let matches = Cli::command().get_matches_from(["rvd", "BV1xx411c7mD"]);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap();
+    cli.apply_config_defaults(&rvd::utils::config::Config::default(), &matches);
+    assert_eq!(cli.parse_quality_priority(), vec!["1080P", "720P", "480P"]);
+}
+
+#[test]
+fn test_archive_profile_defaults() {
+    use clap::{CommandFactory, FromArgMatches};
+
+    let mut config: rvd::utils::config::Config = toml::from_str(
+        r#"
+default_quality = ["1080P 高清"]
+
+[profiles.archive]
+default_quality = ["4K 超清", "1080P 高码率"]
+embed_metadata = true
+audio_lang = "ja"
+download_danmaku = true
+danmaku_format = "xml"
+"#,
+    )
+    .unwrap();
+    config.apply_profile("archive").unwrap();
+
+    let matches = Cli::command().get_matches_from(["rvd", "BV1xx411c7mD"]);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap();
+    cli.apply_config_defaults(&config, &matches);
+    assert_eq!(cli.parse_quality_priority(), vec!["4K 超清", "1080P 高码率"]);
+    assert!(cli.get_embed_options().nfo);
+    assert_eq!(cli.audio_lang.as_deref(), Some("ja"));
+    assert!(cli.download_danmaku);
+    assert_eq!(cli.danmaku_format, "xml");
+
+    // 命令行参数优先于配置组
+    let matches = Cli::command().get_matches_from([
+        "rvd",
+        "BV1xx411c7mD",
+        "--audio-lang",
+        "zh-Hans",
+        "--danmaku-format",
+        "ass",
+        "--no-embed-nfo",
+    ]);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap();
+    cli.apply_config_defaults(&config, &matches);
+    assert_eq!(cli.audio_lang.as_deref(), Some("zh-Hans"));
+    assert_eq!(cli.danmaku_format, "ass");
+    assert!(!cli.get_embed_options().nfo);
+    assert!(cli.get_embed_options().chapters);
+}
+
+#[test]
+fn test_page_number_offset() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD"]).unwrap();
+    assert_eq!(DownloadOptions::from(&cli).page_number_offset, 0);
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--page-number-offset", "12"]).unwrap();
+    assert_eq!(DownloadOptions::from(&cli).page_number_offset, 12);
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--page-number-offset", "-1"]).is_err());
+
+    // 批量文件中可以按行设置
+    let options = DownloadOptions::default()
+        .with_overrides(&[("page-number-offset".to_string(), "5".to_string())])
+        .unwrap();
+    assert_eq!(options.page_number_offset, 5);
+}
+
+#[test]
+fn test_on_interrupt() {
+    use clap::Parser;
+    use rvd::utils::signal::{InterruptPolicy, Signal};
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD"]).unwrap();
+    assert_eq!(cli.get_interrupt_policy(), InterruptPolicy::Resume);
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--on-interrupt", "clean"]).unwrap();
+    assert_eq!(cli.get_interrupt_policy(), InterruptPolicy::Clean);
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--on-interrupt", "ignore"]).is_err());
+
+    // 128 + 信号编号
+    assert_eq!(Signal::Interrupt.exit_code(), 130);
+    assert_eq!(Signal::Terminate.exit_code(), 143);
+}
+
+#[test]
+fn test_parse_cache_flags() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    // 默认不使用缓存
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD"]).unwrap();
+    assert_eq!(DownloadOptions::from(&cli).cache_ttl, None);
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--cache-ttl", "600"]).unwrap();
+    assert_eq!(DownloadOptions::from(&cli).cache_ttl, Some(600));
+
+    // --cache-ttl 0 禁用缓存
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--cache-ttl", "0"]).unwrap();
+    assert_eq!(DownloadOptions::from(&cli).cache_ttl, None);
+
+    // 缓存默认关闭，不再需要 --no-cache
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--no-cache"]).is_err());
+}
+
+#[test]
+fn test_danmaku_merge_flag() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--download-danmaku", "--danmaku-merge"])
+        .unwrap();
+    assert!(DownloadOptions::from(&cli).danmaku_merge);
+
+    // 需要同时下载弹幕
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--danmaku-merge"]).is_err());
+}
+
+#[test]
+fn test_concat_pages_flag() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--concat-pages"]).unwrap();
+    assert!(DownloadOptions::from(&cli).concat_pages);
+
+    // 不混流时没有可拼接的文件
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--concat-pages", "--skip-mux"]).is_err());
+}
+
+#[test]
+fn test_force_overwrite_partial_flag() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD"]).unwrap();
+    assert!(!DownloadOptions::from(&cli).force_overwrite_partial);
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--force-overwrite-partial"]).unwrap();
+    assert!(DownloadOptions::from(&cli).force_overwrite_partial);
+}
+
+#[test]
+fn test_list_subtitles_flag() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--list-subtitles"]).unwrap();
+    assert!(DownloadOptions::from(&cli).list_subtitles);
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--list-subtitles", "--info-all"]).is_ok());
+}
+
+#[test]
+fn test_stream_threads_default_to_threads() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "-t", "8"]).unwrap();
+    let options = DownloadOptions::from(&cli);
+    assert_eq!((options.video_thread_count(), options.audio_thread_count()), (8, 8));
+
+    let cli = Cli::try_parse_from([
+        "rvd",
+        "BV1xx411c7mD",
+        "--video-threads",
+        "16",
+        "--audio-threads",
+        "1",
+    ])
+    .unwrap();
+    let options = DownloadOptions::from(&cli);
+    assert_eq!((options.video_thread_count(), options.audio_thread_count()), (16, 1));
+}
+
+#[test]
+fn test_ip_version_flags() {
+    use clap::Parser;
+    use rvd::utils::http::IpVersion;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD"]).unwrap();
+    assert_eq!(cli.get_ip_version(), IpVersion::Auto);
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--force-ipv4"]).unwrap();
+    assert_eq!(cli.get_ip_version(), IpVersion::V4);
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--force-ipv6"]).unwrap();
+    assert_eq!(cli.get_ip_version(), IpVersion::V6);
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--force-ipv4", "--force-ipv6"]).is_err());
+}
+
+#[test]
+fn test_info_all_with_info_only_lists_streams() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    // 单独使用 --info-all 没有意义
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--info-all"]).is_err());
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--info-only"]).unwrap();
+    assert!(!DownloadOptions::from(&cli).list_qualities_table);
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--info-only", "--info-all"]).unwrap();
+    let options = DownloadOptions::from(&cli);
+    assert!(options.list_qualities_table);
+    assert!(options.info_all);
+}
+
+#[test]
+fn test_retry_failed_flag() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    // 重试时不需要URL，且总是继续处理其余项目
+    let cli = Cli::try_parse_from(["rvd", "--retry-failed", "failures.txt"]).unwrap();
+    assert!(DownloadOptions::from(&cli).continue_on_error);
+    assert!(Cli::try_parse_from(["rvd", "--retry-failed", "a.txt", "-a", "b.txt"]).is_err());
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--retry-failed", "a.txt"]).is_err());
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--failures-file", "failures.txt"]).unwrap();
+    assert_eq!(cli.failures_file, Some(std::path::PathBuf::from("failures.txt")));
+}
+
+#[test]
+fn test_keep_raw_danmaku_requires_danmaku() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--keep-raw-danmaku"]).is_err());
+
+    let cli = Cli::try_parse_from([
+        "rvd",
+        "BV1xx411c7mD",
+        "--download-danmaku",
+        "--keep-raw-danmaku",
+    ])
+    .unwrap();
+    assert!(DownloadOptions::from(&cli).keep_raw_danmaku);
+}
+
+#[test]
+fn test_simulate_flag_and_alias() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--simulate"]).unwrap();
+    assert!(DownloadOptions::from(&cli).simulate);
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--no-download"]).unwrap();
+    assert!(cli.simulate);
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD"]).unwrap();
+    assert!(!DownloadOptions::from(&cli).simulate);
+}
+
+#[test]
+fn test_danmaku_limit_flags() {
+    use clap::Parser;
+    use rvd::app::DownloadOptions;
+    use rvd::core::danmaku::{DanmakuLimit, DEFAULT_PER_SECOND};
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD"]).unwrap();
+    assert_eq!(DownloadOptions::from(&cli).danmaku_limit, DanmakuLimit::default());
+    assert_eq!(cli.get_danmaku_limit().per_second, Some(DEFAULT_PER_SECOND));
+
+    let cli = Cli::try_parse_from([
+        "rvd",
+        "BV1xx411c7mD",
+        "--danmaku-max",
+        "5000",
+        "--danmaku-per-second",
+        "8",
+    ])
+    .unwrap();
+    assert_eq!(
+        cli.get_danmaku_limit(),
+        DanmakuLimit {
+            per_second: Some(8),
+            total: Some(5000),
+        }
+    );
+
+    let cli = Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--no-danmaku-limit"]).unwrap();
+    assert_eq!(cli.get_danmaku_limit(), DanmakuLimit::unlimited());
+
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--no-danmaku-limit", "--danmaku-max", "10"]).is_err());
+    assert!(Cli::try_parse_from(["rvd", "BV1xx411c7mD", "--danmaku-max", "0"]).is_err());
+}
+
+#[test]
+fn test_remux_accepts_merge_output_format() {
+    use clap::Parser;
+    use rvd::core::muxer::MergeOutputFormat;
+
+    let cli = Cli::try_parse_from([
+        "rvd",
+        "--remux",
+        "title.video.m4s",
+        "title.audio.m4s",
+        "--merge-output-format",
+        "mkv",
+    ])
+    .unwrap();
+    assert_eq!(cli.remux.as_ref().map(Vec::len), Some(2));
+    assert_eq!(cli.get_merge_output_format(), Some(MergeOutputFormat::Mkv));
+}
@@ -0,0 +1,316 @@
+// aria2 RPC 客户端单元测试
+use mockito::Matcher;
+use rvd::core::aria2_rpc::Aria2Rpc;
+use rvd::core::downloader::{Aria2cOptions, DownloadMethod, Downloader};
+use rvd::utils::cookie::CookieJar;
+use rvd::utils::http::{HttpClient, IpVersion};
+use serde_json::json;
+use std::sync::Arc;
+
+#[tokio::test]
+async fn test_rpc_download_submits_and_polls() {
+    let mut server = mockito::Server::new_async().await;
+    let add_uri = server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({
+            "method": "aria2.addUri",
+            "params": ["token:s3cret", ["https://example.com/video.m4s"], {
+                "out": "video.m4s",
+                "allow-overwrite": "true",
+                "max-connection-per-server": "8",
+            }],
+        })))
+        .with_body(r#"{"id":"rvd","jsonrpc":"2.0","result":"2089b05ecca3d829"}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    let tell_status = server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({
+            "method": "aria2.tellStatus",
+            "params": ["token:s3cret", "2089b05ecca3d829"],
+        })))
+        .with_body(
+            r#"{"id":"rvd","jsonrpc":"2.0","result":{"status":"complete","totalLength":"1024","completedLength":"1024"}}"#,
+        )
+        .create_async()
+        .await;
+
+    let rpc = Aria2Rpc::new(&format!("{}/jsonrpc", server.url()), Some("s3cret".to_string()));
+    let temp_dir = tempfile::tempdir().unwrap();
+    rpc.download(
+        "https://example.com/video.m4s",
+        &temp_dir.path().join("video.m4s"),
+        vec![("max-connection-per-server".to_string(), "8".to_string())],
+        None,
+    )
+    .await
+    .unwrap();
+
+    add_uri.assert_async().await;
+    tell_status.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_rpc_download_reports_aria2_errors() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({ "method": "aria2.addUri" })))
+        .with_body(r#"{"id":"rvd","jsonrpc":"2.0","result":"gid"}"#)
+        .create_async()
+        .await;
+    server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({ "method": "aria2.tellStatus" })))
+        .with_body(
+            r#"{"id":"rvd","jsonrpc":"2.0","result":{"status":"error","totalLength":"0","completedLength":"0","errorMessage":"403 Forbidden"}}"#,
+        )
+        .create_async()
+        .await;
+
+    let rpc = Aria2Rpc::new(&format!("{}/jsonrpc", server.url()), None);
+    let temp_dir = tempfile::tempdir().unwrap();
+    let err = rpc
+        .download("https://example.com/a.m4s", &temp_dir.path().join("a.m4s"), Vec::new(), None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("403 Forbidden"));
+}
+
+#[tokio::test]
+async fn test_rpc_health_check() {
+    let mut server = mockito::Server::new_async().await;
+    let version = server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({ "method": "aria2.getVersion" })))
+        .with_body(r#"{"id":"rvd","jsonrpc":"2.0","result":{"version":"1.37.0","enabledFeatures":[]}}"#)
+        .expect(2)
+        .create_async()
+        .await;
+
+    let rpc = Aria2Rpc::new(&format!("{}/jsonrpc", server.url()), None);
+    assert_eq!(rpc.version().await.unwrap(), "1.37.0");
+    // 健康检查结果被缓存，第二次不再请求
+    let rpc = Aria2Rpc::new(&format!("{}/jsonrpc", server.url()), None);
+    assert!(rpc.is_available().await);
+    assert!(rpc.is_available().await);
+    version.assert_async().await;
+
+    // 密钥错误时 aria2 返回 error 对象
+    server
+        .mock("POST", "/bad")
+        .with_status(400)
+        .with_body(r#"{"id":"rvd","jsonrpc":"2.0","error":{"code":1,"message":"Unauthorized"}}"#)
+        .create_async()
+        .await;
+    let rpc = Aria2Rpc::new(&format!("{}/bad", server.url()), Some("wrong".to_string()));
+    assert!(rpc.version().await.unwrap_err().to_string().contains("Unauthorized"));
+    assert!(!rpc.is_available().await);
+}
+
+#[tokio::test]
+async fn test_unreachable_rpc_falls_back() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/video.m4s")
+        .with_body("video data")
+        .create_async()
+        .await;
+
+    // RPC 不可用且没有 aria2c 命令时，最终由内置下载器完成
+    let downloader = Downloader::new(Arc::new(HttpClient::new().unwrap().with_retry_count(1)), 1)
+        .with_method(DownloadMethod::Aria2c)
+        .with_aria2c_path("/nonexistent/aria2c".to_string())
+        .with_aria2c_rpc(Some(Arc::new(Aria2Rpc::new("http://127.0.0.1:1/jsonrpc", None))));
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = temp_dir.path().join("video.m4s");
+    downloader
+        .download(&format!("{}/video.m4s", server.url()), &output, None)
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "video data");
+}
+
+#[tokio::test]
+async fn test_rpc_download_sends_split_options() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({ "method": "aria2.getVersion" })))
+        .with_body(r#"{"id":"rvd","jsonrpc":"2.0","result":{"version":"1.37.0"}}"#)
+        .create_async()
+        .await;
+    // 自定义参数中的同名选项覆盖专用参数
+    let add_uri = server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({
+            "method": "aria2.addUri",
+            "params": [["https://example.com/video.m4s"], {
+                "split": "4",
+                "max-connection-per-server": "2",
+                "min-split-size": "1048576",
+            }],
+        })))
+        .with_body(r#"{"id":"rvd","jsonrpc":"2.0","result":"gid"}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({ "method": "aria2.tellStatus" })))
+        .with_body(
+            r#"{"id":"rvd","jsonrpc":"2.0","result":{"status":"complete","totalLength":"1","completedLength":"1"}}"#,
+        )
+        .create_async()
+        .await;
+
+    let downloader = Downloader::new(Arc::new(HttpClient::new().unwrap()), 1)
+        .with_method(DownloadMethod::Aria2c)
+        .with_aria2c_options(Aria2cOptions {
+            split: 4,
+            max_connection_per_server: 8,
+            min_split_size: 1 << 20,
+        })
+        .with_aria2c_args("--max-connection-per-server=2".to_string())
+        .with_aria2c_rpc(Some(Arc::new(Aria2Rpc::new(
+            &format!("{}/jsonrpc", server.url()),
+            None,
+        ))));
+
+    // 模拟 aria2 写入的文件，下载完成后改为最终文件名
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = temp_dir.path().join("video.m4s");
+    std::fs::write(rvd::utils::file::part_path(&output), "video data").unwrap();
+    downloader
+        .download("https://example.com/video.m4s", &output, None)
+        .await
+        .unwrap();
+    add_uri.assert_async().await;
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "video data");
+}
+
+#[tokio::test]
+async fn test_rpc_download_retries_transient_poll_errors() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({ "method": "aria2.addUri" })))
+        .with_body(r#"{"id":"rvd","jsonrpc":"2.0","result":"gid"}"#)
+        .create_async()
+        .await;
+    // 第一次查询失败，之后正常返回
+    let failed = server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({ "method": "aria2.tellStatus" })))
+        .with_status(502)
+        .with_body("Bad Gateway")
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({ "method": "aria2.tellStatus" })))
+        .with_body(
+            r#"{"id":"rvd","jsonrpc":"2.0","result":{"status":"complete","totalLength":"1","completedLength":"1"}}"#,
+        )
+        .create_async()
+        .await;
+
+    let rpc = Aria2Rpc::new(&format!("{}/jsonrpc", server.url()), None);
+    let temp_dir = tempfile::tempdir().unwrap();
+    rpc.download("https://example.com/a.m4s", &temp_dir.path().join("a.m4s"), Vec::new(), None)
+        .await
+        .unwrap();
+    failed.assert_async().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_rpc_download_removes_task_after_timeout() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({ "method": "aria2.addUri" })))
+        .with_body(r#"{"id":"rvd","jsonrpc":"2.0","result":"gid"}"#)
+        .create_async()
+        .await;
+    server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({ "method": "aria2.tellStatus" })))
+        .with_body(
+            r#"{"id":"rvd","jsonrpc":"2.0","result":{"status":"active","totalLength":"100","completedLength":"1"}}"#,
+        )
+        .create_async()
+        .await;
+    let remove = server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({
+            "method": "aria2.forceRemove",
+            "params": ["gid"],
+        })))
+        .with_body(r#"{"id":"rvd","jsonrpc":"2.0","result":"gid"}"#)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let rpc = Aria2Rpc::new(&format!("{}/jsonrpc", server.url()), None)
+        .with_download_timeout(std::time::Duration::from_millis(200));
+    let temp_dir = tempfile::tempdir().unwrap();
+    let err = rpc
+        .download("https://example.com/a.m4s", &temp_dir.path().join("a.m4s"), Vec::new(), None)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("did not finish"));
+    remove.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_rpc_download_disables_ipv6_when_forcing_ipv4() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(
+            json!({ "method": "aria2.getVersion" }),
+        ))
+        .with_body(r#"{"id":"rvd","jsonrpc":"2.0","result":{"version":"1.37.0"}}"#)
+        .create_async()
+        .await;
+    let add_uri = server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({
+            "method": "aria2.addUri",
+            "params": [["https://example.com/video.m4s"], { "disable-ipv6": "true" }],
+        })))
+        .with_body(r#"{"id":"rvd","jsonrpc":"2.0","result":"gid"}"#)
+        .expect(1)
+        .create_async()
+        .await;
+    server
+        .mock("POST", "/jsonrpc")
+        .match_body(Matcher::PartialJson(json!({ "method": "aria2.tellStatus" })))
+        .with_body(
+            r#"{"id":"rvd","jsonrpc":"2.0","result":{"status":"complete","totalLength":"1","completedLength":"1"}}"#,
+        )
+        .create_async()
+        .await;
+
+    let client =
+        HttpClient::with_cookie_jar_and_ip_version(Arc::new(CookieJar::new()), IpVersion::V4)
+            .unwrap();
+    let downloader = Downloader::new(Arc::new(client), 1)
+        .with_method(DownloadMethod::Aria2c)
+        .with_aria2c_rpc(Some(Arc::new(Aria2Rpc::new(
+            &format!("{}/jsonrpc", server.url()),
+            None,
+        ))));
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = temp_dir.path().join("video.m4s");
+    std::fs::write(rvd::utils::file::part_path(&output), "video data").unwrap();
+    downloader
+        .download("https://example.com/video.m4s", &output, None)
+        .await
+        .unwrap();
+    add_uri.assert_async().await;
+}
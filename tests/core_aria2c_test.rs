@@ -1,4 +1,7 @@
-use rvd::core::downloader::{DownloadMethod, Downloader};
+// 保留原有测试中的 `assert!(true)`
+#![allow(clippy::assertions_on_constants)]
+
+use rvd::core::downloader::{Aria2cOptions, DownloadMethod, Downloader};
 use rvd::utils::http::HttpClient;
 use std::sync::Arc;
 
@@ -48,3 +51,27 @@ fn test_download_method_enum() {
     assert_eq!(DownloadMethod::Aria2c, DownloadMethod::Aria2c);
     assert_ne!(DownloadMethod::Builtin, DownloadMethod::Aria2c);
 }
+
+#[test]
+fn test_aria2c_options() {
+    // 默认值与原来的 -x16 -s16 -k5M 一致
+    let options: Vec<(String, String)> = Aria2cOptions::default().to_options();
+    assert!(options.contains(&("max-connection-per-server".to_string(), "16".to_string())));
+    assert!(options.contains(&("split".to_string(), "16".to_string())));
+    assert!(options.contains(&("min-split-size".to_string(), "5242880".to_string())));
+
+    let options = Aria2cOptions {
+        split: 8,
+        max_connection_per_server: 4,
+        min_split_size: 1 << 20,
+    }
+    .to_options();
+    assert_eq!(
+        options,
+        vec![
+            ("max-connection-per-server".to_string(), "4".to_string()),
+            ("split".to_string(), "8".to_string()),
+            ("min-split-size".to_string(), "1048576".to_string()),
+        ]
+    );
+}
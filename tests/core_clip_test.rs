@@ -0,0 +1,88 @@
+// 片段下载单元测试
+use rvd::core::clip::{byte_range_for_clip, parse_byte_range, parse_sidx, ClipRange, SidxSegment};
+
+/// 构造 version 0 的 sidx 盒子：每个片段为 (字节数, 时长)
+fn sidx_box(timescale: u32, earliest: u32, first_offset: u32, refs: &[(u32, u32)]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&[0, 0, 0, 0]); // version + flags
+    body.extend_from_slice(&1u32.to_be_bytes()); // reference_ID
+    body.extend_from_slice(&timescale.to_be_bytes());
+    body.extend_from_slice(&earliest.to_be_bytes());
+    body.extend_from_slice(&first_offset.to_be_bytes());
+    body.extend_from_slice(&0u16.to_be_bytes());
+    body.extend_from_slice(&(refs.len() as u16).to_be_bytes());
+    for &(size, duration) in refs {
+        body.extend_from_slice(&size.to_be_bytes());
+        body.extend_from_slice(&duration.to_be_bytes());
+        body.extend_from_slice(&0x9000_0000u32.to_be_bytes()); // starts_with_SAP
+    }
+
+    let mut data = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+    data.extend_from_slice(b"sidx");
+    data.extend_from_slice(&body);
+    data
+}
+
+#[test]
+fn test_parse_clip_range() {
+    assert_eq!(ClipRange::parse("90-150"), Some(ClipRange { start: 90.0, end: 150.0 }));
+    assert_eq!(ClipRange::parse("1:30-2:30.5"), Some(ClipRange { start: 90.0, end: 150.5 }));
+    assert_eq!(
+        ClipRange::parse("1:00:00-1:05:00"),
+        Some(ClipRange { start: 3600.0, end: 3900.0 })
+    );
+    assert_eq!(ClipRange::parse("1:00-1:00:00").unwrap().duration(), 3540.0);
+
+    // 结束时间必须晚于开始时间
+    assert_eq!(ClipRange::parse("2:00-1:00"), None);
+    assert_eq!(ClipRange::parse("90"), None);
+    assert_eq!(ClipRange::parse("a-b"), None);
+}
+
+#[test]
+fn test_parse_byte_range() {
+    assert_eq!(parse_byte_range("0-927"), Some((0, 927)));
+    assert_eq!(parse_byte_range("928-1415"), Some((928, 1415)));
+    assert_eq!(parse_byte_range("10-5"), None);
+    assert_eq!(parse_byte_range("abc"), None);
+}
+
+#[test]
+fn test_parse_sidx() {
+    let data = sidx_box(1000, 0, 0, &[(100, 5000), (200, 5000), (300, 4000)]);
+    let segments = parse_sidx(&data, 1000).unwrap();
+
+    // 第一个片段紧跟在 sidx 之后
+    let first = 1000 + data.len() as u64;
+    assert_eq!(
+        segments,
+        vec![
+            SidxSegment { offset: first, size: 100, start: 0.0, duration: 5.0 },
+            SidxSegment { offset: first + 100, size: 200, start: 5.0, duration: 5.0 },
+            SidxSegment { offset: first + 300, size: 300, start: 10.0, duration: 4.0 },
+        ]
+    );
+
+    assert!(parse_sidx(b"\0\0\0\x10moof", 0).is_err());
+    // 只有盒子头、没有版本字节时返回错误而不是越界
+    assert!(parse_sidx(b"\0\0\0\x08sidx", 0).is_err());
+}
+
+#[test]
+fn test_byte_range_for_clip() {
+    let data = sidx_box(1000, 0, 0, &[(100, 5000), (200, 5000), (300, 4000)]);
+    let segments = parse_sidx(&data, 0).unwrap();
+    let first = data.len() as u64;
+
+    // 跨越第2、3个片段
+    let clip = ClipRange { start: 6.0, end: 12.0 };
+    assert_eq!(byte_range_for_clip(&segments, clip), Some((first + 100, first + 599, 5.0)));
+
+    // 正好落在片段边界上时不包含前一个片段
+    let clip = ClipRange { start: 5.0, end: 10.0 };
+    assert_eq!(byte_range_for_clip(&segments, clip), Some((first + 100, first + 299, 5.0)));
+
+    // 超出流的范围
+    let clip = ClipRange { start: 20.0, end: 30.0 };
+    assert_eq!(byte_range_for_clip(&segments, clip), None);
+}
@@ -1,5 +1,5 @@
 // 弹幕模块单元测试
-use rvd::core::danmaku::DanmakuFormat;
+use rvd::core::danmaku::{DanmakuFormat, DanmakuLimit};
 
 #[test]
 fn test_danmaku_format_enum() {
@@ -35,3 +35,187 @@ fn test_danmaku_format_debug() {
     
     assert!(debug_str.contains("Xml"));
 }
+
+#[test]
+fn test_convert_danmaku() {
+    use rvd::core::danmaku::convert_danmaku;
+
+    let xml = br#"<?xml version="1.0" encoding="UTF-8"?><i><d p="1.5,1,25,16777215,0,0,0,0">hello</d></i>"#;
+    let converted = convert_danmaku(xml, DanmakuFormat::Xml, "Arial", DanmakuLimit::default()).unwrap().unwrap();
+    assert!(converted.contains("hello"));
+
+    let ass = convert_danmaku(xml, DanmakuFormat::Ass, "Arial", DanmakuLimit::default()).unwrap().unwrap();
+    assert!(ass.contains("[Events]"));
+
+    // 没有弹幕时返回 None
+    assert!(convert_danmaku(b"<i></i>", DanmakuFormat::Ass, "Arial", DanmakuLimit::default()).unwrap().is_none());
+}
+
+#[test]
+fn test_convert_danmaku_uses_font_in_ass_style() {
+    use rvd::core::danmaku::convert_danmaku;
+
+    let xml = r#"<i><d p="1.5,1,25,16777215,0,0,0,0">你好</d></i>"#.as_bytes();
+    let ass = convert_danmaku(xml, DanmakuFormat::Ass, "Noto Sans CJK SC", DanmakuLimit::default()).unwrap().unwrap();
+    assert!(ass.contains("Style: Default,Noto Sans CJK SC,36,"));
+
+    // 字体名称中的逗号不能破坏样式字段
+    let ass = convert_danmaku(xml, DanmakuFormat::Ass, "Foo, Bar", DanmakuLimit::default()).unwrap().unwrap();
+    assert!(ass.contains("Style: Default,Foo  Bar,36,"));
+}
+
+#[tokio::test]
+async fn test_save_danmaku_keeps_raw_xml() {
+    use rvd::core::danmaku::{save_danmaku, RAW_EXTENSION};
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = temp_dir.path().join("video.ass");
+    let raw_output = temp_dir.path().join(format!("video.{}", RAW_EXTENSION));
+    let xml = r#"<i><d p="1.5,1,25,16777215,0,0,0,0">hello</d></i>"#;
+
+    let saved = save_danmaku(
+        xml.as_bytes(),
+        &output,
+        DanmakuFormat::Ass,
+        "Arial",
+        DanmakuLimit::default(),
+        Some(&raw_output),
+    )
+    .await
+    .unwrap();
+    assert!(saved);
+    assert!(std::fs::read_to_string(&output).unwrap().contains("[Events]"));
+    // 原始弹幕保存为未经转换的 XML
+    assert_eq!(raw_output.file_name().unwrap(), "video.raw.xml");
+    assert_eq!(std::fs::read_to_string(&raw_output).unwrap(), xml);
+
+    // 没有弹幕时不写入任何文件
+    let empty_output = temp_dir.path().join("empty.ass");
+    let empty_raw = temp_dir.path().join(format!("empty.{}", RAW_EXTENSION));
+    let saved = save_danmaku(
+        b"<i></i>",
+        &empty_output,
+        DanmakuFormat::Ass,
+        "Arial",
+        DanmakuLimit::default(),
+        Some(&empty_raw),
+    )
+    .await
+    .unwrap();
+    assert!(!saved);
+    assert!(!empty_output.exists());
+    assert!(!empty_raw.exists());
+}
+
+#[test]
+fn test_merge_danmaku_offsets_by_page_duration() {
+    use rvd::core::danmaku::merge_danmaku;
+
+    let p1 = r#"<i><d p="1.5,1,25,16777215,0,0,0,0">first</d><d p="59,1,25,16777215,0,0,0,0">end of p1</d></i>"#;
+    let p2 = r#"<i><d p="2,1,25,16777215,0,0,0,0">second</d></i>"#;
+    let p3 = r#"<i><d p="0.25,5,25,255,0,0,0,0">third</d></i>"#;
+    let parts = vec![
+        (p1.to_string(), 60),
+        (p2.to_string(), 90),
+        (p3.to_string(), 30),
+    ];
+
+    // P2 后移 60 秒，P3 后移 60 + 90 秒，其余参数保持不变
+    let xml = merge_danmaku(&parts, DanmakuFormat::Xml, "Arial", DanmakuLimit::default()).unwrap().unwrap();
+    assert!(xml.contains(r#"<d p="1.50000,1,25,16777215,0,0,0,0">first</d>"#));
+    assert!(xml.contains(r#"<d p="59.00000,1,25,16777215,0,0,0,0">end of p1</d>"#));
+    assert!(xml.contains(r#"<d p="62.00000,1,25,16777215,0,0,0,0">second</d>"#));
+    assert!(xml.contains(r#"<d p="150.25000,5,25,255,0,0,0,0">third</d>"#));
+    let first = xml.find("first").unwrap();
+    let second = xml.find("second").unwrap();
+    let third = xml.find("third").unwrap();
+    assert!(first < second && second < third);
+
+    let ass = merge_danmaku(&parts, DanmakuFormat::Ass, "Arial", DanmakuLimit::default()).unwrap().unwrap();
+    assert!(ass.contains("Dialogue: 0,0:01:02.00,0:01:07.00,"));
+    assert!(ass.contains("Dialogue: 0,0:02:30.25,0:02:35.25,"));
+}
+
+#[test]
+fn test_merge_danmaku_keeps_offset_for_pages_without_danmaku() {
+    use rvd::core::danmaku::merge_danmaku;
+
+    let parts = vec![
+        ("<i></i>".to_string(), 100),
+        (r#"<i><d p="1,1,25,16777215,0,0,0,0">late</d></i>"#.to_string(), 50),
+    ];
+    let xml = merge_danmaku(&parts, DanmakuFormat::Xml, "Arial", DanmakuLimit::default()).unwrap().unwrap();
+    assert!(xml.contains(r#"<d p="101.00000,1,25,16777215,0,0,0,0">late</d>"#));
+
+    // 所有分P都没有弹幕
+    let empty = vec![("<i></i>".to_string(), 100), ("<i></i>".to_string(), 50)];
+    assert!(merge_danmaku(&empty, DanmakuFormat::Ass, "Arial", DanmakuLimit::default()).unwrap().is_none());
+}
+
+/// 生成 `count` 条弹幕，第 i 条出现在 `time(i)` 秒，内容为 `c{i}`
+fn danmaku_xml(count: usize, time: impl Fn(usize) -> f64) -> Vec<u8> {
+    let mut xml = String::from("<i>");
+    for i in 0..count {
+        xml.push_str(&format!(r#"<d p="{},1,25,16777215,0,0,0,0">c{}</d>"#, time(i), i));
+    }
+    xml.push_str("</i>");
+    xml.into_bytes()
+}
+
+fn dialogue_texts(ass: &str) -> Vec<String> {
+    ass.lines()
+        .filter(|line| line.starts_with("Dialogue:"))
+        .map(|line| line.rsplit('}').next().unwrap().trim_end_matches("\\N").to_string())
+        .collect()
+}
+
+#[test]
+fn test_danmaku_limit_per_second() {
+    use rvd::core::danmaku::convert_danmaku;
+
+    // 第 0 秒 10 条，第 1 秒 2 条
+    let xml = danmaku_xml(12, |i| if i < 10 { i as f64 * 0.05 } else { 1.5 });
+    let limit = DanmakuLimit {
+        per_second: Some(4),
+        total: None,
+    };
+    let ass = convert_danmaku(&xml, DanmakuFormat::Ass, "Arial", limit).unwrap().unwrap();
+    // 第 0 秒等间隔保留 4 条，第 1 秒不受影响
+    assert_eq!(dialogue_texts(&ass), ["c0", "c2", "c5", "c7", "c10", "c11"]);
+
+    // 相同输入总是得到相同结果
+    let again = convert_danmaku(&xml, DanmakuFormat::Ass, "Arial", limit).unwrap().unwrap();
+    assert_eq!(ass, again);
+
+    // 不限制时全部保留
+    let ass = convert_danmaku(&xml, DanmakuFormat::Ass, "Arial", DanmakuLimit::unlimited())
+        .unwrap()
+        .unwrap();
+    assert_eq!(dialogue_texts(&ass).len(), 12);
+}
+
+#[test]
+fn test_danmaku_limit_total_and_order() {
+    use rvd::core::danmaku::convert_danmaku;
+
+    // 乱序输入，每秒一条
+    let xml = danmaku_xml(10, |i| (9 - i) as f64);
+    let limit = DanmakuLimit {
+        per_second: None,
+        total: Some(5),
+    };
+    let ass = convert_danmaku(&xml, DanmakuFormat::Ass, "Arial", limit).unwrap().unwrap();
+    // 按时间排序后均匀保留第 0、2、4、6、8 秒
+    assert_eq!(dialogue_texts(&ass), ["c9", "c7", "c5", "c3", "c1"]);
+
+    // XML 格式不受限制
+    let converted = convert_danmaku(&xml, DanmakuFormat::Xml, "Arial", limit).unwrap().unwrap();
+    assert_eq!(converted.matches("<d ").count(), 10);
+
+    // 默认限制每秒 20 条
+    let dense = danmaku_xml(100, |i| i as f64 * 0.001);
+    let ass = convert_danmaku(&dense, DanmakuFormat::Ass, "Arial", DanmakuLimit::default())
+        .unwrap()
+        .unwrap();
+    assert_eq!(dialogue_texts(&ass).len(), rvd::core::danmaku::DEFAULT_PER_SECOND);
+}
@@ -0,0 +1,519 @@
+// 下载器单元测试
+use rvd::core::downloader::Downloader;
+use rvd::utils::http::HttpClient;
+use std::sync::Arc;
+
+fn downloader() -> Downloader {
+    let client = HttpClient::new().unwrap().with_retry_count(1);
+    Downloader::new(Arc::new(client), 1)
+}
+
+#[tokio::test]
+async fn test_download_falls_back_to_backup_url_on_client_error() {
+    let mut server = mockito::Server::new_async().await;
+    let primary = server
+        .mock("GET", "/primary.m4s")
+        .with_status(403)
+        .create_async()
+        .await;
+    let backup = server
+        .mock("GET", "/backup.m4s")
+        .with_status(200)
+        .with_body("video data")
+        .create_async()
+        .await;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = temp_dir.path().join("video.m4s");
+    downloader()
+        .download_with_fallback(
+            &format!("{}/primary.m4s", server.url()),
+            &[format!("{}/backup.m4s", server.url())],
+            &output,
+            None,
+        )
+        .await
+        .unwrap();
+
+    primary.assert_async().await;
+    backup.assert_async().await;
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "video data");
+}
+
+#[tokio::test]
+async fn test_download_fails_when_all_urls_fail() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = temp_dir.path().join("audio.m4s");
+    let result = downloader()
+        .download_with_fallback(
+            &format!("{}/a.m4s", server.url()),
+            &[format!("{}/b.m4s", server.url()), format!("{}/c.m4s", server.url())],
+            &output,
+            None,
+        )
+        .await;
+
+    assert!(result.is_err());
+    assert!(rvd::core::downloader::is_client_error(&result.unwrap_err()));
+}
+
+#[tokio::test]
+async fn test_download_with_small_buffer_size() {
+    let body: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/video.m4s")
+        .with_status(200)
+        .with_body(body.clone())
+        .create_async()
+        .await;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = temp_dir.path().join("video.m4s");
+    // 小于下限的值会被调整为 MIN_BUFFER_SIZE
+    downloader()
+        .with_buffer_size(1)
+        .download(&format!("{}/video.m4s", server.url()), &output, None)
+        .await
+        .unwrap();
+
+    assert_eq!(std::fs::read(&output).unwrap(), body);
+}
+
+#[tokio::test]
+async fn test_download_to_memory() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/cover.jpg")
+        .with_status(200)
+        .with_body("image bytes")
+        .create_async()
+        .await;
+
+    let data = downloader()
+        .download_to_memory(&format!("{}/cover.jpg", server.url()), 1024)
+        .await
+        .unwrap();
+    assert_eq!(data, b"image bytes");
+}
+
+#[tokio::test]
+async fn test_download_to_memory_rejects_large_resources() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/video.m4s")
+        .with_status(200)
+        .with_body(vec![0u8; 2048])
+        .create_async()
+        .await;
+
+    let result = downloader()
+        .download_to_memory(&format!("{}/video.m4s", server.url()), 1024)
+        .await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn test_supports_range_probe_is_cached_per_host() {
+    let mut server = mockito::Server::new_async().await;
+    let probe = server
+        .mock("GET", mockito::Matcher::Any)
+        .match_header("range", "bytes=0-0")
+        .with_status(206)
+        .with_header("content-range", "bytes 0-0/100")
+        .with_body("v")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let downloader = downloader();
+    assert!(downloader.supports_range(&format!("{}/a.m4s", server.url())).await);
+    // 同一主机的其他地址直接使用缓存结果
+    assert!(downloader.supports_range(&format!("{}/b.m4s", server.url())).await);
+    probe.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_supports_range_rejects_hosts_ignoring_range() {
+    let mut server = mockito::Server::new_async().await;
+    // 声明支持却返回完整内容（部分PCDN节点的行为）
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("accept-ranges", "bytes")
+        .with_body("full video data")
+        .create_async()
+        .await;
+
+    assert!(!downloader().supports_range(&format!("{}/a.m4s", server.url())).await);
+}
+
+#[tokio::test]
+async fn test_supports_range_cache_is_shared_between_downloaders() {
+    let mut server = mockito::Server::new_async().await;
+    let probe = server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(206)
+        .with_header("content-range", "bytes 0-0/100")
+        .with_body("v")
+        .expect(1)
+        .create_async()
+        .await;
+
+    // 同一分P的视频、音频下载器共享探测结果
+    let video = downloader();
+    let audio = downloader().with_range_support(video.range_support_cache());
+    assert!(video.supports_range(&format!("{}/video.m4s", server.url())).await);
+    assert!(audio.supports_range(&format!("{}/audio.m4s", server.url())).await);
+    probe.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_supports_range_does_not_cache_error_status() {
+    let mut server = mockito::Server::new_async().await;
+    let throttled = server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(429)
+        .expect(1)
+        .create_async()
+        .await;
+
+    let downloader = downloader();
+    assert!(!downloader.supports_range(&format!("{}/a.m4s", server.url())).await);
+    throttled.assert_async().await;
+
+    // 限流结束后重新探测
+    let probe = server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(206)
+        .with_header("content-range", "bytes 0-0/100")
+        .with_body("v")
+        .expect(1)
+        .create_async()
+        .await;
+    assert!(downloader.supports_range(&format!("{}/a.m4s", server.url())).await);
+    probe.assert_async().await;
+}
+
+#[tokio::test]
+async fn test_content_length_rejects_error_status() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("HEAD", "/forbidden.m4s")
+        .with_status(403)
+        .with_header("content-length", "162")
+        .create_async()
+        .await;
+    server
+        .mock("HEAD", "/video.m4s")
+        .with_status(200)
+        .with_header("content-length", "1048576")
+        .create_async()
+        .await;
+
+    let client = HttpClient::new().unwrap().with_retry_count(1);
+    assert!(client.content_length(&format!("{}/forbidden.m4s", server.url())).await.is_err());
+    assert_eq!(
+        client.content_length(&format!("{}/video.m4s", server.url())).await.unwrap(),
+        1048576
+    );
+}
+
+#[tokio::test]
+async fn test_download_clip_fetches_only_needed_segments() {
+    use rvd::core::clip::ClipRange;
+    use rvd::types::SegmentBase;
+
+    // sidx：时间单位 1000，三个片段各 1 秒，大小 4、6、2 字节
+    let mut sidx = Vec::new();
+    sidx.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0x03, 0xe8, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 3]);
+    for size in [4u32, 6, 2] {
+        sidx.extend_from_slice(&size.to_be_bytes());
+        sidx.extend_from_slice(&1000u32.to_be_bytes());
+        sidx.extend_from_slice(&0u32.to_be_bytes());
+    }
+    let mut index = ((sidx.len() + 8) as u32).to_be_bytes().to_vec();
+    index.extend_from_slice(b"sidx");
+    index.extend_from_slice(&sidx);
+
+    let init = b"INITDATA";
+    let index_end = init.len() + index.len() - 1;
+    let mut header = init.to_vec();
+    header.extend_from_slice(&index);
+
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", "/video.m4s")
+        .match_header("range", format!("bytes=0-{}", index_end).as_str())
+        .with_status(206)
+        .with_body(header)
+        .create_async()
+        .await;
+    // 只请求第二个片段
+    let media = server
+        .mock("GET", "/video.m4s")
+        .match_header("range", format!("bytes={}-{}", index_end + 5, index_end + 10).as_str())
+        .with_status(206)
+        .with_body("BBBBBB")
+        .expect(1)
+        .create_async()
+        .await;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = temp_dir.path().join("video.m4s");
+    let start = downloader()
+        .download_clip(
+            &format!("{}/video.m4s", server.url()),
+            &[],
+            SegmentBase {
+                initialization: (0, init.len() as u64 - 1),
+                index_range: (init.len() as u64, index_end as u64),
+            },
+            ClipRange { start: 1.2, end: 1.8 },
+            &output,
+            None,
+        )
+        .await
+        .unwrap();
+
+    media.assert_async().await;
+    assert_eq!(start, 1.0);
+    assert_eq!(std::fs::read(&output).unwrap(), b"INITDATABBBBBB");
+}
+
+#[tokio::test]
+async fn test_download_resumes_part_file() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("HEAD", "/video.m4s")
+        .with_status(200)
+        .with_header("content-length", "10")
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/video.m4s")
+        .match_header("range", "bytes=0-0")
+        .with_status(206)
+        .with_body("a")
+        .create_async()
+        .await;
+    // 续传前比对已下载部分的末尾字节
+    server
+        .mock("GET", "/video.m4s")
+        .match_header("range", "bytes=0-3")
+        .with_status(206)
+        .with_body("abcd")
+        .create_async()
+        .await;
+    let rest = server
+        .mock("GET", "/video.m4s")
+        .match_header("range", "bytes=4-")
+        .with_status(206)
+        .with_body("efghij")
+        .expect(1)
+        .create_async()
+        .await;
+
+    // 上次中断留下的 `.part` 及其续传信息
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = temp_dir.path().join("video.m4s");
+    let part = rvd::utils::file::part_path(&output);
+    std::fs::write(&part, "abcd").unwrap();
+    std::fs::write(rvd::utils::file::part_meta_path(&part), r#"{"size":10}"#).unwrap();
+
+    downloader()
+        .download(&format!("{}/video.m4s", server.url()), &output, None)
+        .await
+        .unwrap();
+
+    rest.assert_async().await;
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "abcdefghij");
+    assert!(!part.exists());
+    assert!(!rvd::utils::file::part_meta_path(&part).exists());
+}
+
+/// 服务端提供10字节的文件 `abcdefghij`，返回整个文件的请求mock
+async fn mock_restartable_file(server: &mut mockito::ServerGuard) -> mockito::Mock {
+    server
+        .mock("HEAD", "/video.m4s")
+        .with_status(200)
+        .with_header("content-length", "10")
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/video.m4s")
+        .match_header("range", "bytes=0-0")
+        .with_status(206)
+        .with_body("a")
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/video.m4s")
+        .match_header("range", "bytes=0-3")
+        .with_status(206)
+        .with_body("abcd")
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/video.m4s")
+        .match_header("range", mockito::Matcher::Missing)
+        .with_status(200)
+        .with_body("abcdefghij")
+        .expect(1)
+        .create_async()
+        .await
+}
+
+async fn download_over_part(
+    downloader: rvd::core::downloader::Downloader,
+    server: &mockito::ServerGuard,
+    part_content: &str,
+    meta: Option<&str>,
+) -> String {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = temp_dir.path().join("video.m4s");
+    let part = rvd::utils::file::part_path(&output);
+    std::fs::write(&part, part_content).unwrap();
+    if let Some(meta) = meta {
+        std::fs::write(rvd::utils::file::part_meta_path(&part), meta).unwrap();
+    }
+
+    downloader
+        .download(&format!("{}/video.m4s", server.url()), &output, None)
+        .await
+        .unwrap();
+    std::fs::read_to_string(&output).unwrap()
+}
+
+#[tokio::test]
+async fn test_corrupt_part_file_restarts_from_zero() {
+    // 末尾字节与服务器不一致
+    let mut server = mockito::Server::new_async().await;
+    let full = mock_restartable_file(&mut server).await;
+    let content = download_over_part(downloader(), &server, "abXd", Some(r#"{"size":10}"#)).await;
+    full.assert_async().await;
+    assert_eq!(content, "abcdefghij");
+}
+
+#[tokio::test]
+async fn test_part_file_for_different_size_restarts_from_zero() {
+    // 记录的大小与服务器上的文件不同（如换了清晰度或文件已更新）
+    let mut server = mockito::Server::new_async().await;
+    let full = mock_restartable_file(&mut server).await;
+    let content = download_over_part(downloader(), &server, "abcd", Some(r#"{"size":20}"#)).await;
+    full.assert_async().await;
+    assert_eq!(content, "abcdefghij");
+
+    // 没有续传信息
+    let mut server = mockito::Server::new_async().await;
+    let full = mock_restartable_file(&mut server).await;
+    let content = download_over_part(downloader(), &server, "abcd", None).await;
+    full.assert_async().await;
+    assert_eq!(content, "abcdefghij");
+}
+
+#[tokio::test]
+async fn test_force_overwrite_partial_skips_resume() {
+    let mut server = mockito::Server::new_async().await;
+    let full = mock_restartable_file(&mut server).await;
+    let content = download_over_part(
+        downloader().with_force_overwrite_partial(true),
+        &server,
+        "abcd",
+        Some(r#"{"size":10}"#),
+    )
+    .await;
+    full.assert_async().await;
+    assert_eq!(content, "abcdefghij");
+}
+
+#[tokio::test]
+async fn test_failed_download_leaves_only_part_file() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("GET", mockito::Matcher::Any)
+        .with_status(404)
+        .create_async()
+        .await;
+
+    // 最终文件名只在下载完成后出现
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = temp_dir.path().join("video.m4s");
+    assert!(downloader()
+        .download(&format!("{}/video.m4s", server.url()), &output, None)
+        .await
+        .is_err());
+    assert!(!output.exists());
+}
+
+#[tokio::test]
+async fn test_chunked_download_resumes_completed_chunks() {
+    let mut server = mockito::Server::new_async().await;
+    server
+        .mock("HEAD", "/video.m4s")
+        .with_status(200)
+        .with_header("content-length", "10")
+        .create_async()
+        .await;
+    server
+        .mock("GET", "/video.m4s")
+        .match_header("range", "bytes=0-0")
+        .with_status(206)
+        .with_body("a")
+        .create_async()
+        .await;
+    // 已完成的第一个分段不再请求
+    let first = server
+        .mock("GET", "/video.m4s")
+        .match_header("range", "bytes=0-3")
+        .with_status(206)
+        .with_body("abcd")
+        .expect(0)
+        .create_async()
+        .await;
+    let second = server
+        .mock("GET", "/video.m4s")
+        .match_header("range", "bytes=4-7")
+        .with_status(206)
+        .with_body("efgh")
+        .expect(1)
+        .create_async()
+        .await;
+    let third = server
+        .mock("GET", "/video.m4s")
+        .match_header("range", "bytes=8-9")
+        .with_status(206)
+        .with_body("ij")
+        .expect(1)
+        .create_async()
+        .await;
+
+    // 上次中断时第一个分段已完成，第二个分段只下载了一部分
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = temp_dir.path().join("video.m4s");
+    let part = rvd::utils::file::part_path(&output);
+    let chunks = rvd::utils::file::chunk_dir_path(&part);
+    std::fs::create_dir_all(&chunks).unwrap();
+    std::fs::write(chunks.join("chunk_0"), "abcd").unwrap();
+    std::fs::write(chunks.join("chunk_1.part"), "ef").unwrap();
+    std::fs::write(rvd::utils::file::part_meta_path(&part), r#"{"size":10}"#).unwrap();
+
+    downloader()
+        .with_chunk_size(4)
+        .download(&format!("{}/video.m4s", server.url()), &output, None)
+        .await
+        .unwrap();
+
+    first.assert_async().await;
+    second.assert_async().await;
+    third.assert_async().await;
+    assert_eq!(std::fs::read_to_string(&output).unwrap(), "abcdefghij");
+    assert!(!chunks.exists());
+}
@@ -0,0 +1,749 @@
+// 混流模块单元测试
+use rvd::core::muxer::{
+    build_concat_args, build_concat_list, build_contact_sheet_args, build_mux_args,
+    audio_stream_extension, build_remux_args, duration_matches, ffmpeg_encoder, ffprobe_path, hdr10_metadata_filter, metadata_tags,
+    parse_ffmpeg_components, parse_ffmpeg_duration, parse_ffmpeg_resolution, parse_ffprobe_duration, EmbedOptions, FfmpegCapabilities, HdrType, MergeOutputFormat, MuxOptions, Muxer,
+    AudioTrack, ReencodeOptions, TrimOptions,
+};
+use rvd::types::{Chapter, VideoInfo};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+fn args_to_strings(args: &[OsString]) -> Vec<String> {
+    args.iter().map(|a| a.to_string_lossy().to_string()).collect()
+}
+
+#[test]
+fn test_build_mux_args_basic() {
+    let options = MuxOptions::default();
+    let args = build_mux_args(
+        Path::new("video.m4s"),
+        Path::new("audio.m4s"),
+        Path::new("out.mp4"),
+        &options,
+        None,
+    );
+    let args = args_to_strings(&args);
+
+    assert_eq!(
+        args,
+        vec![
+            "-i", "video.m4s", "-i", "audio.m4s", "-map", "0:v:0", "-map", "1:a:0", "-c:v",
+            "copy", "-c:a", "copy", "-y", "out.mp4",
+        ]
+    );
+}
+
+#[test]
+fn test_build_mux_args_extra_audio() {
+    let options = MuxOptions {
+        subtitles: vec![PathBuf::from("zh.srt")],
+        extra_audio: vec![
+            AudioTrack {
+                path: PathBuf::from("audio_ja.m4s"),
+                language: "ja".to_string(),
+            },
+            AudioTrack {
+                path: PathBuf::from("audio_zh.m4s"),
+                language: "zh-Hans".to_string(),
+            },
+        ],
+        ..Default::default()
+    };
+    let args = build_mux_args(
+        Path::new("video.m4s"),
+        Path::new("audio.m4s"),
+        Path::new("out.mkv"),
+        &options,
+        None,
+    );
+    let args = args_to_strings(&args);
+
+    // 额外音轨紧跟主音轨输入，字幕的输入序号随之后移
+    let inputs: Vec<&str> = args
+        .windows(2)
+        .filter(|w| w[0] == "-i")
+        .map(|w| w[1].as_str())
+        .collect();
+    assert_eq!(inputs, vec!["video.m4s", "audio.m4s", "audio_ja.m4s", "audio_zh.m4s", "zh.srt"]);
+    for map in ["1:a:0", "2:a:0", "3:a:0", "4:0"] {
+        assert!(args.windows(2).any(|w| w == ["-map", map]), "missing -map {}", map);
+    }
+    assert!(args.windows(2).any(|w| w == ["-metadata:s:a:1", "language=ja"]));
+    assert!(args.windows(2).any(|w| w == ["-metadata:s:a:2", "language=zh-Hans"]));
+    assert!(args.windows(2).any(|w| w == ["-disposition:a:2", "0"]));
+}
+
+#[test]
+fn test_build_mux_args_inputs_before_output_options() {
+    let options = MuxOptions {
+        subtitles: vec![PathBuf::from("zh.srt"), PathBuf::from("en.srt")],
+        chapters: vec![Chapter {
+            title: "正片".to_string(),
+            start: 0,
+            end: 100,
+        }],
+        cover: Some(PathBuf::from("cover.jpg")),
+        hdr: HdrType::Sdr,
+        video_codec: String::new(),
+        reencode: None,
+        progress: None,
+        metadata: Vec::new(),
+        trim: None,
+        font: None,
+        extra_audio: Vec::new(),
+    };
+    let args = build_mux_args(
+        Path::new("video.m4s"),
+        Path::new("audio.m4s"),
+        Path::new("out.mp4"),
+        &options,
+        Some(Path::new("out.chapters.txt")),
+    );
+    let args = args_to_strings(&args);
+
+    // 所有输入都必须出现在编码参数之前
+    let last_input = args.iter().rposition(|a| a == "-i").unwrap();
+    let first_codec = args.iter().position(|a| a == "-c:v").unwrap();
+    assert!(last_input < first_codec);
+
+    // 每个字幕都被映射
+    assert!(args.windows(2).any(|w| w == ["-map", "2:0"]));
+    assert!(args.windows(2).any(|w| w == ["-map", "3:0"]));
+
+    // 封面作为附加图片
+    assert!(args.windows(2).any(|w| w == ["-map", "4:0"]));
+    assert!(args.windows(2).any(|w| w == ["-disposition:v:1", "attached_pic"]));
+
+    // 章节元数据来自最后一个输入
+    assert!(args.windows(2).any(|w| w == ["-map_metadata", "5"]));
+    assert!(args.windows(2).any(|w| w == ["-map_chapters", "5"]));
+
+    assert_eq!(args.last().unwrap(), "out.mp4");
+}
+
+#[test]
+fn test_build_mux_args_subtitle_codec_follows_container() {
+    let options = MuxOptions {
+        subtitles: vec![PathBuf::from("zh.srt")],
+        ..Default::default()
+    };
+    let subtitle_codec = |output: &str| {
+        let args = build_mux_args(
+            Path::new("video.m4s"),
+            Path::new("audio.m4s"),
+            Path::new(output),
+            &options,
+            None,
+        );
+        let args = args_to_strings(&args);
+        let index = args.iter().position(|a| a == "-c:s").unwrap();
+        args[index + 1].clone()
+    };
+
+    assert_eq!(subtitle_codec("out.mp4"), "mov_text");
+    assert_eq!(subtitle_codec("out.mov"), "mov_text");
+    assert_eq!(subtitle_codec("out.mkv"), "srt");
+}
+
+#[test]
+fn test_build_mux_args_attaches_font_to_mkv() {
+    let options = MuxOptions {
+        subtitles: vec![PathBuf::from("zh.srt")],
+        font: Some(PathBuf::from("/fonts/NotoSansCJK-Regular.ttc")),
+        ..Default::default()
+    };
+    let build = |output: &str, options: &MuxOptions| {
+        let args = build_mux_args(
+            Path::new("video.m4s"),
+            Path::new("audio.m4s"),
+            Path::new(output),
+            options,
+            None,
+        );
+        args_to_strings(&args)
+    };
+
+    let args = build("out.mkv", &options);
+    assert!(args
+        .windows(2)
+        .any(|w| w == ["-attach", "/fonts/NotoSansCJK-Regular.ttc"]));
+    assert!(args
+        .windows(2)
+        .any(|w| w == ["-metadata:s:t:0", "mimetype=application/x-truetype-font"]));
+
+    // MP4 不支持附件
+    assert!(!build("out.mp4", &options).iter().any(|a| a == "-attach"));
+
+    // 没有字幕时不附加字体
+    let no_subtitles = MuxOptions { subtitles: Vec::new(), ..options };
+    assert!(!build("out.mkv", &no_subtitles).iter().any(|a| a == "-attach"));
+}
+
+#[test]
+fn test_merge_output_format_from_path() {
+    assert_eq!(
+        MergeOutputFormat::from_path(Path::new("a/b.MKV")),
+        Some(MergeOutputFormat::Mkv)
+    );
+    assert_eq!(
+        MergeOutputFormat::from_path(Path::new("b.mp4")),
+        Some(MergeOutputFormat::Mp4)
+    );
+    assert_eq!(MergeOutputFormat::from_path(Path::new("b.flv")), None);
+    assert_eq!(MergeOutputFormat::from_path(Path::new("b")), None);
+    assert_eq!(MergeOutputFormat::Mov.extension(), "mov");
+}
+
+#[test]
+fn test_parse_ffmpeg_duration() {
+    let stderr = "Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'out.mp4':\n  Metadata:\n    major_brand     : isom\n  Duration: 01:02:03.50, start: 0.000000, bitrate: 1234 kb/s\n";
+    assert_eq!(parse_ffmpeg_duration(stderr), Some(3723.5));
+
+    assert_eq!(parse_ffmpeg_duration("Duration: N/A, bitrate: N/A"), None);
+    assert_eq!(parse_ffmpeg_duration("no duration here"), None);
+}
+
+#[test]
+fn test_parse_ffprobe_duration() {
+    assert_eq!(parse_ffprobe_duration("3723.500000\n"), Some(3723.5));
+    assert_eq!(parse_ffprobe_duration("N/A\n"), None);
+    assert_eq!(parse_ffprobe_duration(""), None);
+}
+
+#[test]
+fn test_ffprobe_path_next_to_ffmpeg() {
+    assert_eq!(
+        ffprobe_path(Path::new("/opt/ffmpeg/bin/ffmpeg")),
+        PathBuf::from("/opt/ffmpeg/bin/ffprobe")
+    );
+    assert_eq!(
+        ffprobe_path(Path::new("C:/tools/ffmpeg.exe")),
+        PathBuf::from("C:/tools/ffprobe.exe")
+    );
+    assert_eq!(ffprobe_path(Path::new("ffmpeg")), PathBuf::from("ffprobe"));
+    // 自定义的包装脚本使用PATH中的ffprobe
+    assert_eq!(ffprobe_path(Path::new("/usr/local/bin/my-ffmpeg")), PathBuf::from("ffprobe"));
+}
+
+#[test]
+fn test_parse_ffmpeg_resolution() {
+    let stderr = "Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'P01.mp4':\n  Duration: 00:10:00.00, start: 0.000000, bitrate: 2000 kb/s\n  Stream #0:0[0x1](und): Video: h264 (High) (avc1 / 0x31637661), yuv420p(tv, bt709, progressive), 1920x1080 [SAR 1:1 DAR 16:9], 1800 kb/s, 30 fps\n  Stream #0:1[0x2](und): Audio: aac (LC), 48000 Hz, stereo, fltp, 192 kb/s\n";
+    assert_eq!(parse_ffmpeg_resolution(stderr), Some((1920, 1080)));
+
+    // 只有音频流
+    let audio_only = "  Stream #0:0: Audio: aac (LC), 48000 Hz, stereo, fltp\n";
+    assert_eq!(parse_ffmpeg_resolution(audio_only), None);
+}
+
+#[test]
+fn test_build_concat_list() {
+    let inputs = vec![
+        PathBuf::from("/videos/Lecture/P01_Intro.mp4"),
+        PathBuf::from("/videos/Lecture/P02_It's fine.mp4"),
+    ];
+    assert_eq!(
+        build_concat_list(&inputs),
+        "file '/videos/Lecture/P01_Intro.mp4'\nfile '/videos/Lecture/P02_It'\\''s fine.mp4'\n"
+    );
+}
+
+#[test]
+fn test_build_concat_args() {
+    let args = build_concat_args(Path::new("list.txt"), Path::new("Lecture.mp4"));
+    let args = args_to_strings(&args);
+
+    assert_eq!(
+        args,
+        vec![
+            "-f", "concat", "-safe", "0", "-i", "list.txt", "-map", "0", "-c", "copy", "-y",
+            "Lecture.mp4"
+        ]
+    );
+}
+
+#[test]
+fn test_duration_matches() {
+    // 完全一致
+    assert!(duration_matches(300.0, 300));
+    // 短视频允许3秒误差
+    assert!(duration_matches(302.5, 300));
+    assert!(!duration_matches(290.0, 300));
+    // 长视频允许2%误差
+    assert!(duration_matches(3650.0, 3600));
+    assert!(!duration_matches(1800.0, 3600));
+}
+
+#[test]
+fn test_build_mux_args_stream_copy_by_default() {
+    let args = build_mux_args(
+        Path::new("video.m4s"),
+        Path::new("audio.m4s"),
+        Path::new("out.mp4"),
+        &MuxOptions::default(),
+        None,
+    );
+    let args = args_to_strings(&args);
+
+    assert!(args.windows(2).any(|w| w == ["-c:v", "copy"]));
+    assert!(args.windows(2).any(|w| w == ["-c:a", "copy"]));
+    assert!(!args.iter().any(|a| a == "-crf"));
+}
+
+#[test]
+fn test_build_mux_args_reencode() {
+    let options = MuxOptions {
+        cover: Some(PathBuf::from("cover.jpg")),
+        reencode: Some(ReencodeOptions {
+            video_codec: "avc".to_string(),
+            audio_codec: None,
+            crf: Some(23),
+            video_bitrate: None,
+        }),
+        ..Default::default()
+    };
+    let args = build_mux_args(
+        Path::new("video.m4s"),
+        Path::new("audio.m4s"),
+        Path::new("out.mp4"),
+        &options,
+        None,
+    );
+    let args = args_to_strings(&args);
+
+    assert!(args.windows(2).any(|w| w == ["-c:v:0", "libx264"]));
+    assert!(args.windows(2).any(|w| w == ["-crf", "23"]));
+    // 封面不重新编码，音频未指定编码器时直接复制
+    assert!(args.windows(2).any(|w| w == ["-c:v:1", "copy"]));
+    assert!(args.windows(2).any(|w| w == ["-c:a", "copy"]));
+
+    // 指定码率时优先于CRF
+    let options = MuxOptions {
+        reencode: Some(ReencodeOptions {
+            video_codec: "hevc".to_string(),
+            audio_codec: Some("aac".to_string()),
+            crf: Some(23),
+            video_bitrate: Some("4M".to_string()),
+        }),
+        ..Default::default()
+    };
+    let args = build_mux_args(
+        Path::new("video.m4s"),
+        Path::new("audio.m4s"),
+        Path::new("out.mp4"),
+        &options,
+        None,
+    );
+    let args = args_to_strings(&args);
+    assert!(args.windows(2).any(|w| w == ["-c:v:0", "libx265"]));
+    assert!(args.windows(2).any(|w| w == ["-b:v:0", "4M"]));
+    assert!(args.windows(2).any(|w| w == ["-c:a", "aac"]));
+    assert!(!args.iter().any(|a| a == "-crf"));
+}
+
+#[test]
+fn test_ffmpeg_encoder_aliases() {
+    assert_eq!(ffmpeg_encoder("AVC"), "libx264");
+    assert_eq!(ffmpeg_encoder("h265"), "libx265");
+    assert_eq!(ffmpeg_encoder("h264_nvenc"), "h264_nvenc");
+}
+
+#[test]
+fn test_build_mux_args_hdr_metadata() {
+    let build = |hdr: HdrType| {
+        let options = MuxOptions {
+            hdr,
+            ..Default::default()
+        };
+        args_to_strings(&build_mux_args(
+            Path::new("video.m4s"),
+            Path::new("audio.m4s"),
+            Path::new("out.mp4"),
+            &options,
+            None,
+        ))
+    };
+
+    // SDR不添加色彩参数
+    let args = build(HdrType::Sdr);
+    assert!(!args.iter().any(|a| a.starts_with("-color")));
+    assert!(!args.iter().any(|a| a == "-strict"));
+
+    // 复制流时 `-color_*` 不改写码流，不能使用
+    let args = build(HdrType::Hdr10);
+    assert!(!args.iter().any(|a| a.starts_with("-color")));
+
+    let args = build(HdrType::DolbyVision);
+    assert!(args.windows(2).any(|w| w == ["-strict", "unofficial"]));
+    assert!(args.windows(2).any(|w| w == ["-c:v", "copy"]));
+}
+
+#[test]
+fn test_hdr10_metadata_filter() {
+    assert!(hdr10_metadata_filter("HEVC").unwrap().starts_with("hevc_metadata="));
+    assert!(hdr10_metadata_filter("AV1").unwrap().starts_with("av1_metadata="));
+    assert_eq!(hdr10_metadata_filter("AVC"), None);
+}
+
+/// 运行 `ffmpeg`/`ffprobe` 并返回标准输出，命令不可用或失败时返回 None
+fn run_tool(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program).args(args).output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[tokio::test]
+async fn test_mux_hdr10_writes_color_metadata() {
+    // 需要带 libx265 的FFmpeg生成测试用的HEVC流，不可用时跳过
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let video = temp_dir.path().join("video.mp4");
+    let audio = temp_dir.path().join("audio.m4a");
+    let output = temp_dir.path().join("out.mp4");
+    let (video_str, audio_str) = (video.to_str().unwrap(), audio.to_str().unwrap());
+    let generated = run_tool(
+        "ffmpeg",
+        &[
+            "-v", "error", "-f", "lavfi", "-i", "testsrc=duration=1:size=64x64:rate=10",
+            "-c:v", "libx265", "-x265-params", "log-level=none", "-y", video_str,
+        ],
+    )
+    .and_then(|_| {
+        run_tool(
+            "ffmpeg",
+            &["-v", "error", "-f", "lavfi", "-i", "sine=duration=1", "-c:a", "aac", "-y", audio_str],
+        )
+    });
+    if generated.is_none() {
+        println!("⚠ 跳过：FFmpeg 不可用或缺少 libx265");
+        return;
+    }
+
+    let muxer = Muxer::new_with_options(None, false).unwrap();
+    let options = MuxOptions {
+        hdr: HdrType::Hdr10,
+        video_codec: "HEVC".to_string(),
+        ..Default::default()
+    };
+    muxer.mux_advanced(&video, &audio, &output, &options).await.unwrap();
+
+    // 直接读取码流中的色彩信息，确认复制流后输出被识别为HDR10
+    let probed = run_tool(
+        "ffprobe",
+        &[
+            "-v", "error", "-select_streams", "v:0",
+            "-show_entries", "stream=color_primaries,color_transfer,color_space",
+            "-of", "default=noprint_wrappers=1", output.to_str().unwrap(),
+        ],
+    )
+    .expect("ffprobe failed");
+    assert!(probed.contains("color_primaries=bt2020"), "{}", probed);
+    assert!(probed.contains("color_transfer=smpte2084"), "{}", probed);
+    assert!(probed.contains("color_space=bt2020nc"), "{}", probed);
+}
+
+#[test]
+fn test_hdr_type_from_quality_id() {
+    assert_eq!(HdrType::from_quality_id(80), HdrType::Sdr);
+    assert_eq!(HdrType::from_quality_id(125), HdrType::Hdr10);
+    assert_eq!(HdrType::from_quality_id(126), HdrType::DolbyVision);
+}
+
+#[test]
+fn test_build_contact_sheet_args() {
+    let args = build_contact_sheet_args(
+        Path::new("out.mp4"),
+        Path::new("out.contact.jpg"),
+        4,
+        3,
+        599.5,
+    );
+    let args = args_to_strings(&args);
+
+    assert_eq!(
+        args,
+        vec![
+            "-v",
+            "error",
+            "-i",
+            "out.mp4",
+            "-vf",
+            "fps=12/600,scale=320:-2,tile=4x3",
+            "-frames:v",
+            "1",
+            "-q:v",
+            "3",
+            "-y",
+            "out.contact.jpg",
+        ]
+    );
+}
+
+#[test]
+fn test_build_remux_args() {
+    let args = build_remux_args(Path::new("video.m4s"), Path::new("out.video.mp4"));
+    let args = args_to_strings(&args);
+
+    assert_eq!(
+        args,
+        vec!["-i", "video.m4s", "-map", "0", "-c", "copy", "-y", "out.video.mp4"]
+    );
+}
+
+#[test]
+fn test_parse_progress_line() {
+    use rvd::core::muxer::parse_progress_line;
+
+    assert_eq!(parse_progress_line("out_time_us=12500000"), Some(12500));
+    assert_eq!(parse_progress_line("out_time_ms=12500000\n"), Some(12500));
+    assert_eq!(parse_progress_line("out_time_us=N/A"), None);
+    assert_eq!(parse_progress_line("out_time=00:00:12.500000"), None);
+    assert_eq!(parse_progress_line("progress=continue"), None);
+}
+
+#[test]
+fn test_build_mux_args_with_progress() {
+    let args = |options: &MuxOptions| {
+        build_mux_args(
+            Path::new("video.m4s"),
+            Path::new("audio.m4s"),
+            Path::new("out.mp4"),
+            options,
+            None,
+        )
+    };
+
+    let options = MuxOptions {
+        progress: Some(std::sync::Arc::new(indicatif::ProgressBar::hidden())),
+        ..Default::default()
+    };
+    let with_progress = args(&options);
+    let pos = with_progress.iter().position(|a| a == "-progress").unwrap();
+    assert_eq!(with_progress[pos + 1], "pipe:1");
+    // 进度参数需要在输出文件之前
+    assert_eq!(with_progress.last().unwrap(), "out.mp4");
+
+    assert!(!args(&MuxOptions::default()).iter().any(|a| a == "-progress"));
+}
+
+#[test]
+fn test_build_mux_args_metadata_after_chapters() {
+    let options = MuxOptions {
+        chapters: vec![Chapter {
+            title: "正片".to_string(),
+            start: 0,
+            end: 100,
+        }],
+        metadata: vec![
+            ("title".to_string(), "测试视频".to_string()),
+            ("artist".to_string(), "UP主".to_string()),
+        ],
+        ..Default::default()
+    };
+    let args = build_mux_args(
+        Path::new("video.m4s"),
+        Path::new("audio.m4s"),
+        Path::new("out.mp4"),
+        &options,
+        Some(Path::new("out.chapters.txt")),
+    );
+    let args = args_to_strings(&args);
+
+    let map_metadata = args.iter().position(|a| a == "-map_metadata").unwrap();
+    let title = args.iter().position(|a| a == "title=测试视频").unwrap();
+    // 标签写在章节元数据之后，避免被章节文件覆盖
+    assert!(map_metadata < title);
+    assert_eq!(args[title - 1], "-metadata");
+    assert!(args.windows(2).any(|w| w == ["-metadata", "artist=UP主"]));
+}
+
+#[test]
+fn test_embed_options_mux_options() {
+    let subtitles = vec![PathBuf::from("zh.srt")];
+    let chapters = vec![Chapter {
+        title: "正片".to_string(),
+        start: 0,
+        end: 100,
+    }];
+    let cover = Path::new("cover.jpg");
+    let metadata = vec![("title".to_string(), "测试".to_string())];
+
+    // 默认只嵌入章节和字幕
+    let options =
+        EmbedOptions::default().mux_options(&subtitles, &chapters, Some(cover), metadata.clone());
+    assert_eq!(options.subtitles, subtitles);
+    assert_eq!(options.chapters.len(), 1);
+    assert_eq!(options.cover, None);
+    assert!(options.metadata.is_empty());
+
+    let options =
+        EmbedOptions::all().mux_options(&subtitles, &chapters, Some(cover), metadata.clone());
+    assert_eq!(options.cover, Some(cover.to_path_buf()));
+    assert_eq!(options.metadata, metadata);
+
+    let embed = EmbedOptions {
+        chapters: false,
+        subtitles: false,
+        ..EmbedOptions::all()
+    };
+    let options = embed.mux_options(&subtitles, &chapters, Some(cover), metadata);
+    assert!(options.subtitles.is_empty());
+    assert!(options.chapters.is_empty());
+}
+
+#[test]
+fn test_metadata_tags() {
+    let video_info = VideoInfo {
+        id: "BV1xx411c7mD".to_string(),
+        aid: 170001,
+        title: "测试视频".to_string(),
+        description: String::new(),
+        duration: 300,
+        uploader: "UP主".to_string(),
+        uploader_mid: "12345".to_string(),
+        upload_date: "2024-01-01".to_string(),
+        cover_url: String::new(),
+        pages: Vec::new(),
+        is_bangumi: false,
+        ep_id: None,
+        extra_data: None,
+    };
+
+    // 空的简介不写入
+    assert_eq!(
+        metadata_tags(&video_info, None),
+        vec![
+            ("title".to_string(), "测试视频".to_string()),
+            ("artist".to_string(), "UP主".to_string()),
+            ("date".to_string(), "2024-01-01".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_build_mux_args_trim() {
+    let options = MuxOptions {
+        subtitles: vec![PathBuf::from("zh.srt")],
+        trim: Some(TrimOptions {
+            video_seek: 1.5,
+            audio_seek: 0.25,
+            start: 3601.5,
+            duration: 60.0,
+        }),
+        ..Default::default()
+    };
+    let args = build_mux_args(
+        Path::new("video.m4s"),
+        Path::new("audio.m4s"),
+        Path::new("out.mp4"),
+        &options,
+        None,
+    );
+    let args = args_to_strings(&args);
+
+    // 每个输入前各自指定裁剪位置，字幕按原视频中的时间裁剪
+    assert_eq!(
+        &args[..18],
+        [
+            "-ss", "1.500", "-t", "60.000", "-i", "video.m4s", "-ss", "0.250", "-t", "60.000",
+            "-i", "audio.m4s", "-ss", "3601.500", "-t", "60.000", "-i", "zh.srt"
+        ]
+    );
+}
+
+#[test]
+fn test_parse_ffmpeg_components() {
+    let encoders = "Encoders:
+ V..... = Video
+ A..... = Audio
+ S..... = Subtitle
+ .F.... = Frame-level multithreading
+ ......
+ ------
+ V....D libx264              libx264 H.264 / AVC / MPEG-4 AVC (codec h264)
+ V....D mpeg4                MPEG-4 part 2
+ A....D aac                  AAC (Advanced Audio Coding)
+ S..... mov_text             3GPP Timed Text subtitle
+";
+    let parsed = parse_ffmpeg_components(encoders);
+    assert_eq!(parsed.len(), 4);
+    assert!(parsed.contains("libx264"));
+    assert!(parsed.contains("mov_text"));
+    assert!(!parsed.contains("="));
+
+    let filters = "Filters:
+  T.. = Timeline support
+  .S. = Slice threading
+  ..C = Command support
+  A = Audio input/output
+  | = Source or sink filter
+ T.C scale             V->V       Scale the input video size and/or convert the image format.
+ ... tile              V->V       Tile several successive frames together.
+ ... fps               V->V       Force constant framerate.
+ ... abuffer           |->A       Buffer audio frames, and make them accessible to the filterchain.
+";
+    let parsed = parse_ffmpeg_components(filters);
+    assert_eq!(parsed.len(), 4);
+    assert!(parsed.contains("tile") && parsed.contains("abuffer"));
+}
+
+#[test]
+fn test_ffmpeg_capabilities_require() {
+    let capabilities = FfmpegCapabilities {
+        encoders: ["aac", "mpeg4"].iter().map(|s| s.to_string()).collect(),
+        filters: ["fps", "scale"].iter().map(|s| s.to_string()).collect(),
+    };
+
+    assert!(capabilities.require(&["aac"], &["fps", "scale"]).is_ok());
+
+    let err = capabilities.require(&["aac", &ffmpeg_encoder("avc")], &[]).unwrap_err();
+    assert!(matches!(
+        err,
+        rvd::DownloaderError::FFmpegMissingComponent { ref kind, ref name }
+            if kind == "encoder" && name == "libx264"
+    ));
+    // 错误信息给出缺少的组件和解决办法
+    let message = err.to_string();
+    assert!(message.contains("libx264 encoder"));
+    assert!(message.contains("full FFmpeg build"));
+
+    let err = capabilities.require(&[], &["fps", "tile"]).unwrap_err();
+    assert!(err.to_string().contains("tile filter"));
+}
+
+#[test]
+fn test_ffmpeg_capabilities_require_reencode_allows_copy() {
+    let capabilities = FfmpegCapabilities {
+        encoders: ["aac"].iter().map(|s| s.to_string()).collect(),
+        filters: Default::default(),
+    };
+    let reencode = |video: &str, audio: Option<&str>| ReencodeOptions {
+        video_codec: video.to_string(),
+        audio_codec: audio.map(str::to_string),
+        crf: None,
+        video_bitrate: None,
+    };
+
+    // `copy` 直接复制流，不在 `ffmpeg -encoders` 列表中
+    assert!(capabilities.require_reencode(&reencode("copy", Some("aac"))).is_ok());
+    assert!(capabilities.require_reencode(&reencode("copy", Some("COPY"))).is_ok());
+    let err = capabilities.require_reencode(&reencode("avc", Some("copy"))).unwrap_err();
+    assert!(err.to_string().contains("libx264 encoder"));
+}
+
+#[test]
+fn test_audio_stream_extension() {
+    // 解析器给出的音频编码名称
+    assert_eq!(audio_stream_extension("M4A"), "audio.m4a");
+    assert_eq!(audio_stream_extension("E-AC-3 (Dolby)"), "audio.m4a");
+    // MP4 不接受直接复制的 FLAC
+    assert_eq!(audio_stream_extension("FLAC"), "audio.flac");
+    assert_eq!(audio_stream_extension("FLAC (Hi-Res)"), "audio.flac");
+
+    let output = Path::new("/out/title.mp4");
+    assert_eq!(
+        output.with_extension(audio_stream_extension("FLAC (Hi-Res)")),
+        Path::new("/out/title.audio.flac")
+    );
+}
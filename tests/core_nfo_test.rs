@@ -0,0 +1,64 @@
+// NFO 生成单元测试
+use rvd::core::nfo::build_nfo;
+use rvd::types::{BilibiliExtra, ExtraData, Page, VideoInfo};
+
+fn page(number: usize, title: &str, duration: u64) -> Page {
+    Page {
+        number,
+        title: title.to_string(),
+        cid: number.to_string(),
+        duration,
+        ep_id: None,
+    }
+}
+
+fn video_info(pages: Vec<Page>) -> VideoInfo {
+    VideoInfo {
+        id: "BV1xx411c7mD".to_string(),
+        aid: 170001,
+        title: "测试 & <视频>".to_string(),
+        description: "简介".to_string(),
+        duration: 300,
+        uploader: "UP主".to_string(),
+        uploader_mid: "12345".to_string(),
+        upload_date: "2024-01-01".to_string(),
+        cover_url: "https://i0.hdslb.com/cover.jpg".to_string(),
+        pages,
+        is_bangumi: false,
+        ep_id: None,
+        extra_data: Some(ExtraData::Bilibili(BilibiliExtra {
+            tname: "科技".to_string(),
+            tags: vec!["编程".to_string(), "Rust".to_string()],
+            ..Default::default()
+        })),
+    }
+}
+
+#[test]
+fn test_build_nfo() {
+    let info = video_info(vec![page(1, "P1", 90)]);
+    let nfo = build_nfo(&info, info.pages.first());
+
+    assert!(nfo.starts_with("<?xml"));
+    // 特殊字符被转义
+    assert!(nfo.contains("<title>测试 &amp; &lt;视频&gt;</title>"));
+    assert!(nfo.contains("<plot>简介</plot>"));
+    assert!(nfo.contains("<studio>UP主</studio>"));
+    assert!(nfo.contains("<premiered>2024-01-01</premiered>"));
+    assert!(nfo.contains("<year>2024</year>"));
+    // 时长按分钟向上取整
+    assert!(nfo.contains("<runtime>2</runtime>"));
+    assert!(nfo.contains("<genre>科技</genre>"));
+    assert!(nfo.contains("<tag>编程</tag>\n  <tag>Rust</tag>"));
+    assert!(nfo.contains(r#"<uniqueid type="bilibili" default="true">BV1xx411c7mD</uniqueid>"#));
+    assert!(nfo.ends_with("</movie>\n"));
+}
+
+#[test]
+fn test_build_nfo_multi_page_title() {
+    let info = video_info(vec![page(1, "上", 60), page(2, "下", 600)]);
+    let nfo = build_nfo(&info, info.pages.get(1));
+
+    assert!(nfo.contains("<title>测试 &amp; &lt;视频&gt; - 下</title>"));
+    assert!(nfo.contains("<runtime>10</runtime>"));
+}
@@ -0,0 +1,63 @@
+use rvd::core::progress::{ProgressTracker, RunSummary};
+use rvd::utils::i18n::{set_lang, Lang};
+use std::time::Duration;
+
+#[test]
+fn test_run_summary_counts() {
+    let tracker = ProgressTracker::new();
+    assert!(tracker.summary().is_empty());
+
+    // 同一视频的多个分P只算一个视频
+    tracker.record_page("BV1", 1024);
+    tracker.record_page("BV1", 2048);
+    tracker.record_page("BV2", 1024);
+    tracker.record_skipped(2);
+    tracker.record_failed(1);
+
+    let summary = tracker.summary();
+    assert_eq!(summary.videos, 2);
+    assert_eq!(summary.pages, 3);
+    assert_eq!(summary.bytes, 4096);
+    assert_eq!(summary.skipped, 2);
+    assert_eq!(summary.failed, 1);
+    assert!(!summary.is_empty());
+
+    // 只有跳过的分P时也输出摘要
+    let tracker = ProgressTracker::new();
+    tracker.record_skipped(1);
+    assert!(!tracker.summary().is_empty());
+}
+
+#[test]
+fn test_run_summary_lines() {
+    set_lang(Lang::En);
+
+    let summary = RunSummary {
+        videos: 1,
+        pages: 2,
+        bytes: 30 * 1024 * 1024,
+        elapsed: Duration::from_secs(75),
+        skipped: 0,
+        failed: 0,
+    };
+    assert_eq!(summary.average_speed(), 30 * 1024 * 1024 / 75);
+    let lines = summary.lines();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[1].contains("1 video(s), 2 page(s)"));
+    assert!(lines[2].contains("30.0 MB"));
+    assert!(lines[3].contains("0:01:15"));
+    assert!(lines[4].contains("409.6 KB/s"));
+
+    // 有跳过或失败时才显示对应的行
+    let lines = RunSummary {
+        skipped: 1,
+        failed: 2,
+        ..summary
+    }
+    .lines();
+    assert!(lines[5].contains("Skipped: 1"));
+    assert!(lines[6].contains("Failed: 2"));
+
+    // 用时为0时速度为0
+    assert_eq!(RunSummary::default().average_speed(), 0);
+}
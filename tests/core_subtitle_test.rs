@@ -0,0 +1,34 @@
+// 字幕功能单元测试
+use rvd::core::subtitle::filter_by_language;
+use rvd::types::Subtitle;
+
+fn subtitle(code: &str) -> Subtitle {
+    Subtitle {
+        language: code.to_string(),
+        language_code: code.to_string(),
+        url: format!("https://example.com/{}.json", code),
+    }
+}
+
+#[test]
+fn test_filter_subtitles_by_language() {
+    let subtitles = vec![subtitle("zh-Hans"), subtitle("en"), subtitle("ai-zh"), subtitle("ja")];
+
+    let filtered = filter_by_language(subtitles.clone(), &["ZH-hans".to_string(), "ja".to_string()]);
+    let codes: Vec<&str> = filtered.iter().map(|s| s.language_code.as_str()).collect();
+    assert_eq!(codes, vec!["zh-Hans", "ja"]);
+
+    assert!(filter_by_language(subtitles, &["fr".to_string()]).is_empty());
+}
+
+#[test]
+fn test_convert_subtitle() {
+    use rvd::core::subtitle::convert_subtitle;
+
+    let json = br#"{"body":[{"from":1.5,"to":3.25,"content":"Hello"},{"from":61.0,"to":62.0,"content":"World"}]}"#;
+    let srt = convert_subtitle(json).unwrap();
+    assert!(srt.starts_with("1\n00:00:01,500 --> 00:00:03,250\nHello\n"));
+    assert!(srt.contains("2\n00:01:01,000 --> 00:01:02,000\nWorld\n"));
+
+    assert!(convert_subtitle(b"not json").is_err());
+}
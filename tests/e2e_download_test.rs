@@ -1,7 +1,7 @@
 // 端到端下载测试 - 覆盖 Task 32.1-32.9 的所有场景
-use rvd::app::Orchestrator;
-use rvd::cli::Cli;
-use rvd::platform::bilibili::BilibiliPlatform;
+use rvd::app::{DownloadOptions, Orchestrator};
+use rvd::core::danmaku::DanmakuFormat;
+use rvd::platform::bilibili::{ApiMode, BilibiliPlatform};
 use rvd::platform::Platform;
 use rvd::types::Auth;
 use rvd::utils::config::Config;
@@ -214,40 +214,23 @@ async fn test_32_2_download_bangumi_single_episode() {
     let output_dir = setup_test_dir("bangumi");
     let test_url = "https://www.bilibili.com/bangumi/play/ep691450";
 
-    let cli = Cli {
+    let options = DownloadOptions {
         url: Some(test_url.to_string()),
-        quality: None,
-        codec: None,
         output: Some(output_dir.to_string_lossy().to_string()),
         cookie: config.auth.as_ref().and_then(|a| a.cookie.as_ref().cloned()),
         access_token: config.auth.as_ref().and_then(|a| a.access_token.as_ref().cloned()),
-        pages: Some("1".to_string()), // 只下载第一集
+        pages: Some(vec![1]), // 只下载第一集
         threads: 2,
         skip_subtitle: true,
         skip_cover: true,
-        skip_mux: false,
-        interactive: false,
-        config_file: None,
-        verbose: false,
-        info_only: false,
         ffmpeg_path: config.paths.as_ref().and_then(|p| p.ffmpeg.as_ref().cloned()),
-        use_tv_api: false,
-        use_app_api: false,
-        use_intl_api: false,
-        download_danmaku: false,
-        danmaku_format: "ass".to_string(),
-        login_qrcode: false,
-        login_tv: false,
-        use_aria2c: false,
-        aria2c_path: None,
-        aria2c_args: None,
-        use_mp4box: false,
+        ..Default::default()
     };
 
-    if let Ok(orchestrator) = Orchestrator::new(config, &cli) {
+    if let Ok(orchestrator) = Orchestrator::new(config, &options) {
         let result = timeout(
             Duration::from_secs(TEST_TIMEOUT_SECS),
-            orchestrator.run(cli),
+            orchestrator.run(options),
         )
         .await;
 
@@ -495,40 +478,24 @@ async fn test_32_6_download_with_tv_api() {
     let output_dir = setup_test_dir("tv_api");
     let test_url = "BV1qt4y1X7TW";
 
-    let cli = Cli {
+    let options = DownloadOptions {
         url: Some(test_url.to_string()),
-        quality: None,
-        codec: None,
         output: Some(output_dir.to_string_lossy().to_string()),
         cookie: config.auth.as_ref().and_then(|a| a.cookie.as_ref().cloned()),
         access_token: config.auth.as_ref().and_then(|a| a.access_token.as_ref().cloned()),
-        pages: Some("1".to_string()),
+        pages: Some(vec![1]),
         threads: 2,
         skip_subtitle: true,
         skip_cover: true,
-        skip_mux: false,
-        interactive: false,
-        config_file: None,
-        verbose: false,
-        info_only: false,
         ffmpeg_path: config.paths.as_ref().and_then(|p| p.ffmpeg.as_ref().cloned()),
-        use_tv_api: true, // 使用TV API
-        use_app_api: false,
-        use_intl_api: false,
-        download_danmaku: false,
-        danmaku_format: "ass".to_string(),
-        login_qrcode: false,
-        login_tv: false,
-        use_aria2c: false,
-        aria2c_path: None,
-        aria2c_args: None,
-        use_mp4box: false,
+        api_mode: Some(ApiMode::TV), // 使用TV API
+        ..Default::default()
     };
 
-    if let Ok(orchestrator) = Orchestrator::new(config, &cli) {
+    if let Ok(orchestrator) = Orchestrator::new(config, &options) {
         let result = timeout(
             Duration::from_secs(TEST_TIMEOUT_SECS),
-            orchestrator.run(cli),
+            orchestrator.run(options),
         )
         .await;
 
@@ -564,40 +531,26 @@ async fn test_32_7_download_danmaku_xml() {
     let output_dir = setup_test_dir("danmaku_xml");
     let test_url = "BV1B647zAENv"; // 使用有弹幕的视频
 
-    let cli = Cli {
+    let options = DownloadOptions {
         url: Some(test_url.to_string()),
-        quality: None,
-        codec: None,
         output: Some(output_dir.to_string_lossy().to_string()),
         cookie: config.auth.as_ref().and_then(|a| a.cookie.as_ref().cloned()),
         access_token: config.auth.as_ref().and_then(|a| a.access_token.as_ref().cloned()),
-        pages: Some("1".to_string()),
+        pages: Some(vec![1]),
         threads: 2,
         skip_subtitle: true,
         skip_cover: true,
         skip_mux: true, // 跳过混流，只测试弹幕下载
-        interactive: false,
-        config_file: None,
-        verbose: false,
-        info_only: false,
         ffmpeg_path: config.paths.as_ref().and_then(|p| p.ffmpeg.as_ref().cloned()),
-        use_tv_api: false,
-        use_app_api: false,
-        use_intl_api: false,
         download_danmaku: true, // 下载弹幕
-        danmaku_format: "xml".to_string(),
-        login_qrcode: false,
-        login_tv: false,
-        use_aria2c: false,
-        aria2c_path: None,
-        aria2c_args: None,
-        use_mp4box: false,
+        danmaku_format: DanmakuFormat::Xml,
+        ..Default::default()
     };
 
-    if let Ok(orchestrator) = Orchestrator::new(config, &cli) {
+    if let Ok(orchestrator) = Orchestrator::new(config, &options) {
         let result = timeout(
             Duration::from_secs(TEST_TIMEOUT_SECS),
-            orchestrator.run(cli),
+            orchestrator.run(options),
         )
         .await;
 
@@ -626,40 +579,25 @@ async fn test_32_7_download_danmaku_ass() {
     let output_dir = setup_test_dir("danmaku_ass");
     let test_url = "BV1uv411q7Mv";
 
-    let cli = Cli {
+    let options = DownloadOptions {
         url: Some(test_url.to_string()),
-        quality: None,
-        codec: None,
         output: Some(output_dir.to_string_lossy().to_string()),
         cookie: config.auth.as_ref().and_then(|a| a.cookie.as_ref().cloned()),
         access_token: config.auth.as_ref().and_then(|a| a.access_token.as_ref().cloned()),
-        pages: Some("1".to_string()),
+        pages: Some(vec![1]),
         threads: 2,
         skip_subtitle: true,
         skip_cover: true,
         skip_mux: true,
-        interactive: false,
-        config_file: None,
-        verbose: false,
-        info_only: false,
         ffmpeg_path: config.paths.as_ref().and_then(|p| p.ffmpeg.as_ref().cloned()),
-        use_tv_api: false,
-        use_app_api: false,
-        use_intl_api: false,
         download_danmaku: true,
-        danmaku_format: "ass".to_string(), // ASS格式
-        login_qrcode: false,
-        login_tv: false,
-        use_aria2c: false,
-        aria2c_path: None,
-        aria2c_args: None,
-        use_mp4box: false,
+        ..Default::default()
     };
 
-    if let Ok(orchestrator) = Orchestrator::new(config, &cli) {
+    if let Ok(orchestrator) = Orchestrator::new(config, &options) {
         let result = timeout(
             Duration::from_secs(TEST_TIMEOUT_SECS),
-            orchestrator.run(cli),
+            orchestrator.run(options),
         )
         .await;
 
@@ -730,40 +668,23 @@ async fn test_32_8_download_with_chapters() {
     // 使用番剧链接，番剧通常有章节信息
     let test_url = "https://www.bilibili.com/bangumi/play/ep394750";
 
-    let cli = Cli {
+    let options = DownloadOptions {
         url: Some(test_url.to_string()),
-        quality: None,
-        codec: None,
         output: Some(output_dir.to_string_lossy().to_string()),
         cookie: config.auth.as_ref().and_then(|a| a.cookie.as_ref().cloned()),
         access_token: config.auth.as_ref().and_then(|a| a.access_token.as_ref().cloned()),
-        pages: Some("1".to_string()),
+        pages: Some(vec![1]),
         threads: 2,
         skip_subtitle: true,
         skip_cover: true,
-        skip_mux: false, // 需要混流才能嵌入章节
-        interactive: false,
-        config_file: None,
-        verbose: false,
-        info_only: false,
         ffmpeg_path: config.paths.as_ref().and_then(|p| p.ffmpeg.as_ref().cloned()),
-        use_tv_api: false,
-        use_app_api: false,
-        use_intl_api: false,
-        download_danmaku: false,
-        danmaku_format: "ass".to_string(),
-        login_qrcode: false,
-        login_tv: false,
-        use_aria2c: false,
-        aria2c_path: None,
-        aria2c_args: None,
-        use_mp4box: false,
+        ..Default::default()
     };
 
-    if let Ok(orchestrator) = Orchestrator::new(config, &cli) {
+    if let Ok(orchestrator) = Orchestrator::new(config, &options) {
         let result = timeout(
             Duration::from_secs(TEST_TIMEOUT_SECS),
-            orchestrator.run(cli),
+            orchestrator.run(options),
         )
         .await;
 
@@ -796,40 +717,25 @@ async fn test_32_9_interactive_mode_disabled() {
     let output_dir = setup_test_dir("non_interactive");
     let test_url = "BV1qt4y1X7TW";
 
-    let cli = Cli {
+    let options = DownloadOptions {
         url: Some(test_url.to_string()),
-        quality: Some("1080P,720P".to_string()),
-        codec: Some("hevc,avc".to_string()),
+        quality_priority: vec!["1080P".to_string(), "720P".to_string()],
+        codec_priority: vec!["hevc".to_string(), "avc".to_string()],
         output: Some(output_dir.to_string_lossy().to_string()),
         cookie: config.auth.as_ref().and_then(|a| a.cookie.as_ref().cloned()),
         access_token: config.auth.as_ref().and_then(|a| a.access_token.as_ref().cloned()),
-        pages: Some("1".to_string()),
+        pages: Some(vec![1]),
         threads: 2,
         skip_subtitle: true,
         skip_cover: true,
-        skip_mux: false,
-        interactive: false, // 非交互式
-        config_file: None,
-        verbose: false,
-        info_only: false,
         ffmpeg_path: config.paths.as_ref().and_then(|p| p.ffmpeg.as_ref().cloned()),
-        use_tv_api: false,
-        use_app_api: false,
-        use_intl_api: false,
-        download_danmaku: false,
-        danmaku_format: "ass".to_string(),
-        login_qrcode: false,
-        login_tv: false,
-        use_aria2c: false,
-        aria2c_path: None,
-        aria2c_args: None,
-        use_mp4box: false,
+        ..Default::default()
     };
 
-    if let Ok(orchestrator) = Orchestrator::new(config, &cli) {
+    if let Ok(orchestrator) = Orchestrator::new(config, &options) {
         let result = timeout(
             Duration::from_secs(TEST_TIMEOUT_SECS),
-            orchestrator.run(cli),
+            orchestrator.run(options),
         )
         .await;
 
@@ -865,40 +771,23 @@ async fn test_complete_download_workflow() {
     let output_dir = setup_test_dir("complete");
     let test_url = "BV1uv411q7Mv"; // 使用有字幕和弹幕的视频
 
-    let cli = Cli {
+    let options = DownloadOptions {
         url: Some(test_url.to_string()),
-        quality: Some("1080P,720P".to_string()),
-        codec: Some("avc".to_string()),
+        quality_priority: vec!["1080P".to_string(), "720P".to_string()],
+        codec_priority: vec!["avc".to_string()],
         output: Some(output_dir.to_string_lossy().to_string()),
         cookie: config.auth.as_ref().and_then(|a| a.cookie.as_ref().cloned()),
         access_token: config.auth.as_ref().and_then(|a| a.access_token.as_ref().cloned()),
-        pages: Some("1".to_string()),
-        threads: 4,
-        skip_subtitle: false, // 下载字幕
-        skip_cover: false,    // 下载封面
-        skip_mux: false,
-        interactive: false,
-        config_file: None,
-        verbose: true,
-        info_only: false,
+        pages: Some(vec![1]),
         ffmpeg_path: config.paths.as_ref().and_then(|p| p.ffmpeg.as_ref().cloned()),
-        use_tv_api: false,
-        use_app_api: false,
-        use_intl_api: false,
         download_danmaku: true, // 下载弹幕
-        danmaku_format: "ass".to_string(),
-        login_qrcode: false,
-        login_tv: false,
-        use_aria2c: false,
-        aria2c_path: None,
-        aria2c_args: None,
-        use_mp4box: false,
+        ..Default::default()
     };
 
-    if let Ok(orchestrator) = Orchestrator::new(config, &cli) {
+    if let Ok(orchestrator) = Orchestrator::new(config, &options) {
         let result = timeout(
             Duration::from_secs(TEST_TIMEOUT_SECS),
-            orchestrator.run(cli),
+            orchestrator.run(options),
         )
         .await;
 
@@ -942,40 +831,23 @@ async fn test_multi_page_download() {
     let output_dir = setup_test_dir("multi_page");
     let test_url = "BV1At41167aj";
 
-    let cli = Cli {
+    let options = DownloadOptions {
         url: Some(test_url.to_string()),
-        quality: None,
-        codec: None,
         output: Some(output_dir.to_string_lossy().to_string()),
         cookie: config.auth.as_ref().and_then(|a| a.cookie.as_ref().cloned()),
         access_token: config.auth.as_ref().and_then(|a| a.access_token.as_ref().cloned()),
-        pages: Some("1,2".to_string()), // 下载前两个分P
+        pages: Some(vec![1, 2]), // 下载前两个分P
         threads: 2,
         skip_subtitle: true,
         skip_cover: true,
-        skip_mux: false,
-        interactive: false,
-        config_file: None,
-        verbose: false,
-        info_only: false,
         ffmpeg_path: config.paths.as_ref().and_then(|p| p.ffmpeg.as_ref().cloned()),
-        use_tv_api: false,
-        use_app_api: false,
-        use_intl_api: false,
-        download_danmaku: false,
-        danmaku_format: "ass".to_string(),
-        login_qrcode: false,
-        login_tv: false,
-        use_aria2c: false,
-        aria2c_path: None,
-        aria2c_args: None,
-        use_mp4box: false,
+        ..Default::default()
     };
 
-    if let Ok(orchestrator) = Orchestrator::new(config, &cli) {
+    if let Ok(orchestrator) = Orchestrator::new(config, &options) {
         let result = timeout(
             Duration::from_secs(TEST_TIMEOUT_SECS * 2), // 多分P给更多时间
-            orchestrator.run(cli),
+            orchestrator.run(options),
         )
         .await;
 
@@ -1017,40 +889,24 @@ async fn test_info_only_mode() {
     let output_dir = setup_test_dir("info_only");
     let test_url = "BV1qt4y1X7TW";
 
-    let cli = Cli {
+    let options = DownloadOptions {
         url: Some(test_url.to_string()),
-        quality: None,
-        codec: None,
         output: Some(output_dir.to_string_lossy().to_string()),
         cookie: config.auth.as_ref().and_then(|a| a.cookie.as_ref().cloned()),
         access_token: config.auth.as_ref().and_then(|a| a.access_token.as_ref().cloned()),
-        pages: None,
         threads: 2,
         skip_subtitle: true,
         skip_cover: true,
         skip_mux: true,
-        interactive: false,
-        config_file: None,
-        verbose: false,
         info_only: true, // 仅显示信息
         ffmpeg_path: config.paths.as_ref().and_then(|p| p.ffmpeg.as_ref().cloned()),
-        use_tv_api: false,
-        use_app_api: false,
-        use_intl_api: false,
-        download_danmaku: false,
-        danmaku_format: "ass".to_string(),
-        login_qrcode: false,
-        login_tv: false,
-        use_aria2c: false,
-        aria2c_path: None,
-        aria2c_args: None,
-        use_mp4box: false,
+        ..Default::default()
     };
 
-    if let Ok(orchestrator) = Orchestrator::new(config, &cli) {
+    if let Ok(orchestrator) = Orchestrator::new(config, &options) {
         let result = timeout(
             Duration::from_secs(30), // Info-only应该很快
-            orchestrator.run(cli),
+            orchestrator.run(options),
         )
         .await;
 
@@ -0,0 +1,112 @@
+// 错误类型与退出码测试
+use rvd::error::{exit_code, DownloaderError};
+
+#[test]
+fn test_exit_codes_by_category() {
+    assert_eq!(
+        DownloaderError::RiskControl("blocked".to_string()).exit_code(),
+        exit_code::NETWORK
+    );
+    assert_eq!(DownloaderError::AuthRequired.exit_code(), exit_code::AUTH);
+    assert_eq!(
+        DownloaderError::AccessDenied("vip".to_string()).exit_code(),
+        exit_code::AUTH
+    );
+    assert_eq!(
+        DownloaderError::MembersOnly("BV1xx".to_string()).exit_code(),
+        exit_code::AUTH
+    );
+    assert_eq!(
+        DownloaderError::VideoNotFound("BV1xx".to_string()).exit_code(),
+        exit_code::NOT_FOUND
+    );
+    assert_eq!(
+        DownloaderError::UnsupportedPlatform("https://example.com".to_string()).exit_code(),
+        exit_code::UNSUPPORTED
+    );
+    assert_eq!(
+        DownloaderError::PartialFailure { failed: 1, total: 3 }.exit_code(),
+        exit_code::PARTIAL_FAILURE
+    );
+    assert_eq!(
+        DownloaderError::DrmProtected("encrypted".to_string()).exit_code(),
+        exit_code::UNSUPPORTED
+    );
+    assert_eq!(DownloaderError::FFmpegNotFound.exit_code(), exit_code::MUX);
+    assert_eq!(
+        DownloaderError::FFmpegMissingComponent {
+            kind: "encoder".to_string(),
+            name: "libx264".to_string(),
+        }
+        .exit_code(),
+        exit_code::MUX
+    );
+    assert_eq!(
+        DownloaderError::MuxFailed("ffmpeg exited".to_string()).exit_code(),
+        exit_code::MUX
+    );
+    assert_eq!(
+        DownloaderError::Config("bad".to_string()).exit_code(),
+        exit_code::USAGE
+    );
+    assert_eq!(
+        DownloaderError::RegionLocked("ep1".to_string()).exit_code(),
+        exit_code::REGION_LOCKED
+    );
+    assert_eq!(
+        DownloaderError::Parse("bad json".to_string()).exit_code(),
+        exit_code::GENERAL
+    );
+}
+
+#[test]
+fn test_partial_failure_message() {
+    let err = DownloaderError::PartialFailure { failed: 2, total: 5 };
+    assert_eq!(err.to_string(), "2 of 5 video(s) failed to download");
+}
+
+#[test]
+fn test_timeout_stage_and_message() {
+    let err = DownloaderError::Timeout {
+        stage: "request".to_string(),
+        url: "https://upos.example.com/audio.m4s".to_string(),
+    }
+    .in_stage("audio download (P3)");
+    assert_eq!(err.exit_code(), exit_code::NETWORK);
+    assert_eq!(
+        err.to_string(),
+        "Timed out during audio download (P3): https://upos.example.com/audio.m4s \
+         (check the network, or retry later)"
+    );
+
+    // 其他错误不受影响
+    let err = DownloaderError::Parse("bad json".to_string()).in_stage("parse");
+    assert!(matches!(err, DownloaderError::Parse(_)));
+}
+
+#[tokio::test]
+async fn test_reqwest_timeout_converted() {
+    // 接受连接但从不响应的服务器
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut connections = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            connections.push(socket);
+        }
+    });
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_millis(100))
+        .build()
+        .unwrap();
+    let url = format!("http://{}/slow", addr);
+    let err: DownloaderError = client.get(&url).send().await.unwrap_err().into();
+    match err {
+        DownloaderError::Timeout { stage, url: timed_out } => {
+            assert_eq!(stage, "request");
+            assert_eq!(timed_out, url);
+        }
+        other => panic!("expected timeout, got {:?}", other),
+    }
+}
@@ -1,5 +1,6 @@
 // 集成测试 - 测试完整的下载流程
-use rvd::app::Orchestrator;
+use rvd::app::{DownloadOptions, Orchestrator};
+use clap::Parser;
 use rvd::cli::Cli;
 use rvd::core::downloader::Downloader;
 use rvd::core::muxer::Muxer;
@@ -163,7 +164,7 @@ async fn test_get_subtitles() {
 
     // 获取字幕信息（字幕可能不存在，所以不强制要求成功）
     let subtitles = platform
-        .get_subtitles(&video_info.aid.to_string(), &first_page.cid)
+        .get_subtitles(&video_info.aid.to_string(), &first_page.cid, None)
         .await;
 
     // 字幕可能不存在，但API调用应该成功
@@ -175,38 +176,14 @@ async fn test_get_subtitles() {
 
 #[test]
 fn test_orchestrator_creation() {
-    let cli = Cli {
+    let options = DownloadOptions {
         url: Some(TEST_VIDEO_URL.to_string()),
-        quality: None,
-        codec: None,
-        output: None,
-        cookie: None,
-        access_token: None,
-        pages: None,
         threads: 2,
-        skip_subtitle: false,
-        skip_cover: false,
-        skip_mux: false,
-        interactive: false,
-        config_file: None,
-        verbose: false,
-        info_only: false,
-        ffmpeg_path: None,
-        use_tv_api: false,
-        use_app_api: false,
-        use_intl_api: false,
-        download_danmaku: false,
-        danmaku_format: "ass".to_string(),
-        login_qrcode: false,
-        login_tv: false,
-        use_aria2c: false,
-        aria2c_path: None,
-        aria2c_args: None,
-        use_mp4box: false,
+        ..Default::default()
     };
 
     let config = Config::default();
-    let result = Orchestrator::new(config, &cli);
+    let result = Orchestrator::new(config, &options);
 
     // Orchestrator创建可能失败（如果ffmpeg不可用）
     // 但至少不应该panic
@@ -281,41 +258,22 @@ async fn test_muxer_dolby_vision_support() {
 async fn test_info_only_mode() {
     let output_dir = setup_test_output_dir();
 
-    let cli = Cli {
+    let options = DownloadOptions {
         url: Some(TEST_VIDEO_URL.to_string()),
-        quality: None,
-        codec: None,
         output: Some(output_dir.join("test_info_only.mp4").to_string_lossy().to_string()),
-        cookie: None,
-        access_token: None,
-        pages: None,
         threads: 2,
         skip_subtitle: true,
         skip_cover: true,
         skip_mux: true,
-        interactive: false,
-        config_file: None,
-        verbose: false,
         info_only: true, // 仅显示信息，不下载
-        ffmpeg_path: None,
-        use_tv_api: false,
-        use_app_api: false,
-        use_intl_api: false,
-        download_danmaku: false,
-        danmaku_format: "ass".to_string(),
-        login_qrcode: false,
-        login_tv: false,
-        use_aria2c: false,
-        aria2c_path: None,
-        aria2c_args: None,
-        use_mp4box: false,
+        ..Default::default()
     };
 
     let config = Config::default();
 
     // 尝试创建orchestrator
-    if let Ok(orchestrator) = Orchestrator::new(config, &cli) {
-        let result = orchestrator.run(cli).await;
+    if let Ok(orchestrator) = Orchestrator::new(config, &options) {
+        let result = orchestrator.run(options).await;
 
         // info_only模式应该成功，且不产生文件
         if result.is_ok() {
@@ -342,35 +300,8 @@ async fn test_multi_page_selection() {
             println!("✓ 多分P视频解析成功，共 {} 个分P", video_info.pages.len());
 
             // 测试分P选择逻辑
-            let cli = Cli {
-                url: Some(multi_page_url.to_string()),
-                quality: None,
-                codec: None,
-                output: None,
-                cookie: None,
-                access_token: None,
-                pages: Some("1,2".to_string()),
-                threads: 2,
-                skip_subtitle: true,
-                skip_cover: true,
-                skip_mux: true,
-                interactive: false,
-                config_file: None,
-                verbose: false,
-                info_only: true,
-                ffmpeg_path: None,
-        use_tv_api: false,
-        use_app_api: false,
-        use_intl_api: false,
-        download_danmaku: false,
-        danmaku_format: "ass".to_string(),
-        login_qrcode: false,
-        login_tv: false,
-        use_aria2c: false,
-        aria2c_path: None,
-        aria2c_args: None,
-        use_mp4box: false,
-            };
+            let cli = Cli::try_parse_from(["rvd", multi_page_url, "--pages", "1,2", "--info-only"])
+                .unwrap();
 
             let parsed_pages = cli.parse_pages();
             assert!(parsed_pages.is_some());
@@ -425,6 +356,11 @@ async fn test_quality_codec_priority() {
             url: "https://example.com/1080p_avc.m4s".to_string(),
             size: 0,
             bandwidth: 3000000,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+            backup_urls: Vec::new(),
         },
         Stream {
             stream_type: StreamType::Video,
@@ -434,6 +370,11 @@ async fn test_quality_codec_priority() {
             url: "https://example.com/720p_hevc.m4s".to_string(),
             size: 0,
             bandwidth: 2000000,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+            backup_urls: Vec::new(),
         },
         Stream {
             stream_type: StreamType::Audio,
@@ -443,6 +384,11 @@ async fn test_quality_codec_priority() {
             url: "https://example.com/audio.m4s".to_string(),
             size: 0,
             bandwidth: 192000,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+            backup_urls: Vec::new(),
         },
     ];
 
@@ -450,6 +396,9 @@ async fn test_quality_codec_priority() {
     let preferences = StreamPreferences {
         quality_priority: vec!["1080P".to_string(), "720P".to_string()],
         codec_priority: vec!["avc".to_string(), "hevc".to_string()],
+        audio_language: None,
+        format_sort: None,
+        fps: None,
     };
 
     let result = select_best_streams(&streams, &preferences);
@@ -485,6 +434,7 @@ async fn test_file_naming_template() {
         }],
         is_bangumi: false,
         ep_id: None,
+        extra_data: None,
     };
 
     // 测试各种模板
@@ -0,0 +1,278 @@
+// Bilibili解析器测试：通过 MockTransport 注入固定的API响应，不访问网络
+use rvd::platform::bilibili::parser::{
+    append_access_key, fetch_batch, fetch_video_info_by_aid, fetch_video_info_by_bvid,
+    BatchSource,
+};
+use rvd::platform::bilibili::ParseOptions;
+use rvd::testing::MockTransport;
+use rvd::types::{Auth, ExtraData};
+use rvd::DownloaderError;
+use serde_json::json;
+
+fn video_info_json(bvid: &str, aid: u64, pages: usize) -> String {
+    let pages: Vec<_> = (1..=pages)
+        .map(|p| json!({ "page": p, "cid": 1000 + p, "part": format!("P{}", p), "duration": 60 * p }))
+        .collect();
+    json!({
+        "code": 0,
+        "message": "0",
+        "data": {
+            "aid": aid,
+            "bvid": bvid,
+            "cid": 1001,
+            "title": "测试视频",
+            "desc": "简介",
+            "pic": "https://i0.hdslb.com/cover.jpg",
+            "pubdate": 1700000000,
+            "owner": { "mid": 42, "name": "UP主" },
+            "pages": pages,
+            "tname": "科技",
+            "stat": { "view": 123, "like": 4 }
+        }
+    })
+    .to_string()
+}
+
+fn favorite_media(id: u64) -> serde_json::Value {
+    json!({
+        "id": id,
+        "title": format!("收藏{}", id),
+        "intro": "",
+        "cover": "",
+        "duration": 30,
+        "pubtime": 1700000000,
+        "attr": 0,
+        "page": 1,
+        "upper": { "mid": 42, "name": "UP主" },
+        "ugc": { "first_cid": id * 10 }
+    })
+}
+
+fn favorite_page_json(media_count: u32, ids: &[u64]) -> String {
+    json!({
+        "code": 0,
+        "message": "0",
+        "data": {
+            "info": {
+                "id": 7,
+                "title": "默认收藏夹",
+                "intro": "",
+                "media_count": media_count,
+                "ctime": 0,
+                "upper": { "mid": 42, "name": "UP主" }
+            },
+            "medias": ids.iter().map(|&id| favorite_media(id)).collect::<Vec<_>>()
+        }
+    })
+    .to_string()
+}
+
+#[tokio::test]
+async fn test_fetch_video_info_converts_response() {
+    let transport = MockTransport::new().with_response(
+        "bvid=BV1xx411c7mD",
+        &video_info_json("BV1xx411c7mD", 170001, 2),
+    );
+
+    let video = fetch_video_info_by_bvid(&transport, "BV1xx411c7mD", None)
+        .await
+        .unwrap();
+
+    assert_eq!(video.id, "BV1xx411c7mD");
+    assert_eq!(video.aid, 170001);
+    assert_eq!(video.uploader, "UP主");
+    assert_eq!(video.uploader_mid, "42");
+    assert_eq!(video.duration, 60);
+    assert_eq!(video.pages.len(), 2);
+    assert_eq!(video.pages[1].cid, "1002");
+    assert_eq!(video.pages[1].title, "P2");
+    match video.extra_data {
+        Some(ExtraData::Bilibili(extra)) => {
+            assert_eq!(extra.tname, "科技");
+            assert_eq!(extra.stat.view, 123);
+            assert_eq!(extra.stat.like, 4);
+        }
+        other => panic!("unexpected extra data: {:?}", other),
+    }
+    assert_eq!(
+        transport.requests(),
+        vec!["https://api.bilibili.com/x/web-interface/view?bvid=BV1xx411c7mD"]
+    );
+}
+
+#[tokio::test]
+async fn test_fetch_video_info_maps_error_codes() {
+    let transport = MockTransport::new()
+        .with_api_error("aid=1", -404, "啥都木有")
+        .with_api_error("aid=2", -352, "风控校验失败")
+        .with_api_error("aid=3", 62012, "仅UP主自己可见")
+        .with_api_error("aid=4", -10403, "抱歉您所在地区不可观看");
+
+    let err = fetch_video_info_by_aid(&transport, "1", None).await.unwrap_err();
+    assert!(matches!(err, DownloaderError::VideoNotFound(_)), "{:?}", err);
+
+    let err = fetch_video_info_by_aid(&transport, "2", None).await.unwrap_err();
+    assert!(matches!(err, DownloaderError::RiskControl(_)), "{:?}", err);
+
+    let err = fetch_video_info_by_aid(&transport, "3", None).await.unwrap_err();
+    assert!(matches!(err, DownloaderError::ContentRemoved(_)), "{:?}", err);
+
+    let err = fetch_video_info_by_aid(&transport, "4", None).await.unwrap_err();
+    assert!(matches!(err, DownloaderError::RegionLocked(_)), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_fetch_video_info_rejects_malformed_json() {
+    let transport = MockTransport::new().with_response("bvid=", "<html>502 Bad Gateway</html>");
+
+    let err = fetch_video_info_by_bvid(&transport, "BV1xx411c7mD", None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DownloaderError::Parse(_)), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_fetch_batch_follows_favorite_pages() {
+    // 共25个视频，每页20个：需要请求两页
+    let transport = MockTransport::new()
+        .with_response("pn=1&", &favorite_page_json(25, &[1, 2, 3]))
+        .with_response("pn=2&", &favorite_page_json(25, &[4]));
+    let source = BatchSource::Favorite {
+        fav_id: "7".to_string(),
+    };
+
+    let videos = fetch_batch(&transport, &source, None, None, &ParseOptions::default())
+        .await
+        .unwrap();
+
+    let ids: Vec<_> = videos.iter().map(|v| v.id.as_str()).collect();
+    assert_eq!(ids, vec!["av1", "av2", "av3", "av4"]);
+    assert_eq!(videos[3].pages[0].cid, "40");
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_fetch_batch_treats_missing_data_after_first_page_as_end() {
+    // 翻页期间视频被删除，第2页超出末尾时接口不返回 data
+    let transport = MockTransport::new()
+        .with_response("pn=1&", &favorite_page_json(25, &[1, 2, 3]))
+        .with_response("pn=2&", r#"{"code":0,"message":"0","data":null}"#);
+    let source = BatchSource::Favorite {
+        fav_id: "7".to_string(),
+    };
+
+    let videos = fetch_batch(&transport, &source, None, None, &ParseOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(videos.len(), 3);
+
+    // 第1页就缺少 data 仍然是错误
+    let transport = MockTransport::new().with_response("pn=1&", r#"{"code":0,"message":"0","data":null}"#);
+    let err = fetch_batch(&transport, &source, None, None, &ParseOptions::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DownloaderError::Parse(_)), "{:?}", err);
+}
+
+#[tokio::test]
+async fn test_favorite_multi_page_entry_defers_detail_fetch() {
+    // 多P视频只用收藏夹列表中的 first_cid 构建P1，不请求视频详情
+    let mut media = favorite_media(5);
+    media["page"] = json!(3);
+    let list = json!({
+        "code": 0,
+        "message": "0",
+        "data": {
+            "info": {
+                "id": 7, "title": "默认收藏夹", "intro": "", "media_count": 1, "ctime": 0,
+                "upper": { "mid": 42, "name": "UP主" }
+            },
+            "medias": [media]
+        }
+    })
+    .to_string();
+    let transport = MockTransport::new().with_response("fav/resource/list", &list);
+    let source = BatchSource::Favorite {
+        fav_id: "7".to_string(),
+    };
+
+    let videos = fetch_batch(&transport, &source, None, None, &ParseOptions::default())
+        .await
+        .unwrap();
+
+    assert_eq!(videos.len(), 1);
+    assert_eq!(videos[0].pages.len(), 1);
+    assert_eq!(videos[0].pages[0].cid, "50");
+    assert_eq!(videos[0].page_count(), 3);
+    assert!(videos[0].pages_incomplete());
+    assert_eq!(transport.requests().len(), 1);
+}
+
+#[tokio::test]
+async fn test_fetch_batch_stops_at_since_date() {
+    // 系列列表按发布时间倒序，第二个视频早于 since，不再翻页也不获取其详情
+    let series = json!({
+        "code": 0,
+        "message": "0",
+        "data": {
+            "meta": { "name": "系列", "description": "", "total": 100 },
+            "archives": [
+                { "aid": 1, "bvid": "BV1new", "title": "新", "pubdate": 1700000000 },
+                { "aid": 2, "bvid": "BV1old", "title": "旧", "pubdate": 1600000000 }
+            ],
+            "page": { "page_num": 1, "page_size": 30, "total": 100 }
+        }
+    })
+    .to_string();
+    let transport = MockTransport::new()
+        .with_response("series/archives", &series)
+        .with_response("bvid=BV1new", &video_info_json("BV1new", 1, 1));
+    let source = BatchSource::Series {
+        mid: "42".to_string(),
+        sid: "9".to_string(),
+    };
+    let options = ParseOptions {
+        since: Some("2023-01-01".to_string()),
+        ..Default::default()
+    };
+
+    let videos = fetch_batch(&transport, &source, None, None, &options)
+        .await
+        .unwrap();
+
+    assert_eq!(videos.len(), 1);
+    assert_eq!(videos[0].id, "BV1new");
+    assert_eq!(transport.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn test_mock_transport_rejects_unknown_url() {
+    let transport = MockTransport::new();
+
+    let err = fetch_video_info_by_bvid(&transport, "BV1xx411c7mD", None)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, DownloaderError::Api(_)), "{:?}", err);
+}
+
+#[test]
+fn test_append_access_key_from_tv_token() {
+    let url = "https://api.bilibili.com/x/player/wbi/playurl?avid=1&cid=2".to_string();
+
+    // 只有Cookie时不修改
+    let cookie_only = Auth {
+        cookie: Some("SESSDATA=abc".to_string()),
+        access_token: None,
+    };
+    assert_eq!(append_access_key(url.clone(), Some(&cookie_only)), url);
+    assert_eq!(append_access_key(url.clone(), None), url);
+
+    let tv = Auth {
+        cookie: None,
+        access_token: Some("token+1".to_string()),
+    };
+    assert_eq!(
+        append_access_key(url.clone(), Some(&tv)),
+        format!("{}&access_key=token%2B1", url)
+    );
+}
@@ -35,6 +35,11 @@ fn test_select_best_streams_by_quality() {
             url: "https://example.com/1080p_avc.m4s".to_string(),
             size: 0,
             bandwidth: 3000000,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+            backup_urls: Vec::new(),
         },
         Stream {
             stream_type: StreamType::Video,
@@ -44,6 +49,11 @@ fn test_select_best_streams_by_quality() {
             url: "https://example.com/720p_avc.m4s".to_string(),
             size: 0,
             bandwidth: 2000000,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+            backup_urls: Vec::new(),
         },
         Stream {
             stream_type: StreamType::Audio,
@@ -53,12 +63,20 @@ fn test_select_best_streams_by_quality() {
             url: "https://example.com/audio.m4s".to_string(),
             size: 0,
             bandwidth: 192000,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+            backup_urls: Vec::new(),
         },
     ];
 
     let preferences = StreamPreferences {
         quality_priority: vec!["1080P 高清".to_string(), "720P 高清".to_string()],
         codec_priority: vec!["avc".to_string(), "hevc".to_string()],
+        audio_language: None,
+        format_sort: None,
+        fps: None,
     };
 
     let result = select_best_streams(&streams, &preferences);
@@ -80,6 +98,11 @@ fn test_select_best_streams_by_codec() {
             url: "https://example.com/1080p_avc.m4s".to_string(),
             size: 0,
             bandwidth: 3000000,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+            backup_urls: Vec::new(),
         },
         Stream {
             stream_type: StreamType::Video,
@@ -89,6 +112,11 @@ fn test_select_best_streams_by_codec() {
             url: "https://example.com/1080p_hevc.m4s".to_string(),
             size: 0,
             bandwidth: 2500000,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+            backup_urls: Vec::new(),
         },
         Stream {
             stream_type: StreamType::Audio,
@@ -98,12 +126,20 @@ fn test_select_best_streams_by_codec() {
             url: "https://example.com/audio.m4s".to_string(),
             size: 0,
             bandwidth: 192000,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+            backup_urls: Vec::new(),
         },
     ];
 
     let preferences = StreamPreferences {
         quality_priority: vec!["1080P 高清".to_string()],
         codec_priority: vec!["hevc".to_string(), "avc".to_string()],
+        audio_language: None,
+        format_sort: None,
+        fps: None,
     };
 
     let result = select_best_streams(&streams, &preferences);
@@ -124,6 +160,11 @@ fn test_select_best_streams_fallback() {
             url: "https://example.com/480p_avc.m4s".to_string(),
             size: 0,
             bandwidth: 1000000,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+            backup_urls: Vec::new(),
         },
         Stream {
             stream_type: StreamType::Audio,
@@ -133,6 +174,11 @@ fn test_select_best_streams_fallback() {
             url: "https://example.com/audio.m4s".to_string(),
             size: 0,
             bandwidth: 128000,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+            backup_urls: Vec::new(),
         },
     ];
 
@@ -140,6 +186,9 @@ fn test_select_best_streams_fallback() {
     let preferences = StreamPreferences {
         quality_priority: vec!["4K 超清".to_string(), "1080P 高清".to_string()],
         codec_priority: vec!["hevc".to_string(), "avc".to_string()],
+        audio_language: None,
+        format_sort: None,
+        fps: None,
     };
 
     let result = select_best_streams(&streams, &preferences);
@@ -159,6 +208,11 @@ fn test_select_best_streams_no_video() {
         url: "https://example.com/audio.m4s".to_string(),
         size: 0,
         bandwidth: 192000,
+        language: None,
+        height: 0,
+        fps: 0.0,
+        segment_base: None,
+        backup_urls: Vec::new(),
     }];
 
     let preferences = StreamPreferences::default();
@@ -177,6 +231,11 @@ fn test_select_best_streams_no_audio() {
         url: "https://example.com/1080p_avc.m4s".to_string(),
         size: 0,
         bandwidth: 3000000,
+        language: None,
+        height: 0,
+        fps: 0.0,
+        segment_base: None,
+        backup_urls: Vec::new(),
     }];
 
     let preferences = StreamPreferences::default();
@@ -266,3 +325,451 @@ fn test_api_mode_international() {
     // 验证平台创建成功
     assert!(platform.can_handle("https://www.bilibili.com/video/BV1xx411c7mD"));
 }
+
+#[test]
+fn test_api_error_mapping() {
+    use rvd::error::DownloaderError;
+    use rvd::platform::bilibili::parser::api_error;
+
+    assert!(matches!(api_error(-352, "风控校验失败"), DownloaderError::RiskControl(_)));
+    assert!(matches!(api_error(-412, "请求被拦截"), DownloaderError::RiskControl(_)));
+    assert!(matches!(api_error(-403, "访问权限不足"), DownloaderError::AccessDenied(_)));
+    assert!(matches!(api_error(-404, "啥都木有"), DownloaderError::VideoNotFound(_)));
+    assert!(matches!(api_error(62002, "稿件不可见"), DownloaderError::ContentRemoved(_)));
+    assert!(matches!(api_error(-10403, "抱歉您所在地区不可观看！"), DownloaderError::RegionLocked(_)));
+    assert!(matches!(api_error(-101, "账号未登录"), DownloaderError::AuthRequired));
+
+    // 未知错误码保留原始信息和错误码
+    let err = api_error(-999, "未知错误");
+    assert!(matches!(err, DownloaderError::Api(_)));
+    assert!(err.to_string().contains("-999"));
+
+    // 区域限制给出可操作的提示
+    assert!(api_error(-10403, "地区限制").to_string().contains("--use-intl-api"));
+}
+
+#[test]
+fn test_select_audio_language() {
+    use rvd::platform::bilibili::selector::audio_languages;
+
+    let audio = |bandwidth: u64, language: Option<&str>| Stream {
+        stream_type: StreamType::Audio,
+        quality: format!("{}kbps", bandwidth / 1000),
+        quality_id: 30280,
+        codec: "M4A".to_string(),
+        url: format!("https://example.com/{}.m4s", language.unwrap_or("original")),
+        size: 0,
+        bandwidth,
+        language: language.map(|l| l.to_string()),
+        height: 0,
+        fps: 0.0,
+        segment_base: None,
+        backup_urls: Vec::new(),
+    };
+    let streams = vec![
+        Stream {
+            stream_type: StreamType::Video,
+            quality: "1080P 高清".to_string(),
+            quality_id: 80,
+            codec: "AVC".to_string(),
+            url: "https://example.com/video.m4s".to_string(),
+            size: 0,
+            bandwidth: 3000000,
+            language: None,
+            height: 0,
+            fps: 0.0,
+            segment_base: None,
+            backup_urls: Vec::new(),
+        },
+        audio(192000, None),
+        audio(320000, Some("zh-Hans")),
+        audio(128000, Some("ja")),
+    ];
+
+    assert_eq!(audio_languages(&streams), vec!["zh-Hans", "ja"]);
+
+    // 默认选择原声，即使配音码率更高
+    let preferences = StreamPreferences::default();
+    let (_, selected) = select_best_streams(&streams, &preferences).unwrap();
+    assert!(selected.language.is_none());
+
+    let preferences = StreamPreferences {
+        audio_language: Some("ja".to_string()),
+        ..Default::default()
+    };
+    let (_, selected) = select_best_streams(&streams, &preferences).unwrap();
+    assert_eq!(selected.language.as_deref(), Some("ja"));
+
+    // 不存在的语言回退到原声
+    let preferences = StreamPreferences {
+        audio_language: Some("en".to_string()),
+        ..Default::default()
+    };
+    let (_, selected) = select_best_streams(&streams, &preferences).unwrap();
+    assert!(selected.language.is_none());
+}
+
+#[test]
+fn test_resize_image_url() {
+    use rvd::platform::bilibili::image::{parse_image_size, resize_image_url};
+
+    let url = "https://i0.hdslb.com/bfs/archive/abc.jpg";
+    assert_eq!(resize_image_url(url, None, None, None), url);
+    assert_eq!(
+        resize_image_url(url, Some(1920), None, None),
+        "https://i0.hdslb.com/bfs/archive/abc.jpg@1920w"
+    );
+    assert_eq!(
+        resize_image_url(url, Some(480), Some(270), Some("png")),
+        "https://i0.hdslb.com/bfs/archive/abc.jpg@480w_270h.png"
+    );
+    // 已有处理后缀时会被替换
+    assert_eq!(
+        resize_image_url("https://i0.hdslb.com/bfs/archive/abc.jpg@100w_100h.webp", None, None, Some("png")),
+        "https://i0.hdslb.com/bfs/archive/abc.jpg@.png"
+    );
+    assert_eq!(
+        resize_image_url("https://i0.hdslb.com/bfs/archive/abc.jpg@100w_100h.webp", None, None, None),
+        url
+    );
+
+    assert_eq!(parse_image_size("1920"), Some((Some(1920), None)));
+    assert_eq!(parse_image_size("480x270"), Some((Some(480), Some(270))));
+    assert_eq!(parse_image_size("big"), None);
+}
+
+#[test]
+fn test_select_best_streams_with_format_sort() {
+    let video = |quality: &str, quality_id: u32, codec: &str, bandwidth: u64, fps: f64| Stream {
+        stream_type: StreamType::Video,
+        quality: quality.to_string(),
+        quality_id,
+        codec: codec.to_string(),
+        url: String::new(),
+        size: 0,
+        bandwidth,
+        language: None,
+        height: 1080,
+        fps,
+        segment_base: None,
+        backup_urls: Vec::new(),
+    };
+    let mut streams = vec![
+        video("1080P 60帧", 116, "AVC", 4000000, 60.0),
+        video("1080P 高码率", 112, "HEVC", 5000000, 30.0),
+        video("1080P 高清", 80, "HEVC", 2000000, 30.0),
+    ];
+    streams.push(Stream {
+        stream_type: StreamType::Audio,
+        quality: "192kbps".to_string(),
+        quality_id: 30280,
+        codec: "M4A".to_string(),
+        url: String::new(),
+        size: 0,
+        bandwidth: 192000,
+        language: None,
+        height: 0,
+        fps: 0.0,
+        segment_base: None,
+        backup_urls: Vec::new(),
+    });
+
+    let select = |expr: &str| {
+        let preferences = StreamPreferences {
+            format_sort: Some(expr.parse().unwrap()),
+            ..Default::default()
+        };
+        select_best_streams(&streams, &preferences).unwrap().0.quality
+    };
+
+    assert_eq!(select("res,fps"), "1080P 60帧");
+    assert_eq!(select("res,codec:hevc,br"), "1080P 高码率");
+    assert_eq!(select("codec:hevc,+br"), "1080P 高清");
+}
+
+#[test]
+fn test_select_best_streams_with_fps() {
+    let video = |quality: &str, quality_id: u32, fps: f64| Stream {
+        stream_type: StreamType::Video,
+        quality: quality.to_string(),
+        quality_id,
+        codec: "AVC".to_string(),
+        url: String::new(),
+        size: 0,
+        bandwidth: 0,
+        language: None,
+        height: 1080,
+        fps,
+        segment_base: None,
+        backup_urls: Vec::new(),
+    };
+    let mut streams = vec![
+        video("1080P 60帧", 116, 59.94),
+        video("1080P 高清", 80, 29.97),
+        video("720P 准高清", 64, 29.97),
+    ];
+    streams.push(Stream {
+        stream_type: StreamType::Audio,
+        quality: "192kbps".to_string(),
+        quality_id: 30280,
+        codec: "M4A".to_string(),
+        url: String::new(),
+        size: 0,
+        bandwidth: 192000,
+        language: None,
+        height: 0,
+        fps: 0.0,
+        segment_base: None,
+        backup_urls: Vec::new(),
+    });
+
+    let select = |fps: Option<u32>| {
+        let preferences = StreamPreferences {
+            quality_priority: vec!["1080P".to_string()],
+            codec_priority: vec!["avc".to_string()],
+            fps,
+            ..Default::default()
+        };
+        select_best_streams(&streams, &preferences).unwrap().0.quality
+    };
+
+    assert_eq!(select(Some(30)), "1080P 高清");
+    assert_eq!(select(Some(60)), "1080P 60帧");
+    // 没有匹配帧率时忽略 --fps
+    assert_eq!(select(Some(24)), select(None));
+}
+
+#[test]
+fn test_stream_table() {
+    use rvd::platform::bilibili::selector::stream_table;
+
+    let stream = |stream_type: StreamType, quality: &str, quality_id: u32, bandwidth: u64| Stream {
+        stream_type,
+        quality: quality.to_string(),
+        quality_id,
+        codec: if stream_type == StreamType::Video { "AVC" } else { "M4A" }.to_string(),
+        url: String::new(),
+        size: 0,
+        bandwidth,
+        language: None,
+        height: 0,
+        fps: 0.0,
+        segment_base: None,
+        backup_urls: Vec::new(),
+    };
+    let streams = vec![
+        stream(StreamType::Audio, "128kbps", 30216, 128000),
+        stream(StreamType::Video, "720P 高清", 64, 1000000),
+        stream(StreamType::Video, "1080P60 高帧率", 116, 4000000),
+        stream(StreamType::Audio, "192kbps", 30280, 192000),
+    ];
+
+    let rows = stream_table(&streams, 60);
+    assert_eq!(rows.len(), 5);
+    assert!(rows[0].starts_with("TYPE"));
+    // 视频流按清晰度从高到低，音频流在后
+    assert!(rows[1].ends_with("1080P60 高帧率"));
+    assert!(rows[1].contains("   60 "));
+    assert!(rows[2].ends_with("720P 高清"));
+    assert!(rows[3].ends_with("192kbps"));
+    assert!(rows[4].ends_with("128kbps"));
+    // 4000kbps × 60s = 30000000 字节
+    assert!(rows[1].contains("28.6 MB"));
+}
+
+#[test]
+fn test_normalize_streams() {
+    use rvd::platform::bilibili::selector::normalize_streams;
+
+    let stream = |stream_type: StreamType, quality_id: u32, codec: &str, bandwidth: u64, fps: f64| Stream {
+        stream_type,
+        quality: format!("{}", quality_id),
+        quality_id,
+        codec: codec.to_string(),
+        url: format!("https://example.com/{}_{}_{}.m4s", quality_id, codec, bandwidth),
+        size: 0,
+        bandwidth,
+        language: None,
+        height: 0,
+        fps,
+        segment_base: None,
+        backup_urls: Vec::new(),
+    };
+    let mut dubbed = stream(StreamType::Audio, 30280, "M4A", 192000, 0.0);
+    dubbed.language = Some("en".to_string());
+    let streams = vec![
+        stream(StreamType::Audio, 30216, "M4A", 64000, 0.0),
+        stream(StreamType::Video, 64, "AVC", 1000000, 30.0),
+        stream(StreamType::Video, 80, "HEVC", 1500000, 30.0),
+        stream(StreamType::Audio, 30280, "M4A", 192000, 0.0),
+        stream(StreamType::Video, 116, "AVC", 4000000, 60.0),
+        // 备用数组中的重复流，码率更高的保留
+        stream(StreamType::Video, 80, "HEVC", 1800000, 30.0),
+        stream(StreamType::Video, 64, "AVC", 900000, 30.0),
+        stream(StreamType::Audio, 30280, "M4A", 190000, 0.0),
+        stream(StreamType::Video, 80, "AVC", 2000000, 25.0),
+        stream(StreamType::Video, 80, "AV1", 1200000, 50.0),
+        dubbed,
+    ];
+
+    let normalized = normalize_streams(streams);
+    let summary: Vec<(StreamType, u32, &str, u64)> = normalized
+        .iter()
+        .map(|s| (s.stream_type, s.quality_id, s.codec.as_str(), s.bandwidth))
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (StreamType::Video, 116, "AVC", 4000000),
+            (StreamType::Video, 80, "AV1", 1200000),
+            (StreamType::Video, 80, "HEVC", 1800000),
+            (StreamType::Video, 80, "AVC", 2000000),
+            (StreamType::Video, 64, "AVC", 1000000),
+            (StreamType::Audio, 30280, "M4A", 192000),
+            (StreamType::Audio, 30280, "M4A", 192000),
+            (StreamType::Audio, 30216, "M4A", 64000),
+        ]
+    );
+    // 不同语言的音轨不会被当作重复流
+    assert_eq!(normalized[5].language, None);
+    assert_eq!(normalized[6].language.as_deref(), Some("en"));
+    // 再次整理结果不变
+    let again = normalize_streams(normalized.clone());
+    assert_eq!(
+        again.iter().map(|s| &s.url).collect::<Vec<_>>(),
+        normalized.iter().map(|s| &s.url).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_batch_source_resolve() {
+    use rvd::platform::bilibili::parser::BatchSource;
+    use rvd::platform::bilibili::VideoType;
+    use rvd::utils::http::HttpClient;
+    use std::sync::Arc;
+
+    let client = Arc::new(HttpClient::new().unwrap());
+
+    let source = BatchSource::resolve(&client, &VideoType::SpaceVideo("123456".to_string()), None)
+        .await
+        .unwrap();
+    assert_eq!(source, Some(BatchSource::Space { mid: "123456".to_string() }));
+
+    let source = BatchSource::resolve(&client, &VideoType::SeriesList("123456:789".to_string()), None)
+        .await
+        .unwrap();
+    assert_eq!(
+        source,
+        Some(BatchSource::Series {
+            mid: "123456".to_string(),
+            sid: "789".to_string()
+        })
+    );
+
+    // 指定了收藏夹ID时不需要请求默认收藏夹
+    let source = BatchSource::resolve(&client, &VideoType::FavoriteList("789:123456".to_string()), None)
+        .await
+        .unwrap();
+    assert_eq!(source, Some(BatchSource::Favorite { fav_id: "789".to_string() }));
+
+    // 单个视频不是批量来源
+    let source = BatchSource::resolve(&client, &VideoType::Bvid("BV1xx411c7mD".to_string()), None)
+        .await
+        .unwrap();
+    assert_eq!(source, None);
+}
+
+#[tokio::test]
+async fn test_stream_video_batch_ends_after_error() {
+    use futures::StreamExt;
+
+    let platform = BilibiliPlatform::new().unwrap();
+    let mut videos = platform.stream_video_batch("https://www.example.com/video", None);
+
+    assert!(videos.next().await.unwrap().is_err());
+    assert!(videos.next().await.is_none());
+}
+
+#[test]
+fn test_normalize_url() {
+    use rvd::platform::bilibili::normalize_url;
+
+    // 跟踪参数被去掉，分P与时间点参数保留
+    assert_eq!(
+        normalize_url("https://www.bilibili.com/video/BV1xx411c7mD/?p=2&spm_id_from=333.788.videopod.episodes&vd_source=0a1b2c&t=30"),
+        "https://www.bilibili.com/video/BV1xx411c7mD/?p=2&t=30"
+    );
+    assert_eq!(
+        normalize_url("https://www.bilibili.com/video/BV1xx411c7mD?share_source=copy_web&share_medium=iphone&unique_k=AbCdEf&utm_source=wechat"),
+        "https://www.bilibili.com/video/BV1xx411c7mD"
+    );
+    // 评论锚点与首尾标点
+    assert_eq!(
+        normalize_url("  \"https://www.bilibili.com/bangumi/play/ep123456#reply98765\"，"),
+        "https://www.bilibili.com/bangumi/play/ep123456"
+    );
+    assert_eq!(
+        normalize_url("https://space.bilibili.com/123456/favlist?fid=789&ftype=create&spm_id_from=333.1387"),
+        "https://space.bilibili.com/123456/favlist?fid=789&ftype=create"
+    );
+    // 非链接只去掉首尾多余字符
+    assert_eq!(normalize_url(" BV1xx411c7mD。"), "BV1xx411c7mD");
+    assert_eq!(normalize_url("ep123456"), "ep123456");
+}
+
+#[test]
+fn test_page_from_url() {
+    use rvd::platform::bilibili::page_from_url;
+
+    assert_eq!(
+        page_from_url("https://www.bilibili.com/video/BV1xx411c7mD?p=3&vd_source=0a1b2c"),
+        Some(3)
+    );
+    assert_eq!(page_from_url("https://www.bilibili.com/video/BV1xx411c7mD?spm_id_from=1&p=2"), Some(2));
+    assert_eq!(page_from_url("https://www.bilibili.com/video/BV1xx411c7mD"), None);
+    assert_eq!(page_from_url("https://www.bilibili.com/video/BV1xx411c7mD?p=0"), None);
+    assert_eq!(page_from_url("https://www.bilibili.com/video/BV1xx411c7mD?p=abc"), None);
+    assert_eq!(page_from_url("BV1xx411c7mD"), None);
+}
+
+#[test]
+fn test_overseas_cdn_reorders_urls() {
+    use rvd::platform::bilibili::cdn::{reorder_urls, DefaultCdn, OverseasCdn};
+
+    let stream = Stream {
+        stream_type: StreamType::Video,
+        quality: "1080P 高清".to_string(),
+        quality_id: 80,
+        codec: "AVC".to_string(),
+        url: "https://xy1x2x3x4xy.mcdn.bilivideo.cn:4483/upgcxcode/v.m4s".to_string(),
+        size: 0,
+        bandwidth: 3000000,
+        language: None,
+        height: 1080,
+        fps: 30.0,
+        segment_base: None,
+        backup_urls: vec![
+            "https://upos-sz-mirrorcos.bilivideo.com/upgcxcode/v.m4s".to_string(),
+            "https://upos-hz-mirrorakam.akamaized.net/upgcxcode/v.m4s".to_string(),
+            "https://upos-sz-mirroraliov.bilivideo.com/upgcxcode/v.m4s".to_string(),
+        ],
+    };
+
+    // 默认策略保持接口返回的顺序
+    let mut unchanged = stream.clone();
+    reorder_urls(&DefaultCdn, &mut unchanged);
+    assert_eq!(unchanged.url, stream.url);
+    assert_eq!(unchanged.backup_urls, stream.backup_urls);
+
+    let mut overseas = stream.clone();
+    reorder_urls(&OverseasCdn, &mut overseas);
+    assert_eq!(overseas.url, "https://upos-hz-mirrorakam.akamaized.net/upgcxcode/v.m4s");
+    assert_eq!(
+        overseas.backup_urls,
+        vec![
+            "https://upos-sz-mirroraliov.bilivideo.com/upgcxcode/v.m4s".to_string(),
+            "https://upos-sz-mirrorcos.bilivideo.com/upgcxcode/v.m4s".to_string(),
+            "https://xy1x2x3x4xy.mcdn.bilivideo.cn:4483/upgcxcode/v.m4s".to_string(),
+        ]
+    );
+}
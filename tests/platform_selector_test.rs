@@ -0,0 +1,328 @@
+// 平台无关的选流器单元测试
+use rvd::platform::selector::StreamSelector;
+use rvd::types::{FormatSort, Stream, StreamPreferences, StreamType};
+use std::cmp::Ordering;
+
+fn video(quality: &str, quality_id: u32, codec: &str, fps: f64, bandwidth: u64) -> Stream {
+    Stream {
+        stream_type: StreamType::Video,
+        quality: quality.to_string(),
+        quality_id,
+        codec: codec.to_string(),
+        url: format!("https://example.com/{}_{}_{}.m4s", quality_id, codec, bandwidth),
+        size: 0,
+        bandwidth,
+        language: None,
+        height: 0,
+        fps,
+        segment_base: None,
+        backup_urls: Vec::new(),
+    }
+}
+
+fn audio(bandwidth: u64, language: Option<&str>) -> Stream {
+    Stream {
+        stream_type: StreamType::Audio,
+        quality: format!("{}kbps", bandwidth / 1000),
+        quality_id: 30280,
+        codec: "M4A".to_string(),
+        url: format!("https://example.com/audio_{}.m4s", bandwidth),
+        size: 0,
+        bandwidth,
+        language: language.map(str::to_string),
+        height: 0,
+        fps: 0.0,
+        segment_base: None,
+        backup_urls: Vec::new(),
+    }
+}
+
+fn preferences(quality: &[&str], codec: &[&str]) -> StreamPreferences {
+    StreamPreferences {
+        quality_priority: quality.iter().map(|q| q.to_string()).collect(),
+        codec_priority: codec.iter().map(|c| c.to_string()).collect(),
+        ..Default::default()
+    }
+}
+
+fn sample_streams() -> Vec<Stream> {
+    vec![
+        video("4K 超清", 120, "HEVC", 30.0, 12_000_000),
+        video("1080P 60帧", 116, "AVC", 60.0, 6_000_000),
+        video("1080P 高清", 80, "AVC", 30.0, 3_000_000),
+        video("1080P 高清", 80, "HEVC", 30.0, 2_000_000),
+        video("720P 高清", 64, "AVC", 30.0, 1_500_000),
+        audio(192_000, None),
+        audio(320_000, None),
+    ]
+}
+
+#[test]
+fn test_quality_and_codec_priority_permutations() {
+    let streams = sample_streams();
+    let cases: &[(&[&str], &[&str], u32, &str)] = &[
+        (&["1080P 高清"], &["avc", "hevc"], 80, "AVC"),
+        (&["1080P 高清"], &["hevc", "avc"], 80, "HEVC"),
+        (&["4K", "1080P"], &["avc"], 120, "HEVC"),
+        (&["720P", "1080P"], &["hevc"], 64, "AVC"),
+        (&["1080P 60帧", "1080P 高清"], &["hevc"], 116, "AVC"),
+    ];
+
+    for (quality, codec, quality_id, expected_codec) in cases {
+        let selector = StreamSelector::new(&preferences(quality, codec));
+        let (video, audio) = selector.select(&streams).unwrap();
+        assert_eq!(video.quality_id, *quality_id, "{:?} {:?}", quality, codec);
+        assert_eq!(video.codec, *expected_codec, "{:?} {:?}", quality, codec);
+        assert_eq!(audio.bandwidth, 320_000);
+    }
+}
+
+#[test]
+fn test_unmatched_quality_falls_back_to_codec_then_fps_then_bitrate() {
+    let streams = sample_streams();
+    let selector = StreamSelector::new(&preferences(&["8K"], &["avc"]));
+    let (video, _) = selector.select(&streams).unwrap();
+    // 清晰度都不匹配时，AVC 中 60 帧优先
+    assert_eq!(video.quality_id, 116);
+
+    let selector = StreamSelector::new(&preferences(&["8K"], &["hevc"]));
+    let (video, _) = selector.select(&streams).unwrap();
+    // HEVC 中码率更高的 4K 胜出
+    assert_eq!(video.quality_id, 120);
+}
+
+#[test]
+fn test_format_sort_overrides_priorities() {
+    let streams = sample_streams();
+    let mut prefs = preferences(&["720P"], &["avc"]);
+
+    prefs.format_sort = Some("res".parse::<FormatSort>().unwrap());
+    let (video, _) = StreamSelector::new(&prefs).select(&streams).unwrap();
+    assert_eq!(video.quality_id, 120);
+
+    prefs.format_sort = Some("+size,+br".parse::<FormatSort>().unwrap());
+    let (video, _) = StreamSelector::new(&prefs).select(&streams).unwrap();
+    assert_eq!(video.quality_id, 64);
+}
+
+#[test]
+fn test_fps_filter_and_fallback() {
+    let streams = sample_streams();
+    let mut prefs = preferences(&["4K", "1080P"], &["avc"]);
+
+    prefs.fps = Some(60);
+    let (video, _) = StreamSelector::new(&prefs).select(&streams).unwrap();
+    assert_eq!(video.quality_id, 116);
+
+    // 没有该帧率的流时忽略 --fps
+    prefs.fps = Some(120);
+    let (video, _) = StreamSelector::new(&prefs).select(&streams).unwrap();
+    assert_eq!(video.quality_id, 120);
+}
+
+#[test]
+fn test_ties_keep_earlier_stream() {
+    let streams = vec![
+        video("1080P 高清", 80, "AVC", 30.0, 3_000_000),
+        video("1080P 高清", 80, "AVC", 30.0, 3_000_000),
+        audio(192_000, None),
+    ];
+    let selector = StreamSelector::new(&preferences(&["1080P"], &["avc"]));
+    assert_eq!(selector.compare(&streams[0], &streams[1]), Ordering::Equal);
+
+    let (video, _) = selector.select(&streams).unwrap();
+    assert_eq!(video.url, streams[0].url);
+}
+
+#[test]
+fn test_score_and_rank() {
+    let streams = sample_streams();
+    let selector = StreamSelector::new(&preferences(&["1080P 高清", "720P"], &["hevc", "avc"]));
+
+    // quality, codec, fps, br 各一项
+    let score = selector.score(&streams[3]);
+    assert_eq!(score.len(), 4);
+    assert!(score > selector.score(&streams[2]));
+
+    let ranked: Vec<(u32, &str)> = selector
+        .rank(&streams)
+        .into_iter()
+        .map(|s| (s.quality_id, s.codec.as_str()))
+        .collect();
+    assert_eq!(
+        ranked,
+        vec![(80, "HEVC"), (80, "AVC"), (64, "AVC"), (120, "HEVC"), (116, "AVC")]
+    );
+
+    // `+` 前缀的键得分取反
+    let prefs = StreamPreferences {
+        format_sort: Some("+br".parse::<FormatSort>().unwrap()),
+        ..Default::default()
+    };
+    assert_eq!(StreamSelector::new(&prefs).score(&streams[0]), vec![-12_000_000]);
+}
+
+#[test]
+fn test_audio_language_selection() {
+    let mut streams = sample_streams();
+    streams.push(audio(128_000, Some("ja")));
+
+    let (_, audio) = StreamSelector::default().select(&streams).unwrap();
+    assert_eq!(audio.language, None);
+    assert_eq!(audio.bandwidth, 320_000);
+
+    let prefs = StreamPreferences {
+        audio_language: Some("JA".to_string()),
+        ..Default::default()
+    };
+    let (_, audio) = StreamSelector::new(&prefs).select(&streams).unwrap();
+    assert_eq!(audio.language.as_deref(), Some("ja"));
+}
+
+#[test]
+fn test_all_audio_languages() {
+    let mut streams = sample_streams();
+    streams.push(audio(128_000, Some("ja")));
+    streams.push(audio(96_000, Some("ja")));
+    streams.push(audio(132_000, Some("zh-Hans")));
+
+    // 主音轨仍为码率最高的原声，其他每种语言各取码率最高的一条
+    let prefs = StreamPreferences {
+        audio_language: Some("all".to_string()),
+        ..Default::default()
+    };
+    let selector = StreamSelector::new(&prefs);
+    let (_, primary) = selector.select(&streams).unwrap();
+    assert_eq!(primary.language, None);
+    assert_eq!(primary.bandwidth, 320_000);
+    let extra = selector.extra_audio(&streams, &primary);
+    let extra: Vec<(Option<&str>, u64)> =
+        extra.iter().map(|s| (s.language.as_deref(), s.bandwidth)).collect();
+    assert_eq!(extra, vec![(Some("ja"), 128_000), (Some("zh-Hans"), 132_000)]);
+
+    // 未指定 all 时只下载一条音轨
+    assert!(StreamSelector::default().extra_audio(&streams, &primary).is_empty());
+}
+
+#[test]
+fn test_missing_streams_are_errors() {
+    let selector = StreamSelector::default();
+    assert!(selector.select(&[audio(192_000, None)]).is_err());
+    assert!(selector
+        .select(&[video("1080P 高清", 80, "AVC", 30.0, 1)])
+        .is_err());
+}
+
+#[test]
+fn test_describe_stream_includes_video_fps() {
+    use rvd::platform::selector::describe_stream;
+
+    assert_eq!(
+        describe_stream(&video("1080P 高帧率", 116, "AVC", 59.94, 6_000_000)),
+        "1080P 高帧率 AVC 60fps 6000kbps"
+    );
+    // 未提供帧率时按清晰度估算
+    assert_eq!(
+        describe_stream(&video("1080P 高清", 80, "HEVC", 0.0, 2_000_000)),
+        "1080P 高清 HEVC 30fps 2000kbps"
+    );
+    assert_eq!(describe_stream(&audio(192_000, None)), "192kbps M4A 192kbps");
+}
+
+/// 只返回固定流的平台，用于测试 `Platform` 的默认实现
+struct FixedStreams(Vec<Stream>);
+
+#[async_trait::async_trait]
+impl rvd::platform::Platform for FixedStreams {
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn can_handle(&self, _url: &str) -> bool {
+        true
+    }
+
+    async fn parse_video(
+        &self,
+        url: &str,
+        _auth: Option<&rvd::types::Auth>,
+    ) -> rvd::Result<rvd::types::VideoInfo> {
+        Err(rvd::DownloaderError::InvalidUrl(url.to_string()))
+    }
+
+    async fn get_streams(
+        &self,
+        _video_id: &str,
+        _cid: &str,
+        _auth: Option<&rvd::types::Auth>,
+    ) -> rvd::Result<Vec<Stream>> {
+        Ok(self.0.clone())
+    }
+
+    async fn get_subtitles(
+        &self,
+        _video_id: &str,
+        _cid: &str,
+        _auth: Option<&rvd::types::Auth>,
+    ) -> rvd::Result<Vec<rvd::types::Subtitle>> {
+        Ok(Vec::new())
+    }
+
+    fn get_cover(&self, video_info: &rvd::types::VideoInfo) -> String {
+        video_info.cover_url.clone()
+    }
+
+    fn name(&self) -> &str {
+        "fixed"
+    }
+}
+
+#[tokio::test]
+async fn test_default_estimate_size_sums_selected_streams() {
+    use rvd::platform::Platform;
+    use rvd::types::{Page, VideoInfo};
+
+    let page = |number: usize, duration: u64| Page {
+        number,
+        title: format!("P{}", number),
+        cid: number.to_string(),
+        duration,
+        ep_id: None,
+    };
+    let video_info = VideoInfo {
+        id: "v1".to_string(),
+        aid: 1,
+        title: "video".to_string(),
+        description: String::new(),
+        duration: 300,
+        uploader: String::new(),
+        uploader_mid: String::new(),
+        upload_date: String::new(),
+        cover_url: String::new(),
+        pages: vec![page(1, 100), page(2, 200)],
+        is_bangumi: false,
+        ep_id: None,
+        extra_data: None,
+    };
+
+    let mut streams = sample_streams();
+    // 接口给出大小的流直接使用该大小
+    streams[4].size = 50_000_000;
+    let platform = FixedStreams(streams);
+
+    // 720P AVC（已知大小）+ 320kbps 音频（码率 × 时长）
+    let prefs = preferences(&["720P"], &["avc"]);
+    let size = platform
+        .estimate_size(&video_info, &video_info.pages, &prefs, None)
+        .await
+        .unwrap();
+    assert_eq!(size, 2 * 50_000_000 + 320_000 * 300 / 8);
+
+    // 1080P AVC 3000kbps + 320kbps 音频，只计算第一个分P
+    let prefs = preferences(&["1080P 高清"], &["avc"]);
+    let size = platform
+        .estimate_size(&video_info, &video_info.pages[..1], &prefs, None)
+        .await
+        .unwrap();
+    assert_eq!(size, (3_000_000 + 320_000) * 100 / 8);
+}
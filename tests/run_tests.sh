@@ -37,6 +37,8 @@ cargo test --test cli_test --quiet
 cargo test --test core_chapter_test --quiet
 cargo test --test core_danmaku_test --quiet
 cargo test --test platform_bilibili_test --quiet
+cargo test --test platform_bilibili_parser_test --quiet
+cargo test --test platform_selector_test --quiet
 echo -e "${GREEN}✓ 单元测试完成${NC}"
 echo ""
 
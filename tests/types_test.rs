@@ -0,0 +1,113 @@
+// 数据类型辅助方法测试
+use rvd::types::{FormatSort, Page, SortField, SortKey, VideoInfo};
+
+fn bangumi(id: &str) -> VideoInfo {
+    VideoInfo {
+        id: id.to_string(),
+        aid: 0,
+        title: "测试番剧".to_string(),
+        description: String::new(),
+        duration: 0,
+        uploader: String::new(),
+        uploader_mid: String::new(),
+        upload_date: String::new(),
+        cover_url: String::new(),
+        pages: (1..=3)
+            .map(|i| Page {
+                number: i,
+                title: format!("第{}话", i),
+                cid: format!("{}", 1000 + i),
+                duration: 0,
+                ep_id: Some(format!("{}", 100 + i)),
+            })
+            .collect(),
+        is_bangumi: true,
+        ep_id: Some("101".to_string()),
+        extra_data: None,
+    }
+}
+
+#[test]
+fn test_target_ep_id_from_ep_url() {
+    let info = bangumi("bangumi_102");
+    assert_eq!(info.target_ep_id(), Some("102"));
+    assert_eq!(info.page_for_ep("102").map(|p| p.number), Some(2));
+}
+
+#[test]
+fn test_target_ep_id_from_season_url() {
+    let info = bangumi("bangumi_");
+    assert_eq!(info.target_ep_id(), None);
+    assert!(info.page_for_ep("999").is_none());
+}
+
+#[test]
+fn test_target_ep_id_non_bangumi() {
+    let mut info = bangumi("bangumi_102");
+    info.is_bangumi = false;
+    assert_eq!(info.target_ep_id(), None);
+}
+
+#[test]
+fn test_video_url_by_kind() {
+    use rvd::types::{BilibiliExtra, ExtraData};
+
+    assert_eq!(bangumi("bangumi_102").url(), "https://www.bilibili.com/bangumi/play/ep102");
+    // ss链接解析的番剧使用第一集
+    assert_eq!(bangumi("bangumi_").url(), "https://www.bilibili.com/bangumi/play/ep101");
+
+    let mut replay = bangumi("R1sx411c7Xn");
+    replay.is_bangumi = false;
+    replay.extra_data = Some(ExtraData::Bilibili(BilibiliExtra {
+        live_replay: true,
+        ..Default::default()
+    }));
+    assert_eq!(replay.url(), "https://live.bilibili.com/record/R1sx411c7Xn");
+
+    let mut video = bangumi("BV1xx411c7mD");
+    video.is_bangumi = false;
+    assert_eq!(video.url(), "https://www.bilibili.com/video/BV1xx411c7mD");
+}
+
+#[test]
+fn test_bilibili_extra_accessor() {
+    use rvd::types::{BilibiliExtra, ExtraData};
+
+    let mut info = bangumi("BV1xx411c7mD");
+    assert!(info.bilibili_extra().is_none());
+
+    info.extra_data = Some(ExtraData::Bilibili(BilibiliExtra {
+        tid: 17,
+        tname: "单机游戏".to_string(),
+        copyright: 1,
+        ..Default::default()
+    }));
+    let extra = info.bilibili_extra().unwrap();
+    assert_eq!(extra.tid, 17);
+    assert_eq!(extra.tname, "单机游戏");
+
+    // 序列化后带有平台标签，反序列化后仍可访问
+    let json = serde_json::to_string(&info).unwrap();
+    assert!(json.contains("\"platform\":\"bilibili\""));
+    let restored: VideoInfo = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored.bilibili_extra().unwrap().copyright, 1);
+}
+
+#[test]
+fn test_parse_format_sort() {
+    let sort: FormatSort = "res, +size, codec:hevc/av1".parse().unwrap();
+    assert_eq!(
+        sort.keys,
+        vec![
+            SortKey { field: SortField::Res, ascending: false },
+            SortKey { field: SortField::Size, ascending: true },
+            SortKey {
+                field: SortField::Codec(vec!["hevc".to_string(), "av1".to_string()]),
+                ascending: false,
+            },
+        ]
+    );
+
+    assert!("res,unknown".parse::<FormatSort>().is_err());
+    assert!("".parse::<FormatSort>().is_err());
+}
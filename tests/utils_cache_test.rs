@@ -0,0 +1,105 @@
+// 解析结果缓存测试
+use rvd::types::{Page, VideoInfo};
+use rvd::types::Auth;
+use rvd::utils::cache::{auth_identity, ParseCache};
+use std::time::Duration;
+use tempfile::TempDir;
+
+const URL: &str = "https://www.bilibili.com/video/BV1xx411c7mD";
+const ANON: &str = "anonymous";
+
+fn sample_video() -> VideoInfo {
+    VideoInfo {
+        id: "BV1xx411c7mD".to_string(),
+        aid: 170001,
+        title: "测试视频".to_string(),
+        description: String::new(),
+        duration: 60,
+        uploader: "UP主".to_string(),
+        uploader_mid: "42".to_string(),
+        upload_date: "2023-11-14".to_string(),
+        cover_url: String::new(),
+        pages: vec![Page {
+            number: 1,
+            title: "P1".to_string(),
+            cid: "1001".to_string(),
+            duration: 60,
+            ep_id: None,
+        }],
+        is_bangumi: false,
+        ep_id: None,
+        extra_data: None,
+    }
+}
+
+#[test]
+fn test_cache_roundtrip() {
+    let dir = TempDir::new().unwrap();
+    let cache = ParseCache::new(dir.path(), Duration::from_secs(3600));
+
+    assert!(cache.get(URL, ANON).is_none());
+    cache.put(URL, ANON, &sample_video());
+
+    let cached = cache.get(URL, ANON).unwrap();
+    assert_eq!(cached.id, "BV1xx411c7mD");
+    assert_eq!(cached.pages.len(), 1);
+    assert_eq!(cached.pages[0].cid, "1001");
+}
+
+#[test]
+fn test_cache_is_keyed_by_url() {
+    let dir = TempDir::new().unwrap();
+    let cache = ParseCache::new(dir.path(), Duration::from_secs(3600));
+
+    cache.put(URL, ANON, &sample_video());
+    assert!(cache.get("https://www.bilibili.com/video/BV1other", ANON).is_none());
+}
+
+#[test]
+fn test_cache_expires_after_ttl() {
+    let dir = TempDir::new().unwrap();
+    ParseCache::new(dir.path(), Duration::from_secs(3600)).put(URL, ANON, &sample_video());
+
+    let expired = ParseCache::new(dir.path(), Duration::ZERO);
+    assert!(expired.get(URL, ANON).is_none());
+}
+
+#[test]
+fn test_cache_ignores_corrupt_entries() {
+    let dir = TempDir::new().unwrap();
+    let cache = ParseCache::new(dir.path(), Duration::from_secs(3600));
+    cache.put(URL, ANON, &sample_video());
+
+    for entry in std::fs::read_dir(dir.path()).unwrap() {
+        std::fs::write(entry.unwrap().path(), "not json").unwrap();
+    }
+    assert!(cache.get(URL, ANON).is_none());
+
+    // 重新写入后恢复可用
+    cache.put(URL, ANON, &sample_video());
+    assert!(cache.get(URL, ANON).is_some());
+}
+
+#[test]
+fn test_cache_is_keyed_by_login_identity() {
+    let dir = TempDir::new().unwrap();
+    let cache = ParseCache::new(dir.path(), Duration::from_secs(3600));
+    let auth = Auth {
+        cookie: Some("SESSDATA=abc; DedeUserID=42".to_string()),
+        access_token: None,
+    };
+    let logged_in = auth_identity(Some(&auth));
+
+    assert_eq!(auth_identity(None), ANON);
+    assert_eq!(auth_identity(Some(&Auth { cookie: None, access_token: None })), ANON);
+    assert_ne!(logged_in, ANON);
+    // 缓存键中不包含凭据本身
+    assert!(!logged_in.contains("SESSDATA"));
+
+    // 未登录时解析的结果不会在登录后复用
+    cache.put(URL, ANON, &sample_video());
+    assert!(cache.get(URL, &logged_in).is_none());
+    cache.put(URL, &logged_in, &sample_video());
+    assert!(cache.get(URL, &logged_in).is_some());
+    assert!(cache.get(URL, ANON).is_some());
+}
@@ -0,0 +1,51 @@
+// 校验和模块单元测试
+use rvd::utils::checksum::{
+    format_sidecar, parse_sidecar, read_sidecar, sha256_file, sidecar_path, write_sidecar,
+};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+#[tokio::test]
+async fn test_sha256_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("abc.txt");
+    std::fs::write(&path, b"abc").unwrap();
+
+    let hash = sha256_file(&path).await.unwrap();
+    assert_eq!(
+        hash,
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+}
+
+#[test]
+fn test_sidecar_path() {
+    assert_eq!(
+        sidecar_path(Path::new("out/测试视频.mp4")),
+        PathBuf::from("out/测试视频.mp4.sha256")
+    );
+}
+
+#[test]
+fn test_format_and_parse_sidecar() {
+    let hash = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    let content = format_sidecar(hash, Path::new("out/video.mp4"));
+    assert_eq!(content, format!("{}  video.mp4\n", hash));
+    assert_eq!(parse_sidecar(&content), Some(hash.to_string()));
+
+    // 非法内容
+    assert_eq!(parse_sidecar("not-a-hash  video.mp4"), None);
+    assert_eq!(parse_sidecar(""), None);
+}
+
+#[tokio::test]
+async fn test_write_and_read_sidecar() {
+    let temp_dir = TempDir::new().unwrap();
+    let output = temp_dir.path().join("video.mp4");
+    std::fs::write(&output, b"fake video data").unwrap();
+
+    assert!(read_sidecar(&output).await.unwrap().is_none());
+
+    let hash = write_sidecar(&output).await.unwrap();
+    assert_eq!(read_sidecar(&output).await.unwrap(), Some(hash));
+}
@@ -34,6 +34,7 @@ access_token = "test_token"
 
 [paths]
 ffmpeg = "/usr/bin/ffmpeg"
+player = "mpv --fs"
 "#;
 
     fs::write(&config_path, config_content).unwrap();
@@ -57,6 +58,7 @@ ffmpeg = "/usr/bin/ffmpeg"
 
     let paths = config.paths.unwrap();
     assert_eq!(paths.ffmpeg, Some(PathBuf::from("/usr/bin/ffmpeg")));
+    assert_eq!(paths.player, Some("mpv --fs".to_string()));
 }
 
 #[test]
@@ -93,3 +95,151 @@ thread_count = 4
     assert!(config.default_codec.is_none());
     assert!(config.auth.is_none());
 }
+
+#[test]
+fn test_platforms_enabled() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("platforms.toml");
+
+    // 未配置时全部平台启用
+    let config = Config::default();
+    assert!(config.is_platform_enabled("bilibili"));
+
+    fs::write(&config_path, "[platforms]\nenabled = [\"Bilibili\", \"youtube\"]\n").unwrap();
+    let config = Config::load(&config_path).unwrap();
+    assert!(config.is_platform_enabled("bilibili"));
+    assert!(config.is_platform_enabled("youtube"));
+
+    fs::write(&config_path, "[platforms]\nenabled = []\n").unwrap();
+    let config = Config::load(&config_path).unwrap();
+    assert!(!config.is_platform_enabled("bilibili"));
+}
+
+#[test]
+fn test_apply_profile() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("profiles.toml");
+
+    let content = r#"
+default_quality = ["1080P 高清"]
+thread_count = 4
+
+[profiles.archive]
+default_quality = ["4K 超清", "1080P 高码率"]
+output_template = "archive/<videoTitle>"
+
+[profiles.quick]
+thread_count = 8
+"#;
+    fs::write(&config_path, content).unwrap();
+
+    let mut config = Config::load(&config_path).unwrap();
+    assert_eq!(config.profiles.len(), 2);
+
+    config.apply_profile("archive").unwrap();
+    assert_eq!(
+        config.default_quality,
+        Some(vec!["4K 超清".to_string(), "1080P 高码率".to_string()])
+    );
+    assert_eq!(config.output_template, Some("archive/<videoTitle>".to_string()));
+    // 配置组未设置的项保留顶层默认值
+    assert_eq!(config.thread_count, Some(4));
+
+    // 不存在的配置组应报错
+    let err = config.apply_profile("missing").unwrap_err();
+    assert!(err.to_string().contains("archive, quick"));
+}
+
+#[test]
+fn test_auth_per_platform() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("test.toml");
+    let cookies_path = temp_dir.path().join("youtube_cookies.txt");
+    fs::write(&cookies_path, "SID=yt").unwrap();
+
+    let config_content = format!(
+        r#"
+[auth]
+cookie = "legacy_cookie"
+access_token = "legacy_token"
+
+[auth.bilibili]
+cookie = "bili_cookie"
+
+[auth.youtube]
+cookies_file = "{}"
+"#,
+        cookies_path.display().to_string().replace('\\', "/")
+    );
+    fs::write(&config_path, config_content).unwrap();
+
+    let auth = Config::load(&config_path).unwrap().auth.unwrap();
+
+    // [auth.bilibili] 优先，其余字段沿用顶层的 [auth]
+    let bilibili = auth.for_platform("bilibili");
+    assert_eq!(bilibili.cookie.as_deref(), Some("bili_cookie"));
+    assert_eq!(bilibili.access_token.as_deref(), Some("legacy_token"));
+
+    // 顶层字段不用于其他平台
+    let youtube = auth.for_platform("youtube");
+    assert!(youtube.cookie.is_none());
+    assert!(youtube.access_token.is_none());
+    assert_eq!(youtube.cookie_value().unwrap().as_deref(), Some("SID=yt"));
+
+    assert!(auth.for_platform("unknown").cookie_value().unwrap().is_none());
+}
+
+#[test]
+fn test_flat_auth_applies_to_bilibili() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("test.toml");
+    fs::write(&config_path, "[auth]\ncookie = \"SESSDATA=abc\"\n").unwrap();
+
+    let auth = Config::load(&config_path).unwrap().auth.unwrap();
+    assert_eq!(
+        auth.for_platform("bilibili").cookie_value().unwrap().as_deref(),
+        Some("SESSDATA=abc")
+    );
+}
+
+#[test]
+fn test_auth_ignores_unknown_scalar_keys() {
+    let config: Config = toml::from_str(
+        r#"
+[auth]
+cookie = "SESSDATA=abc"
+sessdata = "typo"
+remember = true
+
+[auth.bilibili]
+access_token = "token"
+"#,
+    )
+    .unwrap();
+
+    let auth = config.auth.unwrap();
+    assert_eq!(auth.platforms.len(), 1);
+    let bilibili = auth.for_platform("bilibili");
+    assert_eq!(bilibili.cookie.as_deref(), Some("SESSDATA=abc"));
+    assert_eq!(bilibili.access_token.as_deref(), Some("token"));
+}
+
+#[test]
+fn test_font_sections() {
+    let config: Config = toml::from_str(
+        r#"
+[danmaku]
+font = "Noto Sans CJK SC"
+
+[subtitle]
+font = "/fonts/wqy-microhei.ttc"
+"#,
+    )
+    .unwrap();
+    assert_eq!(config.danmaku_font(), Some("Noto Sans CJK SC"));
+    assert_eq!(config.subtitle_font(), Some("/fonts/wqy-microhei.ttc"));
+
+    let config: Config = toml::from_str("").unwrap();
+    assert_eq!(config.danmaku_font(), None);
+    assert_eq!(config.subtitle_font(), None);
+}
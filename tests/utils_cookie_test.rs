@@ -0,0 +1,175 @@
+// Cookie存储单元测试
+use reqwest::cookie::CookieStore;
+use reqwest::header::HeaderValue;
+use rvd::utils::cookie::{parse_cookie_pairs, parse_cookies_file, CookieJar};
+use url::Url;
+
+#[test]
+fn test_parse_cookie_pairs() {
+    let pairs = parse_cookie_pairs("SESSDATA=abc; bili_jct=def;  buvid3 = xyz ;invalid");
+    assert_eq!(
+        pairs,
+        vec![
+            ("SESSDATA".to_string(), "abc".to_string()),
+            ("bili_jct".to_string(), "def".to_string()),
+            ("buvid3".to_string(), "xyz".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_cookie_jar_seed_and_send() {
+    let jar = CookieJar::new();
+    jar.seed("buvid3=abc; buvid4=def");
+
+    let api = Url::parse("https://api.bilibili.com/x/web-interface/nav").unwrap();
+    let header = jar.cookies(&api).unwrap();
+    assert_eq!(header.to_str().unwrap(), "buvid3=abc; buvid4=def");
+
+    // 非Bilibili域名不携带Cookie
+    let other = Url::parse("https://example.com/").unwrap();
+    assert!(jar.cookies(&other).is_none());
+}
+
+#[test]
+fn test_cookie_jar_stores_server_cookies() {
+    let jar = CookieJar::new();
+    let url = Url::parse("https://api.bilibili.com/x/frontend/finger/spi").unwrap();
+    let headers = [
+        HeaderValue::from_static("buvid3=abc-123; Path=/; Domain=bilibili.com"),
+        HeaderValue::from_static("b_nut=1700000000; Path=/; Max-Age=0"),
+    ];
+    jar.set_cookies(&mut headers.iter(), &url);
+
+    assert_eq!(jar.get("buvid3").as_deref(), Some("abc-123"));
+    assert!(jar.get("b_nut").is_none());
+
+    // 其他域名下发的Cookie被忽略
+    let other = Url::parse("https://example.com/").unwrap();
+    let headers = [HeaderValue::from_static("tracker=1")];
+    jar.set_cookies(&mut headers.iter(), &other);
+    assert!(jar.get("tracker").is_none());
+}
+
+#[test]
+fn test_cookie_jar_persistence() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("cookies.txt");
+
+    let jar = CookieJar::load(&path).unwrap();
+    assert!(jar.is_empty());
+    jar.insert("buvid3", "abc-123");
+
+    let reloaded = CookieJar::load(&path).unwrap();
+    assert_eq!(reloaded.get("buvid3").as_deref(), Some("abc-123"));
+}
+
+#[test]
+fn test_cookie_jar_keys_cookies_by_domain() {
+    let jar = CookieJar::new();
+    let live = Url::parse("https://live.bilibili.com/room").unwrap();
+    let api = Url::parse("https://api.bilibili.com/x/web-interface/nav").unwrap();
+    jar.set_cookies(&mut [HeaderValue::from_static("sid=live; Path=/")].iter(), &live);
+    jar.set_cookies(&mut [HeaderValue::from_static("sid=api; Path=/")].iter(), &api);
+    jar.set_cookies(
+        &mut [HeaderValue::from_static("buvid3=abc; Domain=.bilibili.com")].iter(),
+        &api,
+    );
+
+    // 同名 Cookie 按域名分别保存，只发送给匹配的主机
+    assert_eq!(jar.cookies(&live).unwrap().to_str().unwrap(), "buvid3=abc; sid=live");
+    assert_eq!(jar.cookies(&api).unwrap().to_str().unwrap(), "buvid3=abc; sid=api");
+    let www = Url::parse("https://www.bilibili.com/").unwrap();
+    assert_eq!(jar.cookies(&www).unwrap().to_str().unwrap(), "buvid3=abc");
+}
+
+#[test]
+fn test_cookie_jar_does_not_persist_seeded_cookies() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("cookies.txt");
+
+    let jar = CookieJar::load(&path).unwrap();
+    jar.seed("buvid4=cached");
+    let api = Url::parse("https://api.bilibili.com/x/web-interface/nav").unwrap();
+    // 服务端刷新时同样不写入文件
+    jar.set_cookies(
+        &mut [HeaderValue::from_static("buvid4=refreshed; Domain=bilibili.com")].iter(),
+        &api,
+    );
+    jar.insert("buvid3", "abc-123");
+    jar.flush();
+    assert_eq!(jar.get("buvid4").as_deref(), Some("refreshed"));
+
+    let reloaded = CookieJar::load(&path).unwrap();
+    assert!(reloaded.get("buvid4").is_none());
+    assert_eq!(reloaded.get("buvid3").as_deref(), Some("abc-123"));
+    assert!(!std::fs::read_to_string(&path).unwrap().contains("buvid4"));
+}
+
+#[test]
+fn test_cookie_jar_merges_auth_cookie_per_request() {
+    let jar = CookieJar::new();
+    let api = Url::parse("https://api.bilibili.com/x/web-interface/nav").unwrap();
+    jar.set_cookies(
+        &mut [
+            HeaderValue::from_static("buvid3=abc; Domain=bilibili.com"),
+            HeaderValue::from_static("SESSDATA=stale; Domain=bilibili.com"),
+        ]
+        .iter(),
+        &api,
+    );
+
+    // 认证Cookie覆盖存储中的同名值，且不写入存储
+    let header = jar.cookie_header_with(&api, "SESSDATA=secret; bili_jct=def").unwrap();
+    assert_eq!(header.to_str().unwrap(), "buvid3=abc; SESSDATA=secret; bili_jct=def");
+    assert_eq!(jar.get("SESSDATA").as_deref(), Some("stale"));
+    assert!(jar.get("bili_jct").is_none());
+
+    let other = Url::parse("https://example.com/").unwrap();
+    assert!(jar.cookie_header_with(&other, "SESSDATA=secret").is_none());
+}
+
+#[test]
+fn test_cookie_jar_defers_server_cookie_writes_until_flush() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("cookies.txt");
+
+    let jar = CookieJar::load(&path).unwrap();
+    let api = Url::parse("https://api.bilibili.com/x/frontend/finger/spi").unwrap();
+    jar.set_cookies(
+        &mut [HeaderValue::from_static("buvid3=abc-123; Domain=bilibili.com")].iter(),
+        &api,
+    );
+    assert!(!path.exists());
+
+    jar.flush();
+    let reloaded = CookieJar::load(&path).unwrap();
+    assert_eq!(reloaded.get("buvid3").as_deref(), Some("abc-123"));
+}
+
+#[test]
+fn test_cookie_jar_loads_plain_cookie_string() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("cookies.txt");
+    std::fs::write(&path, "buvid3=abc-123; b_nut=1700000000\n").unwrap();
+
+    let jar = CookieJar::load(&path).unwrap();
+    assert_eq!(jar.get("buvid3").as_deref(), Some("abc-123"));
+    let api = Url::parse("https://api.bilibili.com/").unwrap();
+    assert_eq!(
+        jar.cookies(&api).unwrap().to_str().unwrap(),
+        "b_nut=1700000000; buvid3=abc-123"
+    );
+}
+
+#[test]
+fn test_parse_cookies_file() {
+    // Netscape 格式（浏览器扩展导出）
+    let netscape = "# Netscape HTTP Cookie File\n\
+        .bilibili.com\tTRUE\t/\tFALSE\t1800000000\tbuvid3\txyz\n\
+        #HttpOnly_.bilibili.com\tTRUE\t/\tTRUE\t1800000000\tSESSDATA\tabc\n\n";
+    assert_eq!(parse_cookies_file(netscape), "buvid3=xyz; SESSDATA=abc");
+
+    // 直接保存的 Cookie 字符串
+    assert_eq!(parse_cookies_file("SESSDATA=abc; bili_jct=def\n"), "SESSDATA=abc; bili_jct=def");
+}
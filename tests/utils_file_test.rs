@@ -1,6 +1,10 @@
 // 文件工具模块单元测试
 use rvd::types::{Page, VideoInfo};
-use rvd::utils::file::{get_default_output_path, parse_template, sanitize_filename};
+use rvd::utils::file::{
+    claim_output_path, duplicate_paths, extended_length_path, format_failures_file, parse_failures_file, format_size, get_default_output_path, long_path, offset_page_number,
+    page_file_name, parse_size, part_meta_path, part_path, parse_template, parse_batch_file, remux_output_path, resolve_output_collision,
+    sanitize_filename, CollisionStrategy, FailedDownload, OrganizeBy, SidecarPolicy, MAX_PATH,
+};
 use std::path::PathBuf;
 
 #[test]
@@ -44,6 +48,7 @@ fn test_parse_template_single_page() {
         }],
         is_bangumi: false,
         ep_id: None,
+        extra_data: None,
     };
 
     // 测试基本模板
@@ -99,6 +104,7 @@ fn test_parse_template_multi_page() {
         ],
         is_bangumi: false,
         ep_id: None,
+        extra_data: None,
     };
 
     let page = &video_info.pages[0];
@@ -128,6 +134,65 @@ fn test_parse_template_multi_page() {
     assert_eq!(result, "123456");
 }
 
+#[test]
+fn test_page_file_name() {
+    let video_info = VideoInfo {
+        id: "BV1xx411c7mD".to_string(),
+        aid: 170001,
+        title: "测试视频".to_string(),
+        description: "描述".to_string(),
+        duration: 300,
+        uploader: "测试UP主".to_string(),
+        uploader_mid: "12345".to_string(),
+        upload_date: "2024-01-01".to_string(),
+        cover_url: "https://example.com/cover.jpg".to_string(),
+        pages: vec![Page {
+            number: 3,
+            title: "第三集".to_string(),
+            cid: "123458".to_string(),
+            duration: 300,
+            ep_id: None,
+        }],
+        is_bangumi: false,
+        ep_id: None,
+        extra_data: None,
+    };
+    let page = &video_info.pages[0];
+
+    // 未提供分P模板时使用默认命名
+    assert_eq!(
+        page_file_name(None, &video_info, page, "1080P", "avc"),
+        "P03_第三集.mp4"
+    );
+    assert_eq!(
+        page_file_name(
+            Some("<pageNumberWithZero> - <pageTitle> [<quality>]"),
+            &video_info,
+            page,
+            "1080P",
+            "avc"
+        ),
+        "03 - 第三集 [1080P].mp4"
+    );
+    // 模板自带扩展名时不重复添加
+    assert_eq!(
+        page_file_name(Some("<cid>.mp4"), &video_info, page, "1080P", "avc"),
+        "123458.mp4"
+    );
+
+    // 编号偏移只影响文件名中的编号
+    let shifted = offset_page_number(page, 10);
+    assert_eq!(shifted.cid, page.cid);
+    assert_eq!(
+        page_file_name(None, &video_info, &shifted, "1080P", "avc"),
+        "P13_第三集.mp4"
+    );
+    assert_eq!(
+        page_file_name(Some("<pageNumber>-<pageTitle>"), &video_info, &shifted, "1080P", "avc"),
+        "13-第三集.mp4"
+    );
+}
+
 #[test]
 fn test_get_default_output_path_single_page() {
     let video_info = VideoInfo {
@@ -149,6 +214,7 @@ fn test_get_default_output_path_single_page() {
         }],
         is_bangumi: false,
         ep_id: None,
+        extra_data: None,
     };
 
     let path = get_default_output_path(&video_info, None);
@@ -185,9 +251,390 @@ fn test_get_default_output_path_multi_page() {
         ],
         is_bangumi: false,
         ep_id: None,
+        extra_data: None,
     };
 
     let page = &video_info.pages[0];
     let path = get_default_output_path(&video_info, Some(page));
     assert_eq!(path, PathBuf::from("多P视频/P01_第一集.mp4"));
+
+    // 每个分P的默认路径互不相同
+    let second = get_default_output_path(&video_info, Some(&video_info.pages[1]));
+    assert_eq!(second, PathBuf::from("多P视频/P02_第二集.mp4"));
+}
+
+#[test]
+fn test_get_default_output_path_multi_page_fallbacks() {
+    let page = |number: usize, title: &str| Page {
+        number,
+        title: title.to_string(),
+        cid: format!("{}", 1000 + number),
+        duration: 60,
+        ep_id: None,
+    };
+    let mut video_info = VideoInfo {
+        id: "BV1xx411c7mD".to_string(),
+        aid: 170001,
+        title: "多P视频".to_string(),
+        description: String::new(),
+        duration: 120,
+        uploader: "测试UP主".to_string(),
+        uploader_mid: "12345".to_string(),
+        upload_date: "2024-01-01".to_string(),
+        cover_url: String::new(),
+        pages: vec![page(1, ""), page(2, "  "), page(3, "a/b")],
+        is_bangumi: false,
+        ep_id: None,
+        extra_data: None,
+    };
+
+    // 分P标题为空时只用编号
+    assert_eq!(
+        get_default_output_path(&video_info, Some(&video_info.pages[0])),
+        PathBuf::from("多P视频/P01.mp4")
+    );
+    assert_eq!(
+        get_default_output_path(&video_info, Some(&video_info.pages[1])),
+        PathBuf::from("多P视频/P02.mp4")
+    );
+    assert_eq!(
+        get_default_output_path(&video_info, Some(&video_info.pages[2])),
+        PathBuf::from("多P视频/P03_a_b.mp4")
+    );
+    assert_eq!(page_file_name(None, &video_info, &video_info.pages[0], "1080P", "avc"), "P01.mp4");
+
+    // 收藏夹中只带P1的多P视频同样按多P命名
+    video_info.pages.truncate(1);
+    video_info.pages[0].title = "第一集".to_string();
+    video_info.extra_data = Some(rvd::types::ExtraData::Bilibili(rvd::types::BilibiliExtra {
+        page_count: 3,
+        ..Default::default()
+    }));
+    assert_eq!(
+        get_default_output_path(&video_info, Some(&video_info.pages[0])),
+        PathBuf::from("多P视频/P01_第一集.mp4")
+    );
+
+    // 单P视频的分P标题不影响文件名
+    video_info.extra_data = None;
+    assert_eq!(
+        get_default_output_path(&video_info, Some(&video_info.pages[0])),
+        PathBuf::from("多P视频.mp4")
+    );
+}
+
+#[test]
+fn test_remux_output_path() {
+    // --skip-mux 产生的文件名
+    assert_eq!(
+        remux_output_path(&PathBuf::from("out/测试视频.video.m4s")),
+        PathBuf::from("out/测试视频.mp4")
+    );
+
+    // 普通文件名
+    assert_eq!(
+        remux_output_path(&PathBuf::from("video.m4s")),
+        PathBuf::from("video.mp4")
+    );
+}
+
+#[test]
+fn test_thumbnail_path() {
+    use rvd::utils::file::thumbnail_path;
+
+    let output = PathBuf::from("downloads/video.mp4");
+    assert_eq!(
+        thumbnail_path(&output, None, "jpg"),
+        PathBuf::from("downloads/video.jpg")
+    );
+    assert_eq!(
+        thumbnail_path(&output, Some("1920w"), "png"),
+        PathBuf::from("downloads/video.1920w.png")
+    );
+}
+
+#[test]
+fn test_resolve_output_collision() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("视频.mp4");
+
+    // 文件不存在时所有策略都使用原路径
+    assert_eq!(
+        resolve_output_collision(&path, CollisionStrategy::Error).unwrap(),
+        Some(path.clone())
+    );
+
+    std::fs::write(&path, b"first").unwrap();
+    assert_eq!(
+        resolve_output_collision(&path, CollisionStrategy::Overwrite).unwrap(),
+        Some(path.clone())
+    );
+    assert_eq!(resolve_output_collision(&path, CollisionStrategy::Skip).unwrap(), None);
+    assert!(resolve_output_collision(&path, CollisionStrategy::Error).is_err());
+
+    let renamed = temp_dir.path().join("视频 (2).mp4");
+    assert_eq!(
+        resolve_output_collision(&path, CollisionStrategy::Rename).unwrap(),
+        Some(renamed.clone())
+    );
+    std::fs::write(&renamed, b"second").unwrap();
+    assert_eq!(
+        resolve_output_collision(&path, CollisionStrategy::Rename).unwrap(),
+        Some(temp_dir.path().join("视频 (3).mp4"))
+    );
+}
+
+#[test]
+fn test_claim_output_path_checks_every_output() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("视频.mp4");
+    let skip_mux = |p: &std::path::Path| vec![p.with_extension("video.m4s"), p.with_extension("audio.m4s")];
+
+    // `--skip-mux` 时合并文件不存在，但流文件已存在
+    std::fs::write(path.with_extension("audio.m4s"), b"audio").unwrap();
+    assert!(claim_output_path(&path, CollisionStrategy::Skip, None, &skip_mux, false)
+        .unwrap()
+        .is_none());
+    let claimed = claim_output_path(&path, CollisionStrategy::Rename, None, &skip_mux, false)
+        .unwrap()
+        .unwrap();
+    assert_eq!(claimed.path, temp_dir.path().join("视频 (2).mp4"));
+}
+
+#[test]
+fn test_claim_output_path_rename_id() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("视频.mp4");
+    let single = |p: &std::path::Path| vec![p.to_path_buf()];
+    std::fs::write(&path, b"first").unwrap();
+
+    let claimed = claim_output_path(&path, CollisionStrategy::RenameId, Some("BV1xx411c7mD"), &single, false)
+        .unwrap()
+        .unwrap();
+    assert_eq!(claimed.path, temp_dir.path().join("视频_BV1xx411c7mD.mp4"));
+
+    // 追加ID后仍冲突时再追加序号
+    std::fs::write(&claimed.path, b"second").unwrap();
+    let claimed = claim_output_path(&path, CollisionStrategy::RenameId, Some("BV1xx411c7mD"), &single, false)
+        .unwrap()
+        .unwrap();
+    assert_eq!(claimed.path, temp_dir.path().join("视频_BV1xx411c7mD (2).mp4"));
+}
+
+#[test]
+fn test_claim_output_path_reserves_name() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("视频.mp4");
+    let single = |p: &std::path::Path| vec![p.to_path_buf()];
+
+    // 并发的分P规划出同一路径时，第二个得到新名称
+    let first = claim_output_path(&path, CollisionStrategy::Rename, None, &single, true)
+        .unwrap()
+        .unwrap();
+    let second = claim_output_path(&path, CollisionStrategy::Rename, None, &single, true)
+        .unwrap()
+        .unwrap();
+    assert_eq!(first.path, path);
+    assert_eq!(second.path, temp_dir.path().join("视频 (2).mp4"));
+    assert!(path.exists());
+
+    // 没有写入内容的占位文件在释放时删除，已写入的保留
+    std::fs::write(&second.path, b"video").unwrap();
+    let second_path = second.path.clone();
+    drop(first);
+    drop(second);
+    assert!(!path.exists());
+    assert!(second_path.exists());
+}
+
+#[test]
+fn test_sidecar_policy() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let path = temp_dir.path().join("视频.ass");
+
+    // 文件不存在时所有策略都写入
+    assert!(SidecarPolicy::Follow.should_write(&path));
+    assert!(SidecarPolicy::Keep.should_write(&path));
+
+    std::fs::write(&path, b"old").unwrap();
+    assert!(SidecarPolicy::Follow.should_write(&path));
+    assert!(SidecarPolicy::Overwrite.should_write(&path));
+    assert!(!SidecarPolicy::Keep.should_write(&path));
+}
+
+#[test]
+fn test_format_size() {
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(1536), "1.5 KB");
+    assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+    assert_eq!(format_size(3 * 1024 * 1024 * 1024 / 2), "1.5 GB");
+}
+
+#[test]
+fn test_parse_size() {
+    assert_eq!(parse_size("1024"), Some(1024));
+    assert_eq!(parse_size("500K"), Some(500 * 1024));
+    assert_eq!(parse_size("10M"), Some(10 * 1024 * 1024));
+    assert_eq!(parse_size("10mb"), Some(10 * 1024 * 1024));
+    assert_eq!(parse_size("2G"), Some(2 * 1024 * 1024 * 1024));
+    assert_eq!(parse_size("1.5GiB"), Some(3 * 1024 * 1024 * 1024 / 2));
+    assert_eq!(parse_size("100B"), Some(100));
+    assert_eq!(parse_size(""), None);
+    assert_eq!(parse_size("G"), None);
+    assert_eq!(parse_size("-1M"), None);
+    assert_eq!(parse_size("ten"), None);
+}
+
+#[test]
+fn test_parse_batch_file() {
+    let content = "# 收藏的视频\nBV1xx411c7mD\n\n  https://www.bilibili.com/video/BV1yy411c7mE  \n# https://skipped\r\nep12345 | quality=720P  pages=1-3\n";
+    let entries = parse_batch_file(content).unwrap();
+    let urls: Vec<&str> = entries.iter().map(|e| e.url.as_str()).collect();
+    assert_eq!(
+        urls,
+        vec!["BV1xx411c7mD", "https://www.bilibili.com/video/BV1yy411c7mE", "ep12345"]
+    );
+    assert!(entries[0].overrides.is_empty());
+    assert_eq!(
+        entries[2].overrides,
+        vec![
+            ("quality".to_string(), "720P".to_string()),
+            ("pages".to_string(), "1-3".to_string())
+        ]
+    );
+
+    assert!(parse_batch_file("# only comments\n\n").unwrap().is_empty());
+    // 选项缺少等号
+    assert!(parse_batch_file("BV1xx411c7mD | quality").is_err());
+}
+
+#[test]
+fn test_extended_length_path() {
+    assert_eq!(
+        extended_length_path("C:/Users/me/Videos/./a/../视频.mp4"),
+        r"\\?\C:\Users\me\Videos\视频.mp4"
+    );
+    assert_eq!(
+        extended_length_path(r"\\nas\share\videos\视频.mp4"),
+        r"\\?\UNC\nas\share\videos\视频.mp4"
+    );
+    // `..` 不能越过 UNC 共享名
+    assert_eq!(
+        extended_length_path(r"\\nas\share\..\..\a.mp4"),
+        r"\\?\UNC\nas\share\a.mp4"
+    );
+    // 已带前缀和相对路径原样返回
+    assert_eq!(extended_length_path(r"\\?\C:\a.mp4"), r"\\?\C:\a.mp4");
+    assert_eq!(extended_length_path("videos/a.mp4"), "videos/a.mp4");
+}
+
+#[tokio::test]
+async fn test_long_path_write() {
+    let temp = tempfile::tempdir().unwrap();
+    // 中文标题 + 多级目录，总长度超过 MAX_PATH
+    let mut dir = temp.path().to_path_buf();
+    for i in 0..6 {
+        dir.push(format!("{}_{}", "很长的中文目录名称".repeat(3), i));
+    }
+    let output = dir.join(format!("{}.mp4", "很长的中文视频标题".repeat(8)));
+    assert!(output.to_string_lossy().encode_utf16().count() > MAX_PATH);
+
+    let path = long_path(&output);
+    if cfg!(windows) {
+        assert!(path.to_string_lossy().starts_with(r"\\?\"));
+    } else {
+        assert_eq!(path, output);
+    }
+
+    tokio::fs::create_dir_all(long_path(&dir)).await.unwrap();
+    tokio::fs::write(&path, b"data").await.unwrap();
+    assert_eq!(tokio::fs::read(&path).await.unwrap(), b"data");
+}
+
+#[test]
+fn test_organize_by_subdirectory() {
+    let mut video_info = VideoInfo {
+        id: "BV1xx411c7mD".to_string(),
+        aid: 170001,
+        title: "测试视频".to_string(),
+        description: String::new(),
+        duration: 300,
+        uploader: "测试/UP主".to_string(),
+        uploader_mid: "12345".to_string(),
+        upload_date: "2024-01-01".to_string(),
+        cover_url: String::new(),
+        pages: Vec::new(),
+        is_bangumi: false,
+        ep_id: None,
+        extra_data: None,
+    };
+
+    assert_eq!(OrganizeBy::None.subdirectory(&video_info), None);
+    // UP主名称中的非法字符被替换
+    assert_eq!(OrganizeBy::Uploader.subdirectory(&video_info).as_deref(), Some("测试_UP主"));
+    assert_eq!(OrganizeBy::Date.subdirectory(&video_info).as_deref(), Some("2024-01-01"));
+
+    // 信息缺失时使用 unknown
+    video_info.uploader.clear();
+    video_info.upload_date.clear();
+    assert_eq!(OrganizeBy::Uploader.subdirectory(&video_info).as_deref(), Some("unknown"));
+    assert_eq!(OrganizeBy::Date.subdirectory(&video_info).as_deref(), Some("unknown"));
+}
+
+#[test]
+fn test_part_path() {
+    assert_eq!(
+        part_path(&PathBuf::from("/tmp/rvd/BV1_1/video.m4s")),
+        PathBuf::from("/tmp/rvd/BV1_1/video.m4s.part")
+    );
+    assert_eq!(part_path(&PathBuf::from("cover.jpg")), PathBuf::from("cover.jpg.part"));
+    assert_eq!(
+        part_meta_path(&part_path(&PathBuf::from("/tmp/rvd/BV1_1/video.m4s"))),
+        PathBuf::from("/tmp/rvd/BV1_1/video.m4s.part.json")
+    );
+}
+
+#[test]
+fn test_duplicate_paths() {
+    let paths: Vec<PathBuf> = ["a.mp4", "b.mp4", "a.mp4", "c/P01.mp4", "a.mp4", "c/P01.mp4"]
+        .iter()
+        .map(PathBuf::from)
+        .collect();
+
+    assert_eq!(
+        duplicate_paths(&paths),
+        vec![PathBuf::from("a.mp4"), PathBuf::from("c/P01.mp4")]
+    );
+    assert!(duplicate_paths(&paths[..2]).is_empty());
+}
+
+#[test]
+fn test_failures_file_round_trip() {
+    let failure = |url: &str, pages: Option<Vec<usize>>, error: &str| FailedDownload {
+        url: url.to_string(),
+        pages,
+        error: error.to_string(),
+    };
+    let failures = vec![
+        failure("https://www.bilibili.com/video/BV1a", Some(vec![3, 4]), "Network error"),
+        failure("https://www.bilibili.com/video/BV1b", None, "Video not found"),
+        failure("https://www.bilibili.com/video/BV1a", Some(vec![1]), "Network error"),
+    ];
+
+    let content = format_failures_file(&failures);
+    assert!(content.contains("# Network error\n"));
+
+    // 同一URL的分P合并为一行
+    let entries = parse_failures_file(&content).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].url, "https://www.bilibili.com/video/BV1a");
+    assert_eq!(entries[0].overrides, vec![("pages".to_string(), "1,3,4".to_string())]);
+    // 整个URL失败时不限制分P
+    assert_eq!(entries[1].url, "https://www.bilibili.com/video/BV1b");
+    assert!(entries[1].overrides.is_empty());
+}
+
+#[test]
+fn test_parse_failures_file_only_accepts_pages() {
+    assert!(parse_failures_file("https://www.bilibili.com/video/BV1a | quality=720P").is_err());
 }
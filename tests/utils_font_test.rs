@@ -0,0 +1,125 @@
+// 字体解析测试
+use rvd::utils::font::{find_cjk_font, font_family_name, mime_type, resolve_font};
+use std::path::{Path, PathBuf};
+
+/// 构造只包含 `name` 表的最小字体文件
+fn build_font(names: &[(u16, u16, u16, &str)]) -> Vec<u8> {
+    let mut strings = Vec::new();
+    let mut records = Vec::new();
+    for &(platform_id, language_id, name_id, name) in names {
+        let bytes: Vec<u8> = if platform_id == 1 {
+            name.bytes().collect()
+        } else {
+            name.encode_utf16().flat_map(|u| u.to_be_bytes()).collect()
+        };
+        for value in [platform_id, 1, language_id, name_id, bytes.len() as u16, strings.len() as u16] {
+            records.extend_from_slice(&value.to_be_bytes());
+        }
+        strings.extend_from_slice(&bytes);
+    }
+
+    let mut name_table = Vec::new();
+    name_table.extend_from_slice(&0u16.to_be_bytes());
+    name_table.extend_from_slice(&(names.len() as u16).to_be_bytes());
+    name_table.extend_from_slice(&(6 + records.len() as u16).to_be_bytes());
+    name_table.extend_from_slice(&records);
+    name_table.extend_from_slice(&strings);
+
+    let mut font = Vec::new();
+    font.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    font.extend_from_slice(&1u16.to_be_bytes());
+    font.extend_from_slice(&[0; 6]);
+    font.extend_from_slice(b"name");
+    font.extend_from_slice(&0u32.to_be_bytes());
+    font.extend_from_slice(&28u32.to_be_bytes());
+    font.extend_from_slice(&(name_table.len() as u32).to_be_bytes());
+    font.extend_from_slice(&name_table);
+    font
+}
+
+#[test]
+fn test_font_family_name_prefers_english_typographic_family() {
+    let font = build_font(&[
+        (3, 0x0804, 1, "思源黑体"),
+        (3, 0x0409, 1, "Source Han Sans SC Regular"),
+        (3, 0x0409, 16, "Source Han Sans SC"),
+    ]);
+    assert_eq!(font_family_name(&font).as_deref(), Some("Source Han Sans SC"));
+
+    let font = build_font(&[(3, 0x0804, 1, "微软雅黑"), (3, 0x0409, 1, "Microsoft YaHei")]);
+    assert_eq!(font_family_name(&font).as_deref(), Some("Microsoft YaHei"));
+
+    let font = build_font(&[(1, 0, 1, "Heiti SC")]);
+    assert_eq!(font_family_name(&font).as_deref(), Some("Heiti SC"));
+}
+
+#[test]
+fn test_font_family_name_reads_first_font_of_collection() {
+    let font = build_font(&[(3, 0x0409, 1, "Noto Sans CJK SC")]);
+    let mut collection = Vec::new();
+    collection.extend_from_slice(b"ttcf");
+    collection.extend_from_slice(&0x0001_0000u32.to_be_bytes());
+    collection.extend_from_slice(&1u32.to_be_bytes());
+    collection.extend_from_slice(&16u32.to_be_bytes());
+    // 表的偏移量相对文件开头
+    let mut shifted = font.clone();
+    shifted[20..24].copy_from_slice(&(28u32 + 16).to_be_bytes());
+    collection.extend_from_slice(&shifted);
+
+    assert_eq!(font_family_name(&collection).as_deref(), Some("Noto Sans CJK SC"));
+}
+
+#[test]
+fn test_font_family_name_rejects_invalid_data() {
+    assert_eq!(font_family_name(b""), None);
+    assert_eq!(font_family_name(b"not a font file"), None);
+    assert_eq!(font_family_name(&build_font(&[])), None);
+}
+
+#[test]
+fn test_resolve_font_from_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("custom.ttf");
+    std::fs::write(&path, build_font(&[(3, 0x0409, 1, "Custom Sans")])).unwrap();
+
+    let font = resolve_font(path.to_str()).unwrap();
+    assert_eq!(font.family, "Custom Sans");
+    assert_eq!(font.path.as_deref(), Some(path.as_path()));
+
+    // 无法读取字体名称时使用文件名
+    let path = dir.path().join("Broken Font.otf");
+    std::fs::write(&path, b"broken").unwrap();
+    assert_eq!(resolve_font(path.to_str()).unwrap().family, "Broken Font");
+}
+
+#[test]
+fn test_resolve_font_by_name() {
+    let font = resolve_font(Some("LXGW WenKai")).unwrap();
+    assert_eq!(font.family, "LXGW WenKai");
+    assert_eq!(font.path, None);
+}
+
+#[test]
+fn test_find_cjk_font_uses_first_existing_candidate() {
+    let dir = tempfile::tempdir().unwrap();
+    let existing = dir.path().join("wqy-microhei.ttc");
+    std::fs::write(&existing, b"").unwrap();
+    let missing = dir.path().join("missing.ttc");
+
+    let candidates = [
+        (missing.to_str().unwrap(), "Missing"),
+        (existing.to_str().unwrap(), "WenQuanYi Micro Hei"),
+    ];
+    let font = find_cjk_font(&candidates).unwrap();
+    assert_eq!(font.family, "WenQuanYi Micro Hei");
+    assert_eq!(font.path, Some(PathBuf::from(&existing)));
+
+    assert_eq!(find_cjk_font(&candidates[..1]), None);
+}
+
+#[test]
+fn test_font_mime_type() {
+    assert_eq!(mime_type(Path::new("a.ttf")), "application/x-truetype-font");
+    assert_eq!(mime_type(Path::new("a.TTC")), "application/x-truetype-font");
+    assert_eq!(mime_type(Path::new("a.otf")), "application/vnd.ms-opentype");
+}
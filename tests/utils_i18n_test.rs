@@ -0,0 +1,172 @@
+// 输出语言与消息表测试
+use rvd::utils::i18n::{fill, Lang, Msg};
+
+const ALL_MESSAGES: &[Msg] = &[
+    Msg::Error,
+    Msg::Failed,
+    Msg::Cancelled,
+    Msg::BatchFound,
+    Msg::MoreVideos,
+    Msg::Processing,
+    Msg::BatchDone,
+    Msg::AllDone,
+    Msg::SkippedBySize,
+    Msg::SkippedExists,
+    Msg::LoginLostAbort,
+    Msg::LoginLostWarn,
+    Msg::PageFailed,
+    Msg::StreamsFailed,
+    Msg::BatchSummary,
+    Msg::SummaryVideos,
+    Msg::SummaryDuration,
+    Msg::SummarySize,
+    Msg::SummaryOutput,
+    Msg::RunSummary,
+    Msg::RunDownloaded,
+    Msg::RunTotalSize,
+    Msg::RunElapsed,
+    Msg::RunAverageSpeed,
+    Msg::RunSkipped,
+    Msg::RunFailed,
+    Msg::SizeFromFirstVideo,
+    Msg::Unknown,
+    Msg::Proceed,
+    Msg::VideoInformation,
+    Msg::Title,
+    Msg::Uploader,
+    Msg::Pages,
+    Msg::Category,
+    Msg::Stats,
+    Msg::Tags,
+    Msg::Description,
+    Msg::SelectVideoQuality,
+    Msg::SelectAudioQuality,
+    Msg::VideoQuality,
+    Msg::AudioQuality,
+    Msg::QualityAuthYes,
+    Msg::QualityAuthNo,
+    Msg::QualityListed,
+    Msg::QualityDownloadable,
+    Msg::QualityLocked,
+    Msg::QualityMax,
+    Msg::Downloading,
+    Msg::AudioLanguages,
+    Msg::ExtraAudioNeedsMkv,
+    Msg::ExtraAudioWithClip,
+    Msg::SubtitleDownloaded,
+    Msg::NoSubtitles,
+    Msg::RetryingFailures,
+    Msg::FailuresWritten,
+    Msg::PathPreview,
+    Msg::PathExists,
+    Msg::PathDuplicate,
+    Msg::PathPreviewSummary,
+    Msg::Simulated,
+    Msg::SimulatedStream,
+    Msg::VideoStream,
+    Msg::AudioStream,
+    Msg::SimulatedFile,
+    Msg::SimulatedSubtitles,
+    Msg::SubtitlesFailed,
+    Msg::DanmakuDownloaded,
+    Msg::CoverDownloaded,
+    Msg::VideoDownloaded,
+    Msg::AudioDownloaded,
+    Msg::RemuxFailed,
+    Msg::TempKept,
+    Msg::StreamsSavedSeparately,
+    Msg::FilesSaved,
+    Msg::Muxing,
+    Msg::MuxFailed,
+    Msg::MuxedTo,
+    Msg::Muxed,
+    Msg::Verifying,
+    Msg::VerifyFailed,
+    Msg::Verified,
+    Msg::DurationMismatch,
+    Msg::ContactSheetSaved,
+    Msg::DanmakuSaved,
+    Msg::DanmakuMerged,
+    Msg::DanmakuMergeSkipped,
+    Msg::NoCjkFont,
+    Msg::PagesConcatenated,
+    Msg::ConcatIncomplete,
+    Msg::ConcatResolutionMismatch,
+    Msg::ConcatFailed,
+    Msg::TagsSaved,
+    Msg::SubtitleSaved,
+    Msg::NfoSaved,
+    Msg::SidecarKept,
+    Msg::Interrupted,
+    Msg::PartialKept,
+    Msg::PartialCleaned,
+    Msg::ReusingDownload,
+    Msg::UsingCachedInfo,
+    Msg::CommentsSaved,
+    Msg::ThumbnailSaved,
+    Msg::ChecksumMatches,
+    Msg::ChecksumChanged,
+    Msg::ChecksumKept,
+    Msg::ChecksumWritten,
+    Msg::Opening,
+    Msg::Remuxing,
+    Msg::LoginSuccess,
+    Msg::LoginSuccessSaved,
+    Msg::LoginSaveHint,
+    Msg::ScanQrCode,
+    Msg::QrCodeUrlHint,
+    Msg::CaptchaRequired,
+    Msg::CaptchaHint,
+    Msg::PhonePrompt,
+    Msg::SeccodePrompt,
+    Msg::SmsCodePrompt,
+    Msg::ConnectionReport,
+    Msg::ProxyInUse,
+    Msg::NoProxy,
+    Msg::HostReachable,
+    Msg::HostUnreachable,
+    Msg::NoCredentials,
+    Msg::CredentialsValid,
+    Msg::CredentialsInvalid,
+    Msg::LoginCheckFailed,
+    Msg::Region,
+    Msg::RegionLockLikely,
+    Msg::RegionUnknown,
+];
+
+#[test]
+fn test_lang_parse() {
+    assert_eq!(Lang::parse("zh"), Some(Lang::Zh));
+    assert_eq!(Lang::parse("zh-CN"), Some(Lang::Zh));
+    assert_eq!(Lang::parse("EN"), Some(Lang::En));
+    assert_eq!(Lang::parse("fr"), None);
+}
+
+#[test]
+fn test_lang_from_locale() {
+    assert_eq!(Lang::from_locale("zh_CN.UTF-8"), Lang::Zh);
+    assert_eq!(Lang::from_locale("zh_TW"), Lang::Zh);
+    assert_eq!(Lang::from_locale("en_US.UTF-8"), Lang::En);
+    assert_eq!(Lang::from_locale("ja_JP.UTF-8"), Lang::En);
+}
+
+#[test]
+fn test_messages_consistent_across_languages() {
+    for msg in ALL_MESSAGES {
+        let zh = msg.text(Lang::Zh);
+        let en = msg.text(Lang::En);
+        assert!(!zh.is_empty() && !en.is_empty(), "{:?} is empty", msg);
+        assert_ne!(zh, en, "{:?} is not translated", msg);
+        // 两种语言的参数个数必须一致
+        assert_eq!(zh.matches("{}").count(), en.matches("{}").count(), "{:?}", msg);
+    }
+}
+
+#[test]
+fn test_fill() {
+    assert_eq!(fill("P{} failed: {}", &[&3, &"timeout"]), "P3 failed: timeout");
+    assert_eq!(Msg::Downloading.text(Lang::Zh), "正在下载：P{} - {}");
+    // 参数不足时保留占位
+    assert_eq!(fill("{} + {}", &[&1]), "1 + {}");
+    assert_eq!(fill("no args", &[]), "no args");
+}
@@ -0,0 +1,42 @@
+// 播放器打开命令测试
+use rvd::utils::player::build_open_command;
+use std::ffi::OsString;
+use std::path::Path;
+
+#[test]
+fn test_custom_player_command() {
+    let path = Path::new("/tmp/video.mp4");
+    let (program, args) = build_open_command(path, Some("mpv --fs --volume=50"));
+    assert_eq!(program, "mpv");
+    assert_eq!(
+        args,
+        vec![
+            OsString::from("--fs"),
+            OsString::from("--volume=50"),
+            OsString::from("/tmp/video.mp4"),
+        ]
+    );
+}
+
+#[test]
+fn test_blank_player_uses_system_default() {
+    let path = Path::new("video.mp4");
+    assert_eq!(
+        build_open_command(path, Some("  ")),
+        build_open_command(path, None)
+    );
+}
+
+#[test]
+fn test_system_default_command() {
+    let (program, args) = build_open_command(Path::new("video.mp4"), None);
+    assert_eq!(args.last(), Some(&OsString::from("video.mp4")));
+    if cfg!(target_os = "windows") {
+        assert_eq!(program, "cmd");
+        assert_eq!(args[..3], [OsString::from("/C"), "start".into(), "".into()]);
+    } else if cfg!(target_os = "macos") {
+        assert_eq!(program, "open");
+    } else {
+        assert_eq!(program, "xdg-open");
+    }
+}
@@ -0,0 +1,31 @@
+// API请求节流测试
+use rvd::utils::throttle::ApiThrottle;
+use std::time::{Duration, Instant};
+
+#[tokio::test]
+async fn test_throttle_limits_concurrency() {
+    let throttle = ApiThrottle::new(1, Duration::ZERO);
+
+    let permit = throttle.acquire().await;
+    // 许可未释放时无法再获取
+    let second = tokio::time::timeout(Duration::from_millis(50), throttle.acquire()).await;
+    assert!(second.is_err());
+
+    drop(permit);
+    let second = tokio::time::timeout(Duration::from_millis(50), throttle.acquire()).await;
+    assert!(second.is_ok());
+}
+
+#[tokio::test]
+async fn test_throttle_pause() {
+    let throttle = ApiThrottle::new(4, Duration::from_millis(30));
+    let start = Instant::now();
+    throttle.pause().await;
+    assert!(start.elapsed() >= Duration::from_millis(30));
+
+    // 间隔为0时不等待
+    let throttle = ApiThrottle::new(4, Duration::ZERO);
+    let start = Instant::now();
+    throttle.pause().await;
+    assert!(start.elapsed() < Duration::from_millis(30));
+}